@@ -22,6 +22,10 @@ fn main() {
         res.compile().unwrap();
     }
 
+    // gRPC control API (src/grpc.rs)
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path().unwrap());
+    tonic_build::compile_protos("proto/pc_watcher.proto").unwrap();
+
     // Copy README to release folder
     let out_dir = std::env::var("OUT_DIR").unwrap_or_default();
     if out_dir.contains("release") {
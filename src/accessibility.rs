@@ -0,0 +1,316 @@
+//! Screen Reader Support (UI Automation)
+//!
+//! The alert window is drawn with GDI, so screen readers (Narrator, NVDA) see one empty
+//! window. This provider exposes its parts as UI Automation elements: the status text
+//! (value pattern, live region announced on alerts), the header buttons and log rows
+//! (invoke pattern) and the pipeline status strip. The element list is rebuilt from the
+//! window state on every query; actions are replayed as the mouse messages of the window.
+
+use windows::core::{implement, Error, IUnknown, Interface, Result, BSTR, HRESULT, PCWSTR, VARIANT};
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM, LRESULT, POINT, RECT, WPARAM};
+use windows::Win32::Graphics::Gdi::{ClientToScreen, ScreenToClient};
+use windows::Win32::System::Com::SAFEARRAY;
+use windows::Win32::System::Ole::{SafeArrayCreateVector, SafeArrayPutElement};
+use windows::Win32::System::Variant::VT_I4;
+use windows::Win32::UI::Accessibility::*;
+use windows::Win32::UI::WindowsAndMessaging::PostMessageW;
+
+/// Kind of a custom-drawn element
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Header text (idle status or active alert)
+    Status,
+    Button,
+    LogRow,
+    /// Pipeline status strip
+    StatusStrip,
+}
+
+/// Custom-drawn part of the alert window
+#[derive(Debug, Clone)]
+pub struct Element {
+    /// Stable per part (same button/row index = same ID)
+    pub id: i32,
+    pub role: Role,
+    pub name: String,
+    /// Client coordinates
+    pub rect: RECT,
+    /// Mouse message performing the action at the center of the element (Invoke pattern)
+    pub action: Option<u32>,
+}
+
+/// Answers WM_GETOBJECT for the UI Automation root (None = default handling)
+pub fn on_get_object(hwnd: HWND, wparam: WPARAM, lparam: LPARAM) -> Option<LRESULT> {
+    if lparam.0 as i32 != UiaRootObjectId {
+        return None;
+    }
+    let provider: IRawElementProviderSimple = RootProvider { hwnd }.into();
+    Some(unsafe { UiaReturnRawElementProvider(hwnd, wparam, lparam, &provider) })
+}
+
+/// Releases the providers of the window (WM_DESTROY)
+pub fn disconnect(hwnd: HWND) {
+    unsafe {
+        let _ = UiaReturnRawElementProvider(hwnd, WPARAM(0), LPARAM(0), None::<&IRawElementProviderSimple>);
+    }
+}
+
+/// Announces the new status text (alerts) to listening screen readers
+pub fn notify_status_changed() {
+    let hwnd = HWND(crate::alert_window::window_hwnd() as *mut _);
+    if hwnd.0.is_null() || !unsafe { UiaClientsAreListening() }.as_bool() {
+        return;
+    }
+    let Some(status) = elements().into_iter().find(|e| e.role == Role::Status) else {
+        return;
+    };
+    let provider: IRawElementProviderSimple = ElementProvider { hwnd, id: status.id }.into();
+    unsafe {
+        let _ = UiaRaiseAutomationEvent(&provider, UIA_LiveRegionChangedEventId);
+    }
+}
+
+fn elements() -> Vec<Element> {
+    crate::alert_window::accessible_elements()
+}
+
+fn element(id: i32) -> Option<Element> {
+    elements().into_iter().find(|e| e.id == id)
+}
+
+/// S_OK with a null result (UI Automation's "no such element/pattern")
+fn none<T>() -> Result<T> {
+    Err(Error::empty())
+}
+
+fn fragment(hwnd: HWND, element: Option<&Element>) -> Result<IRawElementProviderFragment> {
+    match element {
+        Some(element) => Ok(ElementProvider { hwnd, id: element.id }.into()),
+        None => none(),
+    }
+}
+
+fn screen_rect(hwnd: HWND, rect: &RECT) -> UiaRect {
+    let mut origin = POINT { x: rect.left, y: rect.top };
+    unsafe {
+        let _ = ClientToScreen(hwnd, &mut origin);
+    }
+    UiaRect {
+        left: origin.x as f64,
+        top: origin.y as f64,
+        width: (rect.right - rect.left) as f64,
+        height: (rect.bottom - rect.top) as f64,
+    }
+}
+
+/// The window itself: parent of all elements
+#[implement(IRawElementProviderSimple, IRawElementProviderFragment, IRawElementProviderFragmentRoot)]
+struct RootProvider {
+    hwnd: HWND,
+}
+
+impl IRawElementProviderSimple_Impl for RootProvider_Impl {
+    fn ProviderOptions(&self) -> Result<ProviderOptions> {
+        Ok(ProviderOptions_ServerSideProvider | ProviderOptions_UseComThreading)
+    }
+
+    fn GetPatternProvider(&self, _pattern: UIA_PATTERN_ID) -> Result<IUnknown> {
+        none()
+    }
+
+    fn GetPropertyValue(&self, property: UIA_PROPERTY_ID) -> Result<VARIANT> {
+        // The IDs are constants with Windows names, so they are compared instead of matched
+        Ok(match property {
+            p if p == UIA_NamePropertyId => VARIANT::from(BSTR::from("PC Watcher alert window")),
+            p if p == UIA_ControlTypePropertyId => VARIANT::from(UIA_PaneControlTypeId.0),
+            p if p == UIA_AutomationIdPropertyId => VARIANT::from(BSTR::from("PCWatcherAlert")),
+            _ => VARIANT::default(),
+        })
+    }
+
+    fn HostRawElementProvider(&self) -> Result<IRawElementProviderSimple> {
+        unsafe { UiaHostProviderFromHwnd(self.hwnd) }
+    }
+}
+
+impl IRawElementProviderFragment_Impl for RootProvider_Impl {
+    fn Navigate(&self, direction: NavigateDirection) -> Result<IRawElementProviderFragment> {
+        let elements = elements();
+        match direction {
+            d if d == NavigateDirection_FirstChild => fragment(self.hwnd, elements.first()),
+            d if d == NavigateDirection_LastChild => fragment(self.hwnd, elements.last()),
+            _ => none(),
+        }
+    }
+
+    fn GetRuntimeId(&self) -> Result<*mut SAFEARRAY> {
+        // The host provider (HWND) identifies the root
+        Ok(std::ptr::null_mut())
+    }
+
+    fn BoundingRectangle(&self) -> Result<UiaRect> {
+        // Taken from the HWND
+        Ok(UiaRect::default())
+    }
+
+    fn GetEmbeddedFragmentRoots(&self) -> Result<*mut SAFEARRAY> {
+        Ok(std::ptr::null_mut())
+    }
+
+    fn SetFocus(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn FragmentRoot(&self) -> Result<IRawElementProviderFragmentRoot> {
+        Ok(RootProvider { hwnd: self.hwnd }.into())
+    }
+}
+
+impl IRawElementProviderFragmentRoot_Impl for RootProvider_Impl {
+    fn ElementProviderFromPoint(&self, x: f64, y: f64) -> Result<IRawElementProviderFragment> {
+        let mut point = POINT { x: x as i32, y: y as i32 };
+        unsafe {
+            let _ = ScreenToClient(self.hwnd, &mut point);
+        }
+        let elements = elements();
+        let hit = elements.iter().find(|e| {
+            point.x >= e.rect.left && point.x < e.rect.right && point.y >= e.rect.top && point.y < e.rect.bottom
+        });
+        match hit {
+            Some(element) => fragment(self.hwnd, Some(element)),
+            None => Ok(RootProvider { hwnd: self.hwnd }.into()),
+        }
+    }
+
+    fn GetFocus(&self) -> Result<IRawElementProviderFragment> {
+        // The window never takes the keyboard focus
+        none()
+    }
+}
+
+/// One custom-drawn element (looked up by ID on every call, rows change constantly)
+#[implement(IRawElementProviderSimple, IRawElementProviderFragment, IInvokeProvider, IValueProvider)]
+struct ElementProvider {
+    hwnd: HWND,
+    id: i32,
+}
+
+impl IRawElementProviderSimple_Impl for ElementProvider_Impl {
+    fn ProviderOptions(&self) -> Result<ProviderOptions> {
+        Ok(ProviderOptions_ServerSideProvider | ProviderOptions_UseComThreading)
+    }
+
+    fn GetPatternProvider(&self, pattern: UIA_PATTERN_ID) -> Result<IUnknown> {
+        let Some(element) = element(self.id) else {
+            return none();
+        };
+        let this = ElementProvider { hwnd: self.hwnd, id: self.id };
+        match pattern {
+            p if p == UIA_InvokePatternId && element.action.is_some() => IInvokeProvider::from(this).cast(),
+            p if p == UIA_ValuePatternId && matches!(element.role, Role::Status | Role::StatusStrip) => {
+                IValueProvider::from(this).cast()
+            }
+            _ => none(),
+        }
+    }
+
+    fn GetPropertyValue(&self, property: UIA_PROPERTY_ID) -> Result<VARIANT> {
+        let Some(element) = element(self.id) else {
+            return Ok(VARIANT::default());
+        };
+        let control_type = match element.role {
+            Role::Status | Role::StatusStrip => UIA_TextControlTypeId,
+            Role::Button => UIA_ButtonControlTypeId,
+            Role::LogRow => UIA_ListItemControlTypeId,
+        };
+        Ok(match property {
+            p if p == UIA_NamePropertyId => VARIANT::from(BSTR::from(element.name.as_str())),
+            p if p == UIA_ControlTypePropertyId => VARIANT::from(control_type.0),
+            p if p == UIA_AutomationIdPropertyId => {
+                VARIANT::from(BSTR::from(format!("element_{}", element.id).as_str()))
+            }
+            p if p == UIA_IsEnabledPropertyId => VARIANT::from(true),
+            p if p == UIA_IsKeyboardFocusablePropertyId => VARIANT::from(false),
+            // Alerts in the header are read out as soon as they appear
+            p if p == UIA_LiveSettingPropertyId && element.role == Role::Status => VARIANT::from(Assertive.0),
+            _ => VARIANT::default(),
+        })
+    }
+
+    fn HostRawElementProvider(&self) -> Result<IRawElementProviderSimple> {
+        none()
+    }
+}
+
+impl IRawElementProviderFragment_Impl for ElementProvider_Impl {
+    fn Navigate(&self, direction: NavigateDirection) -> Result<IRawElementProviderFragment> {
+        if direction == NavigateDirection_Parent {
+            return Ok(RootProvider { hwnd: self.hwnd }.into());
+        }
+        let elements = elements();
+        let Some(index) = elements.iter().position(|e| e.id == self.id) else {
+            return none();
+        };
+        match direction {
+            d if d == NavigateDirection_NextSibling => fragment(self.hwnd, elements.get(index + 1)),
+            d if d == NavigateDirection_PreviousSibling => {
+                fragment(self.hwnd, index.checked_sub(1).and_then(|i| elements.get(i)))
+            }
+            _ => none(),
+        }
+    }
+
+    fn GetRuntimeId(&self) -> Result<*mut SAFEARRAY> {
+        unsafe {
+            let ids = SafeArrayCreateVector(VT_I4, 0, 2);
+            if ids.is_null() {
+                return none();
+            }
+            for (index, value) in [UiaAppendRuntimeId as i32, self.id].iter().enumerate() {
+                SafeArrayPutElement(ids, &(index as i32), value as *const i32 as *const _)?;
+            }
+            Ok(ids)
+        }
+    }
+
+    fn BoundingRectangle(&self) -> Result<UiaRect> {
+        Ok(element(self.id).map(|e| screen_rect(self.hwnd, &e.rect)).unwrap_or_default())
+    }
+
+    fn GetEmbeddedFragmentRoots(&self) -> Result<*mut SAFEARRAY> {
+        Ok(std::ptr::null_mut())
+    }
+
+    fn SetFocus(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn FragmentRoot(&self) -> Result<IRawElementProviderFragmentRoot> {
+        Ok(RootProvider { hwnd: self.hwnd }.into())
+    }
+}
+
+impl IInvokeProvider_Impl for ElementProvider_Impl {
+    fn Invoke(&self) -> Result<()> {
+        let element = element(self.id).ok_or_else(|| Error::from(HRESULT(UIA_E_ELEMENTNOTAVAILABLE as i32)))?;
+        let message = element.action.ok_or_else(|| Error::from(HRESULT(UIA_E_INVALIDOPERATION as i32)))?;
+        let x = (element.rect.left + element.rect.right) / 2;
+        let y = (element.rect.top + element.rect.bottom) / 2;
+        let position = LPARAM(((y as u16 as isize) << 16) | x as u16 as isize);
+        unsafe { PostMessageW(self.hwnd, message, WPARAM(0), position) }
+    }
+}
+
+impl IValueProvider_Impl for ElementProvider_Impl {
+    fn SetValue(&self, _value: &PCWSTR) -> Result<()> {
+        Err(Error::from(HRESULT(UIA_E_INVALIDOPERATION as i32)))
+    }
+
+    fn Value(&self) -> Result<BSTR> {
+        Ok(BSTR::from(element(self.id).map(|e| e.name).unwrap_or_default().as_str()))
+    }
+
+    fn IsReadOnly(&self) -> Result<BOOL> {
+        Ok(true.into())
+    }
+}
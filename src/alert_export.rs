@@ -0,0 +1,209 @@
+//! Alert Export
+//!
+//! Packs one alert into a single ZIP for handing it to IT or another person:
+//! the event record (incl. process hierarchy) and everything captured for it
+//! (screenshots, recording, webcam, audio). An incident bundle holds all member
+//! events, the captures of all its alerts and the DLL scans of their processes.
+
+use crate::database::IncidentSummary;
+use crate::error::Error;
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDateTime, TimeZone};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Capture folders are created shortly after the event (seconds)
+const FOLDER_MATCH_BEFORE_SECS: i64 = 2;
+const FOLDER_MATCH_AFTER_SECS: i64 = 10;
+
+/// Already compressed formats are stored as they are
+const STORED_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif"];
+
+/// Timestamp and process name from an event record (first lines of LogEntry::format_file)
+fn parse_record(record: &str) -> Option<(DateTime<Local>, String)> {
+    let header = record.lines().find(|l| l.starts_with('['))?;
+    let timestamp = header.get(1..header.find(']')?)?;
+    let naive = NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S%.3f").ok()?;
+    let timestamp = Local.from_local_datetime(&naive).earliest()?;
+
+    let process_line = record.lines().find_map(|l| l.trim_start().strip_prefix("Process:"))?;
+    let process = process_line.trim();
+    let process = process.rfind(" (PID:").map(|pos| &process[..pos]).unwrap_or(process);
+
+    Some((timestamp, process.to_string()))
+}
+
+/// Capture folder of an alert (YYYY-MM-DD_HH-MM-SS_Process in one of the session folders of
+/// this user, closest to the event time)
+pub fn find_capture_folder(timestamp: &DateTime<Local>, process_name: &str) -> Option<PathBuf> {
+    crate::logger::user_log_dirs()
+        .iter()
+        .flat_map(|dir| {
+            // Process name shortened the same way as when the folder was created
+            let suffix = format!("_{}", crate::screenshot::folder_process_name(dir, process_name));
+            fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .filter_map(|e| e.ok())
+                .filter(move |e| e.file_name().to_string_lossy().ends_with(&suffix))
+        })
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let naive = NaiveDateTime::parse_from_str(name.get(..19)?, "%Y-%m-%d_%H-%M-%S").ok()?;
+            let created = Local.from_local_datetime(&naive).earliest()?;
+            let offset = created.signed_duration_since(*timestamp);
+            let in_range = offset >= ChronoDuration::seconds(-FOLDER_MATCH_BEFORE_SECS)
+                && offset <= ChronoDuration::seconds(FOLDER_MATCH_AFTER_SECS);
+            in_range.then(|| (offset.num_milliseconds().abs(), e.path()))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, path)| path)
+}
+
+/// Adds all files of the capture folder under `prefix` (e.g. captures/)
+fn add_captures(zip: &mut ZipWriter<fs::File>, folder: &Path, prefix: &str) -> Result<usize, Error> {
+    let mut files: Vec<PathBuf> = fs::read_dir(folder)
+        .map_err(|e| Error::io(folder, &e))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .collect();
+    files.sort();
+
+    for path in &files {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let method = if STORED_EXTENSIONS.contains(&extension.as_str()) {
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::Deflated
+        };
+        let data = fs::read(path).map_err(|e| Error::io(path, &e))?;
+
+        zip.start_file(format!("{}{}", prefix, name), SimpleFileOptions::default().compression_method(method))
+            .map_err(|e| Error::io(path, e))?;
+        zip.write_all(&data).map_err(|e| Error::io(path, &e))?;
+    }
+    Ok(files.len())
+}
+
+/// Exports an alert (event record as shown in the details view) into
+/// logs/<user>/session-<id>/exports/alert_YYYY-MM-DD_HH-MM-SS_Process.zip
+pub fn export_record(record: &str) -> Result<PathBuf, Error> {
+    let (timestamp, process_name) = parse_record(record)
+        .ok_or_else(|| Error::Export("Event record without timestamp/process".to_string()))?;
+
+    let export_dir = crate::logger::get_log_dir().join("exports");
+    fs::create_dir_all(&export_dir)
+        .map_err(|e| Error::io(&export_dir, &e))?;
+
+    let zip_path = export_dir.join(format!(
+        "alert_{}_{}.zip",
+        timestamp.format("%Y-%m-%d_%H-%M-%S"),
+        crate::screenshot::sanitize_filename(&process_name)
+    ));
+    let file = fs::File::create(&zip_path)
+        .map_err(|e| Error::io(&zip_path, &e))?;
+
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let hostname = hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+    let mut event = format!(
+        "PC Watcher alert export\nComputer:    {}\nExported:    {}\n\n{}",
+        hostname,
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        record
+    );
+
+    // Captures may be missing (mode none, already cleaned up) - note it instead of failing
+    match find_capture_folder(&timestamp, &process_name) {
+        Some(folder) => {
+            let count = add_captures(&mut zip, &folder, "captures/")?;
+            event.push_str(&format!("\nCaptures:    {} file(s) from {}\n", count, folder.display()));
+        }
+        None => event.push_str("\nCaptures:    none found\n"),
+    }
+
+    zip.start_file("event.txt", options).map_err(|e| Error::io(&zip_path, e))?;
+    zip.write_all(event.as_bytes()).map_err(|e| Error::io(&zip_path, &e))?;
+
+    zip.finish().map_err(|e| Error::io(&zip_path, e))?;
+    Ok(zip_path)
+}
+
+/// Capture folders of the alerts of an incident (each folder once)
+pub fn incident_capture_folders(events: &[LogEntry]) -> Vec<PathBuf> {
+    let mut folders: Vec<PathBuf> = Vec::new();
+    for event in events.iter().filter(|e| e.severity >= Severity::Warning) {
+        if let Some(folder) = find_capture_folder(&event.timestamp, &event.process_name) {
+            if !folders.contains(&folder) {
+                folders.push(folder);
+            }
+        }
+    }
+    folders
+}
+
+/// Exports an incident into logs/<user>/session-<id>/exports/incident_<id>_YYYY-MM-DD_HH-MM-SS.zip:
+/// incident.txt with all member events and captures/<folder>/ per alert
+pub fn export_incident(incident: &IncidentSummary, events: &[LogEntry]) -> Result<PathBuf, Error> {
+    let export_dir = crate::logger::get_log_dir().join("exports");
+    fs::create_dir_all(&export_dir)
+        .map_err(|e| Error::io(&export_dir, &e))?;
+
+    let zip_path = export_dir.join(format!(
+        "incident_{}_{}.zip",
+        incident.id,
+        incident.started.format("%Y-%m-%d_%H-%M-%S")
+    ));
+    let file = fs::File::create(&zip_path)
+        .map_err(|e| Error::io(&zip_path, &e))?;
+
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let hostname = hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+    let mut text = format!(
+        "PC Watcher incident export\nComputer:    {}\nExported:    {}\n\nIncident:    #{} {}\nSeverity:    {}\nStarted:     {}\nLast event:  {}\nEvents:      {}\n",
+        hostname,
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        incident.id,
+        incident.title,
+        incident.severity.as_str(),
+        incident.started.format("%Y-%m-%d %H:%M:%S"),
+        incident.last_event.format("%Y-%m-%d %H:%M:%S"),
+        events.len()
+    );
+
+    for folder in incident_capture_folders(events) {
+        let name = folder.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let count = add_captures(&mut zip, &folder, &format!("captures/{}/", name))?;
+        text.push_str(&format!("Captures:    {} file(s) from {}\n", count, folder.display()));
+    }
+    // DLL scans of the processes (only known while PC Watcher runs, also logged as DLL_SCAN)
+    let mut scanned: Vec<String> = Vec::new();
+    for event in events.iter().filter(|e| e.severity == Severity::Critical) {
+        let key = event.process_path.to_lowercase();
+        if scanned.contains(&key) {
+            continue;
+        }
+        if let Some(verdict) = crate::module_scan::verdict(&event.process_path) {
+            text.push_str(&format!("DLLs:        {}: {}\n", event.process_path, verdict.describe()));
+        }
+        scanned.push(key);
+    }
+    text.push('\n');
+    events.iter().for_each(|event| text.push_str(&event.format_file()));
+
+    zip.start_file("incident.txt", options).map_err(|e| Error::io(&zip_path, e))?;
+    zip.write_all(text.as_bytes()).map_err(|e| Error::io(&zip_path, &e))?;
+
+    zip.finish().map_err(|e| Error::io(&zip_path, e))?;
+    Ok(zip_path)
+}
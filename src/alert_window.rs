@@ -2,19 +2,20 @@
 //!
 //! A window that lives on the second monitor and visually changes
 //! when suspicious processes are detected - without stealing focus.
-//! Features: Dragging, position saving, log display, transparency, right-click for log
-//! Screenshot preview on alerts, minimize/pin buttons, details window
+//! Features: Dragging with edge snapping/docking, position saving (per monitor setup), log display, transparency/click-through, right-click for log
+//! Screenshot preview on alerts, minimize/pin buttons, details window, collapsed header-only view
 
 use std::sync::atomic::{AtomicBool, AtomicUsize, AtomicI32, Ordering};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::path::PathBuf;
 use std::fs;
-use std::collections::{VecDeque, HashMap};
+use std::collections::{VecDeque, HashMap, HashSet};
+use crossbeam_channel::{bounded, Sender};
 use parking_lot::Mutex;
 use tracing::{info, error};
-use windows::core::w;
-use windows::Win32::Foundation::{HWND, LPARAM, WPARAM, LRESULT, RECT, COLORREF, POINT};
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM, WPARAM, LRESULT, RECT, COLORREF, POINT, SIZE, HANDLE, CloseHandle, GlobalFree};
 use windows::Win32::Graphics::Gdi::{
     CreateSolidBrush, DeleteObject, InvalidateRect,
     BeginPaint, EndPaint, FillRect, SetBkMode, SetTextColor,
@@ -22,17 +23,29 @@ use windows::Win32::Graphics::Gdi::{
     CreateCompatibleDC, CreateDIBSection, SelectObject, StretchBlt,
     BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, SRCCOPY, DeleteDC,
     CreateRoundRectRgn, SetWindowRgn, RoundRect, CreatePen, PS_SOLID,
-    SelectClipRgn,
+    SelectClipRgn, ScreenToClient, GetTextExtentPoint32W,
     DT_CENTER, DT_VCENTER, DT_SINGLELINE,
+    EnumDisplayMonitors, MonitorFromPoint, HDC, HMONITOR, MONITOR_DEFAULTTONULL,
 };
 use windows::Win32::UI::WindowsAndMessaging::*;
+use windows::Win32::UI::Controls::WM_MOUSELEAVE;
 use windows::Win32::UI::Input::KeyboardAndMouse::{SetCapture, ReleaseCapture};
 use windows::Win32::System::LibraryLoader::GetModuleHandleW;
 use windows::Win32::UI::Shell::ExtractIconExW;
+use windows::Win32::System::DataExchange::{OpenClipboard, CloseClipboard, EmptyClipboard, SetClipboardData};
+use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+use windows::Win32::System::Ole::CF_UNICODETEXT;
+use windows::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::error::Error;
+use crate::rules::Rule;
+use crate::snooze::QueuedAlert;
 
 // Colors (BGR Format!)
 const COLOR_NORMAL: u32 = 0x00228B22;     // Green (Forest Green) - all OK
-const COLOR_ALERT: u32 = 0x000000FF;       // Red - Warning!
+const COLOR_ALERT: u32 = 0x000000FF;       // Red - Critical alert!
+const COLOR_WARNING: u32 = 0x00008CFF;     // Orange - Warning alert
 const COLOR_TEXT: u32 = 0x00FFFFFF;        // White
 const COLOR_LOG_BG: u32 = 0x00202020;      // Dark gray for log area
 const COLOR_BUTTON_BG: u32 = 0x00333333;   // Button background
@@ -46,6 +59,7 @@ const COLOR_SHOWN: u32 = 0x0000FF00;       // Green
 const COLOR_MINIMIZED: u32 = 0x00808080;   // Gray
 const COLOR_RESTORED: u32 = 0x00FF00FF;    // Magenta
 const COLOR_ZORDER: u32 = 0x000000FF;      // Red
+const COLOR_DEVICE: u32 = 0x008080FF;      // Light red (microphone/camera)
 
 // Layout constants
 const WINDOW_WIDTH: i32 = 720;
@@ -55,19 +69,80 @@ const SCREENSHOT_WIDTH: i32 = 200;
 const SCREENSHOT_HEIGHT: i32 = 130;
 const LOG_AREA_WIDTH: i32 = WINDOW_WIDTH - SCREENSHOT_WIDTH - 20;
 const MAX_LOG_ENTRIES: usize = 13;
+const LOG_FIRST_ROW_Y: i32 = HEADER_HEIGHT + 22;
+const LOG_ROW_HEIGHT: i32 = 18;
+const LOG_FILTER_TEXT_X: i32 = 340;
+const WATCH_PANEL_Y: i32 = HEADER_HEIGHT + 5 + SCREENSHOT_HEIGHT + 88;
+const WATCH_ROW_HEIGHT: i32 = 16;
+const WATCH_PANEL_MAX_ROWS: usize = 3;
+const STATUS_STRIP_HEIGHT: i32 = 18;
+/// Indent of log rows of owned popups/dialogs
+const LOG_OWNED_INDENT: i32 = 12;
 const CORNER_RADIUS: i32 = 12;
 
+// Events-per-second sparkline in the header: seconds of history, size and colors (BGR)
+const SPARKLINE_SECONDS: usize = 180;
+const SPARKLINE_WIDTH: i32 = 90;
+const SPARKLINE_HEIGHT: i32 = 18;
+const COLOR_SPARKLINE_BG: u32 = 0x00202020;
+const COLOR_SPARKLINE: u32 = 0x00C0C0C0;
+
+// Active alerts in the header (oldest are dropped), seconds each one is shown while cycling
+const MAX_ACTIVE_ALERTS: usize = 20;
+const ALERT_CYCLE_TICKS: usize = 3;
+
+// Timer IDs (1 = TOPMOST check)
+const STATUS_TIMER_ID: usize = 2;
+
+// Posted by other threads: expand the collapsed window (Critical alert)
+const WM_EXPAND: u32 = WM_APP + 1;
+
 // Button constants
 const BTN_HEIGHT: i32 = 20;
 
+// Context menu command IDs (log rows)
+const ID_MENU_DETAILS: u32 = 2001;
+const ID_MENU_COPY_PATH: u32 = 2002;
+const ID_MENU_OPEN_LOCATION: u32 = 2003;
+const ID_MENU_WHITELIST: u32 = 2004;
+const ID_MENU_KILL: u32 = 2005;
+const ID_MENU_FILTER: u32 = 2006;
+const ID_MENU_CLEAR_FILTER: u32 = 2007;
+const ID_MENU_WATCH: u32 = 2008;
+const ID_MENU_UNWATCH: u32 = 2009;
+const ID_MENU_BLOCK_FOCUS: u32 = 2010;
+const ID_MENU_UNBLOCK_FOCUS: u32 = 2011;
+const ID_MENU_OPEN_IMAGE: u32 = 2012;
+const ID_MENU_SHOW_IMAGE: u32 = 2013;
+const ID_MENU_OPEN_FOLDER: u32 = 2014;
+const ID_MENU_HEALTH_COPY: u32 = 2015;
+const ID_MENU_HEALTH_CLEAR: u32 = 2016;
+/// Errors listed in the Health menu
+const HEALTH_MENU_ENTRIES: usize = 10;
+const ID_MENU_MUTE_BASE: u32 = 2100;
+/// "Filter by tag" entries, one per tag of the row
+const ID_MENU_FILTER_TAG_BASE: u32 = 2200;
+const MAX_TAG_MENU_ENTRIES: usize = 10;
+
 // Details window constants
 const DETAILS_WIDTH: i32 = 550;
 const DETAILS_HEIGHT: i32 = 400;
+const DETAILS_HEADER_HEIGHT: i32 = 35;
+const DETAILS_CASCADE_OFFSET: i32 = 25;
+const DETAILS_CLOSE_BTN_W: i32 = 30;
+const DETAILS_PIN_BTN_W: i32 = 70;
+const DETAILS_EXPORT_BTN_W: i32 = 70;
+const DETAILS_NOTE_BTN_W: i32 = 55;
+const DETAILS_MUTE_BTN_W: i32 = 55;
 
 // Global states
 static ALERT_ACTIVE: AtomicBool = AtomicBool::new(false);
+// Index of the active alert shown in the header, status timer ticks for cycling
+static ALERT_CYCLE: AtomicUsize = AtomicUsize::new(0);
+static ALERT_TICKS: AtomicUsize = AtomicUsize::new(0);
+// Window was taken out of TOPMOST for the current snooze
+static SNOOZE_APPLIED: AtomicBool = AtomicBool::new(false);
 static WINDOW_HWND: AtomicUsize = AtomicUsize::new(0);
-static DETAILS_HWND: AtomicUsize = AtomicUsize::new(0);
 static DRAGGING: AtomicBool = AtomicBool::new(false);
 static DRAG_START_X: AtomicI32 = AtomicI32::new(0);
 static DRAG_START_Y: AtomicI32 = AtomicI32::new(0);
@@ -75,6 +150,8 @@ static EVENT_COUNT: AtomicUsize = AtomicUsize::new(0);
 static WINDOW_PINNED: AtomicBool = AtomicBool::new(true);
 static WINDOW_MINIMIZED: AtomicBool = AtomicBool::new(false);
 static SCREENSHOT_HIDDEN: AtomicBool = AtomicBool::new(false);
+// Only the header bar is shown (double-click on the header)
+static COLLAPSED: AtomicBool = AtomicBool::new(false);
 
 /// Screenshot data for display
 #[derive(Clone)]
@@ -87,53 +164,167 @@ pub struct ScreenshotData {
 /// GUI log entry with event type for color coding and details
 #[derive(Clone)]
 pub struct GuiLogEntry {
+    pub id: u64,
     pub text: String,
     pub event_type: String,
     pub details: String,
+    pub process_name: String,
+    pub process_id: u32,
     pub process_path: String,
+    pub severity: Severity,
+    /// Alerts (Warning and above) are marked in the list until acknowledged
+    pub acknowledged: bool,
+    /// Row ID in the event database (for notes)
+    pub db_id: Option<i64>,
+    /// Popup/dialog of an application window (indented below it)
+    pub owned: bool,
+    /// Tags with their chip color
+    pub tags: Vec<(String, u32)>,
 }
 
-/// Icon cache (max 50 entries, LRU-like)
+/// Filter of the log list
+#[derive(Debug, Clone)]
+enum LogFilter {
+    Process(String),
+    Tag(String),
+}
+
+impl LogFilter {
+    fn matches(&self, process_name: &str, tags: &[String]) -> bool {
+        match self {
+            LogFilter::Process(name) => name.eq_ignore_ascii_case(process_name),
+            LogFilter::Tag(tag) => crate::tags::has_tag(tags, tag),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            LogFilter::Process(name) => format!("Filter: {}", name),
+            LogFilter::Tag(tag) => format!("Filter: #{}", tag),
+        }
+    }
+}
+
+/// Alert shown in the header
+struct ActiveAlert {
+    header: String,
+    /// Header color (None = severity color)
+    color: Option<u32>,
+    severity: Severity,
+    /// Auto-clear time (None = kept until acknowledged)
+    clear_at: Option<Instant>,
+}
+
+/// State of an open details window
+struct DetailsWindow {
+    event_id: u64,
+    db_id: Option<i64>,
+    process_name: String,
+    details: String,
+    // Pinned windows stay open, unpinned ones are reused for the next event
+    pinned: bool,
+}
+
+/// Icon cache (initial capacity, the limit is memory.icon_cache_entries, LRU-like)
 const MAX_ICON_CACHE: usize = 50;
 const ICON_SIZE: i32 = 16;
+const ICON_QUEUE_SIZE: usize = 256;
+const COLOR_ICON_PLACEHOLDER: u32 = 0x00404040;
+
+/// Icon variants (16x16 for log rows, 32x32 for details window)
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum IconSize {
+    Small,
+    Large,
+}
 
 // DrawIconEx Flags
 const DI_NORMAL: u32 = 0x0003;
 
 lazy_static::lazy_static! {
     static ref ALERT_MESSAGE: Mutex<String> = Mutex::new("PC Watcher - Waiting...".to_string());
+    // Alerts shown in the header (oldest first), cycled while more than one is active
+    static ref ACTIVE_ALERTS: Mutex<Vec<ActiveAlert>> = Mutex::new(Vec::new());
     static ref LOG_ENTRIES: Mutex<VecDeque<GuiLogEntry>> = Mutex::new(VecDeque::with_capacity(MAX_LOG_ENTRIES));
     static ref LOG_FILE_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
     static ref CURRENT_SCREENSHOT: Mutex<Option<ScreenshotData>> = Mutex::new(None);
-    static ref CURRENT_DETAILS: Mutex<String> = Mutex::new(String::new());
+    // Only show log entries of this process or tag (None = all)
+    static ref LOG_FILTER: Mutex<Option<LogFilter>> = Mutex::new(None);
+    // Open details windows: HWND -> state
+    static ref DETAILS_WINDOWS: Mutex<HashMap<usize, DetailsWindow>> = Mutex::new(HashMap::new());
     static ref CURRENT_SCREENSHOT_FOLDER: Mutex<Option<PathBuf>> = Mutex::new(None);
-    // Icon cache: Path -> HICON (stored as usize)
-    static ref ICON_CACHE: Mutex<HashMap<String, usize>> = Mutex::new(HashMap::with_capacity(MAX_ICON_CACHE));
-    static ref ICON_CACHE_ORDER: Mutex<VecDeque<String>> = Mutex::new(VecDeque::with_capacity(MAX_ICON_CACHE));
+    // Events per second of the last minutes, oldest first (header sparkline)
+    static ref EVENT_RATES: Mutex<VecDeque<f64>> = Mutex::new(VecDeque::with_capacity(SPARKLINE_SECONDS));
+    // Last pipeline sample (status strip)
+    static ref PIPELINE_STATUS: Mutex<crate::metrics::PipelineStatus> = Mutex::new(Default::default());
+    // Last memory snapshot (status strip)
+    static ref MEMORY_USAGE: Mutex<crate::memory::MemoryUsage> = Mutex::new(Default::default());
+    // Icon cache: (Path, Size) -> HICON (stored as usize, 0 = no icon)
+    static ref ICON_CACHE: Mutex<HashMap<(String, IconSize), usize>> = Mutex::new(HashMap::with_capacity(MAX_ICON_CACHE));
+    static ref ICON_CACHE_ORDER: Mutex<VecDeque<(String, IconSize)>> = Mutex::new(VecDeque::with_capacity(MAX_ICON_CACHE));
+    // Icons queued for extraction
+    static ref ICON_PENDING: Mutex<HashSet<(String, IconSize)>> = Mutex::new(HashSet::new());
+    static ref ICON_QUEUE: Sender<(String, IconSize)> = start_icon_worker();
 }
 
-/// Saves the position to a file
+/// Key of the current monitor setup: hash of all monitor rectangles (resolution + arrangement),
+/// so docking/undocking a laptop restores the position saved for that setup
+fn monitor_layout_key() -> String {
+    unsafe extern "system" fn collect(_monitor: HMONITOR, _hdc: HDC, rect: *mut RECT, data: LPARAM) -> BOOL {
+        let monitors = &mut *(data.0 as *mut Vec<(i32, i32, i32, i32)>);
+        monitors.push(((*rect).left, (*rect).top, (*rect).right, (*rect).bottom));
+        BOOL(1)
+    }
+
+    let mut monitors: Vec<(i32, i32, i32, i32)> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(None, None, Some(collect), LPARAM(&mut monitors as *mut _ as isize));
+    }
+    monitors.sort();
+    let layout: String = monitors.iter().map(|(l, t, r, b)| format!("{},{},{},{};", l, t, r, b)).collect();
+    // FNV-1a (stable across builds, unlike the std hasher)
+    let hash = layout.bytes().fold(0xcbf29ce484222325u64, |hash, b| (hash ^ b as u64).wrapping_mul(0x100000001b3));
+    format!("{:016x}", hash)
+}
+
+/// Saved positions: layout key -> (x, y), "" = position of old versions (one for all setups)
+fn read_positions() -> Vec<(String, (i32, i32))> {
+    let content = fs::read_to_string(get_config_path()).unwrap_or_default();
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, position) = line.trim().split_once('=').unwrap_or(("", line.trim()));
+            let (x, y) = position.split_once(',')?;
+            Some((key.to_string(), (x.trim().parse().ok()?, y.trim().parse().ok()?)))
+        })
+        .collect()
+}
+
+/// Saves the position for the current monitor setup
 fn save_position(x: i32, y: i32) {
     let config_path = get_config_path();
     if let Some(parent) = config_path.parent() {
         let _ = fs::create_dir_all(parent);
     }
-    let content = format!("{},{}", x, y);
+    let key = monitor_layout_key();
+    let mut positions = read_positions();
+    positions.retain(|(k, _)| !k.is_empty() && *k != key);
+    positions.push((key, (x, y)));
+    let content: String = positions.iter().map(|(k, (x, y))| format!("{}={},{}\n", k, x, y)).collect();
     let _ = fs::write(&config_path, content);
 }
 
-/// Loads the position from a file
+/// Loads the position saved for the current monitor setup (None if it would be off-screen)
 fn load_position() -> Option<(i32, i32)> {
-    let config_path = get_config_path();
-    if let Ok(content) = fs::read_to_string(&config_path) {
-        let parts: Vec<&str> = content.trim().split(',').collect();
-        if parts.len() == 2 {
-            if let (Ok(x), Ok(y)) = (parts[0].parse(), parts[1].parse()) {
-                return Some((x, y));
-            }
-        }
-    }
-    None
+    let key = monitor_layout_key();
+    let positions = read_positions();
+    let (x, y) = positions
+        .iter()
+        .find(|(k, _)| *k == key)
+        .or_else(|| positions.iter().find(|(k, _)| k.is_empty()))
+        .map(|(_, position)| *position)?;
+    let visible = unsafe { !MonitorFromPoint(POINT { x, y }, MONITOR_DEFAULTTONULL).is_invalid() };
+    visible.then_some((x, y))
 }
 
 /// Path to configuration file
@@ -146,39 +337,139 @@ fn get_config_path() -> PathBuf {
     PathBuf::from("pcwatcher_window.cfg")
 }
 
+/// Repaints the watch panel (called when watched process status changes)
+pub fn refresh_watch_panel() {
+    invalidate_region(&watch_panel_rect());
+}
+
 /// Sets the path to the log file (called by logger)
 pub fn set_log_file_path(path: PathBuf) {
     let mut log_path = LOG_FILE_PATH.lock();
     *log_path = Some(path);
 }
 
+/// Size of the screenshot preview (captures are downscaled to fit before they are set)
+pub fn preview_size() -> (u32, u32) {
+    (SCREENSHOT_WIDTH as u32, SCREENSHOT_HEIGHT as u32)
+}
+
 /// Sets the current screenshot with folder path for display
-pub fn set_screenshot_with_folder(pixels: Vec<u8>, width: u32, height: u32, folder: PathBuf) {
-    {
-        let mut screenshot = CURRENT_SCREENSHOT.lock();
-        *screenshot = Some(ScreenshotData { pixels, width, height });
-    }
+/// Returns the pixel buffer of the replaced screenshot for reuse
+pub fn set_screenshot_with_folder(pixels: Vec<u8>, width: u32, height: u32, folder: PathBuf) -> Option<Vec<u8>> {
+    let replaced = {
+        // Oversized captures are only on disk, the preview keeps the previous one
+        let max_bytes = crate::config::memory().screenshot_max_mb * 1024 * 1024;
+        if pixels.len() > max_bytes {
+            info!("Screenshot {}x{} exceeds memory.screenshot_max_mb - no preview", width, height);
+            Some(pixels)
+        } else {
+            let mut screenshot = CURRENT_SCREENSHOT.lock();
+            screenshot.replace(ScreenshotData { pixels, width, height }).map(|old| old.pixels)
+        }
+    };
     {
         let mut folder_path = CURRENT_SCREENSHOT_FOLDER.lock();
         *folder_path = Some(folder);
     }
     SCREENSHOT_HIDDEN.store(false, Ordering::SeqCst);
-    redraw_window();
+    invalidate_region(&screenshot_area_rect());
+    replaced
 }
 
-/// Opens the current screenshot folder in Explorer
+/// Pixel bytes of the screenshot preview
+pub fn screenshot_bytes() -> usize {
+    CURRENT_SCREENSHOT.lock().as_ref().map(|s| s.pixels.len()).unwrap_or(0)
+}
+
+/// Entries and text bytes of the GUI log history
+pub fn log_usage() -> (usize, usize) {
+    let entries = LOG_ENTRIES.lock();
+    let bytes = entries
+        .iter()
+        .map(|e| e.text.len() + e.event_type.len() + e.details.len() + e.process_name.len() + e.process_path.len())
+        .sum();
+    (entries.len(), bytes)
+}
+
+/// Number of cached icons
+pub fn icon_cache_len() -> usize {
+    ICON_CACHE.lock().len()
+}
+
+/// Cuts the details text of a GUI entry to the configured length
+fn limit_details(details: String, max_chars: usize) -> String {
+    if details.chars().count() <= max_chars {
+        return details;
+    }
+    let mut cut: String = details.chars().take(max_chars).collect();
+    cut.push_str("\n... (truncated, full entry in the log file)");
+    cut
+}
+
+/// Opens the current screenshot folder
 fn open_screenshot_folder() {
     if let Some(folder) = CURRENT_SCREENSHOT_FOLDER.lock().clone() {
-        info!("Opening screenshot folder: {}", folder.display());
-        let _ = std::process::Command::new("explorer.exe")
-            .arg(&folder)
-            .spawn();
+        crate::open_with::open_folder(&folder);
     }
 }
 
+/// First image of the current screenshot folder (the one shown as preview)
+fn first_screenshot_image() -> Option<PathBuf> {
+    let folder = CURRENT_SCREENSHOT_FOLDER.lock().clone()?;
+    let mut images: Vec<PathBuf> = fs::read_dir(&folder)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("jpg")))
+        .collect();
+    images.sort();
+    images.into_iter().next()
+}
 
-/// Extracts an icon from an EXE file and caches it
-fn get_cached_icon(path: &str) -> Option<HICON> {
+/// Opens the preview image (folder if it has none)
+fn open_screenshot_image() {
+    match first_screenshot_image() {
+        Some(image) => crate::open_with::open_image(&image),
+        None => open_screenshot_folder(),
+    }
+}
+
+/// Context menu of the screenshot preview
+unsafe fn show_screenshot_context_menu(hwnd: HWND) {
+    let Ok(menu) = CreatePopupMenu() else {
+        return;
+    };
+    let image = first_screenshot_image();
+    let image_flags = if image.is_some() { MF_STRING } else { MF_STRING | MF_GRAYED };
+    let _ = AppendMenuW(menu, image_flags, ID_MENU_OPEN_IMAGE as usize, w!("Open image"));
+    let _ = AppendMenuW(menu, image_flags, ID_MENU_SHOW_IMAGE as usize, w!("Show image in folder"));
+    let _ = AppendMenuW(menu, MF_STRING, ID_MENU_OPEN_FOLDER as usize, w!("Open folder"));
+
+    let mut pt = POINT::default();
+    let _ = GetCursorPos(&mut pt);
+    let cmd = TrackPopupMenu(
+        menu,
+        TPM_RETURNCMD | TPM_RIGHTBUTTON | TPM_LEFTALIGN | TPM_TOPALIGN,
+        pt.x,
+        pt.y,
+        0,
+        hwnd,
+        None,
+    ).0 as u32;
+    let _ = DestroyMenu(menu);
+
+    match (cmd, image) {
+        (ID_MENU_OPEN_IMAGE, Some(image)) => crate::open_with::open_image(&image),
+        (ID_MENU_SHOW_IMAGE, Some(image)) => crate::open_with::open_location(&image),
+        (ID_MENU_OPEN_FOLDER, _) => open_screenshot_folder(),
+        _ => {}
+    }
+}
+
+
+/// Returns a cached icon; uncached icons are queued for the icon worker
+/// and None is returned until extraction has finished (placeholder is drawn)
+fn get_cached_icon(path: &str, size: IconSize) -> Option<HICON> {
     if path.is_empty() || path == "Access denied" {
         return None;
     }
@@ -186,7 +477,7 @@ fn get_cached_icon(path: &str) -> Option<HICON> {
     // Check cache
     {
         let cache = ICON_CACHE.lock();
-        if let Some(&icon_ptr) = cache.get(path) {
+        if let Some(&icon_ptr) = cache.get(&(path.to_string(), size)) {
             if icon_ptr != 0 {
                 return Some(HICON(icon_ptr as *mut _));
             }
@@ -194,31 +485,93 @@ fn get_cached_icon(path: &str) -> Option<HICON> {
         }
     }
 
-    // Extract icon
-    let icon = extract_icon(path);
+    request_icon(path, size);
+    None
+}
+
+/// Queues an icon for extraction (only once per path and size)
+fn request_icon(path: &str, size: IconSize) {
+    if path.is_empty() || path == "Access denied" {
+        return;
+    }
+
+    let key = (path.to_string(), size);
+    if ICON_CACHE.lock().contains_key(&key) {
+        return;
+    }
+    if !ICON_PENDING.lock().insert(key.clone()) {
+        // Already queued
+        return;
+    }
+
+    if ICON_QUEUE.try_send(key.clone()).is_err() {
+        // Queue full - allow a new attempt on the next paint
+        ICON_PENDING.lock().remove(&key);
+    }
+}
+
+/// Starts the icon worker thread (ExtractIconExW must not run inside WM_PAINT)
+fn start_icon_worker() -> Sender<(String, IconSize)> {
+    let (tx, rx) = bounded::<(String, IconSize)>(ICON_QUEUE_SIZE);
+
+    thread::spawn(move || {
+        while let Ok((path, size)) = rx.recv() {
+            let icon = match size {
+                IconSize::Small => extract_icon(&path),
+                IconSize::Large => extract_large_icon(&path),
+            };
+            store_icon(path.clone(), size, icon);
+            ICON_PENDING.lock().remove(&(path, size));
+
+            // Icon is now available - repaint
+            match size {
+                IconSize::Small => invalidate_region(&log_icon_column_rect()),
+                IconSize::Large => redraw_details_window(),
+            }
+        }
+    });
+
+    tx
+}
+
+/// Saves an extracted icon to the cache
+fn store_icon(path: String, size: IconSize, icon: Option<HICON>) {
     let icon_ptr = icon.map(|h| h.0 as usize).unwrap_or(0);
 
-    // Save to cache
-    {
-        let mut cache = ICON_CACHE.lock();
-        let mut order = ICON_CACHE_ORDER.lock();
-
-        // Limit cache size (remove oldest)
-        while order.len() >= MAX_ICON_CACHE {
-            if let Some(old_path) = order.pop_front() {
-                if let Some(old_icon) = cache.remove(&old_path) {
-                    if old_icon != 0 {
-                        unsafe { let _ = DestroyIcon(HICON(old_icon as *mut _)); }
-                    }
+    let mut cache = ICON_CACHE.lock();
+    let mut order = ICON_CACHE_ORDER.lock();
+
+    // Limit cache size (remove oldest)
+    let limit = crate::config::memory().icon_cache_entries.max(1);
+    while order.len() >= limit {
+        if let Some(old_key) = order.pop_front() {
+            if let Some(old_icon) = cache.remove(&old_key) {
+                if old_icon != 0 {
+                    unsafe { let _ = DestroyIcon(HICON(old_icon as *mut _)); }
                 }
             }
         }
-
-        cache.insert(path.to_string(), icon_ptr);
-        order.push_back(path.to_string());
     }
 
-    icon
+    let key = (path, size);
+    cache.insert(key.clone(), icon_ptr);
+    order.push_back(key);
+}
+
+/// Draws a placeholder where an icon is not (yet) available
+unsafe fn draw_icon_placeholder(hdc: windows::Win32::Graphics::Gdi::HDC, x: i32, y: i32, size: i32) {
+    let brush = CreateSolidBrush(COLORREF(COLOR_ICON_PLACEHOLDER));
+    let pen = CreatePen(PS_SOLID, 1, COLORREF(COLOR_ICON_PLACEHOLDER));
+    let old_brush = SelectObject(hdc, brush);
+    let old_pen = SelectObject(hdc, pen);
+
+    let inset = size / 8;
+    let _ = RoundRect(hdc, x + inset, y + inset, x + size - inset, y + size - inset, 4, 4);
+
+    SelectObject(hdc, old_brush);
+    SelectObject(hdc, old_pen);
+    let _ = DeleteObject(HGDIOBJ(brush.0));
+    let _ = DeleteObject(HGDIOBJ(pen.0));
 }
 
 /// Extracts the icon from an EXE file
@@ -245,9 +598,6 @@ fn extract_icon(path: &str) -> Option<HICON> {
 
 /// Extracts the large icon (32x32) from an EXE file
 fn extract_large_icon(path: &str) -> Option<HICON> {
-    if path.is_empty() || path == "Access denied" {
-        return None;
-    }
     unsafe {
         let path_wide: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
         let mut large_icon: HICON = HICON::default();
@@ -312,58 +662,154 @@ fn extract_paths_from_details(details: &str) -> Vec<(String, String)> {
     paths
 }
 
-/// Adds a log entry (called by logger)
-pub fn add_log_entry(text: String, event_type: String, details: String, process_path: String) {
+/// Handle of the alert window (0 = not created yet)
+pub fn window_hwnd() -> isize {
+    WINDOW_HWND.load(Ordering::SeqCst) as isize
+}
+
+/// Adds a log entry (called by logger, db_id = row in the event database)
+pub fn add_log_entry(entry: &LogEntry, db_id: Option<i64>) {
     let count = EVENT_COUNT.fetch_add(1, Ordering::SeqCst) + 1;
 
     if !ALERT_ACTIVE.load(Ordering::SeqCst) {
-        let mut msg = ALERT_MESSAGE.lock();
-        *msg = format!("PC Watcher - {} Events", count);
+        {
+            let mut msg = ALERT_MESSAGE.lock();
+            *msg = format!("PC Watcher - {} Events", count);
+        }
+        // Only the counter text changed
+        invalidate_region(&header_text_rect());
     }
+    crate::selftest::confirm(entry, crate::selftest::Stage::Gui);
 
-    // Pre-cache icon (in background, non-blocking)
-    if !process_path.is_empty() {
-        let path_clone = process_path.clone();
-        std::thread::spawn(move || {
-            let _ = get_cached_icon(&path_clone);
-        });
+    // Filter active? Entry is counted, but not shown
+    if let Some(ref filter) = *LOG_FILTER.lock() {
+        if !filter.matches(&entry.process_name, &entry.tags) {
+            return;
+        }
     }
 
+    // Pre-cache icon (queued to icon worker, non-blocking)
+    request_icon(&entry.process_path, IconSize::Small);
+    let severity = entry.severity;
+
     let mut entries = LOG_ENTRIES.lock();
-    if entries.len() >= MAX_LOG_ENTRIES {
+    let scrolled = entries.len() >= MAX_LOG_ENTRIES;
+    if scrolled {
         entries.pop_front();
     }
-    entries.push_back(GuiLogEntry { text, event_type, details, process_path });
-    redraw_window();
+    entries.push_back(GuiLogEntry {
+        id: count as u64,
+        // Compact line for the list, full details for double-click
+        text: entry.format_gui(),
+        event_type: entry.event_type.clone(),
+        details: limit_details(entry.format_file(), crate::config::memory().gui_details_max_chars),
+        process_name: entry.process_name.clone(),
+        process_id: entry.process_id,
+        process_path: entry.process_path.clone(),
+        severity,
+        acknowledged: severity < Severity::Warning,
+        db_id,
+        owned: entry.owner_process_id > 0,
+        tags: entry.tags.iter().map(|tag| (tag.clone(), crate::tags::color(tag))).collect(),
+    });
+    let new_row = entries.len() - 1;
+    drop(entries);
+
+    // Open alert counter on the ACK button
+    if severity >= Severity::Warning {
+        invalidate_region(&header_rect());
+    }
+
+    if scrolled {
+        // All rows moved up by one
+        invalidate_region(&log_rows_rect());
+    } else {
+        // Only the newly added row
+        invalidate_region(&log_row_rect(new_row));
+    }
 }
 
 /// Starts the alert window
 pub fn start_alert_window() {
     thread::spawn(|| {
         if let Err(e) = create_window() {
-            error!("Could not create alert window: {}", e);
+            crate::error::report("Could not create alert window", e);
         }
     });
     thread::sleep(Duration::from_millis(100));
 }
 
 /// Sets the alert status (changes color and text)
-pub fn set_alert(process_name: &str, _process_path: &str) {
-    ALERT_ACTIVE.store(true, Ordering::SeqCst);
-    {
-        let mut msg = ALERT_MESSAGE.lock();
-        *msg = format!("!! {} !!", process_name);
+pub fn set_alert(process_name: &str, _process_path: &str, severity: Severity) {
+    let duration = crate::config::get().alert_duration.for_severity(severity);
+    show_alert(process_name, format!("!! {} !!", process_name), None, duration, severity);
+}
+
+/// Sets the alert status of a rule (header text, color and duration from the rule config)
+pub fn set_rule_alert(rule: Rule, message: &str, _process_path: &str, severity: Severity) {
+    let duration = crate::rules::alert_duration(rule, severity);
+    let header = crate::rules::alert_header(rule, message);
+    show_alert(message, header, crate::rules::alert_color(rule), duration, severity);
+}
+
+/// Shows an alert in the header, cleared by the status timer after `duration_secs` (0 = on ACK)
+fn show_alert(process_name: &str, header: String, color: Option<u32>, duration_secs: u64, severity: Severity) {
+    // Overlay line over full-screen apps (never during do not disturb)
+    if !crate::snooze::is_do_not_disturb() && crate::snooze::is_fullscreen_active() {
+        crate::overlay::show(process_name, severity);
     }
-    redraw_window();
 
-    thread::spawn(|| {
-        thread::sleep(Duration::from_secs(5));
-        clear_alert();
-    });
+    // Full-screen app or do not disturb: shown when the snooze ends (Critical only waits for
+    // do not disturb - a full-screen window must not hide detections)
+    let snoozed = if severity == Severity::Critical {
+        crate::snooze::is_do_not_disturb()
+    } else {
+        crate::snooze::is_snoozed()
+    };
+    if snoozed {
+        crate::snooze::queue_alert(QueuedAlert { header, color, duration_secs, severity });
+        return;
+    }
+    display_alert(header, color, duration_secs, severity);
+}
+
+/// Shows an alert held back during the snooze (the clear time starts now)
+pub fn show_queued_alert(alert: QueuedAlert) {
+    display_alert(alert.header, alert.color, alert.duration_secs, alert.severity);
 }
 
-/// Clears the alert status
+/// Adds the alert to the header
+fn display_alert(header: String, color: Option<u32>, duration_secs: u64, severity: Severity) {
+    let clear_at = (duration_secs > 0).then(|| Instant::now() + Duration::from_secs(duration_secs));
+    {
+        let mut alerts = ACTIVE_ALERTS.lock();
+        // Repeated alert: refreshed and moved to the end instead of stacked twice
+        alerts.retain(|a| a.header != header);
+        if alerts.len() >= MAX_ACTIVE_ALERTS {
+            alerts.remove(0);
+        }
+        alerts.push(ActiveAlert { header, color, severity, clear_at });
+        // The newest alert is shown first
+        ALERT_CYCLE.store(alerts.len() - 1, Ordering::SeqCst);
+    }
+    ALERT_ACTIVE.store(true, Ordering::SeqCst);
+    invalidate_region(&header_rect());
+    crate::accessibility::notify_status_changed();
+
+    // Critical alerts need the log and screenshot: expand the collapsed window
+    if severity == Severity::Critical && COLLAPSED.load(Ordering::SeqCst) {
+        let hwnd = WINDOW_HWND.load(Ordering::SeqCst);
+        if hwnd != 0 {
+            unsafe {
+                let _ = PostMessageW(HWND(hwnd as *mut _), WM_EXPAND, WPARAM(0), LPARAM(0));
+            }
+        }
+    }
+}
+
+/// Clears all active alerts
 pub fn clear_alert() {
+    ACTIVE_ALERTS.lock().clear();
     ALERT_ACTIVE.store(false, Ordering::SeqCst);
     {
         let count = EVENT_COUNT.load(Ordering::SeqCst);
@@ -371,25 +817,202 @@ pub fn clear_alert() {
         *msg = format!("PC Watcher - {} Events", count);
     }
     // Screenshot is now preserved!
-    redraw_window();
+    invalidate_region(&header_rect());
+}
+
+/// Removes expired alerts and cycles the header through the remaining ones (status timer)
+fn update_active_alerts() {
+    let now = Instant::now();
+    let remaining = {
+        let mut alerts = ACTIVE_ALERTS.lock();
+        let before = alerts.len();
+        alerts.retain(|a| a.clear_at.is_none_or(|at| now < at));
+        if alerts.len() > 1 && ALERT_TICKS.fetch_add(1, Ordering::SeqCst) % ALERT_CYCLE_TICKS == ALERT_CYCLE_TICKS - 1 {
+            ALERT_CYCLE.store((ALERT_CYCLE.load(Ordering::SeqCst) + 1) % alerts.len(), Ordering::SeqCst);
+        } else if alerts.len() == before {
+            return;
+        }
+        alerts.len()
+    };
+    if remaining == 0 {
+        clear_alert();
+    } else {
+        invalidate_region(&header_rect());
+    }
 }
 
-/// Redraws the window
-fn redraw_window() {
+/// Alert shown in the header: text, color and severity (None for the idle state)
+fn shown_alert() -> Option<(String, Option<u32>, Severity)> {
+    let alerts = ACTIVE_ALERTS.lock();
+    let alert = alerts.get(ALERT_CYCLE.load(Ordering::SeqCst) % alerts.len().max(1))?;
+    let text = if alerts.len() > 1 {
+        format!("{}   ({} active alerts)", alert.header, alerts.len())
+    } else {
+        alert.header.clone()
+    };
+    Some((text, alert.color, alert.severity))
+}
+
+/// Current window height (header only while collapsed)
+fn window_height() -> i32 {
+    if COLLAPSED.load(Ordering::SeqCst) { HEADER_HEIGHT } else { WINDOW_HEIGHT }
+}
+
+/// Collapses the window to the header bar or expands it again (state is saved)
+unsafe fn set_collapsed(hwnd: HWND, collapsed: bool) {
+    if COLLAPSED.swap(collapsed, Ordering::SeqCst) == collapsed {
+        return;
+    }
+    // A docked window is resized at its visible position and docked again
+    crate::dock::detach(hwnd);
+    let height = window_height();
+    let _ = SetWindowPos(hwnd, HWND_TOPMOST, 0, 0, WINDOW_WIDTH, height, SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE);
+    let rgn = CreateRoundRectRgn(0, 0, WINDOW_WIDTH + 1, height + 1, CORNER_RADIUS, CORNER_RADIUS);
+    let _ = SetWindowRgn(hwnd, rgn, true);
+    crate::dock::attach(hwnd);
+    let _ = InvalidateRect(hwnd, None, false);
+    crate::config::update(|config| config.alert_window.collapsed = collapsed);
+    info!("Alert window {}", if collapsed { "collapsed" } else { "expanded" });
+}
+
+/// Redraws only a part of the window (WM_PAINT skips untouched sections)
+fn invalidate_region(region: &RECT) {
     let hwnd = WINDOW_HWND.load(Ordering::SeqCst);
     if hwnd != 0 {
         unsafe {
-            let hwnd = HWND(hwnd as *mut _);
-            let _ = InvalidateRect(hwnd, None, true);
+            let _ = InvalidateRect(HWND(hwnd as *mut _), Some(region), false);
+        }
+    }
+}
+
+/// Header button layout (shared by painting, hit-testing and invalidation)
+struct HeaderButtons {
+    ack_x: i32,
+    ack_w: i32,
+    mute_x: i32,
+    mute_w: i32,
+    tray_x: i32,
+    tray_w: i32,
+    min_x: i32,
+    min_w: i32,
+    pin_x: i32,
+    pin_w: i32,
+    y: i32,
+}
+
+/// Calculates the header button positions: [ACK] [MUTE] [TRAY] [MINIMIZE] [PINNED/UNPIN]
+fn header_buttons() -> HeaderButtons {
+    let is_pinned = WINDOW_PINNED.load(Ordering::SeqCst);
+    let pin_w = if is_pinned { 70 } else { 60 };
+    let min_w = 80;
+    let tray_w = 50;
+    let right_margin = 10;
+    let pin_x = WINDOW_WIDTH - pin_w - right_margin;
+    let min_x = pin_x - min_w - 5;
+    let tray_x = min_x - tray_w - 5;
+    let mute_w = 50;
+    let mute_x = tray_x - mute_w - 5;
+    let ack_w = 60;
+    let ack_x = mute_x - ack_w - 5;
+    HeaderButtons {
+        ack_x,
+        ack_w,
+        mute_x,
+        mute_w,
+        tray_x,
+        tray_w,
+        min_x,
+        min_w,
+        pin_x,
+        pin_w,
+        y: (HEADER_HEIGHT - BTN_HEIGHT) / 2,
+    }
+}
+
+/// Complete header (background color, text, buttons)
+fn header_rect() -> RECT {
+    RECT { left: 0, top: 0, right: WINDOW_WIDTH, bottom: HEADER_HEIGHT }
+}
+
+/// Header text left of the buttons (event counter)
+fn header_text_rect() -> RECT {
+    RECT { left: 0, top: 0, right: header_buttons().ack_x - 5, bottom: HEADER_HEIGHT }
+}
+
+/// Events-per-second sparkline in the header (left of the CAM indicator and the buttons)
+fn sparkline_rect() -> RECT {
+    let right = header_buttons().ack_x - 45;
+    let top = (HEADER_HEIGHT - SPARKLINE_HEIGHT) / 2;
+    RECT { left: right - SPARKLINE_WIDTH, top, right, bottom: top + SPARKLINE_HEIGHT }
+}
+
+/// Log area below the header (legend + rows)
+fn log_area_rect() -> RECT {
+    RECT { left: 0, top: HEADER_HEIGHT, right: LOG_AREA_WIDTH, bottom: WINDOW_HEIGHT }
+}
+
+/// All log rows
+fn log_rows_rect() -> RECT {
+    RECT {
+        left: 0,
+        top: LOG_FIRST_ROW_Y,
+        right: LOG_AREA_WIDTH,
+        bottom: LOG_FIRST_ROW_Y + MAX_LOG_ENTRIES as i32 * LOG_ROW_HEIGHT,
+    }
+}
+
+/// A single log row
+fn log_row_rect(index: usize) -> RECT {
+    let top = LOG_FIRST_ROW_Y + index as i32 * LOG_ROW_HEIGHT;
+    RECT { left: 0, top, right: LOG_AREA_WIDTH, bottom: top + LOG_ROW_HEIGHT }
+}
+
+/// Icon column of all log rows (repainted when icons arrive)
+fn log_icon_column_rect() -> RECT {
+    let rows = log_rows_rect();
+    RECT { left: 5, top: rows.top, right: 5 + ICON_SIZE, bottom: rows.bottom }
+}
+
+/// Screenshot area on the right (preview + hints below)
+fn screenshot_area_rect() -> RECT {
+    RECT { left: LOG_AREA_WIDTH, top: HEADER_HEIGHT, right: WINDOW_WIDTH, bottom: WATCH_PANEL_Y }
+}
+
+/// Pipeline status strip at the bottom of the log area
+fn status_strip_rect() -> RECT {
+    RECT { left: 0, top: WINDOW_HEIGHT - STATUS_STRIP_HEIGHT, right: LOG_AREA_WIDTH, bottom: WINDOW_HEIGHT }
+}
+
+/// Watch panel below the screenshot area
+fn watch_panel_rect() -> RECT {
+    RECT { left: LOG_AREA_WIDTH, top: WATCH_PANEL_Y, right: WINDOW_WIDTH, bottom: health_rect().top }
+}
+
+/// Health section below the watch panel (next to the status strip)
+fn health_rect() -> RECT {
+    RECT { left: LOG_AREA_WIDTH, top: WINDOW_HEIGHT - STATUS_STRIP_HEIGHT, right: WINDOW_WIDTH, bottom: WINDOW_HEIGHT }
+}
+
+/// Checks if two rectangles overlap
+fn rects_intersect(a: &RECT, b: &RECT) -> bool {
+    a.left < b.right && b.left < a.right && a.top < b.bottom && b.top < a.bottom
+}
+
+/// Redraws all open details windows
+pub fn redraw_details_window() {
+    let hwnds: Vec<usize> = DETAILS_WINDOWS.lock().keys().copied().collect();
+    for hwnd in hwnds {
+        unsafe {
+            let _ = InvalidateRect(HWND(hwnd as *mut _), None, true);
         }
     }
 }
 
 /// Creates the window
-fn create_window() -> Result<(), String> {
+fn create_window() -> Result<(), Error> {
     unsafe {
         let instance = GetModuleHandleW(None)
-            .map_err(|e| format!("GetModuleHandle: {}", e))?;
+            .map_err(|e| Error::win32("GetModuleHandle", &e))?;
 
         // Main window class
         let class_name = w!("PCWatcherAlert");
@@ -420,6 +1043,8 @@ fn create_window() -> Result<(), String> {
 
         let (x, y) = load_position().unwrap_or((0, 0));
         info!("Window position loaded: ({}, {})", x, y);
+        COLLAPSED.store(crate::config::get().alert_window.collapsed, Ordering::SeqCst);
+        let height = window_height();
 
         let title = w!("PC Watcher");
 
@@ -430,39 +1055,37 @@ fn create_window() -> Result<(), String> {
             WS_POPUP | WS_VISIBLE,
             x, y,
             WINDOW_WIDTH,
-            WINDOW_HEIGHT,
+            height,
             None,
             None,
             instance,
             None,
         );
 
-        let hwnd = match hwnd {
-            Ok(h) => h,
-            Err(e) => {
-                error!("CreateWindowExW failed: {}", e);
-                return Err(format!("CreateWindowExW: {}", e));
-            }
-        };
-
+        let hwnd = hwnd.map_err(|e| Error::win32("CreateWindowExW", &e))?;
         if hwnd.0.is_null() {
-            return Err("Window handle is NULL".to_string());
+            return Err(Error::Win32 { call: "CreateWindowExW", message: "window handle is NULL".to_string() });
         }
 
         WINDOW_HWND.store(hwnd.0 as usize, Ordering::SeqCst);
 
         // Rounded corners
-        let rgn = CreateRoundRectRgn(0, 0, WINDOW_WIDTH + 1, WINDOW_HEIGHT + 1, CORNER_RADIUS, CORNER_RADIUS);
+        let rgn = CreateRoundRectRgn(0, 0, WINDOW_WIDTH + 1, height + 1, CORNER_RADIUS, CORNER_RADIUS);
         let _ = SetWindowRgn(hwnd, rgn, true);
 
-        let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), 230, LWA_ALPHA);
+        crate::transparency::apply(hwnd);
+        crate::transparency::register_hotkey(hwnd);
         let _ = ShowWindow(hwnd, SW_SHOWNOACTIVATE);
-        let _ = SetWindowPos(hwnd, HWND_TOPMOST, x, y, WINDOW_WIDTH, WINDOW_HEIGHT, SWP_SHOWWINDOW | SWP_NOACTIVATE);
+        let _ = SetWindowPos(hwnd, HWND_TOPMOST, x, y, WINDOW_WIDTH, height, SWP_SHOWWINDOW | SWP_NOACTIVATE);
+        crate::dock::attach(hwnd);
 
         // Timer for regular TOPMOST check (every 3 seconds)
         const TOPMOST_TIMER_ID: usize = 1;
         let _ = SetTimer(hwnd, TOPMOST_TIMER_ID, 3000, None);
 
+        // Timer for the pipeline status strip (every second)
+        let _ = SetTimer(hwnd, STATUS_TIMER_ID, 1000, None);
+
         info!("Alert window created");
 
         let mut msg = MSG::default();
@@ -475,74 +1098,570 @@ fn create_window() -> Result<(), String> {
     Ok(())
 }
 
-/// Opens the log file in the default editor
+/// Opens the log file in the configured editor
 fn open_log_file() {
     if let Some(path) = LOG_FILE_PATH.lock().clone() {
-        info!("Opening log file: {}", path.display());
-        let _ = std::process::Command::new("notepad.exe")
-            .arg(&path)
-            .spawn();
+        crate::open_with::open_log(&path);
+    }
+}
+
+/// Returns the log row index at a y coordinate
+fn log_row_at(y: i32) -> Option<usize> {
+    if y < LOG_FIRST_ROW_Y {
+        return None;
+    }
+    let index = ((y - LOG_FIRST_ROW_Y) / LOG_ROW_HEIGHT) as usize;
+    if index < MAX_LOG_ENTRIES {
+        Some(index)
+    } else {
+        None
+    }
+}
+
+/// Shows the context menu for a log row
+unsafe fn show_row_context_menu(hwnd: HWND, entry: &GuiLogEntry) {
+    let menu = match CreatePopupMenu() {
+        Ok(m) => m,
+        Err(_) => return,
+    };
+
+    let has_path = !entry.process_path.is_empty() && !entry.process_path.starts_with("Access denied");
+    let path_flags = if has_path { MF_STRING } else { MF_STRING | MF_GRAYED };
+    let filter_active = LOG_FILTER.lock().is_some();
+
+    let _ = AppendMenuW(menu, MF_STRING, ID_MENU_DETAILS as usize, w!("Open details"));
+    let _ = AppendMenuW(menu, path_flags, ID_MENU_COPY_PATH as usize, w!("Copy path"));
+    let _ = AppendMenuW(menu, path_flags, ID_MENU_OPEN_LOCATION as usize, w!("Open file location"));
+    let _ = AppendMenuW(menu, MF_SEPARATOR, 0, None);
+    let _ = AppendMenuW(menu, MF_STRING, ID_MENU_WHITELIST as usize, w!("Add to whitelist"));
+    if crate::watch::is_watched(&entry.process_name) {
+        let _ = AppendMenuW(menu, MF_STRING, ID_MENU_UNWATCH as usize, w!("Stop watching"));
+    } else {
+        let _ = AppendMenuW(menu, MF_STRING, ID_MENU_WATCH as usize, w!("Watch this process"));
+    }
+    if crate::focus_guard::is_blocked(&entry.process_name) {
+        let _ = AppendMenuW(menu, MF_STRING, ID_MENU_UNBLOCK_FOCUS as usize, w!("Allow focus stealing"));
+    } else {
+        let _ = AppendMenuW(menu, MF_STRING, ID_MENU_BLOCK_FOCUS as usize, w!("Block focus stealing"));
+    }
+    let _ = AppendMenuW(menu, MF_STRING, ID_MENU_KILL as usize, w!("Kill process"));
+    let _ = AppendMenuW(menu, MF_SEPARATOR, 0, None);
+    if filter_active {
+        let _ = AppendMenuW(menu, MF_STRING, ID_MENU_CLEAR_FILTER as usize, w!("Clear filter"));
+    } else {
+        let _ = AppendMenuW(menu, MF_STRING, ID_MENU_FILTER as usize, w!("Filter by this process"));
+        for (i, (tag, _)) in entry.tags.iter().take(MAX_TAG_MENU_ENTRIES).enumerate() {
+            let label_wide: Vec<u16> = format!("Filter by tag \"{}\"", tag).encode_utf16().chain(std::iter::once(0)).collect();
+            let _ = AppendMenuW(menu, MF_STRING, ID_MENU_FILTER_TAG_BASE as usize + i, PCWSTR(label_wide.as_ptr()));
+        }
+    }
+
+    let mut pt = POINT::default();
+    let _ = GetCursorPos(&mut pt);
+
+    // TPM_RETURNCMD: selected command is returned directly
+    let cmd = TrackPopupMenu(
+        menu,
+        TPM_RETURNCMD | TPM_RIGHTBUTTON | TPM_LEFTALIGN | TPM_TOPALIGN,
+        pt.x,
+        pt.y,
+        0,
+        hwnd,
+        None,
+    ).0 as u32;
+    let _ = DestroyMenu(menu);
+
+    match cmd {
+        ID_MENU_DETAILS => show_details_window(entry),
+        ID_MENU_COPY_PATH => {
+            if let Err(e) = copy_to_clipboard(hwnd, &entry.process_path) {
+                error!("Could not copy path: {}", e);
+            }
+        }
+        ID_MENU_OPEN_LOCATION => open_file_location(&entry.process_path),
+        ID_MENU_WHITELIST => crate::config::add_to_whitelist(&entry.process_name),
+        ID_MENU_WATCH => crate::watch::watch_process(&entry.process_name),
+        ID_MENU_UNWATCH => crate::watch::unwatch_process(&entry.process_name),
+        ID_MENU_BLOCK_FOCUS => crate::focus_guard::block(&entry.process_name),
+        ID_MENU_UNBLOCK_FOCUS => crate::focus_guard::unblock(&entry.process_name),
+        ID_MENU_KILL => kill_process(hwnd, entry.process_id, &entry.process_name),
+        ID_MENU_FILTER => set_log_filter(Some(LogFilter::Process(entry.process_name.clone()))),
+        ID_MENU_CLEAR_FILTER => set_log_filter(None),
+        _ => {
+            let tag = cmd.checked_sub(ID_MENU_FILTER_TAG_BASE).and_then(|i| entry.tags.get(i as usize));
+            if let Some((tag, _)) = tag {
+                set_log_filter(Some(LogFilter::Tag(tag.clone())));
+            }
+        }
     }
 }
 
-/// Shows the details window
-unsafe fn show_details_window(details: String) {
+/// Sets the process or tag filter for the log list (None = show all)
+fn set_log_filter(filter: Option<LogFilter>) {
+    if let Some(ref filter) = filter {
+        info!("Log filter set: {}", filter.label());
+        // Keep only matching entries
+        LOG_ENTRIES.lock().retain(|e| {
+            let tags: Vec<String> = e.tags.iter().map(|(tag, _)| tag.clone()).collect();
+            filter.matches(&e.process_name, &tags)
+        });
+    } else {
+        info!("Log filter cleared");
+    }
+    *LOG_FILTER.lock() = filter;
+    invalidate_region(&log_area_rect());
+}
+
+/// Copies text to the clipboard (CF_UNICODETEXT)
+pub unsafe fn copy_to_clipboard(hwnd: HWND, text: &str) -> Result<(), Error> {
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let size = wide.len() * std::mem::size_of::<u16>();
+
+    OpenClipboard(hwnd).map_err(|e| Error::win32("OpenClipboard", &e))?;
+    let _ = EmptyClipboard();
+
+    let result = (|| {
+        let hmem = GlobalAlloc(GMEM_MOVEABLE, size).map_err(|e| Error::win32("GlobalAlloc", &e))?;
+        let ptr = GlobalLock(hmem) as *mut u16;
+        if ptr.is_null() {
+            let _ = GlobalFree(hmem);
+            return Err(Error::Win32 { call: "GlobalLock", message: "no memory".to_string() });
+        }
+        std::ptr::copy_nonoverlapping(wide.as_ptr(), ptr, wide.len());
+        let _ = GlobalUnlock(hmem);
+
+        // Clipboard owns the memory after success
+        if let Err(e) = SetClipboardData(CF_UNICODETEXT.0 as u32, HANDLE(hmem.0)) {
+            let _ = GlobalFree(hmem);
+            return Err(Error::win32("SetClipboardData", &e));
+        }
+        Ok(())
+    })();
+
+    let _ = CloseClipboard();
+    result
+}
+
+/// Opens the folder of a file (Explorer selects it)
+fn open_file_location(path: &str) {
+    info!("Opening file location: {}", path);
+    crate::open_with::open_location(std::path::Path::new(path));
+}
+
+/// Terminates a process after confirmation
+pub unsafe fn kill_process(hwnd: HWND, process_id: u32, process_name: &str) {
+    if process_id == 0 {
+        return;
+    }
+
+    let question: Vec<u16> = format!("Kill process \"{}\" (PID: {})?", process_name, process_id)
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let answer = MessageBoxW(
+        hwnd,
+        windows::core::PCWSTR(question.as_ptr()),
+        w!("PC Watcher"),
+        MB_YESNO | MB_ICONWARNING | MB_TOPMOST,
+    );
+    if answer != IDYES {
+        return;
+    }
+
+    match terminate_process(process_id) {
+        Ok(()) => info!("Process killed: {} (PID: {})", process_name, process_id),
+        Err(e) => error!("Could not kill {} (PID: {}): {}", process_name, process_id, e),
+    }
+}
+
+/// Terminates a process without asking (confirmed by the caller)
+pub fn terminate_process(process_id: u32) -> Result<(), String> {
+    if process_id == 0 {
+        return Err("no process".to_string());
+    }
+    unsafe {
+        let handle = OpenProcess(PROCESS_TERMINATE, false, process_id).map_err(|e| format!("cannot open: {}", e))?;
+        let result = TerminateProcess(handle, 1).map_err(|e| e.to_string());
+        let _ = CloseHandle(handle);
+        result
+    }
+}
+
+/// Alerts in the list not acknowledged yet (ACK button, tray tooltip, taskbar badge)
+fn open_alert_count() -> usize {
+    LOG_ENTRIES.lock().iter().filter(|e| !e.acknowledged).count()
+}
+
+/// Visible parts of the window for screen readers (header text, buttons, log rows, status strip)
+pub fn accessible_elements() -> Vec<crate::accessibility::Element> {
+    use crate::accessibility::{Element, Role};
+
+    let header_text = match shown_alert() {
+        Some((text, _, severity)) => format!("{}: {}", severity.as_str(), text),
+        None => ALERT_MESSAGE.lock().clone(),
+    };
+    let mut elements = vec![Element {
+        id: 1,
+        role: Role::Status,
+        name: header_text,
+        rect: header_text_rect(),
+        action: None,
+    }];
+
+    let btns = header_buttons();
+    let open_alerts = open_alert_count();
+    let pin_name = if WINDOW_PINNED.load(Ordering::SeqCst) { "Unpin window" } else { "Pin window" };
+    let ack_name = format!("Acknowledge all alerts ({} open)", open_alerts);
+    let buttons = [
+        (btns.ack_x, btns.ack_w, ack_name.as_str()),
+        (btns.mute_x, btns.mute_w, "Mute process of the latest alert"),
+        (btns.tray_x, btns.tray_w, "Minimize to tray"),
+        (btns.min_x, btns.min_w, "Minimize to taskbar"),
+        (btns.pin_x, btns.pin_w, pin_name),
+    ];
+    for (index, (x, w, name)) in buttons.iter().enumerate() {
+        elements.push(Element {
+            id: 2 + index as i32,
+            role: Role::Button,
+            name: name.to_string(),
+            rect: RECT { left: *x, top: btns.y, right: x + w, bottom: btns.y + BTN_HEIGHT },
+            action: Some(WM_LBUTTONDOWN),
+        });
+    }
+
+    // Collapsed: only the header is visible
+    if COLLAPSED.load(Ordering::SeqCst) {
+        return elements;
+    }
+
+    // Rows keep their element while newer entries push them down (ID of the log entry)
+    for (index, entry) in LOG_ENTRIES.lock().iter().enumerate() {
+        let state = if entry.acknowledged { "" } else { ", not acknowledged" };
+        elements.push(Element {
+            id: 100 + (entry.id % 1_000_000_000) as i32,
+            role: Role::LogRow,
+            name: format!("{}{}: {}", entry.severity.as_str(), state, entry.text),
+            rect: log_row_rect(index),
+            action: Some(WM_LBUTTONDBLCLK),
+        });
+    }
+
+    elements.push(Element {
+        id: 10,
+        role: Role::StatusStrip,
+        name: status_strip_text(),
+        rect: status_strip_rect(),
+        action: None,
+    });
+    elements.push(Element {
+        id: 11,
+        role: Role::Button,
+        name: health_text(),
+        rect: health_rect(),
+        action: Some(WM_LBUTTONDOWN),
+    });
+    elements
+}
+
+/// Marks all alerts in the list as acknowledged and clears the alert header
+fn acknowledge_all() {
+    let count = {
+        let mut entries = LOG_ENTRIES.lock();
+        let open: Vec<&mut GuiLogEntry> = entries.iter_mut().filter(|e| !e.acknowledged).collect();
+        let count = open.len();
+        open.into_iter().for_each(|e| e.acknowledged = true);
+        count
+    };
+    info!("{} alerts acknowledged", count);
+    clear_alert();
+    invalidate_region(&log_rows_rect());
+}
+
+/// Mute durations offered in the menu (hours)
+const MUTE_HOURS: [u32; 3] = [1, 4, 24];
+
+/// Menu "Mute <process> for N hours" (temporary whitelist entry)
+unsafe fn show_mute_menu(hwnd: HWND, process_name: Option<&str>) {
+    let menu = match CreatePopupMenu() {
+        Ok(m) => m,
+        Err(_) => return,
+    };
+
+    match process_name {
+        Some(name) => {
+            for (i, hours) in MUTE_HOURS.iter().enumerate() {
+                let label = format!("Mute {} for {} hour{}", name, hours, if *hours == 1 { "" } else { "s" });
+                let label_wide: Vec<u16> = label.encode_utf16().chain(std::iter::once(0)).collect();
+                let _ = AppendMenuW(menu, MF_STRING, ID_MENU_MUTE_BASE as usize + i, PCWSTR(label_wide.as_ptr()));
+            }
+        }
+        None => {
+            let _ = AppendMenuW(menu, MF_STRING | MF_GRAYED, 0, w!("No alert to mute"));
+        }
+    }
+
+    let mut pt = POINT::default();
+    let _ = GetCursorPos(&mut pt);
+    let cmd = TrackPopupMenu(
+        menu,
+        TPM_RETURNCMD | TPM_RIGHTBUTTON | TPM_LEFTALIGN | TPM_TOPALIGN,
+        pt.x,
+        pt.y,
+        0,
+        hwnd,
+        None,
+    ).0 as u32;
+    let _ = DestroyMenu(menu);
+
+    if let (Some(name), Some(hours)) = (process_name, cmd.checked_sub(ID_MENU_MUTE_BASE).and_then(|i| MUTE_HOURS.get(i as usize))) {
+        crate::config::mute_process(name, *hours);
+    }
+}
+
+/// Shows the details for an event
+/// Reuses the window already showing this event, otherwise the unpinned window,
+/// otherwise opens a new one (multiple pinned windows can be open)
+unsafe fn show_details_window(entry: &GuiLogEntry) {
+    // Already open for this event -> bring to front
+    let existing = DETAILS_WINDOWS.lock()
+        .iter()
+        .find(|(_, w)| w.event_id == entry.id)
+        .map(|(&hwnd, _)| hwnd);
+    if let Some(hwnd) = existing {
+        bring_details_to_front(HWND(hwnd as *mut _));
+        return;
+    }
+
+    // Reuse the unpinned window for the new event
+    let unpinned = {
+        let mut windows = DETAILS_WINDOWS.lock();
+        windows.iter_mut()
+            .find(|(_, w)| !w.pinned)
+            .map(|(&hwnd, w)| {
+                w.event_id = entry.id;
+                w.db_id = entry.db_id;
+                w.process_name = entry.process_name.clone();
+                w.details = entry.details.clone();
+                hwnd
+            })
+    };
+    if let Some(hwnd) = unpinned {
+        let hwnd = HWND(hwnd as *mut _);
+        let _ = InvalidateRect(hwnd, None, true);
+        bring_details_to_front(hwnd);
+        return;
+    }
+
+    create_details_window(entry);
+}
+
+/// Brings a details window to the front
+unsafe fn bring_details_to_front(hwnd: HWND) {
+    let _ = ShowWindow(hwnd, SW_SHOW);
+    let _ = SetWindowPos(hwnd, HWND_TOPMOST, 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE | SWP_SHOWWINDOW);
+}
+
+/// Creates a new details window
+unsafe fn create_details_window(entry: &GuiLogEntry) {
     let instance = GetModuleHandleW(None).unwrap_or_default();
     let details_class = w!("PCWatcherDetails");
     let title = w!("PC Watcher - Details");
 
-    // Save details
-    {
-        let mut d = CURRENT_DETAILS.lock();
-        *d = details;
+    // Window position (next to main window, cascaded for every open window)
+    let open_count = DETAILS_WINDOWS.lock().len() as i32;
+    let main_hwnd = WINDOW_HWND.load(Ordering::SeqCst);
+    let (dx, dy) = if main_hwnd != 0 {
+        let mut rect = RECT::default();
+        let _ = GetWindowRect(HWND(main_hwnd as *mut _), &mut rect);
+        (rect.right + 10, rect.top)
+    } else {
+        (100, 100)
+    };
+    let dx = dx + open_count * DETAILS_CASCADE_OFFSET;
+    let dy = dy + open_count * DETAILS_CASCADE_OFFSET;
+
+    // Created hidden, state is registered before the first message is handled
+    let hwnd = CreateWindowExW(
+        WS_EX_TOPMOST | WS_EX_LAYERED,
+        details_class,
+        title,
+        WS_POPUP,
+        dx, dy,
+        DETAILS_WIDTH,
+        DETAILS_HEIGHT,
+        None,
+        None,
+        instance,
+        None,
+    );
+
+    let hwnd = match hwnd {
+        Ok(h) => h,
+        Err(e) => {
+            error!("Could not create details window: {}", e);
+            return;
+        }
+    };
+
+    DETAILS_WINDOWS.lock().insert(hwnd.0 as usize, DetailsWindow {
+        event_id: entry.id,
+        db_id: entry.db_id,
+        process_name: entry.process_name.clone(),
+        details: entry.details.clone(),
+        pinned: false,
+    });
+
+    // Rounded corners (region is owned by the window afterwards)
+    let rgn = CreateRoundRectRgn(0, 0, DETAILS_WIDTH + 1, DETAILS_HEIGHT + 1, CORNER_RADIUS, CORNER_RADIUS);
+    let _ = SetWindowRgn(hwnd, rgn, true);
+
+    let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), 240, LWA_ALPHA);
+
+    // Load and set icon from EXE resources (shared - no need to free it per window)
+    let icon = LoadImageW(
+        instance,
+        windows::core::PCWSTR(std::ptr::without_provenance(1)), // Resource ID 1
+        IMAGE_ICON,
+        32, 32,
+        LR_DEFAULTCOLOR | LR_SHARED,
+    ).ok().map(|h| HICON(h.0));
+
+    if let Some(icon) = icon {
+        let _ = SendMessageW(hwnd, WM_SETICON, WPARAM(0), LPARAM(icon.0 as isize)); // ICON_SMALL
+        let _ = SendMessageW(hwnd, WM_SETICON, WPARAM(1), LPARAM(icon.0 as isize)); // ICON_BIG
+    }
+
+    let _ = ShowWindow(hwnd, SW_SHOW);
+}
+
+/// Closes all details windows (called when the main window closes)
+unsafe fn close_all_details_windows() {
+    let hwnds: Vec<usize> = DETAILS_WINDOWS.lock().keys().copied().collect();
+    for hwnd in hwnds {
+        let _ = DestroyWindow(HWND(hwnd as *mut _));
+    }
+}
+
+/// Details header button positions
+struct DetailsButtons {
+    mute_x: i32,
+    note_x: i32,
+    export_x: i32,
+    pin_x: i32,
+    close_x: i32,
+    y: i32,
+}
+
+/// Details header button layout: [MUTE] [NOTE] [EXPORT] [PIN] [X]
+fn details_buttons() -> DetailsButtons {
+    let close_x = DETAILS_WIDTH - DETAILS_CLOSE_BTN_W - 10;
+    let pin_x = close_x - DETAILS_PIN_BTN_W - 5;
+    let export_x = pin_x - DETAILS_EXPORT_BTN_W - 5;
+    let note_x = export_x - DETAILS_NOTE_BTN_W - 5;
+    let mute_x = note_x - DETAILS_MUTE_BTN_W - 5;
+    DetailsButtons { mute_x, note_x, export_x, pin_x, close_x, y: (DETAILS_HEADER_HEIGHT - BTN_HEIGHT) / 2 }
+}
+
+/// Current note in the details text ("  Note:" line below the header)
+fn note_from_details(details: &str) -> Option<String> {
+    details.lines()
+        .find_map(|l| l.strip_prefix("  Note:"))
+        .map(|n| n.trim().to_string())
+}
+
+/// Replaces the note line of the details text (empty note = remove it)
+fn set_note_in_details(details: &str, note: &str) -> String {
+    let mut output = String::with_capacity(details.len() + note.len() + 16);
+    let mut inserted = false;
+    for line in details.lines().filter(|l| !l.starts_with("  Note:")) {
+        output.push_str(line);
+        output.push('\n');
+        // Directly below the "[timestamp] == EVENT ==" header
+        if !inserted && line.starts_with('[') {
+            if !note.is_empty() {
+                output.push_str(&format!("  Note:        {}\n", note));
+            }
+            inserted = true;
+        }
     }
+    output
+}
 
-    // Window position (next to main window)
-    let main_hwnd = WINDOW_HWND.load(Ordering::SeqCst);
-    let (dx, dy) = if main_hwnd != 0 {
-        let mut rect = RECT::default();
-        let _ = GetWindowRect(HWND(main_hwnd as *mut _), &mut rect);
-        (rect.right + 10, rect.top)
-    } else {
-        (100, 100)
+/// Asks for a note and stores it with the event (database, open windows, log list)
+unsafe fn edit_note(hwnd: HWND) {
+    let Some((event_id, db_id, details)) = DETAILS_WINDOWS.lock()
+        .get(&(hwnd.0 as usize))
+        .map(|w| (w.event_id, w.db_id, w.details.clone()))
+    else {
+        return;
+    };
+    let Some(db_id) = db_id else {
+        error!("Event is not stored in the database - note not possible");
+        return;
     };
 
-    let hwnd = CreateWindowExW(
-        WS_EX_TOPMOST | WS_EX_LAYERED,
-        details_class,
-        title,
-        WS_POPUP | WS_VISIBLE,
-        dx, dy,
-        DETAILS_WIDTH,
-        DETAILS_HEIGHT,
-        None,
-        None,
-        instance,
-        None,
-    );
+    let current = note_from_details(&details).unwrap_or_default();
+    let Some(note) = crate::note_dialog::ask_note(hwnd, &current) else {
+        return;
+    };
+    crate::database::set_note(db_id, &note);
+    info!("Note for event {}: {}", db_id, if note.is_empty() { "(removed)" } else { &note });
 
-    if let Ok(hwnd) = hwnd {
-        // Rounded corners
-        let rgn = CreateRoundRectRgn(0, 0, DETAILS_WIDTH + 1, DETAILS_HEIGHT + 1, CORNER_RADIUS, CORNER_RADIUS);
-        let _ = SetWindowRgn(hwnd, rgn, true);
+    for w in DETAILS_WINDOWS.lock().values_mut().filter(|w| w.event_id == event_id) {
+        w.details = set_note_in_details(&w.details, &note);
+    }
+    if let Some(entry) = LOG_ENTRIES.lock().iter_mut().find(|e| e.id == event_id) {
+        entry.details = set_note_in_details(&entry.details, &note);
+    }
+    redraw_details_window();
+}
 
-        let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), 240, LWA_ALPHA);
-        DETAILS_HWND.store(hwnd.0 as usize, Ordering::SeqCst);
+/// Adds the Defender/VirusTotal/YARA verdicts (looked up in the background) after the path of the process
+fn annotate_details(details: &str) -> String {
+    let Some((_, path)) = extract_paths_from_details(details).into_iter().find(|(label, _)| label == "Process") else {
+        return details.to_string();
+    };
 
-        // Load and set icon from EXE resources
-        let icon = LoadImageW(
-            instance,
-            windows::core::PCWSTR(1 as *const u16),
-            IMAGE_ICON,
-            32, 32,
-            LR_DEFAULTCOLOR,
-        ).ok().map(|h| HICON(h.0));
+    let mut verdicts = Vec::new();
+    if let Some(verdict) = crate::reputation::verdict(&path) {
+        verdicts.push(format!("  Defender:    {}\n", verdict.describe()));
+    }
+    if let Some(verdict) = crate::virustotal::verdict(&path) {
+        verdicts.push(format!("  VirusTotal:  {}\n", verdict.describe()));
+    }
+    if let Some(verdict) = crate::yara_scan::verdict(&path) {
+        verdicts.push(format!("  YARA:        {}\n", verdict.describe()));
+    }
+    if let Some(verdict) = crate::module_scan::verdict(&path) {
+        verdicts.push(format!("  DLLs:        {}\n", verdict.describe()));
+    }
+    if verdicts.is_empty() {
+        return details.to_string();
+    }
 
-        if let Some(icon) = icon {
-            let _ = SendMessageW(hwnd, WM_SETICON, WPARAM(0), LPARAM(icon.0 as isize)); // ICON_SMALL
-            let _ = SendMessageW(hwnd, WM_SETICON, WPARAM(1), LPARAM(icon.0 as isize)); // ICON_BIG
+    let mut output = String::with_capacity(details.len() + 128);
+    let mut inserted = false;
+    for line in details.lines() {
+        output.push_str(line);
+        output.push('\n');
+        if !inserted && line.trim_start().starts_with("Path:") {
+            verdicts.iter().for_each(|v| output.push_str(v));
+            inserted = true;
         }
     }
+    output
+}
+
+/// Exports the alert of a details window as ZIP and shows it in Explorer
+fn export_alert(details: String) {
+    // Zipping recordings can take a moment - keep the window responsive
+    thread::spawn(move || match crate::alert_export::export_record(&details) {
+        Ok(path) => {
+            info!("Alert exported: {}", path.display());
+            open_file_location(&path.to_string_lossy());
+        }
+        Err(e) => crate::error::report("Could not export alert", e),
+    });
 }
 
 /// Draws a rounded button with text
@@ -641,7 +1760,7 @@ unsafe fn draw_screenshot(hdc: windows::Win32::Graphics::Gdi::HDC, x: i32, y: i3
                 biHeight: -(ss.height as i32),
                 biPlanes: 1,
                 biBitCount: 24,
-                biCompression: BI_RGB.0 as u32,
+                biCompression: BI_RGB.0,
                 ..Default::default()
             },
             ..Default::default()
@@ -653,7 +1772,7 @@ unsafe fn draw_screenshot(hdc: windows::Win32::Graphics::Gdi::HDC, x: i32, y: i3
 
         if let Ok(hbm) = hbm {
             if !bits.is_null() {
-                let row_size = ((ss.width * 3 + 3) / 4) * 4;
+                let row_size = (ss.width * 3).div_ceil(4) * 4;
                 let dst_ptr = bits as *mut u8;
 
                 for row in 0..ss.height {
@@ -705,6 +1824,409 @@ unsafe fn draw_screenshot(hdc: windows::Win32::Graphics::Gdi::HDC, x: i32, y: i3
     false
 }
 
+/// Paints the header (status text and buttons)
+unsafe fn paint_header(hdc: windows::Win32::Graphics::Gdi::HDC, rect: &RECT) {
+    let header_rect = RECT { left: 0, top: 0, right: rect.right, bottom: HEADER_HEIGHT };
+    let shown = shown_alert();
+    let header_color = match &shown {
+        None => COLOR_NORMAL,
+        Some((_, Some(color), _)) => *color,
+        Some((_, None, Severity::Critical)) => COLOR_ALERT,
+        Some(_) => COLOR_WARNING,
+    };
+    let brush = CreateSolidBrush(COLORREF(header_color));
+    let _ = FillRect(hdc, &header_rect, brush);
+    let _ = DeleteObject(HGDIOBJ(brush.0));
+
+    let _ = SetTextColor(hdc, COLORREF(COLOR_TEXT));
+
+    // Header text
+    let text = match shown {
+        Some((text, _, _)) => text,
+        None => ALERT_MESSAGE.lock().clone(),
+    };
+    let text_wide: Vec<u16> = text.encode_utf16().collect();
+    let _ = TextOutW(hdc, 10, 10, &text_wide);
+
+    paint_sparkline(hdc);
+
+    // Opt-in webcam capture is always visible
+    if crate::webcam::is_enabled() {
+        let cam_text: Vec<u16> = "CAM".encode_utf16().collect();
+        let _ = TextOutW(hdc, header_buttons().ack_x - 40, 10, &cam_text);
+    }
+
+    // Buttons in header: [ACK] [MUTE] [TRAY] [MINIMIZE] [PINNED/UNPIN]
+    let is_pinned = WINDOW_PINNED.load(Ordering::SeqCst);
+    let btns = header_buttons();
+
+    // Acknowledge all (highlighted while alerts are open)
+    let open_alerts = open_alert_count();
+    let ack_text = if open_alerts > 0 { format!("ACK ({})", open_alerts.min(99)) } else { "ACK".to_string() };
+    draw_button(hdc, btns.ack_x, btns.y, btns.ack_w, BTN_HEIGHT, &ack_text, open_alerts > 0);
+
+    // Mute the process of the latest alert
+    draw_button(hdc, btns.mute_x, btns.y, btns.mute_w, BTN_HEIGHT, "MUTE", false);
+
+    // Tray button
+    draw_button(hdc, btns.tray_x, btns.y, btns.tray_w, BTN_HEIGHT, "TRAY", false);
+
+    // Minimize button
+    draw_button(hdc, btns.min_x, btns.y, btns.min_w, BTN_HEIGHT, "MINIMIZE", false);
+
+    // Pin button
+    let pin_text = if is_pinned { "PINNED" } else { "UNPIN" };
+    draw_button(hdc, btns.pin_x, btns.y, btns.pin_w, BTN_HEIGHT, pin_text, is_pinned);
+}
+
+/// Paints the events-per-second history as bars (one column per two seconds, scaled to the peak)
+unsafe fn paint_sparkline(hdc: windows::Win32::Graphics::Gdi::HDC) {
+    let area = sparkline_rect();
+    let background = CreateSolidBrush(COLORREF(COLOR_SPARKLINE_BG));
+    let _ = FillRect(hdc, &area, background);
+    let _ = DeleteObject(HGDIOBJ(background.0));
+
+    let rates = EVENT_RATES.lock();
+    let peak = rates.iter().cloned().fold(1.0, f64::max);
+    let per_column = SPARKLINE_SECONDS.div_ceil(SPARKLINE_WIDTH as usize);
+    // Newest samples at the right edge
+    let columns = rates.len().div_ceil(per_column);
+    let bar = CreateSolidBrush(COLORREF(COLOR_SPARKLINE));
+    for column in 0..columns {
+        let start = rates.len().saturating_sub((columns - column) * per_column);
+        let end = rates.len() - (columns - column - 1) * per_column;
+        let value = rates.range(start..end).cloned().fold(0.0, f64::max);
+        let height = ((value / peak) * (SPARKLINE_HEIGHT - 2) as f64).round() as i32;
+        if height > 0 {
+            let x = area.right - (columns - column) as i32;
+            let column_rect = RECT { left: x, top: area.bottom - 1 - height, right: x + 1, bottom: area.bottom - 1 };
+            let _ = FillRect(hdc, &column_rect, bar);
+        }
+    }
+    let _ = DeleteObject(HGDIOBJ(bar.0));
+}
+
+/// Paints the log area with legend and entries
+unsafe fn paint_log_area(hdc: windows::Win32::Graphics::Gdi::HDC, rect: &RECT) {
+    let log_rect = RECT { left: 0, top: HEADER_HEIGHT, right: LOG_AREA_WIDTH, bottom: rect.bottom };
+    let log_brush = CreateSolidBrush(COLORREF(COLOR_LOG_BG));
+    let _ = FillRect(hdc, &log_rect, log_brush);
+    let _ = DeleteObject(HGDIOBJ(log_brush.0));
+
+    // Legend with full names
+    draw_legend(hdc, 5, HEADER_HEIGHT + 5);
+
+    // Active process filter (right of the legend)
+    if let Some(ref filter) = *LOG_FILTER.lock() {
+        let _ = SetTextColor(hdc, COLORREF(COLOR_TEXT));
+        let filter_text: Vec<u16> = filter.label().encode_utf16().collect();
+        let _ = TextOutW(hdc, LOG_FILTER_TEXT_X, HEADER_HEIGHT + 3, &filter_text);
+    }
+
+    // Log entries with icons
+    let entries = LOG_ENTRIES.lock();
+    let mut y = LOG_FIRST_ROW_Y;
+    for entry in entries.iter() {
+        let color = match entry.event_type.as_str() {
+            "FOCUS" => COLOR_FOCUS,
+            "CREATED" => COLOR_CREATED,
+            "SHOWN" => COLOR_SHOWN,
+            "MINIMIZED" => COLOR_MINIMIZED,
+            "RESTORED" => COLOR_RESTORED,
+            "Z-ORDER" => COLOR_ZORDER,
+            "MIC" | "CAMERA" => COLOR_DEVICE,
+            _ => COLOR_TEXT,
+        };
+        let _ = SetTextColor(hdc, COLORREF(color));
+
+        // Unacknowledged alert: colored bar left of the icon
+        if !entry.acknowledged {
+            let marker_color = if entry.severity == Severity::Critical { COLOR_ALERT } else { COLOR_WARNING };
+            let marker = RECT { left: 1, top: y, right: 4, bottom: y + ICON_SIZE };
+            let marker_brush = CreateSolidBrush(COLORREF(marker_color));
+            let _ = FillRect(hdc, &marker, marker_brush);
+            let _ = DeleteObject(HGDIOBJ(marker_brush.0));
+        }
+
+        // Owned popups are indented below their application
+        let (icon_x, indent_chars) = if entry.owned { (5 + LOG_OWNED_INDENT, 2) } else { (5, 0) };
+
+        // Draw icon (only from cache, placeholder until extracted)
+        if let Some(icon) = get_cached_icon(&entry.process_path, IconSize::Small) {
+            let _ = DrawIconEx(hdc, icon_x, y, icon, ICON_SIZE, ICON_SIZE, 0, None, DI_FLAGS(DI_NORMAL));
+        } else {
+            draw_icon_placeholder(hdc, icon_x, y, ICON_SIZE);
+        }
+        let text_x = icon_x + ICON_SIZE + 4; // After icon: 4px spacing
+
+        // Tag chips at the right end of the row, the text gets shorter
+        let chips_width = draw_tag_chips(hdc, LOG_AREA_WIDTH - 4, y, &entry.tags);
+        let _ = SetTextColor(hdc, COLORREF(color));
+
+        let chip_chars = (chips_width as usize).div_ceil(9);
+        let max_chars = 54 - indent_chars - chip_chars; // Slightly less due to icon
+        let display = if entry.text.len() > max_chars {
+            format!("{}...", &entry.text[..max_chars - 3])
+        } else {
+            entry.text.clone()
+        };
+        let entry_wide: Vec<u16> = display.encode_utf16().collect();
+        let _ = TextOutW(hdc, text_x, y, &entry_wide);
+        y += LOG_ROW_HEIGHT;
+    }
+}
+
+/// Draws tag chips right-aligned to `right`, returns their total width (0 = no tags)
+unsafe fn draw_tag_chips(hdc: windows::Win32::Graphics::Gdi::HDC, right: i32, y: i32, tags: &[(String, u32)]) -> i32 {
+    let mut x = right;
+    for (tag, color) in tags.iter().rev() {
+        let text: Vec<u16> = tag.encode_utf16().collect();
+        let mut size = SIZE::default();
+        let _ = GetTextExtentPoint32W(hdc, &text, &mut size);
+        let left = x - size.cx - 8;
+        // Chips that do not fit are left out
+        if left < right - LOG_AREA_WIDTH / 2 {
+            break;
+        }
+
+        let brush = CreateSolidBrush(COLORREF(*color));
+        let pen = CreatePen(PS_SOLID, 1, COLORREF(*color));
+        let old_brush = SelectObject(hdc, brush);
+        let old_pen = SelectObject(hdc, pen);
+        let _ = RoundRect(hdc, left, y + 1, x, y + LOG_ROW_HEIGHT - 2, 8, 8);
+        SelectObject(hdc, old_brush);
+        SelectObject(hdc, old_pen);
+        let _ = DeleteObject(HGDIOBJ(brush.0));
+        let _ = DeleteObject(HGDIOBJ(pen.0));
+
+        let _ = SetTextColor(hdc, COLORREF(0x00FFFFFF));
+        let _ = TextOutW(hdc, left + 4, y, &text);
+        x = left - 3;
+    }
+    right - x
+}
+
+/// Paints the screenshot preview and hints below it
+unsafe fn paint_screenshot_area(hdc: windows::Win32::Graphics::Gdi::HDC, rect: &RECT) {
+    let ss_x = LOG_AREA_WIDTH + 10;
+    let ss_y = HEADER_HEIGHT + 5;
+
+    // Frame
+    let ss_frame = RECT {
+        left: ss_x - 2, top: ss_y - 2,
+        right: ss_x + SCREENSHOT_WIDTH + 2, bottom: ss_y + SCREENSHOT_HEIGHT + 2,
+    };
+    let frame_brush = CreateSolidBrush(COLORREF(0x00444444));
+    let _ = FillRect(hdc, &ss_frame, frame_brush);
+    let _ = DeleteObject(HGDIOBJ(frame_brush.0));
+
+    // Fill area below screenshot (first, then draw over)
+    let area = screenshot_area_rect();
+    let bottom_rect = RECT {
+        left: LOG_AREA_WIDTH, top: HEADER_HEIGHT,
+        right: rect.right, bottom: area.bottom,
+    };
+    let bottom_brush = CreateSolidBrush(COLORREF(COLOR_LOG_BG));
+    let _ = FillRect(hdc, &bottom_rect, bottom_brush);
+    let _ = DeleteObject(HGDIOBJ(bottom_brush.0));
+
+    // Draw screenshot
+    let has_screenshot = draw_screenshot(hdc, ss_x, ss_y, SCREENSHOT_WIDTH, SCREENSHOT_HEIGHT);
+
+    // Text below screenshot
+    let _ = SetTextColor(hdc, COLORREF(0x00888888));
+
+    // If screenshot visible: "(Hide)" link + "Click: Open folder"
+    let is_hidden = SCREENSHOT_HIDDEN.load(Ordering::SeqCst);
+    if has_screenshot && !is_hidden {
+        let hide_text: Vec<u16> = "(Hide)".encode_utf16().collect();
+        let _ = TextOutW(hdc, ss_x + 75, ss_y + SCREENSHOT_HEIGHT + 8, &hide_text);
+
+        let click_text: Vec<u16> = "Click: Folder".encode_utf16().collect();
+        let _ = TextOutW(hdc, ss_x + 55, ss_y + SCREENSHOT_HEIGHT + 26, &click_text);
+    }
+
+    // General info
+    let info1: Vec<u16> = "Double-click: Details".encode_utf16().collect();
+    let _ = TextOutW(hdc, ss_x, ss_y + SCREENSHOT_HEIGHT + 50, &info1);
+    let info2: Vec<u16> = "Right-click: Menu / Log".encode_utf16().collect();
+    let _ = TextOutW(hdc, ss_x, ss_y + SCREENSHOT_HEIGHT + 68, &info2);
+}
+
+/// Paints the pipeline status (events/sec, queue fill level, dropped events)
+unsafe fn paint_status_strip(hdc: windows::Win32::Graphics::Gdi::HDC) {
+    let strip = status_strip_rect();
+    let brush = CreateSolidBrush(COLORREF(COLOR_LOG_BG));
+    let _ = FillRect(hdc, &strip, brush);
+    let _ = DeleteObject(HGDIOBJ(brush.0));
+
+    let status = *PIPELINE_STATUS.lock();
+    let overloaded = status.queue_percent >= crate::metrics::QUEUE_WARN_PERCENT
+        || status.new_drops > 0
+        || status.new_errors > 0;
+    let color = if overloaded { COLOR_WARNING } else { 0x00888888 };
+    let _ = SetTextColor(hdc, COLORREF(color));
+
+    let text_wide: Vec<u16> = status_strip_text().encode_utf16().collect();
+    let _ = TextOutW(hdc, 5, strip.top + 1, &text_wide);
+}
+
+/// Text of the pipeline status strip (painting, screen readers)
+fn status_strip_text() -> String {
+    let status = *PIPELINE_STATUS.lock();
+    let memory = *MEMORY_USAGE.lock();
+    format!(
+        "{:.1} events/s | Queue {}% | Dropped {} | Filtered {} | Errors {} | Mem {:.0} MB",
+        status.events_per_sec,
+        status.queue_percent,
+        status.dropped,
+        status.filtered,
+        status.errors,
+        crate::memory::megabytes(memory.working_set)
+    )
+}
+
+/// Paints the Health section (non-fatal errors of the health stream, click: list)
+unsafe fn paint_health(hdc: windows::Win32::Graphics::Gdi::HDC) {
+    let area = health_rect();
+    let brush = CreateSolidBrush(COLORREF(COLOR_LOG_BG));
+    let _ = FillRect(hdc, &area, brush);
+    let _ = DeleteObject(HGDIOBJ(brush.0));
+
+    let healthy = crate::error::recent().is_empty();
+    let _ = SetTextColor(hdc, COLORREF(if healthy { 0x00888888 } else { COLOR_WARNING }));
+    let text_wide: Vec<u16> = health_text().encode_utf16().collect();
+    let _ = TextOutW(hdc, LOG_AREA_WIDTH + 10, area.top + 1, &text_wide);
+}
+
+/// Text of the Health section: "Health: OK" or "Health: 3 errors (GDI)"
+fn health_text() -> String {
+    let events = crate::error::recent();
+    match events.first() {
+        None => "Health: OK".to_string(),
+        Some(latest) => {
+            let count: u32 = events.iter().map(|e| e.count).sum();
+            format!("Health: {} error{} ({})", count, if count == 1 { "" } else { "s" }, latest.subsystem)
+        }
+    }
+}
+
+/// Menu of the Health section: latest errors, copy all, clear
+unsafe fn show_health_menu(hwnd: HWND) {
+    let Ok(menu) = CreatePopupMenu() else {
+        return;
+    };
+    let events = crate::error::recent();
+    if events.is_empty() {
+        let _ = AppendMenuW(menu, MF_STRING | MF_GRAYED, 0, w!("No errors since start"));
+    }
+    for event in events.iter().take(HEALTH_MENU_ENTRIES) {
+        let repeated = if event.count > 1 { format!(" ({}x)", event.count) } else { String::new() };
+        let label = format!("{} [{}] {}{}", event.time.format("%H:%M:%S"), event.subsystem, event.message, repeated);
+        let label: String = label.chars().take(100).collect();
+        let label_wide: Vec<u16> = label.encode_utf16().chain(std::iter::once(0)).collect();
+        let _ = AppendMenuW(menu, MF_STRING | MF_GRAYED, 0, PCWSTR(label_wide.as_ptr()));
+    }
+    let _ = AppendMenuW(menu, MF_SEPARATOR, 0, None);
+    let flags = if events.is_empty() { MF_STRING | MF_GRAYED } else { MF_STRING };
+    let _ = AppendMenuW(menu, flags, ID_MENU_HEALTH_COPY as usize, w!("Copy errors"));
+    let _ = AppendMenuW(menu, flags, ID_MENU_HEALTH_CLEAR as usize, w!("Clear"));
+
+    let mut pt = POINT::default();
+    let _ = GetCursorPos(&mut pt);
+    let cmd = TrackPopupMenu(
+        menu,
+        TPM_RETURNCMD | TPM_RIGHTBUTTON | TPM_LEFTALIGN | TPM_TOPALIGN,
+        pt.x,
+        pt.y,
+        0,
+        hwnd,
+        None,
+    ).0 as u32;
+    let _ = DestroyMenu(menu);
+
+    match cmd {
+        ID_MENU_HEALTH_COPY => {
+            let text: Vec<String> = events
+                .iter()
+                .map(|e| {
+                    let time = e.time.format("%Y-%m-%d %H:%M:%S");
+                    format!("{} [{}] {} ({}x)", time, e.subsystem, e.message, e.count)
+                })
+                .collect();
+            if let Err(e) = copy_to_clipboard(hwnd, &text.join("\r\n")) {
+                error!("Could not copy errors: {}", e);
+            }
+        }
+        ID_MENU_HEALTH_CLEAR => {
+            crate::error::clear();
+            invalidate_region(&health_rect());
+        }
+        _ => {}
+    }
+}
+
+/// Paints the live status of watched processes
+unsafe fn paint_watch_panel(hdc: windows::Win32::Graphics::Gdi::HDC, rect: &RECT) {
+    let panel = RECT { right: rect.right, ..watch_panel_rect() };
+    let brush = CreateSolidBrush(COLORREF(COLOR_LOG_BG));
+    let _ = FillRect(hdc, &panel, brush);
+    let _ = DeleteObject(HGDIOBJ(brush.0));
+
+    let watched = crate::watch::status();
+    if watched.is_empty() {
+        return;
+    }
+
+    let x = LOG_AREA_WIDTH + 10;
+    let mut y = panel.top;
+
+    let _ = SetTextColor(hdc, COLORREF(0x00888888));
+    let title: Vec<u16> = "Watched:".encode_utf16().collect();
+    let _ = TextOutW(hdc, x, y, &title);
+    y += WATCH_ROW_HEIGHT;
+
+    for status in watched.iter().take(WATCH_PANEL_MAX_ROWS) {
+        let color = if status.running { COLOR_SHOWN } else { COLOR_MINIMIZED };
+
+        // Status dot
+        let dot_rect = RECT { left: x, top: y + 4, right: x + 8, bottom: y + 12 };
+        let dot_brush = CreateSolidBrush(COLORREF(color));
+        let _ = FillRect(hdc, &dot_rect, dot_brush);
+        let _ = DeleteObject(HGDIOBJ(dot_brush.0));
+
+        let name = if status.name.chars().count() > 12 {
+            format!("{}...", status.name.chars().take(10).collect::<String>())
+        } else {
+            status.name.clone()
+        };
+        let state = if status.running { "running" } else { "exited" };
+        let count = format!("{}x", status.focus_count_today);
+
+        let _ = SetTextColor(hdc, COLORREF(COLOR_TEXT));
+        let name_wide: Vec<u16> = name.encode_utf16().collect();
+        let _ = TextOutW(hdc, x + 12, y, &name_wide);
+
+        let _ = SetTextColor(hdc, COLORREF(color));
+        let state_wide: Vec<u16> = state.encode_utf16().collect();
+        let _ = TextOutW(hdc, x + 110, y, &state_wide);
+
+        let _ = SetTextColor(hdc, COLORREF(COLOR_FOCUS));
+        let count_wide: Vec<u16> = count.encode_utf16().collect();
+        let _ = TextOutW(hdc, x + 165, y, &count_wide);
+
+        y += WATCH_ROW_HEIGHT;
+    }
+
+    if watched.len() > WATCH_PANEL_MAX_ROWS {
+        let _ = SetTextColor(hdc, COLORREF(0x00888888));
+        let more: Vec<u16> = format!("+{} more", watched.len() - WATCH_PANEL_MAX_ROWS)
+            .encode_utf16()
+            .collect();
+        let _ = TextOutW(hdc, x + 110, panel.top, &more);
+    }
+}
+
 /// Window Procedure for main window
 unsafe extern "system" fn window_proc(
     hwnd: HWND,
@@ -720,129 +2242,40 @@ unsafe extern "system" fn window_proc(
             let mut rect = RECT::default();
             let _ = GetClientRect(hwnd, &mut rect);
 
-            // === HEADER ===
-            let header_rect = RECT { left: 0, top: 0, right: rect.right, bottom: HEADER_HEIGHT };
-            let header_color = if ALERT_ACTIVE.load(Ordering::SeqCst) { COLOR_ALERT } else { COLOR_NORMAL };
-            let brush = CreateSolidBrush(COLORREF(header_color));
-            let _ = FillRect(hdc, &header_rect, brush);
-            let _ = DeleteObject(HGDIOBJ(brush.0));
-
             let _ = SetBkMode(hdc, TRANSPARENT);
-            let _ = SetTextColor(hdc, COLORREF(COLOR_TEXT));
-
-            // Header text
-            let text = ALERT_MESSAGE.lock().clone();
-            let text_wide: Vec<u16> = text.encode_utf16().collect();
-            let _ = TextOutW(hdc, 10, 10, &text_wide);
 
-            // Buttons in header: [TRAY] [MINIMIZE] [PINNED/UNPIN]
-            let is_pinned = WINDOW_PINNED.load(Ordering::SeqCst);
-            let pin_btn_w = if is_pinned { 70 } else { 60 };
-            let min_btn_w = 80;
-            let tray_btn_w = 50;
-            let right_margin = 10;
-            let pin_btn_x = rect.right - pin_btn_w - right_margin;
-            let min_btn_x = pin_btn_x - min_btn_w - 5;
-            let tray_btn_x = min_btn_x - tray_btn_w - 5;
-            let btn_y = (HEADER_HEIGHT - BTN_HEIGHT) / 2;
+            // Only repaint the sections inside the invalidated region
+            let dirty = ps.rcPaint;
 
-            // Tray button
-            draw_button(hdc, tray_btn_x, btn_y, tray_btn_w, BTN_HEIGHT, "TRAY", false);
-
-            // Minimize button
-            draw_button(hdc, min_btn_x, btn_y, min_btn_w, BTN_HEIGHT, "MINIMIZE", false);
-
-            // Pin button
-            let pin_text = if is_pinned { "PINNED" } else { "UNPIN" };
-            draw_button(hdc, pin_btn_x, btn_y, pin_btn_w, BTN_HEIGHT, pin_text, is_pinned);
+            // === HEADER ===
+            if rects_intersect(&dirty, &header_rect()) {
+                paint_header(hdc, &rect);
+            }
 
             // === LOG AREA (left) ===
-            let log_rect = RECT { left: 0, top: HEADER_HEIGHT, right: LOG_AREA_WIDTH, bottom: rect.bottom };
-            let log_brush = CreateSolidBrush(COLORREF(COLOR_LOG_BG));
-            let _ = FillRect(hdc, &log_rect, log_brush);
-            let _ = DeleteObject(HGDIOBJ(log_brush.0));
-
-            // Legend with full names
-            draw_legend(hdc, 5, HEADER_HEIGHT + 5);
-
-            // Log entries with icons
-            let entries = LOG_ENTRIES.lock();
-            let mut y = HEADER_HEIGHT + 22;
-            for entry in entries.iter() {
-                let color = match entry.event_type.as_str() {
-                    "FOCUS" => COLOR_FOCUS,
-                    "CREATED" => COLOR_CREATED,
-                    "SHOWN" => COLOR_SHOWN,
-                    "MINIMIZED" => COLOR_MINIMIZED,
-                    "RESTORED" => COLOR_RESTORED,
-                    "Z-ORDER" => COLOR_ZORDER,
-                    _ => COLOR_TEXT,
-                };
-                let _ = SetTextColor(hdc, COLORREF(color));
-
-                // Draw icon (if available)
-                let text_x = if let Some(icon) = get_cached_icon(&entry.process_path) {
-                    let _ = DrawIconEx(hdc, 5, y, icon, ICON_SIZE, ICON_SIZE, 0, None, DI_FLAGS(DI_NORMAL));
-                    5 + ICON_SIZE + 4 // After icon: 4px spacing
-                } else {
-                    5 + ICON_SIZE + 4 // Same spacing without icon for alignment
-                };
-
-                let max_chars = 54; // Slightly less due to icon
-                let display = if entry.text.len() > max_chars {
-                    format!("{}...", &entry.text[..max_chars - 3])
-                } else {
-                    entry.text.clone()
-                };
-                let entry_wide: Vec<u16> = display.encode_utf16().collect();
-                let _ = TextOutW(hdc, text_x, y, &entry_wide);
-                y += 18;
+            if rects_intersect(&dirty, &log_area_rect()) {
+                paint_log_area(hdc, &rect);
             }
-            drop(entries);
 
             // === SCREENSHOT AREA (right) ===
-            let ss_x = LOG_AREA_WIDTH + 10;
-            let ss_y = HEADER_HEIGHT + 5;
-
-            // Frame
-            let ss_frame = RECT {
-                left: ss_x - 2, top: ss_y - 2,
-                right: ss_x + SCREENSHOT_WIDTH + 2, bottom: ss_y + SCREENSHOT_HEIGHT + 2,
-            };
-            let frame_brush = CreateSolidBrush(COLORREF(0x00444444));
-            let _ = FillRect(hdc, &ss_frame, frame_brush);
-            let _ = DeleteObject(HGDIOBJ(frame_brush.0));
-
-            // Fill area below screenshot (first, then draw over)
-            let bottom_rect = RECT {
-                left: LOG_AREA_WIDTH, top: HEADER_HEIGHT,
-                right: rect.right, bottom: rect.bottom,
-            };
-            let bottom_brush = CreateSolidBrush(COLORREF(COLOR_LOG_BG));
-            let _ = FillRect(hdc, &bottom_rect, bottom_brush);
-            let _ = DeleteObject(HGDIOBJ(bottom_brush.0));
-
-            // Draw screenshot
-            let has_screenshot = draw_screenshot(hdc, ss_x, ss_y, SCREENSHOT_WIDTH, SCREENSHOT_HEIGHT);
-
-            // Text below screenshot
-            let _ = SetTextColor(hdc, COLORREF(0x00888888));
+            if rects_intersect(&dirty, &screenshot_area_rect()) {
+                paint_screenshot_area(hdc, &rect);
+            }
 
-            // If screenshot visible: "(Hide)" link + "Click: Open folder"
-            let is_hidden = SCREENSHOT_HIDDEN.load(Ordering::SeqCst);
-            if has_screenshot && !is_hidden {
-                let hide_text: Vec<u16> = "(Hide)".encode_utf16().collect();
-                let _ = TextOutW(hdc, ss_x + 75, ss_y + SCREENSHOT_HEIGHT + 8, &hide_text);
+            // === STATUS STRIP (bottom left, over the log area) ===
+            if rects_intersect(&dirty, &status_strip_rect()) {
+                paint_status_strip(hdc);
+            }
 
-                let click_text: Vec<u16> = "Click: Folder".encode_utf16().collect();
-                let _ = TextOutW(hdc, ss_x + 55, ss_y + SCREENSHOT_HEIGHT + 26, &click_text);
+            // === WATCH PANEL (bottom right) ===
+            if rects_intersect(&dirty, &watch_panel_rect()) {
+                paint_watch_panel(hdc, &rect);
             }
 
-            // General info
-            let info1: Vec<u16> = "Double-click: Details".encode_utf16().collect();
-            let _ = TextOutW(hdc, ss_x, ss_y + SCREENSHOT_HEIGHT + 50, &info1);
-            let info2: Vec<u16> = "Right-click: Log".encode_utf16().collect();
-            let _ = TextOutW(hdc, ss_x, ss_y + SCREENSHOT_HEIGHT + 68, &info2);
+            // === HEALTH (bottom right, below the watch panel) ===
+            if rects_intersect(&dirty, &health_rect()) {
+                paint_health(hdc);
+            }
 
             let _ = EndPaint(hwnd, &ps);
             LRESULT(0)
@@ -853,15 +2286,11 @@ unsafe extern "system" fn window_proc(
             let y = ((lparam.0 >> 16) & 0xFFFF) as i16 as i32;
 
             // Calculate button positions (as in WM_PAINT)
-            let is_pinned = WINDOW_PINNED.load(Ordering::SeqCst);
-            let pin_btn_w = if is_pinned { 70 } else { 60 };
-            let min_btn_w = 80;
-            let tray_btn_w = 50;
-            let right_margin = 10;
-            let pin_btn_x = WINDOW_WIDTH - pin_btn_w - right_margin;
-            let min_btn_x = pin_btn_x - min_btn_w - 5;
-            let tray_btn_x = min_btn_x - tray_btn_w - 5;
-            let btn_y = (HEADER_HEIGHT - BTN_HEIGHT) / 2;
+            let btns = header_buttons();
+            let (pin_btn_x, pin_btn_w) = (btns.pin_x, btns.pin_w);
+            let (min_btn_x, min_btn_w) = (btns.min_x, btns.min_w);
+            let (tray_btn_x, tray_btn_w) = (btns.tray_x, btns.tray_w);
+            let btn_y = btns.y;
 
             // Screenshot area positions
             let ss_x = LOG_AREA_WIDTH + 10;
@@ -869,20 +2298,26 @@ unsafe extern "system" fn window_proc(
 
             // "(Hide)" link below screenshot clicked?
             let hide_link_y = ss_y + SCREENSHOT_HEIGHT + 8;
-            if x >= ss_x + 60 && x <= ss_x + 160 && y >= hide_link_y && y <= hide_link_y + 16 {
-                if !SCREENSHOT_HIDDEN.load(Ordering::SeqCst) {
-                    SCREENSHOT_HIDDEN.store(true, Ordering::SeqCst);
-                    let _ = InvalidateRect(hwnd, None, true);
-                    return LRESULT(0);
-                }
+            if x >= ss_x + 60
+                && x <= ss_x + 160
+                && y >= hide_link_y
+                && y <= hide_link_y + 16
+                && !SCREENSHOT_HIDDEN.load(Ordering::SeqCst)
+            {
+                SCREENSHOT_HIDDEN.store(true, Ordering::SeqCst);
+                invalidate_region(&screenshot_area_rect());
+                return LRESULT(0);
             }
 
-            // Screenshot image clicked? -> Open folder
+            // Screenshot image clicked? -> Open folder (Ctrl: image)
             if x >= ss_x && x <= ss_x + SCREENSHOT_WIDTH && y >= ss_y && y <= ss_y + SCREENSHOT_HEIGHT {
                 if SCREENSHOT_HIDDEN.load(Ordering::SeqCst) {
                     // Hidden -> show again
                     SCREENSHOT_HIDDEN.store(false, Ordering::SeqCst);
-                    let _ = InvalidateRect(hwnd, None, true);
+                    invalidate_region(&screenshot_area_rect());
+                } else if wparam.0 & 0x0008 != 0 {
+                    // Visible + Ctrl (MK_CONTROL) -> open image
+                    open_screenshot_image();
                 } else {
                     // Visible -> open folder
                     open_screenshot_folder();
@@ -890,6 +2325,13 @@ unsafe extern "system" fn window_proc(
                 return LRESULT(0);
             }
 
+            // Health section: list of the latest errors
+            let health = health_rect();
+            if x >= health.left && x < health.right && y >= health.top && y < health.bottom {
+                show_health_menu(hwnd);
+                return LRESULT(0);
+            }
+
             // Minimize button? (normal taskbar minimization)
             if x >= min_btn_x && x <= min_btn_x + min_btn_w && y >= btn_y && y <= btn_y + BTN_HEIGHT {
                 WINDOW_MINIMIZED.store(true, Ordering::SeqCst);
@@ -905,6 +2347,23 @@ unsafe extern "system" fn window_proc(
                 return LRESULT(0);
             }
 
+            // Acknowledge all alerts
+            if x >= btns.ack_x && x <= btns.ack_x + btns.ack_w && y >= btn_y && y <= btn_y + BTN_HEIGHT {
+                acknowledge_all();
+                return LRESULT(0);
+            }
+
+            // Mute the process of the latest alert
+            if x >= btns.mute_x && x <= btns.mute_x + btns.mute_w && y >= btn_y && y <= btn_y + BTN_HEIGHT {
+                let latest = LOG_ENTRIES.lock()
+                    .iter()
+                    .rev()
+                    .find(|e| e.severity >= Severity::Warning)
+                    .map(|e| e.process_name.clone());
+                show_mute_menu(hwnd, latest.as_deref());
+                return LRESULT(0);
+            }
+
             // Tray button? (minimize to tray - hide window)
             if x >= tray_btn_x && x <= tray_btn_x + tray_btn_w && y >= btn_y && y <= btn_y + BTN_HEIGHT {
                 let _ = ShowWindow(hwnd, SW_HIDE);
@@ -917,11 +2376,12 @@ unsafe extern "system" fn window_proc(
                 WINDOW_PINNED.store(!was_pinned, Ordering::SeqCst);
                 let z_order = if !was_pinned { HWND_TOPMOST } else { HWND_NOTOPMOST };
                 let _ = SetWindowPos(hwnd, z_order, 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE);
-                let _ = InvalidateRect(hwnd, None, true);
+                invalidate_region(&header_rect());
                 return LRESULT(0);
             }
 
-            // Start dragging
+            // Start dragging (a docked window is released from its edge)
+            crate::dock::detach(hwnd);
             DRAGGING.store(true, Ordering::SeqCst);
             DRAG_START_X.store(x, Ordering::SeqCst);
             DRAG_START_Y.store(y, Ordering::SeqCst);
@@ -933,13 +2393,24 @@ unsafe extern "system" fn window_proc(
             if DRAGGING.load(Ordering::SeqCst) {
                 let mut cursor_pos = POINT::default();
                 let _ = GetCursorPos(&mut cursor_pos);
-                let new_x = cursor_pos.x - DRAG_START_X.load(Ordering::SeqCst);
-                let new_y = cursor_pos.y - DRAG_START_Y.load(Ordering::SeqCst);
-                let _ = SetWindowPos(hwnd, HWND_TOPMOST, new_x, new_y, WINDOW_WIDTH, WINDOW_HEIGHT, SWP_NOACTIVATE | SWP_NOZORDER);
+                let (new_x, new_y) = crate::dock::snap(
+                    cursor_pos.x - DRAG_START_X.load(Ordering::SeqCst),
+                    cursor_pos.y - DRAG_START_Y.load(Ordering::SeqCst),
+                    WINDOW_WIDTH,
+                    window_height(),
+                );
+                let _ = SetWindowPos(hwnd, HWND_TOPMOST, new_x, new_y, WINDOW_WIDTH, window_height(), SWP_NOACTIVATE | SWP_NOZORDER);
+            } else {
+                crate::dock::on_mouse_move(hwnd);
             }
             LRESULT(0)
         }
 
+        WM_MOUSELEAVE => {
+            crate::dock::on_mouse_leave(hwnd);
+            LRESULT(0)
+        }
+
         WM_LBUTTONUP => {
             if DRAGGING.load(Ordering::SeqCst) {
                 DRAGGING.store(false, Ordering::SeqCst);
@@ -947,25 +2418,51 @@ unsafe extern "system" fn window_proc(
                 let mut rect = RECT::default();
                 let _ = GetWindowRect(hwnd, &mut rect);
                 save_position(rect.left, rect.top);
+                crate::dock::attach(hwnd);
             }
             LRESULT(0)
         }
 
         WM_RBUTTONUP => {
-            open_log_file();
+            let x = (lparam.0 & 0xFFFF) as i16 as i32;
+            let y = ((lparam.0 >> 16) & 0xFFFF) as i16 as i32;
+
+            // Right-click on the screenshot preview: image menu
+            let (ss_x, ss_y) = (LOG_AREA_WIDTH + 10, HEADER_HEIGHT + 5);
+            if x >= ss_x && x <= ss_x + SCREENSHOT_WIDTH && y >= ss_y && y <= ss_y + SCREENSHOT_HEIGHT
+                && CURRENT_SCREENSHOT_FOLDER.lock().is_some()
+            {
+                show_screenshot_context_menu(hwnd);
+                return LRESULT(0);
+            }
+
+            // Right-click on a log row: context menu, elsewhere: open log
+            let entry = if x < LOG_AREA_WIDTH {
+                log_row_at(y).and_then(|index| LOG_ENTRIES.lock().get(index).cloned())
+            } else {
+                None
+            };
+            match entry {
+                Some(entry) => show_row_context_menu(hwnd, &entry),
+                None => open_log_file(),
+            }
             LRESULT(0)
         }
 
         WM_LBUTTONDBLCLK => {
+            let x = (lparam.0 & 0xFFFF) as i16 as i32;
             let y = ((lparam.0 >> 16) & 0xFFFF) as i16 as i32;
 
-            if y > HEADER_HEIGHT + 22 {
-                let entry_index = ((y - HEADER_HEIGHT - 22) / 18) as usize;
-                let entries = LOG_ENTRIES.lock();
-                if entry_index < entries.len() {
-                    let details = entries[entry_index].details.clone();
-                    drop(entries);
-                    show_details_window(details);
+            // Header text (left of the buttons): collapse/expand
+            if y < HEADER_HEIGHT && x < header_buttons().ack_x {
+                set_collapsed(hwnd, !COLLAPSED.load(Ordering::SeqCst));
+                return LRESULT(0);
+            }
+
+            if let Some(entry_index) = log_row_at(y) {
+                let entry = LOG_ENTRIES.lock().get(entry_index).cloned();
+                if let Some(entry) = entry {
+                    show_details_window(&entry);
                 }
             }
             LRESULT(0)
@@ -987,15 +2484,78 @@ unsafe extern "system" fn window_proc(
         }
 
         WM_TIMER => {
-            // Timer 1: Check and restore TOPMOST status
+            // Timer 1: Check and restore TOPMOST status (not over full-screen apps while snoozed)
             if wparam.0 == 1 && WINDOW_PINNED.load(Ordering::SeqCst) && !WINDOW_MINIMIZED.load(Ordering::SeqCst) {
-                let _ = SetWindowPos(hwnd, HWND_TOPMOST, 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE);
+                let snoozed = crate::snooze::is_snoozed();
+                if snoozed && !SNOOZE_APPLIED.swap(true, Ordering::SeqCst) {
+                    let _ = SetWindowPos(hwnd, HWND_NOTOPMOST, 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE);
+                } else if !snoozed {
+                    SNOOZE_APPLIED.store(false, Ordering::SeqCst);
+                    let _ = SetWindowPos(hwnd, HWND_TOPMOST, 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE);
+                }
+            }
+            // Timer 2: Pipeline status and alert auto-clear
+            if wparam.0 == STATUS_TIMER_ID {
+                update_active_alerts();
+                let status = crate::metrics::sample();
+                {
+                    let mut rates = EVENT_RATES.lock();
+                    if rates.len() >= SPARKLINE_SECONDS {
+                        rates.pop_front();
+                    }
+                    rates.push_back(status.events_per_sec);
+                }
+                *PIPELINE_STATUS.lock() = status;
+                invalidate_region(&sparkline_rect());
+                *MEMORY_USAGE.lock() = crate::memory::usage();
+                invalidate_region(&status_strip_rect());
+                if status.new_errors > 0 {
+                    invalidate_region(&health_rect());
+                }
+                crate::badge::update(hwnd, open_alert_count(), WINDOW_MINIMIZED.load(Ordering::SeqCst));
+            }
+            LRESULT(0)
+        }
+
+        WM_HOTKEY => {
+            if wparam.0 as i32 == crate::transparency::HOTKEY_CLICK_THROUGH {
+                crate::transparency::toggle_click_through(hwnd);
+            }
+            LRESULT(0)
+        }
+
+        WM_EXPAND => {
+            set_collapsed(hwnd, false);
+            LRESULT(0)
+        }
+
+        WM_DISPLAYCHANGE => {
+            // Monitor setup changed (docked/undocked): position saved for the new setup
+            crate::dock::detach(hwnd);
+            if let Some((x, y)) = load_position() {
+                info!("Monitor setup changed, window position restored: ({}, {})", x, y);
+                let _ = SetWindowPos(hwnd, HWND_TOPMOST, x, y, 0, 0, SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE);
             }
+            crate::dock::attach(hwnd);
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+
+        WM_TIMECHANGE => {
+            // Broadcast to all top-level windows when the system clock is set
+            crate::clock::on_time_change();
             LRESULT(0)
         }
 
+        WM_GETOBJECT => match crate::accessibility::on_get_object(hwnd, wparam, lparam) {
+            Some(result) => result,
+            None => DefWindowProcW(hwnd, msg, wparam, lparam),
+        },
+
         WM_DESTROY => {
             let _ = KillTimer(hwnd, 1);
+            let _ = KillTimer(hwnd, STATUS_TIMER_ID);
+            crate::accessibility::disconnect(hwnd);
+            close_all_details_windows();
             PostQuitMessage(0);
             LRESULT(0)
         }
@@ -1019,10 +2579,12 @@ unsafe fn draw_detail_row(hdc: windows::Win32::Graphics::Gdi::HDC, y: i32, label
     let _ = TextOutW(hdc, 15, y, &label_wide);
 
     let _ = SetTextColor(hdc, COLORREF(value_color));
-    // Truncate value if too long
+    // Truncate value if too long (paths in the middle, so the file name stays visible)
     let max_len = 60;
-    let display_val = if value.len() > max_len {
-        format!("{}...", &value[..max_len])
+    let display_val = if value.contains('\\') && value.chars().count() > max_len {
+        crate::long_path::shorten(value, max_len)
+    } else if value.len() > max_len {
+        format!("{}...", value.chars().take(max_len).collect::<String>())
     } else {
         value.to_string()
     };
@@ -1050,8 +2612,14 @@ unsafe extern "system" fn details_window_proc(
             let _ = FillRect(hdc, &rect, brush);
             let _ = DeleteObject(HGDIOBJ(brush.0));
 
+            // State of this window
+            let (details, pinned) = match DETAILS_WINDOWS.lock().get(&(hwnd.0 as usize)) {
+                Some(w) => (w.details.clone(), w.pinned),
+                None => (String::new(), false),
+            };
+
             // Header
-            let header_rect = RECT { left: 0, top: 0, right: rect.right, bottom: 35 };
+            let header_rect = RECT { left: 0, top: 0, right: rect.right, bottom: DETAILS_HEADER_HEIGHT };
             let header_brush = CreateSolidBrush(COLORREF(COLOR_NORMAL));
             let _ = FillRect(hdc, &header_rect, header_brush);
             let _ = DeleteObject(HGDIOBJ(header_brush.0));
@@ -1062,19 +2630,25 @@ unsafe extern "system" fn details_window_proc(
             let title: Vec<u16> = "Event Details".encode_utf16().collect();
             let _ = TextOutW(hdc, 15, 10, &title);
 
-            // Close button hint on right
-            let close_hint: Vec<u16> = "[X] Close".encode_utf16().collect();
-            let _ = SetTextColor(hdc, COLORREF(0x00AAAAAA));
-            let _ = TextOutW(hdc, rect.right - 120, 10, &close_hint);
+            // Buttons on right: [MUTE] [NOTE] [EXPORT] [PIN/PINNED] [X]
+            let buttons = details_buttons();
+            draw_button(hdc, buttons.mute_x, buttons.y, DETAILS_MUTE_BTN_W, BTN_HEIGHT, "MUTE", false);
+            let has_note = note_from_details(&details).is_some();
+            draw_button(hdc, buttons.note_x, buttons.y, DETAILS_NOTE_BTN_W, BTN_HEIGHT, "NOTE", has_note);
+            draw_button(hdc, buttons.export_x, buttons.y, DETAILS_EXPORT_BTN_W, BTN_HEIGHT, "EXPORT", false);
+            let pin_text = if pinned { "PINNED" } else { "PIN" };
+            draw_button(hdc, buttons.pin_x, buttons.y, DETAILS_PIN_BTN_W, BTN_HEIGHT, pin_text, pinned);
+            draw_button(hdc, buttons.close_x, buttons.y, DETAILS_CLOSE_BTN_W, BTN_HEIGHT, "X", false);
 
             // Parse and display details structured
-            let details = CURRENT_DETAILS.lock().clone();
             let label_color = 0x0088AACC;  // Light blue for labels
             let value_color = 0x00FFFFFF;  // White for values
             let section_color = 0x0000FF88; // Green for sections
 
             // Extract and display icons (32x32)
             let paths = extract_paths_from_details(&details);
+
+            let details = annotate_details(&details);
             let icon_size: i32 = 32;
             let icon_spacing: i32 = 40;
             let icons_y: i32 = 45;
@@ -1082,16 +2656,19 @@ unsafe extern "system" fn details_window_proc(
             let mut icon_x: i32 = 15;
             let mut icons_drawn = Vec::new();
             for (label, path) in &paths {
-                if let Some(icon) = extract_large_icon(path) {
+                // Only cached icons - extraction runs in the icon worker
+                if let Some(icon) = get_cached_icon(path, IconSize::Large) {
                     let _ = DrawIconEx(hdc, icon_x, icons_y, icon, icon_size, icon_size, 0, None, DI_FLAGS(DI_NORMAL));
-                    icons_drawn.push((icon_x, label.clone(), icon));
-                    icon_x += icon_spacing;
+                } else {
+                    draw_icon_placeholder(hdc, icon_x, icons_y, icon_size);
                 }
+                icons_drawn.push((icon_x, label.clone()));
+                icon_x += icon_spacing;
             }
 
             // Labels below icons
             let _ = SetTextColor(hdc, COLORREF(0x00888888));
-            for (x, label, icon) in &icons_drawn {
+            for (x, label) in &icons_drawn {
                 let label_short = match label.as_str() {
                     "Process" => "App",
                     "Parent" => "Par",
@@ -1101,8 +2678,6 @@ unsafe extern "system" fn details_window_proc(
                 };
                 let label_wide: Vec<u16> = label_short.encode_utf16().collect();
                 let _ = TextOutW(hdc, *x, icons_y + icon_size + 2, &label_wide);
-                // Free icon (not cached for large icons)
-                let _ = DestroyIcon(*icon);
             }
 
             let mut y = if icons_drawn.is_empty() { 50 } else { icons_y + icon_size + 22 };
@@ -1161,15 +2736,79 @@ unsafe extern "system" fn details_window_proc(
             LRESULT(0)
         }
 
+        WM_NCHITTEST => {
+            // Header (outside the buttons) drags the window
+            let mut pt = POINT {
+                x: (lparam.0 & 0xFFFF) as i16 as i32,
+                y: ((lparam.0 >> 16) & 0xFFFF) as i16 as i32,
+            };
+            let _ = ScreenToClient(hwnd, &mut pt);
+            if pt.y >= 0 && pt.y < DETAILS_HEADER_HEIGHT && pt.x < details_buttons().mute_x {
+                LRESULT(HTCAPTION as isize)
+            } else {
+                DefWindowProcW(hwnd, msg, wparam, lparam)
+            }
+        }
+
         WM_LBUTTONDOWN | WM_RBUTTONDOWN => {
-            // Close window on click
-            let _ = DestroyWindow(hwnd);
-            DETAILS_HWND.store(0, Ordering::SeqCst);
+            let x = (lparam.0 & 0xFFFF) as i16 as i32;
+            let y = ((lparam.0 >> 16) & 0xFFFF) as i16 as i32;
+            let DetailsButtons { mute_x, note_x, export_x, pin_x, close_x, y: btn_y } = details_buttons();
+            let on_buttons = y >= btn_y && y <= btn_y + BTN_HEIGHT;
+
+            // Close button
+            if msg == WM_LBUTTONDOWN && on_buttons && x >= close_x && x <= close_x + DETAILS_CLOSE_BTN_W {
+                let _ = DestroyWindow(hwnd);
+                return LRESULT(0);
+            }
+
+            // Mute button
+            if msg == WM_LBUTTONDOWN && on_buttons && x >= mute_x && x <= mute_x + DETAILS_MUTE_BTN_W {
+                let process_name = DETAILS_WINDOWS.lock()
+                    .get(&(hwnd.0 as usize))
+                    .map(|w| w.process_name.clone())
+                    .filter(|name| !name.is_empty());
+                show_mute_menu(hwnd, process_name.as_deref());
+                return LRESULT(0);
+            }
+
+            // Note button
+            if msg == WM_LBUTTONDOWN && on_buttons && x >= note_x && x <= note_x + DETAILS_NOTE_BTN_W {
+                edit_note(hwnd);
+                return LRESULT(0);
+            }
+
+            // Export button
+            if msg == WM_LBUTTONDOWN && on_buttons && x >= export_x && x <= export_x + DETAILS_EXPORT_BTN_W {
+                if let Some(w) = DETAILS_WINDOWS.lock().get(&(hwnd.0 as usize)) {
+                    export_alert(annotate_details(&w.details));
+                }
+                return LRESULT(0);
+            }
+
+            // Pin button
+            if msg == WM_LBUTTONDOWN && on_buttons && x >= pin_x && x <= pin_x + DETAILS_PIN_BTN_W {
+                if let Some(w) = DETAILS_WINDOWS.lock().get_mut(&(hwnd.0 as usize)) {
+                    w.pinned = !w.pinned;
+                }
+                let header = RECT { left: 0, top: 0, right: DETAILS_WIDTH, bottom: DETAILS_HEADER_HEIGHT };
+                let _ = InvalidateRect(hwnd, Some(&header), false);
+                return LRESULT(0);
+            }
+
+            // Unpinned windows close on click (pinned ones only via [X])
+            let pinned = DETAILS_WINDOWS.lock()
+                .get(&(hwnd.0 as usize))
+                .map(|w| w.pinned)
+                .unwrap_or(false);
+            if !pinned {
+                let _ = DestroyWindow(hwnd);
+            }
             LRESULT(0)
         }
 
         WM_DESTROY => {
-            DETAILS_HWND.store(0, Ordering::SeqCst);
+            DETAILS_WINDOWS.lock().remove(&(hwnd.0 as usize));
             LRESULT(0)
         }
 
@@ -0,0 +1,133 @@
+//! API Tokens
+//!
+//! Named tokens for the remote interfaces (web dashboard, server dashboard), each with a role:
+//! viewers can read events, admins can also pause alerts, kill processes and reload the
+//! configuration. Managed with `pc_watcher token`; only the SHA-256 of a token is stored, in
+//! pcwatcher_tokens.json next to the executable. The file is read on every check, so tokens
+//! added or removed while the watcher runs apply immediately.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use windows::Win32::Security::Cryptography::{
+    BCryptGenRandom, BCryptHash, BCRYPT_SHA256_ALG_HANDLE, BCRYPT_USE_SYSTEM_PREFERRED_RNG,
+};
+
+/// Random bytes per token (hex encoded after the prefix)
+const TOKEN_BYTES: usize = 32;
+const TOKEN_PREFIX: &str = "pcw_";
+
+/// What a token may do (Admin includes everything a Viewer may)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Viewer,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+/// Stored token (the token itself is only shown once, on creation)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub name: String,
+    pub role: Role,
+    /// SHA-256 of the token (lowercase hex)
+    pub sha256: String,
+    /// Creation time (RFC 3339)
+    pub created: String,
+}
+
+fn get_tokens_path() -> PathBuf {
+    if let Ok(exe_path) = std::env::current_exe() {
+        if let Some(exe_dir) = exe_path.parent() {
+            return exe_dir.join("pcwatcher_tokens.json");
+        }
+    }
+    PathBuf::from("pcwatcher_tokens.json")
+}
+
+/// All tokens (empty if the file is missing or invalid)
+pub fn list() -> Vec<ApiToken> {
+    fs::read_to_string(get_tokens_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(tokens: &[ApiToken]) -> Result<(), String> {
+    let path = get_tokens_path();
+    let content = serde_json::to_string_pretty(tokens).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+fn sha256_hex(text: &str) -> String {
+    let mut digest = [0u8; 32];
+    // Cannot fail for the built-in algorithm handle and a fixed output size
+    let _ = unsafe { BCryptHash(BCRYPT_SHA256_ALG_HANDLE, None, text.as_bytes(), &mut digest) };
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Creates a token and returns it (the only time it is readable)
+pub fn create(name: &str, role: Role) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Token name is empty".to_string());
+    }
+    let mut tokens = list();
+    if tokens.iter().any(|t| t.name.eq_ignore_ascii_case(name)) {
+        return Err(format!("A token named \"{}\" already exists", name));
+    }
+
+    let mut bytes = [0u8; TOKEN_BYTES];
+    unsafe { BCryptGenRandom(None, &mut bytes, BCRYPT_USE_SYSTEM_PREFERRED_RNG) }
+        .ok()
+        .map_err(|e| format!("BCryptGenRandom failed: {}", e))?;
+    let token = format!("{}{}", TOKEN_PREFIX, bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>());
+
+    tokens.push(ApiToken {
+        name: name.to_string(),
+        role,
+        sha256: sha256_hex(&token),
+        created: chrono::Local::now().to_rfc3339(),
+    });
+    save(&tokens)?;
+    Ok(token)
+}
+
+/// Deletes a token by name
+pub fn remove(name: &str) -> Result<(), String> {
+    let mut tokens = list();
+    let count = tokens.len();
+    tokens.retain(|t| !t.name.eq_ignore_ascii_case(name.trim()));
+    if tokens.len() == count {
+        return Err(format!("No token named \"{}\"", name));
+    }
+    save(&tokens)
+}
+
+/// Token matching a password of a request (None = unknown token)
+pub fn find(password: &str) -> Option<ApiToken> {
+    if !password.starts_with(TOKEN_PREFIX) {
+        return None;
+    }
+    let hash = sha256_hex(password);
+    list().into_iter().find(|t| crate::tls::token_matches(&t.sha256, &hash))
+}
+
+/// Role of a request: the configured password of the interface is the owner's (admin),
+/// otherwise the role of a matching API token (None = not authorized)
+pub fn authorize(password: Option<&str>, owner_password: &str) -> Option<Role> {
+    let password = password?;
+    if crate::tls::token_matches(owner_password, password) {
+        return Some(Role::Admin);
+    }
+    find(password).map(|token| token.role)
+}
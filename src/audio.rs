@@ -0,0 +1,155 @@
+//! Audio Snippet
+//!
+//! Optional short audio clip on Critical alerts, recorded with WASAPI:
+//! loopback (what the PC plays) and/or the default microphone.
+//! Saved as WAV in the alert folder (same retention as screenshots).
+
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::info;
+use windows::Win32::Media::Audio::{
+    eCapture, eConsole, eRender, EDataFlow, IAudioCaptureClient, IAudioClient, IMMDeviceEnumerator,
+    MMDeviceEnumerator, AUDCLNT_BUFFERFLAGS_SILENT, AUDCLNT_SHAREMODE_SHARED,
+    AUDCLNT_STREAMFLAGS_LOOPBACK, WAVEFORMATEX,
+};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoTaskMemFree, CoUninitialize, CLSCTX_ALL, COINIT_MULTITHREADED,
+};
+
+/// WASAPI buffer duration (100ns units = 1 second)
+const BUFFER_DURATION: i64 = 10_000_000;
+
+/// Poll interval for captured packets
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Audio sources recorded on Critical alerts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioSource {
+    #[default]
+    None,
+    /// What the PC plays (speakers/headphones)
+    Loopback,
+    /// Default microphone
+    Microphone,
+    Both,
+}
+
+/// Starts the configured recordings in the background (one thread per source)
+pub fn start_recording(dir: &Path) {
+    let capture = crate::config::get().capture;
+    let duration = Duration::from_secs(capture.audio_seconds as u64);
+
+    let sources: &[(EDataFlow, &str)] = match capture.audio_on_critical {
+        AudioSource::None => &[],
+        AudioSource::Loopback => &[(eRender, "audio_loopback.wav")],
+        AudioSource::Microphone => &[(eCapture, "audio_microphone.wav")],
+        AudioSource::Both => &[(eRender, "audio_loopback.wav"), (eCapture, "audio_microphone.wav")],
+    };
+
+    for &(flow, file_name) in sources {
+        let path = dir.join(file_name);
+        thread::spawn(move || {
+            match record(flow, duration, &path) {
+                Ok(()) => info!("Audio recorded: {}", path.display()),
+                Err(e) => crate::error::report(&format!("Audio recording ({})", file_name), e),
+            }
+        });
+    }
+}
+
+/// Records from the default device of a direction (eRender = loopback)
+fn record(flow: EDataFlow, duration: Duration, path: &Path) -> Result<(), Error> {
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        let result = record_device(flow, duration, path);
+        CoUninitialize();
+        result
+    }
+}
+
+unsafe fn record_device(flow: EDataFlow, duration: Duration, path: &Path) -> Result<(), Error> {
+    let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+        .map_err(|e| Error::win32("MMDeviceEnumerator", &e))?;
+    let device = enumerator
+        .GetDefaultAudioEndpoint(flow, eConsole)
+        .map_err(|e| Error::Device(format!("No default audio device: {}", e)))?;
+    let client: IAudioClient = device
+        .Activate(CLSCTX_ALL, None)
+        .map_err(|e| Error::win32("Activate", &e))?;
+
+    let format = client.GetMixFormat().map_err(|e| Error::win32("GetMixFormat", &e))?;
+    let result = capture_to_file(&client, flow, format, duration, path);
+    CoTaskMemFree(Some(format as *const _));
+    result
+}
+
+unsafe fn capture_to_file(
+    client: &IAudioClient,
+    flow: EDataFlow,
+    format: *const WAVEFORMATEX,
+    duration: Duration,
+    path: &Path,
+) -> Result<(), Error> {
+    let stream_flags = if flow == eRender { AUDCLNT_STREAMFLAGS_LOOPBACK } else { 0 };
+    client
+        .Initialize(AUDCLNT_SHAREMODE_SHARED, stream_flags, BUFFER_DURATION, 0, format, None)
+        .map_err(|e| Error::win32("Initialize", &e))?;
+    let capture: IAudioCaptureClient = client.GetService().map_err(|e| Error::win32("GetService", &e))?;
+
+    let block_align = (*format).nBlockAlign as usize;
+    let mut samples: Vec<u8> = Vec::new();
+
+    client.Start().map_err(|e| Error::win32("Start", &e))?;
+    let started = Instant::now();
+    // Note: loopback delivers no packets while nothing is playing
+    while started.elapsed() < duration {
+        thread::sleep(POLL_INTERVAL);
+        while capture.GetNextPacketSize().unwrap_or(0) > 0 {
+            let mut data: *mut u8 = std::ptr::null_mut();
+            let mut frames: u32 = 0;
+            let mut flags: u32 = 0;
+            if capture.GetBuffer(&mut data, &mut frames, &mut flags, None, None).is_err() {
+                break;
+            }
+            let bytes = frames as usize * block_align;
+            if flags & AUDCLNT_BUFFERFLAGS_SILENT.0 as u32 != 0 || data.is_null() {
+                samples.resize(samples.len() + bytes, 0);
+            } else {
+                samples.extend_from_slice(std::slice::from_raw_parts(data, bytes));
+            }
+            let _ = capture.ReleaseBuffer(frames);
+        }
+    }
+    let _ = client.Stop();
+
+    write_wav(path, format, &samples)
+}
+
+/// Writes a WAV file with the mix format as fmt chunk (incl. WAVEFORMATEXTENSIBLE part)
+unsafe fn write_wav(path: &Path, format: *const WAVEFORMATEX, samples: &[u8]) -> Result<(), Error> {
+    let fmt_size = std::mem::size_of::<WAVEFORMATEX>() + (*format).cbSize as usize;
+    let fmt_bytes = std::slice::from_raw_parts(format as *const u8, fmt_size);
+
+    let file = File::create(path).map_err(|e| Error::io(path, &e))?;
+    let mut writer = BufWriter::new(file);
+
+    let riff_size = 4 + (8 + fmt_size) + (8 + samples.len());
+    let mut write = |bytes: &[u8]| writer.write_all(bytes).map_err(|e| Error::io(path, &e));
+    write(b"RIFF")?;
+    write(&(riff_size as u32).to_le_bytes())?;
+    write(b"WAVE")?;
+    write(b"fmt ")?;
+    write(&(fmt_size as u32).to_le_bytes())?;
+    write(fmt_bytes)?;
+    write(b"data")?;
+    write(&(samples.len() as u32).to_le_bytes())?;
+    write(samples)?;
+
+    writer.flush().map_err(|e| Error::io(path, &e))
+}
@@ -0,0 +1,323 @@
+//! Autorun Entry Monitoring
+//!
+//! Persistence between reboots usually goes through the Run/RunOnce keys, the Startup
+//! folders or a permanent WMI event subscription. These places are read periodically and
+//! compared with the previous snapshot; new and changed entries raise a Warning. The writing
+//! process cannot be read from the registry - processes started since the previous scan and
+//! a running process of the entry's executable are named as suspects.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use chrono::Local;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+use windows::core::{BSTR, PCWSTR, PWSTR, VARIANT};
+use windows::Win32::Foundation::ERROR_SUCCESS;
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoSetProxyBlanket, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED, EOAC_NONE,
+    RPC_C_AUTHN_LEVEL_CALL, RPC_C_IMP_LEVEL_IMPERSONATE,
+};
+use windows::Win32::System::Registry::{
+    RegCloseKey, RegEnumValueW, RegOpenKeyExW, HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, KEY_READ, REG_DWORD,
+    REG_EXPAND_SZ, REG_MULTI_SZ, REG_QWORD, REG_SZ, REG_VALUE_TYPE,
+};
+use windows::Win32::System::Wmi::{
+    IWbemLocator, IWbemServices, WbemLocator, WBEM_FLAG_FORWARD_ONLY, WBEM_FLAG_RETURN_IMMEDIATELY, WBEM_INFINITE,
+};
+
+/// Event types in the log
+const ADDED_EVENT_TYPE: &str = "AUTORUN_ADDED";
+const CHANGED_EVENT_TYPE: &str = "AUTORUN_CHANGED";
+
+/// RPC authentication service / authorization (rpcdce.h)
+const RPC_C_AUTHN_WINNT: u32 = 10;
+const RPC_C_AUTHZ_NONE: u32 = 0;
+
+/// Scripts of WMI consumers are shortened in the log
+const MAX_SCRIPT_CHARS: usize = 300;
+
+/// Run keys (root, key, display prefix)
+const RUN_KEYS: &[(HKEY, &str, &str)] = &[
+    (HKEY_CURRENT_USER, r"Software\Microsoft\Windows\CurrentVersion\Run", "HKCU"),
+    (HKEY_CURRENT_USER, r"Software\Microsoft\Windows\CurrentVersion\RunOnce", "HKCU"),
+    (HKEY_LOCAL_MACHINE, r"Software\Microsoft\Windows\CurrentVersion\Run", "HKLM"),
+    (HKEY_LOCAL_MACHINE, r"Software\Microsoft\Windows\CurrentVersion\RunOnce", "HKLM"),
+    (HKEY_LOCAL_MACHINE, r"Software\WOW6432Node\Microsoft\Windows\CurrentVersion\Run", "HKLM"),
+    (HKEY_LOCAL_MACHINE, r"Software\WOW6432Node\Microsoft\Windows\CurrentVersion\RunOnce", "HKLM"),
+];
+
+/// WMI consumers that run something (class, property with the command)
+const WMI_CONSUMERS: &[(&str, &str)] = &[
+    ("CommandLineEventConsumer", "CommandLineTemplate"),
+    ("ActiveScriptEventConsumer", "ScriptText"),
+];
+
+/// UTF-16 with null terminator
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Readable form of registry value data
+fn format_value(value_type: REG_VALUE_TYPE, data: &[u8]) -> String {
+    match value_type {
+        REG_SZ | REG_EXPAND_SZ | REG_MULTI_SZ => {
+            let wide: Vec<u16> = data.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+            let text = String::from_utf16_lossy(&wide);
+            // REG_MULTI_SZ: null-separated list
+            text.trim_end_matches('\0').replace('\0', " | ")
+        }
+        REG_DWORD if data.len() >= 4 => u32::from_le_bytes([data[0], data[1], data[2], data[3]]).to_string(),
+        REG_QWORD if data.len() >= 8 => u64::from_le_bytes(data[..8].try_into().unwrap_or_default()).to_string(),
+        _ => {
+            let hex: Vec<String> = data.iter().take(32).map(|b| format!("{:02x}", b)).collect();
+            let more = if data.len() > 32 { " ..." } else { "" };
+            format!("hex:{}{}", hex.join(","), more)
+        }
+    }
+}
+
+/// All values of a registry key as (name, readable data); None if the key does not exist
+pub fn read_values(root: HKEY, path: &str) -> Option<Vec<(String, String)>> {
+    let path = to_wide(path);
+    let mut key = HKEY::default();
+    if unsafe { RegOpenKeyExW(root, PCWSTR(path.as_ptr()), 0, KEY_READ, &mut key) } != ERROR_SUCCESS {
+        return None;
+    }
+
+    let mut values = Vec::new();
+    let mut index = 0;
+    loop {
+        let mut name = [0u16; 16384];
+        let mut name_len = name.len() as u32;
+        let mut value_type: u32 = 0;
+        let mut data = vec![0u8; 64 * 1024];
+        let mut data_len = data.len() as u32;
+        let result = unsafe {
+            RegEnumValueW(
+                key,
+                index,
+                PWSTR(name.as_mut_ptr()),
+                &mut name_len,
+                None,
+                Some(&mut value_type),
+                Some(data.as_mut_ptr()),
+                Some(&mut data_len),
+            )
+        };
+        if result != ERROR_SUCCESS {
+            break;
+        }
+        let name = String::from_utf16_lossy(&name[..name_len as usize]);
+        values.push((name, format_value(REG_VALUE_TYPE(value_type), &data[..data_len as usize])));
+        index += 1;
+    }
+    unsafe {
+        let _ = RegCloseKey(key);
+    }
+    Some(values)
+}
+
+/// Startup folders of the user and of all users
+fn startup_folders() -> Vec<PathBuf> {
+    let mut folders = Vec::new();
+    if let Ok(appdata) = std::env::var("APPDATA") {
+        folders.push(PathBuf::from(appdata).join(r"Microsoft\Windows\Start Menu\Programs\Startup"));
+    }
+    if let Ok(programdata) = std::env::var("PROGRAMDATA") {
+        folders.push(PathBuf::from(programdata).join(r"Microsoft\Windows\Start Menu\Programs\StartUp"));
+    }
+    folders
+}
+
+/// Connection to root\subscription (COM must be initialized on the calling thread)
+unsafe fn connect_wmi() -> Result<IWbemServices, String> {
+    let locator: IWbemLocator =
+        CoCreateInstance(&WbemLocator, None, CLSCTX_INPROC_SERVER).map_err(|e| format!("WbemLocator: {}", e))?;
+    let services = locator
+        .ConnectServer(&BSTR::from(r"ROOT\subscription"), &BSTR::new(), &BSTR::new(), &BSTR::new(), 0, &BSTR::new(), None)
+        .map_err(|e| format!("ConnectServer: {}", e))?;
+    CoSetProxyBlanket(
+        &services,
+        RPC_C_AUTHN_WINNT,
+        RPC_C_AUTHZ_NONE,
+        PCWSTR::null(),
+        RPC_C_AUTHN_LEVEL_CALL,
+        RPC_C_IMP_LEVEL_IMPERSONATE,
+        None,
+        EOAC_NONE,
+    )
+    .map_err(|e| format!("CoSetProxyBlanket: {}", e))?;
+    Ok(services)
+}
+
+/// String property of a WMI object (empty if missing/null)
+unsafe fn wmi_string(object: &windows::Win32::System::Wmi::IWbemClassObject, property: &str) -> String {
+    let property = to_wide(property);
+    let mut value = VARIANT::default();
+    if object.Get(PCWSTR(property.as_ptr()), 0, &mut value, None, None).is_err() {
+        return String::new();
+    }
+    BSTR::try_from(&value).map(|s| s.to_string()).unwrap_or_default()
+}
+
+/// Permanent WMI consumers that run a command or script
+unsafe fn read_wmi_consumers(services: &IWbemServices, entries: &mut HashMap<String, String>) {
+    for (class, property) in WMI_CONSUMERS {
+        let query = BSTR::from(format!("SELECT * FROM {}", class));
+        let flags = WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_RETURN_IMMEDIATELY;
+        let enumerator = match services.ExecQuery(&BSTR::from("WQL"), &query, flags, None) {
+            Ok(enumerator) => enumerator,
+            Err(e) => {
+                debug!("WMI query for {} failed: {}", class, e);
+                continue;
+            }
+        };
+        loop {
+            let mut objects = [None];
+            let mut returned = 0;
+            if enumerator.Next(WBEM_INFINITE, &mut objects, &mut returned).is_err() || returned == 0 {
+                break;
+            }
+            let Some(object) = objects[0].take() else {
+                break;
+            };
+            let command: String = wmi_string(&object, property).chars().take(MAX_SCRIPT_CHARS).collect();
+            entries.insert(format!(r"WMI\{}\{}", class, wmi_string(&object, "Name")), command);
+        }
+    }
+}
+
+/// All autorun entries: location\name -> command
+fn snapshot(wmi: Option<&IWbemServices>) -> HashMap<String, String> {
+    let mut entries = HashMap::new();
+    for (root, path, prefix) in RUN_KEYS {
+        for (name, command) in read_values(*root, path).unwrap_or_default() {
+            entries.insert(format!(r"{}\{}\{}", prefix, path, name), command);
+        }
+    }
+    for folder in startup_folders() {
+        let Ok(files) = std::fs::read_dir(&folder) else {
+            continue;
+        };
+        for file in files.flatten() {
+            let path = file.path();
+            if path.file_name().is_some_and(|n| n.eq_ignore_ascii_case("desktop.ini")) {
+                continue;
+            }
+            let path = path.to_string_lossy().to_string();
+            entries.insert(path.clone(), path);
+        }
+    }
+    if let Some(services) = wmi {
+        unsafe { read_wmi_consumers(services, &mut entries) };
+    }
+    entries
+}
+
+/// Checks if an entry is expected (ignored list of the config, substring of location or command)
+fn is_ignored(location: &str, command: &str) -> bool {
+    let location = location.to_lowercase();
+    let command = command.to_lowercase();
+    crate::config::get().autoruns.ignored.iter().any(|i| {
+        let i = i.to_lowercase();
+        location.contains(&i) || command.contains(&i)
+    })
+}
+
+/// Likely writer: a running process of the entry's executable, else processes started since the last scan
+fn suspects(command: &str, started: &[String], running: &HashSet<String>) -> Vec<String> {
+    let lower = command.to_lowercase();
+    let target = lower
+        .split(".exe")
+        .next()
+        .and_then(|p| p.trim_matches('"').rsplit(['\\', '/']).next())
+        .map(|n| n.trim().to_string())
+        .filter(|n| lower.contains(".exe") && running.contains(n));
+    match target {
+        Some(name) => vec![name],
+        None => started.iter().take(3).cloned().collect(),
+    }
+}
+
+/// Logs a new or changed entry and alerts, unless the rule is disabled
+fn report(location: &str, command: &str, event_type: &str, writers: &[String]) {
+    if is_ignored(location, command) {
+        return;
+    }
+    let action = if event_type == ADDED_EVENT_TYPE { "added" } else { "changed" };
+    let suspect = if writers.is_empty() { "unknown".to_string() } else { writers.join(", ") };
+
+    let severity = Severity::Warning;
+    warn!("!!! AUTORUN {}: {} = {} (writer: {}) !!!", action.to_uppercase(), location, command, suspect);
+    let name = location.rsplit('\\').next().unwrap_or(location).to_string();
+    if crate::rules::is_enabled(Rule::Autorun) {
+        crate::alert_window::set_rule_alert(
+            Rule::Autorun,
+            &format!("Autorun entry {}: {} (by {})", action, name, suspect),
+            command,
+            severity,
+        );
+        crate::sound::play_alert(Rule::Autorun, severity);
+        let capture = crate::rules::capture_mode(Rule::Autorun, severity);
+        crate::screenshot::capture_alert_screenshots(name.clone(), event_type, capture, severity);
+    }
+
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: Local::now(),
+        event_type: event_type.to_string(),
+        severity,
+        process_name: writers.first().cloned().unwrap_or_default(),
+        process_path: location.to_string(),
+        window_title: format!("{} (possible writer: {})", name, suspect),
+        command_line: Some(command.to_string()),
+        ..Default::default()
+    });
+}
+
+/// Starts the polling thread (only if enabled in the config)
+pub fn start_monitor() {
+    let settings = crate::config::get().autoruns;
+    if !settings.enabled {
+        return;
+    }
+
+    thread::spawn(move || {
+        let wmi = if settings.wmi {
+            unsafe {
+                let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+                connect_wmi()
+                    .map_err(|e| error!("WMI subscriptions are not monitored: {}", e))
+                    .ok()
+            }
+        } else {
+            None
+        };
+
+        // Baseline: entries present at start are not reported
+        let mut known = snapshot(wmi.as_ref());
+        let mut running = crate::process_info::get_running_process_names();
+        info!("Autorun monitor started: {} entries, every {}s", known.len(), settings.interval_secs);
+
+        while !crate::tray::should_exit() {
+            thread::sleep(Duration::from_secs(settings.interval_secs.max(1)));
+            let current = snapshot(wmi.as_ref());
+            let now_running = crate::process_info::get_running_process_names();
+            let started: Vec<String> = now_running.difference(&running).cloned().collect();
+
+            for (location, command) in &current {
+                let event_type = match known.get(location) {
+                    None => ADDED_EVENT_TYPE,
+                    Some(previous) if previous != command => CHANGED_EVENT_TYPE,
+                    Some(_) => continue,
+                };
+                report(location, command, event_type, &suspects(command, &started, &now_running));
+            }
+            known = current;
+            running = now_running;
+        }
+    });
+}
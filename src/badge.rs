@@ -0,0 +1,140 @@
+//! Alert Count Badge
+//!
+//! Number of unacknowledged alerts in the standard Windows places: the tray tooltip and,
+//! while the alert window is minimized to the taskbar, an overlay icon with the count on
+//! its taskbar button (ITaskbarList3). Refreshed by the status timer of the alert window,
+//! so the COM object lives on the GUI thread.
+
+use parking_lot::Mutex;
+use std::cell::RefCell;
+use tracing::warn;
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::{COLORREF, HWND, RECT, TRUE};
+use windows::Win32::Graphics::Gdi::{
+    CreateBitmap, CreateCompatibleBitmap, CreateCompatibleDC, CreateSolidBrush, DeleteDC, DeleteObject, DrawTextW,
+    Ellipse, GetDC, GetStockObject, PatBlt, ReleaseDC, SelectObject, SetBkMode, SetTextColor, BLACKNESS, BLACK_BRUSH,
+    DEFAULT_GUI_FONT, DT_CENTER, DT_SINGLELINE, DT_VCENTER, HGDIOBJ, NULL_PEN, TRANSPARENT, WHITENESS,
+};
+use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED};
+use windows::Win32::UI::Shell::{ITaskbarList3, TaskbarList};
+use windows::Win32::UI::WindowsAndMessaging::{CreateIconIndirect, DestroyIcon, HICON, ICONINFO};
+
+/// Overlay icon size (the taskbar draws overlays at 16x16)
+const ICON_SIZE: i32 = 16;
+/// Badge color (BGR): same red as Critical alerts
+const COLOR_BADGE: u32 = 0x000000FF;
+const COLOR_BADGE_TEXT: u32 = 0x00FFFFFF;
+
+lazy_static::lazy_static! {
+    // Last shown state: (open alerts, overlay on the taskbar button)
+    static ref SHOWN: Mutex<Option<(usize, bool)>> = Mutex::new(None);
+}
+
+thread_local! {
+    // Taskbar interface of the GUI thread (None = not available)
+    static TASKBAR: RefCell<Option<ITaskbarList3>> = RefCell::new(create_taskbar());
+}
+
+fn create_taskbar() -> Option<ITaskbarList3> {
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let taskbar: ITaskbarList3 = match CoCreateInstance(&TaskbarList, None, CLSCTX_INPROC_SERVER) {
+            Ok(taskbar) => taskbar,
+            Err(e) => {
+                warn!("Taskbar overlay not available: {}", e);
+                return None;
+            }
+        };
+        taskbar.HrInit().ok().map(|_| taskbar)
+    }
+}
+
+/// Shows the count of open alerts (called every second; only changes are applied)
+pub fn update(hwnd: HWND, open_alerts: usize, taskbar_mode: bool) {
+    let state = (open_alerts, taskbar_mode && open_alerts > 0);
+    if *SHOWN.lock() == Some(state) {
+        return;
+    }
+
+    let tooltip = match open_alerts {
+        0 => "PC Watcher - Right-click to exit".to_string(),
+        1 => "PC Watcher - 1 unacknowledged alert".to_string(),
+        n => format!("PC Watcher - {} unacknowledged alerts", n),
+    };
+    crate::tray::set_tooltip(&tooltip);
+
+    // The taskbar button only exists while minimized to the taskbar
+    let applied = !taskbar_mode || set_overlay(hwnd, state.1.then_some(open_alerts));
+    if applied {
+        *SHOWN.lock() = Some(state);
+    }
+}
+
+/// Sets (Some) or removes (None) the overlay icon; false = not applied yet (button missing)
+fn set_overlay(hwnd: HWND, count: Option<usize>) -> bool {
+    TASKBAR.with(|taskbar| {
+        let Some(taskbar) = taskbar.borrow().clone() else {
+            return true;
+        };
+        unsafe {
+            match count {
+                Some(count) => {
+                    let Some(icon) = count_icon(count) else {
+                        return true;
+                    };
+                    let description: Vec<u16> = format!("{} unacknowledged alerts", count)
+                        .encode_utf16()
+                        .chain(std::iter::once(0))
+                        .collect();
+                    let result = taskbar.SetOverlayIcon(hwnd, icon, PCWSTR(description.as_ptr()));
+                    // The taskbar keeps its own copy
+                    let _ = DestroyIcon(icon);
+                    result.is_ok()
+                }
+                None => taskbar.SetOverlayIcon(hwnd, HICON::default(), w!("")).is_ok(),
+            }
+        }
+    })
+}
+
+/// Red circle with the count (more than 99 as "99")
+unsafe fn count_icon(count: usize) -> Option<HICON> {
+    let screen = GetDC(None);
+    let dc = CreateCompatibleDC(screen);
+    let color = CreateCompatibleBitmap(screen, ICON_SIZE, ICON_SIZE);
+    let mask = CreateBitmap(ICON_SIZE, ICON_SIZE, 1, 1, None);
+    ReleaseDC(None, screen);
+
+    // Mask: white = transparent, black = the circle
+    let old_bitmap = SelectObject(dc, mask);
+    let old_pen = SelectObject(dc, GetStockObject(NULL_PEN));
+    let old_brush = SelectObject(dc, GetStockObject(BLACK_BRUSH));
+    let _ = PatBlt(dc, 0, 0, ICON_SIZE, ICON_SIZE, WHITENESS);
+    let _ = Ellipse(dc, 0, 0, ICON_SIZE + 1, ICON_SIZE + 1);
+
+    // Color: red circle with the white number
+    SelectObject(dc, color);
+    let brush = CreateSolidBrush(COLORREF(COLOR_BADGE));
+    SelectObject(dc, brush);
+    let _ = PatBlt(dc, 0, 0, ICON_SIZE, ICON_SIZE, BLACKNESS);
+    let _ = Ellipse(dc, 0, 0, ICON_SIZE + 1, ICON_SIZE + 1);
+    let old_font = SelectObject(dc, GetStockObject(DEFAULT_GUI_FONT));
+    let _ = SetBkMode(dc, TRANSPARENT);
+    let _ = SetTextColor(dc, COLORREF(COLOR_BADGE_TEXT));
+    let mut text: Vec<u16> = count.min(99).to_string().encode_utf16().collect();
+    let mut rect = RECT { left: 0, top: 0, right: ICON_SIZE, bottom: ICON_SIZE };
+    let _ = DrawTextW(dc, &mut text, &mut rect, DT_CENTER | DT_VCENTER | DT_SINGLELINE);
+
+    SelectObject(dc, old_font);
+    SelectObject(dc, old_brush);
+    SelectObject(dc, old_pen);
+    SelectObject(dc, old_bitmap);
+    let _ = DeleteObject(HGDIOBJ(brush.0));
+    let _ = DeleteDC(dc);
+
+    let info = ICONINFO { fIcon: TRUE, xHotspot: 0, yHotspot: 0, hbmMask: mask, hbmColor: color };
+    let icon = CreateIconIndirect(&info).ok();
+    let _ = DeleteObject(HGDIOBJ(color.0));
+    let _ = DeleteObject(HGDIOBJ(mask.0));
+    icon
+}
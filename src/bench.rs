@@ -0,0 +1,180 @@
+//! Pipeline Benchmark
+//!
+//! `pc_watcher bench` pushes synthetic window events through the real event worker and a
+//! logger stage (event log file + event database in a scratch folder) at increasing rates.
+//! Per step: throughput, latency up to the logger (queue + worker), write time of the logger
+//! and drops at both queues - to size queues and decide where coalescing helps.
+
+use crate::event_hook::{EventType, WindowEvent};
+use crate::logger::LogEntry;
+use crate::process_info::{self, MockProcess, MockProvider};
+use chrono::Local;
+use crossbeam_channel::{bounded, TrySendError};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::Span;
+use windows::Win32::System::Console::GetConsoleWindow;
+use windows::Win32::UI::WindowsAndMessaging::GetDesktopWindow;
+
+/// Queue sizes as in the running application
+const QUEUE_SIZE: usize = 1000;
+
+/// PID of the synthetic process behind the benchmark events
+const BENCH_PROCESS_ID: u32 = 4242;
+
+/// Below this the producer spins instead of sleeping (Windows sleeps at least ~1 ms)
+const SPIN_THRESHOLD: Duration = Duration::from_millis(2);
+
+/// Result of one rate step
+#[derive(Debug, Clone, Serialize)]
+pub struct StepResult {
+    /// Target rate (events/s)
+    pub rate: u32,
+    pub sent: usize,
+    /// Event queue full (worker too slow)
+    pub worker_drops: usize,
+    /// Log queue full (logger too slow)
+    pub logger_drops: usize,
+    /// Events written per second
+    pub throughput: f64,
+    /// Time from the event until it reached the logger
+    pub latency_p50_ms: f64,
+    pub latency_p99_ms: f64,
+    pub latency_max_ms: f64,
+    /// Logger time per event (log file + database)
+    pub write_avg_us: f64,
+}
+
+/// What the logger stage measured
+struct LoggerStats {
+    latencies_ms: Vec<f64>,
+    write_time: Duration,
+    finished: Instant,
+}
+
+/// Logger stage: same writes as the log worker, into the scratch folder
+fn logger_stage(receiver: crossbeam_channel::Receiver<LogEntry>, log_path: PathBuf, db_path: PathBuf) -> Result<LoggerStats, String> {
+    let conn = crate::database::open_at(&db_path).map_err(|e| e.to_string())?;
+    let mut writer = BufWriter::new(File::create(&log_path).map_err(|e| e.to_string())?);
+    let mut stats = LoggerStats { latencies_ms: Vec::new(), write_time: Duration::ZERO, finished: Instant::now() };
+
+    for entry in receiver {
+        let latency = Local::now() - entry.timestamp;
+        stats.latencies_ms.push(latency.num_microseconds().unwrap_or_default() as f64 / 1000.0);
+
+        let started = Instant::now();
+        writer.write_all(entry.format_file().as_bytes()).map_err(|e| e.to_string())?;
+        crate::database::insert_event_into(&conn, &entry).map_err(|e| e.to_string())?;
+        stats.write_time += started.elapsed();
+        stats.finished = Instant::now();
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(stats)
+}
+
+/// Value at a percentile of sorted values
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[index.min(sorted.len() - 1)]
+}
+
+/// Sends events at one rate through a fresh worker and logger
+fn run_step(rate: u32, events: usize, hwnd: isize, folder: &Path) -> Result<StepResult, String> {
+    let (event_tx, event_rx) = bounded::<WindowEvent>(QUEUE_SIZE);
+    let (log_tx, log_rx) = bounded::<LogEntry>(QUEUE_SIZE);
+    let worker = thread::spawn(move || crate::event_hook::event_worker(event_rx, log_tx));
+    let log_path = folder.join(format!("events_{}.log", rate));
+    let db_path = folder.join("events.db");
+    let logger = thread::spawn(move || logger_stage(log_rx, log_path, db_path));
+
+    let interval = Duration::from_secs_f64(1.0 / rate.max(1) as f64);
+    let started = Instant::now();
+    let mut worker_drops = 0;
+    for i in 0..events {
+        // Paced by the schedule, so late events are sent in a burst like real ones
+        let due = started + interval * i as u32;
+        loop {
+            let now = Instant::now();
+            if now >= due {
+                break;
+            }
+            if due - now > SPIN_THRESHOLD {
+                thread::sleep(due - now - SPIN_THRESHOLD);
+            } else {
+                std::hint::spin_loop();
+            }
+        }
+        let event = WindowEvent { event_type: EventType::SelfTest, hwnd, timestamp: Local::now(), span: Span::none() };
+        if let Err(TrySendError::Full(_)) = event_tx.try_send(event) {
+            worker_drops += 1;
+        }
+    }
+    // Ends the worker, which ends the logger
+    drop(event_tx);
+    worker.join().map_err(|_| "event worker panicked".to_string())?;
+    let mut stats = logger.join().map_err(|_| "logger panicked".to_string())??;
+
+    let logged = stats.latencies_ms.len();
+    let elapsed = stats.finished.duration_since(started).as_secs_f64().max(0.001);
+    stats.latencies_ms.sort_by(|a, b| a.total_cmp(b));
+    Ok(StepResult {
+        rate,
+        sent: events,
+        worker_drops,
+        logger_drops: events.saturating_sub(worker_drops + logged),
+        throughput: logged as f64 / elapsed,
+        latency_p50_ms: percentile(&stats.latencies_ms, 0.50),
+        latency_p99_ms: percentile(&stats.latencies_ms, 0.99),
+        latency_max_ms: stats.latencies_ms.last().copied().unwrap_or_default(),
+        write_avg_us: stats.write_time.as_secs_f64() * 1_000_000.0 / logged.max(1) as f64,
+    })
+}
+
+/// Runs all rate steps (scratch files are deleted afterwards)
+pub fn run(events: usize, rates: &[u32], mut on_step: impl FnMut(&StepResult)) -> Result<Vec<StepResult>, String> {
+    let folder = std::env::temp_dir().join(format!("pcwatcher_bench_{}", std::process::id()));
+    fs::create_dir_all(&folder).map_err(|e| format!("cannot create {}: {}", folder.display(), e))?;
+
+    // Events need a real window; its process comes from a fixed table, so the result does not
+    // depend on the rights of this process
+    let hwnd = unsafe {
+        let console = GetConsoleWindow();
+        if console.0.is_null() { GetDesktopWindow() } else { console }
+    };
+    let process = MockProcess {
+        name: "bench".to_string(),
+        path: Some(r"C:\Program Files\Bench\bench.exe".to_string()),
+        command_line: Some(r#""C:\Program Files\Bench\bench.exe" --synthetic"#.to_string()),
+        parent_id: 0,
+        created: Some(0),
+    };
+    process_info::set_provider(Arc::new(
+        MockProvider::new()
+            .with_process(BENCH_PROCESS_ID, process)
+            .with_window(hwnd.0 as isize, BENCH_PROCESS_ID, "PC Watcher Benchmark", "BenchWindow"),
+    ));
+
+    let mut results = Vec::new();
+    for &rate in rates {
+        match run_step(rate, events, hwnd.0 as isize, &folder) {
+            Ok(result) => {
+                on_step(&result);
+                results.push(result);
+            }
+            Err(e) => {
+                let _ = fs::remove_dir_all(&folder);
+                return Err(e);
+            }
+        }
+    }
+    let _ = fs::remove_dir_all(&folder);
+    Ok(results)
+}
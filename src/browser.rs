@@ -0,0 +1,112 @@
+//! Browser Context
+//!
+//! When a known browser gets the focus, the URL of the active tab is read from the
+//! address bar via UIAutomation - "chrome got focus" becomes "chrome on accounts.google.com".
+//! The search through large browser trees can take a while, so it runs on its own thread and
+//! the event worker waits at most URL_TIMEOUT for it (the event is logged without URL otherwise).
+
+use crossbeam_channel::{bounded, Sender};
+use once_cell::sync::OnceCell;
+use std::cell::RefCell;
+use std::thread;
+use std::time::Duration;
+use tracing::debug;
+use windows::core::{BSTR, VARIANT};
+use windows::Win32::Foundation::HWND;
+use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED};
+use windows::Win32::UI::Accessibility::{
+    CUIAutomation, IUIAutomation, TreeScope_Descendants, UIA_ControlTypePropertyId,
+    UIA_EditControlTypeId, UIA_ValueValuePropertyId,
+};
+
+/// Process names of supported browsers (lowercase, without .exe)
+const KNOWN_BROWSERS: &[&str] = &["chrome", "msedge", "firefox", "brave", "opera", "vivaldi"];
+
+/// Longest wait of the event worker for a URL
+const URL_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Lookup requests (window handle, reply) - at most one waits, further ones are skipped
+type UrlRequest = (usize, Sender<Option<String>>);
+static LOOKUP: OnceCell<Sender<UrlRequest>> = OnceCell::new();
+
+thread_local! {
+    // UIAutomation instance of the calling thread (COM is initialized once per thread)
+    static AUTOMATION: RefCell<Option<IUIAutomation>> = const { RefCell::new(None) };
+}
+
+/// Checks if a process is a known browser
+pub fn is_browser(process_name: &str) -> bool {
+    let name = process_name.to_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    KNOWN_BROWSERS.contains(&name)
+}
+
+/// Returns the UIAutomation instance of this thread (created on first use)
+fn automation() -> Option<IUIAutomation> {
+    AUTOMATION.with(|cell| {
+        let mut automation = cell.borrow_mut();
+        if automation.is_none() {
+            unsafe {
+                let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+                match CoCreateInstance::<_, IUIAutomation>(&CUIAutomation, None, CLSCTX_INPROC_SERVER) {
+                    Ok(a) => *automation = Some(a),
+                    Err(e) => debug!("UIAutomation not available: {}", e),
+                }
+            }
+        }
+        automation.clone()
+    })
+}
+
+/// URL of the active tab, read on the lookup thread (None if it takes longer than URL_TIMEOUT)
+pub fn get_active_url(hwnd: HWND) -> Option<String> {
+    let lookup = LOOKUP.get_or_init(|| {
+        let (sender, receiver) = bounded::<UrlRequest>(1);
+        thread::spawn(move || {
+            for (hwnd, reply) in receiver {
+                // The worker may have stopped waiting - the result is dropped then
+                let _ = reply.send(read_active_url(HWND(hwnd as *mut _)));
+            }
+        });
+        sender
+    });
+
+    let (reply, result) = bounded(1);
+    if lookup.try_send((hwnd.0 as usize, reply)).is_err() {
+        debug!("Browser URL lookup still busy - event logged without URL");
+        return None;
+    }
+    result.recv_timeout(URL_TIMEOUT).unwrap_or_else(|_| {
+        debug!("Browser URL lookup timed out");
+        None
+    })
+}
+
+/// Reads the URL of the active tab (text of the first edit field = address bar)
+fn read_active_url(hwnd: HWND) -> Option<String> {
+    let automation = automation()?;
+    unsafe {
+        let window = automation.ElementFromHandle(hwnd).ok()?;
+        let condition = automation
+            .CreatePropertyCondition(UIA_ControlTypePropertyId, &VARIANT::from(UIA_EditControlTypeId.0))
+            .ok()?;
+        let address_bar = window.FindFirst(TreeScope_Descendants, &condition).ok()?;
+        let value = address_bar.GetCurrentPropertyValue(UIA_ValueValuePropertyId).ok()?;
+        let url = BSTR::try_from(&value).ok()?.to_string();
+        let url = url.trim();
+        if url.is_empty() {
+            None
+        } else {
+            Some(url.to_string())
+        }
+    }
+}
+
+/// Host part of a URL ("https://accounts.google.com/signin" -> "accounts.google.com")
+pub fn url_host(url: &str) -> &str {
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or(without_scheme)
+}
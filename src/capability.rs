@@ -0,0 +1,217 @@
+//! Microphone/Camera Usage
+//!
+//! Polls the Windows capability access registry (CapabilityAccessManager\ConsentStore)
+//! to see which app uses the microphone or webcam. Every new usage is logged as MIC/CAMERA
+//! event, apps that are not on the allowed list raise a Warning alert.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+use windows::core::{PCWSTR, PWSTR};
+use windows::Win32::Foundation::ERROR_SUCCESS;
+use windows::Win32::System::Registry::{
+    RegCloseKey, RegEnumKeyExW, RegOpenKeyExW, RegQueryValueExW, HKEY, HKEY_CURRENT_USER, KEY_READ,
+};
+
+/// Poll interval
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+const CONSENT_STORE: &str = r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore";
+
+/// Monitored capabilities: (registry key, event type)
+const CAPABILITIES: &[(&str, &str)] = &[("microphone", "MIC"), ("webcam", "CAMERA")];
+
+/// Usage entry of one app
+#[derive(Debug, Clone)]
+struct AppUsage {
+    /// App name (process name or package name)
+    name: String,
+    /// EXE path (only for non-packaged apps)
+    path: String,
+    /// FILETIME of the last start/stop
+    start: u64,
+    stop: u64,
+}
+
+/// UTF-16 with null terminator
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Opens a registry key below HKCU for reading
+fn open_key(parent: HKEY, sub_key: &str) -> Option<HKEY> {
+    let sub_key = to_wide(sub_key);
+    let mut key = HKEY::default();
+    let result = unsafe { RegOpenKeyExW(parent, PCWSTR(sub_key.as_ptr()), 0, KEY_READ, &mut key) };
+    (result == ERROR_SUCCESS).then_some(key)
+}
+
+/// Names of all subkeys
+fn sub_keys(key: HKEY) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut index = 0;
+    loop {
+        let mut buffer = [0u16; 512];
+        let mut len = buffer.len() as u32;
+        let result = unsafe {
+            RegEnumKeyExW(key, index, PWSTR(buffer.as_mut_ptr()), &mut len, None, PWSTR::null(), None, None)
+        };
+        if result != ERROR_SUCCESS {
+            break;
+        }
+        names.push(String::from_utf16_lossy(&buffer[..len as usize]));
+        index += 1;
+    }
+    names
+}
+
+/// Reads a REG_QWORD value (0 if missing)
+fn read_qword(key: HKEY, name: &str) -> u64 {
+    let name = to_wide(name);
+    let mut value: u64 = 0;
+    let mut size = std::mem::size_of::<u64>() as u32;
+    let result = unsafe {
+        RegQueryValueExW(
+            key,
+            PCWSTR(name.as_ptr()),
+            None,
+            None,
+            Some(&mut value as *mut u64 as *mut u8),
+            Some(&mut size),
+        )
+    };
+    if result == ERROR_SUCCESS { value } else { 0 }
+}
+
+/// Reads start/stop of an app key
+fn read_app(parent: HKEY, key_name: &str, name: String, path: String) -> Option<AppUsage> {
+    let key = open_key(parent, key_name)?;
+    let usage = AppUsage {
+        name,
+        path,
+        start: read_qword(key, "LastUsedTimeStart"),
+        stop: read_qword(key, "LastUsedTimeStop"),
+    };
+    unsafe {
+        let _ = RegCloseKey(key);
+    }
+    Some(usage)
+}
+
+/// All apps that have used a capability (key = registry key name)
+fn read_capability(capability: &str) -> HashMap<String, AppUsage> {
+    let mut apps = HashMap::new();
+    let Some(root) = open_key(HKEY_CURRENT_USER, &format!(r"{}\{}", CONSENT_STORE, capability)) else {
+        return apps;
+    };
+
+    for key_name in sub_keys(root) {
+        if key_name == "NonPackaged" {
+            // Desktop apps: key name is the EXE path with '#' instead of '\'
+            if let Some(non_packaged) = open_key(root, &key_name) {
+                for app_key in sub_keys(non_packaged) {
+                    let path = app_key.replace('#', "\\");
+                    let name = std::path::Path::new(&path)
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_else(|| path.clone());
+                    if let Some(usage) = read_app(non_packaged, &app_key, name, path) {
+                        apps.insert(format!("NonPackaged\\{}", app_key), usage);
+                    }
+                }
+                unsafe {
+                    let _ = RegCloseKey(non_packaged);
+                }
+            }
+        } else {
+            // Store apps: "Microsoft.WindowsCamera_8wekyb3d8bbwe"
+            let name = key_name.split('_').next().unwrap_or(&key_name).to_string();
+            if let Some(usage) = read_app(root, &key_name, name, String::new()) {
+                apps.insert(key_name, usage);
+            }
+        }
+    }
+
+    unsafe {
+        let _ = RegCloseKey(root);
+    }
+    apps
+}
+
+/// Checks if an app may use microphone/webcam without alert
+fn is_allowed(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name == "pc_watcher"
+        || crate::config::get()
+            .devices
+            .allowed_apps
+            .iter()
+            .any(|a| name.contains(&a.to_lowercase()))
+}
+
+/// Logs a new usage and alerts for unexpected apps
+fn report_usage(event_type: &str, usage: &AppUsage) {
+    let in_use = usage.stop == 0;
+    let allowed = is_allowed(&usage.name);
+    let device = if event_type == "MIC" { "Microphone" } else { "Webcam" };
+
+    let severity = if allowed { Severity::Info } else { Severity::Warning };
+    info!("{} used by {} {}", device, usage.name, if in_use { "(in use)" } else { "" });
+
+    if !allowed && crate::rules::is_enabled(Rule::DeviceAccess) && !crate::config::is_whitelisted(&usage.name) {
+        warn!("!!! {} USED BY UNEXPECTED APP: {} !!!", device.to_uppercase(), usage.name);
+        crate::alert_window::set_rule_alert(
+            Rule::DeviceAccess,
+            &format!("{} ({})", usage.name, device),
+            &usage.path,
+            severity,
+        );
+        crate::sound::play_alert(Rule::DeviceAccess, severity);
+        let capture = crate::rules::capture_mode(Rule::DeviceAccess, severity);
+        crate::screenshot::capture_alert_screenshots(usage.name.clone(), event_type, capture, severity);
+    }
+
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: chrono::Local::now(),
+        event_type: event_type.to_string(),
+        severity,
+        process_name: usage.name.clone(),
+        process_path: usage.path.clone(),
+        window_title: format!("{} {}", device, if in_use { "in use" } else { "used" }),
+        ..Default::default()
+    });
+}
+
+/// Starts the polling thread
+pub fn start_monitor() {
+    if !crate::config::get().devices.enabled {
+        return;
+    }
+
+    thread::spawn(|| {
+        // Known start times (first poll only remembers, except apps currently using the device)
+        let mut known: HashMap<(&str, String), u64> = HashMap::new();
+        let mut first_poll = true;
+
+        while !crate::tray::should_exit() {
+            for &(capability, event_type) in CAPABILITIES {
+                for (key, usage) in read_capability(capability) {
+                    let previous = known.insert((capability, key), usage.start);
+                    let is_new = match previous {
+                        Some(start) => usage.start > start,
+                        None => !first_poll || usage.stop == 0,
+                    };
+                    if is_new && usage.start > 0 {
+                        report_usage(event_type, &usage);
+                    }
+                }
+            }
+            first_poll = false;
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
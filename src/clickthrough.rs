@@ -0,0 +1,152 @@
+//! Click-Through Overlay Detection
+//!
+//! Layered windows with WS_EX_TRANSPARENT let every click pass through to the window below
+//! while drawing over it - a fake login prompt on top of the real one never gets focus, so
+//! focus events do not reveal it. A periodic scan alerts once per window that covers a large
+//! part of its monitor.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use chrono::Local;
+use std::collections::HashSet;
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM, RECT};
+use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST};
+use windows::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, GetWindowLongW, GetWindowRect, IsIconic, IsWindowVisible, GWL_EXSTYLE, WS_EX_LAYERED,
+    WS_EX_TRANSPARENT,
+};
+
+/// Event type in the log
+const EVENT_TYPE: &str = "CLICKTHROUGH";
+
+/// Collects visible click-through layered windows
+unsafe extern "system" fn collect_clickthrough(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let windows = &mut *(lparam.0 as *mut Vec<HWND>);
+    let ex_style = GetWindowLongW(hwnd, GWL_EXSTYLE) as u32;
+    let click_through = WS_EX_LAYERED.0 | WS_EX_TRANSPARENT.0;
+    if ex_style & click_through == click_through && IsWindowVisible(hwnd).as_bool() && !IsIconic(hwnd).as_bool() {
+        windows.push(hwnd);
+    }
+    BOOL(1)
+}
+
+/// Share of its monitor the window covers (0-100)
+unsafe fn covered_percent(hwnd: HWND) -> u32 {
+    let mut rect = RECT::default();
+    if GetWindowRect(hwnd, &mut rect).is_err() {
+        return 0;
+    }
+    let mut monitor = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    if !GetMonitorInfoW(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST), &mut monitor).as_bool() {
+        return 0;
+    }
+    let screen = monitor.rcMonitor;
+    let width = (rect.right.min(screen.right) - rect.left.max(screen.left)).max(0) as i64;
+    let height = (rect.bottom.min(screen.bottom) - rect.top.max(screen.top)).max(0) as i64;
+    let screen_area = ((screen.right - screen.left) as i64 * (screen.bottom - screen.top) as i64).max(1);
+    (width * height * 100 / screen_area) as u32
+}
+
+/// Checks if overlays of an app are expected (game overlays, whitelist)
+fn is_ignored(process_name: &str) -> bool {
+    let name = process_name.to_lowercase();
+    crate::config::is_whitelisted(process_name)
+        || crate::config::get()
+            .clickthrough
+            .ignored_apps
+            .iter()
+            .any(|a| name.contains(&a.to_lowercase()))
+}
+
+/// Logs the overlay and alerts, unless the rule is disabled
+fn report_overlay(hwnd: HWND, percent: u32) {
+    let info = crate::process_info::get_process_info_cached(hwnd);
+    if is_ignored(&info.process_name) {
+        return;
+    }
+
+    let severity = Severity::Warning;
+    warn!(
+        "!!! CLICK-THROUGH OVERLAY: {} - \"{}\" covers {}% of the screen !!!",
+        info.process_name, info.window_title, percent
+    );
+    if crate::rules::is_enabled(Rule::ClickThroughOverlay) {
+        crate::alert_window::set_rule_alert(
+            Rule::ClickThroughOverlay,
+            &format!("{} (click-through overlay, {}%)", info.process_name, percent),
+            &info.process_path,
+            severity,
+        );
+        crate::sound::play_alert(Rule::ClickThroughOverlay, severity);
+        let capture = crate::rules::capture_mode(Rule::ClickThroughOverlay, severity);
+        crate::screenshot::capture_alert_screenshots(info.process_name.clone(), EVENT_TYPE, capture, severity);
+    }
+
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: Local::now(),
+        event_type: EVENT_TYPE.to_string(),
+        severity,
+        process_name: info.process_name,
+        process_id: info.process_id,
+        process_path: info.process_path,
+        window_title: format!("{} ({}% of the screen)", info.window_title, percent),
+        window_class: info.window_class,
+        command_line: info.command_line,
+        parent_process_name: info.parent_process_name,
+        parent_process_id: info.parent_process_id,
+        parent_process_path: info.parent_process_path,
+        ..Default::default()
+    });
+}
+
+/// Starts the periodic scan (only if enabled in the config)
+pub fn start_monitor() {
+    let settings = crate::config::get().clickthrough;
+    if !settings.enabled {
+        return;
+    }
+    info!(
+        "Click-through overlay scan started: every {}s, from {}% of a monitor",
+        settings.interval_secs, settings.min_screen_percent
+    );
+
+    thread::spawn(move || {
+        let own_pid = std::process::id();
+        // Reported windows (again only after they disappeared)
+        let mut reported: HashSet<isize> = HashSet::new();
+
+        while !crate::tray::should_exit() {
+            let mut windows: Vec<HWND> = Vec::new();
+            unsafe {
+                let _ = EnumWindows(Some(collect_clickthrough), LPARAM(&mut windows as *mut Vec<HWND> as isize));
+            }
+
+            let mut current = HashSet::new();
+            for hwnd in windows {
+                let percent = unsafe { covered_percent(hwnd) };
+                if percent < settings.min_screen_percent {
+                    continue;
+                }
+                // Own one-line overlay is click-through by design
+                let info = crate::process_info::get_process_info_cached(hwnd);
+                if info.process_id == own_pid {
+                    continue;
+                }
+                current.insert(hwnd.0 as isize);
+                if !reported.contains(&(hwnd.0 as isize)) {
+                    report_overlay(hwnd, percent);
+                }
+            }
+            reported = current;
+
+            thread::sleep(Duration::from_secs(settings.interval_secs.max(1)));
+        }
+    });
+}
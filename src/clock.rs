@@ -0,0 +1,126 @@
+//! Clock Monitoring
+//!
+//! Wall-clock timestamps can be faked by changing the system clock. Every log entry also
+//! records the monotonic uptime (GetTickCount64, unaffected by clock changes), and clock
+//! changes are detected by comparing both sources - reported as CLOCK_CHANGED event.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use chrono::{DateTime, Local};
+use parking_lot::Mutex;
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+use windows::Win32::System::SystemInformation::GetTickCount64;
+
+/// Poll interval (WM_TIMECHANGE triggers an immediate check)
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Differences below this are timer jitter or NTP corrections
+const SKEW_TOLERANCE_MS: i64 = 5_000;
+
+lazy_static::lazy_static! {
+    // Last reference point: (wall clock, uptime in ms)
+    static ref REFERENCE: Mutex<(DateTime<Local>, u64)> = Mutex::new((Local::now(), uptime_ms()));
+}
+
+/// Milliseconds since boot (monotonic, includes sleep)
+pub fn uptime_ms() -> u64 {
+    unsafe { GetTickCount64() }
+}
+
+/// Formats an uptime as "1d 02:03:04.567"
+pub fn format_uptime(uptime_ms: u64) -> String {
+    let secs = uptime_ms / 1000;
+    let (days, hours, minutes, seconds) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    let time = format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, seconds, uptime_ms % 1000);
+    if days > 0 {
+        format!("{}d {}", days, time)
+    } else {
+        time
+    }
+}
+
+/// Formats a clock difference as "+2h 03m 10s"
+fn format_skew(skew_ms: i64) -> String {
+    let sign = if skew_ms < 0 { '-' } else { '+' };
+    let secs = skew_ms.unsigned_abs() / 1000;
+    if secs >= 3600 {
+        format!("{}{}h {:02}m {:02}s", sign, secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}{}m {:02}s", sign, secs / 60, secs % 60)
+    }
+}
+
+/// Compares wall clock and uptime with the last reference point
+fn check() {
+    let now = Local::now();
+    let uptime = uptime_ms();
+
+    let mut reference = REFERENCE.lock();
+    let (last_wall, last_uptime) = *reference;
+    *reference = (now, uptime);
+    drop(reference);
+
+    let expected = last_wall + chrono::Duration::milliseconds(uptime.saturating_sub(last_uptime) as i64);
+    let skew_ms = (now - expected).num_milliseconds();
+    if skew_ms.abs() >= SKEW_TOLERANCE_MS {
+        report_change(expected, now, skew_ms);
+    }
+}
+
+/// Logs a detected clock change (and alerts, unless the rule is disabled)
+fn report_change(expected: DateTime<Local>, now: DateTime<Local>, skew_ms: i64) {
+    let skew = format_skew(skew_ms);
+    warn!(
+        "!!! SYSTEM CLOCK CHANGED by {}: expected {}, now {} !!!",
+        skew,
+        expected.format("%Y-%m-%d %H:%M:%S"),
+        now.format("%Y-%m-%d %H:%M:%S")
+    );
+
+    let severity = Severity::Warning;
+    if crate::rules::is_enabled(Rule::ClockChange) {
+        crate::alert_window::set_rule_alert(
+            Rule::ClockChange,
+            &format!("System clock changed ({})", skew),
+            "",
+            severity,
+        );
+        crate::sound::play_alert(Rule::ClockChange, severity);
+        let capture = crate::rules::capture_mode(Rule::ClockChange, severity);
+        crate::screenshot::capture_alert_screenshots("clock".to_string(), "CLOCK_CHANGED", capture, severity);
+    }
+
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: now,
+        event_type: "CLOCK_CHANGED".to_string(),
+        severity,
+        process_name: "System clock".to_string(),
+        window_title: format!(
+            "Changed by {} (expected {}, now {})",
+            skew,
+            expected.format("%Y-%m-%d %H:%M:%S"),
+            now.format("%Y-%m-%d %H:%M:%S")
+        ),
+        ..Default::default()
+    });
+}
+
+/// Called on WM_TIMECHANGE (someone set the clock)
+pub fn on_time_change() {
+    info!("WM_TIMECHANGE received");
+    check();
+}
+
+/// Starts the polling thread (catches changes without WM_TIMECHANGE, e.g. while the GUI hangs)
+pub fn start_monitor() {
+    lazy_static::initialize(&REFERENCE);
+    thread::spawn(|| {
+        while !crate::tray::should_exit() {
+            thread::sleep(CHECK_INTERVAL);
+            check();
+        }
+    });
+}
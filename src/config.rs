@@ -0,0 +1,1672 @@
+//! Configuration
+//!
+//! Settings stored as JSON next to the EXE (pcwatcher_config.json).
+//! Missing fields fall back to defaults, so older files keep working.
+
+use crate::audio::AudioSource;
+use crate::event_hook::EventBackend;
+use crate::hash_lists::HashListKind;
+use crate::notification::Severity;
+use crate::overlay::Corner;
+use crate::screenshot::CaptureMode;
+use parking_lot::{RwLock, RwLockReadGuard};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{error, info, warn};
+
+/// Application configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Process names that never trigger alerts (case-insensitive, without .exe)
+    pub whitelist: Vec<String>,
+    /// Temporarily muted processes (normalized name -> end of the mute as unix time)
+    pub muted_until: HashMap<String, i64>,
+    /// Process names shown in the watch panel (events are at least Warning)
+    pub watched_processes: Vec<String>,
+    /// Capture behavior per severity (used when a rule has no own setting)
+    pub capture: CaptureConfig,
+    /// Per-rule settings (key = rule name, e.g. "focus_without_click")
+    pub rules: HashMap<String, RuleConfig>,
+    /// Disk usage limits for logs and screenshots
+    pub storage: StorageConfig,
+    /// Event database settings
+    pub database: DatabaseConfig,
+    /// Name of the last applied profile (shown in the tray menu)
+    pub profile: Option<String>,
+    /// Offscreen decoy window
+    pub honeypot: HoneypotConfig,
+    /// Decoy files (relative paths are relative to the user profile)
+    pub honeytokens: Vec<String>,
+    /// Input rate telemetry (counts only, never key values)
+    pub telemetry: TelemetryConfig,
+    /// Microphone/webcam usage monitoring
+    pub devices: DevicesConfig,
+    /// Content changes of unfocused windows (opt-in)
+    pub content_watch: ContentWatchConfig,
+    /// Source of window events ("win_event" or "etw")
+    pub event_backend: EventBackend,
+    /// Monitoring gap detection
+    pub gaps: GapsConfig,
+    /// Defender verdict for executables of Critical alerts (opt-in)
+    pub reputation: ReputationConfig,
+    /// VirusTotal hash lookup for executables of alerts (opt-in, needs own API key)
+    pub virustotal: VirusTotalConfig,
+    /// Alert snooze while full-screen apps are active
+    pub snooze: SnoozeConfig,
+    /// One-line click-through alert over full-screen apps (opt-in)
+    pub overlay: OverlayConfig,
+    /// Alert sounds per severity
+    pub sounds: SoundConfig,
+    /// How long alerts stay in the header per severity
+    pub alert_duration: AlertDurationConfig,
+    /// Discord webhook with rich embeds (opt-in)
+    pub discord: DiscordConfig,
+    /// MQTT broker for home automation (opt-in)
+    pub mqtt: MqttConfig,
+    /// ntfy push notifications (opt-in)
+    pub ntfy: NtfyConfig,
+    /// Gotify push notifications (opt-in)
+    pub gotify: GotifyConfig,
+    /// Warning alerts as periodic summary instead of single notifications
+    pub digest: DigestConfig,
+    /// Central server mode (`pc_watcher server`)
+    pub server: ServerConfig,
+    /// Forwarding of the events to a central server (opt-in)
+    pub forwarder: ForwarderConfig,
+    /// Events to a syslog server over TLS (opt-in)
+    pub syslog: SyslogConfig,
+    /// Periodic test of the event pipeline
+    pub selftest: SelfTestConfig,
+    /// Caps of in-memory buffers and caches
+    pub memory: MemoryConfig,
+    /// Window classes whose events are dropped at the hook (noise of chatty applications)
+    pub window_class_filter: WindowClassFilter,
+    /// Windows that make themselves always-on-top
+    pub topmost: TopmostConfig,
+    /// Scan for click-through overlays covering large screen areas
+    pub clickthrough: ClickThroughConfig,
+    /// Reaction to repeated focus steals
+    pub focus_guard: FocusGuardConfig,
+    /// New services and drivers (SCM polling)
+    pub services: ServicesConfig,
+    /// New scheduled tasks
+    pub tasks: TasksConfig,
+    /// Run keys, Startup folders and WMI subscriptions
+    pub autoruns: AutorunsConfig,
+    /// Registry keys watched for changes
+    pub registry_watch: RegistryWatchConfig,
+    /// Watched files and folders (relative paths are relative to the user profile)
+    pub file_watch: Vec<FileWatch>,
+    /// New executables and scripts in the Downloads folder
+    pub downloads: DownloadsConfig,
+    /// Network connects/disconnects in the timeline
+    pub network: NetworkConfig,
+    /// Sleep/resume/display/lid events in the timeline
+    pub power: PowerConfig,
+    /// Programs for opening the log, folders and images
+    pub open_with: OpenWithConfig,
+    /// Grouping of related events into incidents
+    pub incidents: IncidentConfig,
+    /// Process detections (own or imported with `pc_watcher sigma import`)
+    pub detections: Vec<DetectionRule>,
+    /// YARA scan of executables of Critical alerts (opt-in, needs the `yara` build feature)
+    pub yara: YaraConfig,
+    /// SHA-256 allow/deny lists of executables (local and remote feeds)
+    pub hash_lists: HashListConfig,
+    /// Suspicious executable locations and file names
+    pub path_heuristics: PathHeuristicsConfig,
+    /// Processes disguised as Windows binaries
+    pub masquerade: MasqueradeConfig,
+    /// Unsigned DLLs from user-writable folders in processes of Critical alerts
+    pub module_scan: ModuleScanConfig,
+    /// Labels attached to matching events ("lolbin", "night-activity", "usb")
+    pub tag_rules: Vec<TagRule>,
+    /// Local web dashboard (live events, timeline, incidents, captures)
+    pub web_ui: WebUiConfig,
+    /// gRPC control API for remote management tools (opt-in)
+    pub grpc: GrpcConfig,
+    /// Edge snapping, docking, collapsed view, opacity and click-through of the alert window
+    pub alert_window: AlertWindowConfig,
+    /// Sources of process information
+    pub process_info: ProcessInfoConfig,
+    /// Levels of the app log (app.log / console), per module
+    pub logging: LoggingConfig,
+    /// Instances per user and Windows session
+    pub instance: InstanceConfig,
+}
+
+/// Default capture behavior per alert severity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureConfig {
+    pub warning: CaptureMode,
+    pub critical: CaptureMode,
+    /// Webcam frame on Critical alerts (opt-in)
+    pub webcam_on_critical: bool,
+    /// Audio clip on Critical alerts (opt-in)
+    pub audio_on_critical: AudioSource,
+    /// Length of the audio clip in seconds
+    pub audio_seconds: u32,
+    /// Caption bar with timestamp, host, event and process below saved screenshots
+    pub caption: bool,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        CaptureConfig {
+            warning: CaptureMode::Screenshot,
+            critical: CaptureMode::ScreenshotVideo,
+            webcam_on_critical: false,
+            audio_on_critical: AudioSource::None,
+            audio_seconds: 10,
+            caption: true,
+        }
+    }
+}
+
+impl CaptureConfig {
+    /// Capture behavior for a severity (Info never captures)
+    pub fn for_severity(&self, severity: Severity) -> CaptureMode {
+        match severity {
+            Severity::Info => CaptureMode::None,
+            Severity::Warning => self.warning,
+            Severity::Critical => self.critical,
+        }
+    }
+}
+
+/// Settings of a single detection rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleConfig {
+    /// Rule raises alerts (disabled rules are logged only)
+    pub enabled: bool,
+    /// Capture behavior (None = severity default)
+    pub capture: Option<CaptureMode>,
+    /// Alert sound (None = severity default, "none" = silent)
+    pub sound: Option<String>,
+    /// Alert header text, `{message}` = alert text (None = "!! {message} !!")
+    pub header: Option<String>,
+    /// Alert header color as "#RRGGBB" (None = severity color)
+    pub color: Option<String>,
+    /// Seconds the alert stays in the header (None = severity default, 0 = until acknowledged)
+    pub duration_secs: Option<u64>,
+}
+
+impl Default for RuleConfig {
+    fn default() -> Self {
+        RuleConfig {
+            enabled: true,
+            capture: None,
+            sound: None,
+            header: None,
+            color: None,
+            duration_secs: None,
+        }
+    }
+}
+
+/// Disk usage limits (log folder incl. screenshots and recordings)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Maximum size of the log folder in MB (oldest data is pruned, 0 = no limit)
+    pub max_size_mb: u64,
+    /// Warning when free disk space drops below this value in MB (0 = no warning)
+    pub min_free_disk_mb: u64,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            max_size_mb: 2048,
+            min_free_disk_mb: 1024,
+        }
+    }
+}
+
+/// Event database settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// Events older than this are deleted during maintenance (0 = keep forever)
+    pub retention_days: u32,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig { retention_days: 30 }
+    }
+}
+
+/// Honeypot decoy window (disabled by default)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HoneypotConfig {
+    pub enabled: bool,
+    /// Window title shown to snooping tools
+    pub title: String,
+}
+
+impl Default for HoneypotConfig {
+    fn default() -> Self {
+        HoneypotConfig {
+            enabled: false,
+            title: "passwords.txt - Notepad".to_string(),
+        }
+    }
+}
+
+/// Input rate telemetry settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    pub enabled: bool,
+    /// Minimum key presses per minute for a spike alert (0 = no spike alerts)
+    pub spike_keys_per_minute: u64,
+    /// Spike = this many times the average of the last 30 active minutes
+    pub spike_factor: f64,
+    /// Apps where fast typing is normal (process names without .exe)
+    pub interactive_apps: Vec<String>,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        TelemetryConfig {
+            enabled: false,
+            spike_keys_per_minute: 400,
+            spike_factor: 3.0,
+            interactive_apps: [
+                "winword", "excel", "powerpnt", "outlook", "notepad", "notepad++", "code",
+                "devenv", "chrome", "firefox", "msedge", "WindowsTerminal", "Teams", "Discord",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        }
+    }
+}
+
+/// Microphone/webcam usage monitoring
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DevicesConfig {
+    pub enabled: bool,
+    /// Apps that may use microphone/webcam without alert (part of the name, case-insensitive)
+    pub allowed_apps: Vec<String>,
+}
+
+impl Default for DevicesConfig {
+    fn default() -> Self {
+        DevicesConfig {
+            enabled: true,
+            allowed_apps: ["Teams", "zoom", "Discord", "Skype", "WindowsCamera", "obs64", "SoundRecorder"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Sampler for content changes of unfocused windows (disabled by default)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentWatchConfig {
+    pub enabled: bool,
+    /// Seconds between two samples
+    pub interval_secs: u64,
+    /// Number of topmost windows sampled per round
+    pub max_windows: usize,
+    /// Share of the interval that may be spent capturing (1-100)
+    pub max_cpu_percent: u8,
+    /// Consecutive changed samples that raise an alert
+    pub changes_to_alert: u32,
+    /// Apps whose content changes constantly (part of the name, case-insensitive)
+    pub ignored_apps: Vec<String>,
+}
+
+impl Default for ContentWatchConfig {
+    fn default() -> Self {
+        ContentWatchConfig {
+            enabled: false,
+            interval_secs: 5,
+            max_windows: 5,
+            max_cpu_percent: 2,
+            changes_to_alert: 6,
+            ignored_apps: ["vlc", "wmplayer", "mpc-hc", "mpv", "Video.UI", "Spotify", "obs64", "Taskmgr"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Monitoring gap detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GapsConfig {
+    /// Unexpected gaps (watcher not running while the machine was on) from this length are Warnings
+    pub warn_minutes: u64,
+}
+
+impl Default for GapsConfig {
+    fn default() -> Self {
+        GapsConfig { warn_minutes: 15 }
+    }
+}
+
+/// Defender lookup for the executable behind Critical alerts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReputationConfig {
+    pub enabled: bool,
+    /// Scans taking longer are cancelled (verdict "timeout")
+    pub timeout_secs: u64,
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        ReputationConfig {
+            enabled: false,
+            timeout_secs: 60,
+        }
+    }
+}
+
+/// VirusTotal lookup (only the SHA-256 is sent, never the file)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VirusTotalConfig {
+    pub enabled: bool,
+    pub api_key: String,
+    /// Cached verdicts are reused for this many days
+    pub cache_days: u32,
+}
+
+impl Default for VirusTotalConfig {
+    fn default() -> Self {
+        VirusTotalConfig {
+            enabled: false,
+            api_key: String::new(),
+            cache_days: 7,
+        }
+    }
+}
+
+/// Alert snooze (manual "Do not disturb" is in the tray menu)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnoozeConfig {
+    /// Queue visual alerts while a full-screen app or presentation is active
+    pub on_fullscreen: bool,
+}
+
+impl Default for SnoozeConfig {
+    fn default() -> Self {
+        SnoozeConfig { on_fullscreen: true }
+    }
+}
+
+/// Overlay alert shown while a full-screen app is active
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayConfig {
+    pub enabled: bool,
+    /// "top_right", "top_left", "bottom_right" or "bottom_left"
+    pub corner: Corner,
+    /// How long the line stays visible
+    pub seconds: u32,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        OverlayConfig {
+            enabled: false,
+            corner: Corner::TopRight,
+            seconds: 5,
+        }
+    }
+}
+
+/// Alert sounds: system sound alias or WAV file ("none" = silent)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundConfig {
+    pub warning: String,
+    pub critical: String,
+    /// Global mute (tray menu)
+    pub muted: bool,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        SoundConfig {
+            warning: "SystemExclamation".to_string(),
+            critical: "SystemHand".to_string(),
+            muted: false,
+        }
+    }
+}
+
+impl SoundConfig {
+    /// Sound for a severity (Info is always silent)
+    pub fn for_severity(&self, severity: Severity) -> String {
+        match severity {
+            Severity::Info => String::new(),
+            Severity::Warning => self.warning.clone(),
+            Severity::Critical => self.critical.clone(),
+        }
+    }
+}
+
+/// Seconds an alert stays in the header (0 = until acknowledged)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertDurationConfig {
+    pub warning_secs: u64,
+    pub critical_secs: u64,
+}
+
+impl Default for AlertDurationConfig {
+    fn default() -> Self {
+        AlertDurationConfig { warning_secs: 5, critical_secs: 5 }
+    }
+}
+
+impl AlertDurationConfig {
+    /// Duration for a severity (Info alerts use the Warning duration)
+    pub fn for_severity(&self, severity: Severity) -> u64 {
+        match severity {
+            Severity::Info | Severity::Warning => self.warning_secs,
+            Severity::Critical => self.critical_secs,
+        }
+    }
+}
+
+/// Discord channel webhook for alerts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiscordConfig {
+    pub enabled: bool,
+    /// https://discord.com/api/webhooks/<id>/<token>
+    pub webhook_url: String,
+    /// Events below this severity are not posted
+    pub min_severity: Severity,
+    /// First screenshot of the alert as embed thumbnail
+    pub attach_screenshot: bool,
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        DiscordConfig {
+            enabled: false,
+            webhook_url: String::new(),
+            min_severity: Severity::Warning,
+            attach_screenshot: true,
+        }
+    }
+}
+
+/// MQTT broker connection and topics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    pub enabled: bool,
+    pub host: String,
+    /// 1883, usually 8883 with TLS
+    pub port: u16,
+    pub tls: bool,
+    /// PEM file with own certificate and private key for mTLS (empty = none)
+    pub client_certificate: String,
+    /// SHA-256 fingerprints of accepted broker certificates (empty = system validation)
+    pub pinned_sha256: Vec<String>,
+    /// Empty = anonymous
+    pub username: String,
+    pub password: String,
+    /// "{computer}" is replaced with the computer name
+    pub topic_prefix: String,
+    /// 0 (at most once) or 1 (at least once)
+    pub qos: u8,
+    /// Every event to <prefix>/event/<type>, not only alerts
+    pub publish_events: bool,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        MqttConfig {
+            enabled: false,
+            host: String::new(),
+            port: 1883,
+            tls: false,
+            client_certificate: String::new(),
+            pinned_sha256: Vec::new(),
+            username: String::new(),
+            password: String::new(),
+            topic_prefix: "pc_watcher/{computer}".to_string(),
+            qos: 0,
+            publish_events: false,
+        }
+    }
+}
+
+/// ntfy server and topics per severity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NtfyConfig {
+    pub enabled: bool,
+    /// https://ntfy.sh or own server
+    pub server: String,
+    /// Topic for Warning alerts (empty = not sent)
+    pub warning_topic: String,
+    /// Topic for Critical alerts (empty = not sent)
+    pub critical_topic: String,
+    /// Access token for protected topics (empty = none)
+    pub token: String,
+}
+
+impl Default for NtfyConfig {
+    fn default() -> Self {
+        NtfyConfig {
+            enabled: false,
+            server: "https://ntfy.sh".to_string(),
+            warning_topic: String::new(),
+            critical_topic: String::new(),
+            token: String::new(),
+        }
+    }
+}
+
+/// Gotify server and application tokens per severity
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GotifyConfig {
+    pub enabled: bool,
+    /// e.g. https://gotify.example.com
+    pub server: String,
+    /// Application token for Warning alerts (empty = not sent)
+    pub warning_token: String,
+    /// Application token for Critical alerts (empty = not sent)
+    pub critical_token: String,
+}
+
+/// Digest mode for the notification sinks (Critical alerts stay immediate)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestConfig {
+    pub enabled: bool,
+    /// One summary every N minutes (only if alerts were collected)
+    pub interval_minutes: u64,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        DigestConfig { enabled: false, interval_minutes: 60 }
+    }
+}
+
+/// Central server that collects the events of several watchers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Address for the watcher connections
+    pub listen: String,
+    /// Address of the web dashboard (localhost = only on the server itself)
+    pub dashboard: String,
+    /// PEM file with server certificate and private key (empty = no TLS)
+    pub tls_certificate: String,
+    /// Auth token per machine name (required unless allow_insecure)
+    pub tokens: HashMap<String, String>,
+    /// SHA-256 fingerprints of accepted client certificates (mTLS, empty = no client certificate required)
+    pub client_pins: Vec<String>,
+    /// Password of the dashboard (HTTP basic auth, any user name)
+    pub dashboard_token: String,
+    /// Allows running without TLS certificate / tokens (test setups only)
+    pub allow_insecure: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            listen: "0.0.0.0:7420".to_string(),
+            dashboard: "127.0.0.1:7421".to_string(),
+            tls_certificate: String::new(),
+            tokens: HashMap::new(),
+            client_pins: Vec::new(),
+            dashboard_token: String::new(),
+            allow_insecure: false,
+        }
+    }
+}
+
+/// Connection of this watcher to a central server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForwarderConfig {
+    pub enabled: bool,
+    /// host:port of the server
+    pub server: String,
+    pub tls: bool,
+    /// PEM file with own certificate and private key for mTLS (empty = none)
+    pub client_certificate: String,
+    /// SHA-256 fingerprints of accepted server certificates (empty = system validation)
+    pub pinned_sha256: Vec<String>,
+    /// Name on the server (empty = computer name)
+    pub machine_name: String,
+    /// Token of this machine from the server's tokens list
+    pub token: String,
+    /// Allows sending without TLS (test setups only)
+    pub allow_insecure: bool,
+}
+
+impl Default for ForwarderConfig {
+    fn default() -> Self {
+        ForwarderConfig {
+            enabled: false,
+            server: String::new(),
+            tls: true,
+            client_certificate: String::new(),
+            pinned_sha256: Vec::new(),
+            machine_name: String::new(),
+            token: String::new(),
+            allow_insecure: false,
+        }
+    }
+}
+
+/// Syslog server (RFC 5424 messages over TLS, RFC 5425)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyslogConfig {
+    pub enabled: bool,
+    /// host:port of the server (6514 = syslog over TLS)
+    pub server: String,
+    pub tls: bool,
+    /// PEM file with own certificate and private key for mTLS (empty = none)
+    pub client_certificate: String,
+    /// SHA-256 fingerprints of accepted server certificates (empty = system validation)
+    pub pinned_sha256: Vec<String>,
+    /// Events below this severity are not sent
+    pub min_severity: Severity,
+    /// Allows sending without TLS (test setups only)
+    pub allow_insecure: bool,
+}
+
+impl Default for SyslogConfig {
+    fn default() -> Self {
+        SyslogConfig {
+            enabled: false,
+            server: String::new(),
+            tls: true,
+            client_certificate: String::new(),
+            pinned_sha256: Vec::new(),
+            min_severity: Severity::Info,
+            allow_insecure: false,
+        }
+    }
+}
+
+/// Periodic pipeline self-test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SelfTestConfig {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    /// Time the probe has to reach the log and the GUI
+    pub deadline_seconds: u64,
+}
+
+impl Default for SelfTestConfig {
+    fn default() -> Self {
+        SelfTestConfig { enabled: true, interval_minutes: 60, deadline_seconds: 10 }
+    }
+}
+
+/// Limits for long-running sessions (GUI buffers and caches)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryConfig {
+    /// Cached process lookups (oldest are evicted)
+    pub process_cache_entries: usize,
+    /// Cached program icons (oldest are destroyed)
+    pub icon_cache_entries: usize,
+    /// Larger screenshots are not kept for the preview
+    pub screenshot_max_mb: usize,
+    /// Details text per GUI log entry (the log file keeps everything)
+    pub gui_details_max_chars: usize,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        MemoryConfig {
+            process_cache_entries: 256,
+            icon_cache_entries: 50,
+            screenshot_max_mb: 64,
+            gui_details_max_chars: 8192,
+        }
+    }
+}
+
+/// Window class of a filter rule, optionally only for one process
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct WindowClassRule {
+    /// Class name (case-insensitive, trailing `*` matches a prefix)
+    pub class: String,
+    /// Only windows of this process (empty = all processes)
+    pub process: String,
+}
+
+impl WindowClassRule {
+    fn new(class: &str) -> Self {
+        WindowClassRule { class: class.to_string(), process: String::new() }
+    }
+
+    fn matches_class(&self, class: &str) -> bool {
+        let pattern = self.class.to_lowercase();
+        let class = class.to_lowercase();
+        match pattern.strip_suffix('*') {
+            Some(prefix) => class.starts_with(prefix),
+            None => class == pattern,
+        }
+    }
+
+    fn matches_process(&self, process_name: &str) -> bool {
+        self.process.is_empty() || normalize_process_name(&self.process) == normalize_process_name(process_name)
+    }
+}
+
+/// Denied window classes are dropped before the event queue, allowed ones are exceptions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowClassFilter {
+    pub deny: Vec<WindowClassRule>,
+    pub allow: Vec<WindowClassRule>,
+}
+
+impl Default for WindowClassFilter {
+    fn default() -> Self {
+        WindowClassFilter {
+            // Tooltips and input method windows appear with every hover / keystroke
+            deny: vec![
+                WindowClassRule::new("tooltips_class32"),
+                WindowClassRule::new("IME"),
+                WindowClassRule::new("MSCTFIME UI"),
+            ],
+            allow: Vec::new(),
+        }
+    }
+}
+
+/// Reaction to windows that become TOPMOST (rule topmost_window)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct TopmostConfig {
+    /// Demote them to normal windows right away (off by default, some tools need topmost)
+    pub auto_demote: bool,
+}
+
+/// Click-through overlay scan (layered + WS_EX_TRANSPARENT windows)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClickThroughConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// Alert from this share of a monitor covered (0-100)
+    pub min_screen_percent: u32,
+    /// Apps with expected overlays (substring of the process name, e.g. game overlays)
+    pub ignored_apps: Vec<String>,
+}
+
+impl Default for ClickThroughConfig {
+    fn default() -> Self {
+        ClickThroughConfig { enabled: true, interval_secs: 5, min_screen_percent: 25, ignored_apps: Vec::new() }
+    }
+}
+
+/// Focus-steal prevention
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusGuardConfig {
+    /// Lock SetForegroundWindow after repeated steals by the same app (opt-in)
+    pub lock_foreground: bool,
+    /// Steals within window_minutes that count as repeated
+    pub steals_to_lock: u32,
+    pub window_minutes: u64,
+    /// Apps minimized whenever they take the focus without a click (GUI: right-click a row)
+    pub blocked_apps: Vec<String>,
+}
+
+impl Default for FocusGuardConfig {
+    fn default() -> Self {
+        FocusGuardConfig { lock_foreground: false, steals_to_lock: 3, window_minutes: 5, blocked_apps: Vec::new() }
+    }
+}
+
+/// Service and driver monitoring (rule service_installed)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServicesConfig {
+    pub enabled: bool,
+    /// Seconds between two snapshots of the Service Control Manager
+    pub interval_secs: u64,
+    /// Expected services/drivers (service name, e.g. of an updater)
+    pub ignored: Vec<String>,
+}
+
+impl Default for ServicesConfig {
+    fn default() -> Self {
+        ServicesConfig { enabled: true, interval_secs: 10, ignored: Vec::new() }
+    }
+}
+
+/// Scheduled task monitoring (rule scheduled_task)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TasksConfig {
+    pub enabled: bool,
+    /// Expected tasks (substring of the task path, e.g. GoogleUpdateTask)
+    pub ignored: Vec<String>,
+}
+
+impl Default for TasksConfig {
+    fn default() -> Self {
+        TasksConfig { enabled: true, ignored: Vec::new() }
+    }
+}
+
+/// Autorun entry monitoring (rule autorun)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutorunsConfig {
+    pub enabled: bool,
+    /// Seconds between two snapshots
+    pub interval_secs: u64,
+    /// Also read permanent WMI event consumers (root\subscription)
+    pub wmi: bool,
+    /// Expected entries (substring of the location or the command)
+    pub ignored: Vec<String>,
+}
+
+impl Default for AutorunsConfig {
+    fn default() -> Self {
+        AutorunsConfig { enabled: true, interval_secs: 30, wmi: true, ignored: Vec::new() }
+    }
+}
+
+/// Watched registry key (rule registry_change)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct RegistryWatch {
+    /// Full key path with hive (HKLM, HKCU, HKU or HKCR), e.g. HKLM\SOFTWARE\...\Winlogon
+    pub key: String,
+    /// Also watch all subkeys
+    pub subtree: bool,
+    /// Severity of the alert on a change
+    pub severity: Severity,
+    /// Only these value names (case-insensitive, empty = all values)
+    pub values: Vec<String>,
+}
+
+impl RegistryWatch {
+    fn new(key: &str, subtree: bool, severity: Severity) -> Self {
+        RegistryWatch { key: key.to_string(), subtree, severity, values: Vec::new() }
+    }
+
+    /// Checks if a change of a value is reported (value name without subkey path)
+    pub fn watches_value(&self, name: &str) -> bool {
+        self.values.is_empty() || self.values.iter().any(|v| v.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Registry key watch list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegistryWatchConfig {
+    pub enabled: bool,
+    pub keys: Vec<RegistryWatch>,
+}
+
+impl Default for RegistryWatchConfig {
+    fn default() -> Self {
+        RegistryWatchConfig {
+            enabled: true,
+            keys: vec![
+                // Debugger hijacks (run another program instead of the real one)
+                RegistryWatch::new(
+                    r"HKLM\SOFTWARE\Microsoft\Windows NT\CurrentVersion\Image File Execution Options",
+                    true,
+                    Severity::Critical,
+                ),
+                RegistryWatch::new(r"HKLM\SOFTWARE\Microsoft\Windows NT\CurrentVersion\SilentProcessExit", true, Severity::Critical),
+                // Shell / Userinit replacement
+                RegistryWatch {
+                    values: ["Shell", "Userinit", "Taskman", "AppSetup"].iter().map(|v| v.to_string()).collect(),
+                    ..RegistryWatch::new(r"HKLM\SOFTWARE\Microsoft\Windows NT\CurrentVersion\Winlogon", false, Severity::Critical)
+                },
+                RegistryWatch::new(r"HKLM\SOFTWARE\Policies\Microsoft\Windows Defender", true, Severity::Warning),
+                RegistryWatch::new(r"HKCU\Software\Microsoft\Windows\CurrentVersion\Policies", true, Severity::Warning),
+            ],
+        }
+    }
+}
+
+/// Watched file or folder (rule file_watch)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct FileWatch {
+    pub path: String,
+    /// Also watch subfolders (folders only)
+    pub subtree: bool,
+    /// Raise a Warning alert instead of only logging
+    pub alert: bool,
+}
+
+/// Download folder monitoring (rule download)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloadsConfig {
+    pub enabled: bool,
+    /// File types that raise an alert (without dot)
+    pub extensions: Vec<String>,
+}
+
+impl Default for DownloadsConfig {
+    fn default() -> Self {
+        DownloadsConfig {
+            enabled: true,
+            extensions: [
+                "exe", "msi", "scr", "com", "bat", "cmd", "ps1", "vbs", "vbe", "js", "jse", "wsf", "hta", "lnk", "dll",
+                "jar", "iso", "img",
+            ]
+            .iter()
+            .map(|e| e.to_string())
+            .collect(),
+        }
+    }
+}
+
+/// Network change events (rule new_network)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    pub enabled: bool,
+    /// Warning when the PC joins a network for the first time
+    pub alert_new_networks: bool,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig { enabled: true, alert_new_networks: true }
+    }
+}
+
+/// Power events (rule quiet_resume)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerConfig {
+    pub enabled: bool,
+    /// Warning when someone wakes the PC during the quiet hours
+    pub alert_quiet_resume: bool,
+    /// Quiet hours (local time, start > end wraps around midnight)
+    pub quiet_start_hour: u32,
+    pub quiet_end_hour: u32,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        PowerConfig { enabled: true, alert_quiet_resume: true, quiet_start_hour: 0, quiet_end_hour: 6 }
+    }
+}
+
+/// External programs for open actions: command line with `%1` for the path (appended when
+/// missing), empty = default program of Windows
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenWithConfig {
+    /// Log file (e.g. `code` or `notepad++.exe -n999999 %1`)
+    pub log: String,
+    /// Screenshot folder and file locations
+    pub folder: String,
+    /// Screenshot images
+    pub image: String,
+}
+
+/// Incident grouping: events of an alerted process tree within the window share an incident
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IncidentConfig {
+    pub enabled: bool,
+    /// Seconds after the last member event in which related events still join (and before the
+    /// alert in which earlier events of the process are added)
+    pub window_secs: u64,
+}
+
+impl Default for IncidentConfig {
+    fn default() -> Self {
+        IncidentConfig { enabled: true, window_secs: 120 }
+    }
+}
+
+/// Process conditions of a detection: every non-empty field must match
+/// Patterns are case-insensitive, `*` matches any text (e.g. "*\\powershell.exe").
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessMatch {
+    /// Executable path matches one of the patterns
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub image: Vec<String>,
+    /// Executable path of the parent process matches one of the patterns
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parent_image: Vec<String>,
+    /// Command line matches one of the patterns
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub command_line: Vec<String>,
+    /// Command line matches all of the patterns
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub command_line_all: Vec<String>,
+    /// Command line matches one of the regular expressions (case-insensitive)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub command_line_regex: Vec<String>,
+    /// Command line has all of the flags ("-windowstyle hidden", abbreviations and /x match too)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
+    /// Command line passes a PowerShell -EncodedCommand
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub encoded_command: bool,
+    /// Decoded -EncodedCommand script matches one of the patterns
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub decoded_command: Vec<String>,
+}
+
+impl ProcessMatch {
+    /// No condition at all (would match every process)
+    pub fn is_empty(&self) -> bool {
+        self.image.is_empty()
+            && self.parent_image.is_empty()
+            && self.command_line.is_empty()
+            && self.command_line_all.is_empty()
+            && self.command_line_regex.is_empty()
+            && self.flags.is_empty()
+            && !self.encoded_command
+            && self.decoded_command.is_empty()
+    }
+}
+
+/// Detection on the process of a new window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DetectionRule {
+    pub title: String,
+    /// Origin of an imported rule (e.g. "sigma:<rule id>", replaced on re-import)
+    pub source: Option<String>,
+    pub enabled: bool,
+    pub severity: Severity,
+    /// The process matches one of these
+    pub selections: Vec<ProcessMatch>,
+    /// ...and none of these
+    pub filters: Vec<ProcessMatch>,
+    /// Tags attached to the matching event and its DETECTION entry
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Default for DetectionRule {
+    fn default() -> Self {
+        DetectionRule {
+            title: String::new(),
+            source: None,
+            enabled: true,
+            severity: Severity::Warning,
+            selections: Vec::new(),
+            filters: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+}
+
+/// YARA scanning
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct YaraConfig {
+    pub enabled: bool,
+    /// Folder with .yar/.yara files (relative = next to the EXE)
+    pub rules_dir: String,
+    /// Per rule file, 0 = no limit
+    pub timeout_secs: u64,
+}
+
+impl Default for YaraConfig {
+    fn default() -> Self {
+        YaraConfig {
+            enabled: false,
+            rules_dir: "yara_rules".to_string(),
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// Remote hash list (one SHA-256 per line or CSV, the first SHA-256 of a line counts)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HashFeed {
+    /// Shown as provenance of matches (also the cache file name)
+    pub name: String,
+    /// https:// only
+    pub url: String,
+    pub kind: HashListKind,
+}
+
+/// SHA-256 allow/deny lists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HashListConfig {
+    pub enabled: bool,
+    /// Known-good executables (no alerts)
+    pub allow: Vec<String>,
+    /// Known-bad executables (Critical alert)
+    pub deny: Vec<String>,
+    pub feeds: Vec<HashFeed>,
+    /// Feeds are downloaded again after this many hours
+    pub refresh_hours: u32,
+}
+
+impl Default for HashListConfig {
+    fn default() -> Self {
+        HashListConfig {
+            enabled: false,
+            allow: Vec::new(),
+            deny: Vec::new(),
+            feeds: Vec::new(),
+            refresh_hours: 24,
+        }
+    }
+}
+
+/// Switch and severity of one path heuristic (Info = logged only)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeuristicSetting {
+    pub enabled: bool,
+    pub severity: Severity,
+}
+
+impl Default for HeuristicSetting {
+    fn default() -> Self {
+        HeuristicSetting { enabled: true, severity: Severity::Warning }
+    }
+}
+
+impl HeuristicSetting {
+    fn with(severity: Severity) -> Self {
+        HeuristicSetting { enabled: true, severity }
+    }
+}
+
+/// Path heuristics, checked on the first window of a process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PathHeuristicsConfig {
+    pub enabled: bool,
+    /// %TEMP% and the Windows temp folder
+    pub temp: HeuristicSetting,
+    /// %APPDATA% (some legitimate apps live here, so Info by default)
+    pub appdata: HeuristicSetting,
+    pub recycle_bin: HeuristicSetting,
+    /// Mounted ISO/IMG (and real CDs)
+    pub mounted_image: HeuristicSetting,
+    /// "invoice.pdf.exe"
+    pub double_extension: HeuristicSetting,
+    /// Right-to-left override characters in the file name
+    pub rtlo: HeuristicSetting,
+}
+
+impl Default for PathHeuristicsConfig {
+    fn default() -> Self {
+        PathHeuristicsConfig {
+            enabled: true,
+            temp: HeuristicSetting::with(Severity::Warning),
+            appdata: HeuristicSetting::with(Severity::Info),
+            recycle_bin: HeuristicSetting::with(Severity::Critical),
+            mounted_image: HeuristicSetting::with(Severity::Warning),
+            double_extension: HeuristicSetting::with(Severity::Critical),
+            rtlo: HeuristicSetting::with(Severity::Critical),
+        }
+    }
+}
+
+/// Masquerading detection, checked on the first window of a process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MasqueradeConfig {
+    pub enabled: bool,
+    /// Verify the signature of system binaries in their own folder
+    pub check_signature: bool,
+    /// Names close to a system binary ("scvhost.exe", "expl0rer.exe")
+    pub lookalikes: bool,
+}
+
+impl Default for MasqueradeConfig {
+    fn default() -> Self {
+        MasqueradeConfig { enabled: true, check_signature: true, lookalikes: true }
+    }
+}
+
+/// DLL scan of the process behind a Critical alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModuleScanConfig {
+    pub enabled: bool,
+}
+
+impl Default for ModuleScanConfig {
+    fn default() -> Self {
+        ModuleScanConfig { enabled: true }
+    }
+}
+
+/// Tag for events matching all given conditions (empty conditions match everything)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagRule {
+    pub tag: String,
+    /// Chip color in the GUI ("#RRGGBB", default: derived from the tag name)
+    pub color: Option<String>,
+    /// Event type patterns (`*` = any text, e.g. "NETWORK_*")
+    pub event_types: Vec<String>,
+    /// Executable path patterns (`*` = any text)
+    pub image: Vec<String>,
+    /// Local time range in hours, may wrap around midnight (both needed, e.g. 22 and 6)
+    pub start_hour: Option<u32>,
+    pub end_hour: Option<u32>,
+    /// Only events with at least this severity
+    pub min_severity: Option<Severity>,
+}
+
+/// Local web dashboard of the watcher
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebUiConfig {
+    pub enabled: bool,
+    /// Address to listen on (localhost = only this machine)
+    pub listen: String,
+    /// PEM file with certificate and private key (empty = plain HTTP)
+    pub tls_certificate: String,
+    /// Password (HTTP basic auth, any user name) - required
+    pub token: String,
+}
+
+impl Default for WebUiConfig {
+    fn default() -> Self {
+        WebUiConfig {
+            enabled: false,
+            listen: "127.0.0.1:7422".to_string(),
+            tls_certificate: String::new(),
+            token: String::new(),
+        }
+    }
+}
+
+/// gRPC control API (proto/pc_watcher.proto)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrpcConfig {
+    pub enabled: bool,
+    /// Address to listen on (localhost = only this machine)
+    pub listen: String,
+    /// PEM file with certificate and private key (required unless allow_insecure)
+    pub tls_certificate: String,
+    /// SHA-256 fingerprints of accepted client certificates (mTLS, empty = no client certificate required)
+    pub client_pins: Vec<String>,
+    /// Bearer token of the admin (any API token works with its role)
+    pub token: String,
+    /// Allows running without TLS certificate (test setups only)
+    pub allow_insecure: bool,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        GrpcConfig {
+            enabled: false,
+            listen: "127.0.0.1:7423".to_string(),
+            tls_certificate: String::new(),
+            client_pins: Vec::new(),
+            token: String::new(),
+            allow_insecure: false,
+        }
+    }
+}
+
+/// Placement of the alert window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertWindowConfig {
+    /// Magnetic edge snapping while dragging (pixels, 0 = off)
+    pub snap_distance: i32,
+    /// Window released at an outer screen edge slides out of view and peeks out on hover
+    pub dock: bool,
+    /// Visible strip of a docked window (pixels)
+    pub dock_peek: i32,
+    /// Only the header bar is shown (double-click on the header, expands on Critical alerts)
+    pub collapsed: bool,
+    /// Opacity in percent (20-100, tray menu)
+    pub opacity: u8,
+    /// Global hotkey switching click-through on/off, e.g. "Ctrl+Shift+F12" (empty = none)
+    pub click_through_hotkey: String,
+}
+
+impl Default for AlertWindowConfig {
+    fn default() -> Self {
+        AlertWindowConfig {
+            snap_distance: 12,
+            dock: false,
+            dock_peek: 6,
+            collapsed: false,
+            opacity: 90,
+            click_through_hotkey: "Ctrl+Shift+F12".to_string(),
+        }
+    }
+}
+
+/// Process information lookup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessInfoConfig {
+    /// Ask WMI (Win32_Process) for name, path and command line when OpenProcess is denied
+    pub wmi_fallback: bool,
+}
+
+impl Default for ProcessInfoConfig {
+    fn default() -> Self {
+        ProcessInfoConfig { wmi_fallback: true }
+    }
+}
+
+/// App log levels ("error", "warn", "info", "debug", "trace"); RUST_LOG overrides them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Level of all modules without an override
+    pub level: String,
+    /// Overrides per module, e.g. "event_hook": "debug" (debug shows the pipeline spans)
+    pub modules: HashMap<String, String>,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        LoggingConfig { level: "info".to_string(), modules: HashMap::new() }
+    }
+}
+
+/// Instances per user and Windows session (other users and sessions always run their own)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstanceConfig {
+    /// A second start in the same session exits (off = it runs with its own log files)
+    pub single_instance: bool,
+}
+
+impl Default for InstanceConfig {
+    fn default() -> Self {
+        InstanceConfig { single_instance: true }
+    }
+}
+
+/// Built-in configuration profiles (selectable from the tray)
+/// Only alert/capture settings are changed - whitelist and watched processes are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// Everything alerts, richest evidence
+    Strict,
+    /// Only suspicious processes alert, few screenshots
+    Quiet,
+    /// Watched processes (e.g. games, browsers) get recordings
+    Parental,
+}
+
+impl Profile {
+    pub const ALL: [Profile; 3] = [Profile::Strict, Profile::Quiet, Profile::Parental];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Profile::Strict => "strict",
+            Profile::Quiet => "quiet",
+            Profile::Parental => "parental",
+        }
+    }
+
+    /// Applies the profile settings to a config
+    fn apply(&self, config: &mut Config) {
+        let rule = |enabled, capture| RuleConfig { enabled, capture, ..Default::default() };
+        // Sound and alert style overrides are personal taste, not part of a profile
+        let personal: Vec<(String, RuleConfig)> = config
+            .rules
+            .drain()
+            .filter(|(_, rule)| {
+                rule.sound.is_some() || rule.header.is_some() || rule.color.is_some() || rule.duration_secs.is_some()
+            })
+            .collect();
+
+        match self {
+            Profile::Strict => {
+                config.capture = CaptureConfig {
+                    warning: CaptureMode::ScreenshotVideo,
+                    critical: CaptureMode::FullDesktop,
+                    ..config.capture
+                };
+                config.database.retention_days = 90;
+            }
+            Profile::Quiet => {
+                config.capture = CaptureConfig {
+                    warning: CaptureMode::None,
+                    critical: CaptureMode::Screenshot,
+                    ..config.capture
+                };
+                config.rules.insert("focus_without_click".to_string(), rule(false, None));
+                config.database.retention_days = 14;
+            }
+            Profile::Parental => {
+                config.capture = CaptureConfig {
+                    warning: CaptureConfig::default().warning,
+                    critical: CaptureConfig::default().critical,
+                    ..config.capture
+                };
+                config.rules.insert(
+                    "watched_process".to_string(),
+                    rule(true, Some(CaptureMode::ScreenshotVideo)),
+                );
+                config.database.retention_days = 90;
+            }
+        }
+
+        for (name, old) in personal {
+            let rule = config.rules.entry(name).or_default();
+            rule.sound = old.sound;
+            rule.header = old.header;
+            rule.color = old.color;
+            rule.duration_secs = old.duration_secs;
+        }
+        config.profile = Some(self.name().to_string());
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref CONFIG: RwLock<Config> = RwLock::new(load());
+}
+
+/// Path to the configuration file
+fn get_config_path() -> PathBuf {
+    if let Ok(exe_path) = std::env::current_exe() {
+        if let Some(exe_dir) = exe_path.parent() {
+            return exe_dir.join("pcwatcher_config.json");
+        }
+    }
+    PathBuf::from("pcwatcher_config.json")
+}
+
+/// Loads the configuration (defaults if missing or invalid)
+fn load() -> Config {
+    let path = get_config_path();
+    match fs::read_to_string(&path) {
+        Ok(content) => match serde_json::from_str(&content) {
+            Ok(config) => {
+                info!("Config loaded: {}", path.display());
+                config
+            }
+            Err(e) => {
+                warn!("Invalid config file {} - using defaults: {}", path.display(), e);
+                Config::default()
+            }
+        },
+        Err(_) => Config::default(),
+    }
+}
+
+/// Saves the configuration
+fn save(config: &Config) {
+    let path = get_config_path();
+    match serde_json::to_string_pretty(config) {
+        Ok(content) => {
+            if let Err(e) = fs::write(&path, content) {
+                error!("Could not save config: {}", e);
+            }
+        }
+        Err(e) => error!("Could not serialize config: {}", e),
+    }
+}
+
+/// Returns a copy of the current configuration
+pub fn get() -> Config {
+    CONFIG.read().clone()
+}
+
+/// Memory limits (without cloning the whole configuration, used on hot paths)
+pub fn memory() -> MemoryConfig {
+    CONFIG.read().memory.clone()
+}
+
+/// Process information settings (without cloning the whole configuration)
+pub fn process_info() -> ProcessInfoConfig {
+    CONFIG.read().process_info.clone()
+}
+
+/// Borrowed configuration for hot paths (holds the read lock - do not keep it across calls that change it)
+pub fn read() -> RwLockReadGuard<'static, Config> {
+    CONFIG.read()
+}
+
+/// Changes the configuration and saves it
+pub fn update<F: FnOnce(&mut Config)>(change: F) {
+    let mut config = CONFIG.write();
+    change(&mut config);
+    save(&config);
+}
+
+/// Reads the configuration file again (edited while running), keeps the current one if invalid
+pub fn reload() -> Result<(), String> {
+    let path = get_config_path();
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let config: Config = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))?;
+    *CONFIG.write() = config;
+    info!("Config reloaded: {}", path.display());
+    crate::logger::apply_log_levels();
+    Ok(())
+}
+
+/// Normalizes a process name for comparisons (lowercase, without .exe)
+fn normalize_process_name(name: &str) -> String {
+    let lower = name.trim().to_lowercase();
+    lower.strip_suffix(".exe").unwrap_or(&lower).to_string()
+}
+
+/// Checks if a process is on the whitelist or currently muted
+pub fn is_whitelisted(process_name: &str) -> bool {
+    is_permanently_whitelisted(process_name) || is_muted(process_name)
+}
+
+/// Checks the permanent whitelist only
+fn is_permanently_whitelisted(process_name: &str) -> bool {
+    let name = normalize_process_name(process_name);
+    CONFIG.read().whitelist.iter().any(|w| normalize_process_name(w) == name)
+}
+
+/// Checks if events of a window class are dropped at the hook stage
+/// The process name is only looked up if a matching rule is process-specific
+pub fn is_class_filtered(class: &str, process_name: impl FnOnce() -> String) -> bool {
+    // Rules matching the class (usually none), copied so the lookup runs without the lock
+    let (deny, allow): (Vec<WindowClassRule>, Vec<WindowClassRule>) = {
+        let config = CONFIG.read();
+        let filter = &config.window_class_filter;
+        let matching = |rules: &[WindowClassRule]| rules.iter().filter(|r| r.matches_class(class)).cloned().collect();
+        (matching(&filter.deny), matching(&filter.allow))
+    };
+    if deny.is_empty() {
+        return false;
+    }
+    let process = if deny.iter().chain(&allow).any(|r| !r.process.is_empty()) { process_name() } else { String::new() };
+    deny.iter().any(|r| r.matches_process(&process)) && !allow.iter().any(|r| r.matches_process(&process))
+}
+
+/// Checks if alerts of a process are muted right now
+pub fn is_muted(process_name: &str) -> bool {
+    let name = normalize_process_name(process_name);
+    CONFIG.read()
+        .muted_until
+        .get(&name)
+        .is_some_and(|until| *until > chrono::Local::now().timestamp())
+}
+
+/// Mutes alerts of a process for some hours (temporary whitelist entry)
+pub fn mute_process(process_name: &str, hours: u32) {
+    if process_name.is_empty() {
+        return;
+    }
+    let name = normalize_process_name(process_name);
+    let now = chrono::Local::now().timestamp();
+    update(|config| {
+        // Expired mutes are dropped on the next change
+        config.muted_until.retain(|_, until| *until > now);
+        config.muted_until.insert(name, now + hours as i64 * 3600);
+    });
+    info!("Alerts muted for {} hours: {}", hours, process_name);
+}
+
+/// Adds a process to the whitelist (no duplicates)
+pub fn add_to_whitelist(process_name: &str) {
+    if process_name.is_empty() || is_permanently_whitelisted(process_name) {
+        return;
+    }
+    let name = process_name.to_string();
+    update(|config| config.whitelist.push(name));
+    info!("Added to whitelist: {}", process_name);
+}
+
+/// Applies a built-in profile and saves the config
+pub fn apply_profile(profile: Profile) {
+    update(|config| profile.apply(config));
+    info!("Profile applied: {}", profile.name());
+}
+
+/// Writes the current configuration to a file (for other machines)
+pub fn export_to(path: &Path) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(&*CONFIG.read())
+        .map_err(|e| format!("Could not serialize config: {}", e))?;
+    fs::write(path, content).map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    info!("Config exported: {}", path.display());
+    Ok(())
+}
+
+/// Replaces the configuration with the content of a file and saves it
+pub fn import_from(path: &Path) -> Result<(), String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let imported: Config = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))?;
+    update(|config| *config = imported);
+    info!("Config imported: {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,191 @@
+//! Window Content Watch
+//!
+//! Optional low-frequency sampler: the topmost visible windows are captured, downscaled
+//! and hashed. An unfocused window whose content keeps changing (e.g. a hidden
+//! remote-control viewer) raises a Warning alert. Sampling stays within a CPU budget.
+
+use crate::notification::Severity;
+use crate::rules::Rule;
+use image::imageops::{self, FilterType};
+use image::{GrayImage, ImageBuffer, Rgb};
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM, RECT};
+use windows::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, GetForegroundWindow, GetWindowRect, GetWindowThreadProcessId, IsIconic,
+    IsWindowVisible,
+};
+
+/// Hash side length (8x8 = 64 bit)
+const HASH_SIZE: u32 = 8;
+
+/// Window hashes differing in more bits count as changed
+const CHANGE_BITS: u32 = 6;
+
+/// Smaller windows are ignored (tooltips, tray popups)
+const MIN_WINDOW_SIZE: i32 = 150;
+
+/// State of a sampled window
+struct WindowState {
+    hash: u64,
+    /// Consecutive samples with changed content
+    changes: u32,
+    /// Alert already raised (reset when the content stays still)
+    alerted: bool,
+}
+
+/// Collects visible, not minimized top-level windows in Z-order (topmost first)
+unsafe extern "system" fn collect_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let windows = &mut *(lparam.0 as *mut Vec<HWND>);
+    if IsWindowVisible(hwnd).as_bool() && !IsIconic(hwnd).as_bool() {
+        let mut rect = RECT::default();
+        if GetWindowRect(hwnd, &mut rect).is_ok()
+            && rect.right - rect.left >= MIN_WINDOW_SIZE
+            && rect.bottom - rect.top >= MIN_WINDOW_SIZE
+        {
+            windows.push(hwnd);
+        }
+    }
+    BOOL(1)
+}
+
+/// Topmost candidate windows (without the focused window and own windows)
+fn candidate_windows(max: usize) -> Vec<HWND> {
+    let mut windows: Vec<HWND> = Vec::new();
+    unsafe {
+        let _ = EnumWindows(Some(collect_window), LPARAM(&mut windows as *mut Vec<HWND> as isize));
+    }
+
+    let foreground = unsafe { GetForegroundWindow() };
+    let own_pid = std::process::id();
+    windows
+        .into_iter()
+        .filter(|&hwnd| hwnd != foreground)
+        .filter(|&hwnd| {
+            let mut pid = 0u32;
+            unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) };
+            pid != own_pid
+        })
+        .take(max)
+        .collect()
+}
+
+/// Average hash of a capture (1 bit per cell: brighter than average)
+fn content_hash(pixels: Vec<u8>, width: i32, height: i32) -> Option<u64> {
+    let image: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_raw(width as u32, height as u32, pixels)?;
+    let gray: GrayImage = imageops::grayscale(&image);
+    let small = imageops::resize(&gray, HASH_SIZE, HASH_SIZE, FilterType::Triangle);
+
+    let sum: u32 = small.pixels().map(|p| p.0[0] as u32).sum();
+    let average = sum / (HASH_SIZE * HASH_SIZE);
+    let hash = small
+        .pixels()
+        .enumerate()
+        .filter(|(_, p)| p.0[0] as u32 > average)
+        .fold(0u64, |hash, (i, _)| hash | (1 << i));
+    Some(hash)
+}
+
+/// Checks if changes of an app are expected (media players, whitelist)
+fn is_ignored(process_name: &str) -> bool {
+    let name = process_name.to_lowercase();
+    crate::config::is_whitelisted(process_name)
+        || crate::config::get()
+            .content_watch
+            .ignored_apps
+            .iter()
+            .any(|a| name.contains(&a.to_lowercase()))
+}
+
+/// Raises the alert for a window that keeps changing in the background
+fn report_change(hwnd: HWND, samples: u32) {
+    let info = crate::process_info::get_process_info_cached(hwnd);
+    if is_ignored(&info.process_name) {
+        return;
+    }
+
+    let severity = Severity::Warning;
+    warn!(
+        "!!! UNFOCUSED WINDOW CHANGES: {} - \"{}\" ({} samples) !!!",
+        info.process_name, info.window_title, samples
+    );
+    if crate::rules::is_enabled(Rule::ContentChange) {
+        crate::alert_window::set_rule_alert(
+            Rule::ContentChange,
+            &format!("{} (content changes without focus)", info.process_name),
+            &info.process_path,
+            severity,
+        );
+        crate::sound::play_alert(Rule::ContentChange, severity);
+        let capture = crate::rules::capture_mode(Rule::ContentChange, severity);
+        crate::screenshot::capture_alert_screenshots(info.process_name.clone(), "CONTENT_CHANGE", capture, severity);
+    }
+}
+
+/// Starts the sampler thread (only if enabled in the config)
+pub fn start_monitor() {
+    let settings = crate::config::get().content_watch;
+    if !settings.enabled {
+        return;
+    }
+
+    info!(
+        "Content watch started: {} windows every {}s, max {}% CPU",
+        settings.max_windows, settings.interval_secs, settings.max_cpu_percent
+    );
+
+    thread::spawn(move || {
+        let interval = Duration::from_secs(settings.interval_secs.max(1));
+        let cpu_percent = settings.max_cpu_percent.clamp(1, 100) as u32;
+        let budget = interval * cpu_percent / 100;
+        let mut states: HashMap<isize, WindowState> = HashMap::new();
+
+        while !crate::tray::should_exit() {
+            let started = Instant::now();
+            let windows = candidate_windows(settings.max_windows);
+
+            for &hwnd in &windows {
+                // Budget used up: remaining windows wait for the next round
+                if started.elapsed() > budget {
+                    debug!("Content watch: CPU budget used up");
+                    break;
+                }
+
+                let Some(hash) = crate::screenshot::capture_window(hwnd)
+                    .ok()
+                    .and_then(|(pixels, width, height)| content_hash(pixels, width, height))
+                else {
+                    continue;
+                };
+
+                let state = states.entry(hwnd.0 as isize).or_insert(WindowState {
+                    hash,
+                    changes: 0,
+                    alerted: false,
+                });
+                if (state.hash ^ hash).count_ones() > CHANGE_BITS {
+                    state.changes += 1;
+                } else {
+                    state.changes = 0;
+                    state.alerted = false;
+                }
+                state.hash = hash;
+
+                if state.changes >= settings.changes_to_alert && !state.alerted {
+                    state.alerted = true;
+                    report_change(hwnd, state.changes);
+                }
+            }
+
+            // Forget windows that are gone or got the focus
+            states.retain(|key, _| windows.iter().any(|hwnd| hwnd.0 as isize == *key));
+
+            // Slow down if sampling took longer than the budget allows
+            let spent = started.elapsed();
+            let pause = (spent * 100 / cpu_percent).max(interval).saturating_sub(spent);
+            thread::sleep(pause);
+        }
+    });
+}
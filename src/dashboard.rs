@@ -0,0 +1,535 @@
+//! Server Dashboard
+//!
+//! Read-only web page of the central server: all machines with their last contact and the
+//! newest events of all of them in one list. Minimal HTTP/1.1, one request per connection.
+//! HTTPS with the server certificate, password via HTTP basic auth (dashboard_token or an
+//! API token). /api/events and /api/incidents return the stored events as JSON, with filters
+//! and paging; admins can reload the server configuration (POST /api/reload).
+
+use crate::api_tokens::Role;
+use crate::database::{EventQuery, MachineIncident, MachineSummary};
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::tls::{Certificate, TlsStream, Transport};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use parking_lot::Mutex;
+use rusqlite::Connection;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Events shown on the page
+const EVENT_LIMIT: usize = 200;
+
+/// Version of the JSON schema of the API (raised on incompatible changes)
+const API_SCHEMA: u32 = 1;
+
+/// Page size of the API without/with the largest `limit`
+const API_DEFAULT_LIMIT: usize = 100;
+const API_MAX_LIMIT: usize = 1000;
+
+/// Machines without contact for this long are shown as offline
+const OFFLINE_AFTER_MINUTES: i64 = 5;
+
+/// Longest request line or header line and most headers read before authentication
+const MAX_LINE_BYTES: u64 = 8 * 1024;
+const MAX_HEADERS: usize = 64;
+
+/// Serves the dashboard (blocks)
+pub fn run(address: &str, certificate: Option<Arc<Certificate>>, conn: Arc<Mutex<Connection>>) -> Result<(), String> {
+    let listener = TcpListener::bind(address).map_err(|e| format!("Cannot listen on {}: {}", address, e))?;
+    let scheme = if certificate.is_some() { "https" } else { "http" };
+    info!("Dashboard on {}://{}/", scheme, address);
+    for tcp in listener.incoming().flatten() {
+        let conn = conn.clone();
+        let certificate = certificate.clone();
+        thread::spawn(move || {
+            if let Err(e) = handle_request(tcp, certificate.as_deref(), &conn) {
+                warn!("Dashboard request failed: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Decodes standard base64 (HTTP basic auth), None on invalid input
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    let mut decoded = Vec::new();
+    for byte in text.trim_end_matches('=').bytes() {
+        let value = ALPHABET.iter().position(|&c| c == byte)? as u32;
+        bits = (bits << 6) | value;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            decoded.push((bits >> bit_count) as u8);
+        }
+    }
+    Some(decoded)
+}
+
+/// Password of an "Authorization: Basic ..." header (user name is ignored)
+fn basic_auth_password(header: &str) -> Option<String> {
+    let encoded = header.trim().strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(base64_decode(encoded.trim())?).ok()?;
+    Some(decoded.split_once(':')?.1.to_string())
+}
+
+/// Decodes %XX and + of a query value
+pub fn url_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            // Two hex digits (from_str_radix alone would also take a sign: "%+1")
+            b'%' if i + 2 < bytes.len() && bytes[i + 1..i + 3].iter().all(u8::is_ascii_hexdigit) => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                decoded.push(u8::from_str_radix(hex, 16).unwrap_or_default());
+                i += 2;
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Value of a query parameter (?name=value)
+pub fn query_param(target: &str, name: &str) -> Option<String> {
+    let query = target.split_once('?')?.1;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| url_decode(value))
+        .filter(|value| !value.is_empty())
+}
+
+/// Time of a query parameter: RFC 3339, "YYYY-MM-DD HH:MM:SS" or "YYYY-MM-DD" (local time)
+fn parse_time(value: &str) -> Option<DateTime<Local>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Local));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0))?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// Optional time parameter (Err = message for 400 Bad Request)
+fn time_param(target: &str, name: &str) -> Result<Option<DateTime<Local>>, String> {
+    query_param(target, name)
+        .map(|value| parse_time(&value).ok_or(format!("Invalid {}: {}", name, value)))
+        .transpose()
+}
+
+/// Optional numeric parameter (Err = message for 400 Bad Request)
+fn number_param<T: std::str::FromStr>(target: &str, name: &str) -> Result<Option<T>, String> {
+    query_param(target, name)
+        .map(|value| value.parse().map_err(|_| format!("Invalid {}: {}", name, value)))
+        .transpose()
+}
+
+fn limit_param(target: &str) -> Result<usize, String> {
+    Ok(number_param(target, "limit")?.unwrap_or(API_DEFAULT_LIMIT).clamp(1, API_MAX_LIMIT))
+}
+
+/// Filters of /api/events (also used by the web dashboard)
+pub fn event_query(target: &str) -> Result<EventQuery, String> {
+    let min_severity = match query_param(target, "severity").map(|s| s.to_uppercase()) {
+        None => None,
+        Some(s) if ["INFO", "WARNING", "CRITICAL"].contains(&s.as_str()) => Some(Severity::parse(&s)),
+        Some(s) => return Err(format!("Invalid severity: {}", s)),
+    };
+    Ok(EventQuery {
+        machine: query_param(target, "machine"),
+        event_type: query_param(target, "type").map(|t| t.to_uppercase()),
+        process: query_param(target, "process"),
+        min_severity,
+        tag: query_param(target, "tag"),
+        from: time_param(target, "from")?,
+        to: time_param(target, "to")?,
+        before_id: number_param(target, "before")?,
+        after_id: number_param(target, "after")?,
+        limit: limit_param(target)?,
+    })
+}
+
+/// Time range and page of /api/incidents: (from, to, offset, limit)
+type IncidentPage = (Option<DateTime<Local>>, Option<DateTime<Local>>, usize, usize);
+
+fn incident_page(target: &str) -> Result<IncidentPage, String> {
+    Ok((
+        time_param(target, "from")?,
+        time_param(target, "to")?,
+        number_param(target, "offset")?.unwrap_or(0),
+        limit_param(target)?,
+    ))
+}
+
+/// JSON of /api/events: next_before is the cursor of the next page (null = last page)
+pub fn events_json(events: &[(i64, String, LogEntry)], limit: usize) -> String {
+    let items: Vec<serde_json::Value> = events
+        .iter()
+        .map(|(id, machine, entry)| {
+            let mut json = entry.format_json();
+            json["id"] = (*id).into();
+            json["machine"] = machine.as_str().into();
+            json
+        })
+        .collect();
+    let next_before = (events.len() == limit).then(|| events.last().map(|(id, _, _)| *id)).flatten();
+    serde_json::json!({
+        "schema": API_SCHEMA,
+        "count": items.len(),
+        "events": items,
+        "next_before": next_before,
+    })
+    .to_string()
+}
+
+/// JSON of /api/incidents: next_offset is the offset of the next page (null = last page)
+fn incidents_json(incidents: &[MachineIncident], offset: usize, limit: usize) -> String {
+    let items: Vec<serde_json::Value> = incidents
+        .iter()
+        .map(|incident| {
+            serde_json::json!({
+                "machine": incident.machine,
+                "id": incident.id,
+                "started": incident.started.to_rfc3339(),
+                "last_event": incident.last_event.to_rfc3339(),
+                "severity": incident.severity.as_str(),
+                "events": incident.events,
+                "processes": incident.processes,
+            })
+        })
+        .collect();
+    let next_offset = (incidents.len() == limit).then_some(offset + limit);
+    serde_json::json!({
+        "schema": API_SCHEMA,
+        "count": items.len(),
+        "incidents": items,
+        "next_offset": next_offset,
+    })
+    .to_string()
+}
+
+fn json_error(message: &str) -> String {
+    serde_json::json!({ "schema": API_SCHEMA, "error": message }).to_string()
+}
+
+/// Method, target (/path?query), basic-auth password and origin headers of a request
+pub struct Request {
+    pub method: String,
+    pub target: String,
+    pub password: Option<String>,
+    pub host: Option<String>,
+    /// Origin header, Referer if there is none (page that sent the request)
+    pub origin: Option<String>,
+}
+
+impl Request {
+    /// Target without the query
+    pub fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or("/")
+    }
+
+    /// Sent by a page of this dashboard (or not by a browser at all)
+    /// Browsers resend basic-auth credentials on cross-site form posts and no-cors fetches, so
+    /// POSTs from another origin are rejected (CSRF)
+    pub fn is_same_origin(&self) -> bool {
+        let Some(origin) = self.origin.as_deref() else {
+            // Browsers always send Origin on POST - this is a script or the CLI
+            return true;
+        };
+        let origin_host = crate::browser::url_host(origin);
+        self.host.as_deref().is_some_and(|host| !origin_host.is_empty() && origin_host.eq_ignore_ascii_case(host))
+    }
+}
+
+/// Reads one line of the request head (longer lines are rejected, they are not buffered)
+fn read_head_line(reader: &mut BufReader<Box<dyn Transport>>) -> Result<String, String> {
+    let mut line = String::new();
+    let read = reader.by_ref().take(MAX_LINE_BYTES).read_line(&mut line).map_err(|e| e.to_string())?;
+    if read as u64 == MAX_LINE_BYTES && !line.ends_with('\n') {
+        return Err("request line or header too long".to_string());
+    }
+    Ok(line)
+}
+
+/// Accepts a connection (TLS with certificate) and reads the request line and headers
+pub fn accept(tcp: TcpStream, certificate: Option<&Certificate>) -> Result<(BufReader<Box<dyn Transport>>, Request), String> {
+    tcp.set_read_timeout(Some(Duration::from_secs(10))).map_err(|e| e.to_string())?;
+    let stream: Box<dyn Transport> = match certificate {
+        Some(certificate) => Box::new(TlsStream::accept(tcp, certificate, &[])?),
+        None => Box::new(tcp),
+    };
+    let mut reader = BufReader::new(stream);
+
+    // Request line: GET /path?query HTTP/1.1
+    let request_line = read_head_line(&mut reader)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or("/").to_string();
+
+    // Headers up to the empty line (only Authorization is needed)
+    let mut password = None;
+    let (mut host, mut origin, mut referer) = (None, None, None);
+    for count in 0.. {
+        let line = read_head_line(&mut reader)?;
+        if line.trim().is_empty() {
+            break;
+        }
+        if count >= MAX_HEADERS {
+            return Err("too many headers".to_string());
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim().to_string();
+            if name.eq_ignore_ascii_case("authorization") {
+                password = basic_auth_password(&value);
+            } else if name.eq_ignore_ascii_case("host") {
+                host = Some(value);
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = Some(value);
+            } else if name.eq_ignore_ascii_case("referer") {
+                referer = Some(value);
+            }
+        }
+    }
+    let origin = origin.or(referer);
+    Ok((reader, Request { method, target, password, host, origin }))
+}
+
+/// Writes the response and closes the connection (unauthorized = asks for the password)
+pub fn respond(stream: &mut dyn Write, status: &str, content_type: &str, body: &[u8], authorized: bool) -> Result<(), String> {
+    let challenge = if authorized { "" } else { "WWW-Authenticate: Basic realm=\"PC Watcher\"\r\n" };
+    let header = format!(
+        "HTTP/1.1 {}\r\n{}Content-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        challenge,
+        content_type,
+        body.len()
+    );
+    stream.write_all(header.as_bytes()).and_then(|_| stream.write_all(body)).map_err(|e| e.to_string())
+}
+
+fn handle_request(tcp: TcpStream, certificate: Option<&Certificate>, conn: &Mutex<Connection>) -> Result<(), String> {
+    let (mut reader, request) = accept(tcp, certificate)?;
+    let (method, target, path) = (request.method.as_str(), request.target.as_str(), request.path());
+    let dashboard_token = crate::config::get().server.dashboard_token;
+    // Without any password (allow_insecure) everyone is admin
+    let role = if dashboard_token.is_empty() && crate::api_tokens::list().is_empty() {
+        Some(Role::Admin)
+    } else {
+        crate::api_tokens::authorize(request.password.as_deref(), &dashboard_token)
+    };
+    let authorized = role.is_some();
+
+    let html = "text/html; charset=utf-8";
+    let json = "application/json; charset=utf-8";
+    let (status, content_type, body) = match (method, path) {
+        _ if !authorized => ("401 Unauthorized", html, "Login required".to_string()),
+        ("POST", "/api/reload") if role != Some(Role::Admin) => ("403 Forbidden", json, json_error("Admin role required")),
+        ("POST", _) if !request.is_same_origin() => ("403 Forbidden", json, json_error("Cross-origin request")),
+        ("POST", "/api/reload") => match crate::config::reload() {
+            Ok(()) => ("200 OK", json, serde_json::json!({ "schema": API_SCHEMA, "reloaded": true }).to_string()),
+            Err(e) => ("500 Internal Server Error", json, json_error(&e)),
+        },
+        ("GET", "/") => {
+            let machine = query_param(target, "machine");
+            let alerts_only = query_param(target, "alerts").is_some();
+            let (machines, events) = {
+                let conn = conn.lock();
+                let machines = crate::database::read_machines(&conn).map_err(|e| e.to_string())?;
+                let events = crate::database::read_machine_events(&conn, machine.as_deref(), alerts_only, EVENT_LIMIT)
+                    .map_err(|e| e.to_string())?;
+                (machines, events)
+            };
+            ("200 OK", html, render_page(&machines, &events, machine.as_deref(), alerts_only))
+        }
+        ("GET", "/api/events") => match event_query(target) {
+            Ok(query) => {
+                let events = crate::database::query_machine_events(&conn.lock(), &query).map_err(|e| e.to_string())?;
+                ("200 OK", json, events_json(&events, query.limit))
+            }
+            Err(e) => ("400 Bad Request", json, json_error(&e)),
+        },
+        ("GET", "/api/incidents") => match incident_page(target) {
+            Ok((from, to, offset, limit)) => {
+                let machine = query_param(target, "machine");
+                let incidents = crate::database::query_machine_incidents(
+                    &conn.lock(),
+                    machine.as_deref(),
+                    from.as_ref(),
+                    to.as_ref(),
+                    offset,
+                    limit,
+                )
+                .map_err(|e| e.to_string())?;
+                ("200 OK", json, incidents_json(&incidents, offset, limit))
+            }
+            Err(e) => ("400 Bad Request", json, json_error(&e)),
+        },
+        (_, p) if p.starts_with("/api/") => ("404 Not Found", json, json_error("Not found")),
+        _ => ("404 Not Found", html, "Not found".to_string()),
+    };
+
+    respond(reader.get_mut(), status, content_type, body.as_bytes(), authorized)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Link target keeping the other filter
+fn filter_link(machine: Option<&str>, alerts_only: bool) -> String {
+    let mut params = Vec::new();
+    if let Some(machine) = machine {
+        let encoded: String = machine
+            .bytes()
+            .map(|b| if b.is_ascii_alphanumeric() || b"-_.".contains(&b) { (b as char).to_string() } else { format!("%{:02X}", b) })
+            .collect();
+        params.push(format!("machine={}", encoded));
+    }
+    if alerts_only {
+        params.push("alerts=1".to_string());
+    }
+    if params.is_empty() { "/".to_string() } else { format!("/?{}", params.join("&")) }
+}
+
+fn render_page(machines: &[MachineSummary], events: &[(String, LogEntry)], machine: Option<&str>, alerts_only: bool) -> String {
+    let now = chrono::Local::now();
+    let mut html = String::from(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><meta http-equiv=\"refresh\" content=\"30\">\
+         <title>PC Watcher Server</title><style>\
+         body{font-family:Segoe UI,sans-serif;margin:20px;background:#f4f4f4}\
+         table{border-collapse:collapse;background:#fff;margin-bottom:24px;width:100%}\
+         th,td{padding:4px 8px;border-bottom:1px solid #ddd;text-align:left;font-size:13px}\
+         th{background:#333;color:#fff}.warning{background:#fff1dc}.critical{background:#ffd9d9}\
+         .offline{color:#999}a{color:#0b5cad}\
+         .tag{background:#4080c0;color:#fff;border-radius:8px;padding:0 6px;margin-left:4px;font-size:11px}\
+         </style></head><body><h2>PC Watcher Server</h2>",
+    );
+
+    html.push_str("<table><tr><th>Machine</th><th>Address</th><th>Version</th><th>Last contact</th><th>Events today</th><th>Alerts today</th></tr>");
+    for m in machines {
+        let offline = (now - m.last_seen).num_minutes() >= OFFLINE_AFTER_MINUTES;
+        html.push_str(&format!(
+            "<tr{}><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>{}{}</td><td>{}</td><td>{}</td></tr>",
+            if offline { " class=\"offline\"" } else { "" },
+            escape(&filter_link(Some(&m.name), alerts_only)),
+            escape(&m.name),
+            escape(&m.address),
+            escape(&m.version),
+            m.last_seen.format("%Y-%m-%d %H:%M:%S"),
+            if offline { " (offline)" } else { "" },
+            m.events_today,
+            m.alerts_today
+        ));
+    }
+    html.push_str("</table>");
+
+    html.push_str(&format!(
+        "<p>{} | <a href=\"{}\">{}</a></p>",
+        match machine {
+            Some(name) => format!("Machine: <b>{}</b> (<a href=\"{}\">all</a>)", escape(name), escape(&filter_link(None, alerts_only))),
+            None => "All machines".to_string(),
+        },
+        escape(&filter_link(machine, !alerts_only)),
+        if alerts_only { "show all events" } else { "alerts only" }
+    ));
+
+    html.push_str("<table><tr><th>Time</th><th>Machine</th><th>Severity</th><th>Event</th><th>Process</th><th>Window</th><th>Path</th></tr>");
+    for (name, entry) in events {
+        let class = match entry.severity {
+            Severity::Critical => " class=\"critical\"",
+            Severity::Warning => " class=\"warning\"",
+            Severity::Info => "",
+        };
+        let tags: String = entry.tags.iter().map(|tag| format!("<span class=\"tag\">{}</span>", escape(tag))).collect();
+        html.push_str(&format!(
+            "<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{}{}</td><td>{} ({})</td><td>{}</td><td>{}</td></tr>",
+            class,
+            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            escape(name),
+            entry.severity.as_str(),
+            escape(&entry.event_type),
+            tags,
+            escape(&entry.process_name),
+            entry.process_id,
+            escape(&entry.window_title),
+            escape(&entry.process_path)
+        ));
+    }
+    html.push_str("</table></body></html>");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn url_decode_handles_escapes_and_plus() {
+        assert_eq!(url_decode("note+pad%20x"), "note pad x");
+        assert_eq!(url_decode("caf%C3%A9%E2%82%ac"), "café€");
+        assert_eq!(url_decode("%2Fapi%3Fa%3Db"), "/api?a=b");
+        // Broken escapes stay as they are
+        assert_eq!(url_decode("100%"), "100%");
+        assert_eq!(url_decode("%4"), "%4");
+        assert_eq!(url_decode("%zz"), "%zz");
+        assert_eq!(url_decode("%+1"), "% 1");
+    }
+
+    #[test]
+    fn query_params_are_decoded() {
+        let target = "/api/events?limit=5&process=note%20pad&tag=";
+        assert_eq!(query_param(target, "process").as_deref(), Some("note pad"));
+        assert_eq!(query_param(target, "limit").as_deref(), Some("5"));
+        assert_eq!(query_param(target, "tag"), None);
+        assert_eq!(query_param("/api/events", "limit"), None);
+    }
+
+    #[test]
+    fn base64_and_basic_auth() {
+        assert_eq!(base64_decode("dXNlcjpzZWNyZXQ=").as_deref(), Some(&b"user:secret"[..]));
+        assert_eq!(base64_decode("YQ==").as_deref(), Some(&b"a"[..]));
+        assert_eq!(base64_decode("").as_deref(), Some(&b""[..]));
+        assert_eq!(base64_decode("a*b"), None);
+        assert_eq!(basic_auth_password("Basic dXNlcjpzZWNyZXQ=").as_deref(), Some("secret"));
+        assert_eq!(basic_auth_password("Basic OnA6cQ==").as_deref(), Some("p:q"));
+        assert_eq!(basic_auth_password("Bearer dXNlcjpzZWNyZXQ="), None);
+        assert_eq!(basic_auth_password("Basic bm9jb2xvbg=="), None);
+    }
+
+    #[test]
+    fn parse_time_formats() {
+        let utc = parse_time("2024-03-01T12:30:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(utc, Utc.with_ymd_and_hms(2024, 3, 1, 12, 30, 0).unwrap());
+        let offset = parse_time("2024-03-01T12:30:00+02:00").unwrap().with_timezone(&Utc);
+        assert_eq!(offset, Utc.with_ymd_and_hms(2024, 3, 1, 10, 30, 0).unwrap());
+
+        // Without offset: local time
+        let local = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(12, 30, 0).unwrap();
+        assert_eq!(parse_time("2024-03-01 12:30:00").map(|t| t.naive_local()), Some(local));
+        assert_eq!(parse_time("2024-03-01T12:30:00").map(|t| t.naive_local()), Some(local));
+        let midnight = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!(parse_time("2024-03-01").map(|t| t.naive_local()), Some(midnight));
+
+        assert_eq!(parse_time("yesterday"), None);
+        assert_eq!(parse_time("2024-13-01"), None);
+        assert_eq!(parse_time("2024-03-01 25:00:00"), None);
+    }
+}
@@ -0,0 +1,940 @@
+//! Event Database
+//!
+//! Stores all events in SQLite (pcwatcher_events_<user>.db next to the EXE) for later queries.
+//! Periodic maintenance prunes rows older than the retention, vacuums and reindexes,
+//! so long-running installs don't accumulate huge databases.
+
+use crate::error::Error;
+use crate::input_stats::InputMinute;
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDateTime, TimeZone};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use tracing::info;
+
+/// Maintenance interval (first run shortly after start)
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const MAINTENANCE_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Timestamp format in the database (sortable as text)
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+lazy_static::lazy_static! {
+    // Connection of the running application (opened by the log worker)
+    static ref DB: Mutex<Option<Connection>> = Mutex::new(None);
+}
+
+/// End of the previous run of the application
+#[derive(Debug, Clone)]
+pub struct PreviousRun {
+    /// Last heartbeat (or last event)
+    pub last_alive: DateTime<Local>,
+    /// Application was ended normally (tray exit / CTRL+C)
+    pub clean_stop: bool,
+}
+
+/// Result of a maintenance run
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceStats {
+    pub deleted_rows: usize,
+    pub size_before: u64,
+    pub size_after: u64,
+}
+
+/// Database of older versions (shared by all users), taken over by the first one who starts
+const LEGACY_DB_FILE: &str = "pcwatcher_events.db";
+
+/// Path to the database file: one per user (all sessions of a user share it), further
+/// instances get their own (pcwatcher_events_<user>-2.db)
+pub fn get_db_path() -> PathBuf {
+    let name = format!("pcwatcher_events_{}{}.db", crate::instance::user_name(), crate::instance::file_tag());
+    if let Ok(exe_path) = std::env::current_exe() {
+        if let Some(exe_dir) = exe_path.parent() {
+            return exe_dir.join(name);
+        }
+    }
+    PathBuf::from(name)
+}
+
+/// Renames the shared database of older versions to the one of this user (first instance only,
+/// skipped while an older version still has it open)
+fn adopt_legacy_db(path: &Path) {
+    let legacy = path.with_file_name(LEGACY_DB_FILE);
+    if crate::instance::number() > 1 || path.exists() || !legacy.exists() {
+        return;
+    }
+    if std::fs::rename(&legacy, path).is_ok() {
+        info!("Event database {} taken over as {}", legacy.display(), path.display());
+        for suffix in ["-wal", "-shm"] {
+            let from = PathBuf::from(format!("{}{}", legacy.display(), suffix));
+            let _ = std::fs::rename(&from, format!("{}{}", path.display(), suffix));
+        }
+    }
+}
+
+/// Opens the database and creates the schema if needed
+pub fn open() -> rusqlite::Result<Connection> {
+    open_at(&get_db_path())
+}
+
+/// Opens an event database at another path (e.g. a scratch database for `pc_watcher bench`)
+pub fn open_at(path: &Path) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    // The CLI (db compact) may run while the application is writing
+    conn.busy_timeout(Duration::from_secs(10))?;
+    conn.execute_batch(
+        "PRAGMA journal_mode = WAL;
+         PRAGMA synchronous = NORMAL;
+         CREATE TABLE IF NOT EXISTS events (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             timestamp TEXT NOT NULL,
+             event_type TEXT NOT NULL,
+             severity TEXT NOT NULL,
+             process_name TEXT NOT NULL,
+             process_id INTEGER NOT NULL,
+             process_path TEXT NOT NULL,
+             window_title TEXT NOT NULL,
+             window_class TEXT NOT NULL,
+             command_line TEXT,
+             parent_process_name TEXT NOT NULL,
+             parent_process_id INTEGER NOT NULL,
+             parent_process_path TEXT NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS idx_events_timestamp ON events(timestamp);
+         CREATE INDEX IF NOT EXISTS idx_events_process ON events(process_name);
+         CREATE TABLE IF NOT EXISTS input_stats (
+             minute TEXT PRIMARY KEY,
+             keys INTEGER NOT NULL,
+             mouse_distance INTEGER NOT NULL,
+             locked_input INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS meta (
+             key TEXT PRIMARY KEY,
+             value TEXT NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS file_verdicts (
+             sha256 TEXT PRIMARY KEY,
+             verdict TEXT NOT NULL,
+             checked TEXT NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS incidents (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             started TEXT NOT NULL,
+             last_event TEXT NOT NULL,
+             severity TEXT NOT NULL,
+             process_name TEXT NOT NULL,
+             title TEXT NOT NULL,
+             acknowledged INTEGER NOT NULL DEFAULT 0
+         );",
+    )?;
+
+    // Added later - fails with "duplicate column" on existing databases
+    let _ = conn.execute("ALTER TABLE events ADD COLUMN browser_url TEXT", []);
+    let _ = conn.execute("ALTER TABLE events ADD COLUMN cursor_pos TEXT", []);
+    let _ = conn.execute("ALTER TABLE events ADD COLUMN uptime_ms INTEGER", []);
+    let _ = conn.execute("ALTER TABLE events ADD COLUMN note TEXT", []);
+    let _ = conn.execute("ALTER TABLE events ADD COLUMN owner_process_name TEXT", []);
+    let _ = conn.execute("ALTER TABLE events ADD COLUMN owner_process_id INTEGER", []);
+    let _ = conn.execute("ALTER TABLE events ADD COLUMN owner_window_title TEXT", []);
+    let _ = conn.execute("ALTER TABLE events ADD COLUMN incident_id INTEGER", []);
+    let _ = conn.execute("ALTER TABLE events ADD COLUMN parent_spoofed TEXT", []);
+    // ",tag1,tag2," - the outer commas let LIKE '%,tag,%' match whole tags
+    let _ = conn.execute("ALTER TABLE events ADD COLUMN tags TEXT", []);
+    let _ = conn.execute("ALTER TABLE events ADD COLUMN host_process_name TEXT", []);
+    let _ = conn.execute("ALTER TABLE events ADD COLUMN host_process_id INTEGER", []);
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_events_incident ON events(incident_id)", [])?;
+
+    Ok(conn)
+}
+
+/// Opens the database for the running application
+pub fn init() {
+    adopt_legacy_db(&get_db_path());
+    match open() {
+        Ok(conn) => {
+            info!("Event database: {}", get_db_path().display());
+            *DB.lock() = Some(conn);
+        }
+        Err(e) => crate::error::report("Could not open event database", Error::Database(e.to_string())),
+    }
+}
+
+/// Stores an event, returns its row ID (None if the database could not be opened)
+pub fn insert_event(entry: &LogEntry) -> Option<i64> {
+    let db = DB.lock();
+    let conn = db.as_ref()?;
+
+    match insert_event_into(conn, entry) {
+        Ok(id) => Some(id),
+        Err(e) => {
+            crate::error::report("Could not store event", Error::Database(e.to_string()));
+            None
+        }
+    }
+}
+
+/// Stores an event in the given database, returns its row ID
+pub fn insert_event_into(conn: &Connection, entry: &LogEntry) -> rusqlite::Result<i64> {
+    conn.execute(
+        "INSERT INTO events (timestamp, event_type, severity, process_name, process_id, process_path,
+             window_title, window_class, command_line, parent_process_name, parent_process_id, parent_process_path,
+             browser_url, cursor_pos, uptime_ms, owner_process_name, owner_process_id, owner_window_title, incident_id,
+             parent_spoofed, tags, host_process_name, host_process_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21,
+                 ?22, ?23)",
+        params![
+            entry.timestamp.format(TIMESTAMP_FORMAT).to_string(),
+            entry.event_type,
+            entry.severity.as_str(),
+            entry.process_name,
+            entry.process_id,
+            entry.process_path,
+            entry.window_title,
+            entry.window_class,
+            entry.command_line,
+            entry.parent_process_name,
+            entry.parent_process_id,
+            entry.parent_process_path,
+            entry.browser_url,
+            entry.cursor_pos.map(|(x, y)| format!("{},{}", x, y)),
+            entry.uptime_ms as i64,
+            entry.owner_process_name,
+            entry.owner_process_id,
+            entry.owner_window_title,
+            entry.incident_id,
+            entry.parent_spoofed,
+            (!entry.tags.is_empty()).then(|| format!(",{},", entry.tags.join(","))),
+            (entry.host_process_id > 0).then_some(&entry.host_process_name),
+            (entry.host_process_id > 0).then_some(entry.host_process_id),
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Sets or removes (empty text) the user note of an event
+pub fn set_note(id: i64, note: &str) {
+    let db = DB.lock();
+    let Some(conn) = db.as_ref() else {
+        return;
+    };
+
+    let note = Some(note.trim()).filter(|n| !n.is_empty());
+    if let Err(e) = conn.execute("UPDATE events SET note = ?1 WHERE id = ?2", params![note, id]) {
+        crate::error::report("Could not store note", Error::Database(e.to_string()));
+    }
+}
+
+/// Stores the input counts of one minute
+pub fn insert_input_minute(stats: &InputMinute) {
+    let db = DB.lock();
+    let Some(conn) = db.as_ref() else {
+        return;
+    };
+
+    let result = conn.execute(
+        "INSERT OR REPLACE INTO input_stats (minute, keys, mouse_distance, locked_input) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            stats.minute.format("%Y-%m-%d %H:%M").to_string(),
+            stats.keys,
+            stats.mouse_distance,
+            stats.locked_input,
+        ],
+    );
+
+    if let Err(e) = result {
+        crate::error::report("Could not store input stats", Error::Database(e.to_string()));
+    }
+}
+
+/// Stores a value in the meta table (e.g. heartbeat of the running application)
+pub fn set_meta(key: &str, value: &str) {
+    let db = DB.lock();
+    let Some(conn) = db.as_ref() else {
+        return;
+    };
+
+    if let Err(e) = conn.execute("INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)", params![key, value]) {
+        crate::error::report(&format!("Could not store {}", key), Error::Database(e.to_string()));
+    }
+}
+
+/// Cached verdict of a file hash (verdict JSON, time of the lookup)
+pub fn get_file_verdict(sha256: &str) -> Option<(String, DateTime<Local>)> {
+    let db = DB.lock();
+    let conn = db.as_ref()?;
+    let (verdict, checked): (String, String) = conn
+        .query_row(
+            "SELECT verdict, checked FROM file_verdicts WHERE sha256 = ?1",
+            params![sha256],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok()?;
+    Some((verdict, parse_timestamp(&checked)?))
+}
+
+/// Caches the verdict of a file hash
+pub fn set_file_verdict(sha256: &str, verdict: &str) {
+    let db = DB.lock();
+    let Some(conn) = db.as_ref() else {
+        return;
+    };
+
+    let result = conn.execute(
+        "INSERT OR REPLACE INTO file_verdicts (sha256, verdict, checked) VALUES (?1, ?2, ?3)",
+        params![sha256, verdict, format_timestamp(&Local::now())],
+    );
+    if let Err(e) = result {
+        crate::error::report("Could not store file verdict", Error::Database(e.to_string()));
+    }
+}
+
+/// Formats a timestamp like the database does
+pub fn format_timestamp(timestamp: &DateTime<Local>) -> String {
+    timestamp.format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Parses a timestamp from the database
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Local>> {
+    let naive = NaiveDateTime::parse_from_str(value, TIMESTAMP_FORMAT).ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// How the previous run ended (read with an own connection before the log worker starts)
+pub fn read_previous_run() -> Option<PreviousRun> {
+    let conn = open().ok()?;
+    let meta = |key: &str| -> Option<String> {
+        conn.query_row("SELECT value FROM meta WHERE key = ?1", params![key], |row| row.get(0))
+            .ok()
+    };
+
+    match meta("last_alive").as_deref().and_then(parse_timestamp) {
+        Some(last_alive) => Some(PreviousRun {
+            last_alive,
+            clean_stop: meta("clean_stop").as_deref() == Some("1"),
+        }),
+        // Older databases without heartbeat: last event, stop reason unknown
+        None => {
+            let last_event: String = conn
+                .query_row("SELECT MAX(timestamp) FROM events", [], |row| row.get(0))
+                .ok()?;
+            Some(PreviousRun {
+                last_alive: parse_timestamp(&last_event)?,
+                clean_stop: true,
+            })
+        }
+    }
+}
+
+/// Columns read back into a LogEntry (see read_event_row)
+const EVENT_COLUMNS: &str = "timestamp, event_type, severity, process_name, process_id, process_path, window_title,
+                             window_class, command_line, parent_process_name, parent_process_id, parent_process_path,
+                             browser_url, cursor_pos, uptime_ms, note,
+                             owner_process_name, owner_process_id, owner_window_title, incident_id, parent_spoofed, tags,
+                             host_process_name, host_process_id";
+
+/// Index of a column selected after EVENT_COLUMNS (e.g. the row ID)
+const EVENT_COLUMNS_END: usize = 24;
+
+/// LogEntry from a row with EVENT_COLUMNS
+/// Grandparent processes are not stored and stay empty
+fn read_event_row(row: &rusqlite::Row) -> rusqlite::Result<LogEntry> {
+    let timestamp: String = row.get(0)?;
+    let severity: String = row.get(2)?;
+    let cursor_pos: Option<String> = row.get(13)?;
+    let uptime_ms: Option<i64> = row.get(14)?;
+    Ok(LogEntry {
+        timestamp: parse_timestamp(&timestamp).unwrap_or_else(Local::now),
+        uptime_ms: uptime_ms.unwrap_or(0) as u64,
+        event_type: row.get(1)?,
+        severity: Severity::parse(&severity),
+        process_name: row.get(3)?,
+        process_id: row.get(4)?,
+        process_path: row.get(5)?,
+        window_title: row.get(6)?,
+        window_class: row.get(7)?,
+        command_line: row.get(8)?,
+        browser_url: row.get(12)?,
+        cursor_pos: cursor_pos.and_then(|p| {
+            let (x, y) = p.split_once(',')?;
+            Some((x.parse().ok()?, y.parse().ok()?))
+        }),
+        note: row.get(15)?,
+        incident_id: row.get(19)?,
+        tags: row
+            .get::<_, Option<String>>(21)?
+            .map(|tags| tags.split(',').filter(|t| !t.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default(),
+        parent_process_name: row.get(9)?,
+        parent_process_id: row.get(10)?,
+        parent_process_path: row.get(11)?,
+        grandparent_process_name: String::new(),
+        grandparent_process_id: 0,
+        grandparent_process_path: String::new(),
+        greatgrandparent_process_name: String::new(),
+        greatgrandparent_process_id: 0,
+        greatgrandparent_process_path: String::new(),
+        parent_spoofed: row.get(20)?,
+        // Older rows have no owner columns filled
+        owner_process_name: row.get::<_, Option<String>>(16)?.unwrap_or_default(),
+        owner_process_id: row.get::<_, Option<u32>>(17)?.unwrap_or(0),
+        owner_window_title: row.get::<_, Option<String>>(18)?.unwrap_or_default(),
+        host_process_name: row.get::<_, Option<String>>(22)?.unwrap_or_default(),
+        host_process_id: row.get::<_, Option<u32>>(23)?.unwrap_or(0),
+        span: None,
+    })
+}
+
+/// Reads an event (default: latest Warning/Critical event) for export
+pub fn read_alert(conn: &Connection, id: Option<i64>) -> rusqlite::Result<LogEntry> {
+    match id {
+        Some(id) => conn.query_row(
+            &format!("SELECT {} FROM events WHERE id = ?1", EVENT_COLUMNS),
+            params![id],
+            read_event_row,
+        ),
+        None => conn.query_row(
+            &format!("SELECT {} FROM events WHERE severity != 'INFO' ORDER BY id DESC LIMIT 1", EVENT_COLUMNS),
+            [],
+            read_event_row,
+        ),
+    }
+}
+
+/// Events after a row ID in insert order: (id, entry) - used to forward/backfill to the central server
+pub fn read_events_after(conn: &Connection, after_id: i64, limit: usize) -> rusqlite::Result<Vec<(i64, LogEntry)>> {
+    // ID as last column, so read_event_row can read the others
+    let mut statement = conn.prepare(&format!(
+        "SELECT {}, id FROM events WHERE id > ?1 ORDER BY id LIMIT ?2",
+        EVENT_COLUMNS
+    ))?;
+    let rows = statement.query_map(params![after_id, limit as i64], |row| Ok((row.get(EVENT_COLUMNS_END)?, read_event_row(row)?)))?;
+    rows.collect()
+}
+
+/// Newest events first: (id, entry) - for `pc_watcher events` (tag: only events with this tag)
+pub fn read_latest_events(
+    conn: &Connection,
+    alerts_only: bool,
+    tag: Option<&str>,
+    limit: usize,
+) -> rusqlite::Result<Vec<(i64, LogEntry)>> {
+    let mut statement = conn.prepare(&format!(
+        "SELECT {}, id FROM events WHERE (?1 = 0 OR severity != 'INFO')
+             AND (?3 IS NULL OR tags LIKE '%,' || ?3 || ',%') ORDER BY id DESC LIMIT ?2",
+        EVENT_COLUMNS
+    ))?;
+    let rows = statement.query_map(params![alerts_only, limit as i64, tag], |row| {
+        Ok((row.get(EVENT_COLUMNS_END)?, read_event_row(row)?))
+    })?;
+    rows.collect()
+}
+
+/// Events since a time in time order: (timestamp, event type, process name) - for the timeline
+pub fn read_timeline_events(conn: &Connection, since: &DateTime<Local>) -> rusqlite::Result<Vec<(DateTime<Local>, String, String)>> {
+    let mut statement = conn.prepare(
+        "SELECT timestamp, event_type, process_name FROM events WHERE timestamp >= ?1 ORDER BY timestamp",
+    )?;
+    let rows = statement.query_map(params![format_timestamp(since)], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+    })?;
+    Ok(rows
+        .filter_map(|row| row.ok())
+        .filter_map(|(timestamp, event_type, process_name)| Some((parse_timestamp(&timestamp)?, event_type, process_name)))
+        .collect())
+}
+
+/// Minutes with keyboard/mouse input since a time (input telemetry)
+pub fn read_input_minutes(conn: &Connection, since: &DateTime<Local>) -> rusqlite::Result<Vec<DateTime<Local>>> {
+    let mut statement = conn.prepare("SELECT minute FROM input_stats WHERE minute >= ?1 ORDER BY minute")?;
+    let rows = statement.query_map(params![since.format("%Y-%m-%d %H:%M").to_string()], |row| row.get::<_, String>(0))?;
+    Ok(rows
+        .filter_map(|row| row.ok())
+        .filter_map(|minute| {
+            let naive = NaiveDateTime::parse_from_str(&minute, "%Y-%m-%d %H:%M").ok()?;
+            Local.from_local_datetime(&naive).earliest()
+        })
+        .collect())
+}
+
+/// Creates an incident for the event that opened it, returns its ID
+pub fn create_incident(entry: &LogEntry, title: &str) -> Option<i64> {
+    let db = DB.lock();
+    let conn = db.as_ref()?;
+    let timestamp = format_timestamp(&entry.timestamp);
+    let result = conn.execute(
+        "INSERT INTO incidents (started, last_event, severity, process_name, title) VALUES (?1, ?1, ?2, ?3, ?4)",
+        params![timestamp, entry.severity.as_str(), entry.process_name, title],
+    );
+    match result {
+        Ok(_) => Some(conn.last_insert_rowid()),
+        Err(e) => {
+            crate::error::report("Could not create incident", Error::Database(e.to_string()));
+            None
+        }
+    }
+}
+
+/// Updates time of the last member event and severity of an incident
+pub fn update_incident(id: i64, last_event: &DateTime<Local>, severity: Severity) {
+    let db = DB.lock();
+    let Some(conn) = db.as_ref() else {
+        return;
+    };
+    let result = conn.execute(
+        "UPDATE incidents SET last_event = ?1, severity = ?2 WHERE id = ?3",
+        params![format_timestamp(last_event), severity.as_str(), id],
+    );
+    if let Err(e) = result {
+        crate::error::report("Could not update incident", Error::Database(e.to_string()));
+    }
+}
+
+/// Adds the events of a process (and the processes it started) since a time to an incident
+pub fn tag_incident_events(id: i64, process_id: u32, since: &DateTime<Local>) {
+    let db = DB.lock();
+    let Some(conn) = db.as_ref() else {
+        return;
+    };
+    let result = conn.execute(
+        "UPDATE events SET incident_id = ?1
+         WHERE incident_id IS NULL AND timestamp >= ?2 AND (process_id = ?3 OR parent_process_id = ?3)",
+        params![id, format_timestamp(since), process_id],
+    );
+    if let Err(e) = result {
+        crate::error::report("Could not tag incident events", Error::Database(e.to_string()));
+    }
+}
+
+/// Incident with the number of member events (incident review window)
+#[derive(Debug, Clone)]
+pub struct IncidentSummary {
+    pub id: i64,
+    pub started: DateTime<Local>,
+    pub last_event: DateTime<Local>,
+    pub severity: Severity,
+    pub process_name: String,
+    pub title: String,
+    pub acknowledged: bool,
+    pub event_count: u64,
+}
+
+/// Newest incidents first
+pub fn read_incidents(conn: &Connection, limit: usize) -> rusqlite::Result<Vec<IncidentSummary>> {
+    let mut statement = conn.prepare(
+        "SELECT id, started, last_event, severity, process_name, title, acknowledged,
+                (SELECT COUNT(*) FROM events WHERE events.incident_id = incidents.id)
+         FROM incidents ORDER BY id DESC LIMIT ?1",
+    )?;
+    let rows = statement.query_map(params![limit as i64], |row| {
+        let started: String = row.get(1)?;
+        let last_event: String = row.get(2)?;
+        let severity: String = row.get(3)?;
+        Ok(IncidentSummary {
+            id: row.get(0)?,
+            started: parse_timestamp(&started).unwrap_or_else(Local::now),
+            last_event: parse_timestamp(&last_event).unwrap_or_else(Local::now),
+            severity: Severity::parse(&severity),
+            process_name: row.get(4)?,
+            title: row.get(5)?,
+            acknowledged: row.get(6)?,
+            event_count: row.get::<_, i64>(7)? as u64,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Member events of an incident in time order
+pub fn read_incident_events(conn: &Connection, id: i64) -> rusqlite::Result<Vec<LogEntry>> {
+    let mut statement = conn.prepare(&format!(
+        "SELECT {} FROM events WHERE incident_id = ?1 ORDER BY timestamp, id",
+        EVENT_COLUMNS
+    ))?;
+    let rows = statement.query_map(params![id], read_event_row)?;
+    rows.collect()
+}
+
+/// Marks an incident as reviewed
+pub fn acknowledge_incident(conn: &Connection, id: i64) -> rusqlite::Result<()> {
+    conn.execute("UPDATE incidents SET acknowledged = 1 WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Watcher instance known to the central server
+#[derive(Debug, Clone)]
+pub struct MachineSummary {
+    pub name: String,
+    pub address: String,
+    pub version: String,
+    pub last_seen: DateTime<Local>,
+    pub events_today: u64,
+    pub alerts_today: u64,
+}
+
+/// Path to the central server database (server mode)
+pub fn get_server_db_path() -> PathBuf {
+    get_db_path().with_file_name("pcwatcher_server.db")
+}
+
+/// Opens the central server database (events of all machines)
+pub fn open_server() -> rusqlite::Result<Connection> {
+    let conn = Connection::open(get_server_db_path())?;
+    conn.busy_timeout(Duration::from_secs(10))?;
+    conn.execute_batch(
+        "PRAGMA journal_mode = WAL;
+         PRAGMA synchronous = NORMAL;
+         CREATE TABLE IF NOT EXISTS machines (
+             name TEXT PRIMARY KEY,
+             address TEXT NOT NULL,
+             version TEXT NOT NULL,
+             last_seen TEXT NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS events (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             machine TEXT NOT NULL,
+             client_id INTEGER NOT NULL,
+             timestamp TEXT NOT NULL,
+             event_type TEXT NOT NULL,
+             severity TEXT NOT NULL,
+             process_name TEXT NOT NULL,
+             data TEXT NOT NULL,
+             UNIQUE(machine, client_id)
+         );
+         CREATE INDEX IF NOT EXISTS idx_server_events_timestamp ON events(timestamp);
+         CREATE INDEX IF NOT EXISTS idx_server_events_machine ON events(machine, timestamp);",
+    )?;
+    Ok(conn)
+}
+
+/// Registers a connected machine / updates its last contact
+pub fn touch_machine(conn: &Connection, name: &str, address: &str, version: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO machines (name, address, version, last_seen) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(name) DO UPDATE SET address = ?2, version = ?3, last_seen = ?4",
+        params![name, address, version, format_timestamp(&Local::now())],
+    )?;
+    Ok(())
+}
+
+/// Highest event ID of a machine already stored (0 = none, clients resume after it)
+pub fn last_machine_event(conn: &Connection, machine: &str) -> rusqlite::Result<i64> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(client_id), 0) FROM events WHERE machine = ?1",
+        params![machine],
+        |row| row.get(0),
+    )
+}
+
+/// Stores an event of a machine (duplicates after reconnects are ignored)
+pub fn insert_machine_event(conn: &Connection, machine: &str, client_id: i64, entry: &LogEntry) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO events (machine, client_id, timestamp, event_type, severity, process_name, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            machine,
+            client_id,
+            format_timestamp(&entry.timestamp),
+            entry.event_type,
+            entry.severity.as_str(),
+            entry.process_name,
+            entry.format_json().to_string(),
+        ],
+    )?;
+    Ok(())
+}
+
+/// All machines with today's event counts
+pub fn read_machines(conn: &Connection) -> rusqlite::Result<Vec<MachineSummary>> {
+    let today = format_timestamp(&Local::now()).get(..10).unwrap_or_default().to_string();
+    let mut statement = conn.prepare(
+        "SELECT m.name, m.address, m.version, m.last_seen,
+             (SELECT COUNT(*) FROM events e WHERE e.machine = m.name AND e.timestamp >= ?1),
+             (SELECT COUNT(*) FROM events e WHERE e.machine = m.name AND e.timestamp >= ?1 AND e.severity != 'INFO')
+         FROM machines m ORDER BY m.name",
+    )?;
+    let rows = statement.query_map(params![today], |row| {
+        let last_seen: String = row.get(3)?;
+        Ok(MachineSummary {
+            name: row.get(0)?,
+            address: row.get(1)?,
+            version: row.get(2)?,
+            last_seen: parse_timestamp(&last_seen).unwrap_or_else(Local::now),
+            events_today: row.get::<_, i64>(4)? as u64,
+            alerts_today: row.get::<_, i64>(5)? as u64,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Newest events of all machines or one machine: (machine, entry)
+pub fn read_machine_events(
+    conn: &Connection,
+    machine: Option<&str>,
+    alerts_only: bool,
+    limit: usize,
+) -> rusqlite::Result<Vec<(String, LogEntry)>> {
+    let mut statement = conn.prepare(
+        "SELECT machine, data FROM events
+         WHERE (?1 IS NULL OR machine = ?1) AND (?2 = 0 OR severity != 'INFO')
+         ORDER BY timestamp DESC LIMIT ?3",
+    )?;
+    let rows = statement.query_map(params![machine, alerts_only, limit as i64], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    let mut events = Vec::new();
+    for row in rows {
+        let (machine, data) = row?;
+        let entry = serde_json::from_str(&data).ok().and_then(|json| LogEntry::from_json(&json));
+        if let Some(entry) = entry {
+            events.push((machine, entry));
+        }
+    }
+    Ok(events)
+}
+
+/// Filters of the event API of the server and the web dashboard (None = no restriction)
+#[derive(Debug, Default)]
+pub struct EventQuery {
+    pub machine: Option<String>,
+    pub event_type: Option<String>,
+    /// Process name without .exe (case-insensitive)
+    pub process: Option<String>,
+    pub min_severity: Option<Severity>,
+    pub tag: Option<String>,
+    pub from: Option<DateTime<Local>>,
+    pub to: Option<DateTime<Local>>,
+    /// Only events with a smaller row ID (page cursor)
+    pub before_id: Option<i64>,
+    /// Only events with a larger row ID (polling for new events)
+    pub after_id: Option<i64>,
+    pub limit: usize,
+}
+
+/// SQL rank of the severity column (Info 0, Warning 1, Critical 2)
+const SEVERITY_RANK: &str = "CASE severity WHEN 'CRITICAL' THEN 2 WHEN 'WARNING' THEN 1 ELSE 0 END";
+
+fn severity_rank(severity: Severity) -> i64 {
+    match severity {
+        Severity::Info => 0,
+        Severity::Warning => 1,
+        Severity::Critical => 2,
+    }
+}
+
+/// Events of the server matching the query, newest first: (row ID, machine, entry)
+pub fn query_machine_events(conn: &Connection, query: &EventQuery) -> rusqlite::Result<Vec<(i64, String, LogEntry)>> {
+    let mut statement = conn.prepare(&format!(
+        "SELECT id, machine, data FROM events
+         WHERE (?1 IS NULL OR machine = ?1)
+           AND (?2 IS NULL OR event_type = ?2)
+           AND (?3 IS NULL OR process_name = ?3 COLLATE NOCASE OR process_name = ?3 || '.exe' COLLATE NOCASE)
+           AND ({} >= ?4)
+           AND (?5 IS NULL OR EXISTS (SELECT 1 FROM json_each(data, '$.tags') WHERE value = ?5 COLLATE NOCASE))
+           AND (?6 IS NULL OR timestamp >= ?6)
+           AND (?7 IS NULL OR timestamp < ?7)
+           AND (?8 IS NULL OR id < ?8)
+           AND (?9 IS NULL OR id > ?9)
+         ORDER BY id DESC LIMIT ?10",
+        SEVERITY_RANK
+    ))?;
+    let rows = statement.query_map(
+        params![
+            query.machine,
+            query.event_type,
+            query.process,
+            query.min_severity.map(severity_rank).unwrap_or(0),
+            query.tag,
+            query.from.as_ref().map(format_timestamp),
+            query.to.as_ref().map(format_timestamp),
+            query.before_id,
+            query.after_id,
+            query.limit as i64,
+        ],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)),
+    )?;
+
+    let mut events = Vec::new();
+    for row in rows {
+        let (id, machine, data) = row?;
+        let entry = serde_json::from_str(&data).ok().and_then(|json| LogEntry::from_json(&json));
+        if let Some(entry) = entry {
+            events.push((id, machine, entry));
+        }
+    }
+    Ok(events)
+}
+
+/// Local events matching the query, newest first: (row ID, entry) - the machine filter does not apply
+pub fn query_events(conn: &Connection, query: &EventQuery) -> rusqlite::Result<Vec<(i64, LogEntry)>> {
+    let mut statement = conn.prepare(&format!(
+        "SELECT {}, id FROM events
+         WHERE (?1 IS NULL OR event_type = ?1)
+           AND (?2 IS NULL OR process_name = ?2 COLLATE NOCASE OR process_name = ?2 || '.exe' COLLATE NOCASE)
+           AND ({} >= ?3)
+           AND (?4 IS NULL OR tags LIKE '%,' || ?4 || ',%')
+           AND (?5 IS NULL OR timestamp >= ?5)
+           AND (?6 IS NULL OR timestamp < ?6)
+           AND (?7 IS NULL OR id < ?7)
+           AND (?8 IS NULL OR id > ?8)
+         ORDER BY id DESC LIMIT ?9",
+        EVENT_COLUMNS, SEVERITY_RANK
+    ))?;
+    let rows = statement.query_map(
+        params![
+            query.event_type,
+            query.process,
+            query.min_severity.map(severity_rank).unwrap_or(0),
+            query.tag,
+            query.from.as_ref().map(format_timestamp),
+            query.to.as_ref().map(format_timestamp),
+            query.before_id,
+            query.after_id,
+            query.limit as i64,
+        ],
+        |row| Ok((row.get(EVENT_COLUMNS_END)?, read_event_row(row)?)),
+    )?;
+    rows.collect()
+}
+
+/// Incident of a machine, aggregated from its forwarded events
+#[derive(Debug, Clone)]
+pub struct MachineIncident {
+    pub machine: String,
+    /// Incident ID on the machine
+    pub id: i64,
+    pub started: DateTime<Local>,
+    pub last_event: DateTime<Local>,
+    pub severity: Severity,
+    pub events: u64,
+    pub processes: Vec<String>,
+}
+
+/// Incidents of the server, most recently active first (offset/limit for paging)
+pub fn query_machine_incidents(
+    conn: &Connection,
+    machine: Option<&str>,
+    from: Option<&DateTime<Local>>,
+    to: Option<&DateTime<Local>>,
+    offset: usize,
+    limit: usize,
+) -> rusqlite::Result<Vec<MachineIncident>> {
+    let mut statement = conn.prepare(&format!(
+        "SELECT machine, json_extract(data, '$.incident_id') AS incident, MIN(timestamp), MAX(timestamp),
+             MAX({}), COUNT(*), GROUP_CONCAT(DISTINCT process_name)
+         FROM events
+         WHERE incident IS NOT NULL AND (?1 IS NULL OR machine = ?1)
+         GROUP BY machine, incident
+         HAVING (?2 IS NULL OR MAX(timestamp) >= ?2) AND (?3 IS NULL OR MIN(timestamp) < ?3)
+         ORDER BY MAX(timestamp) DESC LIMIT ?4 OFFSET ?5",
+        SEVERITY_RANK
+    ))?;
+    let rows = statement.query_map(
+        params![machine, from.map(format_timestamp), to.map(format_timestamp), limit as i64, offset as i64],
+        |row| {
+            let started: String = row.get(2)?;
+            let last_event: String = row.get(3)?;
+            let processes: Option<String> = row.get(6)?;
+            Ok(MachineIncident {
+                machine: row.get(0)?,
+                id: row.get(1)?,
+                started: parse_timestamp(&started).unwrap_or_else(Local::now),
+                last_event: parse_timestamp(&last_event).unwrap_or_else(Local::now),
+                severity: match row.get::<_, i64>(4)? {
+                    2 => Severity::Critical,
+                    1 => Severity::Warning,
+                    _ => Severity::Info,
+                },
+                events: row.get::<_, i64>(5)? as u64,
+                processes: processes.map(|p| p.split(',').map(str::to_string).collect()).unwrap_or_default(),
+            })
+        },
+    )?;
+    rows.collect()
+}
+
+/// Size of the database file incl. WAL (bytes)
+fn db_size() -> u64 {
+    let path = get_db_path();
+    let wal = path.with_extension("db-wal");
+    [path, wal]
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Prunes rows older than the retention, then vacuums and reindexes
+pub fn maintain(conn: &Connection, retention_days: u32) -> rusqlite::Result<MaintenanceStats> {
+    let size_before = db_size();
+
+    let deleted_rows = if retention_days > 0 {
+        let cutoff = Local::now() - ChronoDuration::days(retention_days as i64);
+        let cutoff = cutoff.format(TIMESTAMP_FORMAT).to_string();
+        conn.execute("DELETE FROM input_stats WHERE minute < ?1", params![cutoff])?;
+        conn.execute("DELETE FROM events WHERE timestamp < ?1", params![cutoff])?
+    } else {
+        0
+    };
+
+    conn.execute_batch(
+        "VACUUM;
+         REINDEX;
+         PRAGMA wal_checkpoint(TRUNCATE);",
+    )?;
+
+    Ok(MaintenanceStats {
+        deleted_rows,
+        size_before,
+        size_after: db_size(),
+    })
+}
+
+/// Runs maintenance on its own connection: VACUUM can take minutes, the log worker keeps
+/// inserting through the shared one meanwhile (WAL, busy timeout)
+fn run_maintenance() {
+    if DB.lock().is_none() {
+        return;
+    }
+    let retention_days = crate::config::get().database.retention_days;
+    let conn = match open() {
+        Ok(conn) => conn,
+        Err(e) => {
+            crate::error::report("Database maintenance failed", Error::Database(e.to_string()));
+            return;
+        }
+    };
+
+    match maintain(&conn, retention_days) {
+        Ok(stats) => info!(
+            "Database maintenance: {} old events deleted, {} KB -> {} KB",
+            stats.deleted_rows,
+            stats.size_before / 1024,
+            stats.size_after / 1024
+        ),
+        Err(e) => crate::error::report("Database maintenance failed", Error::Database(e.to_string())),
+    }
+}
+
+/// Starts the periodic maintenance thread
+pub fn start_maintenance() {
+    thread::spawn(|| {
+        let mut wait = MAINTENANCE_DELAY;
+        loop {
+            // Sleep in small steps so the thread ends with the application
+            let mut waited = Duration::ZERO;
+            while waited < wait {
+                if crate::tray::should_exit() {
+                    return;
+                }
+                thread::sleep(Duration::from_secs(1));
+                waited += Duration::from_secs(1);
+            }
+            run_maintenance();
+            wait = MAINTENANCE_INTERVAL;
+        }
+    });
+}
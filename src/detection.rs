@@ -0,0 +1,295 @@
+//! Process Detections
+//!
+//! Conditions on the image, parent image and command line of a process (own rules or imported
+//! Sigma rules, see sigma.rs). Checked once per process on its first window - the watcher sees
+//! processes when they show up, not on creation. A match raises the severity and the alert.
+//! Command lines can also be matched with regular expressions, PowerShell-style flags
+//! ("-w hidden") and the decoded script of a PowerShell -EncodedCommand.
+
+use crate::config::{DetectionRule, ProcessMatch};
+use crate::process_info::ProcessInfo;
+use parking_lot::Mutex;
+use regex::{Regex, RegexBuilder};
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Characters that start a command-line flag (PowerShell also accepts dashes from Word)
+const FLAG_PREFIXES: &[char] = &['-', '/', '\u{2013}', '\u{2014}', '\u{2015}'];
+
+lazy_static::lazy_static! {
+    // Compiled command-line regexes (None = invalid, warned once)
+    static ref REGEXES: Mutex<HashMap<String, Option<Regex>>> = Mutex::new(HashMap::new());
+}
+
+/// Case-insensitive match with `*` as wildcard for any text
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            p += 1;
+            backtrack = Some((p, t));
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            // Let the last `*` swallow one more character
+            p = star_p;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Case-insensitive regex match (invalid patterns never match)
+fn regex_match(pattern: &str, text: &str) -> bool {
+    let mut regexes = REGEXES.lock();
+    let regex = regexes.entry(pattern.to_string()).or_insert_with(|| {
+        match RegexBuilder::new(pattern).case_insensitive(true).build() {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                warn!("Invalid command-line regex {:?}: {}", pattern, e);
+                None
+            }
+        }
+    });
+    regex.as_ref().is_some_and(|r| r.is_match(text))
+}
+
+/// Splits a command line into arguments (double quotes group, and are removed)
+fn arguments(command_line: &str) -> Vec<String> {
+    let mut arguments = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in command_line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    arguments.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        arguments.push(current);
+    }
+    arguments
+}
+
+/// Name and value of an argument that is a flag ("-w:hidden" = ("w", Some("hidden")))
+fn flag(argument: &str) -> Option<(String, Option<String>)> {
+    let body = argument.strip_prefix(FLAG_PREFIXES)?.to_lowercase();
+    match body.split_once(':') {
+        Some((name, value)) if !name.is_empty() => Some((name.to_string(), Some(value.to_string()))),
+        _ => (!body.is_empty()).then_some((body, None)),
+    }
+}
+
+/// Checks for a flag like "-windowstyle hidden": the command line may abbreviate the name
+/// ("-w", "-win") and the value ("h"), as PowerShell does
+fn has_flag(arguments: &[String], expected: &str) -> bool {
+    let mut parts = expected.split_whitespace();
+    let Some(name) = parts.next().map(|n| n.trim_start_matches(FLAG_PREFIXES).to_lowercase()) else {
+        return false;
+    };
+    let value = parts.next().map(str::to_lowercase);
+    arguments.iter().enumerate().any(|(i, argument)| {
+        let Some((given, inline_value)) = flag(argument) else {
+            return false;
+        };
+        if !name.starts_with(&given) {
+            return false;
+        }
+        match &value {
+            None => true,
+            Some(value) => inline_value
+                .or_else(|| arguments.get(i + 1).map(|v| v.to_lowercase()))
+                .is_some_and(|given| !given.is_empty() && value.starts_with(&given)),
+        }
+    })
+}
+
+/// Standard base64 (whitespace and padding ignored)
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace() && *c != b'=') {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// The script of a PowerShell -EncodedCommand (-e, -enc, -ec, ...): None = no such flag,
+/// the raw argument if it is not valid base64 UTF-16
+fn encoded_command(command_line: &str) -> Option<String> {
+    let arguments = arguments(command_line);
+    let position = arguments.iter().position(|argument| {
+        flag(argument).is_some_and(|(name, _)| name == "ec" || "encodedcommand".starts_with(&name))
+    })?;
+    let encoded = arguments.get(position + 1).cloned().unwrap_or_default();
+    let decoded = base64_decode(&encoded).filter(|b| b.len() % 2 == 0).and_then(|bytes| {
+        let wide: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+        String::from_utf16(&wide).ok()
+    });
+    Some(decoded.unwrap_or(encoded))
+}
+
+/// Checks if a process fulfills all conditions
+fn matches(conditions: &ProcessMatch, info: &ProcessInfo) -> bool {
+    let command_line = info.command_line.as_deref().unwrap_or("");
+    let any = |patterns: &[String], text: &str| patterns.is_empty() || patterns.iter().any(|p| wildcard_match(p, text));
+    let basic = any(&conditions.image, &info.process_path)
+        && any(&conditions.parent_image, &info.parent_process_path)
+        && any(&conditions.command_line, command_line)
+        && conditions.command_line_all.iter().all(|p| wildcard_match(p, command_line))
+        && (conditions.command_line_regex.is_empty()
+            || conditions.command_line_regex.iter().any(|p| regex_match(p, command_line)));
+    if !basic {
+        return false;
+    }
+
+    if !conditions.flags.is_empty() {
+        let arguments = arguments(command_line);
+        if !conditions.flags.iter().all(|f| has_flag(&arguments, f)) {
+            return false;
+        }
+    }
+    if conditions.encoded_command || !conditions.decoded_command.is_empty() {
+        let Some(script) = encoded_command(command_line) else {
+            return false;
+        };
+        return any(&conditions.decoded_command, &script);
+    }
+    true
+}
+
+/// Checks if a filter excludes the process (not by a parent that is possibly spoofed)
+fn excluded_by(filter: &ProcessMatch, info: &ProcessInfo) -> bool {
+    matches(filter, info) && (info.parent_spoofed.is_none() || filter.parent_image.is_empty())
+}
+
+/// First enabled detection matching the process
+pub fn matching_rule(info: &ProcessInfo) -> Option<DetectionRule> {
+    first_match(&crate::config::read().detections, info).cloned()
+}
+
+/// First enabled rule of a list matching the process (also used by replay)
+pub fn first_match<'a>(rules: &'a [DetectionRule], info: &ProcessInfo) -> Option<&'a DetectionRule> {
+    rules.iter().find(|rule| {
+        rule.enabled
+            && rule.selections.iter().any(|s| matches(s, info))
+            && !rule.filters.iter().any(|f| excluded_by(f, info))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_info::{read_process, MockProcess, MockProvider};
+
+    const WORD: &str = r"C:\Program Files\Microsoft Office\root\Office16\WINWORD.EXE";
+    const EXPLORER: &str = r"C:\Windows\explorer.exe";
+    const POWERSHELL: &str = r"C:\Windows\System32\WindowsPowerShell\v1.0\powershell.exe";
+
+    /// Process 20 with its parent 10, read through the mock provider like a real process
+    /// (`parent_created` after 200 makes the parent PID look spoofed)
+    fn process(parent_path: &str, parent_created: u64, path: &str, command_line: &str) -> ProcessInfo {
+        let provider = MockProvider::new()
+            .with_process(10, MockProcess {
+                name: "parent".to_string(),
+                path: Some(parent_path.to_string()),
+                created: Some(parent_created),
+                ..Default::default()
+            })
+            .with_process(20, MockProcess {
+                name: "child".to_string(),
+                path: Some(path.to_string()),
+                command_line: Some(command_line.to_string()),
+                parent_id: 10,
+                created: Some(200),
+            });
+        read_process(&provider, 20)
+    }
+
+    fn powershell_rule() -> DetectionRule {
+        DetectionRule {
+            title: "PowerShell outside Explorer".to_string(),
+            selections: vec![ProcessMatch { image: vec!["*\\powershell.exe".to_string()], ..Default::default() }],
+            filters: vec![ProcessMatch { parent_image: vec!["*\\explorer.exe".to_string()], ..Default::default() }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn wildcards_match_case_insensitively() {
+        assert!(wildcard_match("*\\powershell.exe", POWERSHELL));
+        assert!(wildcard_match("c:\\windows\\*\\*.EXE", POWERSHELL));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("*\\cmd.exe", POWERSHELL));
+        assert!(!wildcard_match("powershell", POWERSHELL));
+    }
+
+    #[test]
+    fn parent_image_and_abbreviated_flags_match() {
+        let conditions = ProcessMatch {
+            parent_image: vec!["*\\winword.exe".to_string()],
+            image: vec!["*\\powershell.exe".to_string()],
+            flags: vec!["-windowstyle hidden".to_string(), "-noprofile".to_string()],
+            ..Default::default()
+        };
+        assert!(matches(&conditions, &process(WORD, 100, POWERSHELL, "powershell.exe -w h -nop -c calc")));
+        assert!(matches(&conditions, &process(WORD, 100, POWERSHELL, "powershell.exe /WindowStyle:Hidden -NoProfile")));
+        assert!(!matches(&conditions, &process(WORD, 100, POWERSHELL, "powershell.exe -w normal -nop")));
+        assert!(!matches(&conditions, &process(EXPLORER, 100, POWERSHELL, "powershell.exe -w h -nop")));
+    }
+
+    #[test]
+    fn encoded_commands_are_decoded() {
+        let conditions = ProcessMatch { decoded_command: vec!["*calc*".to_string()], ..Default::default() };
+        assert!(matches(&conditions, &process(EXPLORER, 100, POWERSHELL, "powershell -enc YwBhAGwAYwA=")));
+        assert!(matches(&conditions, &process(EXPLORER, 100, POWERSHELL, "powershell -EncodedCommand YwBhAGwAYwA=")));
+        assert!(!matches(&conditions, &process(EXPLORER, 100, POWERSHELL, "powershell -c calc")));
+    }
+
+    #[test]
+    fn filters_exclude_by_parent() {
+        let rules = vec![powershell_rule()];
+        assert!(first_match(&rules, &process(EXPLORER, 100, POWERSHELL, "powershell")).is_none());
+        let matched = first_match(&rules, &process(WORD, 100, POWERSHELL, "powershell"));
+        assert_eq!(matched.map(|r| r.title.as_str()), Some("PowerShell outside Explorer"));
+    }
+
+    #[test]
+    fn spoofed_parent_does_not_exclude() {
+        let info = process(EXPLORER, 300, POWERSHELL, "powershell");
+        assert!(info.parent_spoofed.is_some());
+        assert!(first_match(&[powershell_rule()], &info).is_some());
+    }
+
+    #[test]
+    fn disabled_rules_never_match() {
+        let rule = DetectionRule { enabled: false, ..powershell_rule() };
+        assert!(first_match(&[rule], &process(WORD, 100, POWERSHELL, "powershell")).is_none());
+    }
+}
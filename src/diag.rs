@@ -0,0 +1,195 @@
+//! Diagnostics Bundle
+//!
+//! `pc_watcher diag` collects everything needed for a bug report into one ZIP file:
+//! last app log, config (secrets redacted), recent event log excerpt and system info.
+
+use crate::error::Error;
+use chrono::Local;
+use serde_json::Value;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use windows::Win32::Foundation::{BOOL, LPARAM, RECT};
+use windows::Win32::Graphics::Gdi::{EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO};
+use windows::Win32::UI::Shell::IsUserAnAdmin;
+use windows::Win32::UI::WindowsAndMessaging::MONITORINFOF_PRIMARY;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Only the end of the logs is included (bytes)
+const APP_LOG_BYTES: u64 = 1024 * 1024;
+const EVENT_LOG_EXCERPT_BYTES: u64 = 256 * 1024;
+
+/// Config keys containing one of these words are redacted
+const SECRET_KEYS: &[&str] = &["password", "secret", "token", "key", "webhook", "credential"];
+
+/// Newest file in a directory whose name starts with prefix
+fn newest_file(dir: &Path, prefix: &str) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(prefix))
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// Reads the last max_bytes of a file (starting at a line boundary)
+fn read_tail(path: &Path, max_bytes: u64) -> std::io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(max_bytes);
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+    let text = String::from_utf8_lossy(&buffer).into_owned();
+
+    if start > 0 {
+        // Skip the cut line
+        if let Some(pos) = text.find('\n') {
+            return Ok(format!("[... {} bytes skipped ...]\n{}", start, &text[pos + 1..]));
+        }
+    }
+    Ok(text)
+}
+
+/// Replaces values of secret-looking keys with "***" (recursive)
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                let key_lower = key.to_lowercase();
+                if SECRET_KEYS.iter().any(|s| key_lower.contains(s)) && !v.is_null() {
+                    *v = Value::String("***".to_string());
+                } else {
+                    redact(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// Current config as JSON with secrets redacted
+fn redacted_config() -> String {
+    match serde_json::to_value(crate::config::get()) {
+        Ok(mut value) => {
+            redact(&mut value);
+            serde_json::to_string_pretty(&value).unwrap_or_default()
+        }
+        Err(e) => format!("Could not serialize config: {}", e),
+    }
+}
+
+/// Callback for EnumDisplayMonitors (collects monitor descriptions)
+unsafe extern "system" fn monitor_callback(hmonitor: HMONITOR, _hdc: HDC, _rect: *mut RECT, data: LPARAM) -> BOOL {
+    let monitors = &mut *(data.0 as *mut Vec<String>);
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    if GetMonitorInfoW(hmonitor, &mut info).as_bool() {
+        let r = info.rcMonitor;
+        monitors.push(format!(
+            "{}x{} at ({}, {}){}",
+            r.right - r.left,
+            r.bottom - r.top,
+            r.left,
+            r.top,
+            if info.dwFlags & MONITORINFOF_PRIMARY != 0 { " [primary]" } else { "" }
+        ));
+    }
+    BOOL(1)
+}
+
+/// Monitor layout (one line per monitor)
+fn monitor_layout() -> Vec<String> {
+    let mut monitors: Vec<String> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            None,
+            None,
+            Some(monitor_callback),
+            LPARAM(&mut monitors as *mut Vec<String> as isize),
+        );
+    }
+    monitors
+}
+
+/// OS version (output of "ver")
+fn os_version() -> String {
+    std::process::Command::new("cmd")
+        .args(["/C", "ver"])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_else(|e| format!("unknown ({})", e))
+}
+
+/// System information as text
+fn system_info() -> String {
+    let mut info = String::new();
+    info.push_str(&format!("PC Watcher: {}\n", env!("CARGO_PKG_VERSION")));
+    info.push_str(&format!("Created: {}\n", Local::now().format("%Y-%m-%d %H:%M:%S")));
+    info.push_str(&format!("OS: {}\n", os_version()));
+    info.push_str(&format!(
+        "Computer: {}\n",
+        hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default()
+    ));
+    info.push_str(&format!("Admin: {}\n", unsafe { IsUserAnAdmin().as_bool() }));
+    info.push_str("Monitors:\n");
+    for monitor in monitor_layout() {
+        info.push_str(&format!("  {}\n", monitor));
+    }
+    info
+}
+
+/// Creates the diagnostics ZIP next to the EXE and returns its path
+pub fn create_bundle() -> Result<PathBuf, Error> {
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|d| d.to_path_buf()))
+        .unwrap_or_else(|| PathBuf::from("."));
+    let log_dir = crate::logger::get_log_dir();
+
+    let zip_path = exe_dir.join(format!(
+        "pcwatcher_diag_{}.zip",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    ));
+    let file = fs::File::create(&zip_path)
+        .map_err(|e| Error::io(&zip_path, &e))?;
+
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut add = |name: &str, content: &str| -> Result<(), Error> {
+        zip.start_file(name, options).map_err(|e| Error::io(&zip_path, e))?;
+        zip.write_all(content.as_bytes()).map_err(|e| Error::io(&zip_path, &e))
+    };
+
+    add("system_info.txt", &system_info())?;
+    add("config.json", &redacted_config())?;
+
+    // Logs may be missing (e.g. first start) - note it instead of failing
+    let app_log = newest_file(&log_dir, &crate::logger::app_log_prefix())
+        .map(|p| {
+            read_tail(&p, APP_LOG_BYTES)
+                .unwrap_or_else(|e| format!("Could not read {}: {}", p.display(), e))
+        })
+        .unwrap_or_else(|| "No app log found".to_string());
+    add("app.log", &app_log)?;
+    // Also when app.log could not be written
+    add("app_log_memory.log", &crate::log_ring::text())?;
+
+    let event_log = newest_file(&log_dir, &crate::logger::event_log_prefix())
+        .map(|p| {
+            read_tail(&p, EVENT_LOG_EXCERPT_BYTES)
+                .unwrap_or_else(|e| format!("Could not read {}: {}", p.display(), e))
+        })
+        .unwrap_or_else(|| "No event log found".to_string());
+    add("event_log_excerpt.log", &event_log)?;
+
+    zip.finish().map_err(|e| Error::io(&zip_path, e))?;
+    Ok(zip_path)
+}
@@ -0,0 +1,217 @@
+//! Diagnostics Log Window
+//!
+//! Tray action "Diagnostics log": the app log from the memory ring (see log_ring.rs), newest
+//! line at the bottom, followed while open (paused while a line is selected). Works without
+//! console and without app.log. Actions: copy the log, create the diagnostics bundle.
+//! Runs its own message loop like the incident window.
+
+use std::cell::RefCell;
+use tracing::{error, info};
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{GetStockObject, COLOR_BTNFACE, DEFAULT_GUI_FONT, HBRUSH};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Input::KeyboardAndMouse::EnableWindow;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+const DIALOG_WIDTH: i32 = 860;
+const DIALOG_HEIGHT: i32 = 470;
+
+/// New lines are picked up this often
+const REFRESH_TIMER_ID: usize = 1;
+const REFRESH_INTERVAL_MS: u32 = 1000;
+
+// Control IDs
+const ID_FOLLOW: i32 = 101;
+const ID_COPY: i32 = 102;
+const ID_BUNDLE: i32 = 103;
+
+/// State of the open dialog (only one at a time, tray thread)
+struct DialogState {
+    list: HWND,
+    /// Lines written when the list was filled (None = not filled yet)
+    written: Option<u64>,
+    done: bool,
+}
+
+thread_local! {
+    static STATE: RefCell<Option<DialogState>> = const { RefCell::new(None) };
+}
+
+/// Fills the list with the ring when new lines were written and scrolls to the newest one
+/// (a selected line pauses following, so it can be read and copied)
+unsafe fn refresh() {
+    let Some((list, shown)) = STATE.with(|s| s.borrow().as_ref().map(|d| (d.list, d.written))) else {
+        return;
+    };
+    let written = crate::log_ring::written();
+    let selected = SendMessageW(list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0 >= 0;
+    if shown == Some(written) || (shown.is_some() && selected) {
+        return;
+    }
+
+    let lines = crate::log_ring::lines();
+    SendMessageW(list, LB_RESETCONTENT, WPARAM(0), LPARAM(0));
+    if lines.is_empty() {
+        SendMessageW(list, LB_ADDSTRING, WPARAM(0), LPARAM(w!("No app log lines yet").as_ptr() as isize));
+    }
+    for line in &lines {
+        let text: Vec<u16> = line.encode_utf16().chain(std::iter::once(0)).collect();
+        SendMessageW(list, LB_ADDSTRING, WPARAM(0), LPARAM(text.as_ptr() as isize));
+    }
+    SendMessageW(list, LB_SETTOPINDEX, WPARAM(lines.len().saturating_sub(1)), LPARAM(0));
+
+    STATE.with(|s| {
+        if let Some(state) = s.borrow_mut().as_mut() {
+            state.written = Some(written);
+        }
+    });
+}
+
+/// Shows the dialog above the owner until it is closed
+pub unsafe fn show_dialog(owner: HWND) {
+    if STATE.with(|s| s.borrow().is_some()) {
+        return;
+    }
+
+    let instance = GetModuleHandleW(None).unwrap_or_default();
+    let class_name = w!("PCWatcherDiagnostics");
+    let wc = WNDCLASSW {
+        lpfnWndProc: Some(diagnostics_window_proc),
+        hInstance: instance.into(),
+        hCursor: LoadCursorW(None, IDC_ARROW).unwrap_or_default(),
+        hbrBackground: HBRUSH((COLOR_BTNFACE.0 + 1) as isize as *mut _),
+        lpszClassName: class_name,
+        ..Default::default()
+    };
+    // Fails harmlessly if already registered
+    let _ = RegisterClassW(&wc);
+
+    // Centered on the primary monitor (the tray window is invisible)
+    let x = (GetSystemMetrics(SM_CXSCREEN) - DIALOG_WIDTH) / 2;
+    let y = (GetSystemMetrics(SM_CYSCREEN) - DIALOG_HEIGHT) / 2;
+
+    let hwnd = match CreateWindowExW(
+        WS_EX_DLGMODALFRAME,
+        class_name,
+        w!("PC Watcher - Diagnostics log"),
+        WS_POPUP | WS_CAPTION | WS_SYSMENU,
+        x, y,
+        DIALOG_WIDTH,
+        DIALOG_HEIGHT,
+        owner,
+        None,
+        instance,
+        None,
+    ) {
+        Ok(h) => h,
+        Err(e) => {
+            error!("Could not create diagnostics window: {}", e);
+            return;
+        }
+    };
+
+    let font = GetStockObject(DEFAULT_GUI_FONT);
+    let child = |class: PCWSTR, text: PCWSTR, style: WINDOW_STYLE, x: i32, y: i32, w: i32, h: i32, id: i32| {
+        let control = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            class,
+            text,
+            WS_CHILD | WS_VISIBLE | style,
+            x, y, w, h,
+            hwnd,
+            HMENU(id as isize as *mut _),
+            instance,
+            None,
+        )
+        .unwrap_or_default();
+        SendMessageW(control, WM_SETFONT, WPARAM(font.0 as usize), LPARAM(1));
+        control
+    };
+    let list_style = WS_BORDER | WS_VSCROLL | WS_HSCROLL | WS_TABSTOP;
+
+    let label = w!("App log (select a line to pause):");
+    child(w!("STATIC"), label, WINDOW_STYLE::default(), 10, 10, 400, 18, 0);
+    let list = child(w!("LISTBOX"), PCWSTR::null(), list_style, 10, 32, 830, 360, 0);
+    // Long lines can be scrolled horizontally
+    SendMessageW(list, LB_SETHORIZONTALEXTENT, WPARAM(2400), LPARAM(0));
+
+    let button = WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32);
+    child(w!("BUTTON"), w!("Follow"), button, 10, 402, 100, 26, ID_FOLLOW);
+    child(w!("BUTTON"), w!("Copy"), button, 120, 402, 100, 26, ID_COPY);
+    child(w!("BUTTON"), w!("Diagnostics bundle"), button, 230, 402, 130, 26, ID_BUNDLE);
+    child(w!("BUTTON"), w!("Close"), WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32), 740, 402, 100, 26, IDCANCEL.0);
+
+    STATE.with(|s| *s.borrow_mut() = Some(DialogState { list, written: None, done: false }));
+    refresh();
+    SetTimer(hwnd, REFRESH_TIMER_ID, REFRESH_INTERVAL_MS, None);
+
+    let _ = EnableWindow(owner, false);
+    let _ = ShowWindow(hwnd, SW_SHOW);
+    let _ = SetForegroundWindow(hwnd);
+
+    let mut msg = MSG::default();
+    while !STATE.with(|s| s.borrow().as_ref().map(|d| d.done).unwrap_or(true)) {
+        if !GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            // WM_QUIT belongs to the outer loop
+            PostQuitMessage(msg.wParam.0 as i32);
+            break;
+        }
+        if !IsDialogMessageW(hwnd, &msg).as_bool() {
+            let _ = TranslateMessage(&msg);
+            let _ = DispatchMessageW(&msg);
+        }
+    }
+
+    let _ = KillTimer(hwnd, REFRESH_TIMER_ID);
+    let _ = EnableWindow(owner, true);
+    let _ = DestroyWindow(hwnd);
+    STATE.with(|s| s.borrow_mut().take());
+}
+
+/// Window procedure of the diagnostics window
+unsafe extern "system" fn diagnostics_window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    match msg {
+        WM_TIMER if wparam.0 == REFRESH_TIMER_ID => {
+            refresh();
+            LRESULT(0)
+        }
+        WM_COMMAND => {
+            let id = (wparam.0 & 0xFFFF) as i32;
+            if id == ID_FOLLOW {
+                // Clears the selection: new lines are followed again
+                let list = STATE.with(|s| {
+                    let mut state = s.borrow_mut();
+                    let state = state.as_mut()?;
+                    state.written = None;
+                    Some(state.list)
+                });
+                if let Some(list) = list {
+                    SendMessageW(list, LB_SETCURSEL, WPARAM(usize::MAX), LPARAM(0));
+                }
+                refresh();
+            } else if id == ID_COPY {
+                if let Err(e) = crate::alert_window::copy_to_clipboard(hwnd, &crate::log_ring::text()) {
+                    error!("Could not copy the app log: {}", e);
+                }
+            } else if id == ID_BUNDLE {
+                // Zipping logs can take a moment - keep the window responsive
+                std::thread::spawn(|| match crate::diag::create_bundle() {
+                    Ok(path) => {
+                        info!("Diagnostics bundle created: {}", path.display());
+                        crate::open_with::open_location(&path);
+                    }
+                    Err(e) => crate::error::report("Could not create diagnostics bundle", e),
+                });
+            } else if id == IDCANCEL.0 {
+                STATE.with(|s| s.borrow_mut().as_mut().map(|d| d.done = true));
+            }
+            LRESULT(0)
+        }
+        WM_CLOSE => {
+            STATE.with(|s| s.borrow_mut().as_mut().map(|d| d.done = true));
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
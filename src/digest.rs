@@ -0,0 +1,111 @@
+//! Alert Digest
+//!
+//! Optional batching against notification fatigue: Warning alerts are collected and sent as
+//! one summary every few minutes through the notification sinks (Discord, ntfy, Gotify).
+//! Critical alerts are always sent immediately. MQTT keeps publishing every event because
+//! automations react to single events.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use parking_lot::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Lines listed in one summary (the rest is counted)
+const MAX_LINES: usize = 25;
+
+/// How often the interval is checked (shutdown, changed settings)
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+lazy_static::lazy_static! {
+    // Alerts since the last summary
+    static ref BATCH: Mutex<Vec<LogEntry>> = Mutex::new(Vec::new());
+}
+
+/// Collects the alert for the next summary (false = send it immediately)
+pub fn hold(entry: &LogEntry) -> bool {
+    if !crate::config::get().digest.enabled || entry.severity != Severity::Warning {
+        return false;
+    }
+    BATCH.lock().push(entry.clone());
+    true
+}
+
+/// Counts per name, most frequent first
+fn count<'a>(names: impl Iterator<Item = &'a String>) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for name in names {
+        match counts.iter_mut().find(|(n, _)| n == name) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((name.clone(), 1)),
+        }
+    }
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts
+}
+
+/// Summary text: one line per alert, grouped counts (processes, tags) at the top
+fn summarize(batch: &[LogEntry]) -> (String, String) {
+    let counts = count(batch.iter().map(|e| &e.process_name));
+    let tag_counts = count(batch.iter().flat_map(|e| &e.tags));
+
+    let computer = hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+    let title = format!("PC Watcher: {} alerts on {}", batch.len(), computer);
+
+    let mut text = counts
+        .iter()
+        .map(|(name, count)| format!("{}x {}", count, name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !tag_counts.is_empty() {
+        let tags: Vec<String> = tag_counts.iter().map(|(tag, count)| format!("{}x #{}", count, tag)).collect();
+        text.push_str(&format!("\nTags: {}", tags.join(", ")));
+    }
+    text.push('\n');
+    for entry in batch.iter().take(MAX_LINES) {
+        text.push_str(&format!(
+            "\n{} {} {}",
+            entry.timestamp.format("%H:%M:%S"),
+            entry.event_type,
+            entry.process_name
+        ));
+    }
+    if batch.len() > MAX_LINES {
+        text.push_str(&format!("\n... and {} more", batch.len() - MAX_LINES));
+    }
+    (title, text)
+}
+
+/// Sends the collected alerts as one summary
+fn flush() {
+    let batch = std::mem::take(&mut *BATCH.lock());
+    if batch.is_empty() {
+        return;
+    }
+    info!("Sending digest of {} alerts", batch.len());
+    let (title, text) = summarize(&batch);
+    crate::discord::send_digest(&title, &text);
+    crate::push::notify(crate::push::Push {
+        title,
+        message: text,
+        severity: Severity::Warning,
+    });
+}
+
+/// Starts the thread that sends the summaries
+pub fn start_monitor() {
+    thread::spawn(|| {
+        let mut last_flush = Instant::now();
+        while !crate::tray::should_exit() {
+            thread::sleep(CHECK_INTERVAL);
+            let config = crate::config::get().digest;
+            let interval = Duration::from_secs(config.interval_minutes.max(1) * 60);
+            // Switched off: send what was collected
+            if !config.enabled || last_flush.elapsed() >= interval {
+                flush();
+                last_flush = Instant::now();
+            }
+        }
+    });
+}
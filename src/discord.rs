@@ -0,0 +1,254 @@
+//! Discord Webhook
+//!
+//! Posts alerts to a Discord channel webhook as rich embeds: color by severity, fields for
+//! the process tree and the first screenshot of the alert as attached thumbnail. Plain text
+//! webhooks render poorly in Discord. Posting runs on one background thread.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use chrono::Local;
+use crossbeam_channel::{bounded, Sender};
+use once_cell::sync::OnceCell;
+use serde_json::json;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Pending posts (further alerts are dropped while full)
+const QUEUE_SIZE: usize = 64;
+
+/// Time after the alert until the first screenshot is saved
+const SCREENSHOT_DELAY: Duration = Duration::from_secs(3);
+
+/// Discord upload limit without boost
+const MAX_ATTACHMENT_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Discord limit for embed field values
+const MAX_FIELD_LENGTH: usize = 1024;
+
+/// Longest wait for a rate limit before the post is dropped
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(30);
+
+/// One webhook post
+enum Post {
+    Alert(Box<LogEntry>),
+    /// Summary of batched alerts (digest mode)
+    Digest { title: String, text: String },
+}
+
+// Queue of the posting thread
+static SENDER: OnceCell<Sender<Post>> = OnceCell::new();
+
+fn is_enabled() -> bool {
+    let config = crate::config::get().discord;
+    config.enabled && !config.webhook_url.is_empty()
+}
+
+fn queue(post: Post) {
+    let sender = SENDER.get_or_init(start_worker);
+    if sender.try_send(post).is_err() {
+        warn!("Discord queue full - post dropped");
+    }
+}
+
+/// Queues an event for the webhook (no-op if disabled or below the minimum severity)
+pub fn send(entry: &LogEntry) {
+    if is_enabled() && entry.severity >= crate::config::get().discord.min_severity {
+        queue(Post::Alert(Box::new(entry.clone())));
+    }
+}
+
+/// Queues a digest summary
+pub fn send_digest(title: &str, text: &str) {
+    if is_enabled() {
+        queue(Post::Digest { title: title.to_string(), text: text.to_string() });
+    }
+}
+
+/// Posting thread (one request at a time)
+fn start_worker() -> Sender<Post> {
+    let (sender, receiver) = bounded::<Post>(QUEUE_SIZE);
+    thread::spawn(move || {
+        info!("Discord webhook worker started");
+        for post in receiver {
+            let result = match post {
+                Post::Alert(entry) => post_alert(&entry),
+                Post::Digest { title, text } => post_digest(&title, &text),
+            };
+            if let Err(e) = result {
+                error!("Discord webhook failed: {}", e);
+            }
+        }
+    });
+    sender
+}
+
+/// Embed color per severity
+fn color(severity: Severity) -> u32 {
+    match severity {
+        Severity::Critical => 0xCC0000,
+        Severity::Warning => 0xCC6F00,
+        Severity::Info => 0x808080,
+    }
+}
+
+/// Cuts a field value to the Discord limit
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(max.saturating_sub(3)).collect();
+    cut.push_str("...");
+    cut
+}
+
+/// Process chain from the oldest ancestor down to the process itself
+fn process_tree(entry: &LogEntry) -> String {
+    let chain = [
+        (&entry.greatgrandparent_process_name, entry.greatgrandparent_process_id),
+        (&entry.grandparent_process_name, entry.grandparent_process_id),
+        (&entry.parent_process_name, entry.parent_process_id),
+        (&entry.process_name, entry.process_id),
+    ];
+    let lines: Vec<String> = chain
+        .iter()
+        .filter(|(name, _)| !name.is_empty())
+        .enumerate()
+        .map(|(depth, (name, pid))| {
+            let prefix = if depth == 0 { String::new() } else { format!("{}└ ", "  ".repeat(depth - 1)) };
+            format!("{}{} (PID {})", prefix, name, pid)
+        })
+        .collect();
+    format!("```\n{}\n```", truncate(&lines.join("\n"), MAX_FIELD_LENGTH - 8))
+}
+
+/// Embed for an event
+fn build_embed(entry: &LogEntry, thumbnail: bool) -> serde_json::Value {
+    let mut fields = vec![json!({ "name": "Process tree", "value": process_tree(entry) })];
+    if !entry.process_path.is_empty() {
+        fields.push(json!({ "name": "Path", "value": truncate(&entry.process_path, MAX_FIELD_LENGTH) }));
+    }
+    if let Some(command_line) = entry.command_line.as_deref().filter(|c| !c.is_empty()) {
+        fields.push(json!({ "name": "Command line", "value": truncate(command_line, MAX_FIELD_LENGTH) }));
+    }
+    if let Some(url) = entry.browser_url.as_deref().filter(|u| !u.is_empty()) {
+        fields.push(json!({ "name": "URL", "value": truncate(url, MAX_FIELD_LENGTH) }));
+    }
+    let computer = hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+    fields.push(json!({ "name": "Computer", "value": computer, "inline": true }));
+    fields.push(json!({ "name": "Severity", "value": entry.severity.as_str(), "inline": true }));
+    if let Some(incident_id) = entry.incident_id {
+        fields.push(json!({ "name": "Incident", "value": format!("#{}", incident_id), "inline": true }));
+    }
+
+    let description = if entry.window_title.is_empty() { "(no title)" } else { &entry.window_title };
+    let mut embed = json!({
+        "title": format!("{}: {}", entry.event_type, entry.process_name),
+        "description": truncate(description, 2048),
+        "color": color(entry.severity),
+        "timestamp": entry.timestamp.to_rfc3339(),
+        "fields": fields,
+        "footer": { "text": "PC Watcher" },
+    });
+    if thumbnail {
+        embed["thumbnail"] = json!({ "url": "attachment://screenshot.jpg" });
+    }
+    embed
+}
+
+/// First screenshot of the alert (waits until it had time to be saved)
+fn find_screenshot(entry: &LogEntry) -> Option<PathBuf> {
+    if !crate::config::get().discord.attach_screenshot {
+        return None;
+    }
+    let ready_at = entry.timestamp + chrono::Duration::from_std(SCREENSHOT_DELAY).ok()?;
+    if let Ok(wait) = (ready_at - Local::now()).to_std() {
+        thread::sleep(wait);
+    }
+
+    let path = crate::alert_export::find_capture_folder(&entry.timestamp, &entry.process_name)?.join("screenshot_1.jpg");
+    let size = std::fs::metadata(&path).ok()?.len();
+    (size <= MAX_ATTACHMENT_BYTES).then_some(path)
+}
+
+/// multipart/form-data body with payload_json and one image: (content type, body)
+fn multipart(payload: &str, image: &[u8]) -> (String, Vec<u8>) {
+    let boundary = format!("----PCWatcher{}", Local::now().timestamp_nanos_opt().unwrap_or_default());
+    let mut body = Vec::with_capacity(payload.len() + image.len() + 512);
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"payload_json\"\r\nContent-Type: application/json\r\n\r\n{}\r\n",
+            boundary, payload
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"files[0]\"; filename=\"screenshot.jpg\"\r\nContent-Type: image/jpeg\r\n\r\n",
+            boundary
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(image);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    (format!("multipart/form-data; boundary={}", boundary), body)
+}
+
+/// Posts one event with its screenshot
+fn post_alert(entry: &LogEntry) -> Result<(), String> {
+    let image = find_screenshot(entry).and_then(|path| std::fs::read(path).ok());
+
+    let payload = json!({
+        "username": "PC Watcher",
+        "embeds": [build_embed(entry, image.is_some())],
+    })
+    .to_string();
+    let (content_type, body) = match &image {
+        Some(image) => multipart(&payload, image),
+        None => ("application/json".to_string(), payload.into_bytes()),
+    };
+    post(&content_type, &body)
+}
+
+/// Posts a digest summary as one embed
+fn post_digest(title: &str, text: &str) -> Result<(), String> {
+    let payload = json!({
+        "username": "PC Watcher",
+        "embeds": [{
+            "title": title,
+            "description": truncate(text, 4096),
+            "color": color(Severity::Warning),
+            "timestamp": Local::now().to_rfc3339(),
+            "footer": { "text": "PC Watcher digest" },
+        }],
+    })
+    .to_string();
+    post("application/json", payload.as_bytes())
+}
+
+/// Sends one request (retries once after a rate limit)
+fn post(content_type: &str, body: &[u8]) -> Result<(), String> {
+    let url = crate::config::get().discord.webhook_url;
+    for attempt in 0..2 {
+        let response = crate::http::request("POST", &url, &[("Content-Type", content_type)], body)?;
+        match response.status {
+            200..=299 => return Ok(()),
+            429 if attempt == 0 => {
+                // {"retry_after": 1.5, ...} in seconds
+                let retry_after = serde_json::from_slice::<serde_json::Value>(&response.body)
+                    .ok()
+                    .and_then(|json| json["retry_after"].as_f64())
+                    .unwrap_or(1.0);
+                let wait = Duration::from_secs_f64(retry_after.max(0.0)).min(MAX_RETRY_WAIT);
+                warn!("Discord rate limit - retrying in {:.1}s", wait.as_secs_f64());
+                thread::sleep(wait);
+            }
+            status => {
+                let text = String::from_utf8_lossy(&response.body);
+                return Err(format!("HTTP {}: {}", status, truncate(&text, 200)));
+            }
+        }
+    }
+    Err("rate limited".to_string())
+}
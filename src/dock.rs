@@ -0,0 +1,197 @@
+//! Alert Window Docking
+//!
+//! Magnetic edge snapping while the alert window is dragged (within `snap_distance` pixels
+//! of a monitor's work area edge) and the optional docked mode: a window released at an
+//! outer screen edge slides out of view except for a thin strip and peeks out while the
+//! mouse is over it, so the always-on-top window only takes space when it is looked at.
+
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::info;
+use windows::Win32::Foundation::{HWND, POINT, RECT};
+use windows::Win32::Graphics::Gdi::{
+    GetMonitorInfoW, MonitorFromPoint, MonitorFromRect, MONITORINFO, MONITOR_DEFAULTTONEAREST, MONITOR_DEFAULTTONULL,
+};
+use windows::Win32::UI::Input::KeyboardAndMouse::{TrackMouseEvent, TME_LEAVE, TRACKMOUSEEVENT};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetCursorPos, GetWindowRect, SetWindowPos, HWND_TOPMOST, SWP_NOACTIVATE, SWP_NOSIZE, SWP_NOZORDER,
+};
+
+/// Screen edge the window is docked to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    Left,
+    Top,
+    Right,
+    Bottom,
+}
+
+/// Docked window: edge and the position where it is fully visible
+#[derive(Debug, Clone, Copy)]
+struct Docked {
+    edge: Edge,
+    x: i32,
+    y: i32,
+}
+
+lazy_static::lazy_static! {
+    static ref DOCKED: Mutex<Option<Docked>> = Mutex::new(None);
+}
+
+// Docked window is slid out of view right now
+static HIDDEN: AtomicBool = AtomicBool::new(false);
+
+/// Work area (without taskbar) of the monitor showing most of a rectangle
+fn work_area(rect: &RECT) -> Option<RECT> {
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    unsafe { GetMonitorInfoW(MonitorFromRect(rect, MONITOR_DEFAULTTONEAREST), &mut info) }
+        .as_bool()
+        .then_some(info.rcWork)
+}
+
+/// Window position pulled to work area edges within the snap distance
+pub fn snap(x: i32, y: i32, width: i32, height: i32) -> (i32, i32) {
+    let distance = crate::config::get().alert_window.snap_distance;
+    if distance <= 0 {
+        return (x, y);
+    }
+    let Some(work) = work_area(&RECT { left: x, top: y, right: x + width, bottom: y + height }) else {
+        return (x, y);
+    };
+    let pull = |position: i32, near: i32, far: i32, size: i32| {
+        if (position - near).abs() <= distance {
+            near
+        } else if (position + size - far).abs() <= distance {
+            far - size
+        } else {
+            position
+        }
+    };
+    (pull(x, work.left, work.right, width), pull(y, work.top, work.bottom, height))
+}
+
+/// Edge of the work area the rectangle touches, if nothing lies beyond it (no other monitor)
+fn outer_edge(rect: &RECT) -> Option<Edge> {
+    let work = work_area(rect)?;
+    let center = POINT { x: (rect.left + rect.right) / 2, y: (rect.top + rect.bottom) / 2 };
+    let candidates = [
+        (Edge::Left, rect.left <= work.left, POINT { x: work.left - 1, y: center.y }),
+        (Edge::Right, rect.right >= work.right, POINT { x: work.right, y: center.y }),
+        (Edge::Top, rect.top <= work.top, POINT { x: center.x, y: work.top - 1 }),
+        (Edge::Bottom, rect.bottom >= work.bottom, POINT { x: center.x, y: work.bottom }),
+    ];
+    candidates
+        .iter()
+        .find(|(_, touches, beyond)| *touches && unsafe { MonitorFromPoint(*beyond, MONITOR_DEFAULTTONULL) }.is_invalid())
+        .map(|(edge, _, _)| *edge)
+}
+
+/// Docks the window if enabled and it sits at an outer screen edge (after a drag, at startup)
+pub fn attach(hwnd: HWND) {
+    let mut rect = RECT::default();
+    if unsafe { GetWindowRect(hwnd, &mut rect) }.is_err() {
+        return;
+    }
+    let edge = if crate::config::get().alert_window.dock { outer_edge(&rect) } else { None };
+    *DOCKED.lock() = edge.map(|edge| Docked { edge, x: rect.left, y: rect.top });
+    HIDDEN.store(false, Ordering::SeqCst);
+    if let Some(edge) = edge {
+        info!("Alert window docked to the {:?} edge", edge);
+        hide(hwnd);
+    }
+}
+
+/// Releases the dock (dragging starts, docked mode switched off); the window is shown again
+pub fn detach(hwnd: HWND) {
+    if DOCKED.lock().is_some() {
+        show(hwnd);
+    }
+    *DOCKED.lock() = None;
+}
+
+/// Mouse over the window: a hidden docked window peeks out until the mouse leaves
+pub fn on_mouse_move(hwnd: HWND) {
+    if DOCKED.lock().is_none() || !HIDDEN.load(Ordering::SeqCst) {
+        return;
+    }
+    show(hwnd);
+    let mut track = TRACKMOUSEEVENT {
+        cbSize: std::mem::size_of::<TRACKMOUSEEVENT>() as u32,
+        dwFlags: TME_LEAVE,
+        hwndTrack: hwnd,
+        dwHoverTime: 0,
+    };
+    let _ = unsafe { TrackMouseEvent(&mut track) };
+}
+
+/// Mouse left the window (WM_MOUSELEAVE): slide back out of view
+pub fn on_mouse_leave(hwnd: HWND) {
+    if DOCKED.lock().is_none() {
+        return;
+    }
+    // Leave is also sent while a context menu is open over the window
+    let mut cursor = POINT::default();
+    let mut rect = RECT::default();
+    unsafe {
+        let _ = GetCursorPos(&mut cursor);
+        let _ = GetWindowRect(hwnd, &mut rect);
+    }
+    let inside = cursor.x >= rect.left && cursor.x < rect.right && cursor.y >= rect.top && cursor.y < rect.bottom;
+    if !inside {
+        hide(hwnd);
+    }
+}
+
+/// Moves the docked window to its fully visible position
+fn show(hwnd: HWND) {
+    let Some(docked) = *DOCKED.lock() else {
+        return;
+    };
+    HIDDEN.store(false, Ordering::SeqCst);
+    move_window(hwnd, docked.x, docked.y);
+}
+
+/// Slides the docked window out of view, except for the peek strip
+fn hide(hwnd: HWND) {
+    let Some(docked) = *DOCKED.lock() else {
+        return;
+    };
+    let mut rect = RECT::default();
+    if unsafe { GetWindowRect(hwnd, &mut rect) }.is_err() {
+        return;
+    }
+    let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+    let visible = RECT { left: docked.x, top: docked.y, right: docked.x + width, bottom: docked.y + height };
+    let Some(work) = work_area(&visible) else {
+        return;
+    };
+    let peek = crate::config::get().alert_window.dock_peek.max(1);
+    let (x, y) = match docked.edge {
+        Edge::Left => (work.left - width + peek, docked.y),
+        Edge::Right => (work.right - peek, docked.y),
+        Edge::Top => (docked.x, work.top - height + peek),
+        Edge::Bottom => (docked.x, work.bottom - peek),
+    };
+    HIDDEN.store(true, Ordering::SeqCst);
+    move_window(hwnd, x, y);
+}
+
+fn move_window(hwnd: HWND, x: i32, y: i32) {
+    unsafe {
+        let _ = SetWindowPos(hwnd, HWND_TOPMOST, x, y, 0, 0, SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE);
+    }
+}
+
+/// Switches the docked mode on/off (tray menu) and applies it to the window
+pub fn set_enabled(hwnd: HWND, enabled: bool) {
+    crate::config::update(|config| config.alert_window.dock = enabled);
+    info!("Alert window docking: {}", if enabled { "on" } else { "off" });
+    if enabled {
+        attach(hwnd);
+    } else {
+        detach(hwnd);
+    }
+}
@@ -0,0 +1,153 @@
+//! Download Folder Monitoring
+//!
+//! Watches the Downloads folder for new executables and scripts (browsers download to a
+//! temporary name and rename at the end - both count). The alert carries the SHA-256, the
+//! source URL from the Mark-of-the-Web and the process that was focused when the file
+//! appeared - an early warning before the file is ever run.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use chrono::Local;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use windows::Win32::Storage::FileSystem::{
+    FILE_ACTION_ADDED, FILE_ACTION_RENAMED_NEW_NAME, FILE_NOTIFY_CHANGE_FILE_NAME,
+};
+use windows::Win32::System::Com::CoTaskMemFree;
+use windows::Win32::UI::Shell::{FOLDERID_Downloads, SHGetKnownFolderPath, KF_FLAG_DEFAULT};
+use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+/// Event type in the log
+const EVENT_TYPE: &str = "DOWNLOAD";
+
+/// The file size is checked this often until it stops growing
+const SETTLE_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_SETTLE_CHECKS: u32 = 60;
+
+/// Downloads folder of the user (also when moved to another drive)
+fn downloads_folder() -> Option<PathBuf> {
+    unsafe {
+        let path = SHGetKnownFolderPath(&FOLDERID_Downloads, KF_FLAG_DEFAULT, None).ok()?;
+        let folder = path.to_string().ok().map(PathBuf::from);
+        CoTaskMemFree(Some(path.0 as *const _));
+        folder
+    }
+}
+
+/// Checks if a file type is watched (extension list of the config)
+fn is_watched_type(path: &Path) -> bool {
+    let Some(extension) = path.extension().map(|e| e.to_string_lossy().to_lowercase()) else {
+        return false;
+    };
+    crate::config::get()
+        .downloads
+        .extensions
+        .iter()
+        .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(&extension))
+}
+
+/// Source URL of the Mark-of-the-Web (Zone.Identifier stream written by the browser)
+fn source_url(path: &Path) -> Option<String> {
+    let stream = std::fs::read_to_string(format!("{}:Zone.Identifier", path.display())).ok()?;
+    let value = |key: &str| {
+        stream
+            .lines()
+            .find_map(|l| l.strip_prefix(key))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty() && v != "about:internet")
+    };
+    value("HostUrl=").or_else(|| value("ReferrerUrl="))
+}
+
+/// Waits until the file exists with a stable size (download finished)
+fn wait_until_written(path: &Path) -> bool {
+    let mut last_size = None;
+    for _ in 0..MAX_SETTLE_CHECKS {
+        thread::sleep(SETTLE_INTERVAL);
+        let Ok(metadata) = std::fs::metadata(path) else {
+            return false;
+        };
+        if last_size == Some(metadata.len()) {
+            return true;
+        }
+        last_size = Some(metadata.len());
+    }
+    true
+}
+
+/// Hashes the new file, logs it and alerts, unless the rule is disabled
+fn report_download(path: PathBuf, foreground: crate::process_info::ProcessInfo) {
+    // Removed again (temporary file, virus scanner)
+    if !wait_until_written(&path) {
+        return;
+    }
+    let sha256 = crate::virustotal::sha256_file(&path).unwrap_or_else(|e| format!("({})", e));
+    let url = source_url(&path);
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+
+    let severity = Severity::Warning;
+    warn!(
+        "!!! EXECUTABLE DOWNLOADED: {} (SHA-256 {}) from {} while {} was focused !!!",
+        path.display(),
+        sha256,
+        url.as_deref().unwrap_or("unknown source"),
+        foreground.process_name
+    );
+    if crate::rules::is_enabled(Rule::Download) {
+        crate::alert_window::set_rule_alert(
+            Rule::Download,
+            &format!("Downloaded: {} ({})", file_name, foreground.process_name),
+            &path.to_string_lossy(),
+            severity,
+        );
+        crate::sound::play_alert(Rule::Download, severity);
+        let capture = crate::rules::capture_mode(Rule::Download, severity);
+        crate::screenshot::capture_alert_screenshots(foreground.process_name.clone(), EVENT_TYPE, capture, severity);
+    }
+
+    let source = url.map(|u| format!(" from {}", u)).unwrap_or_default();
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: Local::now(),
+        event_type: EVENT_TYPE.to_string(),
+        severity,
+        process_name: foreground.process_name,
+        process_id: foreground.process_id,
+        process_path: foreground.process_path,
+        window_title: format!("{}{} (SHA-256 {})", path.display(), source, sha256),
+        window_class: foreground.window_class,
+        ..Default::default()
+    });
+}
+
+/// Starts the watcher thread (only if enabled in the config)
+pub fn start_watcher() {
+    if !crate::config::get().downloads.enabled {
+        return;
+    }
+    let Some(folder) = downloads_folder() else {
+        error!("Download monitor disabled: Downloads folder not found");
+        return;
+    };
+
+    thread::spawn(move || {
+        info!("Watching downloads in: {}", folder.display());
+        let result = crate::file_watch::watch_directory(&folder, false, FILE_NOTIFY_CHANGE_FILE_NAME, |name, action| {
+            if action != FILE_ACTION_ADDED && action != FILE_ACTION_RENAMED_NEW_NAME {
+                return;
+            }
+            let path = folder.join(&name);
+            if !is_watched_type(&path) {
+                return;
+            }
+            // Focused process at the time the file appeared (usually the browser)
+            let foreground = crate::process_info::get_process_info_cached(unsafe { GetForegroundWindow() });
+            thread::spawn(move || report_download(path, foreground));
+        });
+        if let Err(e) = result {
+            crate::error::report("Download watcher", e);
+        }
+    });
+}
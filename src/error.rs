@@ -0,0 +1,121 @@
+//! Errors and Health
+//!
+//! Crate-wide error type of the capture, window, hook and file code. Non-fatal errors (a GDI
+//! call that failed, a hook that could not be set, a log write) are reported to the health
+//! stream instead of being swallowed: they are logged, counted in the metrics and kept for the
+//! Health section of the alert window.
+
+use chrono::{DateTime, Local};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use tracing::error;
+
+/// Health events kept for the GUI
+const MAX_HEALTH_EVENTS: usize = 50;
+
+/// Error of a subsystem
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    /// GDI call that failed (screen capture, caption rendering)
+    #[error("{0} failed")]
+    Gdi(&'static str),
+    /// Other Windows API call (window creation, clipboard, COM, Media Foundation, WASAPI)
+    #[error("{call} failed: {message}")]
+    Win32 { call: &'static str, message: String },
+    /// Window event or input hook, or folder watch that could not be set
+    #[error("{0}")]
+    Hook(String),
+    /// File operation (also ZIP archives)
+    #[error("{}: {message}", path.display())]
+    Io { path: PathBuf, message: String },
+    /// Export of an event or incident that cannot be built from its input
+    #[error("{0}")]
+    Export(String),
+    /// Event database
+    #[error("{0}")]
+    Database(String),
+    /// Capture source missing or unusable (webcam, audio device, monitor, window)
+    #[error("{0}")]
+    Device(String),
+}
+
+impl Error {
+    /// File error with the path it happened on
+    pub fn io(path: &Path, error: impl std::fmt::Display) -> Self {
+        Error::Io { path: path.to_path_buf(), message: error.to_string() }
+    }
+
+    /// Windows API call that returned an error
+    pub fn win32(call: &'static str, error: &windows::core::Error) -> Self {
+        Error::Win32 { call, message: error.to_string() }
+    }
+
+    /// Subsystem shown in the Health section
+    pub fn subsystem(&self) -> &'static str {
+        match self {
+            Error::Gdi(_) => "GDI",
+            Error::Win32 { .. } => "Windows",
+            Error::Hook(_) => "Hook",
+            Error::Io { .. } => "File",
+            Error::Export(_) => "Export",
+            Error::Database(_) => "Database",
+            Error::Device(_) => "Device",
+        }
+    }
+}
+
+/// Reported error as shown in the Health section
+#[derive(Debug, Clone)]
+pub struct HealthEvent {
+    /// Last occurrence
+    pub time: DateTime<Local>,
+    pub subsystem: &'static str,
+    pub message: String,
+    /// Occurrences in a row (repeated errors are merged)
+    pub count: u32,
+}
+
+lazy_static::lazy_static! {
+    static ref HEALTH: Mutex<VecDeque<HealthEvent>> = Mutex::new(VecDeque::with_capacity(MAX_HEALTH_EVENTS));
+}
+
+/// Reports a non-fatal error ("Screenshot 2" + error)
+pub fn report(context: &str, error: Error) {
+    error!("{}: {}", context, error);
+    crate::metrics::record_error();
+
+    let message = format!("{}: {}", context, error);
+    let subsystem = error.subsystem();
+    let mut events = HEALTH.lock();
+    if let Some(last) = events.back_mut().filter(|e| e.subsystem == subsystem && e.message == message) {
+        last.count += 1;
+        last.time = Local::now();
+        return;
+    }
+    if events.len() >= MAX_HEALTH_EVENTS {
+        events.pop_front();
+    }
+    events.push_back(HealthEvent { time: Local::now(), subsystem, message, count: 1 });
+}
+
+/// Reported errors, newest first
+pub fn recent() -> Vec<HealthEvent> {
+    HEALTH.lock().iter().rev().cloned().collect()
+}
+
+/// Clears the Health section (the error counter of the metrics keeps counting)
+pub fn clear() {
+    HEALTH.lock().clear();
+}
+
+/// Reports the error of a Result and continues without the value
+pub trait ReportExt<T> {
+    fn or_report(self, context: &str) -> Option<T>;
+}
+
+impl<T, E: Into<Error>> ReportExt<T> for Result<T, E> {
+    fn or_report(self, context: &str) -> Option<T> {
+        self.map_err(|e| report(context, e.into())).ok()
+    }
+}
@@ -0,0 +1,254 @@
+//! ETW Event Backend
+//!
+//! Alternative to SetWinEventHook for systems where accessibility hooks are blocked or
+//! unreliable. A real-time ETW session on the Microsoft-Windows-Win32k provider signals
+//! window activity; the window state is then compared to the last known state and the
+//! differences are sent as normal window events (FOCUS, SHOWN, MINIMIZED, ...).
+//! Starting the session needs admin rights (or the "Performance Log Users" group).
+
+use crate::event_hook::{self, EventSource, EventType};
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use windows::core::{GUID, PCWSTR, PWSTR};
+use windows::Win32::Foundation::{BOOL, ERROR_ALREADY_EXISTS, ERROR_SUCCESS, HWND, LPARAM};
+use windows::Win32::System::Diagnostics::Etw::{
+    CloseTrace, ControlTraceW, EnableTraceEx2, OpenTraceW, ProcessTrace, StartTraceW,
+    CONTROLTRACE_HANDLE, EVENT_CONTROL_CODE_ENABLE_PROVIDER, EVENT_RECORD,
+    EVENT_TRACE_CONTROL_STOP, EVENT_TRACE_LOGFILEW, EVENT_TRACE_PROPERTIES,
+    EVENT_TRACE_REAL_TIME_MODE, PROCESSTRACE_HANDLE, PROCESS_TRACE_MODE_EVENT_RECORD,
+    PROCESS_TRACE_MODE_REAL_TIME, TRACE_LEVEL_INFORMATION, WNODE_FLAG_TRACED_GUID,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, GetForegroundWindow, GetWindowThreadProcessId, IsIconic, IsWindowVisible,
+};
+
+/// Microsoft-Windows-Win32k
+const WIN32K_PROVIDER: GUID = GUID::from_u128(0x8c416c79_d49b_4f01_a467_e56d3aa8234c);
+
+const SESSION_NAME: &str = "PCWatcher-Win32k";
+
+/// Window state is compared at most this often (the provider is very chatty)
+const CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Set by the ETW callback, cleared by the state check
+static ACTIVITY: AtomicBool = AtomicBool::new(false);
+
+/// ETW backend
+#[derive(Default)]
+pub struct EtwSource {
+    session: CONTROLTRACE_HANDLE,
+    trace: Option<PROCESSTRACE_HANDLE>,
+    running: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+/// UTF-16 with null terminator
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// EVENT_TRACE_PROPERTIES with room for the session name behind it
+/// (u64 buffer for the alignment of the struct)
+fn trace_properties() -> Vec<u64> {
+    let struct_size = std::mem::size_of::<EVENT_TRACE_PROPERTIES>();
+    let total_size = struct_size + (SESSION_NAME.len() + 1) * 2;
+    let mut buffer = vec![0u64; total_size.div_ceil(8)];
+    unsafe {
+        let properties = &mut *(buffer.as_mut_ptr() as *mut EVENT_TRACE_PROPERTIES);
+        properties.Wnode.BufferSize = total_size as u32;
+        properties.Wnode.Flags = WNODE_FLAG_TRACED_GUID;
+        properties.Wnode.ClientContext = 1; // QPC timestamps
+        properties.LogFileMode = EVENT_TRACE_REAL_TIME_MODE;
+        properties.LoggerNameOffset = struct_size as u32;
+    }
+    buffer
+}
+
+/// Stops the session (also one left over from a crashed run)
+fn stop_session(session: CONTROLTRACE_HANDLE) {
+    let name = to_wide(SESSION_NAME);
+    let mut properties = trace_properties();
+    unsafe {
+        let _ = ControlTraceW(
+            session,
+            PCWSTR(name.as_ptr()),
+            properties.as_mut_ptr() as *mut EVENT_TRACE_PROPERTIES,
+            EVENT_TRACE_CONTROL_STOP,
+        );
+    }
+}
+
+/// ETW callback - only notes that something happened (runs for every Win32k event)
+unsafe extern "system" fn on_event(_record: *mut EVENT_RECORD) {
+    ACTIVITY.store(true, Ordering::Relaxed);
+    event_hook::note_source_activity();
+}
+
+/// Known state of a top-level window
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct WindowState {
+    minimized: bool,
+}
+
+/// Visible top-level windows of other processes in Z-order (topmost first)
+unsafe extern "system" fn collect_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let windows = &mut *(lparam.0 as *mut Vec<(HWND, WindowState)>);
+    let mut pid = 0u32;
+    GetWindowThreadProcessId(hwnd, Some(&mut pid));
+    if pid != std::process::id() && IsWindowVisible(hwnd).as_bool() {
+        windows.push((hwnd, WindowState { minimized: IsIconic(hwnd).as_bool() }));
+    }
+    BOOL(1)
+}
+
+fn visible_windows() -> Vec<(HWND, WindowState)> {
+    let mut windows = Vec::new();
+    unsafe {
+        let _ = EnumWindows(Some(collect_window), LPARAM(&mut windows as *mut Vec<(HWND, WindowState)> as isize));
+    }
+    windows
+}
+
+/// Compares the window state after ETW activity and sends the differences as events
+fn state_checker(running: Arc<AtomicBool>) {
+    let mut foreground = unsafe { GetForegroundWindow() };
+    let mut topmost: Option<HWND> = None;
+    let mut known: HashMap<isize, WindowState> = visible_windows()
+        .into_iter()
+        .map(|(hwnd, state)| (hwnd.0 as isize, state))
+        .collect();
+
+    while running.load(Ordering::Relaxed) {
+        thread::sleep(CHECK_INTERVAL);
+        if !ACTIVITY.swap(false, Ordering::Relaxed) {
+            continue;
+        }
+
+        let current_foreground = unsafe { GetForegroundWindow() };
+        if current_foreground != foreground && !current_foreground.0.is_null() {
+            foreground = current_foreground;
+            event_hook::send_window_event(EventType::Foreground, foreground);
+        }
+
+        let windows = visible_windows();
+        for &(hwnd, state) in &windows {
+            match known.insert(hwnd.0 as isize, state) {
+                None if !state.minimized => event_hook::send_window_event(EventType::Shown, hwnd),
+                Some(old) if !old.minimized && state.minimized => {
+                    event_hook::send_window_event(EventType::Minimized, hwnd)
+                }
+                Some(old) if old.minimized && !state.minimized => {
+                    event_hook::send_window_event(EventType::Restored, hwnd)
+                }
+                _ => {}
+            }
+        }
+        known.retain(|key, _| windows.iter().any(|(hwnd, _)| hwnd.0 as isize == *key));
+
+        // New topmost window that did not get the focus
+        let current_topmost = windows.iter().find(|(_, state)| !state.minimized).map(|(hwnd, _)| *hwnd);
+        if current_topmost != topmost {
+            if let Some(hwnd) = current_topmost.filter(|&hwnd| hwnd != foreground && topmost.is_some()) {
+                event_hook::send_window_event(EventType::ZOrderChanged, hwnd);
+            }
+            topmost = current_topmost;
+        }
+    }
+}
+
+impl EventSource for EtwSource {
+    fn name(&self) -> &'static str {
+        "ETW (Microsoft-Windows-Win32k)"
+    }
+
+    fn start(&mut self) -> Result<()> {
+        let name = to_wide(SESSION_NAME);
+        unsafe {
+            let mut properties = trace_properties();
+            let mut result = StartTraceW(
+                &mut self.session,
+                PCWSTR(name.as_ptr()),
+                properties.as_mut_ptr() as *mut EVENT_TRACE_PROPERTIES,
+            );
+            if result == ERROR_ALREADY_EXISTS {
+                debug!("Stopping old ETW session");
+                stop_session(CONTROLTRACE_HANDLE::default());
+                properties = trace_properties();
+                result = StartTraceW(
+                    &mut self.session,
+                    PCWSTR(name.as_ptr()),
+                    properties.as_mut_ptr() as *mut EVENT_TRACE_PROPERTIES,
+                );
+            }
+            if result != ERROR_SUCCESS {
+                bail!("StartTrace failed: {:?} (admin rights needed)", result);
+            }
+
+            let result = EnableTraceEx2(
+                self.session,
+                &WIN32K_PROVIDER,
+                EVENT_CONTROL_CODE_ENABLE_PROVIDER.0,
+                TRACE_LEVEL_INFORMATION as u8,
+                0,
+                0,
+                0,
+                None,
+            );
+            if result != ERROR_SUCCESS {
+                stop_session(self.session);
+                bail!("EnableTraceEx2 failed: {:?}", result);
+            }
+
+            let mut logger_name = name.clone();
+            let mut logfile = EVENT_TRACE_LOGFILEW {
+                LoggerName: PWSTR(logger_name.as_mut_ptr()),
+                ..Default::default()
+            };
+            logfile.Anonymous1.ProcessTraceMode = PROCESS_TRACE_MODE_REAL_TIME | PROCESS_TRACE_MODE_EVENT_RECORD;
+            logfile.Anonymous2.EventRecordCallback = Some(on_event);
+            let trace = OpenTraceW(&mut logfile);
+            if trace.Value == u64::MAX {
+                stop_session(self.session);
+                bail!("OpenTrace failed");
+            }
+            self.trace = Some(trace);
+
+            self.running.store(true, Ordering::Relaxed);
+
+            // ProcessTrace blocks until the session is stopped
+            self.threads.push(thread::spawn(move || {
+                let result = ProcessTrace(&[trace], None, None);
+                if result != ERROR_SUCCESS {
+                    warn!("ETW ProcessTrace ended: {:?}", result);
+                }
+            }));
+
+            let running = self.running.clone();
+            self.threads.push(thread::spawn(move || state_checker(running)));
+        }
+
+        info!("ETW session started: {}", SESSION_NAME);
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        if !self.running.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        stop_session(self.session);
+        if let Some(trace) = self.trace.take() {
+            unsafe {
+                let _ = CloseTrace(trace);
+            }
+        }
+        for handle in self.threads.drain(..) {
+            let _ = handle.join();
+        }
+        info!("ETW session stopped");
+    }
+}
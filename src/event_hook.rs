@@ -1,27 +1,31 @@
 //! Event Hook System
 //!
-//! Uses Windows SetWinEventHook to capture all window events.
+//! Uses Windows SetWinEventHook to capture all window events
+//! (or an ETW session as alternative backend, see etw.rs).
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use crossbeam_channel::{bounded, Sender, Receiver};
 use once_cell::sync::OnceCell;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, debug_span, error, info, warn, Span};
 use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
 use windows::Win32::UI::Accessibility::{
     SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
     GetMessageW, TranslateMessage, DispatchMessageW, PostThreadMessageW,
-    MSG, WM_QUIT, GetForegroundWindow, IsWindowVisible, IsIconic,
+    MSG, WM_APP, WM_QUIT, GetForegroundWindow, IsWindowVisible, IsIconic,
     SetWindowsHookExW, UnhookWindowsHookEx, CallNextHookEx,
-    HHOOK, WH_MOUSE_LL,
-    WM_LBUTTONDOWN, WM_RBUTTONDOWN, WM_MBUTTONDOWN,
+    HHOOK, WH_MOUSE_LL, WH_KEYBOARD_LL, MSLLHOOKSTRUCT, KBDLLHOOKSTRUCT,
+    LLMHF_INJECTED, LLKHF_INJECTED,
+    WM_LBUTTONDOWN, WM_RBUTTONDOWN, WM_MBUTTONDOWN, WM_MOUSEMOVE, WM_KEYDOWN, WM_SYSKEYDOWN,
 };
 use windows::Win32::System::Threading::GetCurrentThreadId;
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
 use std::sync::atomic::AtomicU64;
 
 // Windows Event constants (must be defined as u32)
@@ -35,12 +39,18 @@ const EVENT_OBJECT_REORDER: u32 = 0x8004;  // Z-Order change (Topmost!)
 const WINEVENT_OUTOFCONTEXT: u32 = 0x0000;
 const WINEVENT_SKIPOWNPROCESS: u32 = 0x0002;
 
+use crate::error::Error;
 use crate::logger::LogEntry;
+use crate::notification::Severity;
 use crate::process_info;
+use crate::rules::Rule;
 
 /// Global channel sender for event data
 static EVENT_SENDER: OnceCell<Sender<WindowEvent>> = OnceCell::new();
 
+/// Global channel sender for log entries (events from other sources, e.g. microphone)
+static LOG_SENDER: OnceCell<Sender<LogEntry>> = OnceCell::new();
+
 /// Shutdown flag
 static SHUTDOWN: AtomicBool = AtomicBool::new(false);
 
@@ -50,12 +60,36 @@ static MESSAGE_THREAD_ID: OnceCell<u32> = OnceCell::new();
 /// Timestamp of last mouse click (in milliseconds since program start)
 static LAST_MOUSE_CLICK_MS: AtomicU64 = AtomicU64::new(0);
 
+/// Timestamp of last injected (SendInput) click or key press
+static LAST_INJECTED_INPUT_MS: AtomicU64 = AtomicU64::new(0);
+
+/// Timestamp of last SYNTHETIC event sent to the worker (rate limit)
+static LAST_SYNTHETIC_EVENT_MS: AtomicU64 = AtomicU64::new(0);
+
+/// Timestamp of the last event from the backend (any event, before filtering)
+static LAST_SOURCE_EVENT_MS: AtomicU64 = AtomicU64::new(0);
+
 /// Mouse hook handle (as usize because HHOOK is not Sync)
 static MOUSE_HOOK_PTR: AtomicUsize = AtomicUsize::new(0);
 
+/// Keyboard hook handle (only used for the injected flag, never key values)
+static KEYBOARD_HOOK_PTR: AtomicUsize = AtomicUsize::new(0);
+
 /// Time window for "recently clicked" (in milliseconds)
 const CLICK_WINDOW_MS: u64 = 500; // 500ms
 
+/// Minimum time between two SYNTHETIC events (macros inject a lot)
+const SYNTHETIC_EVENT_INTERVAL_MS: u64 = 2000;
+
+/// Thread message for the message loop: re-register all hooks
+const WM_APP_REHOOK: u32 = WM_APP + 1;
+
+/// Hook health: no backend event for this long while the user is active = hooks are dead
+const HOOK_SILENCE_MS: u64 = 60_000;
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Health checks with new user input (during the silence) before rehooking
+const ACTIVE_CHECKS_FOR_REHOOK: u32 = 6;
+
 /// Window event types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EventType {
@@ -66,6 +100,8 @@ pub enum EventType {
     Minimized,
     Restored,
     ZOrderChanged,  // Topmost/Z-Order change
+    SyntheticInput, // Injected click/key press (SendInput)
+    SelfTest,       // Probe of the pipeline self-test
 }
 
 impl EventType {
@@ -78,16 +114,105 @@ impl EventType {
             EventType::Minimized => "MINIMIZED",
             EventType::Restored => "RESTORED",
             EventType::ZOrderChanged => "Z-ORDER",
+            EventType::SyntheticInput => "SYNTHETIC",
+            EventType::SelfTest => crate::selftest::EVENT_TYPE,
         }
     }
 }
 
+/// Source of window events (selectable in the config)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventBackend {
+    /// SetWinEventHook (accessibility hooks)
+    #[default]
+    WinEvent,
+    /// ETW session on the Microsoft-Windows-Win32k provider (needs admin rights)
+    /// It only yields state changes (FOREGROUND, SHOWN, MINIMIZED, RESTORED, REORDER) - CREATE and
+    /// object FOCUS still come from WinEvent hooks set alongside it
+    Etw,
+}
+
+/// Backend that delivers window events to the worker (via send_window_event)
+pub trait EventSource {
+    fn name(&self) -> &'static str;
+    fn start(&mut self) -> Result<()>;
+    fn stop(&mut self);
+}
+
 /// Window event data
 #[derive(Debug, Clone)]
 pub struct WindowEvent {
     pub event_type: EventType,
     pub hwnd: isize,
     pub timestamp: chrono::DateTime<chrono::Local>,
+    /// Pipeline span, opened when the hook fired (worker, enrichment and sinks are its children)
+    pub span: Span,
+}
+
+/// Milliseconds since UNIX epoch
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Notes that the backend is alive (called for every raw event, also from ETW)
+pub fn note_source_activity() {
+    LAST_SOURCE_EVENT_MS.store(now_ms(), Ordering::Relaxed);
+}
+
+/// Sends a window event to the worker (used by the event backends)
+pub fn send_window_event(event_type: EventType, hwnd: HWND) {
+    // Noisy window classes never reach the queue
+    if event_type != EventType::SelfTest && is_filtered_class(hwnd) {
+        crate::metrics::record_filtered();
+        return;
+    }
+    if let Some(sender) = EVENT_SENDER.get() {
+        let event = WindowEvent {
+            event_type,
+            hwnd: hwnd.0 as isize,
+            timestamp: chrono::Local::now(),
+            span: debug_span!("hook", event = event_type.as_str()),
+        };
+        if sender.try_send(event).is_err() {
+            crate::metrics::record_drop();
+        }
+    }
+}
+
+/// Checks the window class filter (the process is only looked up for process-specific rules)
+fn is_filtered_class(hwnd: HWND) -> bool {
+    let class = process_info::get_window_class(hwnd);
+    !class.is_empty()
+        && crate::config::is_class_filtered(&class, || process_info::get_process_info_cached(hwnd).process_name)
+}
+
+/// Fill level of the fuller queue (events or log entries) in percent
+pub fn queue_percent() -> u32 {
+    fn percent<T>(sender: Option<&Sender<T>>) -> u32 {
+        match sender.and_then(|s| s.capacity().map(|cap| (s.len(), cap))) {
+            Some((len, cap)) if cap > 0 => (len * 100 / cap) as u32,
+            _ => 0,
+        }
+    }
+    percent(EVENT_SENDER.get()).max(percent(LOG_SENDER.get()))
+}
+
+/// Checks if the application is shutting down
+pub fn is_shutting_down() -> bool {
+    SHUTDOWN.load(Ordering::Relaxed)
+}
+
+/// Logs an entry from another event source (file, GUI, database)
+pub fn send_log_entry(entry: LogEntry) {
+    if let Some(sender) = LOG_SENDER.get() {
+        if sender.try_send(entry).is_err() {
+            crate::metrics::record_drop();
+        }
+    }
 }
 
 /// Checks if a mouse click occurred recently
@@ -102,6 +227,30 @@ fn was_recent_mouse_click() -> bool {
     now.saturating_sub(last_click) < CLICK_WINDOW_MS
 }
 
+/// Checks if input was injected (SendInput) recently
+fn was_recent_injected_input() -> bool {
+    let last_injected = LAST_INJECTED_INPUT_MS.load(Ordering::SeqCst);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+
+    now.saturating_sub(last_injected) < CLICK_WINDOW_MS
+}
+
+/// Remembers injected input and sends a SYNTHETIC event (rate limited)
+fn on_injected_input(now: u64) {
+    LAST_INJECTED_INPUT_MS.store(now, Ordering::SeqCst);
+
+    let last_event = LAST_SYNTHETIC_EVENT_MS.load(Ordering::SeqCst);
+    if now.saturating_sub(last_event) < SYNTHETIC_EVENT_INTERVAL_MS {
+        return;
+    }
+    LAST_SYNTHETIC_EVENT_MS.store(now, Ordering::SeqCst);
+
+    send_window_event(EventType::SyntheticInput, unsafe { GetForegroundWindow() });
+}
+
 /// Low-Level Mouse Hook Callback
 unsafe extern "system" fn mouse_hook_proc(
     code: i32,
@@ -116,7 +265,17 @@ unsafe extern "system" fn mouse_hook_proc(
                 .duration_since(std::time::UNIX_EPOCH)
                 .map(|d| d.as_millis() as u64)
                 .unwrap_or(0);
-            LAST_MOUSE_CLICK_MS.store(now, Ordering::SeqCst);
+            let hook_data = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+            if hook_data.flags & LLMHF_INJECTED != 0 {
+                // Injected clicks don't count as real clicks
+                on_injected_input(now);
+            } else {
+                LAST_MOUSE_CLICK_MS.store(now, Ordering::SeqCst);
+            }
+            crate::input_stats::record_click();
+        } else if msg == WM_MOUSEMOVE {
+            let hook_data = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+            crate::input_stats::record_mouse_move(hook_data.pt.x, hook_data.pt.y);
         }
     }
 
@@ -124,6 +283,30 @@ unsafe extern "system" fn mouse_hook_proc(
     CallNextHookEx(None, code, wparam, lparam)
 }
 
+/// Low-Level Keyboard Hook Callback (injected flag + key count, never key values)
+unsafe extern "system" fn keyboard_hook_proc(
+    code: i32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> windows::Win32::Foundation::LRESULT {
+    if code >= 0 {
+        let msg = wparam.0 as u32;
+        if msg == WM_KEYDOWN || msg == WM_SYSKEYDOWN {
+            crate::input_stats::record_key();
+            let hook_data = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+            if (hook_data.flags & LLKHF_INJECTED).0 != 0 {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0);
+                on_injected_input(now);
+            }
+        }
+    }
+
+    CallNextHookEx(None, code, wparam, lparam)
+}
+
 /// Callback function for Windows Events
 unsafe extern "system" fn win_event_proc(
     _h_win_event_hook: HWINEVENTHOOK,
@@ -134,6 +317,9 @@ unsafe extern "system" fn win_event_proc(
     _dw_event_thread: u32,
     _dwms_event_time: u32,
 ) {
+    // Health monitor: every event counts (also child objects like the caret)
+    note_source_activity();
+
     // Only top-level windows (id_object == 0)
     if id_object != 0 {
         return;
@@ -161,20 +347,12 @@ unsafe extern "system" fn win_event_proc(
         }
     }
 
-    let window_event = WindowEvent {
-        event_type,
-        hwnd: hwnd.0 as isize,
-        timestamp: chrono::Local::now(),
-    };
-
     // Send event to worker thread
-    if let Some(sender) = EVENT_SENDER.get() {
-        let _ = sender.try_send(window_event);
-    }
+    send_window_event(event_type, hwnd);
 }
 
-/// Worker thread that processes and logs events
-fn event_worker(receiver: Receiver<WindowEvent>, log_sender: Sender<LogEntry>) {
+/// Worker thread that processes and logs events (also driven by `pc_watcher bench`)
+pub fn event_worker(receiver: Receiver<WindowEvent>, log_sender: Sender<LogEntry>) {
     info!("Event worker started");
 
     // Duplicate filter: Remember last events
@@ -183,13 +361,17 @@ fn event_worker(receiver: Receiver<WindowEvent>, log_sender: Sender<LogEntry>) {
     while !SHUTDOWN.load(Ordering::Relaxed) {
         match receiver.recv_timeout(Duration::from_millis(100)) {
             Ok(event) => {
+                crate::metrics::record_event();
+                let _worker = debug_span!(parent: &event.span, "worker", hwnd = event.hwnd).entered();
+
                 // Duplicate check (same window + event within 100ms)
                 let now_ms = event.timestamp.timestamp_millis();
                 let is_duplicate = last_events.iter().any(|(hwnd, etype, time)| {
                     *hwnd == event.hwnd && *etype == event.event_type && (now_ms - time).abs() < 100
                 });
 
-                if is_duplicate {
+                // Honeypot handles its own events (reading its title would trigger it)
+                if is_duplicate || crate::honeypot::is_honeypot(event.hwnd) {
                     continue;
                 }
 
@@ -201,7 +383,9 @@ fn event_worker(receiver: Receiver<WindowEvent>, log_sender: Sender<LogEntry>) {
 
                 // Collect process information (with cache for performance)
                 let hwnd = HWND(event.hwnd as *mut _);
-                let proc_info = process_info::get_process_info_cached(hwnd);
+                let enrichment = debug_span!("enrichment", process = tracing::field::Empty);
+                let proc_info = enrichment.in_scope(|| process_info::get_process_info_cached(hwnd));
+                enrichment.record("process", proc_info.process_name.as_str());
 
                 // Warning for suspicious processes (on FOCUS, SHOWN, CREATED)
                 let dominated_event = matches!(
@@ -215,15 +399,24 @@ fn event_worker(receiver: Receiver<WindowEvent>, log_sender: Sender<LogEntry>) {
                 // Check for focus change without mouse click (suspicious!)
                 let focus_without_click = event.event_type == EventType::Foreground && !was_recent_mouse_click();
 
-                if dominated_event && is_suspicious_process {
+                // Whitelisted and allow-listed processes (and self-test probes) never trigger alerts
+                let is_self_test = event.event_type == EventType::SelfTest;
+                let is_whitelisted = is_self_test
+                    || crate::config::is_whitelisted(&proc_info.process_name)
+                    || crate::hash_lists::is_allowed(&proc_info.process_path);
+
+                let mut severity = Severity::Info;
+                let mut alert_message = None;
+                // Counter-measure taken (logged as its own entry after the event)
+                let mut action_event: Option<&'static str> = None;
+
+                if is_whitelisted {
+                    // Logged only
+                } else if dominated_event && is_suspicious_process {
                     warn!("!!! SUSPICIOUS PROCESS: {} - {} !!!",
                         proc_info.process_name, proc_info.process_path);
-                    crate::alert_window::set_alert(
-                        &proc_info.process_name,
-                        &proc_info.process_path
-                    );
-                    // Take screenshots (3 with delay)
-                    crate::screenshot::capture_alert_screenshots(proc_info.process_name.clone());
+                    severity = Severity::Critical;
+                    alert_message = Some((Rule::SuspiciousProcess, proc_info.process_name.clone()));
                 } else if focus_without_click {
                     // Focus change without mouse click - suspicious!
                     // But not for own windows or desktop
@@ -241,25 +434,211 @@ fn event_worker(receiver: Receiver<WindowEvent>, log_sender: Sender<LogEntry>) {
                     if !is_ignored {
                         warn!("!!! FOCUS WITHOUT CLICK: {} - {} !!!",
                             proc_info.process_name, proc_info.process_path);
-                        crate::alert_window::set_alert(
-                            &format!("{} (no click!)", proc_info.process_name),
-                            &proc_info.process_path
-                        );
-                        // Take screenshots (3 with delay)
-                        crate::screenshot::capture_alert_screenshots(proc_info.process_name.clone());
+                        severity = Severity::Warning;
+                        let repeated = crate::focus_guard::record_steal(&proc_info.process_name);
+                        // Blocked by the user: the stealer goes back to the taskbar
+                        if crate::focus_guard::is_blocked(&proc_info.process_name) {
+                            crate::focus_guard::minimize(hwnd);
+                            action_event = Some(crate::focus_guard::BLOCKED_EVENT_TYPE);
+                        }
+                        let reason = if repeated { "steals focus repeatedly - right-click to block" } else { "no click!" };
+                        alert_message = Some((
+                            Rule::FocusWithoutClick,
+                            format!("{} ({})", proc_info.process_name, reason),
+                        ));
+                    }
+                }
+
+                // Synthetic input (SendInput): remote control / macro malware
+                let synthetic_input = !is_whitelisted
+                    && (event.event_type == EventType::SyntheticInput
+                        || (event.event_type == EventType::Foreground && was_recent_injected_input()));
+                if synthetic_input {
+                    warn!("!!! SYNTHETIC INPUT DETECTED: {} - {} !!!",
+                        proc_info.process_name, proc_info.process_path);
+                    // Focus change caused by injected input raises the severity
+                    severity = if event.event_type == EventType::Foreground {
+                        severity.raised()
+                    } else {
+                        severity.max(Severity::Warning)
+                    };
+                    // Replaces "no click" (the injected click was not counted as a click)
+                    if !matches!(alert_message, Some((Rule::SuspiciousProcess, _))) {
+                        alert_message = Some((
+                            Rule::SyntheticInput,
+                            format!("{} (synthetic input!)", proc_info.process_name),
+                        ));
+                    }
+                }
+
+                // Window made itself always-on-top (overlay / clickjacking)
+                let own_window = proc_info.process_id == std::process::id();
+                let became_topmost = !is_whitelisted
+                    && !own_window
+                    && matches!(event.event_type, EventType::ZOrderChanged | EventType::Shown | EventType::Created)
+                    && crate::topmost::became_topmost(hwnd, &proc_info.window_class);
+                if became_topmost {
+                    warn!("!!! WINDOW BECAME TOPMOST: {} - {} !!!",
+                        proc_info.process_name, proc_info.window_title);
+                    severity = severity.max(Severity::Warning);
+                    if crate::rules::is_enabled(Rule::TopmostWindow) && crate::config::get().topmost.auto_demote {
+                        match crate::topmost::demote(hwnd) {
+                            Ok(()) => action_event = Some(crate::topmost::DEMOTED_EVENT_TYPE),
+                            Err(e) => error!("Could not demote {}: {}", proc_info.process_name, e),
+                        }
+                    }
+                    if alert_message.is_none() {
+                        let action = if action_event.is_some() { "always on top, demoted" } else { "always on top!" };
+                        alert_message = Some((
+                            Rule::TopmostWindow,
+                            format!("{} ({})", proc_info.process_name, action),
+                        ));
+                    }
+                }
+
+                // Watched processes: every event is at least Warning
+                if !is_self_test && crate::watch::is_watched(&proc_info.process_name) {
+                    if event.event_type == EventType::Foreground {
+                        crate::watch::record_focus(&proc_info.process_name);
+                    }
+                    if severity < Severity::Warning {
+                        info!("Watched process event: {} ({})",
+                            proc_info.process_name, event.event_type.as_str());
+                        severity = Severity::Warning;
+                        alert_message = Some((
+                            Rule::WatchedProcess,
+                            format!("{} (watched)", proc_info.process_name),
+                        ));
+                    }
+                }
+
+                // Executable against the hash lists (hashed in the background, once per file)
+                if dominated_event && !is_self_test {
+                    crate::hash_lists::check_process(&proc_info);
+                }
+
+                // One-time checks of the executable on the first window of a process
+                let new_process = !is_whitelisted && dominated_event && process_info::is_new_process(&proc_info);
+
+                // Forged parent PID: the hierarchy cannot be trusted
+                if new_process {
+                    if let Some(reason) = &proc_info.parent_spoofed {
+                        warn!("Possibly spoofed parent of {} (PID {}): {}",
+                            proc_info.process_name, proc_info.process_id, reason);
+                    }
+                }
+
+                // Suspicious location or file name of the executable
+                let heuristics = if new_process {
+                    crate::path_heuristics::check(&proc_info.process_path)
+                } else {
+                    Vec::new()
+                };
+                if let Some(strongest) = heuristics.iter().map(|f| f.severity).max() {
+                    let descriptions: Vec<&str> = heuristics.iter().map(|f| f.description.as_str()).collect();
+                    if strongest >= Severity::Warning {
+                        warn!("!!! SUSPICIOUS PATH: {} ({}) !!!", proc_info.process_path, descriptions.join(", "));
+                        if alert_message.is_none() || strongest >= severity {
+                            alert_message = Some((
+                                Rule::PathHeuristic,
+                                format!("{} ({})", proc_info.process_name, descriptions.join(", ")),
+                            ));
+                        }
+                    }
+                    severity = severity.max(strongest);
+                }
+
+                // Process disguised as a Windows binary
+                let masquerade = if new_process { crate::masquerade::check(&proc_info.process_path) } else { None };
+                if let Some(finding) = &masquerade {
+                    warn!("!!! MASQUERADING PROCESS: {} ({}) !!!", proc_info.process_name, finding.description);
+                    if alert_message.is_none() || finding.severity >= severity {
+                        alert_message = Some((
+                            Rule::Masquerade,
+                            format!("{} ({})", proc_info.process_name, finding.description),
+                        ));
+                    }
+                    severity = severity.max(finding.severity);
+                }
+
+                // Own and imported process detections
+                let detection = if new_process { crate::detection::matching_rule(&proc_info) } else { None };
+                if let Some(detection) = &detection {
+                    warn!("!!! DETECTION \"{}\": {} - {} !!!",
+                        detection.title, proc_info.process_name, proc_info.process_path);
+                    // Replaces weaker alerts (a rule is more specific than "no click")
+                    if alert_message.is_none() || detection.severity >= severity {
+                        alert_message = Some((
+                            Rule::Detection,
+                            format!("{} ({})", proc_info.process_name, detection.title),
+                        ));
+                    }
+                    severity = severity.max(detection.severity);
+                }
+
+                // Disabled rules: logged with severity, but no alert
+                let alert_message = alert_message.filter(|(rule, _)| crate::rules::is_enabled(*rule));
+
+                // Where the pointer was when the evidence was captured
+                let cursor_pos = if alert_message.is_some() {
+                    crate::screenshot::cursor_position()
+                } else {
+                    None
+                };
+
+                if let Some((rule, message)) = alert_message {
+                    crate::alert_window::set_rule_alert(rule, &message, &proc_info.process_path, severity);
+                    crate::sound::play_alert(rule, severity);
+                    // Capture evidence as configured for the rule
+                    let capture = crate::rules::capture_mode(rule, severity);
+                    crate::screenshot::capture_alert_screenshots(
+                        proc_info.process_name.clone(),
+                        event.event_type.as_str(),
+                        capture,
+                        severity,
+                    );
+                    // Defender/VirusTotal/YARA verdict for the executable (opt-in, in the background)
+                    if severity == Severity::Critical {
+                        crate::reputation::lookup(&proc_info.process_name, &proc_info.process_path);
+                        crate::yara_scan::lookup(&proc_info.process_name, &proc_info.process_path);
+                        // Unsigned DLLs loaded into the process
+                        crate::module_scan::lookup(proc_info.process_id, &proc_info.process_name, &proc_info.process_path);
                     }
+                    crate::virustotal::lookup(&proc_info.process_name, &proc_info.process_path);
+                    // Log when the offending process exits
+                    crate::process_exit::watch(proc_info.process_id, &proc_info.process_name);
                 }
 
+                // Browser focus: URL of the active tab
+                let browser_url = if event.event_type == EventType::Foreground
+                    && crate::browser::is_browser(&proc_info.process_name)
+                {
+                    crate::browser::get_active_url(hwnd)
+                } else {
+                    None
+                };
+
+                // Popups and dialogs belong to their owning application window
+                let (owner_process_name, owner_process_id, owner_window_title) =
+                    process_info::get_owner_info(hwnd).unwrap_or_default();
+
                 // Create log entry
                 let log_entry = LogEntry {
                     timestamp: event.timestamp,
+                    uptime_ms: 0,
                     event_type: event.event_type.as_str().to_string(),
+                    severity,
                     process_name: proc_info.process_name,
                     process_id: proc_info.process_id,
                     process_path: proc_info.process_path,
                     window_title: proc_info.window_title,
                     window_class: proc_info.window_class,
                     command_line: proc_info.command_line,
+                    browser_url,
+                    cursor_pos,
+                    note: None,
+                    incident_id: None,
+                    tags: detection.as_ref().map(|d| d.tags.clone()).unwrap_or_default(),
                     parent_process_name: proc_info.parent_process_name,
                     parent_process_id: proc_info.parent_process_id,
                     parent_process_path: proc_info.parent_process_path,
@@ -269,10 +648,80 @@ fn event_worker(receiver: Receiver<WindowEvent>, log_sender: Sender<LogEntry>) {
                     greatgrandparent_process_name: proc_info.greatgrandparent_process_name,
                     greatgrandparent_process_id: proc_info.greatgrandparent_process_id,
                     greatgrandparent_process_path: proc_info.greatgrandparent_process_path,
+                    parent_spoofed: proc_info.parent_spoofed,
+                    owner_process_name,
+                    owner_process_id,
+                    owner_window_title,
+                    host_process_name: proc_info.host_process_name,
+                    host_process_id: proc_info.host_process_id,
+                    span: Some(Span::current()),
                 };
 
+                let action_entry = action_event.map(|event_type| LogEntry {
+                    timestamp: chrono::Local::now(),
+                    event_type: event_type.to_string(),
+                    process_name: log_entry.process_name.clone(),
+                    process_id: log_entry.process_id,
+                    process_path: log_entry.process_path.clone(),
+                    window_title: log_entry.window_title.clone(),
+                    window_class: log_entry.window_class.clone(),
+                    ..Default::default()
+                });
+
+                // Which rule matched (own entry, so the window event keeps its fields)
+                let detection_entry = detection.map(|detection| LogEntry {
+                    timestamp: chrono::Local::now(),
+                    event_type: "DETECTION".to_string(),
+                    severity: detection.severity,
+                    process_name: log_entry.process_name.clone(),
+                    process_id: log_entry.process_id,
+                    process_path: log_entry.process_path.clone(),
+                    window_title: match &detection.source {
+                        Some(source) => format!("{} ({})", detection.title, source),
+                        None => detection.title,
+                    },
+                    command_line: log_entry.command_line.clone(),
+                    tags: detection.tags,
+                    ..Default::default()
+                });
+
+                // One entry per matched path heuristic
+                let heuristic_entries: Vec<LogEntry> = heuristics
+                    .into_iter()
+                    .map(|finding| LogEntry {
+                        timestamp: chrono::Local::now(),
+                        event_type: "PATH_HEURISTIC".to_string(),
+                        severity: finding.severity,
+                        process_name: log_entry.process_name.clone(),
+                        process_id: log_entry.process_id,
+                        process_path: log_entry.process_path.clone(),
+                        window_title: finding.description,
+                        ..Default::default()
+                    })
+                    .collect();
+
+                let masquerade_entry = masquerade.map(|finding| LogEntry {
+                    timestamp: chrono::Local::now(),
+                    event_type: "MASQUERADE".to_string(),
+                    severity: finding.severity,
+                    process_name: log_entry.process_name.clone(),
+                    process_id: log_entry.process_id,
+                    process_path: log_entry.process_path.clone(),
+                    window_title: finding.description,
+                    ..Default::default()
+                });
+
                 // Send to logger
-                let _ = log_sender.try_send(log_entry);
+                let follow_ups = action_entry
+                    .into_iter()
+                    .chain(detection_entry)
+                    .chain(heuristic_entries)
+                    .chain(masquerade_entry);
+                for entry in std::iter::once(log_entry).chain(follow_ups) {
+                    if log_sender.try_send(entry).is_err() {
+                        crate::metrics::record_drop();
+                    }
+                }
             }
             Err(crossbeam_channel::RecvTimeoutError::Timeout) => continue,
             Err(crossbeam_channel::RecvTimeoutError::Disconnected) => break,
@@ -282,114 +731,133 @@ fn event_worker(receiver: Receiver<WindowEvent>, log_sender: Sender<LogEntry>) {
     info!("Event worker ended");
 }
 
-/// Sets all Windows event hooks
-fn set_hooks() -> Result<Vec<HWINEVENTHOOK>> {
-    let mut hooks = Vec::new();
-    let flags = WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS;
+/// SetWinEventHook backend (default)
+#[derive(Default)]
+struct WinEventSource {
+    hooks: Vec<HWINEVENTHOOK>,
+}
+
+impl EventSource for WinEventSource {
+    fn name(&self) -> &'static str {
+        "WinEvent hooks"
+    }
+
+    fn start(&mut self) -> Result<()> {
+        self.hooks = set_win_event_hooks(WIN_EVENTS)?;
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        unhook_all(&mut self.hooks);
+    }
+}
+
+/// ETW backend plus WinEvent hooks for the events it does not produce (CREATE, object FOCUS)
+#[derive(Default)]
+struct EtwEventSource {
+    etw: crate::etw::EtwSource,
+    hooks: Vec<HWINEVENTHOOK>,
+}
+
+impl EventSource for EtwEventSource {
+    fn name(&self) -> &'static str {
+        self.etw.name()
+    }
+
+    fn start(&mut self) -> Result<()> {
+        self.etw.start()?;
+        // Missing hooks only lose these events, ETW still delivers the rest
+        self.hooks = set_win_event_hooks(ETW_MISSING_EVENTS).unwrap_or_default();
+        Ok(())
+    }
 
+    fn stop(&mut self) {
+        self.etw.stop();
+        unhook_all(&mut self.hooks);
+    }
+}
+
+/// Removes window event hooks
+fn unhook_all(hooks: &mut Vec<HWINEVENTHOOK>) {
     unsafe {
-        // Foreground focus (most important hook!)
-        let hook = SetWinEventHook(
-            EVENT_SYSTEM_FOREGROUND,
-            EVENT_SYSTEM_FOREGROUND,
-            None,
-            Some(win_event_proc),
-            0,
-            0,
-            flags,
-        );
-        if hook.is_invalid() {
-            error!("Could not set FOREGROUND hook");
-        } else {
-            hooks.push(hook);
-            debug!("FOREGROUND hook set");
+        for hook in hooks.drain(..) {
+            let _ = UnhookWinEvent(hook);
         }
+    }
+}
 
-        // Window creation
-        let hook = SetWinEventHook(
-            EVENT_OBJECT_CREATE,
-            EVENT_OBJECT_CREATE,
-            None,
-            Some(win_event_proc),
-            0,
-            0,
-            flags,
-        );
-        if hook.is_invalid() {
-            warn!("Could not set CREATE hook");
-        } else {
-            hooks.push(hook);
-            debug!("CREATE hook set");
+/// Starts the configured event backend (falls back to WinEvent hooks if ETW fails)
+fn start_event_source() -> Result<Box<dyn EventSource>> {
+    if crate::config::get().event_backend == EventBackend::Etw {
+        let mut etw = EtwEventSource::default();
+        match etw.start() {
+            Ok(()) => {
+                info!("Event backend: {}", etw.name());
+                return Ok(Box::new(etw));
+            }
+            Err(e) => warn!("ETW backend not available, using WinEvent hooks: {}", e),
         }
+    }
 
-        // Window shown
-        let hook = SetWinEventHook(
-            EVENT_OBJECT_SHOW,
-            EVENT_OBJECT_SHOW,
-            None,
-            Some(win_event_proc),
-            0,
-            0,
-            flags,
-        );
-        if hook.is_invalid() {
-            warn!("Could not set SHOW hook");
-        } else {
-            hooks.push(hook);
-            debug!("SHOW hook set");
-        }
+    let mut source = WinEventSource::default();
+    source.start()?;
+    info!("Event backend: {}", source.name());
+    Ok(Box::new(source))
+}
 
-        // Focus within windows
-        let hook = SetWinEventHook(
-            EVENT_OBJECT_FOCUS,
-            EVENT_OBJECT_FOCUS,
-            None,
-            Some(win_event_proc),
-            0,
-            0,
-            flags,
-        );
-        if hook.is_invalid() {
-            warn!("Could not set FOCUS hook");
-        } else {
-            hooks.push(hook);
-            debug!("FOCUS hook set");
-        }
+/// Hooked window events (first, last, name)
+const WIN_EVENTS: &[(u32, u32, &str)] = &[
+    // Foreground focus (most important hook!)
+    (EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_FOREGROUND, "FOREGROUND"),
+    // Window creation
+    (EVENT_OBJECT_CREATE, EVENT_OBJECT_CREATE, "CREATE"),
+    // Window shown
+    (EVENT_OBJECT_SHOW, EVENT_OBJECT_SHOW, "SHOW"),
+    // Focus within windows
+    (EVENT_OBJECT_FOCUS, EVENT_OBJECT_FOCUS, "FOCUS"),
+    // Minimize/Restore
+    (EVENT_SYSTEM_MINIMIZESTART, EVENT_SYSTEM_MINIMIZEEND, "MINIMIZE"),
+    // Z-Order changes (Topmost!)
+    (EVENT_OBJECT_REORDER, EVENT_OBJECT_REORDER, "REORDER"),
+];
+
+/// Events the ETW backend can't derive from the window state - hooked alongside it
+const ETW_MISSING_EVENTS: &[(u32, u32, &str)] = &[
+    (EVENT_OBJECT_CREATE, EVENT_OBJECT_CREATE, "CREATE"),
+    (EVENT_OBJECT_FOCUS, EVENT_OBJECT_FOCUS, "FOCUS"),
+];
+
+/// Sets the Windows event hooks of the list
+fn set_win_event_hooks(events: &[(u32, u32, &str)]) -> Result<Vec<HWINEVENTHOOK>> {
+    let flags = WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS;
+    let mut hooks = Vec::new();
 
-        // Minimize/Restore
-        let hook = SetWinEventHook(
-            EVENT_SYSTEM_MINIMIZESTART,
-            EVENT_SYSTEM_MINIMIZEEND,
-            None,
-            Some(win_event_proc),
-            0,
-            0,
-            flags,
-        );
+    for &(first, last, name) in events {
+        let hook = unsafe { SetWinEventHook(first, last, None, Some(win_event_proc), 0, 0, flags) };
         if hook.is_invalid() {
-            warn!("Could not set MINIMIZE hook");
+            report_hook_failure(name);
         } else {
             hooks.push(hook);
-            debug!("MINIMIZE hook set");
+            debug!("{} hook set", name);
         }
+    }
 
-        // Z-Order changes (Topmost!)
-        let hook = SetWinEventHook(
-            EVENT_OBJECT_REORDER,
-            EVENT_OBJECT_REORDER,
-            None,
-            Some(win_event_proc),
-            0,
-            0,
-            flags,
-        );
-        if hook.is_invalid() {
-            warn!("Could not set REORDER hook");
-        } else {
-            hooks.push(hook);
-            debug!("REORDER hook set (Z-Order/Topmost)");
-        }
+    if hooks.is_empty() {
+        anyhow::bail!("No hooks could be set!");
+    }
+
+    info!("{} event hooks active", hooks.len());
+    Ok(hooks)
+}
+
+/// Reports a window event hook that could not be set (Health section)
+fn report_hook_failure(name: &str) {
+    crate::error::report("Event hooks", Error::Hook(format!("Could not set {} hook", name)));
+}
 
+/// Sets the low-level mouse/keyboard hooks (needed by every backend)
+fn set_input_hooks() {
+    unsafe {
         // Low-Level Mouse Hook for click detection
         let mouse_hook = SetWindowsHookExW(
             WH_MOUSE_LL,
@@ -403,31 +871,43 @@ fn set_hooks() -> Result<Vec<HWINEVENTHOOK>> {
                 info!("Mouse hook set (click detection)");
             }
             Err(e) => {
-                warn!("Could not set mouse hook: {}", e);
+                crate::error::report("Input hooks", Error::Hook(format!("Could not set mouse hook: {}", e)));
             }
         }
-    }
 
-    if hooks.is_empty() {
-        anyhow::bail!("No hooks could be set!");
+        // Low-Level Keyboard Hook for injected input detection
+        let keyboard_hook = SetWindowsHookExW(
+            WH_KEYBOARD_LL,
+            Some(keyboard_hook_proc),
+            None,
+            0,
+        );
+        match keyboard_hook {
+            Ok(h) => {
+                KEYBOARD_HOOK_PTR.store(h.0 as usize, Ordering::SeqCst);
+                info!("Keyboard hook set (injected input detection)");
+            }
+            Err(e) => {
+                crate::error::report("Input hooks", Error::Hook(format!("Could not set keyboard hook: {}", e)));
+            }
+        }
     }
-
-    info!("{} event hooks active", hooks.len());
-    Ok(hooks)
 }
 
-/// Removes all hooks
-fn unhook_all(hooks: Vec<HWINEVENTHOOK>) {
+/// Removes the low-level input hooks
+fn unhook_input() {
     unsafe {
-        for hook in hooks {
-            let _ = UnhookWinEvent(hook);
-        }
         // Remove mouse hook
         let mouse_ptr = MOUSE_HOOK_PTR.load(Ordering::SeqCst);
         if mouse_ptr != 0 {
             let mouse_hook = HHOOK(mouse_ptr as *mut _);
             let _ = UnhookWindowsHookEx(mouse_hook);
         }
+        // Remove keyboard hook
+        let keyboard_ptr = KEYBOARD_HOOK_PTR.load(Ordering::SeqCst);
+        if keyboard_ptr != 0 {
+            let _ = UnhookWindowsHookEx(HHOOK(keyboard_ptr as *mut _));
+        }
     }
     info!("All hooks removed");
 }
@@ -441,14 +921,79 @@ fn log_current_foreground(sender: &Sender<WindowEvent>) {
                 event_type: EventType::Foreground,
                 hwnd: hwnd.0 as isize,
                 timestamp: chrono::Local::now(),
+                span: debug_span!("hook", event = EventType::Foreground.as_str()),
             };
             let _ = sender.try_send(event);
         }
     }
 }
 
+/// Re-registers backend and input hooks (runs on the message loop thread)
+fn rehook(source: &mut dyn EventSource) {
+    let silence_secs = now_ms().saturating_sub(LAST_SOURCE_EVENT_MS.load(Ordering::Relaxed)) / 1000;
+
+    source.stop();
+    unhook_input();
+    let result = source.start();
+    set_input_hooks();
+    note_source_activity();
+
+    match result {
+        Ok(()) => {
+            warn!("Hooks re-registered after {}s without events", silence_secs);
+            send_log_entry(LogEntry {
+                timestamp: chrono::Local::now(),
+                event_type: "HOOK_RECOVERED".to_string(),
+                severity: Severity::Warning,
+                process_name: "pc_watcher".to_string(),
+                window_title: format!("{} re-registered after {}s without events", source.name(), silence_secs),
+                ..Default::default()
+            });
+        }
+        Err(e) => error!("Re-registering hooks failed: {}", e),
+    }
+}
+
+/// Watches for prolonged silence of the backend while the user is active
+/// (hooks can silently stop after shell crashes or DWM restarts)
+fn hook_health_monitor() {
+    let mut last_input_tick = 0u32;
+    let mut active_checks = 0u32;
+
+    while !SHUTDOWN.load(Ordering::Relaxed) {
+        thread::sleep(HEALTH_CHECK_INTERVAL);
+
+        // System-wide input (independent of our own low-level hooks)
+        let mut input = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        if unsafe { GetLastInputInfo(&mut input) }.as_bool() && input.dwTime != last_input_tick {
+            last_input_tick = input.dwTime;
+            active_checks += 1;
+        }
+
+        let silence = now_ms().saturating_sub(LAST_SOURCE_EVENT_MS.load(Ordering::Relaxed));
+        if silence < HOOK_SILENCE_MS {
+            active_checks = 0;
+            continue;
+        }
+
+        if active_checks >= ACTIVE_CHECKS_FOR_REHOOK {
+            warn!("No window events for {}s despite user input - re-registering hooks", silence / 1000);
+            active_checks = 0;
+            note_source_activity();
+            if let Some(&thread_id) = MESSAGE_THREAD_ID.get() {
+                unsafe {
+                    let _ = PostThreadMessageW(thread_id, WM_APP_REHOOK, WPARAM(0), LPARAM(0));
+                }
+            }
+        }
+    }
+}
+
 /// Windows Message Loop
-fn message_loop() {
+fn message_loop(source: &mut dyn EventSource) {
     unsafe {
         // Save thread ID for later shutdown
         let thread_id = GetCurrentThreadId();
@@ -459,6 +1004,10 @@ fn message_loop() {
             if SHUTDOWN.load(Ordering::Relaxed) {
                 break;
             }
+            if msg.hwnd.0.is_null() && msg.message == WM_APP_REHOOK {
+                rehook(source);
+                continue;
+            }
             let _ = TranslateMessage(&msg);
             let _ = DispatchMessageW(&msg);
         }
@@ -476,6 +1025,10 @@ pub fn run_with_tray_check() -> Result<()> {
 
     // Set event sender globally
     EVENT_SENDER.set(event_tx.clone()).ok();
+    LOG_SENDER.set(log_tx.clone()).ok();
+
+    // Time without monitoring since the previous run (before the logger opens the database)
+    crate::gaps::check_startup();
 
     // Start logger thread
     let logger_handle = thread::spawn(move || {
@@ -487,8 +1040,9 @@ pub fn run_with_tray_check() -> Result<()> {
         event_worker(event_rx, log_tx);
     });
 
-    // Set hooks
-    let hooks = set_hooks()?;
+    // Start event backend + input hooks
+    let mut source = start_event_source()?;
+    set_input_hooks();
 
     // Log current window
     log_current_foreground(&event_tx);
@@ -530,12 +1084,17 @@ pub fn run_with_tray_check() -> Result<()> {
         }
     });
 
+    // Re-register hooks if they go silent
+    note_source_activity();
+    thread::spawn(hook_health_monitor);
+
     // Message Loop (blocks)
-    message_loop();
+    message_loop(source.as_mut());
 
     // Cleanup
     SHUTDOWN.store(true, Ordering::Relaxed);
-    unhook_all(hooks);
+    source.stop();
+    unhook_input();
 
     // Let threads finish
     drop(event_tx);
@@ -0,0 +1,209 @@
+//! File System Watch List
+//!
+//! Watches the files and folders of the config (Documents, a KeePass database, ...) with
+//! ReadDirectoryChangesW and logs every access, change, rename and delete. Windows does not
+//! report who touched a file: the process holding a handle to it (Restart Manager) is named,
+//! fallback the focused process - a best-effort "who touched it".
+
+use crate::config::FileWatch;
+use crate::error::Error;
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use chrono::Local;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::CloseHandle;
+use windows::Win32::Storage::FileSystem::{
+    CreateFileW, ReadDirectoryChangesW, FILE_ACTION, FILE_ACTION_ADDED, FILE_ACTION_MODIFIED, FILE_ACTION_REMOVED,
+    FILE_ACTION_RENAMED_NEW_NAME, FILE_ACTION_RENAMED_OLD_NAME, FILE_FLAG_BACKUP_SEMANTICS, FILE_LIST_DIRECTORY,
+    FILE_NOTIFY_CHANGE, FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_ACCESS, FILE_NOTIFY_CHANGE_LAST_WRITE,
+    FILE_NOTIFY_INFORMATION, FILE_SHARE_DELETE, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+};
+use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+/// Event type in the log
+const EVENT_TYPE: &str = "FILE_ACCESS";
+
+/// Repeated events for the same file and action within this time are logged once
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+lazy_static::lazy_static! {
+    // File path (lowercase) + action -> last logged event
+    static ref LAST_EVENTS: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+}
+
+/// Watches a folder and calls `on_change(relative name, action)` for every change (blocking)
+pub fn watch_directory(
+    folder: &Path,
+    subtree: bool,
+    filter: FILE_NOTIFY_CHANGE,
+    mut on_change: impl FnMut(String, FILE_ACTION),
+) -> Result<(), Error> {
+    unsafe {
+        let folder_wide = crate::long_path::to_wide(folder);
+        let handle = CreateFileW(
+            PCWSTR(folder_wide.as_ptr()),
+            FILE_LIST_DIRECTORY.0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            None,
+            OPEN_EXISTING,
+            FILE_FLAG_BACKUP_SEMANTICS,
+            None,
+        )
+        .map_err(|e| Error::Io { path: folder.to_path_buf(), message: format!("CreateFileW: {}", e) })?;
+
+        // u32 buffer = DWORD-aligned as required by ReadDirectoryChangesW
+        let mut buffer = vec![0u32; 16 * 1024];
+
+        while !crate::tray::should_exit() {
+            let mut bytes_returned: u32 = 0;
+            let result = ReadDirectoryChangesW(
+                handle,
+                buffer.as_mut_ptr() as *mut _,
+                (buffer.len() * 4) as u32,
+                subtree,
+                filter,
+                Some(&mut bytes_returned),
+                None,
+                None,
+            );
+            if let Err(e) = result {
+                let _ = CloseHandle(handle);
+                return Err(Error::Io { path: folder.to_path_buf(), message: format!("ReadDirectoryChangesW: {}", e) });
+            }
+
+            // 0 bytes = buffer overflow, changes are lost
+            if bytes_returned == 0 {
+                debug!("Change buffer overflow for {}", folder.display());
+                continue;
+            }
+
+            let mut offset = 0usize;
+            loop {
+                let info = (buffer.as_ptr() as *const u8).add(offset) as *const FILE_NOTIFY_INFORMATION;
+                let name_len = (*info).FileNameLength as usize / 2;
+                let name = String::from_utf16_lossy(std::slice::from_raw_parts((*info).FileName.as_ptr(), name_len));
+                on_change(name, (*info).Action);
+
+                if (*info).NextEntryOffset == 0 {
+                    break;
+                }
+                offset += (*info).NextEntryOffset as usize;
+            }
+        }
+
+        let _ = CloseHandle(handle);
+    }
+
+    Ok(())
+}
+
+fn action_text(action: FILE_ACTION) -> &'static str {
+    match action {
+        FILE_ACTION_ADDED => "created",
+        FILE_ACTION_REMOVED => "deleted",
+        FILE_ACTION_MODIFIED => "accessed/modified",
+        FILE_ACTION_RENAMED_OLD_NAME => "renamed",
+        FILE_ACTION_RENAMED_NEW_NAME => "renamed to",
+        _ => "touched",
+    }
+}
+
+/// Logs one change (and alerts, if the watch entry is set to alert)
+fn on_change(watch: &FileWatch, path: &Path, action: FILE_ACTION) {
+    let key = format!("{}|{}", path.to_string_lossy().to_lowercase(), action.0);
+    {
+        let mut last_events = LAST_EVENTS.lock();
+        if last_events.get(&key).is_some_and(|t| t.elapsed() < DEBOUNCE) {
+            return;
+        }
+        last_events.retain(|_, t| t.elapsed() < DEBOUNCE);
+        last_events.insert(key, Instant::now());
+    }
+
+    // Process with open handle, otherwise the focused process
+    let (info, attribution) = match crate::honeytoken::processes_using(path).first() {
+        Some(&(pid, ref name)) => (
+            crate::process_info::ProcessInfo { process_name: name.clone(), process_id: pid, ..Default::default() },
+            "open handle",
+        ),
+        None => (crate::process_info::get_process_info_cached(unsafe { GetForegroundWindow() }), "focused"),
+    };
+    if info.process_id == std::process::id() {
+        return;
+    }
+
+    let action = action_text(action);
+    let severity = if watch.alert { Severity::Warning } else { Severity::Info };
+    if watch.alert {
+        warn!("!!! WATCHED FILE {}: {} by {} ({}) !!!", action.to_uppercase(), path.display(), info.process_name, attribution);
+        if crate::rules::is_enabled(Rule::FileWatch) {
+            let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            crate::alert_window::set_rule_alert(
+                Rule::FileWatch,
+                &format!("{} {} ({}, {})", file_name, action, info.process_name, attribution),
+                &path.to_string_lossy(),
+                severity,
+            );
+            crate::sound::play_alert(Rule::FileWatch, severity);
+            let capture = crate::rules::capture_mode(Rule::FileWatch, severity);
+            crate::screenshot::capture_alert_screenshots(info.process_name.clone(), EVENT_TYPE, capture, severity);
+        }
+    }
+
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: Local::now(),
+        event_type: EVENT_TYPE.to_string(),
+        severity,
+        process_name: info.process_name,
+        process_id: info.process_id,
+        process_path: info.process_path,
+        window_title: format!("{} {} ({})", path.display(), action, attribution),
+        window_class: info.window_class,
+        command_line: info.command_line,
+        ..Default::default()
+    });
+}
+
+/// Starts one watcher thread per configured file/folder
+pub fn start_watcher() {
+    let watches = crate::config::get().file_watch;
+    if watches.is_empty() {
+        return;
+    }
+
+    for watch in watches {
+        let path = crate::honeytoken::resolve_path(&watch.path);
+        // Single file: watch its folder, only changes of the file count
+        let (folder, file_name) = if path.is_dir() {
+            (path, None)
+        } else {
+            match path.parent() {
+                Some(parent) => (parent.to_path_buf(), path.file_name().map(|n| n.to_string_lossy().to_lowercase())),
+                None => continue,
+            }
+        };
+
+        thread::spawn(move || {
+            info!("Watching files in: {}", folder.display());
+            let filter = FILE_NOTIFY_CHANGE_FILE_NAME | FILE_NOTIFY_CHANGE_LAST_WRITE | FILE_NOTIFY_CHANGE_LAST_ACCESS;
+            let subtree = watch.subtree && file_name.is_none();
+            let result = watch_directory(&folder, subtree, filter, |name, action| {
+                if file_name.as_ref().is_some_and(|f| *f != name.to_lowercase()) {
+                    return;
+                }
+                let path: PathBuf = folder.join(&name);
+                on_change(&watch, &path, action);
+            });
+            if let Err(e) = result {
+                crate::error::report("File watcher", e);
+            }
+        });
+    }
+}
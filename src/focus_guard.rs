@@ -0,0 +1,81 @@
+//! Focus-Steal Prevention
+//!
+//! Counts focus changes without a click per process. Optionally, after repeated steals by
+//! the same app, SetForegroundWindow is locked (LockSetForegroundWindow - Windows releases
+//! the lock again on the next Alt key or click of the user). Apps the user blocked from
+//! the GUI are minimized whenever they take the focus without a click.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use windows::Win32::Foundation::HWND;
+use windows::Win32::UI::WindowsAndMessaging::{LockSetForegroundWindow, ShowWindowAsync, LSFW_LOCK, SW_MINIMIZE};
+
+/// Event type of the log entry for a minimized focus stealer
+pub const BLOCKED_EVENT_TYPE: &str = "FOCUS_BLOCKED";
+
+lazy_static::lazy_static! {
+    // Recent steals per process (normalized name -> times)
+    static ref STEALS: Mutex<HashMap<String, Vec<Instant>>> = Mutex::new(HashMap::new());
+}
+
+fn normalize(process_name: &str) -> String {
+    process_name.trim().to_lowercase().trim_end_matches(".exe").to_string()
+}
+
+/// Checks if focus stealing of an app is blocked
+pub fn is_blocked(process_name: &str) -> bool {
+    let name = normalize(process_name);
+    crate::config::get().focus_guard.blocked_apps.iter().any(|a| normalize(a) == name)
+}
+
+/// Blocks focus stealing of an app (minimized on future steals)
+pub fn block(process_name: &str) {
+    if process_name.is_empty() || is_blocked(process_name) {
+        return;
+    }
+    let name = process_name.to_string();
+    crate::config::update(|config| config.focus_guard.blocked_apps.push(name));
+    info!("Focus stealing blocked: {}", process_name);
+}
+
+/// Allows focus stealing of an app again
+pub fn unblock(process_name: &str) {
+    let name = normalize(process_name);
+    crate::config::update(|config| config.focus_guard.blocked_apps.retain(|a| normalize(a) != name));
+    info!("Focus stealing allowed again: {}", process_name);
+}
+
+/// Counts a focus steal; returns true when the app reached the repeat threshold
+/// (the foreground lock is set then, if enabled)
+pub fn record_steal(process_name: &str) -> bool {
+    let config = crate::config::get().focus_guard;
+    let window = Duration::from_secs(config.window_minutes.max(1) * 60);
+
+    let repeated = {
+        let mut steals = STEALS.lock();
+        // Forget apps without recent steals
+        steals.retain(|_, times| times.last().is_some_and(|t| t.elapsed() < window));
+        let times = steals.entry(normalize(process_name)).or_default();
+        times.retain(|t| t.elapsed() < window);
+        times.push(Instant::now());
+        times.len() as u32 == config.steals_to_lock.max(1)
+    };
+
+    if repeated && config.lock_foreground {
+        warn!("{} stole the focus {} times - locking the foreground", process_name, config.steals_to_lock);
+        // Only works while PC Watcher (or the user's input) owns the foreground right
+        if let Err(e) = unsafe { LockSetForegroundWindow(LSFW_LOCK) } {
+            debug!("LockSetForegroundWindow failed: {}", e);
+        }
+    }
+    repeated
+}
+
+/// Minimizes the window of a blocked app (asynchronous, a hung window does not block)
+pub fn minimize(hwnd: HWND) {
+    unsafe {
+        let _ = ShowWindowAsync(hwnd, SW_MINIMIZE);
+    }
+}
@@ -0,0 +1,161 @@
+//! Central Server Forwarding
+//!
+//! Streams the events of this watcher to a central server (`pc_watcher server`). The local
+//! event database is the offline buffer: the server answers the hello with the last event
+//! it has of this machine and everything after it is sent, so events of offline periods
+//! are backfilled on reconnect.
+
+use crate::config::ForwarderConfig;
+use crate::server::{read_frame, write_frame};
+use crate::tls::{TlsStream, Transport};
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender};
+use once_cell::sync::OnceCell;
+use serde_json::json;
+use std::io::Read;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// Events sent before waiting for their acks
+const BATCH_SIZE: usize = 100;
+
+/// Ping while idle (server closes silent connections after 120s)
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Longest wait for an answer of the server
+const REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Reconnect delay (doubles up to the maximum)
+const RECONNECT_MIN: Duration = Duration::from_secs(5);
+const RECONNECT_MAX: Duration = Duration::from_secs(300);
+
+// Wakes the forwarder when a new event was stored
+static WAKE: OnceCell<Sender<()>> = OnceCell::new();
+
+/// A new event is in the database (no-op if forwarding is off)
+pub fn notify() {
+    if let Some(sender) = WAKE.get() {
+        let _ = sender.try_send(());
+    }
+}
+
+/// Name this machine reports to the server
+pub fn machine_name(config: &ForwarderConfig) -> String {
+    if !config.machine_name.is_empty() {
+        return config.machine_name.clone();
+    }
+    hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_else(|_| "pc".to_string())
+}
+
+/// Starts the forwarding thread if enabled
+pub fn start() {
+    let config = crate::config::get().forwarder;
+    if !config.enabled || config.server.is_empty() {
+        return;
+    }
+    if !config.tls && !config.allow_insecure {
+        error!("Forwarding disabled: TLS is off (set allow_insecure for test setups)");
+        return;
+    }
+    let (sender, receiver) = bounded::<()>(1);
+    let _ = WAKE.set(sender);
+
+    thread::spawn(move || {
+        info!("Forwarding events to {} as {}", config.server, machine_name(&config));
+        let mut delay = RECONNECT_MIN;
+        while !crate::tray::should_exit() {
+            let started = Instant::now();
+            if let Err(e) = run_session(&receiver) {
+                warn!("Central server connection failed: {} - retrying in {}s", e, delay.as_secs());
+            }
+            // Connection was stable: next failure starts with the short delay again
+            if started.elapsed() > RECONNECT_MAX {
+                delay = RECONNECT_MIN;
+            }
+            let until = Instant::now() + delay;
+            while Instant::now() < until && !crate::tray::should_exit() {
+                thread::sleep(Duration::from_secs(1));
+            }
+            delay = (delay * 2).min(RECONNECT_MAX);
+        }
+    });
+}
+
+/// TCP (+ TLS) connection to the server
+fn connect(config: &ForwarderConfig) -> Result<Box<dyn Transport>, String> {
+    let address = config
+        .server
+        .to_socket_addrs()
+        .map_err(|e| format!("{}: {}", config.server, e))?
+        .next()
+        .ok_or_else(|| format!("{}: no address", config.server))?;
+    let tcp = TcpStream::connect_timeout(&address, REPLY_TIMEOUT).map_err(|e| e.to_string())?;
+    tcp.set_read_timeout(Some(REPLY_TIMEOUT)).map_err(|e| e.to_string())?;
+    let _ = tcp.set_nodelay(true);
+
+    if config.tls {
+        let host = config.server.rsplit_once(':').map(|(host, _)| host).unwrap_or(&config.server);
+        Ok(Box::new(TlsStream::connect(tcp, host, &config.client_certificate, &config.pinned_sha256)?))
+    } else {
+        Ok(Box::new(tcp))
+    }
+}
+
+/// Reads the next answer, error frames of the server become errors
+fn read_reply(stream: &mut impl Read, expected: &str) -> Result<serde_json::Value, String> {
+    let reply = read_frame(stream).map_err(|e| e.to_string())?;
+    match reply["type"].as_str() {
+        Some(kind) if kind == expected => Ok(reply),
+        Some("error") => Err(format!("server: {}", reply["message"].as_str().unwrap_or_default())),
+        _ => Err(format!("unexpected answer: {}", reply)),
+    }
+}
+
+/// One connection: hello, backfill, then live events until an error or exit
+fn run_session(wake: &Receiver<()>) -> Result<(), String> {
+    let config = crate::config::get().forwarder;
+    let conn = crate::database::open().map_err(|e| format!("event database: {}", e))?;
+    let mut stream = connect(&config)?;
+
+    let hello = json!({
+        "type": "hello",
+        "machine": machine_name(&config),
+        "version": env!("CARGO_PKG_VERSION"),
+        "token": config.token,
+    });
+    write_frame(&mut stream, &hello).map_err(|e| e.to_string())?;
+    let welcome = read_reply(&mut stream, "welcome")?;
+    let mut cursor = welcome["last_id"].as_i64().unwrap_or_default();
+    info!("Connected to central server {} (sending events after {})", config.server, cursor);
+
+    let mut last_contact = Instant::now();
+    while !crate::tray::should_exit() {
+        let batch = crate::database::read_events_after(&conn, cursor, BATCH_SIZE).map_err(|e| e.to_string())?;
+
+        if batch.is_empty() {
+            match wake.recv_timeout(Duration::from_secs(1)) {
+                Ok(()) | Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+            if last_contact.elapsed() >= PING_INTERVAL {
+                write_frame(&mut stream, &json!({ "type": "ping" })).map_err(|e| e.to_string())?;
+                read_reply(&mut stream, "pong")?;
+                last_contact = Instant::now();
+            }
+            continue;
+        }
+
+        // Send the whole batch, then collect the acks
+        for (id, entry) in &batch {
+            let message = json!({ "type": "event", "id": id, "event": entry.format_json() });
+            write_frame(&mut stream, &message).map_err(|e| e.to_string())?;
+        }
+        for _ in &batch {
+            let ack = read_reply(&mut stream, "ack")?;
+            cursor = cursor.max(ack["id"].as_i64().unwrap_or_default());
+        }
+        last_contact = Instant::now();
+    }
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! Monitoring Gaps
+//!
+//! A heartbeat in the event database records when the watcher was last running. On startup
+//! it is compared with the boot time, and a MONITORING_GAP entry is written for the time in
+//! which the machine was off or the watcher was not running. Gaps while the machine was on
+//! (late start after boot, watcher killed) are Warning alerts.
+
+use crate::clock;
+use crate::database;
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use chrono::{DateTime, Duration as ChronoDuration, Local};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Heartbeat interval (gaps are accurate to this)
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+/// First heartbeat (the log worker opens the database right after start)
+const HEARTBEAT_DELAY: Duration = Duration::from_secs(5);
+
+/// Shorter gaps are not logged (restart, heartbeat interval)
+const MIN_GAP_MINUTES: i64 = 2;
+
+/// Formats a gap as "5h 12m" / "3d 4h"
+fn format_gap(gap: ChronoDuration) -> String {
+    let minutes = gap.num_minutes();
+    match minutes {
+        m if m >= 24 * 60 => format!("{}d {}h", m / (24 * 60), m / 60 % 24),
+        m if m >= 60 => format!("{}h {:02}m", m / 60, m % 60),
+        m => format!("{}m", m),
+    }
+}
+
+/// Logs a gap (and alerts for unexpected ones)
+fn report_gap(from: DateTime<Local>, to: DateTime<Local>, reason: &str, unexpected: bool) {
+    let gap = to - from;
+    if gap.num_minutes() < MIN_GAP_MINUTES {
+        return;
+    }
+
+    let warn_after = ChronoDuration::minutes(crate::config::get().gaps.warn_minutes as i64);
+    let severity = if unexpected && gap >= warn_after { Severity::Warning } else { Severity::Info };
+    let text = format!(
+        "No monitoring for {} ({}) from {} to {}",
+        format_gap(gap),
+        reason,
+        from.format("%Y-%m-%d %H:%M"),
+        to.format("%Y-%m-%d %H:%M")
+    );
+
+    if severity == Severity::Warning {
+        warn!("!!! MONITORING GAP: {} !!!", text);
+        if crate::rules::is_enabled(Rule::MonitoringGap) {
+            crate::alert_window::set_rule_alert(
+                Rule::MonitoringGap,
+                &format!("Monitoring gap: {} ({})", format_gap(gap), reason),
+                "",
+                severity,
+            );
+            crate::sound::play_alert(Rule::MonitoringGap, severity);
+        }
+    } else {
+        info!("Monitoring gap: {}", text);
+    }
+
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: Local::now(),
+        event_type: "MONITORING_GAP".to_string(),
+        severity,
+        process_name: "pc_watcher".to_string(),
+        window_title: text,
+        ..Default::default()
+    });
+}
+
+/// Compares the previous run with the boot time (call before the log worker starts)
+pub fn check_startup() {
+    let Some(previous) = database::read_previous_run() else {
+        return; // First run
+    };
+
+    let now = Local::now();
+    let boot = now - ChronoDuration::milliseconds(clock::uptime_ms() as i64);
+
+    if previous.last_alive < boot {
+        // Before this boot: machine was off (or the watcher ended before shutdown)
+        report_gap(previous.last_alive, boot, "machine off or watcher not running", false);
+        // After boot: watcher started late
+        report_gap(boot, now, "watcher not running after boot", true);
+    } else {
+        // Same boot: watcher was restarted
+        let reason = if previous.clean_stop { "watcher was stopped" } else { "watcher was killed or crashed" };
+        report_gap(previous.last_alive, now, reason, !previous.clean_stop);
+    }
+}
+
+/// Starts the heartbeat thread
+pub fn start_heartbeat() {
+    thread::spawn(|| {
+        thread::sleep(HEARTBEAT_DELAY);
+        // Until the next normal end, a stop counts as unexpected
+        database::set_meta("clean_stop", "0");
+        while !crate::tray::should_exit() {
+            database::set_meta("last_alive", &database::format_timestamp(&Local::now()));
+            thread::sleep(HEARTBEAT_INTERVAL);
+        }
+    });
+}
+
+/// Marks a normal end of the application
+pub fn mark_clean_stop() {
+    database::set_meta("last_alive", &database::format_timestamp(&Local::now()));
+    database::set_meta("clean_stop", "1");
+}
@@ -0,0 +1,298 @@
+//! gRPC Control API
+//!
+//! Typed, versioned interface for remote management tools (tonic, proto/pc_watcher.proto):
+//! StreamEvents, GetStatus, Pause, TriggerCapture and ReloadConfig - the same actions as the
+//! web dashboard. TLS through the shared rustls layer (certificate, client pins), bearer token
+//! in the "authorization" metadata: the configured token is admin, API tokens have their role.
+
+use crate::api_tokens::Role;
+use crate::config::GrpcConfig;
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::screenshot::CaptureMode;
+use crate::tls::Certificate;
+use proto::control_server::{Control, ControlServer};
+use proto::{
+    Event, GetStatusRequest, PauseReply, PauseRequest, ReloadConfigReply, ReloadConfigRequest, Status as StatusReply,
+    StreamEventsRequest, TriggerCaptureReply, TriggerCaptureRequest,
+};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_rustls::TlsAcceptor;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+use tracing::{error, info, warn};
+
+pub mod proto {
+    tonic::include_proto!("pcwatcher.control.v1");
+}
+
+/// Events read from the database per poll
+const BATCH_SIZE: usize = 100;
+
+/// How often event streams look for new events
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Events buffered per stream (the reader waits while a client is slow)
+const STREAM_BUFFER: usize = 256;
+
+/// Starts the gRPC server (opt-in, refuses to run without token or certificate)
+pub fn start() {
+    let config = crate::config::get().grpc;
+    if !config.enabled {
+        return;
+    }
+    if config.token.is_empty() && crate::api_tokens::list().is_empty() {
+        error!("gRPC API not started: grpc.token is empty and no API tokens exist");
+        return;
+    }
+    let certificate = match config.tls_certificate.as_str() {
+        "" if config.allow_insecure => {
+            warn!("gRPC API running without TLS");
+            None
+        }
+        "" => {
+            error!("gRPC API not started: tls_certificate is required (or set allow_insecure)");
+            return;
+        }
+        file => match Certificate::load(file) {
+            Ok(certificate) => Some(certificate),
+            Err(e) => {
+                error!("gRPC API not started: {}", e);
+                return;
+            }
+        },
+    };
+
+    thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_multi_thread().worker_threads(2).enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                error!("gRPC API not started: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = runtime.block_on(serve(config, certificate)) {
+            error!("gRPC API stopped: {}", e);
+        }
+    });
+}
+
+async fn serve(config: GrpcConfig, certificate: Option<Certificate>) -> Result<(), String> {
+    let listener = TcpListener::bind(&config.listen)
+        .await
+        .map_err(|e| format!("cannot listen on {}: {}", config.listen, e))?;
+    let router = Server::builder().add_service(ControlServer::new(ControlService));
+
+    let Some(certificate) = certificate else {
+        info!("gRPC API on http://{}", config.listen);
+        return router.serve_with_incoming(TcpListenerStream::new(listener)).await.map_err(|e| e.to_string());
+    };
+
+    let mut tls = crate::tls::server_config(&certificate, &config.client_pins)?;
+    tls.alpn_protocols = vec![b"h2".to_vec()];
+    let acceptor = TlsAcceptor::from(Arc::new(tls));
+    let (sender, receiver) = mpsc::channel(16);
+    tokio::spawn(async move {
+        loop {
+            let tcp = match listener.accept().await {
+                Ok((tcp, _)) => tcp,
+                Err(e) => {
+                    warn!("gRPC accept failed: {}", e);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                    continue;
+                }
+            };
+            // Handshake per connection, a slow client does not hold up the others
+            let acceptor = acceptor.clone();
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                match acceptor.accept(tcp).await {
+                    Ok(stream) => {
+                        let _ = sender.send(Ok::<_, std::io::Error>(stream)).await;
+                    }
+                    Err(e) => warn!("gRPC TLS handshake failed: {}", e),
+                }
+            });
+        }
+    });
+    info!("gRPC API on https://{}", config.listen);
+    router.serve_with_incoming(ReceiverStream::new(receiver)).await.map_err(|e| e.to_string())
+}
+
+/// Token of an "authorization: Bearer <token>" value
+fn bearer(value: Option<&str>) -> Option<&str> {
+    let (scheme, token) = value?.trim().split_once(' ')?;
+    scheme.eq_ignore_ascii_case("bearer").then_some(token.trim())
+}
+
+/// Error unless the caller has at least the role (boxed, Status is large)
+fn authorize<T>(request: &Request<T>, required: Role) -> Result<(), Box<Status>> {
+    let value = request.metadata().get("authorization").and_then(|v| v.to_str().ok());
+    match crate::api_tokens::authorize(bearer(value), &crate::config::get().grpc.token) {
+        Some(role) if role >= required => Ok(()),
+        Some(_) => Err(Box::new(Status::permission_denied("admin role required"))),
+        None => Err(Box::new(Status::unauthenticated("missing or invalid bearer token"))),
+    }
+}
+
+fn event(id: i64, entry: &LogEntry) -> Event {
+    Event {
+        id,
+        timestamp: entry.timestamp.to_rfc3339(),
+        event_type: entry.event_type.clone(),
+        severity: entry.severity.as_str().to_string(),
+        process_name: entry.process_name.clone(),
+        process_id: entry.process_id,
+        process_path: entry.process_path.clone(),
+        window_title: entry.window_title.clone(),
+        json: entry.format_json().to_string(),
+    }
+}
+
+/// ID of the newest event (0 = empty database)
+fn last_event_id(conn: &rusqlite::Connection) -> Result<i64, String> {
+    let latest = crate::database::read_latest_events(conn, false, None, 1).map_err(|e| e.to_string())?;
+    Ok(latest.first().map(|(id, _)| *id).unwrap_or(0))
+}
+
+/// Stream thread: polls the event database until the client is gone
+fn stream_events(request: StreamEventsRequest, sender: mpsc::Sender<Result<Event, Status>>) {
+    let result = crate::database::open().map_err(|e| e.to_string()).and_then(|conn| {
+        let mut cursor = match request.after_id {
+            0 => last_event_id(&conn)?,
+            after_id => after_id,
+        };
+        while !crate::tray::should_exit() && !sender.is_closed() {
+            let events = crate::database::read_events_after(&conn, cursor, BATCH_SIZE).map_err(|e| e.to_string())?;
+            if events.is_empty() {
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            for (id, entry) in events {
+                cursor = id;
+                if request.alerts_only && entry.severity == Severity::Info {
+                    continue;
+                }
+                if sender.blocking_send(Ok(event(id, &entry))).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        let _ = sender.blocking_send(Err(Status::unavailable(format!("event database: {}", e))));
+    }
+}
+
+struct ControlService;
+
+#[tonic::async_trait]
+impl Control for ControlService {
+    type StreamEventsStream = ReceiverStream<Result<Event, Status>>;
+
+    async fn stream_events(
+        &self,
+        request: Request<StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        authorize(&request, Role::Viewer).map_err(|e| *e)?;
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        let request = request.into_inner();
+        thread::spawn(move || stream_events(request, sender));
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn get_status(&self, request: Request<GetStatusRequest>) -> Result<Response<StatusReply>, Status> {
+        authorize(&request, Role::Viewer).map_err(|e| *e)?;
+        let last_event_id = tokio::task::spawn_blocking(|| {
+            crate::database::open().map_err(|e| e.to_string()).and_then(|conn| last_event_id(&conn))
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map_err(|e| Status::unavailable(format!("event database: {}", e)))?;
+
+        Ok(Response::new(StatusReply {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            machine: hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default(),
+            user: crate::instance::user_name(),
+            session_id: crate::instance::session_id(),
+            instance: crate::instance::number(),
+            do_not_disturb: crate::snooze::is_do_not_disturb(),
+            last_event_id,
+            health_errors: crate::error::recent().len() as u32,
+        }))
+    }
+
+    async fn pause(&self, request: Request<PauseRequest>) -> Result<Response<PauseReply>, Status> {
+        authorize(&request, Role::Admin).map_err(|e| *e)?;
+        let enabled = request.into_inner().enabled;
+        crate::snooze::set_do_not_disturb(enabled);
+        info!("Do not disturb {} via gRPC", if enabled { "on" } else { "off" });
+        Ok(Response::new(PauseReply { do_not_disturb: enabled }))
+    }
+
+    async fn trigger_capture(
+        &self,
+        request: Request<TriggerCaptureRequest>,
+    ) -> Result<Response<TriggerCaptureReply>, Status> {
+        authorize(&request, Role::Admin).map_err(|e| *e)?;
+        let request = request.into_inner();
+        let reason = match request.reason.trim() {
+            "" => "remote".to_string(),
+            reason => reason.to_string(),
+        };
+        let mode = if request.all_monitors { CaptureMode::AllMonitors } else { CaptureMode::Screenshot };
+        info!("Capture triggered via gRPC: {}", reason);
+        // Settings and caption are prepared synchronously before the capture thread starts
+        tokio::task::spawn_blocking(move || {
+            crate::screenshot::capture_alert_screenshots(reason, "REMOTE_CAPTURE", mode, Severity::Info)
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(TriggerCaptureReply {}))
+    }
+
+    async fn reload_config(
+        &self,
+        request: Request<ReloadConfigRequest>,
+    ) -> Result<Response<ReloadConfigReply>, Status> {
+        authorize(&request, Role::Admin).map_err(|e| *e)?;
+        crate::config::reload().map_err(Status::failed_precondition)?;
+        Ok(Response::new(ReloadConfigReply {}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_bearer_tokens() {
+        assert_eq!(bearer(Some("Bearer pcw_abc")), Some("pcw_abc"));
+        assert_eq!(bearer(Some(" bearer  secret ")), Some("secret"));
+        assert_eq!(bearer(Some("Basic dXNlcjpwdw==")), None);
+        assert_eq!(bearer(Some("secret")), None);
+        assert_eq!(bearer(None), None);
+    }
+
+    #[test]
+    fn converts_events() {
+        let entry = LogEntry {
+            event_type: "DETECTION".to_string(),
+            severity: Severity::Critical,
+            process_name: "cmd".to_string(),
+            process_id: 42,
+            ..Default::default()
+        };
+        let event = event(7, &entry);
+        assert_eq!((event.id, event.process_id), (7, 42));
+        assert_eq!(event.severity, "CRITICAL");
+        let json: serde_json::Value = serde_json::from_str(&event.json).unwrap();
+        assert_eq!(json["event_type"], "DETECTION");
+    }
+}
@@ -0,0 +1,278 @@
+//! Hash Allow/Deny Lists
+//!
+//! SHA-256 lists of known-good and known-bad executables: local lists from the config plus
+//! remote feeds (newline-delimited or CSV over HTTPS), refreshed on a schedule and cached next
+//! to the EXE so they also work offline. The executable of every new process is hashed once in
+//! the background. A deny match raises a Critical alert, an allow match suppresses alerts of
+//! the process. Every match is logged as HASH_MATCH together with the list it came from.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::process_info::ProcessInfo;
+use crate::rules::Rule;
+use chrono::{DateTime, Local};
+use crossbeam_channel::{bounded, Sender};
+use once_cell::sync::OnceCell;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use tracing::{error, info, warn};
+
+/// Event type in the log
+const EVENT_TYPE: &str = "HASH_MATCH";
+
+/// Feeds are checked for a due refresh this often
+const CHECK_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Pending files to hash (further processes are skipped while full)
+const QUEUE_SIZE: usize = 64;
+
+/// What a list entry means
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashListKind {
+    /// Known-bad: Critical alert
+    #[default]
+    Deny,
+    /// Known-good: no alerts for the process
+    Allow,
+}
+
+/// List entry of a hash with its provenance
+#[derive(Debug, Clone)]
+pub struct HashEntry {
+    pub kind: HashListKind,
+    /// Where the hash comes from (e.g. `feed "abuse" (https://..., updated 2026-10-14 03:00)`)
+    pub source: String,
+}
+
+lazy_static::lazy_static! {
+    // Merged lists (key = lowercase SHA-256)
+    static ref ENTRIES: RwLock<HashMap<String, HashEntry>> = RwLock::new(HashMap::new());
+    // Result per executable (key = lowercase path, None = not listed or still hashing)
+    static ref RESULTS: Mutex<HashMap<String, Option<HashEntry>>> = Mutex::new(HashMap::new());
+}
+
+// Queue of the hashing thread: (process name, PID, process path)
+static HASH_SENDER: OnceCell<Sender<(String, u32, String)>> = OnceCell::new();
+
+/// SHA-256s of a list (any 64-digit hex field of a line counts, so plain lists and CSV both work)
+fn parse_hashes(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .filter_map(|line| {
+            line.split(|c: char| c == ',' || c == ';' || c == '"' || c == '\'' || c.is_whitespace())
+                .find(|field| field.len() == 64 && field.chars().all(|c| c.is_ascii_hexdigit()))
+                .map(str::to_lowercase)
+        })
+        .collect()
+}
+
+/// Cache folder of the downloaded feeds (next to the EXE)
+fn cache_dir() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|p| p.join("hash_feeds")))
+        .unwrap_or_else(|| PathBuf::from("hash_feeds"))
+}
+
+/// Cache file of a feed
+fn cache_file(name: &str) -> PathBuf {
+    let file_name: String = name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect();
+    cache_dir().join(format!("{}.txt", file_name))
+}
+
+/// Time of the last successful download of a feed
+fn cache_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Merges the local lists and the cached feeds (a deny entry wins over an allow entry)
+fn rebuild() {
+    let config = crate::config::get().hash_lists;
+    let mut entries: HashMap<String, HashEntry> = HashMap::new();
+    let mut add = |hash: String, kind: HashListKind, source: &str| {
+        let replace = entries.get(&hash).is_none_or(|existing| existing.kind == HashListKind::Allow);
+        if replace {
+            entries.insert(hash, HashEntry { kind, source: source.to_string() });
+        }
+    };
+
+    for (list, kind, source) in [
+        (&config.allow, HashListKind::Allow, "local allow list"),
+        (&config.deny, HashListKind::Deny, "local deny list"),
+    ] {
+        for hash in parse_hashes(&list.join("\n")) {
+            add(hash, kind, source);
+        }
+    }
+
+    for feed in &config.feeds {
+        let path = cache_file(&feed.name);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let updated = cache_time(&path)
+            .map(|t| DateTime::<Local>::from(t).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let source = format!("feed \"{}\" ({}, updated {})", feed.name, feed.url, updated);
+        let hashes = parse_hashes(&content);
+        info!("Hash feed {}: {} hashes", feed.name, hashes.len());
+        for hash in hashes {
+            add(hash, feed.kind, &source);
+        }
+    }
+
+    info!("Hash lists: {} entries", entries.len());
+    *ENTRIES.write() = entries;
+    // Executables are checked again against the new lists
+    RESULTS.lock().clear();
+}
+
+/// Downloads the feeds whose cache is older than the refresh interval (true = any changed)
+fn refresh_feeds() -> bool {
+    let config = crate::config::get().hash_lists;
+    let max_age = Duration::from_secs(config.refresh_hours.max(1) as u64 * 3600);
+    let mut changed = false;
+    for feed in &config.feeds {
+        let path = cache_file(&feed.name);
+        let fresh = cache_time(&path).and_then(|t| t.elapsed().ok()).is_some_and(|age| age < max_age);
+        if fresh {
+            continue;
+        }
+        // Lists decide about alerts - only from an authenticated source
+        if !feed.url.starts_with("https://") {
+            warn!("Hash feed {} skipped: only https:// URLs are supported", feed.name);
+            continue;
+        }
+        match crate::http::request("GET", &feed.url, &[], &[]) {
+            Ok(response) if response.status == 200 => {
+                let _ = std::fs::create_dir_all(cache_dir());
+                match std::fs::write(&path, &response.body) {
+                    Ok(()) => {
+                        info!("Hash feed {} downloaded ({} bytes)", feed.name, response.body.len());
+                        changed = true;
+                    }
+                    Err(e) => error!("Could not save hash feed {}: {}", feed.name, e),
+                }
+            }
+            Ok(response) => warn!("Hash feed {}: HTTP {}", feed.name, response.status),
+            Err(e) => warn!("Hash feed {}: {}", feed.name, e),
+        }
+    }
+    changed
+}
+
+/// Starts the feed refresh thread (only if enabled in the config)
+pub fn start_monitor() {
+    if !crate::config::get().hash_lists.enabled {
+        return;
+    }
+
+    thread::spawn(|| {
+        // Cached feeds first, so the lists work before (or without) a download
+        rebuild();
+        while !crate::tray::should_exit() {
+            if refresh_feeds() {
+                rebuild();
+            }
+            thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+/// Checks if the executable of a process is on the allow list (known after hashing)
+pub fn is_allowed(process_path: &str) -> bool {
+    RESULTS
+        .lock()
+        .get(&process_path.to_lowercase())
+        .is_some_and(|entry| entry.as_ref().is_some_and(|e| e.kind == HashListKind::Allow))
+}
+
+/// Queues the executable of a process for hashing (once per file)
+pub fn check_process(info: &ProcessInfo) {
+    if !crate::config::get().hash_lists.enabled || !Path::new(&info.process_path).is_absolute() {
+        return;
+    }
+    let key = info.process_path.to_lowercase();
+    {
+        let mut results = RESULTS.lock();
+        if results.contains_key(&key) {
+            return;
+        }
+        results.insert(key.clone(), None);
+    }
+
+    let sender = HASH_SENDER.get_or_init(start_worker);
+    if sender.try_send((info.process_name.clone(), info.process_id, info.process_path.clone())).is_err() {
+        // Allow a new attempt with the next window
+        RESULTS.lock().remove(&key);
+    }
+}
+
+/// Hashing thread (one file at a time)
+fn start_worker() -> Sender<(String, u32, String)> {
+    let (sender, receiver) = bounded::<(String, u32, String)>(QUEUE_SIZE);
+    thread::spawn(move || {
+        for (process_name, process_id, process_path) in receiver {
+            let sha256 = match crate::virustotal::sha256_file(Path::new(&process_path)) {
+                Ok(sha256) => sha256,
+                Err(e) => {
+                    warn!("Hash lists: cannot hash {}: {}", process_path, e);
+                    continue;
+                }
+            };
+            let Some(entry) = ENTRIES.read().get(&sha256).cloned() else {
+                continue;
+            };
+            RESULTS.lock().insert(process_path.to_lowercase(), Some(entry.clone()));
+            report(process_name, process_id, process_path, &sha256, &entry);
+        }
+    });
+    sender
+}
+
+/// Logs a match and alerts for deny entries
+fn report(process_name: String, process_id: u32, process_path: String, sha256: &str, entry: &HashEntry) {
+    let severity = match entry.kind {
+        HashListKind::Allow => {
+            info!("Allow-listed executable {} (SHA-256 {}) from {}", process_path, sha256, entry.source);
+            Severity::Info
+        }
+        HashListKind::Deny => {
+            warn!("!!! DENY-LISTED EXECUTABLE: {} (SHA-256 {}) from {} !!!", process_path, sha256, entry.source);
+            Severity::Critical
+        }
+    };
+    if severity == Severity::Critical && crate::rules::is_enabled(Rule::HashList) {
+        crate::alert_window::set_rule_alert(
+            Rule::HashList,
+            &format!("{} (deny-listed hash)", process_name),
+            &process_path,
+            severity,
+        );
+        crate::sound::play_alert(Rule::HashList, severity);
+        let capture = crate::rules::capture_mode(Rule::HashList, severity);
+        crate::screenshot::capture_alert_screenshots(process_name.clone(), EVENT_TYPE, capture, severity);
+        crate::process_exit::watch(process_id, &process_name);
+    }
+
+    let list = match entry.kind {
+        HashListKind::Allow => "Allow list",
+        HashListKind::Deny => "Deny list",
+    };
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: Local::now(),
+        event_type: EVENT_TYPE.to_string(),
+        severity,
+        process_name,
+        process_id,
+        process_path,
+        window_title: format!("{} match: SHA-256 {} from {}", list, sha256, entry.source),
+        ..Default::default()
+    });
+}
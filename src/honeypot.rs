@@ -0,0 +1,182 @@
+//! Honeypot Window
+//!
+//! Optional offscreen decoy window with an enticing title (e.g. "passwords.txt - Notepad").
+//! Normal users never see it - any process that focuses it, reads its text or sends
+//! input to it is most likely an automated snooping tool and triggers a Critical alert.
+
+use crate::error::Error;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use windows::core::w;
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+/// Minimum time between two alerts (tools often send many messages at once)
+const ALERT_COOLDOWN: Duration = Duration::from_secs(10);
+
+/// Far outside of all monitors
+const OFFSCREEN_POS: i32 = -32000;
+
+static HONEYPOT_HWND: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static::lazy_static! {
+    static ref LAST_ALERT: Mutex<Option<Instant>> = Mutex::new(None);
+}
+
+/// Checks if a window is the honeypot (its events are handled here, not by the event hook)
+pub fn is_honeypot(hwnd: isize) -> bool {
+    hwnd != 0 && HONEYPOT_HWND.load(Ordering::SeqCst) == hwnd as usize
+}
+
+/// Starts the honeypot window in its own thread (if enabled in the config)
+pub fn start_honeypot() {
+    let config = crate::config::get().honeypot;
+    if !config.enabled {
+        return;
+    }
+
+    thread::spawn(move || {
+        if let Err(e) = create_honeypot_window(&config.title) {
+            crate::error::report("Honeypot window", e);
+        }
+    });
+}
+
+/// Closes the honeypot window
+pub fn stop_honeypot() {
+    let hwnd = HONEYPOT_HWND.load(Ordering::SeqCst);
+    if hwnd != 0 {
+        unsafe {
+            let _ = PostMessageW(HWND(hwnd as *mut _), WM_CLOSE, WPARAM(0), LPARAM(0));
+        }
+    }
+}
+
+/// Creates the offscreen decoy window
+fn create_honeypot_window(title: &str) -> Result<(), Error> {
+    unsafe {
+        let instance = GetModuleHandleW(None)
+            .map_err(|e| Error::win32("GetModuleHandle", &e))?;
+
+        let class_name = w!("PCWatcherHoneypot");
+        let wc = WNDCLASSW {
+            lpfnWndProc: Some(honeypot_window_proc),
+            hInstance: instance.into(),
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        RegisterClassW(&wc);
+
+        let title_wide: Vec<u16> = title.encode_utf16().chain(std::iter::once(0)).collect();
+
+        // Visible (so window enumerations find it), but offscreen and not in the taskbar
+        let hwnd = CreateWindowExW(
+            WS_EX_TOOLWINDOW,
+            class_name,
+            windows::core::PCWSTR(title_wide.as_ptr()),
+            WS_POPUP | WS_VISIBLE,
+            OFFSCREEN_POS, OFFSCREEN_POS, 400, 300,
+            None,
+            None,
+            instance,
+            None,
+        )
+        .map_err(|e| Error::win32("CreateWindowExW", &e))?;
+
+        HONEYPOT_HWND.store(hwnd.0 as usize, Ordering::SeqCst);
+        info!("Honeypot window created: \"{}\"", title);
+
+        // Message Loop
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            let _ = DispatchMessageW(&msg);
+        }
+
+        HONEYPOT_HWND.store(0, Ordering::SeqCst);
+    }
+
+    Ok(())
+}
+
+/// Name of the process owning a window (empty if unknown)
+fn window_process_name(hwnd: HWND) -> String {
+    if hwnd.0.is_null() {
+        return String::new();
+    }
+    crate::process_info::get_process_info(hwnd).process_name
+}
+
+/// Raises the Critical alert (with cooldown)
+fn trigger(action: &str, suspect: String) {
+    {
+        let mut last = LAST_ALERT.lock();
+        if last.is_some_and(|t| t.elapsed() < ALERT_COOLDOWN) {
+            return;
+        }
+        *last = Some(Instant::now());
+    }
+
+    warn!("!!! HONEYPOT {}: {} !!!", action, if suspect.is_empty() { "unknown process" } else { &suspect });
+
+    if !crate::rules::is_enabled(Rule::Honeypot) {
+        return;
+    }
+
+    let message = if suspect.is_empty() {
+        format!("HONEYPOT {}", action)
+    } else {
+        format!("HONEYPOT {} ({})", action, suspect)
+    };
+    crate::alert_window::set_rule_alert(Rule::Honeypot, &message, "", Severity::Critical);
+    crate::sound::play_alert(Rule::Honeypot, Severity::Critical);
+
+    let capture = crate::rules::capture_mode(Rule::Honeypot, Severity::Critical);
+    let name = if suspect.is_empty() { "honeypot".to_string() } else { suspect };
+    crate::screenshot::capture_alert_screenshots(name, "HONEYPOT", capture, Severity::Critical);
+}
+
+/// Window Procedure of the decoy
+unsafe extern "system" fn honeypot_window_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_ACTIVATE => {
+            if (wparam.0 & 0xFFFF) as u32 != WA_INACTIVE {
+                // lparam = window that lost the focus (most likely the tool)
+                let previous = HWND(lparam.0 as *mut _);
+                trigger("FOCUSED", window_process_name(previous));
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+
+        WM_GETTEXT => {
+            // Sent by another thread/process (GetWindowText from outside does not send it)
+            if InSendMessageEx(None) & ISMEX_SEND != 0 {
+                trigger("READ", window_process_name(GetForegroundWindow()));
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+
+        WM_KEYDOWN | WM_CHAR | WM_SYSKEYDOWN | WM_LBUTTONDOWN | WM_RBUTTONDOWN | WM_SETTEXT | WM_PASTE => {
+            trigger("INPUT", window_process_name(GetForegroundWindow()));
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
+
+        WM_DESTROY => {
+            PostQuitMessage(0);
+            LRESULT(0)
+        }
+
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
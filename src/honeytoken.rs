@@ -0,0 +1,267 @@
+//! Honeytoken Files
+//!
+//! Creates decoy files (e.g. Documents\passwords.xlsx) and watches their folders with
+//! ReadDirectoryChangesW. Nobody has a reason to touch them - any access, change,
+//! rename or delete raises a Critical alert. The accessing process is resolved via the
+//! Restart Manager (processes holding a handle to the file), fallback: foreground window.
+
+use crate::error::Error;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use windows::core::{PCWSTR, PWSTR};
+use windows::Win32::Foundation::{CloseHandle, ERROR_SUCCESS};
+use windows::Win32::Storage::FileSystem::{
+    CreateFileW, ReadDirectoryChangesW, FILE_ACTION, FILE_ACTION_MODIFIED, FILE_ACTION_REMOVED,
+    FILE_ACTION_RENAMED_OLD_NAME, FILE_FLAG_BACKUP_SEMANTICS, FILE_LIST_DIRECTORY,
+    FILE_NOTIFY_CHANGE_ATTRIBUTES, FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_ACCESS,
+    FILE_NOTIFY_CHANGE_LAST_WRITE, FILE_NOTIFY_CHANGE_SECURITY, FILE_NOTIFY_INFORMATION,
+    FILE_SHARE_DELETE, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+};
+use windows::Win32::System::RestartManager::{
+    RmEndSession, RmGetList, RmRegisterResources, RmStartSession, CCH_RM_SESSION_KEY,
+    RM_PROCESS_INFO,
+};
+use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+/// Minimum time between two alerts for the same file
+const ALERT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Events right after creating the decoys are ignored (e.g. virus scanner)
+const STARTUP_GRACE: Duration = Duration::from_secs(10);
+
+/// Content of newly created decoy files
+const DECOY_CONTENT: &str = "Accounts\r\n========\r\n\r\nbank: see sheet 2\r\nmail: see sheet 3\r\n";
+
+lazy_static::lazy_static! {
+    // Decoy path (lowercase) -> last alert
+    static ref LAST_ALERTS: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+}
+
+/// UTF-16 with null terminator (long paths with the `\\?\` prefix)
+fn to_wide(path: &Path) -> Vec<u16> {
+    crate::long_path::to_wide(path)
+}
+
+/// Resolves a configured path (relative paths are relative to the user profile)
+pub fn resolve_path(path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    if path.is_absolute() {
+        return path;
+    }
+    match std::env::var("USERPROFILE") {
+        Ok(profile) => PathBuf::from(profile).join(path),
+        Err(_) => path,
+    }
+}
+
+/// Creates the decoy file if it doesn't exist yet
+fn create_decoy(path: &Path) -> Result<(), Error> {
+    if path.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| Error::io(parent, &e))?;
+    }
+    fs::write(path, DECOY_CONTENT).map_err(|e| Error::io(path, &e))?;
+    info!("Honeytoken created: {}", path.display());
+    Ok(())
+}
+
+/// Starts the watcher threads (one per folder) for all configured honeytokens
+pub fn start_watcher() {
+    let paths: Vec<PathBuf> = crate::config::get()
+        .honeytokens
+        .iter()
+        .map(|p| resolve_path(p))
+        .collect();
+    if paths.is_empty() {
+        return;
+    }
+
+    // Folder -> decoy files in it
+    let mut folders: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    for path in paths {
+        if let Err(e) = create_decoy(&path) {
+            crate::error::report("Could not create honeytoken", e);
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            folders.entry(parent.to_path_buf()).or_default().push(path);
+        }
+    }
+
+    let started = Instant::now();
+    for (folder, files) in folders {
+        thread::spawn(move || {
+            if let Err(e) = watch_folder(&folder, &files, started) {
+                crate::error::report("Honeytoken watcher", e);
+            }
+        });
+    }
+}
+
+/// Watches a folder and checks every change against the decoys (blocking)
+fn watch_folder(folder: &Path, files: &[PathBuf], started: Instant) -> Result<(), Error> {
+    let names: Vec<String> = files
+        .iter()
+        .filter_map(|f| f.file_name().map(|n| n.to_string_lossy().to_lowercase()))
+        .collect();
+
+    unsafe {
+        let folder_wide = to_wide(folder);
+        let handle = CreateFileW(
+            PCWSTR(folder_wide.as_ptr()),
+            FILE_LIST_DIRECTORY.0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            None,
+            OPEN_EXISTING,
+            FILE_FLAG_BACKUP_SEMANTICS,
+            None,
+        )
+        .map_err(|e| Error::Io { path: folder.to_path_buf(), message: format!("CreateFileW: {}", e) })?;
+
+        info!("Watching honeytokens in: {}", folder.display());
+
+        // u32 buffer = DWORD-aligned as required by ReadDirectoryChangesW
+        let mut buffer = vec![0u32; 16 * 1024];
+
+        while !crate::tray::should_exit() {
+            let mut bytes_returned: u32 = 0;
+            let result = ReadDirectoryChangesW(
+                handle,
+                buffer.as_mut_ptr() as *mut _,
+                (buffer.len() * 4) as u32,
+                false,
+                FILE_NOTIFY_CHANGE_LAST_ACCESS
+                    | FILE_NOTIFY_CHANGE_LAST_WRITE
+                    | FILE_NOTIFY_CHANGE_FILE_NAME
+                    | FILE_NOTIFY_CHANGE_ATTRIBUTES
+                    | FILE_NOTIFY_CHANGE_SECURITY,
+                Some(&mut bytes_returned),
+                None,
+                None,
+            );
+            if let Err(e) = result {
+                let _ = CloseHandle(handle);
+                return Err(Error::Io { path: folder.to_path_buf(), message: format!("ReadDirectoryChangesW: {}", e) });
+            }
+
+            // 0 bytes = buffer overflow, changes are lost
+            if bytes_returned == 0 || started.elapsed() < STARTUP_GRACE {
+                continue;
+            }
+
+            let mut offset = 0usize;
+            loop {
+                let info = (buffer.as_ptr() as *const u8).add(offset) as *const FILE_NOTIFY_INFORMATION;
+                let name_len = (*info).FileNameLength as usize / 2;
+                let name = String::from_utf16_lossy(std::slice::from_raw_parts((*info).FileName.as_ptr(), name_len));
+
+                if let Some(index) = names.iter().position(|n| *n == name.to_lowercase()) {
+                    on_access(&files[index], (*info).Action);
+                }
+
+                if (*info).NextEntryOffset == 0 {
+                    break;
+                }
+                offset += (*info).NextEntryOffset as usize;
+            }
+        }
+
+        let _ = CloseHandle(handle);
+    }
+
+    Ok(())
+}
+
+/// Processes holding a handle to the file (pid, name) - via Restart Manager
+pub fn processes_using(path: &Path) -> Vec<(u32, String)> {
+    let mut result = Vec::new();
+    let own_pid = std::process::id();
+
+    unsafe {
+        let mut session: u32 = 0;
+        let mut session_key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+        if RmStartSession(&mut session, 0, PWSTR(session_key.as_mut_ptr())) != ERROR_SUCCESS {
+            return result;
+        }
+
+        let path_wide = to_wide(path);
+        let resources = [PCWSTR(path_wide.as_ptr())];
+        if RmRegisterResources(session, Some(&resources), None, None) == ERROR_SUCCESS {
+            let mut infos = [RM_PROCESS_INFO::default(); 16];
+            let mut needed: u32 = 0;
+            let mut count = infos.len() as u32;
+            let mut reasons: u32 = 0;
+            if RmGetList(session, &mut needed, &mut count, Some(infos.as_mut_ptr()), &mut reasons) == ERROR_SUCCESS {
+                for info in &infos[..count as usize] {
+                    let pid = info.Process.dwProcessId;
+                    if pid == own_pid {
+                        continue;
+                    }
+                    let name = crate::process_info::get_process_name_from_snapshot(pid).unwrap_or_else(|| {
+                        let len = info.strAppName.iter().position(|&c| c == 0).unwrap_or(info.strAppName.len());
+                        String::from_utf16_lossy(&info.strAppName[..len])
+                    });
+                    result.push((pid, name));
+                }
+            }
+        }
+
+        let _ = RmEndSession(session);
+    }
+
+    result
+}
+
+/// Raises the Critical alert for an access (with cooldown per file)
+fn on_access(path: &Path, action: FILE_ACTION) {
+    let key = path.to_string_lossy().to_lowercase();
+    {
+        let mut last_alerts = LAST_ALERTS.lock();
+        if last_alerts.get(&key).is_some_and(|t| t.elapsed() < ALERT_COOLDOWN) {
+            return;
+        }
+        last_alerts.insert(key, Instant::now());
+    }
+
+    let action_text = match action {
+        FILE_ACTION_REMOVED => "DELETED",
+        FILE_ACTION_RENAMED_OLD_NAME => "RENAMED",
+        FILE_ACTION_MODIFIED => "ACCESSED",
+        _ => "TOUCHED",
+    };
+
+    // Process with open handle, otherwise the foreground window (e.g. Explorer copy)
+    let suspects = processes_using(path);
+    let suspect = match suspects.first() {
+        Some((pid, name)) => format!("{} (PID {})", name, pid),
+        None => crate::process_info::get_process_info(unsafe { GetForegroundWindow() }).process_name,
+    };
+
+    warn!("!!! HONEYTOKEN {}: {} by {} !!!", action_text, path.display(), suspect);
+
+    if !crate::rules::is_enabled(Rule::Honeytoken) {
+        return;
+    }
+
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    crate::alert_window::set_rule_alert(
+        Rule::Honeytoken,
+        &format!("HONEYTOKEN {}: {} ({})", action_text, file_name, suspect),
+        &path.to_string_lossy(),
+        Severity::Critical,
+    );
+    crate::sound::play_alert(Rule::Honeytoken, Severity::Critical);
+
+    let capture = crate::rules::capture_mode(Rule::Honeytoken, Severity::Critical);
+    let name = suspects.first().map(|(_, n)| n.clone()).unwrap_or_else(|| "honeytoken".to_string());
+    crate::screenshot::capture_alert_screenshots(name, "HONEYTOKEN", capture, Severity::Critical);
+}
@@ -0,0 +1,109 @@
+//! Host Process Resolution
+//!
+//! Some windows belong to a host instead of the program the user sees: console windows to
+//! conhost/OpenConsole, WSL interop to wslhost, packaged (UWP) apps to ApplicationFrameHost
+//! and WSL GUI apps (WSLg) to msrdc. Without resolving them every terminal event says
+//! "conhost". The process fields are replaced with the hosted program; the host is kept in
+//! `host_process_name`/`host_process_id`.
+
+use crate::process_info::{read_process, ProcessInfo, ProcessInfoProvider};
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+use windows::Win32::System::Registry::HKEY_CURRENT_USER;
+use windows::Win32::UI::WindowsAndMessaging::EnumChildWindows;
+
+/// Console hosts: the console program started them, so it is their parent
+const CONSOLE_HOSTS: &[&str] = &["conhost", "openconsole"];
+
+/// WSL interop host (parent: wsl.exe or the distribution launcher)
+const WSL_HOST: &str = "wslhost";
+
+/// Frame of packaged apps (the app itself owns a child window)
+const FRAME_HOST: &str = "applicationframehost";
+
+/// Remote desktop client showing WSLg windows
+const WSLG_HOST: &str = "msrdc";
+const WSLG_WINDOW_CLASS: &str = "RAIL_WINDOW";
+
+/// Replaces a host process with the program it serves (unchanged for other processes)
+pub fn resolve(provider: &dyn ProcessInfoProvider, hwnd: HWND, info: ProcessInfo) -> ProcessInfo {
+    let host = info.process_name.to_lowercase();
+    let hosted = if CONSOLE_HOSTS.contains(&host.as_str()) {
+        hosted_by_parent(provider, &info, info.process_name.clone())
+    } else if host == WSL_HOST {
+        let host_name = match info.command_line.as_deref().and_then(wsl_distribution) {
+            Some(distribution) => format!("{} ({})", info.process_name, distribution),
+            None => info.process_name.clone(),
+        };
+        hosted_by_parent(provider, &info, host_name)
+    } else if host == FRAME_HOST {
+        frame_app(provider, hwnd, info.process_id)
+            .map(|process_id| (read_process(provider, process_id), info.process_name.clone()))
+    } else if host == WSLG_HOST && info.window_class == WSLG_WINDOW_CLASS {
+        // The Linux program is not visible to Windows: named after the distribution in the title
+        let mut hosted = info.clone();
+        hosted.process_name = match wslg_distribution(&info.window_title) {
+            Some(distribution) => format!("wsl:{}", distribution),
+            None => "wsl".to_string(),
+        };
+        Some((hosted, info.process_name.clone()))
+    } else {
+        None
+    };
+
+    let Some((mut hosted, host_name)) = hosted else {
+        return info;
+    };
+    hosted.window_title = info.window_title;
+    hosted.window_class = info.window_class;
+    hosted.host_process_name = host_name;
+    hosted.host_process_id = info.process_id;
+    hosted
+}
+
+/// Program that started the host (None if the parent is not known)
+fn hosted_by_parent(
+    provider: &dyn ProcessInfoProvider,
+    info: &ProcessInfo,
+    host_name: String,
+) -> Option<(ProcessInfo, String)> {
+    (info.parent_process_id > 0).then(|| (read_process(provider, info.parent_process_id), host_name))
+}
+
+/// Process of the app inside an ApplicationFrameHost frame (first child window of another process)
+fn frame_app(provider: &dyn ProcessInfoProvider, hwnd: HWND, host_id: u32) -> Option<u32> {
+    unsafe extern "system" fn collect(child: HWND, lparam: LPARAM) -> BOOL {
+        let children = &mut *(lparam.0 as *mut Vec<HWND>);
+        children.push(child);
+        BOOL(1)
+    }
+
+    let mut children: Vec<HWND> = Vec::new();
+    unsafe {
+        let _ = EnumChildWindows(hwnd, Some(collect), LPARAM(&mut children as *mut _ as isize));
+    }
+    children
+        .into_iter()
+        .map(|child| provider.window_process_id(child))
+        .find(|&process_id| process_id != 0 && process_id != host_id)
+}
+
+/// Distribution of a wslhost command line ("--distro-id {GUID}"), name from the Lxss registry
+fn wsl_distribution(command_line: &str) -> Option<String> {
+    let mut parts = command_line.split_whitespace();
+    parts.find(|part| part.eq_ignore_ascii_case("--distro-id"))?;
+    let id = parts.next()?.trim_matches('"');
+    let path = format!(r"Software\Microsoft\Windows\CurrentVersion\Lxss\{}", id);
+    let values = crate::autoruns::read_values(HKEY_CURRENT_USER, &path)?;
+    values
+        .into_iter()
+        .find(|(name, _)| name == "DistributionName")
+        .map(|(_, distribution)| distribution)
+        .or_else(|| Some(id.to_string()))
+}
+
+/// WSLg appends the distribution to window titles: "gedit (Ubuntu)"
+fn wslg_distribution(title: &str) -> Option<String> {
+    let open = title.rfind(" (")?;
+    let distribution = title[open + 2..].strip_suffix(')')?;
+    (!distribution.is_empty()).then(|| distribution.to_string())
+}
@@ -0,0 +1,143 @@
+//! HTTP Client
+//!
+//! Minimal blocking HTTP(S) client on WinHTTP (system proxy settings, no extra dependencies)
+//! for the network integrations. Call it from background threads only.
+
+use std::ffi::c_void;
+use windows::core::{w, PCWSTR};
+use windows::Win32::Networking::WinHttp::*;
+
+/// Request timeout (ms)
+const HTTP_TIMEOUT_MS: i32 = 15_000;
+
+/// Status code and body of a response
+pub struct Response {
+    pub status: u32,
+    pub body: Vec<u8>,
+}
+
+/// WinHTTP handle, closed on drop
+struct InternetHandle(*mut c_void);
+
+impl InternetHandle {
+    fn new(handle: *mut c_void, what: &str) -> Result<Self, String> {
+        if handle.is_null() {
+            Err(format!("{} failed: {}", what, windows::core::Error::from_win32()))
+        } else {
+            Ok(InternetHandle(handle))
+        }
+    }
+}
+
+impl Drop for InternetHandle {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = WinHttpCloseHandle(self.0);
+        }
+    }
+}
+
+/// Parts of an http(s) URL: (secure, host, port, path incl. query)
+fn parse_url(url: &str) -> Result<(bool, String, u16, String), String> {
+    let (secure, rest) = if let Some(rest) = url.strip_prefix("https://") {
+        (true, rest)
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        (false, rest)
+    } else {
+        return Err(format!("unsupported URL: {}", url));
+    };
+
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let default_port = if secure { INTERNET_DEFAULT_HTTPS_PORT } else { INTERNET_DEFAULT_HTTP_PORT };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().map_err(|_| format!("invalid port in URL: {}", url))?),
+        None => (authority, default_port),
+    };
+    if host.is_empty() {
+        return Err(format!("no host in URL: {}", url));
+    }
+    Ok((secure, host.to_string(), port, path.to_string()))
+}
+
+/// Sends a request and reads the whole response
+pub fn request(method: &str, url: &str, headers: &[(&str, &str)], body: &[u8]) -> Result<Response, String> {
+    let (secure, host, port, path) = parse_url(url)?;
+    let wide = |s: &str| -> Vec<u16> { s.encode_utf16().chain(std::iter::once(0)).collect() };
+
+    unsafe {
+        let session = InternetHandle::new(
+            WinHttpOpen(w!("PCWatcher"), WINHTTP_ACCESS_TYPE_AUTOMATIC_PROXY, PCWSTR::null(), PCWSTR::null(), 0),
+            "WinHttpOpen",
+        )?;
+        let _ = WinHttpSetTimeouts(session.0, HTTP_TIMEOUT_MS, HTTP_TIMEOUT_MS, HTTP_TIMEOUT_MS, HTTP_TIMEOUT_MS);
+
+        let host_wide = wide(&host);
+        let connection = InternetHandle::new(
+            WinHttpConnect(session.0, PCWSTR(host_wide.as_ptr()), port, 0),
+            "WinHttpConnect",
+        )?;
+
+        let method_wide = wide(method);
+        let path_wide = wide(&path);
+        let flags = if secure { WINHTTP_FLAG_SECURE } else { WINHTTP_OPEN_REQUEST_FLAGS(0) };
+        let request = InternetHandle::new(
+            WinHttpOpenRequest(
+                connection.0,
+                PCWSTR(method_wide.as_ptr()),
+                PCWSTR(path_wide.as_ptr()),
+                PCWSTR::null(),
+                PCWSTR::null(),
+                std::ptr::null(),
+                flags,
+            ),
+            "WinHttpOpenRequest",
+        )?;
+
+        let header_text: String = headers.iter().map(|(name, value)| format!("{}: {}\r\n", name, value)).collect();
+        let header_wide: Vec<u16> = header_text.encode_utf16().collect();
+        let body_ptr = (!body.is_empty()).then_some(body.as_ptr() as *const c_void);
+        WinHttpSendRequest(
+            request.0,
+            (!header_wide.is_empty()).then_some(header_wide.as_slice()),
+            body_ptr,
+            body.len() as u32,
+            body.len() as u32,
+            0,
+        )
+        .map_err(|e| format!("send failed: {}", e))?;
+        WinHttpReceiveResponse(request.0, std::ptr::null_mut()).map_err(|e| format!("no response: {}", e))?;
+
+        let mut status: u32 = 0;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let mut index = 0u32;
+        WinHttpQueryHeaders(
+            request.0,
+            WINHTTP_QUERY_STATUS_CODE | WINHTTP_QUERY_FLAG_NUMBER,
+            PCWSTR::null(),
+            Some(&mut status as *mut u32 as *mut c_void),
+            &mut size,
+            &mut index,
+        )
+        .map_err(|e| format!("no status code: {}", e))?;
+
+        let mut body = Vec::new();
+        loop {
+            let mut available = 0u32;
+            WinHttpQueryDataAvailable(request.0, &mut available).map_err(|e| format!("read failed: {}", e))?;
+            if available == 0 {
+                break;
+            }
+            let start = body.len();
+            body.resize(start + available as usize, 0);
+            let mut read = 0u32;
+            WinHttpReadData(request.0, body[start..].as_mut_ptr() as *mut c_void, available, &mut read)
+                .map_err(|e| format!("read failed: {}", e))?;
+            body.truncate(start + read as usize);
+        }
+
+        Ok(Response { status, body })
+    }
+}
@@ -0,0 +1,81 @@
+//! Incidents
+//!
+//! Groups related events into incidents. An alert opens an incident for its process; later
+//! events of the same process tree (the process and everything it starts) join it as long as
+//! they follow within the time window, and the events of the process shortly before the alert
+//! (CREATED, SHOWN, ...) are added afterwards. Every member event carries the incident ID -
+//! log file, GUI, notifications and exports show it.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use chrono::{DateTime, Duration as ChronoDuration, Local};
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use tracing::info;
+
+/// Incident that can still grow
+struct OpenIncident {
+    id: i64,
+    /// Member processes (the alerted one and those started by members)
+    processes: HashSet<u32>,
+    last_event: DateTime<Local>,
+    severity: Severity,
+}
+
+lazy_static::lazy_static! {
+    static ref OPEN: Mutex<Vec<OpenIncident>> = Mutex::new(Vec::new());
+}
+
+/// Process and its ancestors of an event (0 = unknown)
+fn process_tree(entry: &LogEntry) -> [u32; 4] {
+    [
+        entry.process_id,
+        entry.parent_process_id,
+        entry.grandparent_process_id,
+        entry.greatgrandparent_process_id,
+    ]
+}
+
+/// Sets the incident ID of an event (called by the log worker before the event is stored)
+pub fn assign(entry: &mut LogEntry) {
+    let config = crate::config::get().incidents;
+    if !config.enabled || entry.process_id == 0 {
+        return;
+    }
+    let window = ChronoDuration::seconds(config.window_secs as i64);
+    let mut open = OPEN.lock();
+    open.retain(|incident| entry.timestamp - incident.last_event <= window);
+
+    // Member of an open incident: the process itself or one of its ancestors belongs to it
+    let tree = process_tree(entry);
+    if let Some(incident) = open
+        .iter_mut()
+        .find(|incident| tree.iter().any(|pid| *pid != 0 && incident.processes.contains(pid)))
+    {
+        incident.processes.insert(entry.process_id);
+        incident.last_event = entry.timestamp;
+        incident.severity = incident.severity.max(entry.severity);
+        entry.incident_id = Some(incident.id);
+        crate::database::update_incident(incident.id, &entry.timestamp, incident.severity);
+        return;
+    }
+
+    // Alerts open a new incident
+    if entry.severity < Severity::Warning {
+        return;
+    }
+    let title = format!("{} {}", entry.event_type, entry.process_name);
+    let Some(id) = crate::database::create_incident(entry, &title) else {
+        return;
+    };
+    info!("Incident #{} opened: {}", id, title);
+    entry.incident_id = Some(id);
+    // The lead-up of the alert (window creation, focus, ...)
+    crate::database::tag_incident_events(id, entry.process_id, &(entry.timestamp - window));
+    open.push(OpenIncident {
+        id,
+        processes: HashSet::from([entry.process_id]),
+        last_event: entry.timestamp,
+        severity: entry.severity,
+    });
+}
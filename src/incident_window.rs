@@ -0,0 +1,358 @@
+//! Incident Review Window
+//!
+//! Tray action "Incidents": lists the grouped incidents (newest first) with severity and
+//! state. The selected incident shows the timeline of its member events and the files
+//! captured for its alerts. Actions: acknowledge, export a ZIP bundle, whitelist the process.
+//! Runs its own message loop like the reveal dialog.
+
+use crate::database::IncidentSummary;
+use crate::logger::LogEntry;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use tracing::{error, info};
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{GetStockObject, COLOR_BTNFACE, DEFAULT_GUI_FONT, HBRUSH};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Input::KeyboardAndMouse::EnableWindow;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+const DIALOG_WIDTH: i32 = 860;
+const DIALOG_HEIGHT: i32 = 470;
+
+/// Incidents shown in the list
+const MAX_INCIDENTS: usize = 200;
+
+// Control IDs
+const ID_INCIDENTS: i32 = 101;
+const ID_FILES: i32 = 102;
+const ID_ACKNOWLEDGE: i32 = 103;
+const ID_EXPORT: i32 = 104;
+const ID_WHITELIST: i32 = 105;
+const ID_REFRESH: i32 = 106;
+
+/// State of the open dialog (only one at a time, tray thread)
+struct DialogState {
+    incident_list: HWND,
+    event_list: HWND,
+    file_list: HWND,
+    incidents: Vec<IncidentSummary>,
+    /// Members and captured files of the selected incident
+    events: Vec<LogEntry>,
+    files: Vec<PathBuf>,
+    done: bool,
+}
+
+thread_local! {
+    static STATE: RefCell<Option<DialogState>> = const { RefCell::new(None) };
+}
+
+/// Line of an incident in the list
+fn incident_label(incident: &IncidentSummary) -> String {
+    format!(
+        "#{} [{}] {} {} ({} events){}",
+        incident.id,
+        incident.severity.as_str(),
+        incident.started.format("%Y-%m-%d %H:%M:%S"),
+        incident.title,
+        incident.event_count,
+        if incident.acknowledged { " - acknowledged" } else { "" }
+    )
+}
+
+/// Line of a member event in the timeline
+fn event_label(event: &LogEntry) -> String {
+    let title = if event.window_title.is_empty() { String::new() } else { format!(": {}", event.window_title) };
+    format!("{} {} {} (PID {}){}", event.timestamp.format("%H:%M:%S"), event.event_type, event.process_name, event.process_id, title)
+}
+
+/// Replaces the rows of a list box
+unsafe fn fill_list(list: HWND, rows: &[String], empty_text: &str) {
+    SendMessageW(list, LB_RESETCONTENT, WPARAM(0), LPARAM(0));
+    if rows.is_empty() {
+        let text: Vec<u16> = empty_text.encode_utf16().chain(std::iter::once(0)).collect();
+        SendMessageW(list, LB_ADDSTRING, WPARAM(0), LPARAM(text.as_ptr() as isize));
+    }
+    for row in rows {
+        let text: Vec<u16> = row.encode_utf16().chain(std::iter::once(0)).collect();
+        SendMessageW(list, LB_ADDSTRING, WPARAM(0), LPARAM(text.as_ptr() as isize));
+    }
+}
+
+/// Selected row of a list box
+unsafe fn selected_index(list: HWND) -> Option<usize> {
+    usize::try_from(SendMessageW(list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0).ok()
+}
+
+/// Captured files of the alerts of an incident
+fn capture_files(events: &[LogEntry]) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = crate::alert_export::incident_capture_folders(events)
+        .iter()
+        .filter_map(|folder| std::fs::read_dir(folder).ok())
+        .flat_map(|entries| entries.flatten().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Reloads the incident list, keeping the selection
+unsafe fn reload_incidents() {
+    let incidents = match crate::database::open().and_then(|conn| crate::database::read_incidents(&conn, MAX_INCIDENTS)) {
+        Ok(incidents) => incidents,
+        Err(e) => {
+            error!("Could not read incidents: {}", e);
+            Vec::new()
+        }
+    };
+    let Some((list, selection)) = STATE.with(|s| s.borrow().as_ref().map(|d| (d.incident_list, selected_index(d.incident_list)))) else {
+        return;
+    };
+    let rows: Vec<String> = incidents.iter().map(incident_label).collect();
+    fill_list(list, &rows, "No incidents recorded");
+    STATE.with(|s| {
+        if let Some(state) = s.borrow_mut().as_mut() {
+            state.incidents = incidents;
+        }
+    });
+    if let Some(index) = selection {
+        SendMessageW(list, LB_SETCURSEL, WPARAM(index), LPARAM(0));
+    }
+    show_selected();
+}
+
+/// Fills timeline and files of the selected incident
+unsafe fn show_selected() {
+    let Some((event_list, file_list, incident)) = STATE.with(|s| {
+        let state = s.borrow();
+        let state = state.as_ref()?;
+        let incident = selected_index(state.incident_list).and_then(|i| state.incidents.get(i).cloned());
+        Some((state.event_list, state.file_list, incident))
+    }) else {
+        return;
+    };
+
+    let events = incident
+        .and_then(|incident| {
+            crate::database::open()
+                .and_then(|conn| crate::database::read_incident_events(&conn, incident.id))
+                .map_err(|e| error!("Could not read incident events: {}", e))
+                .ok()
+        })
+        .unwrap_or_default();
+    let files = capture_files(&events);
+
+    let event_rows: Vec<String> = events.iter().map(event_label).collect();
+    fill_list(event_list, &event_rows, "Select an incident");
+    let file_rows: Vec<String> = files
+        .iter()
+        .map(|f| {
+            let folder = f.parent().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let name = f.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            format!("{}\\{}", folder, name)
+        })
+        .collect();
+    fill_list(file_list, &file_rows, "No captures");
+
+    STATE.with(|s| {
+        if let Some(state) = s.borrow_mut().as_mut() {
+            state.events = events;
+            state.files = files;
+        }
+    });
+}
+
+/// Selected incident with its member events
+fn selected_incident() -> Option<(IncidentSummary, Vec<LogEntry>)> {
+    STATE.with(|s| {
+        let state = s.borrow();
+        let state = state.as_ref()?;
+        let index = unsafe { selected_index(state.incident_list) }?;
+        Some((state.incidents.get(index)?.clone(), state.events.clone()))
+    })
+}
+
+/// Opens a captured file (images with the image program, others selected in their folder)
+fn open_file(path: &std::path::Path) {
+    let is_image = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("jpg") || e.eq_ignore_ascii_case("png"));
+    if is_image {
+        crate::open_with::open_image(path);
+    } else {
+        crate::open_with::open_location(path);
+    }
+}
+
+/// Shows the dialog above the owner until it is closed
+pub unsafe fn show_dialog(owner: HWND) {
+    if STATE.with(|s| s.borrow().is_some()) {
+        return;
+    }
+
+    let instance = GetModuleHandleW(None).unwrap_or_default();
+    let class_name = w!("PCWatcherIncidents");
+    let wc = WNDCLASSW {
+        lpfnWndProc: Some(incident_window_proc),
+        hInstance: instance.into(),
+        hCursor: LoadCursorW(None, IDC_ARROW).unwrap_or_default(),
+        hbrBackground: HBRUSH((COLOR_BTNFACE.0 + 1) as isize as *mut _),
+        lpszClassName: class_name,
+        ..Default::default()
+    };
+    // Fails harmlessly if already registered
+    let _ = RegisterClassW(&wc);
+
+    // Centered on the primary monitor (the tray window is invisible)
+    let x = (GetSystemMetrics(SM_CXSCREEN) - DIALOG_WIDTH) / 2;
+    let y = (GetSystemMetrics(SM_CYSCREEN) - DIALOG_HEIGHT) / 2;
+
+    let hwnd = match CreateWindowExW(
+        WS_EX_DLGMODALFRAME,
+        class_name,
+        w!("PC Watcher - Incidents"),
+        WS_POPUP | WS_CAPTION | WS_SYSMENU,
+        x, y,
+        DIALOG_WIDTH,
+        DIALOG_HEIGHT,
+        owner,
+        None,
+        instance,
+        None,
+    ) {
+        Ok(h) => h,
+        Err(e) => {
+            error!("Could not create incident window: {}", e);
+            return;
+        }
+    };
+
+    let font = GetStockObject(DEFAULT_GUI_FONT);
+    let child = |class: PCWSTR, text: PCWSTR, style: WINDOW_STYLE, x: i32, y: i32, w: i32, h: i32, id: i32| {
+        let control = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            class,
+            text,
+            WS_CHILD | WS_VISIBLE | style,
+            x, y, w, h,
+            hwnd,
+            HMENU(id as isize as *mut _),
+            instance,
+            None,
+        )
+        .unwrap_or_default();
+        SendMessageW(control, WM_SETFONT, WPARAM(font.0 as usize), LPARAM(1));
+        control
+    };
+    let list_style = WS_BORDER | WS_VSCROLL | WS_HSCROLL | WS_TABSTOP | WINDOW_STYLE(LBS_NOTIFY as u32);
+
+    child(w!("STATIC"), w!("Incidents:"), WINDOW_STYLE::default(), 10, 10, 200, 18, 0);
+    let incident_list = child(w!("LISTBOX"), PCWSTR::null(), list_style, 10, 32, 420, 360, ID_INCIDENTS);
+    child(w!("STATIC"), w!("Events:"), WINDOW_STYLE::default(), 440, 10, 200, 18, 0);
+    let event_list = child(w!("LISTBOX"), PCWSTR::null(), list_style, 440, 32, 400, 220, 0);
+    child(w!("STATIC"), w!("Captures (double-click to open):"), WINDOW_STYLE::default(), 440, 262, 300, 18, 0);
+    let file_list = child(w!("LISTBOX"), PCWSTR::null(), list_style, 440, 284, 400, 108, ID_FILES);
+    // Long event lines can be scrolled horizontally
+    SendMessageW(event_list, LB_SETHORIZONTALEXTENT, WPARAM(1200), LPARAM(0));
+    SendMessageW(incident_list, LB_SETHORIZONTALEXTENT, WPARAM(800), LPARAM(0));
+
+    child(w!("BUTTON"), w!("Acknowledge"), WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32), 10, 402, 110, 26, ID_ACKNOWLEDGE);
+    child(w!("BUTTON"), w!("Export bundle"), WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32), 130, 402, 110, 26, ID_EXPORT);
+    child(w!("BUTTON"), w!("Whitelist process"), WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32), 250, 402, 120, 26, ID_WHITELIST);
+    child(w!("BUTTON"), w!("Refresh"), WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32), 380, 402, 100, 26, ID_REFRESH);
+    child(w!("BUTTON"), w!("Close"), WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32), 740, 402, 100, 26, IDCANCEL.0);
+
+    STATE.with(|s| {
+        *s.borrow_mut() = Some(DialogState {
+            incident_list,
+            event_list,
+            file_list,
+            incidents: Vec::new(),
+            events: Vec::new(),
+            files: Vec::new(),
+            done: false,
+        })
+    });
+    reload_incidents();
+
+    let _ = EnableWindow(owner, false);
+    let _ = ShowWindow(hwnd, SW_SHOW);
+    let _ = SetForegroundWindow(hwnd);
+
+    let mut msg = MSG::default();
+    while !STATE.with(|s| s.borrow().as_ref().map(|d| d.done).unwrap_or(true)) {
+        if !GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            // WM_QUIT belongs to the outer loop
+            PostQuitMessage(msg.wParam.0 as i32);
+            break;
+        }
+        if !IsDialogMessageW(hwnd, &msg).as_bool() {
+            let _ = TranslateMessage(&msg);
+            let _ = DispatchMessageW(&msg);
+        }
+    }
+
+    let _ = EnableWindow(owner, true);
+    let _ = DestroyWindow(hwnd);
+    STATE.with(|s| s.borrow_mut().take());
+}
+
+/// Window procedure of the incident window
+unsafe extern "system" fn incident_window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    match msg {
+        WM_COMMAND => {
+            let id = (wparam.0 & 0xFFFF) as i32;
+            let notification = ((wparam.0 >> 16) & 0xFFFF) as u32;
+            if id == ID_INCIDENTS && notification == LBN_SELCHANGE {
+                show_selected();
+            } else if id == ID_FILES && notification == LBN_DBLCLK {
+                let file = STATE.with(|s| {
+                    let state = s.borrow();
+                    let state = state.as_ref()?;
+                    state.files.get(selected_index(state.file_list)?).cloned()
+                });
+                if let Some(file) = file {
+                    open_file(&file);
+                }
+            } else if id == ID_ACKNOWLEDGE {
+                if let Some((incident, _)) = selected_incident() {
+                    match crate::database::open().and_then(|conn| crate::database::acknowledge_incident(&conn, incident.id)) {
+                        Ok(()) => info!("Incident #{} acknowledged", incident.id),
+                        Err(e) => error!("Could not acknowledge incident #{}: {}", incident.id, e),
+                    }
+                    reload_incidents();
+                }
+            } else if id == ID_EXPORT {
+                if let Some((incident, events)) = selected_incident() {
+                    // Zipping recordings can take a moment - keep the window responsive
+                    std::thread::spawn(move || match crate::alert_export::export_incident(&incident, &events) {
+                        Ok(path) => {
+                            info!("Incident #{} exported: {}", incident.id, path.display());
+                            crate::open_with::open_location(&path);
+                        }
+                        Err(e) => crate::error::report(&format!("Could not export incident #{}", incident.id), e),
+                    });
+                }
+            } else if id == ID_WHITELIST {
+                if let Some((incident, _)) = selected_incident() {
+                    let text: Vec<u16> = format!("Add \"{}\" to the whitelist?", incident.process_name)
+                        .encode_utf16()
+                        .chain(std::iter::once(0))
+                        .collect();
+                    let answer = MessageBoxW(hwnd, PCWSTR(text.as_ptr()), w!("PC Watcher"), MB_YESNO | MB_ICONQUESTION);
+                    if answer == IDYES {
+                        crate::config::add_to_whitelist(&incident.process_name);
+                    }
+                }
+            } else if id == ID_REFRESH {
+                reload_incidents();
+            } else if id == IDCANCEL.0 {
+                STATE.with(|s| s.borrow_mut().as_mut().map(|d| d.done = true));
+            }
+            LRESULT(0)
+        }
+        WM_CLOSE => {
+            STATE.with(|s| s.borrow_mut().as_mut().map(|d| d.done = true));
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
@@ -0,0 +1,230 @@
+//! Input Rate Telemetry
+//!
+//! Counts key presses and mouse movement distance per minute - never key values.
+//! Alerts on input while the session is locked (Critical) and on typing rate spikes
+//! while no known interactive app has the focus (Warning, e.g. a script typing).
+
+use crate::notification::Severity;
+use crate::rules::Rule;
+use chrono::{DateTime, Local};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+use windows::Win32::Foundation::{HANDLE, HWND, WPARAM};
+use windows::Win32::System::RemoteDesktop::{WTSRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION};
+use windows::Win32::System::StationsAndDesktops::{
+    CloseDesktop, GetUserObjectInformationW, OpenInputDesktop, DESKTOP_CONTROL_FLAGS,
+    DESKTOP_READOBJECTS, UOI_NAME,
+};
+use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, WTS_SESSION_LOCK, WTS_SESSION_UNLOCK};
+
+/// Exit check interval (minute = 60 checks)
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const CHECKS_PER_MINUTE: u32 = 60;
+
+/// Minutes used for the average typing rate
+const HISTORY_MINUTES: usize = 30;
+
+/// No mouse position yet
+const NO_POSITION: i32 = i32::MIN;
+
+// Counters of the current minute (updated by the low-level hooks)
+static KEY_COUNT: AtomicU64 = AtomicU64::new(0);
+static MOUSE_DISTANCE: AtomicU64 = AtomicU64::new(0);
+static LOCKED_INPUT: AtomicU64 = AtomicU64::new(0);
+static LAST_X: AtomicI32 = AtomicI32::new(NO_POSITION);
+static LAST_Y: AtomicI32 = AtomicI32::new(NO_POSITION);
+
+/// Session is locked (set on lock/unlock notifications - polling would count the first
+/// input after unlock as locked input, the hooks never see the lock screen itself)
+static SESSION_LOCKED: AtomicBool = AtomicBool::new(false);
+
+/// Aggregated input of one minute
+#[derive(Debug, Clone)]
+pub struct InputMinute {
+    pub minute: DateTime<Local>,
+    pub keys: u64,
+    /// Mouse movement in pixels
+    pub mouse_distance: u64,
+    /// Key presses/clicks while the session was locked
+    pub locked_input: u64,
+}
+
+lazy_static::lazy_static! {
+    // Key counts of the last minutes with input (for the average)
+    static ref KEY_HISTORY: Mutex<VecDeque<u64>> = Mutex::new(VecDeque::with_capacity(HISTORY_MINUTES));
+}
+
+/// Counts a key press (called from the keyboard hook - value is not recorded)
+pub fn record_key() {
+    KEY_COUNT.fetch_add(1, Ordering::Relaxed);
+    if SESSION_LOCKED.load(Ordering::Relaxed) {
+        LOCKED_INPUT.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Counts a mouse click while locked (called from the mouse hook)
+pub fn record_click() {
+    if SESSION_LOCKED.load(Ordering::Relaxed) {
+        LOCKED_INPUT.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Adds the distance to the last mouse position (called from the mouse hook)
+pub fn record_mouse_move(x: i32, y: i32) {
+    let last_x = LAST_X.swap(x, Ordering::Relaxed);
+    let last_y = LAST_Y.swap(y, Ordering::Relaxed);
+    if last_x == NO_POSITION || last_y == NO_POSITION {
+        return;
+    }
+    let dx = (x - last_x) as f64;
+    let dy = (y - last_y) as f64;
+    MOUSE_DISTANCE.fetch_add((dx * dx + dy * dy).sqrt() as u64, Ordering::Relaxed);
+}
+
+/// Checks if the session is locked (input desktop is "Winlogon" instead of "Default")
+/// Only used for the state at start, changes come from WM_WTSSESSION_CHANGE
+fn is_session_locked() -> bool {
+    unsafe {
+        // No access to the input desktop = secure desktop (lock screen/UAC)
+        let desktop = match OpenInputDesktop(DESKTOP_CONTROL_FLAGS(0), false, DESKTOP_READOBJECTS) {
+            Ok(d) => d,
+            Err(_) => return true,
+        };
+
+        let mut name = [0u16; 64];
+        let result = GetUserObjectInformationW(
+            HANDLE(desktop.0),
+            UOI_NAME,
+            Some(name.as_mut_ptr() as *mut _),
+            (name.len() * 2) as u32,
+            None,
+        );
+        let _ = CloseDesktop(desktop);
+
+        if result.is_err() {
+            return false;
+        }
+        let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+        !String::from_utf16_lossy(&name[..len]).eq_ignore_ascii_case("Default")
+    }
+}
+
+/// Registers the lock/unlock notifications for a window (WM_WTSSESSION_CHANGE)
+pub fn register(hwnd: HWND) {
+    if !crate::config::get().telemetry.enabled {
+        return;
+    }
+    SESSION_LOCKED.store(is_session_locked(), Ordering::Relaxed);
+    if let Err(e) = unsafe { WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) } {
+        warn!("Could not register session notifications: {}", e);
+    }
+}
+
+/// Handles WM_WTSSESSION_CHANGE of the tray window
+pub fn on_session_change(wparam: WPARAM) {
+    let locked = match wparam.0 as u32 {
+        WTS_SESSION_LOCK => true,
+        WTS_SESSION_UNLOCK => false,
+        _ => return,
+    };
+    if SESSION_LOCKED.swap(locked, Ordering::Relaxed) != locked {
+        info!("Session {}", if locked { "locked" } else { "unlocked" });
+    }
+}
+
+/// Collects the counters of the finished minute
+fn take_minute() -> InputMinute {
+    InputMinute {
+        minute: Local::now(),
+        keys: KEY_COUNT.swap(0, Ordering::Relaxed),
+        mouse_distance: MOUSE_DISTANCE.swap(0, Ordering::Relaxed),
+        locked_input: LOCKED_INPUT.swap(0, Ordering::Relaxed),
+    }
+}
+
+/// Checks a finished minute for anomalies and stores it
+fn process_minute(stats: InputMinute) {
+    let config = crate::config::get().telemetry;
+
+    if stats.keys > 0 || stats.mouse_distance > 0 {
+        crate::database::insert_input_minute(&stats);
+    }
+
+    // Input while locked: nobody should be typing
+    if stats.locked_input > 0 {
+        warn!("!!! INPUT WHILE SESSION LOCKED: {} key presses/clicks !!!", stats.locked_input);
+        raise_alert(
+            &format!("INPUT WHILE LOCKED ({} keys/clicks)", stats.locked_input),
+            Severity::Critical,
+        );
+    }
+
+    // Typing rate spike compared to the average of the last minutes
+    let average = {
+        let history = KEY_HISTORY.lock();
+        if history.is_empty() {
+            0.0
+        } else {
+            history.iter().sum::<u64>() as f64 / history.len() as f64
+        }
+    };
+    let is_spike = config.spike_keys_per_minute > 0
+        && stats.keys >= config.spike_keys_per_minute
+        && (average == 0.0 || stats.keys as f64 > average * config.spike_factor);
+
+    if is_spike {
+        let foreground = crate::process_info::get_process_info(unsafe { GetForegroundWindow() }).process_name;
+        let is_interactive = config
+            .interactive_apps
+            .iter()
+            .any(|app| app.eq_ignore_ascii_case(foreground.trim_end_matches(".exe")));
+        if !is_interactive {
+            warn!("!!! TYPING RATE SPIKE: {} keys/min (avg {:.0}) in {} !!!", stats.keys, average, foreground);
+            raise_alert(&format!("{} ({} keys/min!)", foreground, stats.keys), Severity::Warning);
+        }
+    }
+
+    if stats.keys > 0 {
+        let mut history = KEY_HISTORY.lock();
+        if history.len() >= HISTORY_MINUTES {
+            history.pop_front();
+        }
+        history.push_back(stats.keys);
+    }
+}
+
+/// Alert for the input anomaly rule
+fn raise_alert(message: &str, severity: Severity) {
+    if !crate::rules::is_enabled(Rule::InputAnomaly) {
+        return;
+    }
+    crate::alert_window::set_rule_alert(Rule::InputAnomaly, message, "", severity);
+    crate::sound::play_alert(Rule::InputAnomaly, severity);
+    let capture = crate::rules::capture_mode(Rule::InputAnomaly, severity);
+    crate::screenshot::capture_alert_screenshots("input_anomaly".to_string(), "INPUT_ANOMALY", capture, severity);
+}
+
+/// Starts the telemetry thread (aggregation every minute)
+pub fn start_monitor() {
+    if !crate::config::get().telemetry.enabled {
+        return;
+    }
+
+    thread::spawn(|| {
+        info!("Input rate telemetry started");
+        let mut checks = 0;
+        while !crate::tray::should_exit() {
+            checks += 1;
+            if checks >= CHECKS_PER_MINUTE {
+                checks = 0;
+                process_minute(take_minute());
+            }
+
+            thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
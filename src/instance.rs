@@ -0,0 +1,89 @@
+//! Instances
+//!
+//! One PC Watcher per user and Windows session: a named mutex in the session namespace
+//! (`Local\`) that includes the user name, so other users and RDP sessions run their own watcher.
+//! Logs are kept apart the same way (logs/<user>/session-<id>). With `instance.single_instance`
+//! off, further starts in a session get the next free number, which is added to their log files.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::{CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, HWND};
+use windows::Win32::System::RemoteDesktop::ProcessIdToSessionId;
+use windows::Win32::System::Threading::{CreateMutexW, GetCurrentProcessId};
+use windows::Win32::UI::WindowsAndMessaging::{MessageBoxW, MB_ICONINFORMATION, MB_OK, MB_TOPMOST};
+
+/// Highest instance number per user and session
+const MAX_INSTANCES: u32 = 9;
+
+/// Number of this instance (CLI commands stay at 1 and read the logs of the first instance)
+static NUMBER: AtomicU32 = AtomicU32::new(1);
+
+/// User name as folder name
+pub fn user_name() -> String {
+    let name = crate::screenshot::sanitize_filename(&std::env::var("USERNAME").unwrap_or_default());
+    if name.is_empty() {
+        "unknown".to_string()
+    } else {
+        name
+    }
+}
+
+/// Windows session of this process (0 if unknown)
+pub fn session_id() -> u32 {
+    let mut session = 0;
+    unsafe {
+        let _ = ProcessIdToSessionId(GetCurrentProcessId(), &mut session);
+    }
+    session
+}
+
+/// Claims the first free instance number (the mutex is held until exit)
+fn acquire() -> Option<u32> {
+    let user = user_name();
+    (1..=MAX_INSTANCES).find(|number| {
+        let name: Vec<u16> = format!("Local\\PCWatcher_{}_{}", user, number)
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        unsafe {
+            match CreateMutexW(None, false, PCWSTR(name.as_ptr())) {
+                Ok(handle) if GetLastError() == ERROR_ALREADY_EXISTS => {
+                    let _ = CloseHandle(handle);
+                    false
+                }
+                Ok(_) => true,
+                Err(_) => false,
+            }
+        }
+    })
+}
+
+/// Registers this instance, before the logger starts (the number is part of the log file names)
+/// Returns false if it must not run: already running in this session, or too many instances
+pub fn start() -> bool {
+    let message = match acquire() {
+        Some(number) if number == 1 || !crate::config::get().instance.single_instance => {
+            NUMBER.store(number, Ordering::Relaxed);
+            return true;
+        }
+        Some(_) => w!("PC Watcher is already running (see the tray icon)."),
+        None => w!("Too many PC Watcher instances are running in this session."),
+    };
+    unsafe {
+        MessageBoxW(HWND::default(), message, w!("PC Watcher"), MB_OK | MB_ICONINFORMATION | MB_TOPMOST);
+    }
+    false
+}
+
+/// Number of this instance (1 = first in the session)
+pub fn number() -> u32 {
+    NUMBER.load(Ordering::Relaxed)
+}
+
+/// Added to the log file names of further instances ("" for the first, "-2" for the second)
+pub fn file_tag() -> String {
+    match number() {
+        1 => String::new(),
+        number => format!("-{}", number),
+    }
+}
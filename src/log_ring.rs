@@ -0,0 +1,62 @@
+//! App Log Ring
+//!
+//! The newest lines of the app log (tracing output, not the event log) kept in memory for the
+//! "Diagnostics log" window and the diagnostics bundle. Without a console the app is opaque
+//! otherwise when app.log cannot be written (file logger failed to start, disk full, no rights).
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Lines kept (oldest are dropped)
+const MAX_LINES: usize = 2000;
+
+/// Longest line kept (long command lines and paths are cut)
+const MAX_LINE_CHARS: usize = 1000;
+
+/// Lines written since start (the window refreshes when it changes)
+static WRITTEN: AtomicU64 = AtomicU64::new(0);
+
+lazy_static::lazy_static! {
+    static ref LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::with_capacity(MAX_LINES));
+}
+
+/// Writer for the tracing fmt layer (`.with_writer(|| RingWriter)`, one event per write)
+pub struct RingWriter;
+
+impl Write for RingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        let mut lines = LINES.lock();
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            if lines.len() >= MAX_LINES {
+                lines.pop_front();
+            }
+            lines.push_back(line.chars().take(MAX_LINE_CHARS).collect());
+            WRITTEN.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Kept lines, oldest first
+pub fn lines() -> Vec<String> {
+    LINES.lock().iter().cloned().collect()
+}
+
+/// Kept lines as text (diagnostics bundle, clipboard)
+pub fn text() -> String {
+    let mut text = lines().join("\r\n");
+    text.push_str("\r\n");
+    text
+}
+
+/// Number of lines written since start
+pub fn written() -> u64 {
+    WRITTEN.load(Ordering::Relaxed)
+}
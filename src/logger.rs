@@ -5,73 +5,205 @@
 use anyhow::Result;
 use chrono::{DateTime, Local};
 use crossbeam_channel::Receiver;
+use once_cell::sync::OnceCell;
 use std::fs::{self, OpenOptions};
 use std::io::{BufWriter, Write};
-use std::path::PathBuf;
-use tracing::{info, error};
-use tracing_subscriber::{fmt, prelude::*, EnvFilter};
-
-/// Log directory (in project folder next to EXE)
-fn get_log_dir() -> PathBuf {
-    // Try to determine EXE directory
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(exe_dir) = exe_path.parent() {
-            return exe_dir.join("logs");
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::{debug_span, info, warn, Span};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::Directive;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::{fmt, prelude::*, reload, EnvFilter, Registry};
+use crate::config::LoggingConfig;
+use crate::error::Error;
+use crate::log_ring::RingWriter;
+use crate::notification::Severity;
+
+/// Filter of the app log (replaced when the config is reloaded)
+static FILTER_HANDLE: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+
+/// Log directory (next to EXE, per user and Windows session: logs/<user>/session-<id>)
+pub fn get_log_dir() -> PathBuf {
+    get_logs_root()
+        .join(crate::instance::user_name())
+        .join(format!("session-{}", crate::instance::session_id()))
+}
+
+/// Root of the log folders of all users and sessions (logs/ next to the EXE)
+pub fn get_logs_root() -> PathBuf {
+    // Try to determine EXE directory, fallback: current working directory
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe_path| exe_path.parent().map(|exe_dir| exe_dir.join("logs")))
+        .unwrap_or_else(|| PathBuf::from(".").join("logs"))
+}
+
+/// Session folder (logs/<user>/session-<id>)
+pub fn is_session_dir(path: &Path) -> bool {
+    path.is_dir() && path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("session-"))
+}
+
+/// Log folders of this user: this session first, then the earlier ones (newest first)
+/// Captures of earlier logons stay reachable this way (exports, the web UI)
+pub fn user_log_dirs() -> Vec<PathBuf> {
+    let current = get_log_dir();
+    let mut earlier: Vec<(SystemTime, PathBuf)> = fs::read_dir(get_logs_root().join(crate::instance::user_name()))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| *p != current && is_session_dir(p))
+        .map(|p| (p.metadata().and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH), p))
+        .collect();
+    earlier.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    std::iter::once(current).chain(earlier.into_iter().map(|(_, p)| p)).collect()
+}
+
+/// Prefix of the app log files of this instance (app.log, app-2.log, ...)
+pub fn app_log_prefix() -> String {
+    format!("app{}.log", crate::instance::file_tag())
+}
+
+/// Prefix of the event log files of this instance (event_, event-2_, ...)
+pub fn event_log_prefix() -> String {
+    format!("event{}_", crate::instance::file_tag())
+}
+
+/// Log filter: RUST_LOG if set, otherwise the levels of the config (`logging`)
+/// Returns the invalid config entries as well (logged once the logger is running)
+fn log_filter() -> (EnvFilter, Vec<String>) {
+    if let Ok(filter) = EnvFilter::try_from_default_env() {
+        return (filter, Vec::new());
+    }
+    config_filter(&crate::config::get().logging)
+}
+
+/// Filter from the config: default level plus one directive per module ("event_hook" = this
+/// crate's module, paths with "::" are taken as they are, e.g. for dependencies)
+fn config_filter(logging: &LoggingConfig) -> (EnvFilter, Vec<String>) {
+    let mut invalid = Vec::new();
+    let mut filter = EnvFilter::try_new(&logging.level).unwrap_or_else(|_| {
+        invalid.push(format!("level \"{}\"", logging.level));
+        EnvFilter::new("info")
+    });
+    for (module, level) in &logging.modules {
+        let target = if module.contains("::") {
+            module.clone()
+        } else {
+            format!("{}::{}", env!("CARGO_CRATE_NAME"), module)
+        };
+        match format!("{}={}", target, level).parse::<Directive>() {
+            Ok(directive) => filter = filter.add_directive(directive),
+            Err(_) => invalid.push(format!("\"{}\": \"{}\"", module, level)),
         }
     }
-    // Fallback: current working directory
-    PathBuf::from(".").join("logs")
+    (filter, invalid)
+}
+
+/// Warns about log levels of the config that were skipped
+fn warn_invalid_levels(invalid: &[String]) {
+    for entry in invalid {
+        warn!("Invalid log level in the config (skipped): {}", entry);
+    }
+}
+
+/// Applies the log levels of the (reloaded) config without a restart
+pub fn apply_log_levels() {
+    let Some(handle) = FILTER_HANDLE.get() else {
+        return;
+    };
+    let (filter, invalid) = log_filter();
+    match handle.reload(filter) {
+        Ok(()) => warn_invalid_levels(&invalid),
+        Err(e) => warn!("Could not apply log levels: {}", e),
+    }
 }
 
 /// Initializes the console logger
 pub fn init_console_logger() -> Result<()> {
-    let filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter, invalid) = log_filter();
+    let (filter, handle) = reload::Layer::new(filter);
+    let _ = FILTER_HANDLE.set(handle);
 
+    // Pipeline spans (hook -> worker -> enrichment -> sinks) are logged with their timing when closed
     tracing_subscriber::registry()
-        .with(fmt::layer().with_target(false).compact())
         .with(filter)
+        .with(fmt::layer().with_target(false).with_span_events(FmtSpan::CLOSE).compact())
+        .with(fmt::layer().with_writer(|| RingWriter).with_ansi(false).with_target(false))
         .init();
 
+    warn_invalid_levels(&invalid);
     Ok(())
 }
 
 /// Initializes the file logger (app.log for debug messages)
+/// If app.log cannot be written the app still starts: the memory ring keeps the app log
+/// (Diagnostics log in the tray menu) and the failure is shown in the Health section.
 pub fn init_file_logger() -> Result<()> {
     let log_dir = get_log_dir();
-    fs::create_dir_all(&log_dir)?;
-
-    // Clean up old app.log files (keep only 2)
-    cleanup_old_logs(&log_dir, 2, "app.log");
-
-    let file_appender = tracing_appender::rolling::daily(&log_dir, "app.log");
-    let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
-
-    // Guard must stay alive - we intentionally leak it for app lifetime
-    Box::leak(Box::new(_guard));
+    let appender = fs::create_dir_all(&log_dir).map_err(|e| e.to_string()).and_then(|()| {
+        // Clean up old app.log files (keep only 2)
+        cleanup_old_logs(&log_dir, 2, &app_log_prefix());
+        RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(app_log_prefix())
+            .build(&log_dir)
+            .map_err(|e| e.to_string())
+    });
+
+    let (file_layer, file_error) = match appender {
+        Ok(appender) => {
+            let (non_blocking, guard) = tracing_appender::non_blocking(appender);
+            // Guard must stay alive - we intentionally leak it for app lifetime
+            Box::leak(Box::new(guard));
+            let layer = fmt::layer().with_writer(non_blocking).with_target(false).with_span_events(FmtSpan::CLOSE);
+            (Some(layer), None)
+        }
+        Err(e) => (None, Some(e)),
+    };
 
-    let filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter, invalid) = log_filter();
+    let (filter, handle) = reload::Layer::new(filter);
+    let _ = FILTER_HANDLE.set(handle);
 
     tracing_subscriber::registry()
-        .with(fmt::layer().with_writer(non_blocking).with_target(false))
         .with(filter)
+        .with(file_layer)
+        .with(fmt::layer().with_writer(|| RingWriter).with_ansi(false).with_target(false))
         .init();
 
+    if let Some(message) = file_error {
+        crate::error::report("Could not write app.log", Error::Io { path: log_dir, message });
+    }
+    warn_invalid_levels(&invalid);
     Ok(())
 }
 
 /// Log entry structure
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct LogEntry {
     pub timestamp: DateTime<Local>,
+    /// Milliseconds since boot (unaffected by clock changes, 0 = filled in by the logger)
+    pub uptime_ms: u64,
     pub event_type: String,
+    pub severity: Severity,
     pub process_name: String,
     pub process_id: u32,
     pub process_path: String,
     pub window_title: String,
     pub window_class: String,
     pub command_line: Option<String>,
+    /// URL of the active tab (only for browsers)
+    pub browser_url: Option<String>,
+    /// Cursor position when the alert was captured (screen coordinates)
+    pub cursor_pos: Option<(i32, i32)>,
+    /// User annotation (added later from the details window)
+    pub note: Option<String>,
+    /// Incident the event belongs to (assigned by the log worker)
+    pub incident_id: Option<i64>,
+    /// Labels from tag rules and detections (see tags.rs)
+    pub tags: Vec<String>,
     // Parent process (who started this process?)
     pub parent_process_name: String,
     pub parent_process_id: u32,
@@ -84,6 +216,17 @@ pub struct LogEntry {
     pub greatgrandparent_process_name: String,
     pub greatgrandparent_process_id: u32,
     pub greatgrandparent_process_path: String,
+    /// Why the parent PID is possibly spoofed (see process_info::check_parent)
+    pub parent_spoofed: Option<String>,
+    // Owning application window (popups and dialogs, empty for top-level windows)
+    pub owner_process_name: String,
+    pub owner_process_id: u32,
+    pub owner_window_title: String,
+    // Host of the window (conhost, ApplicationFrameHost, ...; empty for normal windows)
+    pub host_process_name: String,
+    pub host_process_id: u32,
+    /// Pipeline span of the window event (the sinks are traced as its child, not stored)
+    pub span: Option<Span>,
 }
 
 impl LogEntry {
@@ -91,14 +234,21 @@ impl LogEntry {
     pub fn format_file(&self) -> String {
         let mut output = String::with_capacity(512);
 
-        output.push_str(&format!(
-            "────────────────────────────────────────────────────────────────────────────────\n"
-        ));
+        output.push_str("────────────────────────────────────────────────────────────────────────────────\n");
         output.push_str(&format!(
             "[{}] ══ {} ══\n",
             self.timestamp.format("%Y-%m-%d %H:%M:%S%.3f"),
             self.event_type
         ));
+        if let Some(ref note) = self.note {
+            output.push_str(&format!("  Note:        {}\n", note));
+        }
+        if let Some(incident_id) = self.incident_id {
+            output.push_str(&format!("  Incident:    #{}\n", incident_id));
+        }
+        if !self.tags.is_empty() {
+            output.push_str(&format!("  Tags:        {}\n", self.tags.join(", ")));
+        }
         output.push_str(&format!(
             "  Process:     {} (PID: {})\n",
             self.process_name, self.process_id
@@ -113,6 +263,28 @@ impl LogEntry {
             }
         ));
         output.push_str(&format!("  Class:       {}\n", self.window_class));
+        if self.host_process_id > 0 {
+            output.push_str(&format!(
+                "  Host:        {} (PID: {})\n",
+                self.host_process_name, self.host_process_id
+            ));
+        }
+        if self.owner_process_id > 0 {
+            output.push_str(&format!(
+                "  Owner:       {} (PID: {}): {}\n",
+                self.owner_process_name, self.owner_process_id, self.owner_window_title
+            ));
+        }
+        if let Some(ref url) = self.browser_url {
+            output.push_str(&format!("  URL:         {}\n", url));
+        }
+        output.push_str(&format!("  Uptime:      {}\n", crate::clock::format_uptime(self.uptime_ms)));
+        if let Some((x, y)) = self.cursor_pos {
+            output.push_str(&format!("  Cursor:      {}, {}\n", x, y));
+        }
+        if self.severity != Severity::Info {
+            output.push_str(&format!("  Severity:    {}\n", self.severity.as_str()));
+        }
 
         if let Some(ref cmd) = self.command_line {
             if !cmd.is_empty() {
@@ -132,6 +304,9 @@ impl LogEntry {
             if !self.parent_process_path.is_empty() && self.parent_process_path != "Access denied" {
                 output.push_str(&format!("  │  Path:             {}\n", self.parent_process_path));
             }
+            if let Some(ref reason) = self.parent_spoofed {
+                output.push_str(&format!("  │  POSSIBLY SPOOFED: {}\n", reason));
+            }
 
             // Grandparent (level 2)
             if self.grandparent_process_id > 0 && !self.grandparent_process_name.is_empty() {
@@ -169,10 +344,11 @@ impl LogEntry {
 
         // Add parent info with path
         let parent_info = if self.parent_process_id > 0 && !self.parent_process_name.is_empty() {
+            let spoofed = if self.parent_spoofed.is_some() { ", possibly spoofed" } else { "" };
             if !self.parent_process_path.is_empty() && self.parent_process_path != "Access denied" {
-                format!(" [from: {} ({})]", self.parent_process_name, self.parent_process_path)
+                format!(" [from: {} ({}){}]", self.parent_process_name, self.parent_process_path, spoofed)
             } else {
-                format!(" [from: {}]", self.parent_process_name)
+                format!(" [from: {}{}]", self.parent_process_name, spoofed)
             }
         } else {
             String::new()
@@ -199,6 +375,10 @@ impl LogEntry {
             "MINIMIZED" => "MIN",
             "RESTORED" => "RST",
             "Z-ORDER" => "Z-O",
+            "CAMERA" => "CAM",
+            "HOOK_RECOVERED" => "HKR",
+            "CLOCK_CHANGED" => "CLK",
+            "MONITORING_GAP" => "GAP",
             _ => &self.event_type[..3.min(self.event_type.len())],
         };
 
@@ -224,6 +404,12 @@ impl LogEntry {
             String::new()
         };
 
+        // Browser: show the host of the active tab
+        let name = match self.browser_url {
+            Some(ref url) => format!("{} @ {}", name, crate::browser::url_host(url)),
+            None => name,
+        };
+
         // Shorten title for GUI
         let title = if !self.window_title.is_empty() {
             let t = if self.window_title.len() > 25 {
@@ -236,15 +422,106 @@ impl LogEntry {
             String::new()
         };
 
+        // Member of an incident
+        let title = match self.incident_id {
+            Some(incident_id) => format!("{} #{}", title, incident_id),
+            None => title,
+        };
+
+        // Dialog of another application: name the owner
+        let owner = if self.owner_process_id > 0 && self.owner_process_id != self.process_id {
+            format!(" (in {})", self.owner_process_name)
+        } else {
+            String::new()
+        };
+
         format!(
-            "{} [{:3}] {}{}{}",
+            "{} [{:3}] {}{}{}{}",
             self.timestamp.format("%H:%M:%S"),
             event,
             name,
             title,
+            owner,
             parent
         )
     }
+
+    /// Formats the entry as JSON object (network integrations)
+    pub fn format_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "timestamp": self.timestamp.to_rfc3339(),
+            "uptime_ms": self.uptime_ms,
+            "event_type": self.event_type,
+            "severity": self.severity.as_str(),
+            "process_name": self.process_name,
+            "process_id": self.process_id,
+            "process_path": self.process_path,
+            "window_title": self.window_title,
+            "window_class": self.window_class,
+            "command_line": self.command_line,
+            "browser_url": self.browser_url,
+            "cursor_pos": self.cursor_pos,
+            "note": self.note,
+            "incident_id": self.incident_id,
+            "tags": self.tags,
+            "parent_process_name": self.parent_process_name,
+            "parent_process_id": self.parent_process_id,
+            "parent_process_path": self.parent_process_path,
+            "grandparent_process_name": self.grandparent_process_name,
+            "grandparent_process_id": self.grandparent_process_id,
+            "grandparent_process_path": self.grandparent_process_path,
+            "greatgrandparent_process_name": self.greatgrandparent_process_name,
+            "greatgrandparent_process_id": self.greatgrandparent_process_id,
+            "greatgrandparent_process_path": self.greatgrandparent_process_path,
+            "owner_process_name": self.owner_process_name,
+            "owner_process_id": self.owner_process_id,
+            "owner_window_title": self.owner_window_title,
+            "host_process_name": self.host_process_name,
+            "host_process_id": self.host_process_id,
+            "parent_spoofed": self.parent_spoofed,
+        })
+    }
+
+    /// Reads an entry written by format_json (missing fields stay empty)
+    pub fn from_json(json: &serde_json::Value) -> Option<LogEntry> {
+        let text = |key: &str| json[key].as_str().unwrap_or_default().to_string();
+        let number = |key: &str| json[key].as_u64().unwrap_or(0);
+        let timestamp = DateTime::parse_from_rfc3339(json["timestamp"].as_str()?).ok()?.with_timezone(&Local);
+
+        Some(LogEntry {
+            timestamp,
+            uptime_ms: number("uptime_ms"),
+            event_type: text("event_type"),
+            severity: Severity::parse(json["severity"].as_str().unwrap_or_default()),
+            process_name: text("process_name"),
+            process_id: number("process_id") as u32,
+            process_path: text("process_path"),
+            window_title: text("window_title"),
+            window_class: text("window_class"),
+            command_line: json["command_line"].as_str().map(str::to_string),
+            browser_url: json["browser_url"].as_str().map(str::to_string),
+            cursor_pos: serde_json::from_value(json["cursor_pos"].clone()).ok().flatten(),
+            note: json["note"].as_str().map(str::to_string),
+            incident_id: json["incident_id"].as_i64(),
+            tags: serde_json::from_value(json["tags"].clone()).unwrap_or_default(),
+            parent_process_name: text("parent_process_name"),
+            parent_process_id: number("parent_process_id") as u32,
+            parent_process_path: text("parent_process_path"),
+            grandparent_process_name: text("grandparent_process_name"),
+            grandparent_process_id: number("grandparent_process_id") as u32,
+            grandparent_process_path: text("grandparent_process_path"),
+            greatgrandparent_process_name: text("greatgrandparent_process_name"),
+            greatgrandparent_process_id: number("greatgrandparent_process_id") as u32,
+            greatgrandparent_process_path: text("greatgrandparent_process_path"),
+            parent_spoofed: json["parent_spoofed"].as_str().map(str::to_string),
+            owner_process_name: text("owner_process_name"),
+            owner_process_id: number("owner_process_id") as u32,
+            owner_window_title: text("owner_window_title"),
+            host_process_name: text("host_process_name"),
+            host_process_id: number("host_process_id") as u32,
+            span: None,
+        })
+    }
 }
 
 /// Deletes old log files with specific prefix, keeps only the newest N
@@ -273,7 +550,7 @@ fn cleanup_old_logs(log_dir: &PathBuf, keep_count: usize, prefix: &str) {
         // Delete old files (all except the newest keep_count)
         for old_file in log_files.iter().skip(keep_count) {
             if let Err(e) = fs::remove_file(old_file.path()) {
-                error!("Could not delete old log file: {}", e);
+                crate::error::report("Could not delete old log file", Error::io(&old_file.path(), &e));
             } else {
                 info!("Old log file deleted: {}", old_file.path().display());
             }
@@ -288,16 +565,17 @@ pub fn log_worker(receiver: Receiver<LogEntry>, console_output: bool) {
     // Create log directory
     let log_dir = get_log_dir();
     if let Err(e) = fs::create_dir_all(&log_dir) {
-        error!("Could not create log directory: {}", e);
+        crate::error::report("Could not create log directory", Error::io(&log_dir, &e));
         return;
     }
 
     // Clean up old event logs (keep only 2)
-    cleanup_old_logs(&log_dir, 2, "event_");
+    cleanup_old_logs(&log_dir, 2, &event_log_prefix());
 
     // Open log file
     let log_file_path = log_dir.join(format!(
-        "event_{}.log",
+        "{}{}.log",
+        event_log_prefix(),
         Local::now().format("%Y-%m-%d_%H-%M-%S")
     ));
 
@@ -308,7 +586,7 @@ pub fn log_worker(receiver: Receiver<LogEntry>, console_output: bool) {
     {
         Ok(f) => f,
         Err(e) => {
-            error!("Could not open log file: {}", e);
+            crate::error::report("Could not open log file", Error::io(&log_file_path, &e));
             return;
         }
     };
@@ -318,17 +596,18 @@ pub fn log_worker(receiver: Receiver<LogEntry>, console_output: bool) {
     // Write header
     let header = format!(
         "════════════════════════════════════════════════════════════════════════════════\n\
-         PC Watcher Log started: {}\n\
+         PC Watcher Log started: {} (uptime {})\n\
          Computer: {}\n\
          User: {}\n\
          ════════════════════════════════════════════════════════════════════════════════\n\n",
         Local::now().format("%Y-%m-%d %H:%M:%S"),
+        crate::clock::format_uptime(crate::clock::uptime_ms()),
         hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default(),
         std::env::var("USERNAME").unwrap_or_default()
     );
 
     if let Err(e) = writer.write_all(header.as_bytes()) {
-        error!("Error writing header: {}", e);
+        crate::error::report("Error writing header", Error::io(&log_file_path, &e));
     }
     let _ = writer.flush();
 
@@ -343,21 +622,57 @@ pub fn log_worker(receiver: Receiver<LogEntry>, console_output: bool) {
 
     info!("Log file: {}", log_file_path.display());
 
+    // Event database (for queries, maintained periodically)
+    crate::database::init();
+
     // Receive and write entries
     let mut entry_count = 0u64;
     let flush_interval = 10; // Flush every 10 entries
 
-    while let Ok(entry) = receiver.recv() {
+    loop {
+        // The global log sender keeps the channel open - end on shutdown once it is empty
+        let mut entry = match receiver.recv_timeout(std::time::Duration::from_millis(200)) {
+            Ok(entry) => entry,
+            Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
+                if crate::event_hook::is_shutting_down() {
+                    break;
+                }
+                continue;
+            }
+            Err(crossbeam_channel::RecvTimeoutError::Disconnected) => break,
+        };
+        // File, database, MQTT, forwarder, notifications and GUI (child of the event's span)
+        let pipeline = entry.span.take().unwrap_or_else(Span::none);
+        let _sinks = debug_span!(parent: &pipeline, "sinks", event = %entry.event_type).entered();
+
+        if entry.uptime_ms == 0 {
+            entry.uptime_ms = crate::clock::uptime_ms();
+        }
+        // Labels of the tag rules
+        crate::tags::apply(&mut entry);
+        // Related events share an incident ID
+        crate::incident::assign(&mut entry);
+
         // Write to file
         let formatted = entry.format_file();
         if let Err(e) = writer.write_all(formatted.as_bytes()) {
-            error!("Error writing: {}", e);
+            crate::error::report("Error writing log entry", Error::io(&log_file_path, &e));
+        } else {
+            crate::selftest::confirm(&entry, crate::selftest::Stage::Log);
+        }
+        let db_id = crate::database::insert_event(&entry);
+        crate::mqtt::publish_event(&entry);
+        crate::syslog::send(&entry);
+        // Central server reads it from the database
+        crate::forwarder::notify();
+        // Notification sinks: immediately or in the next digest
+        if !crate::digest::hold(&entry) {
+            crate::discord::send(&entry);
+            crate::push::send(&entry);
         }
 
         // Update GUI (compact line with event type for color and details for double-click)
-        let gui_line = entry.format_gui();
-        let details = entry.format_file(); // Full details for double-click
-        crate::alert_window::add_log_entry(gui_line, entry.event_type.clone(), details, entry.process_path.clone());
+        crate::alert_window::add_log_entry(&entry, db_id);
 
         // Console output
         if console_output {
@@ -378,8 +693,10 @@ pub fn log_worker(receiver: Receiver<LogEntry>, console_output: bool) {
         entry_count += 1;
 
         // Periodically flush
-        if entry_count % flush_interval == 0 {
-            let _ = writer.flush();
+        if entry_count.is_multiple_of(flush_interval) {
+            if let Err(e) = writer.flush() {
+                crate::error::report("Error flushing log file", Error::io(&log_file_path, &e));
+            }
         }
     }
 
@@ -0,0 +1,104 @@
+//! Long Paths
+//!
+//! Deep npm/temp folders exceed MAX_PATH (260 characters). Win32 file APIs only accept such
+//! paths with the extended-length prefix `\\?\`, and some APIs return paths with it. Paths are
+//! kept and logged without the prefix (so whitelists, hash lists and rules match either form);
+//! the prefix is only added when a long path is handed to a Win32 API.
+
+use std::path::Path;
+use windows::core::PCWSTR;
+use windows::Win32::Storage::FileSystem::GetLongPathNameW;
+
+/// Classic path limit (characters including the terminating null)
+pub const MAX_PATH_CHARS: usize = 260;
+
+/// Limit of extended-length paths
+pub const MAX_LONG_PATH_CHARS: usize = 32_767;
+
+/// From this length on the prefix is added (directories need room for an 8.3 file name)
+const PREFIX_FROM: usize = MAX_PATH_CHARS - 12;
+
+const VERBATIM: &str = r"\\?\";
+const VERBATIM_UNC: &str = r"\\?\UNC\";
+
+/// Path without the extended-length prefix (`\\?\C:\x` -> `C:\x`, `\\?\UNC\srv\x` -> `\\srv\x`)
+pub fn normalize(path: &str) -> String {
+    if let Some(rest) = path.strip_prefix(VERBATIM_UNC) {
+        format!(r"\\{}", rest)
+    } else if let Some(rest) = path.strip_prefix(VERBATIM) {
+        rest.to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Path in the form Win32 file APIs accept: absolute long paths get the prefix
+pub fn for_win32(path: &Path) -> String {
+    let path = normalize(&path.to_string_lossy()).replace('/', r"\");
+    let absolute = path.starts_with(r"\\") || path.get(1..3) == Some(r":\");
+    if path.chars().count() < PREFIX_FROM || !absolute {
+        return path;
+    }
+    match path.strip_prefix(r"\\") {
+        Some(unc) => format!("{}{}", VERBATIM_UNC, unc),
+        None => format!("{}{}", VERBATIM, path),
+    }
+}
+
+/// UTF-16 with null terminator of `for_win32`
+pub fn to_wide(path: &Path) -> Vec<u16> {
+    for_win32(path).encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Long form of a path with 8.3 short names (`C:\Users\FABIAN~1` -> `C:\Users\Fabian`);
+/// unchanged if it has none or does not exist
+pub fn expand_short_names(path: &str) -> String {
+    // Generated short names always contain a tilde, other paths skip the file system
+    if !path.contains('~') {
+        return path.to_string();
+    }
+    let wide = to_wide(Path::new(path));
+    let mut buffer = vec![0u16; MAX_PATH_CHARS];
+    loop {
+        let len = unsafe { GetLongPathNameW(PCWSTR(wide.as_ptr()), Some(&mut buffer)) } as usize;
+        if len == 0 {
+            return path.to_string();
+        }
+        if len < buffer.len() {
+            return normalize(&String::from_utf16_lossy(&buffer[..len]));
+        }
+        // Too small: len is the needed size including the null
+        if len > MAX_LONG_PATH_CHARS {
+            return path.to_string();
+        }
+        buffer.resize(len, 0);
+    }
+}
+
+/// Shortens a path in the middle for display: "C:\Users\...\node_modules\pkg\cli.js"
+/// (the root and as many trailing folders as fit are kept)
+pub fn shorten(path: &str, max_chars: usize) -> String {
+    let path = normalize(path);
+    let chars: Vec<char> = path.chars().collect();
+    if chars.len() <= max_chars {
+        return path;
+    }
+
+    // Root: drive ("C:\") or server and share ("\\srv\share\")
+    let separators: Vec<usize> = chars.iter().enumerate().filter(|(_, c)| **c == '\\').map(|(i, _)| i).collect();
+    let root_end = if path.starts_with(r"\\") { separators.get(3) } else { separators.first() }
+        .map(|&i| i + 1)
+        .unwrap_or(0);
+    let root: String = chars[..root_end].iter().collect();
+
+    let room = max_chars.saturating_sub(root.chars().count() + 3);
+    if room < 8 {
+        let tail: String = chars[chars.len() - max_chars.saturating_sub(3)..].iter().collect();
+        return format!("...{}", tail);
+    }
+    // Start the tail at a folder boundary if one is in reach
+    let tail_start = chars.len() - room;
+    let tail_start = separators.iter().find(|&&i| i >= tail_start).copied().unwrap_or(tail_start);
+    let tail: String = chars[tail_start..].iter().collect();
+    format!("{}...{}", root, tail)
+}
@@ -6,15 +6,98 @@
 // Only show console in console mode
 #![windows_subsystem = "windows"]
 
+mod accessibility;
+mod alert_export;
 mod alert_window;
+mod api_tokens;
+mod audio;
+mod autoruns;
+mod badge;
+mod bench;
+mod browser;
+mod capability;
+mod clickthrough;
+mod clock;
+mod config;
+mod content_watch;
+mod dashboard;
+mod database;
+mod detection;
+mod diag;
+mod diagnostics_window;
+mod digest;
+mod discord;
+mod dock;
+mod downloads;
+mod error;
+mod etw;
+mod file_watch;
+mod focus_guard;
+mod forwarder;
+mod gaps;
+mod grpc;
 mod event_hook;
+mod hash_lists;
+mod honeypot;
+mod honeytoken;
+mod host_process;
+mod http;
+mod incident;
+mod incident_window;
+mod input_stats;
+mod instance;
+mod log_ring;
 mod logger;
+mod long_path;
+mod masquerade;
+mod memory;
+mod metrics;
+mod module_scan;
+mod mqtt;
+mod network;
+mod note_dialog;
 mod notification;
+mod open_with;
+mod overlay;
+mod path_heuristics;
+mod power;
+mod powershell;
+mod process_exit;
 mod process_info;
+mod push;
+mod registry_watch;
+mod replay;
+mod reputation;
+mod reveal;
+mod rules;
 mod screenshot;
+mod selftest;
+mod server;
+mod services;
+mod sigma;
+mod signature;
+mod snooze;
+mod sound;
+mod storage;
+mod syslog;
+mod tags;
+mod tasks;
+mod timeline;
+mod tls;
+mod topmost;
+mod transparency;
 mod tray;
+mod virustotal;
+mod watch;
+mod web_ui;
+mod webcam;
+mod wmi_process;
+mod yara_scan;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use clap::{Parser, Subcommand};
 use tracing::info;
 use windows::Win32::System::Console::{AllocConsole, AttachConsole, ATTACH_PARENT_PROCESS};
@@ -26,6 +109,9 @@ use windows::Win32::System::Console::{AllocConsole, AttachConsole, ATTACH_PARENT
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Machine-readable output: one JSON object on stdout (for scripts)
+    #[arg(long, global = true)]
+    json: bool,
 }
 
 #[derive(Subcommand)]
@@ -36,12 +122,145 @@ enum Commands {
     Install,
     /// Remove Task Scheduler autostart
     Uninstall,
+    /// Configuration export/import (share between machines)
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Create a diagnostics ZIP (for GitHub issues)
+    Diag,
+    /// Export an alert (event record + captures) as ZIP
+    ExportAlert {
+        /// Database ID of the event (default: latest alert)
+        #[arg(long)]
+        id: Option<i64>,
+    },
+    /// Event database maintenance
+    Db {
+        #[command(subcommand)]
+        command: DbCommands,
+    },
+    /// Central server: collects the events of other watchers
+    Server,
+    /// List the newest events from the event database
+    Events {
+        /// Number of events
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+        /// Only Warning/Critical events
+        #[arg(long)]
+        alerts: bool,
+        /// Only events with this tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
+    /// Benchmark of the event pipeline (synthetic events at increasing rates)
+    Bench {
+        /// Events per rate step
+        #[arg(long, default_value_t = 2000)]
+        events: usize,
+        /// Target rates in events per second
+        #[arg(long, value_delimiter = ',', default_values_t = [250, 1000, 5000, 20000])]
+        rates: Vec<u32>,
+    },
+    /// Run a recorded process table through the detections (no system access)
+    Replay { file: PathBuf },
+    /// Generate helper files
+    Generate {
+        #[command(subcommand)]
+        command: GenerateCommands,
+    },
+    /// Sigma detection rules
+    Sigma {
+        #[command(subcommand)]
+        command: SigmaCommands,
+    },
+    /// API tokens for the web dashboards (viewer or admin role)
+    Token {
+        #[command(subcommand)]
+        command: TokenCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum TokenCommands {
+    /// Create a token (printed once, only its hash is stored)
+    Create {
+        name: String,
+        /// viewer: read events, admin: also pause alerts, kill processes, reload the config
+        #[arg(long, value_enum, default_value_t = api_tokens::Role::Viewer)]
+        role: api_tokens::Role,
+    },
+    /// List the tokens (names and roles)
+    List,
+    /// Delete a token
+    Remove { name: String },
+}
+
+#[derive(Subcommand)]
+enum SigmaCommands {
+    /// Convert process_creation rules of a .yml file or folder into detections
+    Import { path: PathBuf },
+}
+
+#[derive(Subcommand)]
+enum GenerateCommands {
+    /// PowerShell module wrapping the CLI (Get-PCWatcherEvents, ...)
+    PowershellModule {
+        /// Target file (default: PCWatcher.psm1 in the current folder)
+        file: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Write the current configuration to a file
+    Export { file: PathBuf },
+    /// Load the configuration from a file
+    Import { file: PathBuf },
+}
+
+#[derive(Subcommand)]
+enum DbCommands {
+    /// Delete events older than the retention, vacuum and reindex
+    Compact,
+}
+
+// --json: CLI commands print one JSON object instead of text
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Line of the human-readable output (suppressed with --json)
+fn say(line: impl std::fmt::Display) {
+    if !JSON_OUTPUT.load(Ordering::Relaxed) {
+        println!("{}", line);
+    }
+}
+
+/// Result object of a command (only printed with --json)
+fn emit(command: &str, mut result: serde_json::Value) {
+    if JSON_OUTPUT.load(Ordering::Relaxed) {
+        result["command"] = json!(command);
+        result["ok"] = json!(true);
+        println!("{}", result);
+    }
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    JSON_OUTPUT.store(cli.json, Ordering::Relaxed);
+
+    let result = run_command(cli.command);
+    if let Err(e) = &result {
+        if cli.json {
+            println!("{}", json!({ "ok": false, "error": e.to_string() }));
+            std::process::exit(1);
+        }
+    }
+    result
+}
 
-    match cli.command {
+fn run_command(command: Option<Commands>) -> Result<()> {
+    match command {
         Some(Commands::Console) => {
             // Create own console (don't attach to parent)
             // User can close console with X button
@@ -49,6 +268,11 @@ fn main() -> Result<()> {
                 let _ = AllocConsole();
             }
 
+            // One instance per user and session (decides the log files)
+            if !instance::start() {
+                return Ok(());
+            }
+
             // Initialize console logger
             logger::init_console_logger()?;
             info!("PC Watcher started in console mode");
@@ -62,8 +286,44 @@ fn main() -> Result<()> {
         Some(Commands::Uninstall) => {
             uninstall_autostart()?;
         }
+        Some(Commands::Config { command }) => {
+            run_config_command(command)?;
+        }
+        Some(Commands::Diag) => {
+            create_diagnostics()?;
+        }
+        Some(Commands::ExportAlert { id }) => {
+            export_alert(id)?;
+        }
+        Some(Commands::Db { command: DbCommands::Compact }) => {
+            compact_database()?;
+        }
+        Some(Commands::Server) => {
+            run_server()?;
+        }
+        Some(Commands::Events { limit, alerts, tag }) => {
+            list_events(limit, alerts, tag.as_deref())?;
+        }
+        Some(Commands::Bench { events, rates }) => {
+            run_benchmark(events, &rates)?;
+        }
+        Some(Commands::Replay { file }) => {
+            run_replay(&file)?;
+        }
+        Some(Commands::Generate { command: GenerateCommands::PowershellModule { file } }) => {
+            generate_powershell_module(file)?;
+        }
+        Some(Commands::Sigma { command: SigmaCommands::Import { path } }) => {
+            import_sigma_rules(path)?;
+        }
+        Some(Commands::Token { command }) => {
+            run_token_command(command)?;
+        }
         None => {
             // Normal start (without console) - for autostart
+            if !instance::start() {
+                return Ok(());
+            }
             logger::init_file_logger()?;
             info!("PC Watcher started");
 
@@ -85,14 +345,103 @@ fn run_app() -> Result<()> {
     // Start alert window
     alert_window::start_alert_window();
 
+    // Live status of watched processes
+    watch::start_monitor();
+
+    // Size limit of the log folder + low disk warning
+    storage::start_monitor();
+
+    // Prune/vacuum the event database periodically
+    database::start_maintenance();
+
+    // Decoy window (optional)
+    honeypot::start_honeypot();
+
+    // Decoy files (optional)
+    honeytoken::start_watcher();
+
+    // SHA-256 allow/deny lists and their feeds
+    hash_lists::start_monitor();
+
+    // Watch list of files and folders (best-effort who touched it)
+    file_watch::start_watcher();
+
+    // New executables and scripts in the Downloads folder
+    downloads::start_watcher();
+
+    // Heartbeat for monitoring gap detection
+    gaps::start_heartbeat();
+
+    // Typing/mouse rate per minute (no key values)
+    input_stats::start_monitor();
+
+    // Which app uses microphone/webcam
+    capability::start_monitor();
+
+    // Background windows whose content keeps changing (opt-in)
+    content_watch::start_monitor();
+
+    // System clock changes (someone hiding when the PC was used)
+    clock::start_monitor();
+
+    // Network connects/disconnects (new hotspots)
+    network::start_monitor();
+
+    // Invisible click-through overlays over large screen areas
+    clickthrough::start_monitor();
+
+    // New services and loaded drivers (persistence, kernel-level spyware)
+    services::start_monitor();
+
+    // New scheduled tasks (persistence)
+    tasks::start_monitor();
+
+    // New Run keys, Startup folder files and WMI subscriptions (persistence)
+    autoruns::start_monitor();
+
+    // Watch list of sensitive registry keys
+    registry_watch::start_watcher();
+
+    // Queued alerts after full-screen apps / do not disturb
+    snooze::start_monitor();
+
+    // One-line alerts over full-screen apps (opt-in)
+    overlay::start_overlay();
+
+    // MQTT publishing for home automation (opt-in)
+    mqtt::start();
+
+    // Summaries of batched Warning alerts (opt-in)
+    digest::start_monitor();
+
+    // Events to the central server, backfilled after offline periods (opt-in)
+    forwarder::start();
+
+    // Events to a syslog server / SIEM over TLS (opt-in)
+    syslog::start();
+
+    // Local web dashboard (opt-in)
+    web_ui::start();
+
+    // gRPC control API for remote management (opt-in)
+    grpc::start();
+
+    // Synthetic event through the pipeline, alert if it does not arrive
+    selftest::start_monitor();
+
+    // Hourly memory readout in the app log
+    memory::start_monitor();
+
     // Start info
     notification::show_start_notification();
 
     // Start event loop (blocks until CTRL+C or tray exit)
     event_hook::run_with_tray_check()?;
+    gaps::mark_clean_stop();
 
     // Cleanup
     tray::stop_tray();
+    honeypot::stop_honeypot();
     alert_window::close_alert_window();
     notification::show_stop_notification();
 
@@ -100,6 +449,19 @@ fn run_app() -> Result<()> {
     Ok(())
 }
 
+/// Central server mode (blocks until CTRL+C)
+fn run_server() -> Result<()> {
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS).is_err() {
+            let _ = AllocConsole();
+        }
+    }
+
+    logger::init_console_logger()?;
+    info!("PC Watcher server started");
+    server::run().map_err(|e| anyhow!(e))
+}
+
 /// Sets up autostart via Task Scheduler
 fn install_autostart() -> Result<()> {
     // Console for output
@@ -112,7 +474,7 @@ fn install_autostart() -> Result<()> {
     let exe_path = std::env::current_exe()?;
     let exe_str = exe_path.to_string_lossy();
 
-    println!("Setting up autostart...");
+    say("Setting up autostart...");
 
     // Create task with schtasks
     let output = std::process::Command::new("schtasks")
@@ -127,20 +489,25 @@ fn install_autostart() -> Result<()> {
         .output()?;
 
     if output.status.success() {
-        println!("Autostart configured!");
-        println!("PC Watcher will start automatically at logon.");
-        println!();
-        println!("Starting PC Watcher now...");
+        say("Autostart configured!");
+        say("PC Watcher will start automatically at logon.");
+        say("");
+        say("Starting PC Watcher now...");
 
         // Start program directly (no arguments = normal mode)
-        let _ = std::process::Command::new(&exe_path)
-            .spawn();
+        let started = std::process::Command::new(&exe_path)
+            .spawn()
+            .is_ok();
 
-        println!("PC Watcher is running! (Check tray icon)");
-        println!();
-        println!("To remove: pc_watcher uninstall");
+        say("PC Watcher is running! (Check tray icon)");
+        say("");
+        say("To remove: pc_watcher uninstall");
+        emit("install", json!({ "task": "PCWatcher", "started": started }));
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
+        if JSON_OUTPUT.load(Ordering::Relaxed) {
+            return Err(anyhow!("schtasks failed: {} (run as administrator)", stderr.trim()));
+        }
         println!("Error setting up: {}", stderr);
         println!();
         println!("Tip: Run as administrator!");
@@ -149,6 +516,285 @@ fn install_autostart() -> Result<()> {
     Ok(())
 }
 
+/// Exports/imports the configuration (pc_watcher config export|import <file>)
+fn run_config_command(command: ConfigCommands) -> Result<()> {
+    // Console for output
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS).is_err() {
+            let _ = AllocConsole();
+        }
+    }
+
+    match command {
+        ConfigCommands::Export { file } => {
+            config::export_to(&file).map_err(|e| anyhow!(e))?;
+            say(format!("Configuration exported to: {}", file.display()));
+            emit("config export", json!({ "file": file }));
+        }
+        ConfigCommands::Import { file } => {
+            config::import_from(&file).map_err(|e| anyhow!(e))?;
+            say(format!("Configuration imported from: {}", file.display()));
+            say("Restart PC Watcher to apply it.");
+            emit("config import", json!({ "file": file, "restart_required": true }));
+        }
+    }
+
+    Ok(())
+}
+
+/// Imports Sigma rules into the detections (pc_watcher sigma import)
+fn import_sigma_rules(path: PathBuf) -> Result<()> {
+    // Console for output
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS).is_err() {
+            let _ = AllocConsole();
+        }
+    }
+
+    let summary = sigma::import(&path).map_err(|e| anyhow!(e))?;
+    for title in &summary.imported {
+        say(format!("Imported: {}", title));
+    }
+    for (name, reason) in &summary.skipped {
+        say(format!("Skipped:  {} ({})", name, reason));
+    }
+    say(format!("{} rule(s) imported, {} skipped", summary.imported.len(), summary.skipped.len()));
+    if !summary.imported.is_empty() {
+        say("Restart PC Watcher to apply them.");
+    }
+    let skipped: Vec<_> = summary.skipped.iter().map(|(name, reason)| json!({ "rule": name, "reason": reason })).collect();
+    emit("sigma import", json!({ "imported": summary.imported, "skipped": skipped, "restart_required": !summary.imported.is_empty() }));
+
+    Ok(())
+}
+
+/// Creates the diagnostics bundle (pc_watcher diag)
+fn create_diagnostics() -> Result<()> {
+    // Console for output
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS).is_err() {
+            let _ = AllocConsole();
+        }
+    }
+
+    say("Creating diagnostics bundle...");
+    let path = diag::create_bundle()?;
+    say(format!("Diagnostics bundle created: {}", path.display()));
+    say("Please check the content before attaching it to an issue.");
+    emit("diag", json!({ "file": path }));
+
+    Ok(())
+}
+
+/// Exports an alert from the event database (pc_watcher export-alert [--id N])
+fn export_alert(id: Option<i64>) -> Result<()> {
+    // Console for output
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS).is_err() {
+            let _ = AllocConsole();
+        }
+    }
+
+    let conn = database::open()?;
+    let entry = database::read_alert(&conn, id)?;
+    say(format!("Exporting {} of {} at {}", entry.event_type, entry.process_name, entry.timestamp.format("%Y-%m-%d %H:%M:%S")));
+
+    let path = alert_export::export_record(&entry.format_file())?;
+    say(format!("Alert exported: {}", path.display()));
+    emit("export-alert", json!({ "file": path, "event": entry.format_json() }));
+
+    Ok(())
+}
+
+/// Compacts the event database (pc_watcher db compact)
+fn compact_database() -> Result<()> {
+    // Console for output
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS).is_err() {
+            let _ = AllocConsole();
+        }
+    }
+
+    let retention_days = config::get().database.retention_days;
+    say(format!("Compacting event database: {}", database::get_db_path().display()));
+    if retention_days > 0 {
+        say(format!("Retention: {} days", retention_days));
+    }
+
+    let conn = database::open()?;
+    let stats = database::maintain(&conn, retention_days)?;
+
+    say(format!("Deleted events: {}", stats.deleted_rows));
+    say(format!("Size: {} KB -> {} KB", stats.size_before / 1024, stats.size_after / 1024));
+    emit(
+        "db compact",
+        json!({
+            "database": database::get_db_path(),
+            "retention_days": retention_days,
+            "deleted_rows": stats.deleted_rows,
+            "size_before": stats.size_before,
+            "size_after": stats.size_after,
+        }),
+    );
+
+    Ok(())
+}
+
+/// Lists the newest events (pc_watcher events [--limit N] [--alerts])
+fn list_events(limit: usize, alerts_only: bool, tag: Option<&str>) -> Result<()> {
+    // Console for output
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS).is_err() {
+            let _ = AllocConsole();
+        }
+    }
+
+    let conn = database::open()?;
+    let mut events = database::read_latest_events(&conn, alerts_only, tag, limit)?;
+    // Oldest first, like the log
+    events.reverse();
+
+    for (id, entry) in &events {
+        say(format!("#{} {}", id, entry.format_console()));
+    }
+    let events: Vec<serde_json::Value> = events
+        .iter()
+        .map(|(id, entry)| {
+            let mut event = entry.format_json();
+            event["id"] = json!(id);
+            event
+        })
+        .collect();
+    emit("events", json!({ "count": events.len(), "events": events }));
+
+    Ok(())
+}
+
+/// Pipeline benchmark (pc_watcher bench [--events N] [--rates a,b,c])
+fn run_benchmark(events: usize, rates: &[u32]) -> Result<()> {
+    // Console for output
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS).is_err() {
+            let _ = AllocConsole();
+        }
+    }
+
+    say(format!("Benchmarking the event pipeline with {} events per step...", events));
+    say("");
+    say(format!(
+        "{:>8} {:>10} {:>9} {:>9} {:>9} {:>9} {:>9} {:>10}",
+        "rate/s", "logged/s", "p50 ms", "p99 ms", "max ms", "write us", "drops W", "drops L"
+    ));
+    let results = bench::run(events, rates, |step| {
+        say(format!(
+            "{:>8} {:>10.0} {:>9.2} {:>9.2} {:>9.2} {:>9.1} {:>9} {:>10}",
+            step.rate,
+            step.throughput,
+            step.latency_p50_ms,
+            step.latency_p99_ms,
+            step.latency_max_ms,
+            step.write_avg_us,
+            step.worker_drops,
+            step.logger_drops
+        ));
+    })
+    .map_err(|e| anyhow!(e))?;
+    say("");
+    say("drops W = event queue full (worker), drops L = log queue full (logger)");
+    emit("bench", json!({ "events": events, "steps": results }));
+
+    Ok(())
+}
+
+/// Replays a process table through the detections (pc_watcher replay <file>)
+fn run_replay(file: &Path) -> Result<()> {
+    // Console for output
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS).is_err() {
+            let _ = AllocConsole();
+        }
+    }
+
+    let results = replay::run(file).map_err(|e| anyhow!(e))?;
+    for result in &results {
+        let parents = result.parents.join(" < ");
+        let mut line = format!("{:>6} {:<20} parents: {}", result.process_id, result.process_name, parents);
+        if let Some(reason) = &result.parent_spoofed {
+            line.push_str(&format!("  SPOOFED? {}", reason));
+        }
+        if let (Some(title), Some(severity)) = (&result.detection, result.severity) {
+            line.push_str(&format!("  -> {} ({})", title, severity));
+        }
+        say(line);
+    }
+    let detections = results.iter().filter(|r| r.detection.is_some()).count();
+    say(format!("{} process(es) replayed, {} detection(s)", results.len(), detections));
+    emit("replay", json!({ "processes": results }));
+
+    Ok(())
+}
+
+/// Manages API tokens (pc_watcher token create|list|remove)
+fn run_token_command(command: TokenCommands) -> Result<()> {
+    // Console for output
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS).is_err() {
+            let _ = AllocConsole();
+        }
+    }
+
+    match command {
+        TokenCommands::Create { name, role } => {
+            let token = api_tokens::create(&name, role).map_err(|e| anyhow!(e))?;
+            say(format!("Token \"{}\" ({}) created:", name.trim(), role.as_str()));
+            say(&token);
+            say("Use it as password (any user name). It is not shown again.");
+            emit("token create", json!({ "name": name.trim(), "role": role, "token": token }));
+        }
+        TokenCommands::List => {
+            let tokens = api_tokens::list();
+            if tokens.is_empty() {
+                say("No API tokens");
+            }
+            for token in &tokens {
+                say(format!("{:<24} {:<8} created {}", token.name, token.role.as_str(), token.created));
+            }
+            let tokens: Vec<serde_json::Value> = tokens
+                .iter()
+                .map(|t| json!({ "name": t.name, "role": t.role, "created": t.created }))
+                .collect();
+            emit("token list", json!({ "count": tokens.len(), "tokens": tokens }));
+        }
+        TokenCommands::Remove { name } => {
+            api_tokens::remove(&name).map_err(|e| anyhow!(e))?;
+            say(format!("Token \"{}\" removed", name.trim()));
+            emit("token remove", json!({ "name": name.trim() }));
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the PowerShell module (pc_watcher generate powershell-module [file])
+fn generate_powershell_module(file: Option<PathBuf>) -> Result<()> {
+    // Console for output
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS).is_err() {
+            let _ = AllocConsole();
+        }
+    }
+
+    let file = file.unwrap_or_else(|| PathBuf::from(powershell::MODULE_FILE));
+    let exe_path = std::env::current_exe()?;
+    std::fs::write(&file, powershell::module_source(&exe_path))?;
+
+    say(format!("PowerShell module written: {}", file.display()));
+    say(format!("Load it with: Import-Module {}", file.display()));
+    emit("generate powershell-module", json!({ "file": file }));
+
+    Ok(())
+}
+
 /// Removes autostart
 fn uninstall_autostart() -> Result<()> {
     // Console for output
@@ -158,18 +804,22 @@ fn uninstall_autostart() -> Result<()> {
         }
     }
 
-    println!("Removing autostart...");
+    say("Removing autostart...");
 
     let output = std::process::Command::new("schtasks")
         .args(["/Delete", "/TN", "PCWatcher", "/F"])
         .output()?;
 
     if output.status.success() {
-        println!("Autostart removed!");
+        say("Autostart removed!");
+        emit("uninstall", json!({ "removed": true }));
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
         if stderr.contains("existiert nicht") || stderr.contains("does not exist") {
-            println!("No autostart task found.");
+            say("No autostart task found.");
+            emit("uninstall", json!({ "removed": false }));
+        } else if JSON_OUTPUT.load(Ordering::Relaxed) {
+            return Err(anyhow!("schtasks failed: {}", stderr.trim()));
         } else {
             println!("Error: {}", stderr);
         }
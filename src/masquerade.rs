@@ -0,0 +1,204 @@
+//! Masquerading Detection
+//!
+//! Malware likes to hide behind the names of Windows binaries ("svchost.exe" in AppData) or
+//! names that look like them ("scvhost.exe", "expl0rer.exe"). Checked once per process on its
+//! first window: a system name outside its Windows folder and a system binary without a valid
+//! signature are Critical. Look-alike names outside the Windows folder are Critical with digits
+//! in place of letters or without a valid signature; other one-typo names ("service.exe") only
+//! warn, they are often legitimate.
+
+use crate::notification::Severity;
+use crate::path_heuristics::Finding;
+use std::path::Path;
+
+/// Windows binaries and the folders under %SystemRoot% they belong in ("" = %SystemRoot% itself)
+const SYSTEM_BINARIES: &[(&str, &[&str])] = &[
+    ("svchost.exe", &["System32", "SysWOW64"]),
+    ("explorer.exe", &["", "SysWOW64"]),
+    ("winlogon.exe", &["System32"]),
+    ("csrss.exe", &["System32"]),
+    ("lsass.exe", &["System32"]),
+    ("lsaiso.exe", &["System32"]),
+    ("services.exe", &["System32"]),
+    ("smss.exe", &["System32"]),
+    ("wininit.exe", &["System32"]),
+    ("taskhostw.exe", &["System32"]),
+    ("spoolsv.exe", &["System32"]),
+    ("conhost.exe", &["System32"]),
+    ("dwm.exe", &["System32"]),
+    ("ctfmon.exe", &["System32", "SysWOW64"]),
+    ("runtimebroker.exe", &["System32"]),
+    ("sihost.exe", &["System32"]),
+    ("fontdrvhost.exe", &["System32"]),
+    ("dllhost.exe", &["System32", "SysWOW64"]),
+    ("rundll32.exe", &["System32", "SysWOW64"]),
+    ("regsvr32.exe", &["System32", "SysWOW64"]),
+    ("taskmgr.exe", &["System32", "SysWOW64"]),
+    ("searchindexer.exe", &["System32"]),
+    ("wmiprvse.exe", &["System32\\wbem", "SysWOW64\\wbem"]),
+    ("cmd.exe", &["System32", "SysWOW64"]),
+    ("powershell.exe", &["System32\\WindowsPowerShell\\v1.0", "SysWOW64\\WindowsPowerShell\\v1.0"]),
+];
+
+/// Look-alikes are only checked for names of at least this length (short names collide too easily)
+const MIN_LOOKALIKE_STEM: usize = 5;
+
+/// Windows folder (without trailing backslash)
+fn system_root() -> String {
+    std::env::var("SystemRoot")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "C:\\Windows".to_string())
+        .trim_end_matches('\\')
+        .to_string()
+}
+
+/// Full expected paths of a system binary
+fn expected_paths(root: &str, name: &str, folders: &[&str]) -> Vec<String> {
+    folders
+        .iter()
+        .map(|folder| {
+            if folder.is_empty() {
+                format!("{}\\{}", root, name)
+            } else {
+                format!("{}\\{}\\{}", root, folder, name)
+            }
+        })
+        .collect()
+}
+
+/// Digits commonly used in place of letters ("expl0rer", "svch0st")
+fn normalize_digits(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '0' => 'o',
+            '1' => 'l',
+            '3' => 'e',
+            '5' => 's',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Edit distance of at most one (substitution, insertion, deletion or swap of neighbours)
+fn within_one_edit(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (short, long) = if a.len() <= b.len() { (&a, &b) } else { (&b, &a) };
+    if long.len() - short.len() > 1 {
+        return false;
+    }
+    let prefix = short.iter().zip(long.iter()).take_while(|(x, y)| x == y).count();
+    if short.len() == long.len() {
+        let differences = short.iter().zip(long.iter()).filter(|(x, y)| x != y).count();
+        // One substitution or one swap of neighbouring characters ("scvhost")
+        differences <= 1
+            || (differences == 2
+                && prefix + 1 < short.len()
+                && short[prefix] == long[prefix + 1]
+                && short[prefix + 1] == long[prefix]
+                && short[prefix + 2..] == long[prefix + 2..])
+    } else {
+        // One insertion: the rest must line up after skipping it
+        short[prefix..] == long[prefix + 1..]
+    }
+}
+
+/// System binary a file name imitates and whether only digits replace letters ("expl0rer")
+/// (None = not similar to any)
+fn lookalike_of(file_name: &str) -> Option<(&'static (&'static str, &'static [&'static str]), bool)> {
+    let (stem, extension) = file_name.rsplit_once('.')?;
+    let normalized = normalize_digits(stem);
+    SYSTEM_BINARIES.iter().find_map(|entry| {
+        let (system_stem, system_extension) = entry.0.rsplit_once('.').unwrap_or((entry.0, ""));
+        let similar = system_stem.len() >= MIN_LOOKALIKE_STEM
+            && extension == system_extension
+            && stem != system_stem
+            && (normalized == system_stem || within_one_edit(&normalized, system_stem));
+        similar.then_some((entry, normalized == system_stem))
+    })
+}
+
+/// Why the executable looks like a disguised system binary, with severity (None = nothing found)
+pub fn check(process_path: &str) -> Option<Finding> {
+    let config = crate::config::get().masquerade;
+    if !config.enabled || !Path::new(process_path).is_absolute() {
+        return None;
+    }
+    let file_name = Path::new(process_path).file_name()?.to_string_lossy().to_lowercase();
+    let path = process_path.to_lowercase();
+    let root = system_root();
+
+    if let Some((name, folders)) = SYSTEM_BINARIES.iter().find(|(name, _)| *name == file_name) {
+        let expected = expected_paths(&root, name, folders);
+        if !expected.iter().any(|p| p.to_lowercase() == path) {
+            let description = format!(
+                "system name outside its folder: expected {}, runs from {}",
+                expected.join(" or "),
+                process_path
+            );
+            return Some(Finding { description, severity: Severity::Critical });
+        }
+        if config.check_signature && !crate::signature::is_signed(process_path) {
+            let description = format!("system binary without valid signature: {}", process_path);
+            return Some(Finding { description, severity: Severity::Critical });
+        }
+        return None;
+    }
+
+    if !config.lookalikes || path.starts_with(&format!("{}\\", root.to_lowercase())) {
+        return None;
+    }
+    let ((name, folders), digits_only) = lookalike_of(&file_name)?;
+    let expected = expected_paths(&root, name, folders).join(" or ");
+    let mut description = format!("look-alike of {}: expected {}, runs from {}", name, expected, process_path);
+    // A typo alone is weak evidence, a missing signature makes it strong
+    let unsigned = !digits_only && config.check_signature && !crate::signature::is_signed(process_path);
+    if unsigned {
+        description.push_str(", not signed");
+    }
+    let severity = if digits_only || unsigned { Severity::Critical } else { Severity::Warning };
+    Some(Finding { description, severity })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_edit_covers_substitution_insertion_deletion_and_swap() {
+        assert!(within_one_edit("svchost", "svchost"));
+        assert!(within_one_edit("svchxst", "svchost"));
+        assert!(within_one_edit("svchosts", "svchost"));
+        assert!(within_one_edit("svhost", "svchost"));
+        assert!(within_one_edit("scvhost", "svchost"));
+        assert!(within_one_edit("explörer", "explorer"));
+        assert!(within_one_edit("", "a"));
+    }
+
+    #[test]
+    fn more_than_one_edit_is_rejected() {
+        assert!(!within_one_edit("svhcots", "svchost"));
+        assert!(!within_one_edit("scvhots", "svchost"));
+        assert!(!within_one_edit("svcxhosx", "svchost"));
+        assert!(!within_one_edit("bbcc", "abcd"));
+        assert!(!within_one_edit("svc", "svchost"));
+        assert!(!within_one_edit("", "ab"));
+    }
+
+    #[test]
+    fn lookalikes_of_system_binaries() {
+        let imitated = |file_name: &str| lookalike_of(file_name).map(|((name, _), digits_only)| (*name, digits_only));
+        assert_eq!(imitated("scvhost.exe"), Some(("svchost.exe", false)));
+        assert_eq!(imitated("expl0rer.exe"), Some(("explorer.exe", true)));
+        assert_eq!(imitated("svch0st.exe"), Some(("svchost.exe", true)));
+        assert_eq!(imitated("lsasss.exe"), Some(("lsass.exe", false)));
+        // One typo from a system name, but also a common name of its own (only a warning)
+        assert_eq!(imitated("service.exe"), Some(("services.exe", false)));
+        // The real name, other extensions and short names are no look-alikes
+        assert_eq!(imitated("svchost.exe"), None);
+        assert_eq!(imitated("svchost.dll"), None);
+        assert_eq!(imitated("cmd1.exe"), None);
+        assert_eq!(imitated("notepad.exe"), None);
+    }
+}
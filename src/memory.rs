@@ -0,0 +1,82 @@
+//! Memory Accounting
+//!
+//! What the long-lived in-memory structures currently hold (GUI log history, screenshot
+//! preview, icon and process caches) plus the working set of the process, for the status
+//! strip and an hourly line in the app log. The caps themselves are in the `memory` config
+//! section.
+
+use std::thread;
+use std::time::Duration;
+use tracing::info;
+use windows::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+use windows::Win32::System::Threading::GetCurrentProcess;
+
+/// Snapshot of the memory usage
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemoryUsage {
+    /// Working set of the whole process
+    pub working_set: usize,
+    pub log_entries: usize,
+    /// Text held by the GUI log history
+    pub log_bytes: usize,
+    /// Pixels of the screenshot preview
+    pub screenshot_bytes: usize,
+    pub icon_cache_entries: usize,
+    pub process_cache_entries: usize,
+}
+
+/// Working set of this process in bytes (0 if unknown)
+fn working_set() -> usize {
+    let mut counters = PROCESS_MEMORY_COUNTERS {
+        cb: std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
+        ..Default::default()
+    };
+    unsafe {
+        match GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, counters.cb) {
+            Ok(()) => counters.WorkingSetSize,
+            Err(_) => 0,
+        }
+    }
+}
+
+/// Takes a snapshot (locks the GUI buffers briefly)
+pub fn usage() -> MemoryUsage {
+    let (log_entries, log_bytes) = crate::alert_window::log_usage();
+    MemoryUsage {
+        working_set: working_set(),
+        log_entries,
+        log_bytes,
+        screenshot_bytes: crate::alert_window::screenshot_bytes(),
+        icon_cache_entries: crate::alert_window::icon_cache_len(),
+        process_cache_entries: crate::process_info::cache_len(),
+    }
+}
+
+/// Bytes as megabytes for display
+pub fn megabytes(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+/// Logs the usage every hour (growth of long sessions is visible in app.log)
+pub fn start_monitor() {
+    thread::spawn(|| {
+        while !crate::tray::should_exit() {
+            let usage = usage();
+            info!(
+                "Memory: working set {:.1} MB, GUI log {} entries ({} KB), screenshot {:.1} MB, icons {}, processes {}",
+                megabytes(usage.working_set),
+                usage.log_entries,
+                usage.log_bytes / 1024,
+                megabytes(usage.screenshot_bytes),
+                usage.icon_cache_entries,
+                usage.process_cache_entries
+            );
+            for _ in 0..3600 {
+                if crate::tray::should_exit() {
+                    return;
+                }
+                thread::sleep(Duration::from_secs(1));
+            }
+        }
+    });
+}
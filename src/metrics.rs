@@ -0,0 +1,89 @@
+//! Pipeline Metrics
+//!
+//! Counters of the event pipeline (hooks -> event worker -> logger) for the status strip
+//! in the GUI, so an overloaded system is visible instead of silently missing entries.
+//! Errors reported to the health stream (`error::report`) are counted as well.
+
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+/// Queue usage from this percentage on counts as overloaded
+pub const QUEUE_WARN_PERCENT: u32 = 80;
+
+// Counters since program start
+static EVENTS: AtomicU64 = AtomicU64::new(0);
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+static FILTERED: AtomicU64 = AtomicU64::new(0);
+static ERRORS: AtomicU64 = AtomicU64::new(0);
+
+/// Current state of the pipeline
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PipelineStatus {
+    pub events_per_sec: f64,
+    /// Fill level of the fullest queue (0-100)
+    pub queue_percent: u32,
+    /// Events dropped because a queue was full (since start)
+    pub dropped: u64,
+    /// Drops since the previous sample
+    pub new_drops: u64,
+    /// Events dropped by the window class filter (since start)
+    pub filtered: u64,
+    /// Non-fatal errors reported to the health stream (since start)
+    pub errors: u64,
+    /// Errors since the previous sample
+    pub new_errors: u64,
+}
+
+lazy_static::lazy_static! {
+    // Previous sample: (time, events, dropped, errors)
+    static ref LAST_SAMPLE: Mutex<(Instant, u64, u64, u64)> = Mutex::new((Instant::now(), 0, 0, 0));
+}
+
+/// Counts an event processed by the event worker
+pub fn record_event() {
+    EVENTS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Counts an event that was dropped (queue full)
+pub fn record_drop() {
+    DROPPED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Counts an event dropped by the window class filter
+pub fn record_filtered() {
+    FILTERED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Counts an error reported to the health stream
+pub fn record_error() {
+    ERRORS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Takes a sample (rates are calculated since the previous call)
+pub fn sample() -> PipelineStatus {
+    let events = EVENTS.load(Ordering::Relaxed);
+    let dropped = DROPPED.load(Ordering::Relaxed);
+    let errors = ERRORS.load(Ordering::Relaxed);
+
+    let mut last = LAST_SAMPLE.lock();
+    let elapsed = last.0.elapsed().as_secs_f64();
+    let events_per_sec = if elapsed > 0.0 {
+        events.saturating_sub(last.1) as f64 / elapsed
+    } else {
+        0.0
+    };
+    let new_drops = dropped.saturating_sub(last.2);
+    let new_errors = errors.saturating_sub(last.3);
+    *last = (Instant::now(), events, dropped, errors);
+
+    PipelineStatus {
+        events_per_sec,
+        queue_percent: crate::event_hook::queue_percent(),
+        dropped,
+        new_drops,
+        filtered: FILTERED.load(Ordering::Relaxed),
+        errors,
+        new_errors,
+    }
+}
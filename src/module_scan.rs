@@ -0,0 +1,165 @@
+//! Module Scan (DLL Injection Indicators)
+//!
+//! When a Critical alert fires, the modules loaded by the offending process are enumerated and
+//! DLLs from user-writable folders (anything outside the Windows and Program Files folders)
+//! are checked for a valid signature. Unsigned ones are a common sign of DLL injection or
+//! search-order hijacking. Findings are logged as DLL_SCAN event, shown in the details window
+//! and written into exported incident bundles.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::path::Path;
+use std::thread;
+use tracing::{info, warn};
+use windows::Win32::Foundation::CloseHandle;
+use windows::Win32::System::Diagnostics::ToolHelp::{
+    CreateToolhelp32Snapshot, Module32FirstW, Module32NextW, MODULEENTRY32W, TH32CS_SNAPMODULE, TH32CS_SNAPMODULE32,
+};
+
+/// Event type in the log
+const EVENT_TYPE: &str = "DLL_SCAN";
+
+/// Folders only administrators can write to (environment variables)
+const PROTECTED_FOLDERS: &[&str] = &["SystemRoot", "ProgramFiles", "ProgramFiles(x86)", "ProgramW6432"];
+
+/// Result of a scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pending,
+    /// Number of loaded modules, none suspicious
+    Clean(usize),
+    /// Unsigned DLLs from user-writable folders (full paths)
+    Suspicious(Vec<String>),
+    /// Modules not readable (access denied, process exited, ...)
+    Unavailable(String),
+}
+
+impl Verdict {
+    pub fn describe(&self) -> String {
+        match self {
+            Verdict::Pending => "scanning...".to_string(),
+            Verdict::Clean(count) => format!("{} modules, no unsigned DLL from user-writable folders", count),
+            Verdict::Suspicious(dlls) => format!("UNSIGNED DLL(s) from user-writable folders: {}", dlls.join(", ")),
+            Verdict::Unavailable(reason) => format!("not available ({})", reason),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    // Verdict of the last scanned instance per executable (key = lowercase path): (PID, verdict)
+    static ref VERDICTS: Mutex<HashMap<String, (u32, Verdict)>> = Mutex::new(HashMap::new());
+}
+
+/// Protected folders (lowercase, with trailing backslash)
+fn protected_folders() -> Vec<String> {
+    PROTECTED_FOLDERS
+        .iter()
+        .filter_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+        .map(|folder| format!("{}\\", folder.trim_end_matches('\\').to_lowercase()))
+        .collect()
+}
+
+/// Paths of all modules loaded by a process (32- and 64-bit)
+fn loaded_modules(process_id: u32) -> Result<Vec<String>, String> {
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPMODULE | TH32CS_SNAPMODULE32, process_id)
+            .map_err(|e| e.message().to_string())?;
+
+        let mut entry = MODULEENTRY32W {
+            dwSize: std::mem::size_of::<MODULEENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut modules = Vec::new();
+        if Module32FirstW(snapshot, &mut entry).is_ok() {
+            loop {
+                let len = entry.szExePath.iter().position(|&c| c == 0).unwrap_or(entry.szExePath.len());
+                modules.push(String::from_utf16_lossy(&entry.szExePath[..len]));
+                if Module32NextW(snapshot, &mut entry).is_err() {
+                    break;
+                }
+            }
+        }
+        let _ = CloseHandle(snapshot);
+        Ok(modules)
+    }
+}
+
+/// Enumerates the modules and checks the DLLs outside protected folders
+fn scan(process_id: u32, process_path: &str) -> Verdict {
+    let modules = match loaded_modules(process_id) {
+        Ok(modules) if !modules.is_empty() => modules,
+        Ok(_) => return Verdict::Unavailable("no modules".to_string()),
+        Err(e) => return Verdict::Unavailable(e),
+    };
+    let protected = protected_folders();
+    let suspicious: Vec<String> = modules
+        .iter()
+        .filter(|module| !module.eq_ignore_ascii_case(process_path))
+        .filter(|module| {
+            let lower = module.to_lowercase();
+            !protected.iter().any(|folder| lower.starts_with(folder.as_str()))
+        })
+        .filter(|module| !crate::signature::is_signed(module))
+        .cloned()
+        .collect();
+
+    if suspicious.is_empty() {
+        Verdict::Clean(modules.len())
+    } else {
+        Verdict::Suspicious(suspicious)
+    }
+}
+
+/// Verdict of the last scan of an executable (None = never scanned)
+pub fn verdict(process_path: &str) -> Option<Verdict> {
+    VERDICTS.lock().get(&process_path.to_lowercase()).map(|(_, verdict)| verdict.clone())
+}
+
+/// Starts a background scan of the process of a Critical alert (once per process instance)
+pub fn lookup(process_id: u32, process_name: &str, process_path: &str) {
+    if !crate::config::get().module_scan.enabled || process_id == 0 || !Path::new(process_path).is_absolute() {
+        return;
+    }
+
+    {
+        let mut verdicts = VERDICTS.lock();
+        let key = process_path.to_lowercase();
+        if verdicts.get(&key).is_some_and(|(pid, _)| *pid == process_id) {
+            return;
+        }
+        verdicts.insert(key, (process_id, Verdict::Pending));
+    }
+
+    let process_name = process_name.to_string();
+    let process_path = process_path.to_string();
+    thread::spawn(move || {
+        let verdict = scan(process_id, &process_path);
+        VERDICTS.lock().insert(process_path.to_lowercase(), (process_id, verdict.clone()));
+        report(process_name, process_id, process_path, &verdict);
+    });
+}
+
+/// Logs the verdict and refreshes open details windows
+fn report(process_name: String, process_id: u32, process_path: String, verdict: &Verdict) {
+    let severity = if matches!(verdict, Verdict::Suspicious(_)) {
+        warn!("!!! DLL scan of {} (PID {}): {} !!!", process_path, process_id, verdict.describe());
+        Severity::Warning
+    } else {
+        info!("DLL scan of {} (PID {}): {}", process_path, process_id, verdict.describe());
+        Severity::Info
+    };
+
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: chrono::Local::now(),
+        event_type: EVENT_TYPE.to_string(),
+        severity,
+        process_name,
+        process_id,
+        process_path,
+        window_title: format!("DLLs: {}", verdict.describe()),
+        ..Default::default()
+    });
+    crate::alert_window::redraw_details_window();
+}
@@ -0,0 +1,395 @@
+//! MQTT Publishing
+//!
+//! Publishes alerts (and optionally all events) as JSON to an MQTT broker for home automation,
+//! e.g. Home Assistant flashing a light when the PC wakes up at night. Minimal MQTT 3.1.1
+//! client (QoS 0/1, retained online/offline status as last will), optionally over TLS.
+//!
+//! Topics (prefix configurable, "{computer}" = computer name):
+//! - `<prefix>/status`: "online" / "offline" (retained)
+//! - `<prefix>/alert`: every Warning/Critical event
+//! - `<prefix>/event/<event_type>`: every event (if publish_events)
+
+use crate::config::MqttConfig;
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::tls::{TlsStream, Transport};
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender};
+use once_cell::sync::OnceCell;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// Pending messages (further events are dropped while full, e.g. broker offline)
+const QUEUE_SIZE: usize = 256;
+
+/// Keep alive announced to the broker (ping after half of it without traffic)
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reconnect backoff
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(5);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(120);
+
+/// How often the worker checks for new messages and shutdown
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Packet types (fixed header, upper nibble)
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const PUBACK: u8 = 0x40;
+const PINGREQ: u8 = 0xC0;
+const PINGRESP: u8 = 0xD0;
+const DISCONNECT: u8 = 0xE0;
+
+/// One message for the broker
+struct Message {
+    topic: String,
+    payload: Vec<u8>,
+    retain: bool,
+}
+
+// Queue of the publishing thread (created by start())
+static SENDER: OnceCell<Sender<Message>> = OnceCell::new();
+
+/// Topic prefix with the computer name filled in
+fn topic_prefix(config: &MqttConfig) -> String {
+    let computer = hostname::get()
+        .map(|h| h.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|_| "pc".to_string());
+    config.topic_prefix.replace("{computer}", &computer).trim_end_matches('/').to_string()
+}
+
+/// Starts the publishing thread if enabled (announces "online")
+pub fn start() {
+    let config = crate::config::get().mqtt;
+    if !config.enabled || config.host.is_empty() {
+        return;
+    }
+    SENDER.get_or_init(|| {
+        let (sender, receiver) = bounded::<Message>(QUEUE_SIZE);
+        thread::spawn(move || worker(receiver));
+        sender
+    });
+}
+
+/// Publishes an event (alert topic for Warning/Critical, event topic if enabled)
+pub fn publish_event(entry: &LogEntry) {
+    let Some(sender) = SENDER.get() else {
+        return;
+    };
+    let config = crate::config::get().mqtt;
+    let prefix = topic_prefix(&config);
+
+    let mut json = entry.format_json();
+    json["computer"] = serde_json::Value::String(
+        hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default(),
+    );
+    let payload = json.to_string().into_bytes();
+
+    let mut topics = Vec::new();
+    if entry.severity >= Severity::Warning {
+        topics.push(format!("{}/alert", prefix));
+    }
+    if config.publish_events {
+        topics.push(format!("{}/event/{}", prefix, entry.event_type.to_lowercase()));
+    }
+    for topic in topics {
+        let message = Message { topic, payload: payload.clone(), retain: false };
+        if sender.try_send(message).is_err() {
+            warn!("MQTT queue full - {} event not published", entry.event_type);
+            break;
+        }
+    }
+}
+
+/// Publishing thread: keeps the connection, reconnects with backoff
+fn worker(receiver: Receiver<Message>) {
+    info!("MQTT worker started");
+    let mut connection: Option<Connection> = None;
+    let mut pending: Option<Message> = None;
+    let mut retry_delay = MIN_RETRY_DELAY;
+
+    loop {
+        if crate::event_hook::is_shutting_down() {
+            if let Some(mut connection) = connection.take() {
+                connection.close();
+            }
+            break;
+        }
+
+        let Some(active) = connection.as_mut() else {
+            match Connection::open(&crate::config::get().mqtt) {
+                Ok(c) => {
+                    connection = Some(c);
+                    retry_delay = MIN_RETRY_DELAY;
+                }
+                Err(e) => {
+                    error!("MQTT connection failed: {} (retry in {}s)", e, retry_delay.as_secs());
+                    thread::sleep(retry_delay);
+                    retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+                }
+            }
+            continue;
+        };
+
+        let message = match pending.take() {
+            Some(message) => Some(message),
+            None => match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(message) => Some(message),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => break,
+            },
+        };
+
+        let result = match &message {
+            Some(message) => active.publish(message),
+            None if active.last_sent.elapsed() >= KEEP_ALIVE / 2 => active.ping(),
+            None => Ok(()),
+        };
+        if let Err(e) = result {
+            warn!("MQTT connection lost: {}", e);
+            connection = None;
+            // Sent again after reconnecting
+            pending = message;
+        }
+    }
+}
+
+/// Connected broker session
+struct Connection {
+    stream: Box<dyn Transport>,
+    qos: u8,
+    status_topic: String,
+    next_packet_id: u16,
+    last_sent: Instant,
+}
+
+/// Remaining length (variable length encoding)
+fn encode_length(mut length: usize, out: &mut Vec<u8>) {
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+}
+
+/// UTF-8 string / binary data with 16-bit length prefix
+fn encode_string(data: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    out.extend_from_slice(data);
+}
+
+/// Complete packet from fixed header byte and body
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = vec![header];
+    encode_length(body.len(), &mut out);
+    out.extend_from_slice(body);
+    out
+}
+
+/// Meaning of a CONNACK return code
+fn connack_error(code: u8) -> String {
+    match code {
+        1 => "unacceptable protocol version".to_string(),
+        2 => "client id rejected".to_string(),
+        3 => "broker unavailable".to_string(),
+        4 => "bad username or password".to_string(),
+        5 => "not authorized".to_string(),
+        code => format!("refused (code {})", code),
+    }
+}
+
+impl Connection {
+    /// TCP (+TLS) connect and MQTT CONNECT with "offline" as last will
+    fn open(config: &MqttConfig) -> Result<Self, String> {
+        let address = (config.host.as_str(), config.port)
+            .to_socket_addrs()
+            .map_err(|e| format!("cannot resolve {}: {}", config.host, e))?
+            .next()
+            .ok_or_else(|| format!("cannot resolve {}", config.host))?;
+        let tcp = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).map_err(|e| e.to_string())?;
+        let _ = tcp.set_read_timeout(Some(READ_TIMEOUT));
+        let _ = tcp.set_write_timeout(Some(READ_TIMEOUT));
+        let _ = tcp.set_nodelay(true);
+        let stream: Box<dyn Transport> = if config.tls {
+            Box::new(TlsStream::connect(tcp, &config.host, &config.client_certificate, &config.pinned_sha256)?)
+        } else {
+            Box::new(tcp)
+        };
+
+        let prefix = topic_prefix(config);
+        let qos = config.qos.min(1);
+        let mut connection = Connection {
+            stream,
+            qos,
+            status_topic: format!("{}/status", prefix),
+            next_packet_id: 1,
+            last_sent: Instant::now(),
+        };
+
+        // Flags: clean session, will (retained, QoS), username, password
+        let mut flags = 0x02 | 0x04 | 0x20 | (qos << 3);
+        if !config.username.is_empty() {
+            flags |= 0x80;
+            if !config.password.is_empty() {
+                flags |= 0x40;
+            }
+        }
+        let client_id = format!("pc_watcher_{}", prefix.replace('/', "_"));
+
+        let mut body = Vec::new();
+        encode_string(b"MQTT", &mut body);
+        body.push(4); // Protocol level 3.1.1
+        body.push(flags);
+        body.extend_from_slice(&(KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+        encode_string(client_id.as_bytes(), &mut body);
+        encode_string(connection.status_topic.as_bytes(), &mut body);
+        encode_string(b"offline", &mut body);
+        if !config.username.is_empty() {
+            encode_string(config.username.as_bytes(), &mut body);
+            if !config.password.is_empty() {
+                encode_string(config.password.as_bytes(), &mut body);
+            }
+        }
+        connection.send(&packet(CONNECT, &body))?;
+
+        let (header, body) = connection.read_packet()?;
+        if header & 0xF0 != CONNACK || body.len() < 2 {
+            return Err("no CONNACK from broker".to_string());
+        }
+        if body[1] != 0 {
+            return Err(connack_error(body[1]));
+        }
+
+        info!("MQTT connected to {}:{}{}", config.host, config.port, if config.tls { " (TLS)" } else { "" });
+        let online = Message { topic: connection.status_topic.clone(), payload: b"online".to_vec(), retain: true };
+        connection.publish(&online)?;
+        Ok(connection)
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), String> {
+        self.stream.write_all(data).map_err(|e| format!("send failed: {}", e))?;
+        self.stream.flush().map_err(|e| format!("send failed: {}", e))?;
+        self.last_sent = Instant::now();
+        Ok(())
+    }
+
+    /// Reads one packet: (fixed header byte, body)
+    fn read_packet(&mut self) -> Result<(u8, Vec<u8>), String> {
+        let mut byte = [0u8; 1];
+        self.stream.read_exact(&mut byte).map_err(|e| format!("receive failed: {}", e))?;
+        let header = byte[0];
+
+        let mut length = 0usize;
+        for shift in 0..4 {
+            self.stream.read_exact(&mut byte).map_err(|e| format!("receive failed: {}", e))?;
+            length |= ((byte[0] & 0x7F) as usize) << (7 * shift);
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+        }
+
+        let mut body = vec![0u8; length];
+        self.stream.read_exact(&mut body).map_err(|e| format!("receive failed: {}", e))?;
+        Ok((header, body))
+    }
+
+    /// Waits for a packet type (other packets are skipped)
+    fn wait_for(&mut self, packet_type: u8, packet_id: Option<u16>) -> Result<(), String> {
+        loop {
+            let (header, body) = self.read_packet()?;
+            let id_matches = packet_id.is_none_or(|id| body.get(..2) == Some(&id.to_be_bytes()[..]));
+            if header & 0xF0 == packet_type && id_matches {
+                return Ok(());
+            }
+        }
+    }
+
+    /// PUBLISH (QoS 1 waits for PUBACK)
+    fn publish(&mut self, message: &Message) -> Result<(), String> {
+        let mut header = PUBLISH | (self.qos << 1);
+        if message.retain {
+            header |= 0x01;
+        }
+
+        let mut body = Vec::with_capacity(message.topic.len() + message.payload.len() + 4);
+        encode_string(message.topic.as_bytes(), &mut body);
+        let packet_id = (self.qos > 0).then(|| {
+            let id = self.next_packet_id;
+            self.next_packet_id = self.next_packet_id.checked_add(1).unwrap_or(1);
+            id
+        });
+        if let Some(id) = packet_id {
+            body.extend_from_slice(&id.to_be_bytes());
+        }
+        body.extend_from_slice(&message.payload);
+
+        self.send(&packet(header, &body))?;
+        if packet_id.is_some() {
+            self.wait_for(PUBACK, packet_id)?;
+        }
+        Ok(())
+    }
+
+    fn ping(&mut self) -> Result<(), String> {
+        self.send(&packet(PINGREQ, &[]))?;
+        self.wait_for(PINGRESP, None)
+    }
+
+    /// Announces "offline" and disconnects cleanly (the last will is not sent then)
+    fn close(&mut self) {
+        let offline = Message { topic: self.status_topic.clone(), payload: b"offline".to_vec(), retain: true };
+        let _ = self.publish(&offline);
+        let _ = self.send(&packet(DISCONNECT, &[]));
+        info!("MQTT disconnected");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn length(value: usize) -> Vec<u8> {
+        let mut out = Vec::new();
+        encode_length(value, &mut out);
+        out
+    }
+
+    #[test]
+    fn remaining_length_uses_one_to_four_bytes() {
+        assert_eq!(length(0), [0x00]);
+        assert_eq!(length(127), [0x7F]);
+        assert_eq!(length(128), [0x80, 0x01]);
+        assert_eq!(length(321), [0xC1, 0x02]);
+        assert_eq!(length(16_383), [0xFF, 0x7F]);
+        assert_eq!(length(16_384), [0x80, 0x80, 0x01]);
+        assert_eq!(length(2_097_151), [0xFF, 0xFF, 0x7F]);
+        assert_eq!(length(2_097_152), [0x80, 0x80, 0x80, 0x01]);
+        // Largest length MQTT allows
+        assert_eq!(length(268_435_455), [0xFF, 0xFF, 0xFF, 0x7F]);
+    }
+
+    #[test]
+    fn packets_and_strings_are_length_prefixed() {
+        assert_eq!(packet(0xC0, &[]), [0xC0, 0x00]);
+        let body = vec![7u8; 200];
+        let encoded = packet(0x30, &body);
+        assert_eq!(encoded[..3], [0x30, 0xC8, 0x01]);
+        assert_eq!(encoded.len(), 3 + body.len());
+
+        let mut out = Vec::new();
+        encode_string(b"MQTT", &mut out);
+        assert_eq!(out, [0x00, 0x04, b'M', b'Q', b'T', b'T']);
+    }
+}
@@ -0,0 +1,170 @@
+//! Network Change Events
+//!
+//! Polls the connected networks of the Network List Manager (name = SSID for Wi-Fi) and logs
+//! connects and disconnects as timeline events. A network whose profile Windows created just
+//! now was never joined before - optionally a Warning ("joined an unknown hotspot").
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use chrono::Local;
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use windows::core::GUID;
+use windows::Win32::Networking::NetworkListManager::{
+    INetwork, INetworkListManager, NetworkListManager, NLM_CONNECTIVITY_IPV4_INTERNET,
+    NLM_CONNECTIVITY_IPV6_INTERNET, NLM_ENUM_NETWORK_CONNECTED, NLM_NETWORK_CATEGORY_DOMAIN_AUTHENTICATED,
+    NLM_NETWORK_CATEGORY_PRIVATE,
+};
+use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED};
+
+/// Poll interval
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Network profiles created within this time count as new networks
+const NEW_NETWORK_AGE_SECS: i64 = 600;
+
+/// Seconds between 1601-01-01 (FILETIME) and 1970-01-01
+const FILETIME_UNIX_OFFSET: i64 = 11_644_473_600;
+
+/// One connected network
+#[derive(Debug, Clone)]
+struct Network {
+    name: String,
+    category: &'static str,
+    internet: bool,
+    /// Age of the network profile in seconds
+    profile_age: i64,
+}
+
+impl Network {
+    fn describe(&self) -> String {
+        format!("{} ({}{})", self.name, self.category, if self.internet { ", internet" } else { "" })
+    }
+}
+
+/// Reads one network of the Network List Manager
+unsafe fn read_network(network: &INetwork) -> Option<(GUID, Network)> {
+    let id = network.GetNetworkId().ok()?;
+    let name = network.GetName().map(|n| n.to_string()).unwrap_or_default();
+    let category = match network.GetCategory() {
+        Ok(NLM_NETWORK_CATEGORY_DOMAIN_AUTHENTICATED) => "domain",
+        Ok(NLM_NETWORK_CATEGORY_PRIVATE) => "private",
+        _ => "public",
+    };
+    let internet = network.GetConnectivity().is_ok_and(|c| {
+        c.0 & (NLM_CONNECTIVITY_IPV4_INTERNET.0 | NLM_CONNECTIVITY_IPV6_INTERNET.0) != 0
+    });
+
+    let (mut created_low, mut created_high, mut connected_low, mut connected_high) = (0u32, 0u32, 0u32, 0u32);
+    let profile_age = match network.GetTimeCreatedAndConnected(
+        &mut created_low,
+        &mut created_high,
+        &mut connected_low,
+        &mut connected_high,
+    ) {
+        Ok(()) => {
+            let created = ((created_high as i64) << 32 | created_low as i64) / 10_000_000 - FILETIME_UNIX_OFFSET;
+            Local::now().timestamp() - created
+        }
+        Err(_) => i64::MAX,
+    };
+
+    Some((id, Network { name, category, internet, profile_age }))
+}
+
+/// All connected networks by id
+unsafe fn connected_networks(manager: &INetworkListManager) -> Result<HashMap<GUID, Network>, String> {
+    let networks = manager.GetNetworks(NLM_ENUM_NETWORK_CONNECTED).map_err(|e| format!("GetNetworks: {}", e))?;
+    let mut result = HashMap::new();
+    loop {
+        let mut items = [None];
+        let mut fetched = 0;
+        if networks.Next(&mut items, Some(&mut fetched)).is_err() || fetched == 0 {
+            break;
+        }
+        if let Some((id, network)) = items[0].as_ref().and_then(|n| read_network(n)) {
+            result.insert(id, network);
+        }
+    }
+    Ok(result)
+}
+
+/// Logs a connect/disconnect (Warning for a network never joined before)
+fn report(network: &Network, connected: bool) {
+    let is_new = connected && network.profile_age < NEW_NETWORK_AGE_SECS;
+    let (event_type, text) = match (connected, is_new) {
+        (true, true) => ("NETWORK_NEW", format!("Joined new network: {}", network.describe())),
+        (true, false) => ("NETWORK_CONNECTED", format!("Connected: {}", network.describe())),
+        (false, _) => ("NETWORK_DISCONNECTED", format!("Disconnected: {}", network.name)),
+    };
+
+    let severity = if is_new && crate::config::get().network.alert_new_networks { Severity::Warning } else { Severity::Info };
+    if severity == Severity::Warning {
+        warn!("!!! {} !!!", text);
+        if crate::rules::is_enabled(Rule::NewNetwork) {
+            crate::alert_window::set_rule_alert(Rule::NewNetwork, &text, "", severity);
+            crate::sound::play_alert(Rule::NewNetwork, severity);
+            let capture = crate::rules::capture_mode(Rule::NewNetwork, severity);
+            crate::screenshot::capture_alert_screenshots("network".to_string(), event_type, capture, severity);
+        }
+    } else {
+        info!("{}", text);
+    }
+
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: Local::now(),
+        event_type: event_type.to_string(),
+        severity,
+        process_name: network.name.clone(),
+        window_title: text,
+        ..Default::default()
+    });
+}
+
+/// Starts the polling thread (only if enabled in the config)
+pub fn start_monitor() {
+    if !crate::config::get().network.enabled {
+        return;
+    }
+
+    thread::spawn(|| unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        let manager: INetworkListManager = match CoCreateInstance(&NetworkListManager, None, CLSCTX_ALL) {
+            Ok(manager) => manager,
+            Err(e) => {
+                error!("Network monitor disabled: NetworkListManager: {}", e);
+                return;
+            }
+        };
+
+        // Networks connected at start are not logged
+        let mut known = connected_networks(&manager).unwrap_or_default();
+        info!("Network monitor started: {} connected network(s)", known.len());
+
+        while !crate::tray::should_exit() {
+            thread::sleep(POLL_INTERVAL);
+            let current = match connected_networks(&manager) {
+                Ok(networks) => networks,
+                Err(e) => {
+                    error!("Network monitor: {}", e);
+                    continue;
+                }
+            };
+
+            for (id, network) in &known {
+                if !current.contains_key(id) {
+                    report(network, false);
+                }
+            }
+            for (id, network) in &current {
+                if !known.contains_key(id) {
+                    report(network, true);
+                }
+            }
+            known = current;
+        }
+    });
+}
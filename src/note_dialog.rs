@@ -0,0 +1,178 @@
+//! Note Dialog
+//!
+//! Small modal input window for user notes on events ("that was me installing a printer driver").
+//! Runs its own message loop like a MessageBox, Enter saves and Escape cancels.
+
+use std::cell::RefCell;
+use tracing::error;
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, RECT, WPARAM};
+use windows::Win32::Graphics::Gdi::{GetStockObject, COLOR_BTNFACE, DEFAULT_GUI_FONT, HBRUSH};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Input::KeyboardAndMouse::{EnableWindow, SetFocus};
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+const DIALOG_WIDTH: i32 = 420;
+const DIALOG_HEIGHT: i32 = 140;
+const MAX_NOTE_LENGTH: usize = 500;
+
+// Edit control messages (Win32_UI_Controls is not needed otherwise)
+const EM_LIMITTEXT: u32 = 197;
+const EM_SETSEL: u32 = 177;
+
+/// State of the open dialog (only one at a time, GUI thread)
+struct DialogState {
+    edit: HWND,
+    result: Option<String>,
+    done: bool,
+}
+
+thread_local! {
+    static STATE: RefCell<Option<DialogState>> = const { RefCell::new(None) };
+}
+
+/// Shows the dialog above the owner and returns the entered note (None = cancelled)
+pub unsafe fn ask_note(owner: HWND, initial: &str) -> Option<String> {
+    if STATE.with(|s| s.borrow().is_some()) {
+        return None;
+    }
+
+    let instance = GetModuleHandleW(None).unwrap_or_default();
+    let class_name = w!("PCWatcherNote");
+    let wc = WNDCLASSW {
+        lpfnWndProc: Some(note_window_proc),
+        hInstance: instance.into(),
+        hCursor: LoadCursorW(None, IDC_ARROW).unwrap_or_default(),
+        hbrBackground: HBRUSH((COLOR_BTNFACE.0 + 1) as isize as *mut _),
+        lpszClassName: class_name,
+        ..Default::default()
+    };
+    // Fails harmlessly if already registered
+    let _ = RegisterClassW(&wc);
+
+    // Centered over the owner
+    let mut owner_rect = RECT::default();
+    let _ = GetWindowRect(owner, &mut owner_rect);
+    let x = owner_rect.left + ((owner_rect.right - owner_rect.left) - DIALOG_WIDTH) / 2;
+    let y = owner_rect.top + ((owner_rect.bottom - owner_rect.top) - DIALOG_HEIGHT) / 2;
+
+    let hwnd = match CreateWindowExW(
+        WS_EX_TOPMOST | WS_EX_DLGMODALFRAME,
+        class_name,
+        w!("PC Watcher - Note"),
+        WS_POPUP | WS_CAPTION | WS_SYSMENU,
+        x, y,
+        DIALOG_WIDTH,
+        DIALOG_HEIGHT,
+        owner,
+        None,
+        instance,
+        None,
+    ) {
+        Ok(h) => h,
+        Err(e) => {
+            error!("Could not create note dialog: {}", e);
+            return None;
+        }
+    };
+
+    let font = GetStockObject(DEFAULT_GUI_FONT);
+    let child = |class: PCWSTR, text: PCWSTR, style: WINDOW_STYLE, x: i32, y: i32, w: i32, h: i32, id: i32| {
+        let control = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            class,
+            text,
+            WS_CHILD | WS_VISIBLE | style,
+            x, y, w, h,
+            hwnd,
+            HMENU(id as isize as *mut _),
+            instance,
+            None,
+        )
+        .unwrap_or_default();
+        SendMessageW(control, WM_SETFONT, WPARAM(font.0 as usize), LPARAM(1));
+        control
+    };
+
+    child(w!("STATIC"), w!("Note for this event (empty = remove):"), WINDOW_STYLE::default(), 10, 10, 390, 18, 0);
+    let edit = child(
+        w!("EDIT"),
+        PCWSTR::null(),
+        WS_BORDER | WS_TABSTOP | WINDOW_STYLE(ES_AUTOHSCROLL as u32),
+        10, 32, 390, 22,
+        0,
+    );
+    child(w!("BUTTON"), w!("Save"), WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32), 230, 66, 80, 26, IDOK.0);
+    child(w!("BUTTON"), w!("Cancel"), WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32), 320, 66, 80, 26, IDCANCEL.0);
+
+    let initial: Vec<u16> = initial.encode_utf16().chain(std::iter::once(0)).collect();
+    let _ = SetWindowTextW(edit, PCWSTR(initial.as_ptr()));
+    SendMessageW(edit, EM_LIMITTEXT, WPARAM(MAX_NOTE_LENGTH), LPARAM(0));
+    SendMessageW(edit, EM_SETSEL, WPARAM(0), LPARAM(-1));
+
+    STATE.with(|s| *s.borrow_mut() = Some(DialogState { edit, result: None, done: false }));
+
+    // Modal: owner is disabled until the dialog closes
+    let _ = EnableWindow(owner, false);
+    let _ = ShowWindow(hwnd, SW_SHOW);
+    let _ = SetFocus(edit);
+
+    let mut msg = MSG::default();
+    while !STATE.with(|s| s.borrow().as_ref().map(|d| d.done).unwrap_or(true)) {
+        if !GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            // WM_QUIT belongs to the outer loop
+            PostQuitMessage(msg.wParam.0 as i32);
+            break;
+        }
+        // Enter -> IDOK, Escape -> IDCANCEL, Tab between controls
+        if !IsDialogMessageW(hwnd, &msg).as_bool() {
+            let _ = TranslateMessage(&msg);
+            let _ = DispatchMessageW(&msg);
+        }
+    }
+
+    let _ = EnableWindow(owner, true);
+    let _ = DestroyWindow(hwnd);
+    let _ = SetForegroundWindow(owner);
+
+    STATE.with(|s| s.borrow_mut().take()).and_then(|d| d.result)
+}
+
+/// Text of the edit control
+unsafe fn edit_text(edit: HWND) -> String {
+    let mut buffer = vec![0u16; MAX_NOTE_LENGTH + 1];
+    let len = GetWindowTextW(edit, &mut buffer);
+    String::from_utf16_lossy(&buffer[..len.max(0) as usize])
+}
+
+/// Ends the dialog (result None = cancelled)
+fn finish(save: bool) {
+    STATE.with(|s| {
+        if let Some(state) = s.borrow_mut().as_mut() {
+            if save {
+                state.result = Some(unsafe { edit_text(state.edit) }.trim().to_string());
+            }
+            state.done = true;
+        }
+    });
+}
+
+/// Window procedure of the note dialog
+unsafe extern "system" fn note_window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    match msg {
+        WM_COMMAND => {
+            let id = (wparam.0 & 0xFFFF) as i32;
+            if id == IDOK.0 {
+                finish(true);
+            } else if id == IDCANCEL.0 {
+                finish(false);
+            }
+            LRESULT(0)
+        }
+        WM_CLOSE => {
+            finish(false);
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
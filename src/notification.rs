@@ -2,8 +2,45 @@
 //!
 //! Detects suspicious processes.
 
+use serde::{Deserialize, Serialize};
 use tracing::info;
 
+/// Severity of an event (Warning and above trigger an alert)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "INFO",
+            Severity::Warning => "WARNING",
+            Severity::Critical => "CRITICAL",
+        }
+    }
+
+    /// Parses as_str() output (unknown = Info)
+    pub fn parse(value: &str) -> Severity {
+        match value {
+            "CRITICAL" => Severity::Critical,
+            "WARNING" => Severity::Warning,
+            _ => Severity::Info,
+        }
+    }
+
+    /// Next higher severity (Critical stays Critical)
+    pub fn raised(&self) -> Severity {
+        match self {
+            Severity::Info => Severity::Warning,
+            Severity::Warning | Severity::Critical => Severity::Critical,
+        }
+    }
+}
+
 // List of suspicious processes
 const SUSPICIOUS_PROCESSES: &[&str] = &[
     "powershell",
@@ -27,6 +64,10 @@ pub fn show_start_notification() {
     info!("=== PC Watcher started ===");
     info!("Monitoring window focus events...");
     info!("Alert on: {:?}", SUSPICIOUS_PROCESSES);
+    let whitelist = crate::config::get().whitelist;
+    if !whitelist.is_empty() {
+        info!("Whitelist: {:?}", whitelist);
+    }
 }
 
 /// Shows stop info (log only)
@@ -0,0 +1,115 @@
+//! Open Actions
+//!
+//! Opens the log file, folders and screenshot images with the programs of the `open_with`
+//! config. `%1` in the command line is replaced by the path (appended when missing). An
+//! empty command, or one that cannot be started, falls back to the default program of
+//! Windows (ShellExecute "open").
+
+use std::path::Path;
+use tracing::{info, warn};
+use windows::core::{w, PCWSTR};
+use windows::Win32::System::Com::{CoInitializeEx, COINIT_APARTMENTTHREADED};
+use windows::Win32::UI::Shell::{ILCreateFromPathW, ILFree, SHOpenFolderAndSelectItems, ShellExecuteW};
+use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+/// Opens the log file
+pub fn open_log(path: &Path) {
+    open(&crate::config::get().open_with.log, path);
+}
+
+/// Opens a folder
+pub fn open_folder(path: &Path) {
+    open(&crate::config::get().open_with.folder, path);
+}
+
+/// Opens an image
+pub fn open_image(path: &Path) {
+    open(&crate::config::get().open_with.image, path);
+}
+
+/// Shows a file in its folder (selected by the default file manager or Explorer)
+pub fn open_location(path: &Path) {
+    let command = crate::config::get().open_with.folder;
+    let program = split_command(&command).into_iter().next().unwrap_or_default().to_lowercase();
+    if command.trim().is_empty() {
+        if let Err(e) = select_in_folder(path) {
+            warn!("Could not select {} in its folder: {}", path.display(), e);
+            if let Some(folder) = path.parent() {
+                shell_open(folder);
+            }
+        }
+    } else if program == "explorer" || program == "explorer.exe" {
+        let _ = std::process::Command::new("explorer.exe").arg(format!("/select,{}", path.display())).spawn();
+    } else if let Some(folder) = path.parent() {
+        open(&command, folder);
+    }
+}
+
+/// Opens the folder of a file with the file selected (shell, honors replacement file managers)
+fn select_in_folder(path: &Path) -> Result<(), String> {
+    let wide: Vec<u16> = path.as_os_str().to_string_lossy().encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let item = ILCreateFromPathW(PCWSTR(wide.as_ptr()));
+        if item.is_null() {
+            return Err("path not found".to_string());
+        }
+        let result = SHOpenFolderAndSelectItems(item, None, 0);
+        ILFree(Some(item));
+        result.map_err(|e| e.to_string())
+    }
+}
+
+/// Splits a command line into program and arguments (double quotes group)
+fn split_command(command: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in command.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    parts.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Starts the configured program, or the default program if none is set or it fails
+fn open(command: &str, path: &Path) {
+    let path_text = path.to_string_lossy();
+    let mut parts = split_command(command);
+    if !parts.is_empty() {
+        let program = parts.remove(0);
+        let mut arguments: Vec<String> = parts.iter().map(|a| a.replace("%1", &path_text)).collect();
+        if !parts.iter().any(|a| a.contains("%1")) {
+            arguments.push(path_text.to_string());
+        }
+        match std::process::Command::new(&program).args(&arguments).spawn() {
+            Ok(_) => {
+                info!("Opening {} with {}", path.display(), program);
+                return;
+            }
+            Err(e) => warn!("Could not start {}: {} - using the default program", program, e),
+        }
+    }
+    shell_open(path);
+}
+
+/// Opens a path with its default program
+fn shell_open(path: &Path) {
+    info!("Opening {}", path.display());
+    let wide: Vec<u16> = path.as_os_str().to_string_lossy().encode_utf16().chain(std::iter::once(0)).collect();
+    let result = unsafe { ShellExecuteW(None, w!("open"), PCWSTR(wide.as_ptr()), None, None, SW_SHOWNORMAL) };
+    // Values up to 32 are error codes
+    if result.0 as isize <= 32 {
+        warn!("ShellExecute failed for {} (code {})", path.display(), result.0 as isize);
+    }
+}
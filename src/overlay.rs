@@ -0,0 +1,184 @@
+//! Full-Screen Overlay
+//!
+//! Optional minimal alert for users who do want interruptions during games: a small
+//! click-through layered window in a screen corner shows a one-line alert over full-screen
+//! apps (borderless/windowed - exclusive full-screen cannot be drawn over) for a few seconds.
+
+use crate::error::Error;
+use crate::notification::Severity;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use tracing::info;
+use windows::core::w;
+use windows::Win32::Foundation::{COLORREF, HWND, LPARAM, LRESULT, RECT, WPARAM};
+use windows::Win32::Graphics::Gdi::{
+    BeginPaint, CreateSolidBrush, DeleteObject, DrawTextW, EndPaint, FillRect, SetBkMode, SetTextColor,
+    DT_END_ELLIPSIS, DT_LEFT, DT_SINGLELINE, DT_VCENTER, HGDIOBJ, InvalidateRect, PAINTSTRUCT, TRANSPARENT,
+};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+const OVERLAY_WIDTH: i32 = 420;
+const OVERLAY_HEIGHT: i32 = 28;
+const OVERLAY_MARGIN: i32 = 12;
+const HIDE_TIMER_ID: usize = 1;
+
+// Posted by show() - the window belongs to the overlay thread
+const WM_APP_SHOW: u32 = WM_APP + 1;
+
+/// Screen corner of the overlay
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    #[default]
+    TopRight,
+    TopLeft,
+    BottomRight,
+    BottomLeft,
+}
+
+static OVERLAY_HWND: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static::lazy_static! {
+    // Current line and severity color
+    static ref OVERLAY_TEXT: Mutex<(String, Severity)> = Mutex::new((String::new(), Severity::Warning));
+}
+
+/// Shows a one-line alert in the overlay (no-op if disabled)
+pub fn show(message: &str, severity: Severity) {
+    let hwnd = OVERLAY_HWND.load(Ordering::SeqCst);
+    if hwnd == 0 {
+        return;
+    }
+    *OVERLAY_TEXT.lock() = (format!("PC Watcher: {}", message), severity);
+    unsafe {
+        let _ = PostMessageW(HWND(hwnd as *mut _), WM_APP_SHOW, WPARAM(0), LPARAM(0));
+    }
+}
+
+/// Creates the (hidden) overlay window in its own thread if enabled
+pub fn start_overlay() {
+    if !crate::config::get().overlay.enabled {
+        return;
+    }
+    thread::spawn(|| {
+        if let Err(e) = create_overlay_window() {
+            crate::error::report("Could not create overlay window", e);
+        }
+    });
+}
+
+/// Position of the overlay in the configured corner of the primary monitor
+fn overlay_position(corner: Corner) -> (i32, i32) {
+    let (screen_w, screen_h) = unsafe { (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN)) };
+    let left = OVERLAY_MARGIN;
+    let right = screen_w - OVERLAY_WIDTH - OVERLAY_MARGIN;
+    let top = OVERLAY_MARGIN;
+    let bottom = screen_h - OVERLAY_HEIGHT - OVERLAY_MARGIN;
+    match corner {
+        Corner::TopRight => (right, top),
+        Corner::TopLeft => (left, top),
+        Corner::BottomRight => (right, bottom),
+        Corner::BottomLeft => (left, bottom),
+    }
+}
+
+/// Creates the overlay window and runs its message loop
+fn create_overlay_window() -> Result<(), Error> {
+    unsafe {
+        let instance = GetModuleHandleW(None).map_err(|e| Error::win32("GetModuleHandle", &e))?;
+        let class_name = w!("PCWatcherOverlay");
+        let wc = WNDCLASSW {
+            lpfnWndProc: Some(overlay_window_proc),
+            hInstance: instance.into(),
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        let _ = RegisterClassW(&wc);
+
+        // Click-through (TRANSPARENT), never activated, not in the taskbar
+        let (x, y) = overlay_position(crate::config::get().overlay.corner);
+        let hwnd = CreateWindowExW(
+            WS_EX_TOPMOST | WS_EX_LAYERED | WS_EX_TRANSPARENT | WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE,
+            class_name,
+            w!("PC Watcher Overlay"),
+            WS_POPUP,
+            x, y,
+            OVERLAY_WIDTH,
+            OVERLAY_HEIGHT,
+            None,
+            None,
+            instance,
+            None,
+        )
+        .map_err(|e| Error::win32("CreateWindowExW", &e))?;
+
+        let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), 220, LWA_ALPHA);
+        OVERLAY_HWND.store(hwnd.0 as usize, Ordering::SeqCst);
+        info!("Overlay window created");
+
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            let _ = DispatchMessageW(&msg);
+        }
+        OVERLAY_HWND.store(0, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+/// Window procedure of the overlay
+unsafe extern "system" fn overlay_window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    match msg {
+        WM_APP_SHOW => {
+            let config = crate::config::get().overlay;
+            let (x, y) = overlay_position(config.corner);
+            let _ = SetWindowPos(
+                hwnd,
+                HWND_TOPMOST,
+                x, y,
+                OVERLAY_WIDTH,
+                OVERLAY_HEIGHT,
+                SWP_NOACTIVATE | SWP_SHOWWINDOW,
+            );
+            let _ = InvalidateRect(hwnd, None, true);
+            // Restart the hide timer with every new alert
+            let _ = SetTimer(hwnd, HIDE_TIMER_ID, config.seconds.max(1) * 1000, None);
+            LRESULT(0)
+        }
+
+        WM_TIMER => {
+            if wparam.0 == HIDE_TIMER_ID {
+                let _ = KillTimer(hwnd, HIDE_TIMER_ID);
+                let _ = ShowWindow(hwnd, SW_HIDE);
+            }
+            LRESULT(0)
+        }
+
+        WM_PAINT => {
+            let mut ps = PAINTSTRUCT::default();
+            let hdc = BeginPaint(hwnd, &mut ps);
+            let mut rect = RECT::default();
+            let _ = GetClientRect(hwnd, &mut rect);
+
+            let (text, severity) = OVERLAY_TEXT.lock().clone();
+            let color = if severity == Severity::Critical { 0x000000CC } else { 0x00006FCC };
+            let brush = CreateSolidBrush(COLORREF(color));
+            let _ = FillRect(hdc, &rect, brush);
+            let _ = DeleteObject(HGDIOBJ(brush.0));
+
+            let _ = SetBkMode(hdc, TRANSPARENT);
+            let _ = SetTextColor(hdc, COLORREF(0x00FFFFFF));
+            let mut text_rect = RECT { left: rect.left + 10, right: rect.right - 10, ..rect };
+            let mut text_wide: Vec<u16> = text.encode_utf16().collect();
+            DrawTextW(hdc, &mut text_wide, &mut text_rect, DT_LEFT | DT_VCENTER | DT_SINGLELINE | DT_END_ELLIPSIS);
+
+            let _ = EndPaint(hwnd, &ps);
+            LRESULT(0)
+        }
+
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
@@ -0,0 +1,146 @@
+//! Process Path Heuristics
+//!
+//! Cheap tells in the path of an executable, checked once per process on its first window:
+//! running from the temp folder, the roaming AppData folder, the recycle bin or a mounted disk
+//! image (ISO), a document-like double extension ("invoice.pdf.exe") and right-to-left override
+//! characters that disguise the real extension. Each heuristic has its own switch and severity.
+
+use crate::config::HeuristicSetting;
+use crate::notification::Severity;
+use std::path::Path;
+use windows::core::PCWSTR;
+use windows::Win32::Storage::FileSystem::GetDriveTypeW;
+
+/// GetDriveTypeW result for optical drives - mounted ISO images show up as one
+const DRIVE_CDROM: u32 = 5;
+
+/// Extensions that make a double extension look like a document
+const DOCUMENT_EXTENSIONS: &[&str] = &[
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "txt", "rtf", "jpg", "jpeg", "png", "gif", "mp3", "mp4",
+    "avi", "zip", "rar",
+];
+
+/// Unicode direction overrides used to reverse the visible end of a file name
+const BIDI_OVERRIDES: &[char] = &['\u{202E}', '\u{202D}', '\u{2066}', '\u{2067}', '\u{2068}'];
+
+/// One heuristic that matched
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub description: String,
+    pub severity: Severity,
+}
+
+/// Lowercase long form of a folder with trailing backslash (8.3 names like "FABIAN~1" expanded)
+fn folder_prefix(folder: &str) -> Option<String> {
+    let folder = folder.trim_end_matches('\\');
+    (!folder.is_empty()).then(|| format!("{}\\", crate::long_path::expand_short_names(folder).to_lowercase()))
+}
+
+/// Folder from an environment variable, as folder_prefix
+fn env_folder(name: &str) -> Option<String> {
+    folder_prefix(&std::env::var(name).ok()?)
+}
+
+/// Checks if a lowercase long path is inside one of the folders
+fn in_folder(path: &str, folders: &[Option<String>]) -> bool {
+    folders.iter().flatten().any(|folder| path.starts_with(folder.as_str()))
+}
+
+/// Executable on a CD-ROM drive (mounted ISO/IMG)
+fn on_optical_drive(path: &str) -> bool {
+    let Some(root) = path.get(..3).filter(|r| r.ends_with(":\\")) else {
+        return false;
+    };
+    let root: Vec<u16> = root.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe { GetDriveTypeW(PCWSTR(root.as_ptr())) == DRIVE_CDROM }
+}
+
+/// Document extension directly before the real one ("report.pdf.exe")
+fn double_extension(file_name: &str) -> Option<String> {
+    let mut parts = file_name.rsplit('.');
+    let _extension = parts.next()?;
+    let inner = parts.next()?.trim().to_lowercase();
+    parts.next()?;
+    DOCUMENT_EXTENSIONS.contains(&inner.as_str()).then_some(inner)
+}
+
+/// Heuristics matching the executable path
+pub fn check(process_path: &str) -> Vec<Finding> {
+    let config = crate::config::get().path_heuristics;
+    if !config.enabled || !Path::new(process_path).is_absolute() {
+        return Vec::new();
+    }
+    let path = crate::long_path::expand_short_names(process_path).to_lowercase();
+    let file_name = Path::new(process_path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+
+    let mut findings = Vec::new();
+    let mut add = |setting: &HeuristicSetting, matched: bool, description: String| {
+        if setting.enabled && matched {
+            findings.push(Finding { description, severity: setting.severity });
+        }
+    };
+
+    let windows_temp = env_folder("SystemRoot").map(|f| format!("{}temp\\", f));
+    let temp_folders = [env_folder("TEMP"), env_folder("TMP"), windows_temp];
+    add(&config.temp, in_folder(&path, &temp_folders), "runs from the temp folder".to_string());
+    add(&config.appdata, in_folder(&path, &[env_folder("APPDATA")]), "runs from AppData\\Roaming".to_string());
+    add(&config.recycle_bin, path.contains("\\$recycle.bin\\"), "runs from the recycle bin".to_string());
+    add(&config.mounted_image, on_optical_drive(process_path), "runs from a mounted disk image or CD".to_string());
+    if let Some(inner) = double_extension(&file_name) {
+        add(&config.double_extension, true, format!("double extension (.{} + real extension)", inner));
+    }
+    add(
+        &config.rtlo,
+        file_name.contains(BIDI_OVERRIDES),
+        "right-to-left override in the file name (disguised extension)".to_string(),
+    );
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows::Win32::Storage::FileSystem::GetShortPathNameW;
+
+    #[test]
+    fn detects_document_double_extensions() {
+        assert_eq!(double_extension("invoice.pdf.exe").as_deref(), Some("pdf"));
+        assert_eq!(double_extension("Photo.JPG .scr").as_deref(), Some("jpg"));
+        assert_eq!(double_extension("setup.exe"), None);
+        assert_eq!(double_extension("pdf.exe"), None);
+        assert_eq!(double_extension("node.v18.exe"), None);
+    }
+
+    #[test]
+    fn matches_whole_folders_only() {
+        let appdata = [folder_prefix(r"C:\Users\Me\AppData\Roaming\")];
+        assert_eq!(appdata[0].as_deref(), Some(r"c:\users\me\appdata\roaming\"));
+        assert!(in_folder(r"c:\users\me\appdata\roaming\x\evil.exe", &appdata));
+        assert!(!in_folder(r"c:\users\me\appdata\roamingcache\app.exe", &appdata));
+        assert!(!in_folder(r"c:\users\me\appdata\local\app.exe", &appdata));
+        assert!(!in_folder(r"c:\users\me\appdata\roaming\evil.exe", &[None]));
+        assert_eq!(folder_prefix(""), None);
+    }
+
+    #[test]
+    fn short_folder_names_match_their_long_form() {
+        let dir = std::env::temp_dir().join("pc_watcher path heuristics test");
+        std::fs::create_dir_all(&dir).unwrap();
+        // Only existing files are expanded
+        std::fs::write(dir.join("app.exe"), b"").unwrap();
+        let wide = crate::long_path::to_wide(&dir);
+        let mut buffer = vec![0u16; crate::long_path::MAX_PATH_CHARS];
+        let len = unsafe { GetShortPathNameW(PCWSTR(wide.as_ptr()), Some(&mut buffer)) } as usize;
+        let short = String::from_utf16_lossy(&buffer[..len]);
+        // Volumes without 8.3 names return the long path
+        if len > 0 && short.contains('~') {
+            let long = folder_prefix(&dir.to_string_lossy());
+            assert_eq!(folder_prefix(&short), long);
+            let exe = format!(r"{}\app.exe", short).to_lowercase();
+            assert!(!in_folder(&exe, std::slice::from_ref(&long)));
+            let exe = crate::long_path::expand_short_names(&format!(r"{}\app.exe", short)).to_lowercase();
+            assert!(in_folder(&exe, &[long]));
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
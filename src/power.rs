@@ -0,0 +1,247 @@
+//! Power Events
+//!
+//! The tray window receives WM_POWERBROADCAST: sleep, resume, display off/on and lid
+//! open/close are logged as timeline events. Windows reports a resume with user input
+//! (PBT_APMRESUMESUSPEND) separately from an automatic one (wake timer, network) - a resume
+//! by someone at the PC during the configured quiet hours raises a Warning. The RESUME entry
+//! names the wake source (power button, device, timer/task), read from the Power-Troubleshooter
+//! event Windows writes a few seconds after waking up - the data of `powercfg /lastwake`.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use chrono::{DateTime, Duration as ChronoDuration, Local, Timelike};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use windows::core::{GUID, PCWSTR};
+use windows::Win32::Foundation::{HANDLE, HWND, LPARAM, WPARAM};
+use windows::Win32::System::EventLog::{
+    EvtClose, EvtNext, EvtQuery, EvtRender, EvtQueryChannelPath, EvtQueryReverseDirection, EvtRenderEventXml,
+    EVT_HANDLE,
+};
+use windows::Win32::System::Power::{RegisterPowerSettingNotification, POWERBROADCAST_SETTING};
+use windows::Win32::UI::WindowsAndMessaging::{
+    DEVICE_NOTIFY_WINDOW_HANDLE, PBT_APMRESUMEAUTOMATIC, PBT_APMRESUMESUSPEND, PBT_APMSUSPEND, PBT_POWERSETTINGCHANGE,
+};
+
+/// GUID_CONSOLE_DISPLAY_STATE (data: 0 = off, 1 = on, 2 = dimmed)
+const GUID_CONSOLE_DISPLAY_STATE: GUID = GUID::from_u128(0x6fe69556_704a_47a0_8f24_c28d936fda47);
+
+/// GUID_LIDSWITCH_STATE_CHANGE (data: 0 = closed, 1 = opened)
+const GUID_LIDSWITCH_STATE_CHANGE: GUID = GUID::from_u128(0xba3e0f4d_b817_4094_a2d1_d56379e6a0f3);
+
+/// Wake events of the Power-Troubleshooter in the System log
+const WAKE_QUERY: &str = "*[System[Provider[@Name='Microsoft-Windows-Power-Troubleshooter'] and EventID=1]]";
+
+/// The wake event is written shortly after the resume - checked this often
+const WAKE_SOURCE_ATTEMPTS: u32 = 12;
+const WAKE_SOURCE_INTERVAL: Duration = Duration::from_secs(5);
+
+lazy_static::lazy_static! {
+    // Start of the last sleep (wake events before it belong to an older resume)
+    static ref SUSPENDED_AT: Mutex<Option<DateTime<Local>>> = Mutex::new(None);
+}
+
+/// Last display/lid state (u32::MAX = not yet known; Windows sends the current state on register)
+static DISPLAY_STATE: AtomicU32 = AtomicU32::new(u32::MAX);
+static LID_STATE: AtomicU32 = AtomicU32::new(u32::MAX);
+
+/// Registers the display and lid notifications for a window (WM_POWERBROADCAST)
+pub fn register(hwnd: HWND) {
+    if !crate::config::get().power.enabled {
+        return;
+    }
+    for setting in [GUID_CONSOLE_DISPLAY_STATE, GUID_LIDSWITCH_STATE_CHANGE] {
+        if let Err(e) = unsafe { RegisterPowerSettingNotification(HANDLE(hwnd.0), &setting, DEVICE_NOTIFY_WINDOW_HANDLE) } {
+            warn!("RegisterPowerSettingNotification failed: {}", e);
+        }
+    }
+}
+
+/// Checks if the current time is within the quiet hours (may wrap around midnight)
+fn in_quiet_hours() -> bool {
+    let config = crate::config::get().power;
+    let hour = Local::now().hour();
+    let (start, end) = (config.quiet_start_hour, config.quiet_end_hour);
+    match start.cmp(&end) {
+        std::cmp::Ordering::Less => hour >= start && hour < end,
+        std::cmp::Ordering::Greater => hour >= start || hour < end,
+        std::cmp::Ordering::Equal => false,
+    }
+}
+
+/// Logs a power event
+fn log_event(event_type: &str, text: &str, severity: Severity) {
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: Local::now(),
+        event_type: event_type.to_string(),
+        severity,
+        process_name: "System".to_string(),
+        window_title: text.to_string(),
+        ..Default::default()
+    });
+}
+
+/// Value of <Data Name='name'> in an event XML
+fn event_data(xml: &str, name: &str) -> Option<String> {
+    let start = xml.find(&format!("<Data Name='{}'>", name))? + name.len() + 14;
+    let end = start + xml[start..].find("</Data>")?;
+    Some(xml[start..end].trim().to_string())
+}
+
+/// XML of the newest wake event
+unsafe fn newest_wake_event() -> Option<String> {
+    let channel: Vec<u16> = "System".encode_utf16().chain(std::iter::once(0)).collect();
+    let query: Vec<u16> = WAKE_QUERY.encode_utf16().chain(std::iter::once(0)).collect();
+    let results = EvtQuery(
+        None,
+        PCWSTR(channel.as_ptr()),
+        PCWSTR(query.as_ptr()),
+        EvtQueryChannelPath.0 | EvtQueryReverseDirection.0,
+    )
+    .ok()?;
+
+    let mut events = [0isize; 1];
+    let mut returned = 0;
+    let mut xml = None;
+    if EvtNext(results, &mut events, 0, 0, &mut returned).is_ok() && returned == 1 {
+        let event = EVT_HANDLE(events[0]);
+        let mut buffer = vec![0u16; 8192];
+        let (mut used, mut properties) = (0u32, 0u32);
+        let rendered = EvtRender(
+            None,
+            event,
+            EvtRenderEventXml.0,
+            (buffer.len() * 2) as u32,
+            Some(buffer.as_mut_ptr() as *mut _),
+            &mut used,
+            &mut properties,
+        );
+        if rendered.is_ok() {
+            let len = (used as usize / 2).min(buffer.len());
+            xml = Some(String::from_utf16_lossy(&buffer[..len]).trim_end_matches('\0').to_string());
+        }
+        let _ = EvtClose(event);
+    }
+    let _ = EvtClose(results);
+    xml
+}
+
+/// Wake source of the resume after `since` (waits for Windows to write the event)
+fn wake_source(since: DateTime<Local>) -> Option<String> {
+    for _ in 0..WAKE_SOURCE_ATTEMPTS {
+        thread::sleep(WAKE_SOURCE_INTERVAL);
+        let Some(xml) = (unsafe { newest_wake_event() }) else {
+            continue;
+        };
+        let created = xml
+            .split("SystemTime='")
+            .nth(1)
+            .and_then(|s| s.split('\'').next())
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok());
+        if created.is_some_and(|t| t < since) {
+            continue;
+        }
+        return match event_data(&xml, "WakeSourceText").filter(|t| !t.is_empty()) {
+            Some(text) => Some(text),
+            None => event_data(&xml, "WakeSourceType").map(|t| format!("unknown (type {})", t)),
+        };
+    }
+    debug!("No wake event found since {}", since);
+    None
+}
+
+/// Logs the automatic resume once the wake source is known
+fn log_resume() {
+    let resumed = Local::now();
+    let since = SUSPENDED_AT.lock().take().unwrap_or(resumed - ChronoDuration::minutes(2));
+    thread::spawn(move || {
+        let text = match wake_source(since) {
+            Some(source) => format!("System resumed (automatic) - wake source: {}", source),
+            None => "System resumed (automatic) - wake source unknown".to_string(),
+        };
+        info!("{}", text);
+        crate::event_hook::send_log_entry(LogEntry {
+            timestamp: resumed,
+            event_type: "RESUME".to_string(),
+            severity: Severity::Info,
+            process_name: "System".to_string(),
+            window_title: text,
+            ..Default::default()
+        });
+    });
+}
+
+/// Resume with user input during the quiet hours
+fn report_quiet_resume() {
+    let config = crate::config::get().power;
+    let text = format!(
+        "PC woken by someone during quiet hours ({:02}:00-{:02}:00)",
+        config.quiet_start_hour, config.quiet_end_hour
+    );
+    let severity = Severity::Warning;
+    warn!("!!! {} !!!", text);
+    if crate::rules::is_enabled(Rule::QuietResume) {
+        crate::alert_window::set_rule_alert(Rule::QuietResume, &text, "", severity);
+        crate::sound::play_alert(Rule::QuietResume, severity);
+        let capture = crate::rules::capture_mode(Rule::QuietResume, severity);
+        crate::screenshot::capture_alert_screenshots("resume".to_string(), "RESUME", capture, severity);
+    }
+    log_event("RESUME", &text, severity);
+}
+
+/// Handles WM_POWERBROADCAST of the tray window
+pub fn on_power_broadcast(wparam: WPARAM, lparam: LPARAM) {
+    if !crate::config::get().power.enabled {
+        return;
+    }
+    match wparam.0 as u32 {
+        PBT_APMSUSPEND => {
+            info!("System goes to sleep");
+            *SUSPENDED_AT.lock() = Some(Local::now());
+            log_event("SLEEP", "System goes to sleep", Severity::Info);
+        }
+        PBT_APMRESUMEAUTOMATIC => log_resume(),
+        // Follows the automatic resume when the user pressed a key/button
+        PBT_APMRESUMESUSPEND => {
+            if in_quiet_hours() && crate::config::get().power.alert_quiet_resume {
+                report_quiet_resume();
+            } else {
+                log_event("RESUME", "System resumed by user input", Severity::Info);
+            }
+        }
+        PBT_POWERSETTINGCHANGE if lparam.0 != 0 => {
+            let setting = unsafe { &*(lparam.0 as *const POWERBROADCAST_SETTING) };
+            // Dimmed counts as on
+            let value = if setting.Data[0] == 2 { 1 } else { setting.Data[0] };
+            let last_state = match setting.PowerSetting {
+                GUID_CONSOLE_DISPLAY_STATE => &DISPLAY_STATE,
+                GUID_LIDSWITCH_STATE_CHANGE => &LID_STATE,
+                _ => return,
+            };
+            // Initial state after register, or repeated state
+            let previous = last_state.swap(value as u32, Ordering::Relaxed);
+            if previous == u32::MAX || previous == value as u32 {
+                return;
+            }
+            let (event_type, text) = match setting.PowerSetting {
+                GUID_CONSOLE_DISPLAY_STATE => match value {
+                    0 => ("DISPLAY_OFF", "Display turned off"),
+                    1 => ("DISPLAY_ON", "Display turned on"),
+                    _ => return,
+                },
+                GUID_LIDSWITCH_STATE_CHANGE => match value {
+                    0 => ("LID_CLOSED", "Lid closed"),
+                    _ => ("LID_OPENED", "Lid opened"),
+                },
+                _ => return,
+            };
+            info!("{}", text);
+            log_event(event_type, text, Severity::Info);
+        }
+        _ => {}
+    }
+}
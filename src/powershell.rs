@@ -0,0 +1,88 @@
+//! PowerShell Module
+//!
+//! `pc_watcher generate powershell-module` writes a thin PowerShell module that wraps the
+//! CLI: every cmdlet calls pc_watcher with `--json` and returns the parsed objects, so the
+//! tool fits into admin scripts (`Get-PCWatcherEvents -AlertsOnly | Where-Object ...`).
+
+use std::path::Path;
+
+/// Default file name of the module
+pub const MODULE_FILE: &str = "PCWatcher.psm1";
+
+/// Module source with the path of this executable
+pub fn module_source(exe: &Path) -> String {
+    let exe = exe.display().to_string().replace('\'', "''");
+    format!(
+        r#"# PCWatcher PowerShell module (generated by pc_watcher generate powershell-module)
+# Import-Module .\PCWatcher.psm1
+
+$script:PCWatcherExe = '{exe}'
+
+function Invoke-PCWatcher {{
+    param([Parameter(ValueFromRemainingArguments)] [string[]] $Arguments)
+    $output = & $script:PCWatcherExe @Arguments --json | Out-String
+    $result = $output | ConvertFrom-Json
+    if (-not $result.ok) {{ throw "pc_watcher $($Arguments -join ' '): $($result.error)" }}
+    $result
+}}
+
+function Get-PCWatcherEvents {{
+    [CmdletBinding()]
+    param([int] $Limit = 50, [switch] $AlertsOnly, [string] $Tag)
+    $arguments = @('events', '--limit', $Limit)
+    if ($AlertsOnly) {{ $arguments += '--alerts' }}
+    if ($Tag) {{ $arguments += @('--tag', $Tag) }}
+    (Invoke-PCWatcher @arguments).events
+}}
+
+function Export-PCWatcherAlert {{
+    [CmdletBinding()]
+    param([long] $Id)
+    $arguments = @('export-alert')
+    if ($PSBoundParameters.ContainsKey('Id')) {{ $arguments += @('--id', $Id) }}
+    Invoke-PCWatcher @arguments
+}}
+
+function Export-PCWatcherConfig {{
+    [CmdletBinding()]
+    param([Parameter(Mandatory)] [string] $Path)
+    Invoke-PCWatcher config export $Path
+}}
+
+function Import-PCWatcherConfig {{
+    [CmdletBinding()]
+    param([Parameter(Mandatory)] [string] $Path)
+    Invoke-PCWatcher config import $Path
+}}
+
+function New-PCWatcherDiagnostics {{
+    [CmdletBinding()]
+    param()
+    Invoke-PCWatcher diag
+}}
+
+function Invoke-PCWatcherDbCompact {{
+    [CmdletBinding()]
+    param()
+    Invoke-PCWatcher db compact
+}}
+
+function Install-PCWatcher {{
+    [CmdletBinding()]
+    param()
+    Invoke-PCWatcher install
+}}
+
+function Uninstall-PCWatcher {{
+    [CmdletBinding()]
+    param()
+    Invoke-PCWatcher uninstall
+}}
+
+Export-ModuleMember -Function Get-PCWatcherEvents, Export-PCWatcherAlert, Export-PCWatcherConfig,
+    Import-PCWatcherConfig, New-PCWatcherDiagnostics, Invoke-PCWatcherDbCompact, Install-PCWatcher,
+    Uninstall-PCWatcher
+"#,
+        exe = exe
+    )
+}
@@ -0,0 +1,110 @@
+//! Process Exit Tracking
+//!
+//! After an alert the offending process is watched by a helper thread (one per PID). When it
+//! exits, PROCESS_EXITED is logged with exit code and lifetime - did it stop, or is it still
+//! running?
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use chrono::Local;
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::thread;
+use tracing::{debug, info};
+use windows::Win32::Foundation::{CloseHandle, FILETIME, HANDLE, WAIT_OBJECT_0};
+use windows::Win32::System::Threading::{
+    GetExitCodeProcess, GetProcessTimes, OpenProcess, WaitForSingleObject, PROCESS_QUERY_LIMITED_INFORMATION,
+    PROCESS_SYNCHRONIZE,
+};
+
+/// Event type in the log
+const EVENT_TYPE: &str = "PROCESS_EXITED";
+
+/// Wait slice (the thread checks for program exit in between)
+const WAIT_MS: u32 = 5000;
+
+lazy_static::lazy_static! {
+    // PIDs with a waiting thread
+    static ref WATCHED: Mutex<HashSet<u32>> = Mutex::new(HashSet::new());
+}
+
+/// FILETIME as 100 ns units
+fn filetime_value(time: &FILETIME) -> u64 {
+    (time.dwHighDateTime as u64) << 32 | time.dwLowDateTime as u64
+}
+
+/// Human-readable lifetime ("2h 5m", "42s")
+fn format_lifetime(secs: u64) -> String {
+    match secs {
+        s if s >= 3600 => format!("{}h {}m", s / 3600, s % 3600 / 60),
+        s if s >= 60 => format!("{}m {}s", s / 60, s % 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// Exit code as text (NTSTATUS values like 0xC0000005 in hex)
+fn format_exit_code(code: u32) -> String {
+    if code >= 0xC000_0000 {
+        format!("0x{:08X}", code)
+    } else {
+        code.to_string()
+    }
+}
+
+/// Waits for the process to exit and logs exit code and lifetime
+unsafe fn wait_for_exit(handle: HANDLE, process_id: u32, process_name: &str) {
+    loop {
+        if WaitForSingleObject(handle, WAIT_MS) == WAIT_OBJECT_0 {
+            break;
+        }
+        if crate::tray::should_exit() {
+            return;
+        }
+    }
+
+    let mut exit_code = 0u32;
+    let _ = GetExitCodeProcess(handle, &mut exit_code);
+    let (mut created, mut exited, mut kernel, mut user) =
+        (FILETIME::default(), FILETIME::default(), FILETIME::default(), FILETIME::default());
+    let lifetime = match GetProcessTimes(handle, &mut created, &mut exited, &mut kernel, &mut user) {
+        Ok(()) => format_lifetime(filetime_value(&exited).saturating_sub(filetime_value(&created)) / 10_000_000),
+        Err(_) => "unknown".to_string(),
+    };
+
+    let text = format!("Exited with code {} after {}", format_exit_code(exit_code), lifetime);
+    info!("Alerted process {} (PID {}): {}", process_name, process_id, text);
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: Local::now(),
+        event_type: EVENT_TYPE.to_string(),
+        severity: Severity::Info,
+        process_name: process_name.to_string(),
+        process_id,
+        window_title: text,
+        ..Default::default()
+    });
+}
+
+/// Watches an alerted process until it exits (no-op if already watched)
+pub fn watch(process_id: u32, process_name: &str) {
+    if process_id == 0 || !WATCHED.lock().insert(process_id) {
+        return;
+    }
+    let handle = match unsafe { OpenProcess(PROCESS_SYNCHRONIZE | PROCESS_QUERY_LIMITED_INFORMATION, false, process_id) } {
+        Ok(handle) => handle,
+        Err(e) => {
+            debug!("Cannot watch exit of {} (PID {}): {}", process_name, process_id, e);
+            WATCHED.lock().remove(&process_id);
+            return;
+        }
+    };
+
+    let process_name = process_name.to_string();
+    // Raw handle value: HANDLE is not Send
+    let raw = handle.0 as isize;
+    thread::spawn(move || unsafe {
+        let handle = HANDLE(raw as *mut _);
+        wait_for_exit(handle, process_id, &process_name);
+        let _ = CloseHandle(handle);
+        WATCHED.lock().remove(&process_id);
+    });
+}
@@ -1,14 +1,27 @@
 //! Process Information
 //!
 //! Reads process name, path, window title, command line and PARENT process.
+//! The parent PID can be forged at creation (PROC_THREAD_ATTRIBUTE_PARENT_PROCESS), so it is
+//! cross-checked against the creation times.
+//!
+//! The system queries sit behind `ProcessInfoProvider`: `Win32Provider` asks Windows,
+//! `MockProvider` answers from a fixed process table (pipeline benchmark, replay, tests). Name, hierarchy and
+//! spoofing checks are built on top of the provider, so they behave the same for both.
+//! Where OpenProcess is denied, `Win32Provider` asks WMI instead (see wmi_process).
 
 use std::ffi::OsString;
 use std::os::windows::ffi::OsStringExt;
 use std::path::Path;
-use windows::Win32::Foundation::{HANDLE, HWND, CloseHandle, MAX_PATH};
+use std::sync::Arc;
+use crate::long_path::{self, MAX_LONG_PATH_CHARS, MAX_PATH_CHARS};
+use crate::wmi_process::WmiProvider;
+use windows::Wdk::System::Threading::{NtQueryInformationProcess, ProcessCommandLineInformation};
+use windows::Win32::Foundation::{
+    HANDLE, HWND, CloseHandle, ERROR_INSUFFICIENT_BUFFER, ERROR_INVALID_PARAMETER, FILETIME, UNICODE_STRING,
+};
 use windows::Win32::System::ProcessStatus::GetModuleFileNameExW;
 use windows::Win32::System::Threading::{
-    OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ,
+    GetProcessTimes, OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_VM_READ,
     QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
 };
 use windows::Win32::System::Diagnostics::ToolHelp::{
@@ -17,9 +30,206 @@ use windows::Win32::System::Diagnostics::ToolHelp::{
 };
 use windows::Win32::UI::WindowsAndMessaging::{
     GetWindowTextW, GetWindowTextLengthW, GetClassNameW,
-    GetWindowThreadProcessId,
+    GetWindowThreadProcessId, GetAncestor, GA_ROOTOWNER,
 };
 
+/// Source of the raw process data
+pub trait ProcessInfoProvider: Send + Sync {
+    /// Process owning a window (0 = none)
+    fn window_process_id(&self, hwnd: HWND) -> u32;
+    fn window_title(&self, hwnd: HWND) -> String;
+    fn window_class(&self, hwnd: HWND) -> String;
+    /// Executable path (None = access denied)
+    fn process_path(&self, process_id: u32) -> Option<String>;
+    fn command_line(&self, process_id: u32) -> Option<String>;
+    /// Parent PID as recorded at creation (None = process not found)
+    fn parent_id(&self, process_id: u32) -> Option<u32>;
+    /// Executable name without .exe, also readable without access to the process
+    fn snapshot_name(&self, process_id: u32) -> Option<String>;
+    /// Creation time as FILETIME ticks (None = not accessible), Err(()) = no such process
+    fn creation_time(&self, process_id: u32) -> Result<Option<u64>, ()>;
+    /// Names of all running processes (lowercase, without .exe)
+    fn running_process_names(&self) -> HashSet<String>;
+}
+
+lazy_static::lazy_static! {
+    static ref PROVIDER: RwLock<Arc<dyn ProcessInfoProvider>> = RwLock::new(Arc::new(Win32Provider));
+}
+
+/// Provider used for all lookups
+fn provider() -> Arc<dyn ProcessInfoProvider> {
+    PROVIDER.read().clone()
+}
+
+/// Replaces the provider (cached lookups of the previous one are dropped)
+pub fn set_provider(provider: Arc<dyn ProcessInfoProvider>) {
+    *PROVIDER.write() = provider;
+    PROCESS_CACHE.write().clear();
+}
+
+/// Queries Windows directly
+pub struct Win32Provider;
+
+impl ProcessInfoProvider for Win32Provider {
+    fn window_process_id(&self, hwnd: HWND) -> u32 {
+        let mut process_id: u32 = 0;
+        unsafe {
+            GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+        }
+        process_id
+    }
+
+    fn window_title(&self, hwnd: HWND) -> String {
+        read_window_title(hwnd)
+    }
+
+    fn window_class(&self, hwnd: HWND) -> String {
+        read_window_class(hwnd)
+    }
+
+    fn process_path(&self, process_id: u32) -> Option<String> {
+        unsafe {
+            if let Ok(h) = OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, process_id) {
+                if !h.is_invalid() {
+                    let path = get_process_path(h);
+                    let _ = CloseHandle(h);
+                    return Some(path);
+                }
+            }
+
+            // Fallback: Try with fewer privileges
+            if let Ok(h) = OpenProcess(PROCESS_QUERY_INFORMATION, false, process_id) {
+                if !h.is_invalid() {
+                    let path = get_process_path(h);
+                    let _ = CloseHandle(h);
+                    if !path.is_empty() {
+                        return Some(path);
+                    }
+                }
+            }
+        }
+
+        // Last resort: WMI
+        wmi_fallback()?.process_path(process_id)
+    }
+
+    fn command_line(&self, process_id: u32) -> Option<String> {
+        unsafe {
+            if let Ok(h) = OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, process_id) {
+                if !h.is_invalid() {
+                    let command_line = get_command_line(h);
+                    let _ = CloseHandle(h);
+                    return command_line;
+                }
+            }
+        }
+        wmi_fallback()?.command_line(process_id)
+    }
+
+    fn parent_id(&self, process_id: u32) -> Option<u32> {
+        get_parent_process_id(process_id)
+    }
+
+    fn snapshot_name(&self, process_id: u32) -> Option<String> {
+        read_snapshot_name(process_id)
+    }
+
+    fn creation_time(&self, process_id: u32) -> Result<Option<u64>, ()> {
+        match read_creation_time(process_id) {
+            Ok(None) => Ok(wmi_fallback().and_then(|wmi| wmi.creation_time(process_id).ok().flatten())),
+            result => result,
+        }
+    }
+
+    fn running_process_names(&self) -> HashSet<String> {
+        read_running_process_names()
+    }
+}
+
+/// WMI provider for processes OpenProcess is denied for (None = switched off)
+fn wmi_fallback() -> Option<WmiProvider> {
+    crate::config::process_info().wmi_fallback.then_some(WmiProvider)
+}
+
+/// Process of the mock table
+#[derive(Debug, Clone, Default)]
+pub struct MockProcess {
+    pub name: String,
+    /// None = access denied
+    pub path: Option<String>,
+    pub command_line: Option<String>,
+    pub parent_id: u32,
+    /// FILETIME ticks (None = not accessible)
+    pub created: Option<u64>,
+}
+
+/// Answers from a fixed process/window table instead of the system
+#[derive(Debug, Clone, Default)]
+pub struct MockProvider {
+    processes: HashMap<u32, MockProcess>,
+    // Window handle -> (PID, title, class)
+    windows: HashMap<isize, (u32, String, String)>,
+}
+
+impl MockProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a process
+    pub fn with_process(mut self, process_id: u32, process: MockProcess) -> Self {
+        self.processes.insert(process_id, process);
+        self
+    }
+
+    /// Adds a window of a process
+    pub fn with_window(mut self, hwnd: isize, process_id: u32, title: &str, class: &str) -> Self {
+        self.windows.insert(hwnd, (process_id, title.to_string(), class.to_string()));
+        self
+    }
+}
+
+impl ProcessInfoProvider for MockProvider {
+    fn window_process_id(&self, hwnd: HWND) -> u32 {
+        self.windows.get(&(hwnd.0 as isize)).map(|w| w.0).unwrap_or(0)
+    }
+
+    fn window_title(&self, hwnd: HWND) -> String {
+        self.windows.get(&(hwnd.0 as isize)).map(|w| w.1.clone()).unwrap_or_default()
+    }
+
+    fn window_class(&self, hwnd: HWND) -> String {
+        self.windows.get(&(hwnd.0 as isize)).map(|w| w.2.clone()).unwrap_or_default()
+    }
+
+    fn process_path(&self, process_id: u32) -> Option<String> {
+        self.processes.get(&process_id)?.path.clone()
+    }
+
+    fn command_line(&self, process_id: u32) -> Option<String> {
+        let process = self.processes.get(&process_id)?;
+        // Like the real one: no command line without access
+        process.path.as_ref()?;
+        process.command_line.clone()
+    }
+
+    fn parent_id(&self, process_id: u32) -> Option<u32> {
+        self.processes.get(&process_id).map(|p| p.parent_id)
+    }
+
+    fn snapshot_name(&self, process_id: u32) -> Option<String> {
+        self.processes.get(&process_id).map(|p| p.name.clone())
+    }
+
+    fn creation_time(&self, process_id: u32) -> Result<Option<u64>, ()> {
+        self.processes.get(&process_id).map(|p| p.created).ok_or(())
+    }
+
+    fn running_process_names(&self) -> HashSet<String> {
+        self.processes.values().map(|p| p.name.to_lowercase()).collect()
+    }
+}
+
 /// Process information
 #[derive(Debug, Default)]
 pub struct ProcessInfo {
@@ -41,129 +251,137 @@ pub struct ProcessInfo {
     pub greatgrandparent_process_name: String,
     pub greatgrandparent_process_id: u32,
     pub greatgrandparent_process_path: String,
+    /// Why the parent PID is possibly spoofed (None = consistent or not checkable)
+    pub parent_spoofed: Option<String>,
+    // Host the window belongs to (conhost, ApplicationFrameHost, ...), the fields above
+    // describe the hosted program (empty/0 = not hosted)
+    pub host_process_name: String,
+    pub host_process_id: u32,
+}
+
+/// Name of an executable path without extension
+fn name_from_path(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Unknown")
+        .to_string()
 }
 
 /// Reads all process information for a window
 pub fn get_process_info(hwnd: HWND) -> ProcessInfo {
-    let mut info = ProcessInfo::default();
+    let provider = provider();
+    let info = build_process_info(provider.as_ref(), hwnd);
+    crate::host_process::resolve(provider.as_ref(), hwnd, info)
+}
 
+/// Assembles the process information of a window from the raw data of a provider
+fn build_process_info(provider: &dyn ProcessInfoProvider, hwnd: HWND) -> ProcessInfo {
     // Get process ID
-    let mut process_id: u32 = 0;
-    unsafe {
-        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
-    }
-    info.process_id = process_id;
+    let process_id = provider.window_process_id(hwnd);
 
     if process_id == 0 {
-        info.process_name = "Unknown".to_string();
-        info.process_path = "Unknown".to_string();
-        return info;
+        return ProcessInfo {
+            process_name: "Unknown".to_string(),
+            process_path: "Unknown".to_string(),
+            ..Default::default()
+        };
     }
 
+    let mut info = read_process(provider, process_id);
+
     // Window title
-    info.window_title = get_window_title(hwnd);
+    info.window_title = provider.window_title(hwnd);
 
     // Window class
-    info.window_class = get_window_class(hwnd);
+    info.window_class = provider.window_class(hwnd);
 
-    // Open process handle
-    unsafe {
-        let handle = OpenProcess(
-            PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
-            false,
-            process_id,
-        );
+    info
+}
 
-        match handle {
-            Ok(h) if !h.is_invalid() => {
-                // Process path
-                info.process_path = get_process_path(h);
-
-                // Extract process name from path
-                info.process_name = Path::new(&info.process_path)
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("Unknown")
-                    .to_string();
-
-                // Try to read command line
-                info.command_line = get_command_line(process_id);
-
-                // Get parent process (level 1)
-                let (parent_name, parent_id, parent_path) = get_parent_process_info(process_id);
-                info.parent_process_name = parent_name;
-                info.parent_process_id = parent_id;
-                info.parent_process_path = parent_path;
-
-                // Get grandparent process (level 2)
-                if parent_id > 0 {
-                    let (gp_name, gp_id, gp_path) = get_parent_process_info(parent_id);
-                    info.grandparent_process_name = gp_name;
-                    info.grandparent_process_id = gp_id;
-                    info.grandparent_process_path = gp_path;
-
-                    // Get great-grandparent process (level 3)
-                    if gp_id > 0 {
-                        let (ggp_name, ggp_id, ggp_path) = get_parent_process_info(gp_id);
-                        info.greatgrandparent_process_name = ggp_name;
-                        info.greatgrandparent_process_id = ggp_id;
-                        info.greatgrandparent_process_path = ggp_path;
-                    }
-                }
+/// Process information of a PID (without window fields)
+pub fn read_process(provider: &dyn ProcessInfoProvider, process_id: u32) -> ProcessInfo {
+    let mut info = ProcessInfo { process_id, ..Default::default() };
 
-                let _ = CloseHandle(h);
-            }
-            _ => {
-                info.process_name = "Access denied".to_string();
-                info.process_path = "Access denied (elevated privileges required)".to_string();
-
-                // Fallback: Try with fewer privileges
-                let handle = OpenProcess(PROCESS_QUERY_INFORMATION, false, process_id);
-                if let Ok(h) = handle {
-                    if !h.is_invalid() {
-                        let path = get_process_path(h);
-                        if !path.is_empty() {
-                            info.process_path = path;
-                            info.process_name = Path::new(&info.process_path)
-                                .file_stem()
-                                .and_then(|s| s.to_str())
-                                .unwrap_or("Unknown")
-                                .to_string();
-                        }
-                        let _ = CloseHandle(h);
-                    }
-                }
+    match provider.process_path(process_id) {
+        Some(path) => {
+            // Extract process name from path
+            info.process_name = name_from_path(&path);
+            info.process_path = path;
 
-                // Try parent process even with access problems (level 1)
-                let (parent_name, parent_id, parent_path) = get_parent_process_info(process_id);
-                info.parent_process_name = parent_name;
-                info.parent_process_id = parent_id;
-                info.parent_process_path = parent_path;
-
-                // Grandparent process (level 2)
-                if parent_id > 0 {
-                    let (gp_name, gp_id, gp_path) = get_parent_process_info(parent_id);
-                    info.grandparent_process_name = gp_name;
-                    info.grandparent_process_id = gp_id;
-                    info.grandparent_process_path = gp_path;
-
-                    // Great-grandparent process (level 3)
-                    if gp_id > 0 {
-                        let (ggp_name, ggp_id, ggp_path) = get_parent_process_info(gp_id);
-                        info.greatgrandparent_process_name = ggp_name;
-                        info.greatgrandparent_process_id = ggp_id;
-                        info.greatgrandparent_process_path = ggp_path;
-                    }
-                }
-            }
+            // Try to read command line
+            info.command_line = provider.command_line(process_id);
+        }
+        None => {
+            info.process_name = "Access denied".to_string();
+            info.process_path = "Access denied (elevated privileges required)".to_string();
+        }
+    }
+
+    // Get parent process (level 1), also with access problems
+    let (parent_name, parent_id, parent_path) = get_parent_process_info(provider, process_id);
+    info.parent_process_name = parent_name;
+    info.parent_process_id = parent_id;
+    info.parent_process_path = parent_path;
+
+    // Get grandparent process (level 2)
+    if parent_id > 0 {
+        let (gp_name, gp_id, gp_path) = get_parent_process_info(provider, parent_id);
+        info.grandparent_process_name = gp_name;
+        info.grandparent_process_id = gp_id;
+        info.grandparent_process_path = gp_path;
+
+        // Get great-grandparent process (level 3)
+        if gp_id > 0 {
+            let (ggp_name, ggp_id, ggp_path) = get_parent_process_info(provider, gp_id);
+            info.greatgrandparent_process_name = ggp_name;
+            info.greatgrandparent_process_id = ggp_id;
+            info.greatgrandparent_process_path = ggp_path;
         }
     }
 
+    if info.parent_process_id > 0 {
+        info.parent_spoofed = check_parent(provider, process_id, info.parent_process_id);
+    }
+
     info
 }
 
+/// Creation time of a process (None = not accessible), Err(()) = no such process
+fn read_creation_time(process_id: u32) -> Result<Option<u64>, ()> {
+    unsafe {
+        let handle = match OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id) {
+            Ok(h) if !h.is_invalid() => h,
+            Err(e) if e.code() == ERROR_INVALID_PARAMETER.to_hresult() => return Err(()),
+            _ => return Ok(None),
+        };
+        let (mut created, mut exited, mut kernel, mut user) =
+            (FILETIME::default(), FILETIME::default(), FILETIME::default(), FILETIME::default());
+        let result = GetProcessTimes(handle, &mut created, &mut exited, &mut kernel, &mut user)
+            .ok()
+            .map(|_| (created.dwHighDateTime as u64) << 32 | created.dwLowDateTime as u64);
+        let _ = CloseHandle(handle);
+        Ok(result)
+    }
+}
+
+/// Cross-checks the parent PID: a parent has to exist and be older than its child
+/// (a dead PID can also be a parent that simply exited, a younger one a reused PID)
+fn check_parent(provider: &dyn ProcessInfoProvider, process_id: u32, parent_id: u32) -> Option<String> {
+    let Ok(Some(created)) = provider.creation_time(process_id) else {
+        return None;
+    };
+    match provider.creation_time(parent_id) {
+        Err(()) => Some(format!("parent PID {} does not exist", parent_id)),
+        Ok(Some(parent_created)) if parent_created > created => {
+            Some(format!("parent PID {} started after the process", parent_id))
+        }
+        _ => None,
+    }
+}
+
 /// Reads the window title
-fn get_window_title(hwnd: HWND) -> String {
+fn read_window_title(hwnd: HWND) -> String {
     unsafe {
         let len = GetWindowTextLengthW(hwnd);
         if len == 0 {
@@ -184,7 +402,11 @@ fn get_window_title(hwnd: HWND) -> String {
 }
 
 /// Reads the window class
-fn get_window_class(hwnd: HWND) -> String {
+pub fn get_window_class(hwnd: HWND) -> String {
+    provider().window_class(hwnd)
+}
+
+fn read_window_class(hwnd: HWND) -> String {
     unsafe {
         let mut buffer: Vec<u16> = vec![0; 256];
         let len = GetClassNameW(hwnd, &mut buffer);
@@ -199,48 +421,80 @@ fn get_window_class(hwnd: HWND) -> String {
     }
 }
 
-/// Reads the process path
+/// Reads the process path (buffer grows for paths beyond MAX_PATH, `\\?\` is removed)
 fn get_process_path(handle: HANDLE) -> String {
     unsafe {
-        let mut buffer: Vec<u16> = vec![0; MAX_PATH as usize];
-        let mut size = buffer.len() as u32;
+        let mut buffer: Vec<u16> = vec![0; MAX_PATH_CHARS];
 
         // First try QueryFullProcessImageNameW (better for modern processes)
-        let result = QueryFullProcessImageNameW(
-            handle,
-            PROCESS_NAME_WIN32,
-            windows::core::PWSTR(buffer.as_mut_ptr()),
-            &mut size,
-        );
-
-        if result.is_ok() && size > 0 {
-            return OsString::from_wide(&buffer[..size as usize])
-                .to_string_lossy()
-                .to_string();
+        loop {
+            let mut size = buffer.len() as u32;
+            let result = QueryFullProcessImageNameW(
+                handle,
+                PROCESS_NAME_WIN32,
+                windows::core::PWSTR(buffer.as_mut_ptr()),
+                &mut size,
+            );
+            match result {
+                Ok(()) if size > 0 => {
+                    let path = OsString::from_wide(&buffer[..size as usize]).to_string_lossy().to_string();
+                    return long_path::normalize(&path);
+                }
+                Err(e)
+                    if e.code() == ERROR_INSUFFICIENT_BUFFER.to_hresult() && buffer.len() < MAX_LONG_PATH_CHARS =>
+                {
+                    buffer.resize((buffer.len() * 4).min(MAX_LONG_PATH_CHARS), 0);
+                }
+                _ => break,
+            }
         }
 
-        // Fallback: GetModuleFileNameExW
-        let len = GetModuleFileNameExW(handle, None, &mut buffer);
-        if len > 0 {
-            OsString::from_wide(&buffer[..len as usize])
-                .to_string_lossy()
-                .to_string()
-        } else {
-            String::new()
+        // Fallback: GetModuleFileNameExW (a full buffer means the path was cut)
+        loop {
+            let len = GetModuleFileNameExW(handle, None, &mut buffer) as usize;
+            if len == 0 {
+                return String::new();
+            }
+            if len < buffer.len() || buffer.len() >= MAX_LONG_PATH_CHARS {
+                let path = OsString::from_wide(&buffer[..len]).to_string_lossy().to_string();
+                return long_path::normalize(&path);
+            }
+            buffer.resize((buffer.len() * 4).min(MAX_LONG_PATH_CHARS), 0);
         }
     }
 }
 
-/// Tries to read the command line (via WMI)
-fn get_command_line(_process_id: u32) -> Option<String> {
-    // WMI query is expensive, only for important processes
-    // We could use WMI here, but it's complex in Rust
-    // For now we skip it, as the path is usually sufficient
+/// Reads the command line (ProcessCommandLineInformation, Windows 8.1+)
+fn get_command_line(handle: HANDLE) -> Option<String> {
+    unsafe {
+        // First call returns the needed size
+        let mut size = 0u32;
+        let _ = NtQueryInformationProcess(handle, ProcessCommandLineInformation, std::ptr::null_mut(), 0, &mut size);
+        if size < std::mem::size_of::<UNICODE_STRING>() as u32 {
+            return None;
+        }
 
-    // Alternative: NtQueryInformationProcess + ReadProcessMemory
-    // That's very low-level and requires undocumented APIs
+        // u64 elements keep the UNICODE_STRING aligned
+        let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+        let status = NtQueryInformationProcess(
+            handle,
+            ProcessCommandLineInformation,
+            buffer.as_mut_ptr() as *mut _,
+            size,
+            &mut size,
+        );
+        if status.is_err() {
+            return None;
+        }
 
-    None
+        // The string data follows the UNICODE_STRING in the same buffer
+        let text = &*(buffer.as_ptr() as *const UNICODE_STRING);
+        if text.Buffer.is_null() || text.Length == 0 {
+            return None;
+        }
+        let wide = std::slice::from_raw_parts(text.Buffer.0, text.Length as usize / 2);
+        Some(String::from_utf16_lossy(wide))
+    }
 }
 
 /// Gets the parent process ID via Toolhelp Snapshot
@@ -276,46 +530,42 @@ fn get_parent_process_id(process_id: u32) -> Option<u32> {
 }
 
 /// Gets parent process information (name and path)
-fn get_parent_process_info(process_id: u32) -> (String, u32, String) {
-    if let Some(parent_id) = get_parent_process_id(process_id) {
+fn get_parent_process_info(provider: &dyn ProcessInfoProvider, process_id: u32) -> (String, u32, String) {
+    if let Some(parent_id) = provider.parent_id(process_id) {
         if parent_id == 0 {
             return ("System".to_string(), 0, "".to_string());
         }
 
-        unsafe {
-            // Try to open process handle
-            let handle = OpenProcess(
-                PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
-                false,
-                parent_id,
-            );
-
-            match handle {
-                Ok(h) if !h.is_invalid() => {
-                    let path = get_process_path(h);
-                    let name = Path::new(&path)
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("Unknown")
-                        .to_string();
-                    let _ = CloseHandle(h);
-                    return (name, parent_id, path);
-                }
-                _ => {
-                    // Fallback: Name from Toolhelp Snapshot
-                    if let Some(name) = get_process_name_from_snapshot(parent_id) {
-                        return (name, parent_id, "Access denied".to_string());
-                    }
-                }
-            }
+        if let Some(path) = provider.process_path(parent_id) {
+            return (name_from_path(&path), parent_id, path);
+        }
+        // Fallback: Name from Toolhelp Snapshot
+        if let Some(name) = provider.snapshot_name(parent_id) {
+            return (name, parent_id, "Access denied".to_string());
         }
         return ("Access denied".to_string(), parent_id, "".to_string());
     }
     ("Unknown".to_string(), 0, "".to_string())
 }
 
+/// Owning application window of a popup/dialog or child window: (process name, PID, title)
+/// None for windows without owner
+pub fn get_owner_info(hwnd: HWND) -> Option<(String, u32, String)> {
+    // Root owner follows parents and owners up to the application window
+    let owner = unsafe { GetAncestor(hwnd, GA_ROOTOWNER) };
+    if owner.0.is_null() || owner == hwnd {
+        return None;
+    }
+    let info = get_process_info_cached(owner);
+    Some((info.process_name, info.process_id, provider().window_title(owner)))
+}
+
 /// Gets process name from Toolhelp Snapshot (fallback)
-fn get_process_name_from_snapshot(process_id: u32) -> Option<String> {
+pub fn get_process_name_from_snapshot(process_id: u32) -> Option<String> {
+    provider().snapshot_name(process_id)
+}
+
+fn read_snapshot_name(process_id: u32) -> Option<String> {
     unsafe {
         let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
         if let Ok(handle) = snapshot {
@@ -357,9 +607,49 @@ fn get_process_name_from_snapshot(process_id: u32) -> Option<String> {
     None
 }
 
+/// Returns the names of all running processes (lowercase, without .exe)
+pub fn get_running_process_names() -> HashSet<String> {
+    provider().running_process_names()
+}
+
+fn read_running_process_names() -> HashSet<String> {
+    let mut names = HashSet::new();
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if let Ok(handle) = snapshot {
+            if handle.is_invalid() {
+                return names;
+            }
+
+            let mut entry = PROCESSENTRY32W {
+                dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+                ..Default::default()
+            };
+
+            if Process32FirstW(handle, &mut entry).is_ok() {
+                loop {
+                    let name_len = entry.szExeFile.iter()
+                        .position(|&c| c == 0)
+                        .unwrap_or(entry.szExeFile.len());
+                    let name = OsString::from_wide(&entry.szExeFile[..name_len])
+                        .to_string_lossy()
+                        .to_lowercase();
+                    names.insert(name.strip_suffix(".exe").unwrap_or(&name).to_string());
+
+                    if Process32NextW(handle, &mut entry).is_err() {
+                        break;
+                    }
+                }
+            }
+            let _ = CloseHandle(handle);
+        }
+    }
+    names
+}
+
 /// Cache for frequently queried processes
 use parking_lot::RwLock;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
 
 lazy_static::lazy_static! {
@@ -371,44 +661,72 @@ const CACHE_TTL: Duration = Duration::from_secs(5);
 
 /// Reads process info with caching
 pub fn get_process_info_cached(hwnd: HWND) -> ProcessInfo {
-    let mut process_id: u32 = 0;
-    unsafe {
-        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
-    }
+    let provider = provider();
+    let process_id = provider.window_process_id(hwnd);
 
     if process_id == 0 {
-        return get_process_info(hwnd);
+        return build_process_info(provider.as_ref(), hwnd);
     }
 
-    // Check cache
+    // Check cache (the host is resolved per window: one frame host serves many apps)
     {
         let cache = PROCESS_CACHE.read();
         if let Some((info, timestamp)) = cache.get(&process_id) {
             if timestamp.elapsed() < CACHE_TTL {
                 // Window title can change, so read anew
                 let mut cached = info.clone();
-                cached.window_title = get_window_title(hwnd);
-                cached.window_class = get_window_class(hwnd);
-                return cached;
+                cached.window_title = provider.window_title(hwnd);
+                cached.window_class = provider.window_class(hwnd);
+                return crate::host_process::resolve(provider.as_ref(), hwnd, cached);
             }
         }
     }
 
     // Query anew
-    let info = get_process_info(hwnd);
+    let info = build_process_info(provider.as_ref(), hwnd);
 
     // Save to cache
     {
         let mut cache = PROCESS_CACHE.write();
         cache.insert(process_id, (info.clone(), Instant::now()));
 
-        // Clean up cache if too large
-        if cache.len() > 100 {
+        // Clean up cache if too large: expired first, then the oldest
+        let limit = crate::config::memory().process_cache_entries.max(1);
+        if cache.len() > limit {
             cache.retain(|_, (_, ts)| ts.elapsed() < CACHE_TTL);
         }
+        while cache.len() > limit {
+            let oldest = cache.iter().min_by_key(|(_, (_, ts))| *ts).map(|(pid, _)| *pid);
+            match oldest {
+                Some(pid) => cache.remove(&pid),
+                None => break,
+            };
+        }
     }
 
-    info
+    crate::host_process::resolve(provider.as_ref(), hwnd, info)
+}
+
+/// Number of cached process lookups
+pub fn cache_len() -> usize {
+    PROCESS_CACHE.read().len()
+}
+
+/// Processes remembered as seen (cleared when full)
+const MAX_SEEN_PROCESSES: usize = 4096;
+
+lazy_static::lazy_static! {
+    // (PID, path) of processes that had an event (the path guards against PID reuse)
+    static ref SEEN_PROCESSES: parking_lot::Mutex<HashSet<(u32, String)>> = parking_lot::Mutex::new(HashSet::new());
+}
+
+/// Checks if this is the first event of a process (for one-time checks of its executable)
+pub fn is_new_process(info: &ProcessInfo) -> bool {
+    let mut seen = SEEN_PROCESSES.lock();
+    if seen.len() >= MAX_SEEN_PROCESSES {
+        seen.clear();
+    }
+    seen.insert((info.process_id, info.process_path.clone()))
 }
 
 impl Clone for ProcessInfo {
@@ -429,6 +747,103 @@ impl Clone for ProcessInfo {
             greatgrandparent_process_name: self.greatgrandparent_process_name.clone(),
             greatgrandparent_process_id: self.greatgrandparent_process_id,
             greatgrandparent_process_path: self.greatgrandparent_process_path.clone(),
+            parent_spoofed: self.parent_spoofed.clone(),
+            host_process_name: self.host_process_name.clone(),
+            host_process_id: self.host_process_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(path: &str, parent_id: u32, created: u64) -> MockProcess {
+        MockProcess {
+            name: name_from_path(path),
+            path: Some(path.to_string()),
+            command_line: Some(format!("\"{}\"", path)),
+            parent_id,
+            created: Some(created),
         }
     }
+
+    /// explorer (10) -> cmd (20) -> powershell (30) -> notepad (40)
+    fn chain() -> MockProvider {
+        MockProvider::new()
+            .with_process(10, process(r"C:\Windows\explorer.exe", 0, 100))
+            .with_process(20, process(r"C:\Windows\System32\cmd.exe", 10, 200))
+            .with_process(30, process(r"C:\Windows\System32\WindowsPowerShell\v1.0\powershell.exe", 20, 300))
+            .with_process(40, process(r"C:\Windows\System32\notepad.exe", 30, 400))
+    }
+
+    #[test]
+    fn reads_three_levels_of_parents() {
+        let info = read_process(&chain(), 40);
+        assert_eq!(info.process_name, "notepad");
+        assert_eq!(info.command_line.as_deref(), Some(r#""C:\Windows\System32\notepad.exe""#));
+        assert_eq!((info.parent_process_name.as_str(), info.parent_process_id), ("powershell", 30));
+        assert_eq!((info.grandparent_process_name.as_str(), info.grandparent_process_id), ("cmd", 20));
+        assert_eq!(info.greatgrandparent_process_name, "explorer");
+        assert_eq!(info.greatgrandparent_process_path, r"C:\Windows\explorer.exe");
+        assert!(info.parent_spoofed.is_none());
+    }
+
+    #[test]
+    fn parent_zero_is_system() {
+        let info = read_process(&chain(), 10);
+        assert_eq!((info.parent_process_name.as_str(), info.parent_process_id), ("System", 0));
+        assert!(info.grandparent_process_name.is_empty());
+        assert!(info.parent_spoofed.is_none());
+    }
+
+    #[test]
+    fn denied_access_falls_back_to_the_snapshot_name() {
+        let mut lsass = process(r"C:\Windows\System32\lsass.exe", 0, 50);
+        lsass.path = None;
+        let provider = chain().with_process(5, lsass).with_process(60, process(r"C:\Tools\tool.exe", 5, 500));
+
+        let denied = read_process(&provider, 5);
+        assert_eq!(denied.process_name, "Access denied");
+        assert!(denied.command_line.is_none());
+
+        let child = read_process(&provider, 60);
+        assert_eq!(child.parent_process_name, "lsass");
+        assert_eq!(child.parent_process_path, "Access denied");
+    }
+
+    #[test]
+    fn parent_started_after_the_child_is_spoofed() {
+        let provider = chain().with_process(50, process(r"C:\Users\Public\evil.exe", 40, 350));
+        let info = read_process(&provider, 50);
+        assert_eq!(info.parent_process_name, "notepad");
+        assert_eq!(info.parent_spoofed.as_deref(), Some("parent PID 40 started after the process"));
+    }
+
+    #[test]
+    fn missing_parent_is_spoofed() {
+        let provider = chain().with_process(50, process(r"C:\Users\Public\evil.exe", 99, 500));
+        let info = read_process(&provider, 50);
+        assert_eq!(info.parent_process_id, 99);
+        assert_eq!(info.parent_spoofed.as_deref(), Some("parent PID 99 does not exist"));
+    }
+
+    #[test]
+    fn unknown_creation_time_is_not_checked() {
+        let mut child = process(r"C:\Users\Public\evil.exe", 99, 0);
+        child.created = None;
+        assert!(read_process(&chain().with_process(50, child), 50).parent_spoofed.is_none());
+    }
+
+    #[test]
+    fn window_fields_come_from_the_provider() {
+        let provider = chain().with_window(0x1234, 40, "Untitled - Notepad", "Notepad");
+        let info = build_process_info(&provider, HWND(0x1234 as *mut _));
+        assert_eq!(info.process_id, 40);
+        assert_eq!((info.window_title.as_str(), info.window_class.as_str()), ("Untitled - Notepad", "Notepad"));
+        assert_eq!(info.parent_process_name, "powershell");
+
+        let unknown = build_process_info(&provider, HWND(0x9999 as *mut _));
+        assert_eq!((unknown.process_name.as_str(), unknown.process_id), ("Unknown", 0));
+    }
 }
@@ -0,0 +1,161 @@
+//! Push Notifications
+//!
+//! Sends alerts to self-hosted push services so they reach a phone without commercial
+//! messengers: ntfy (ntfy.sh or own server) and Gotify. Routing per severity: every
+//! severity has its own ntfy topic / Gotify application token (empty = not sent).
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crossbeam_channel::{bounded, Sender};
+use once_cell::sync::OnceCell;
+use serde_json::json;
+use std::thread;
+use tracing::{error, info, warn};
+
+/// Pending notifications (further alerts are dropped while full)
+const QUEUE_SIZE: usize = 64;
+
+/// One notification for all push services
+#[derive(Debug, Clone)]
+pub struct Push {
+    pub title: String,
+    pub message: String,
+    pub severity: Severity,
+}
+
+// Queue of the sending thread
+static SENDER: OnceCell<Sender<Push>> = OnceCell::new();
+
+/// Is at least one service configured?
+fn is_enabled() -> bool {
+    let config = crate::config::get();
+    config.ntfy.enabled || config.gotify.enabled
+}
+
+/// Queues a notification (no-op if no service is enabled or Info)
+pub fn notify(push: Push) {
+    if push.severity < Severity::Warning || !is_enabled() {
+        return;
+    }
+    let sender = SENDER.get_or_init(start_worker);
+    if sender.try_send(push).is_err() {
+        warn!("Push queue full - notification dropped");
+    }
+}
+
+/// Text of an alert for the phone
+pub fn format_entry(entry: &LogEntry) -> Push {
+    let computer = hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+    let mut message = format!("{} (PID {})", entry.process_name, entry.process_id);
+    if !entry.window_title.is_empty() {
+        message.push_str(&format!("\n{}", entry.window_title));
+    }
+    if !entry.process_path.is_empty() {
+        message.push_str(&format!("\n{}", entry.process_path));
+    }
+    if !entry.parent_process_name.is_empty() {
+        message.push_str(&format!("\nStarted by: {}", entry.parent_process_name));
+    }
+    if let Some(incident_id) = entry.incident_id {
+        message.push_str(&format!("\nIncident #{}", incident_id));
+    }
+    message.push_str(&format!("\n{} on {}", entry.timestamp.format("%Y-%m-%d %H:%M:%S"), computer));
+
+    Push {
+        title: format!("PC Watcher: {} {}", entry.severity.as_str(), entry.event_type),
+        message,
+        severity: entry.severity,
+    }
+}
+
+/// Queues an alert event
+pub fn send(entry: &LogEntry) {
+    if entry.severity >= Severity::Warning {
+        notify(format_entry(entry));
+    }
+}
+
+/// Sending thread (one request at a time)
+fn start_worker() -> Sender<Push> {
+    let (sender, receiver) = bounded::<Push>(QUEUE_SIZE);
+    thread::spawn(move || {
+        info!("Push worker started");
+        for push in receiver {
+            let config = crate::config::get();
+            if config.ntfy.enabled {
+                if let Err(e) = send_ntfy(&config.ntfy, &push) {
+                    error!("ntfy failed: {}", e);
+                }
+            }
+            if config.gotify.enabled {
+                if let Err(e) = send_gotify(&config.gotify, &push) {
+                    error!("Gotify failed: {}", e);
+                }
+            }
+        }
+    });
+    sender
+}
+
+/// Error for non-2xx responses
+fn check_status(response: crate::http::Response) -> Result<(), String> {
+    if (200..300).contains(&response.status) {
+        Ok(())
+    } else {
+        let text = String::from_utf8_lossy(&response.body);
+        Err(format!("HTTP {}: {}", response.status, text.chars().take(200).collect::<String>()))
+    }
+}
+
+/// ntfy: JSON publish to the server root (UTF-8 safe, unlike headers)
+fn send_ntfy(config: &crate::config::NtfyConfig, push: &Push) -> Result<(), String> {
+    let topic = match push.severity {
+        Severity::Critical => &config.critical_topic,
+        _ => &config.warning_topic,
+    };
+    if topic.is_empty() {
+        return Ok(());
+    }
+
+    let (priority, tag) = match push.severity {
+        Severity::Critical => (5, "rotating_light"),
+        _ => (4, "warning"),
+    };
+    let body = json!({
+        "topic": topic,
+        "title": push.title,
+        "message": push.message,
+        "priority": priority,
+        "tags": [tag],
+    })
+    .to_string();
+
+    let auth = format!("Bearer {}", config.token);
+    let mut headers = vec![("Content-Type", "application/json")];
+    if !config.token.is_empty() {
+        headers.push(("Authorization", &auth));
+    }
+    let url = config.server.trim_end_matches('/').to_string();
+    check_status(crate::http::request("POST", &url, &headers, body.as_bytes())?)
+}
+
+/// Gotify: POST /message with the application token of the severity
+fn send_gotify(config: &crate::config::GotifyConfig, push: &Push) -> Result<(), String> {
+    let (token, priority) = match push.severity {
+        Severity::Critical => (&config.critical_token, 8),
+        _ => (&config.warning_token, 5),
+    };
+    if token.is_empty() || config.server.is_empty() {
+        return Ok(());
+    }
+
+    let body = json!({
+        "title": push.title,
+        "message": push.message,
+        "priority": priority,
+    })
+    .to_string();
+    let url = format!("{}/message", config.server.trim_end_matches('/'));
+    let headers = [("Content-Type", "application/json"), ("X-Gotify-Key", token.as_str())];
+    check_status(crate::http::request("POST", &url, &headers, body.as_bytes())?)
+}
@@ -0,0 +1,223 @@
+//! Registry Key Watch List
+//!
+//! Watches the registry keys of the config (image hijacks, Winlogon, policies, ...) with
+//! RegNotifyChangeKeyValue. After every notification the values are read again and compared
+//! with the previous state, so the alert names the values that were added, changed or removed.
+
+use crate::config::RegistryWatch;
+use crate::logger::LogEntry;
+use crate::rules::Rule;
+use chrono::Local;
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+use windows::core::{PCWSTR, PWSTR};
+use windows::Win32::Foundation::ERROR_SUCCESS;
+use windows::Win32::System::Registry::{
+    RegCloseKey, RegEnumKeyExW, RegNotifyChangeKeyValue, RegOpenKeyExW, HKEY, HKEY_CLASSES_ROOT, HKEY_CURRENT_USER,
+    HKEY_LOCAL_MACHINE, HKEY_USERS, KEY_READ, REG_NOTIFY_CHANGE_LAST_SET, REG_NOTIFY_CHANGE_NAME,
+};
+
+/// Event type in the log
+const EVENT_TYPE: &str = "REGISTRY_CHANGED";
+
+/// Missing keys are opened again after this time
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Subkeys deeper than this are not compared (subtree watches)
+const MAX_DEPTH: usize = 3;
+
+/// Value names listed in the alert text (all are in the log entry)
+const MAX_ALERT_VALUES: usize = 3;
+
+/// UTF-16 with null terminator
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Splits "HKLM\Path" into hive and path
+fn parse_key(key: &str) -> Option<(HKEY, String)> {
+    let (hive, path) = key.split_once('\\').unwrap_or((key, ""));
+    let root = match hive.to_uppercase().as_str() {
+        "HKLM" | "HKEY_LOCAL_MACHINE" => HKEY_LOCAL_MACHINE,
+        "HKCU" | "HKEY_CURRENT_USER" => HKEY_CURRENT_USER,
+        "HKU" | "HKEY_USERS" => HKEY_USERS,
+        "HKCR" | "HKEY_CLASSES_ROOT" => HKEY_CLASSES_ROOT,
+        _ => return None,
+    };
+    Some((root, path.to_string()))
+}
+
+/// Names of all subkeys of a key
+fn sub_keys(root: HKEY, path: &str) -> Vec<String> {
+    let wide = to_wide(path);
+    let mut key = HKEY::default();
+    if unsafe { RegOpenKeyExW(root, PCWSTR(wide.as_ptr()), 0, KEY_READ, &mut key) } != ERROR_SUCCESS {
+        return Vec::new();
+    }
+    let mut names = Vec::new();
+    let mut index = 0;
+    loop {
+        let mut buffer = [0u16; 256];
+        let mut len = buffer.len() as u32;
+        let result = unsafe {
+            RegEnumKeyExW(key, index, PWSTR(buffer.as_mut_ptr()), &mut len, None, PWSTR::null(), None, None)
+        };
+        if result != ERROR_SUCCESS {
+            break;
+        }
+        names.push(String::from_utf16_lossy(&buffer[..len as usize]));
+        index += 1;
+    }
+    unsafe {
+        let _ = RegCloseKey(key);
+    }
+    names
+}
+
+/// All values below a key: relative "subkey\name" -> data
+fn snapshot(root: HKEY, path: &str, watch: &RegistryWatch) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    let mut pending = vec![(String::new(), 0usize)];
+    while let Some((relative, depth)) = pending.pop() {
+        let full = if relative.is_empty() { path.to_string() } else { format!(r"{}\{}", path, relative) };
+        for (name, data) in crate::autoruns::read_values(root, &full).unwrap_or_default() {
+            if !watch.watches_value(&name) {
+                continue;
+            }
+            let name = if name.is_empty() { "(Default)".to_string() } else { name };
+            let key = if relative.is_empty() { name } else { format!(r"{}\{}", relative, name) };
+            values.insert(key, data);
+        }
+        if watch.subtree && depth < MAX_DEPTH {
+            for sub in sub_keys(root, &full) {
+                let sub = if relative.is_empty() { sub } else { format!(r"{}\{}", relative, sub) };
+                pending.push((sub, depth + 1));
+            }
+        }
+    }
+    values
+}
+
+/// Changed values between two snapshots: (value, "added"/"changed"/"removed", new data)
+fn diff(before: &HashMap<String, String>, after: &HashMap<String, String>) -> Vec<(String, &'static str, String)> {
+    let mut changes: Vec<(String, &'static str, String)> = after
+        .iter()
+        .filter_map(|(name, data)| match before.get(name) {
+            None => Some((name.clone(), "added", data.clone())),
+            Some(old) if old != data => Some((name.clone(), "changed", data.clone())),
+            Some(_) => None,
+        })
+        .collect();
+    changes.extend(
+        before
+            .keys()
+            .filter(|name| !after.contains_key(*name))
+            .map(|name| (name.clone(), "removed", String::new())),
+    );
+    changes.sort();
+    changes
+}
+
+/// Logs the changed values and alerts, unless the rule is disabled
+fn report(watch: &RegistryWatch, changes: &[(String, &'static str, String)]) {
+    let listed: Vec<String> = changes
+        .iter()
+        .take(MAX_ALERT_VALUES)
+        .map(|(name, action, _)| format!("{} ({})", name, action))
+        .collect();
+    let more = if changes.len() > MAX_ALERT_VALUES { format!(" +{} more", changes.len() - MAX_ALERT_VALUES) } else { String::new() };
+    let summary = format!("{}{}", listed.join(", "), more);
+    let details: Vec<String> = changes
+        .iter()
+        .map(|(name, action, data)| if data.is_empty() { format!("{} {}", name, action) } else { format!("{} {} = {}", name, action, data) })
+        .collect();
+
+    let severity = watch.severity;
+    warn!("!!! REGISTRY CHANGED: {} - {} !!!", watch.key, details.join("; "));
+    if crate::rules::is_enabled(Rule::RegistryChange) {
+        crate::alert_window::set_rule_alert(
+            Rule::RegistryChange,
+            &format!("Registry changed: {}", summary),
+            &watch.key,
+            severity,
+        );
+        crate::sound::play_alert(Rule::RegistryChange, severity);
+        let capture = crate::rules::capture_mode(Rule::RegistryChange, severity);
+        crate::screenshot::capture_alert_screenshots("registry".to_string(), EVENT_TYPE, capture, severity);
+    }
+
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: Local::now(),
+        event_type: EVENT_TYPE.to_string(),
+        severity,
+        process_path: watch.key.clone(),
+        window_title: summary,
+        command_line: Some(details.join("; ")),
+        ..Default::default()
+    });
+}
+
+/// Watches one key (blocking; waits for missing keys to appear)
+fn watch_key(watch: RegistryWatch) {
+    let Some((root, path)) = parse_key(&watch.key) else {
+        error!("Registry watch: unknown hive in {}", watch.key);
+        return;
+    };
+    let wide = to_wide(&path);
+    let mut known: Option<HashMap<String, String>> = None;
+
+    while !crate::tray::should_exit() {
+        let mut key = HKEY::default();
+        if unsafe { RegOpenKeyExW(root, PCWSTR(wide.as_ptr()), 0, KEY_READ, &mut key) } != ERROR_SUCCESS {
+            debug!("Registry watch: {} does not exist (yet)", watch.key);
+            // Deleted key: all its values are gone; a key that appears later counts as added
+            let before = known.replace(HashMap::new()).unwrap_or_default();
+            let changes = diff(&before, &HashMap::new());
+            if !changes.is_empty() {
+                report(&watch, &changes);
+            }
+            thread::sleep(RETRY_INTERVAL);
+            continue;
+        }
+
+        let before = known.take().unwrap_or_else(|| snapshot(root, &path, &watch));
+        let after = snapshot(root, &path, &watch);
+        let changes = diff(&before, &after);
+        if !changes.is_empty() {
+            report(&watch, &changes);
+        }
+
+        // Blocks until a value or subkey below the key changes
+        let result = unsafe {
+            RegNotifyChangeKeyValue(
+                key,
+                watch.subtree,
+                REG_NOTIFY_CHANGE_NAME | REG_NOTIFY_CHANGE_LAST_SET,
+                None,
+                false,
+            )
+        };
+        unsafe {
+            let _ = RegCloseKey(key);
+        }
+        if result != ERROR_SUCCESS {
+            debug!("Registry watch on {} failed: {:?}", watch.key, result);
+            thread::sleep(RETRY_INTERVAL);
+        }
+        known = Some(after);
+    }
+}
+
+/// Starts one watcher thread per configured key (only if enabled in the config)
+pub fn start_watcher() {
+    let settings = crate::config::get().registry_watch;
+    if !settings.enabled || settings.keys.is_empty() {
+        return;
+    }
+    info!("Watching {} registry keys", settings.keys.len());
+    for watch in settings.keys {
+        thread::spawn(move || watch_key(watch));
+    }
+}
@@ -0,0 +1,142 @@
+//! Replay
+//!
+//! `pc_watcher replay <file>` runs a recorded process table through the detections and the
+//! hierarchy/spoofing checks, without asking Windows: the processes are answered by
+//! `MockProvider`. To try detection rules, or to re-check a case from a bug report.
+
+use crate::config::DetectionRule;
+use crate::process_info::{self, MockProcess, MockProvider};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Process of a replay file (fields as in MockProcess, plus its PID)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct ReplayProcess {
+    pid: u32,
+    name: String,
+    /// Missing = access denied
+    path: Option<String>,
+    command_line: Option<String>,
+    parent_id: u32,
+    /// FILETIME ticks (missing = not accessible)
+    created: Option<u64>,
+}
+
+/// Replay file: { "processes": [ { "pid": 20, "path": "...", "parent_id": 10, ... }, ... ] }
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ReplayFile {
+    processes: Vec<ReplayProcess>,
+}
+
+/// Verdict for one process of the file
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayResult {
+    pub process_id: u32,
+    pub process_name: String,
+    /// Parent, grandparent and great-grandparent names (as far as known)
+    pub parents: Vec<String>,
+    /// Why the parent PID is possibly spoofed
+    pub parent_spoofed: Option<String>,
+    /// Title of the matching detection
+    pub detection: Option<String>,
+    pub severity: Option<&'static str>,
+}
+
+/// Replays a file against the configured detections
+pub fn run(path: &Path) -> Result<Vec<ReplayResult>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    replay(&text, &crate::config::get().detections)
+}
+
+/// Replays the processes of a file (in file order) against the rules
+fn replay(text: &str, rules: &[DetectionRule]) -> Result<Vec<ReplayResult>, String> {
+    let file: ReplayFile = serde_json::from_str(text).map_err(|e| format!("invalid replay file: {}", e))?;
+    let provider = file.processes.iter().fold(MockProvider::new(), |provider, p| {
+        // Snapshot name (readable without access): taken from the path if not given
+        let name = match (&p.path, p.name.is_empty()) {
+            (Some(path), true) => {
+                Path::new(path).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+            }
+            _ => p.name.clone(),
+        };
+        provider.with_process(p.pid, MockProcess {
+            name,
+            path: p.path.clone(),
+            command_line: p.command_line.clone(),
+            parent_id: p.parent_id,
+            created: p.created,
+        })
+    });
+
+    Ok(file
+        .processes
+        .iter()
+        .map(|p| {
+            let info = process_info::read_process(&provider, p.pid);
+            let rule = crate::detection::first_match(rules, &info);
+            let parents = [
+                &info.parent_process_name,
+                &info.grandparent_process_name,
+                &info.greatgrandparent_process_name,
+            ]
+            .into_iter()
+            .filter(|name| !name.is_empty())
+            .cloned()
+            .collect();
+            ReplayResult {
+                process_id: p.pid,
+                process_name: info.process_name.clone(),
+                parents,
+                parent_spoofed: info.parent_spoofed.clone(),
+                detection: rule.map(|r| r.title.clone()),
+                severity: rule.map(|r| r.severity.as_str()),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProcessMatch;
+
+    const FILE: &str = r#"{ "processes": [
+        { "pid": 10, "path": "C:\\Windows\\explorer.exe", "created": 100 },
+        { "pid": 20, "path": "C:\\Program Files\\Office\\WINWORD.EXE", "parent_id": 10, "created": 200 },
+        { "pid": 30, "path": "C:\\Windows\\System32\\cmd.exe", "command_line": "cmd /c whoami",
+          "parent_id": 20, "created": 300 },
+        { "pid": 40, "name": "svchost", "parent_id": 77, "created": 400 }
+    ] }"#;
+
+    #[test]
+    fn replays_hierarchy_spoofing_and_detections() {
+        let rules = vec![DetectionRule {
+            title: "Office spawns a shell".to_string(),
+            selections: vec![ProcessMatch {
+                parent_image: vec!["*\\winword.exe".to_string()],
+                image: vec!["*\\cmd.exe".to_string()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }];
+        let results = replay(FILE, &rules).unwrap();
+        assert_eq!(results.len(), 4);
+
+        assert_eq!(results[2].process_name, "cmd");
+        assert_eq!(results[2].parents, ["WINWORD", "explorer", "System"]);
+        assert_eq!(results[2].detection.as_deref(), Some("Office spawns a shell"));
+        assert_eq!(results[2].severity, Some("WARNING"));
+        assert!(results[1].detection.is_none());
+
+        // No path = access denied, parent 77 is not in the table
+        assert_eq!(results[3].process_name, "Access denied");
+        assert_eq!(results[3].parent_spoofed.as_deref(), Some("parent PID 77 does not exist"));
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert!(replay("{ \"processes\": 1 }", &[]).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! Defender Reputation
+//!
+//! Optionally scans the executable behind a Critical alert with the local Microsoft Defender
+//! (MpCmdRun.exe custom scan of the single file, no remediation). The verdict is logged as
+//! REPUTATION event and shown in the details window (e.g. "known PUA").
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::io::Read;
+use std::os::windows::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// No console window for MpCmdRun
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// MpCmdRun exit codes
+const EXIT_NO_THREAT: i32 = 0;
+const EXIT_THREAT_FOUND: i32 = 2;
+
+/// Poll interval while waiting for the scan
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Verdict for one executable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pending,
+    Clean,
+    /// Threat names reported by Defender (e.g. "PUA:Win32/Presenoker")
+    Threat(Vec<String>),
+    /// Scan not possible (Defender missing/disabled, timeout, ...)
+    Unavailable(String),
+}
+
+impl Verdict {
+    pub fn describe(&self) -> String {
+        match self {
+            Verdict::Pending => "scanning...".to_string(),
+            Verdict::Clean => "no threats found".to_string(),
+            Verdict::Threat(names) => format!("THREAT {}", names.join(", ")),
+            Verdict::Unavailable(reason) => format!("not available ({})", reason),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    // Verdict per executable (key = lowercase path), every file is scanned once per run
+    static ref VERDICTS: Mutex<HashMap<String, Verdict>> = Mutex::new(HashMap::new());
+}
+
+/// Cached verdict for an executable (None = never looked up)
+pub fn verdict(process_path: &str) -> Option<Verdict> {
+    VERDICTS.lock().get(&process_path.to_lowercase()).cloned()
+}
+
+/// Starts a background lookup for the executable of a Critical alert (once per file)
+pub fn lookup(process_name: &str, process_path: &str) {
+    let config = crate::config::get().reputation;
+    if !config.enabled || !Path::new(process_path).is_absolute() {
+        return;
+    }
+
+    {
+        let mut verdicts = VERDICTS.lock();
+        let key = process_path.to_lowercase();
+        if verdicts.contains_key(&key) {
+            return;
+        }
+        verdicts.insert(key, Verdict::Pending);
+    }
+
+    let process_name = process_name.to_string();
+    let process_path = process_path.to_string();
+    thread::spawn(move || {
+        let verdict = scan(Path::new(&process_path), Duration::from_secs(config.timeout_secs));
+        VERDICTS.lock().insert(process_path.to_lowercase(), verdict.clone());
+        report(process_name, process_path, &verdict);
+    });
+}
+
+/// Logs the verdict and refreshes open details windows
+fn report(process_name: String, process_path: String, verdict: &Verdict) {
+    let severity = if matches!(verdict, Verdict::Threat(_)) {
+        warn!("!!! Defender verdict for {}: {} !!!", process_path, verdict.describe());
+        Severity::Warning
+    } else {
+        info!("Defender verdict for {}: {}", process_path, verdict.describe());
+        Severity::Info
+    };
+
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: chrono::Local::now(),
+        event_type: "REPUTATION".to_string(),
+        severity,
+        process_name,
+        process_path,
+        window_title: format!("Defender: {}", verdict.describe()),
+        ..Default::default()
+    });
+    crate::alert_window::redraw_details_window();
+}
+
+/// MpCmdRun.exe: newest platform version first, then the classic install folder
+fn mpcmdrun_path() -> Option<PathBuf> {
+    let program_data = std::env::var("ProgramData").unwrap_or_else(|_| "C:\\ProgramData".to_string());
+    let platform_dir = Path::new(&program_data).join("Microsoft\\Windows Defender\\Platform");
+    let mut platforms: Vec<PathBuf> = std::fs::read_dir(platform_dir)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path().join("MpCmdRun.exe")).collect())
+        .unwrap_or_default();
+    platforms.sort();
+
+    let program_files = std::env::var("ProgramFiles").unwrap_or_else(|_| "C:\\Program Files".to_string());
+    let classic = Path::new(&program_files).join("Windows Defender\\MpCmdRun.exe");
+
+    platforms.into_iter().rev().chain(std::iter::once(classic)).find(|p| p.is_file())
+}
+
+/// Threat names from the MpCmdRun output ("Threat                  : PUA:Win32/...")
+fn parse_threats(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == "Threat").then(|| value.trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Scans one file with Defender (blocks up to timeout)
+fn scan(path: &Path, timeout: Duration) -> Verdict {
+    let Some(mpcmdrun) = mpcmdrun_path() else {
+        return Verdict::Unavailable("MpCmdRun.exe not found".to_string());
+    };
+
+    let child = Command::new(mpcmdrun)
+        .args(["-Scan", "-ScanType", "3", "-DisableRemediation", "-File"])
+        .arg(path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn();
+    let mut child = match child {
+        Ok(c) => c,
+        Err(e) => return Verdict::Unavailable(format!("start failed: {}", e)),
+    };
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() < timeout => thread::sleep(POLL_INTERVAL),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Verdict::Unavailable("timeout".to_string());
+            }
+            Err(e) => return Verdict::Unavailable(e.to_string()),
+        }
+    };
+
+    let mut output = String::new();
+    if let Some(mut stdout) = child.stdout.take() {
+        let _ = stdout.read_to_string(&mut output);
+    }
+
+    match status.code() {
+        Some(EXIT_NO_THREAT) => Verdict::Clean,
+        Some(EXIT_THREAT_FOUND) => {
+            let threats = parse_threats(&output);
+            if threats.is_empty() {
+                Verdict::Threat(vec!["(unnamed)".to_string()])
+            } else {
+                Verdict::Threat(threats)
+            }
+        }
+        code => Verdict::Unavailable(format!("exit code {}", code.unwrap_or(-1))),
+    }
+}
@@ -0,0 +1,337 @@
+//! Hidden Window Reveal
+//!
+//! Tray action "Reveal hidden topmost windows": lists topmost windows that are visible to
+//! Windows but not to the user - cloaked (other virtual desktop or hidden by DWM), moved
+//! off all monitors or fully transparent - with their process. The selected window can be
+//! brought on-screen or its process killed. Runs its own message loop like the note dialog.
+
+use std::cell::RefCell;
+use tracing::{error, info};
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::{BOOL, COLORREF, HWND, LPARAM, LRESULT, RECT, WPARAM};
+use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED};
+use windows::Win32::Graphics::Gdi::{GetStockObject, COLOR_BTNFACE, DEFAULT_GUI_FONT, HBRUSH};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Input::KeyboardAndMouse::EnableWindow;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+const DIALOG_WIDTH: i32 = 640;
+const DIALOG_HEIGHT: i32 = 320;
+
+// Transparent below this alpha
+const MIN_VISIBLE_ALPHA: u8 = 10;
+
+// Control IDs
+const ID_REVEAL: i32 = 101;
+const ID_KILL: i32 = 102;
+const ID_REFRESH: i32 = 103;
+
+/// A topmost window the user cannot see
+#[derive(Debug, Clone)]
+pub struct HiddenWindow {
+    pub hwnd: isize,
+    pub process_name: String,
+    pub process_id: u32,
+    pub title: String,
+    pub class: String,
+    /// Why it is not visible
+    pub reason: &'static str,
+}
+
+impl HiddenWindow {
+    /// Line in the list
+    fn label(&self) -> String {
+        format!(
+            "[{}] {} (PID {}): {} [{}]",
+            self.reason,
+            self.process_name,
+            self.process_id,
+            if self.title.is_empty() { "(no title)" } else { &self.title },
+            self.class
+        )
+    }
+}
+
+/// State of the open dialog (only one at a time, tray thread)
+struct DialogState {
+    list: HWND,
+    windows: Vec<HiddenWindow>,
+    done: bool,
+}
+
+thread_local! {
+    static STATE: RefCell<Option<DialogState>> = const { RefCell::new(None) };
+}
+
+/// Why a visible topmost window is hidden from the user (None = it is visible)
+unsafe fn hidden_reason(hwnd: HWND) -> Option<&'static str> {
+    let mut cloaked: u32 = 0;
+    let cloaked_ok = DwmGetWindowAttribute(
+        hwnd,
+        DWMWA_CLOAKED,
+        &mut cloaked as *mut u32 as *mut _,
+        std::mem::size_of::<u32>() as u32,
+    )
+    .is_ok();
+    if cloaked_ok && cloaked != 0 {
+        return Some("cloaked");
+    }
+
+    // Not on any monitor of the virtual screen
+    let mut rect = RECT::default();
+    if GetWindowRect(hwnd, &mut rect).is_ok() {
+        let left = GetSystemMetrics(SM_XVIRTUALSCREEN);
+        let top = GetSystemMetrics(SM_YVIRTUALSCREEN);
+        let right = left + GetSystemMetrics(SM_CXVIRTUALSCREEN);
+        let bottom = top + GetSystemMetrics(SM_CYVIRTUALSCREEN);
+        if rect.right <= left || rect.left >= right || rect.bottom <= top || rect.top >= bottom {
+            return Some("offscreen");
+        }
+    }
+
+    let ex_style = GetWindowLongW(hwnd, GWL_EXSTYLE) as u32;
+    if ex_style & WS_EX_LAYERED.0 != 0 {
+        let mut alpha: u8 = 255;
+        let mut flags = LAYERED_WINDOW_ATTRIBUTES_FLAGS(0);
+        let ok = GetLayeredWindowAttributes(hwnd, None, Some(&mut alpha), Some(&mut flags)).is_ok();
+        if ok && flags.0 & LWA_ALPHA.0 != 0 && alpha < MIN_VISIBLE_ALPHA {
+            return Some("transparent");
+        }
+    }
+    None
+}
+
+/// Collects visible topmost windows
+unsafe extern "system" fn collect_topmost(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let windows = &mut *(lparam.0 as *mut Vec<HWND>);
+    let ex_style = GetWindowLongW(hwnd, GWL_EXSTYLE) as u32;
+    if IsWindowVisible(hwnd).as_bool() && ex_style & WS_EX_TOPMOST.0 != 0 {
+        windows.push(hwnd);
+    }
+    BOOL(1)
+}
+
+/// Topmost windows that are cloaked, offscreen or transparent (own windows excluded)
+pub fn find_hidden_windows() -> Vec<HiddenWindow> {
+    let mut candidates: Vec<HWND> = Vec::new();
+    unsafe {
+        let _ = EnumWindows(Some(collect_topmost), LPARAM(&mut candidates as *mut Vec<HWND> as isize));
+    }
+
+    let own_pid = std::process::id();
+    candidates
+        .into_iter()
+        .filter_map(|hwnd| {
+            let reason = unsafe { hidden_reason(hwnd) }?;
+            let info = crate::process_info::get_process_info_cached(hwnd);
+            (info.process_id != own_pid).then_some(HiddenWindow {
+                hwnd: hwnd.0 as isize,
+                process_name: info.process_name,
+                process_id: info.process_id,
+                title: info.window_title,
+                class: info.window_class,
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// Moves a window onto the primary monitor and makes it opaque
+unsafe fn bring_on_screen(window: &HiddenWindow) {
+    let hwnd = HWND(window.hwnd as *mut _);
+    let mut rect = RECT::default();
+    let _ = GetWindowRect(hwnd, &mut rect);
+    let screen_w = GetSystemMetrics(SM_CXSCREEN);
+    let screen_h = GetSystemMetrics(SM_CYSCREEN);
+    let width = (rect.right - rect.left).clamp(200, screen_w);
+    let height = (rect.bottom - rect.top).clamp(100, screen_h);
+
+    let _ = SetWindowPos(hwnd, HWND_TOPMOST, (screen_w - width) / 2, (screen_h - height) / 2, width, height, SWP_SHOWWINDOW);
+    if window.reason == "transparent" {
+        let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), 255, LWA_ALPHA);
+    }
+    // Cloaked windows of another virtual desktop: activating switches to that desktop
+    let _ = ShowWindow(hwnd, SW_SHOWNORMAL);
+    let _ = SetForegroundWindow(hwnd);
+    info!(
+        "Hidden window revealed: {} (PID: {}) - {} [{}]",
+        window.process_name, window.process_id, window.title, window.reason
+    );
+}
+
+/// Fills the list with the current hidden windows
+unsafe fn refresh(list: HWND) -> Vec<HiddenWindow> {
+    let windows = find_hidden_windows();
+    SendMessageW(list, LB_RESETCONTENT, WPARAM(0), LPARAM(0));
+    if windows.is_empty() {
+        let text: Vec<u16> = "No hidden topmost windows found".encode_utf16().chain(std::iter::once(0)).collect();
+        SendMessageW(list, LB_ADDSTRING, WPARAM(0), LPARAM(text.as_ptr() as isize));
+    }
+    for window in &windows {
+        let text: Vec<u16> = window.label().encode_utf16().chain(std::iter::once(0)).collect();
+        SendMessageW(list, LB_ADDSTRING, WPARAM(0), LPARAM(text.as_ptr() as isize));
+    }
+    windows
+}
+
+/// Shows the dialog above the owner until it is closed
+pub unsafe fn show_dialog(owner: HWND) {
+    if STATE.with(|s| s.borrow().is_some()) {
+        return;
+    }
+
+    let instance = GetModuleHandleW(None).unwrap_or_default();
+    let class_name = w!("PCWatcherReveal");
+    let wc = WNDCLASSW {
+        lpfnWndProc: Some(reveal_window_proc),
+        hInstance: instance.into(),
+        hCursor: LoadCursorW(None, IDC_ARROW).unwrap_or_default(),
+        hbrBackground: HBRUSH((COLOR_BTNFACE.0 + 1) as isize as *mut _),
+        lpszClassName: class_name,
+        ..Default::default()
+    };
+    // Fails harmlessly if already registered
+    let _ = RegisterClassW(&wc);
+
+    // Centered on the primary monitor (the tray window is invisible)
+    let x = (GetSystemMetrics(SM_CXSCREEN) - DIALOG_WIDTH) / 2;
+    let y = (GetSystemMetrics(SM_CYSCREEN) - DIALOG_HEIGHT) / 2;
+
+    let hwnd = match CreateWindowExW(
+        WS_EX_TOPMOST | WS_EX_DLGMODALFRAME,
+        class_name,
+        w!("PC Watcher - Hidden topmost windows"),
+        WS_POPUP | WS_CAPTION | WS_SYSMENU,
+        x, y,
+        DIALOG_WIDTH,
+        DIALOG_HEIGHT,
+        owner,
+        None,
+        instance,
+        None,
+    ) {
+        Ok(h) => h,
+        Err(e) => {
+            error!("Could not create reveal dialog: {}", e);
+            return;
+        }
+    };
+
+    let font = GetStockObject(DEFAULT_GUI_FONT);
+    let child = |class: PCWSTR, text: PCWSTR, style: WINDOW_STYLE, x: i32, y: i32, w: i32, h: i32, id: i32| {
+        let control = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            class,
+            text,
+            WS_CHILD | WS_VISIBLE | style,
+            x, y, w, h,
+            hwnd,
+            HMENU(id as isize as *mut _),
+            instance,
+            None,
+        )
+        .unwrap_or_default();
+        SendMessageW(control, WM_SETFONT, WPARAM(font.0 as usize), LPARAM(1));
+        control
+    };
+
+    child(
+        w!("STATIC"),
+        w!("Topmost windows that are cloaked, off all monitors or transparent:"),
+        WINDOW_STYLE::default(),
+        10, 10, 610, 18,
+        0,
+    );
+    let list = child(
+        w!("LISTBOX"),
+        PCWSTR::null(),
+        WS_BORDER | WS_VSCROLL | WS_TABSTOP | WINDOW_STYLE(LBS_NOTIFY as u32),
+        10, 32, 610, 210,
+        0,
+    );
+    child(w!("BUTTON"), w!("Bring on-screen"), WS_TABSTOP | WINDOW_STYLE(BS_DEFPUSHBUTTON as u32), 10, 250, 120, 26, ID_REVEAL);
+    child(w!("BUTTON"), w!("Kill owner"), WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32), 140, 250, 100, 26, ID_KILL);
+    child(w!("BUTTON"), w!("Refresh"), WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32), 250, 250, 100, 26, ID_REFRESH);
+    child(w!("BUTTON"), w!("Close"), WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32), 520, 250, 100, 26, IDCANCEL.0);
+
+    let windows = refresh(list);
+    STATE.with(|s| *s.borrow_mut() = Some(DialogState { list, windows, done: false }));
+
+    let _ = EnableWindow(owner, false);
+    let _ = ShowWindow(hwnd, SW_SHOW);
+    let _ = SetForegroundWindow(hwnd);
+
+    let mut msg = MSG::default();
+    while !STATE.with(|s| s.borrow().as_ref().map(|d| d.done).unwrap_or(true)) {
+        if !GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            // WM_QUIT belongs to the outer loop
+            PostQuitMessage(msg.wParam.0 as i32);
+            break;
+        }
+        if !IsDialogMessageW(hwnd, &msg).as_bool() {
+            let _ = TranslateMessage(&msg);
+            let _ = DispatchMessageW(&msg);
+        }
+    }
+
+    let _ = EnableWindow(owner, true);
+    let _ = DestroyWindow(hwnd);
+    STATE.with(|s| s.borrow_mut().take());
+}
+
+/// Window in the selected list row
+unsafe fn selected() -> Option<HiddenWindow> {
+    STATE.with(|s| {
+        let state = s.borrow();
+        let state = state.as_ref()?;
+        let index = SendMessageW(state.list, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+        usize::try_from(index).ok().and_then(|i| state.windows.get(i).cloned())
+    })
+}
+
+/// Reloads the list (after an action)
+unsafe fn reload() {
+    let list = STATE.with(|s| s.borrow().as_ref().map(|d| d.list));
+    if let Some(list) = list {
+        let windows = refresh(list);
+        STATE.with(|s| {
+            if let Some(state) = s.borrow_mut().as_mut() {
+                state.windows = windows;
+            }
+        });
+    }
+}
+
+/// Window procedure of the reveal dialog
+unsafe extern "system" fn reveal_window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    match msg {
+        WM_COMMAND => {
+            let id = (wparam.0 & 0xFFFF) as i32;
+            let notification = ((wparam.0 >> 16) & 0xFFFF) as u32;
+            // Double-click on a row = bring on-screen
+            let double_click = HWND(lparam.0 as *mut _) == STATE.with(|s| s.borrow().as_ref().map(|d| d.list).unwrap_or_default())
+                && notification == LBN_DBLCLK;
+            if id == ID_REVEAL || double_click {
+                if let Some(window) = selected() {
+                    bring_on_screen(&window);
+                    reload();
+                }
+            } else if id == ID_KILL {
+                if let Some(window) = selected() {
+                    crate::alert_window::kill_process(hwnd, window.process_id, &window.process_name);
+                    reload();
+                }
+            } else if id == ID_REFRESH {
+                reload();
+            } else if id == IDCANCEL.0 {
+                STATE.with(|s| s.borrow_mut().as_mut().map(|d| d.done = true));
+            }
+            LRESULT(0)
+        }
+        WM_CLOSE => {
+            STATE.with(|s| s.borrow_mut().as_mut().map(|d| d.done = true));
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
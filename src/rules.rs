@@ -0,0 +1,122 @@
+//! Detection Rules
+//!
+//! Built-in detections and their per-rule settings from the config
+//! (e.g. capture behavior, so noisy rules don't fill the disk).
+
+use crate::config::{self, RuleConfig};
+use crate::notification::Severity;
+use crate::screenshot::CaptureMode;
+
+/// Built-in detection rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    SuspiciousProcess,
+    FocusWithoutClick,
+    WatchedProcess,
+    Honeypot,
+    Honeytoken,
+    SyntheticInput,
+    InputAnomaly,
+    DeviceAccess,
+    ContentChange,
+    ClockChange,
+    MonitoringGap,
+    SelfTest,
+    TopmostWindow,
+    ClickThroughOverlay,
+    ServiceInstalled,
+    ScheduledTask,
+    Autorun,
+    RegistryChange,
+    FileWatch,
+    Download,
+    NewNetwork,
+    QuietResume,
+    Detection,
+    HashList,
+    PathHeuristic,
+    Masquerade,
+}
+
+impl Rule {
+    /// Rule name (key in the "rules" section of the config)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::SuspiciousProcess => "suspicious_process",
+            Rule::FocusWithoutClick => "focus_without_click",
+            Rule::WatchedProcess => "watched_process",
+            Rule::Honeypot => "honeypot",
+            Rule::Honeytoken => "honeytoken",
+            Rule::SyntheticInput => "synthetic_input",
+            Rule::InputAnomaly => "input_anomaly",
+            Rule::DeviceAccess => "device_access",
+            Rule::ContentChange => "content_change",
+            Rule::ClockChange => "clock_change",
+            Rule::MonitoringGap => "monitoring_gap",
+            Rule::SelfTest => "self_test",
+            Rule::TopmostWindow => "topmost_window",
+            Rule::ClickThroughOverlay => "clickthrough_overlay",
+            Rule::ServiceInstalled => "service_installed",
+            Rule::ScheduledTask => "scheduled_task",
+            Rule::Autorun => "autorun",
+            Rule::RegistryChange => "registry_change",
+            Rule::FileWatch => "file_watch",
+            Rule::Download => "download",
+            Rule::NewNetwork => "new_network",
+            Rule::QuietResume => "quiet_resume",
+            Rule::Detection => "detection",
+            Rule::HashList => "hash_list",
+            Rule::PathHeuristic => "path_heuristic",
+            Rule::Masquerade => "masquerade",
+        }
+    }
+}
+
+/// Returns the config of a rule (defaults if not configured)
+pub fn rule_config(rule: Rule) -> RuleConfig {
+    config::read().rules.get(rule.name()).cloned().unwrap_or_default()
+}
+
+/// Checks if a rule raises alerts (disabled rules are logged only)
+pub fn is_enabled(rule: Rule) -> bool {
+    config::read().rules.get(rule.name()).map_or_else(|| RuleConfig::default().enabled, |c| c.enabled)
+}
+
+/// Capture behavior for an alert: rule setting, otherwise the severity default
+pub fn capture_mode(rule: Rule, severity: Severity) -> CaptureMode {
+    rule_config(rule)
+        .capture
+        .unwrap_or_else(|| config::read().capture.for_severity(severity))
+}
+
+/// Alert header text of a rule (`{message}` in the template is replaced)
+pub fn alert_header(rule: Rule, message: &str) -> String {
+    match rule_config(rule).header {
+        Some(template) if template.contains("{message}") => template.replace("{message}", message),
+        Some(template) => template,
+        None => format!("!! {} !!", message),
+    }
+}
+
+/// Alert header color of a rule as COLORREF (config "#RRGGBB")
+pub fn alert_color(rule: Rule) -> Option<u32> {
+    let color = rule_config(rule).color?;
+    let hex = color.trim().trim_start_matches('#');
+    let rgb = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 6)?;
+    // COLORREF is 0x00BBGGRR
+    Some(((rgb & 0xFF) << 16) | (rgb & 0xFF00) | (rgb >> 16))
+}
+
+/// Seconds an alert stays in the header: rule setting, otherwise the severity default
+pub fn alert_duration(rule: Rule, severity: Severity) -> u64 {
+    rule_config(rule)
+        .duration_secs
+        .unwrap_or_else(|| config::read().alert_duration.for_severity(severity))
+}
+
+/// Alert sound: rule setting, otherwise the severity default
+pub fn alert_sound(rule: Rule, severity: Severity) -> String {
+    rule_config(rule)
+        .sound
+        .unwrap_or_else(|| config::read().sounds.for_severity(severity))
+}
@@ -2,38 +2,90 @@
 //!
 //! Takes screenshots on alerts and saves them as JPEG in the log directory.
 //! 3 screenshots with delay: immediately, +200ms, +500ms
-//! Captures only the focused window, not the entire screen.
+//! Captures the focused window (or the entire desktop, depending on the capture mode).
+//! Optionally followed by a short recording (animated GIF).
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::thread;
 use std::time::Duration;
 use std::fs;
 use chrono::Local;
-use tracing::{info, error};
-use image::{ImageBuffer, Rgb};
-use windows::Win32::Foundation::{HWND, RECT};
+use serde::{Deserialize, Serialize};
+use crate::audio::AudioSource;
+use crate::error::{Error, ReportExt};
+use crate::notification::Severity;
+use parking_lot::Mutex;
+use tracing::info;
+use image::{ImageBuffer, Rgb, RgbaImage, Frame, Delay};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::{self, FilterType};
+use windows::Win32::Foundation::{BOOL, COLORREF, HWND, LPARAM, POINT, RECT};
 use windows::Win32::Graphics::Gdi::{
     GetDC, ReleaseDC, CreateCompatibleDC, CreateCompatibleBitmap,
-    SelectObject, GetDIBits, DeleteDC, DeleteObject,
-    BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    SelectObject, GetDIBits, DeleteDC, DeleteObject, BitBlt,
+    BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, HBITMAP, HDC,
+    SRCCOPY, CAPTUREBLT, EnumDisplayMonitors, HMONITOR,
+    FillRect, GetStockObject, SetBkMode, SetTextColor, TextOutW, BLACK_BRUSH, DEFAULT_GUI_FONT,
+    HBRUSH, TRANSPARENT,
 };
 use windows::Win32::Storage::Xps::{PrintWindow, PRINT_WINDOW_FLAGS};
 use windows::Win32::UI::WindowsAndMessaging::{
-    GetForegroundWindow, GetWindowRect,
+    GetForegroundWindow, GetWindowRect, GetSystemMetrics, GetCursorInfo, GetCursorPos, GetIconInfo,
+    DrawIconEx, CURSORINFO, CURSOR_SHOWING, DI_NORMAL, DI_DEFAULTSIZE, HICON, ICONINFO,
+    SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN,
 };
 
+/// Recording: 10 frames every 500ms (5 seconds)
+const VIDEO_FRAMES: usize = 10;
+const VIDEO_FRAME_INTERVAL: Duration = Duration::from_millis(500);
+/// Recording frames are downscaled to this width (keeps GIF small)
+const VIDEO_MAX_WIDTH: u32 = 800;
+/// Height of the caption bar below saved screenshots
+const CAPTION_HEIGHT: i32 = 22;
+/// Perceptual hash size (16x16 gradients = 256 bit)
+const HASH_SIZE: u32 = 16;
+/// Frames whose hashes differ in at most this many bits are duplicates
+const DUPLICATE_BITS: u32 = 2;
+
+/// Capture behavior on alerts (configurable per rule and severity)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureMode {
+    /// Nothing is captured
+    None,
+    /// 3 screenshots of the focused window
+    Screenshot,
+    /// 3 screenshots + 5 second recording of the focused window
+    ScreenshotVideo,
+    /// 3 screenshots + recording of the entire desktop (all monitors)
+    FullDesktop,
+    /// 3 screenshots per monitor (one image each, no recording)
+    AllMonitors,
+}
+
+/// Perceptual hash of a frame
+type FrameHash = [u64; 4];
+
+lazy_static::lazy_static! {
+    // Preview buffer handed back by the GUI, reused for the next preview
+    static ref SPARE_PREVIEW: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+}
+
+/// Capture function (pixels as RGB, width, height)
+type CaptureFn = fn() -> Result<(Vec<u8>, i32, i32), Error>;
+
 /// Screenshot directory (in log folder)
 fn get_screenshot_dir() -> PathBuf {
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(exe_dir) = exe_path.parent() {
-            return exe_dir.join("logs");
-        }
-    }
-    PathBuf::from(".").join("logs")
+    crate::logger::get_log_dir()
 }
 
 /// Deletes all screenshot subfolders (called at startup)
 pub fn cleanup_screenshots() {
+    // Further instances share the folder - only the first one cleans up
+    if crate::instance::number() > 1 {
+        return;
+    }
     let dir = get_screenshot_dir();
     if let Ok(entries) = fs::read_dir(&dir) {
         for entry in entries.filter_map(|e| e.ok()) {
@@ -44,7 +96,7 @@ pub fn cleanup_screenshots() {
                     // Folders starting with date (e.g., "2025-12-14_...")
                     if name.len() >= 10 && name.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false) {
                         if let Err(e) = fs::remove_dir_all(&path) {
-                            error!("Could not delete screenshot folder: {} - {}", path.display(), e);
+                            crate::error::report("Could not delete screenshot folder", Error::io(&path, &e));
                         }
                     }
                 }
@@ -61,100 +113,362 @@ pub fn cleanup_screenshots() {
 }
 
 /// Starts screenshot thread for an alert
-/// Takes 3 screenshots: immediately, +200ms, +500ms
+/// Takes 3 screenshots: immediately, +200ms, +500ms (plus recording, depending on mode)
+/// Critical alerts additionally get a webcam frame if enabled (opt-in)
 /// Screenshots are saved in subfolder: logs/YYYY-MM-DD_HH-MM-SS_ProcessName/
-pub fn capture_alert_screenshots(process_name: String) {
+pub fn capture_alert_screenshots(process_name: String, event_type: &str, mode: CaptureMode, severity: Severity) {
+    let critical = severity == Severity::Critical;
+    let webcam = critical && crate::webcam::is_enabled();
+    let audio = critical && crate::config::get().capture.audio_on_critical != AudioSource::None;
+    if mode == CaptureMode::None && !webcam && !audio {
+        return;
+    }
+
+    let capture: CaptureFn = if mode == CaptureMode::FullDesktop {
+        capture_desktop
+    } else {
+        capture_foreground_window
+    };
+
+    // Caption bar keeps images self-describing outside of the folder structure
+    let caption = crate::config::get().capture.caption.then(|| {
+        format!(
+            "{} | {} | {} | {}",
+            Local::now().format("%Y-%m-%d %H:%M:%S"),
+            hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default(),
+            event_type,
+            process_name
+        )
+    });
+
     thread::spawn(move || {
+        let caption = caption.as_deref();
         let base_dir = get_screenshot_dir();
 
         // Subfolder with date, time and process name
         let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
-        let folder_name = format!("{}_{}", timestamp, sanitize_filename(&process_name));
-        let screenshot_dir = base_dir.join(&folder_name);
+        let screenshot_dir = base_dir.join(folder_name(&base_dir, &timestamp, &process_name));
 
         if let Err(e) = fs::create_dir_all(&screenshot_dir) {
-            error!("Could not create screenshot folder: {}", e);
+            crate::error::report("Could not create screenshot folder", Error::io(&screenshot_dir, &e));
+            return;
+        }
+
+        // Audio runs in the background while the screenshots are taken
+        if audio {
+            crate::audio::start_recording(&screenshot_dir);
+        }
+
+        // Webcam in parallel (who is at the PC right now?) - camera start-up takes seconds
+        if webcam {
+            let dir = screenshot_dir.clone();
+            thread::spawn(move || {
+                crate::webcam::capture_snapshot(&dir).or_report("Webcam snapshot");
+            });
+        }
+
+        if mode == CaptureMode::None {
             return;
         }
 
+        // All monitors: one image per monitor instead of one per screenshot
+        let all_monitors = mode == CaptureMode::AllMonitors;
+        let mut dedup = FrameDedup::default();
+        let mut saved = 0;
+        let mut skipped = 0;
+
+        // Takes and saves a screenshot (nearly identical frames are skipped)
+        // Returns the first image (window or first monitor) for the GUI preview
+        let mut take = |name: &str| -> Result<(Vec<u8>, i32, i32), Error> {
+            let images = if all_monitors { capture_monitors()? } else { vec![capture()?] };
+            for (i, (pixels, width, height)) in images.iter().enumerate() {
+                let file_name = if all_monitors {
+                    format!("{}_monitor_{}", name, i + 1)
+                } else {
+                    name.to_string()
+                };
+                if dedup.is_duplicate(i, pixels, *width, *height) {
+                    info!("{} skipped (duplicate)", file_name);
+                    skipped += 1;
+                    continue;
+                }
+                save_screenshot(&screenshot_dir, &file_name, pixels, *width, *height, caption)?;
+                saved += 1;
+            }
+            images.into_iter().next().ok_or_else(|| Error::Device("No monitor found".to_string()))
+        };
+
         // Screenshot 1: Immediately - also send to GUI
-        match capture_foreground_window() {
+        match take("screenshot_1") {
             Ok((pixels, width, height)) => {
+                // Downscaled here, the full-resolution frame is freed before the GUI gets it
+                let (max_width, max_height) = crate::alert_window::preview_size();
+                let mut preview = std::mem::take(&mut *SPARE_PREVIEW.lock());
+                let (preview_width, preview_height) =
+                    downscale_into(&pixels, width as u32, height as u32, max_width, max_height, &mut preview);
+                drop(pixels);
+
                 // Send to GUI for preview + folder path
-                crate::alert_window::set_screenshot_with_folder(
-                    pixels.clone(),
-                    width as u32,
-                    height as u32,
+                let replaced = crate::alert_window::set_screenshot_with_folder(
+                    preview,
+                    preview_width,
+                    preview_height,
                     screenshot_dir.clone()
                 );
-
-                // Save as JPEG
-                if let Err(e) = save_screenshot(&screenshot_dir, "screenshot_1", &pixels, width, height) {
-                    error!("Screenshot 1 save failed: {}", e);
+                if let Some(buffer) = replaced {
+                    *SPARE_PREVIEW.lock() = buffer;
                 }
             }
-            Err(e) => error!("Screenshot 1 failed: {}", e),
+            Err(e) => crate::error::report("Screenshot 1", e),
         }
 
         // Screenshot 2: +200ms
         thread::sleep(Duration::from_millis(200));
-        if let Err(e) = capture_and_save(&screenshot_dir, "screenshot_2") {
-            error!("Screenshot 2 failed: {}", e);
-        }
+        take("screenshot_2").or_report("Screenshot 2");
 
         // Screenshot 3: +500ms (300ms after screenshot 2)
         thread::sleep(Duration::from_millis(300));
-        if let Err(e) = capture_and_save(&screenshot_dir, "screenshot_3") {
-            error!("Screenshot 3 failed: {}", e);
-        }
+        take("screenshot_3").or_report("Screenshot 3");
+
+        info!(
+            "{} screenshots created in: {} ({} duplicates skipped)",
+            saved,
+            screenshot_dir.display(),
+            skipped
+        );
 
-        info!("3 screenshots created in: {}", screenshot_dir.display());
+        // Recording (Critical alerts by default, skipped when disk space is low)
+        if matches!(mode, CaptureMode::ScreenshotVideo | CaptureMode::FullDesktop)
+            && !crate::storage::is_disk_low()
+        {
+            match record_video(capture, &screenshot_dir) {
+                Ok((frames, duplicates)) => info!(
+                    "Recording created ({} frames, {} duplicates merged) in: {}",
+                    frames,
+                    duplicates,
+                    screenshot_dir.display()
+                ),
+                Err(e) => crate::error::report("Recording", e),
+            }
+        }
     });
 }
 
+/// Records a short animated GIF (recording.gif)
+/// Returns the number of frames and of duplicate frames (merged into the previous one)
+fn record_video(capture: CaptureFn, dir: &Path) -> Result<(usize, usize), Error> {
+    // Frames with display duration (a duplicate extends the previous frame)
+    let mut frames: Vec<(RgbaImage, Duration)> = Vec::with_capacity(VIDEO_FRAMES);
+    let mut frame_size: Option<(u32, u32)> = None;
+    let mut dedup = FrameDedup::default();
+    let mut duplicates = 0;
+
+    for i in 0..VIDEO_FRAMES {
+        if i > 0 {
+            thread::sleep(VIDEO_FRAME_INTERVAL);
+        }
+
+        let (pixels, width, height) = match capture() {
+            Ok(frame) => frame,
+            // Window may be gone for a moment - skip frame
+            Err(_) => continue,
+        };
+        let duplicate = dedup.is_duplicate(0, &pixels, width, height);
+        if let Some((_, duration)) = frames.last_mut().filter(|_| duplicate) {
+            *duration += VIDEO_FRAME_INTERVAL;
+            duplicates += 1;
+            continue;
+        }
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = match ImageBuffer::from_raw(width as u32, height as u32, pixels) {
+            Some(img) => img,
+            None => continue,
+        };
+
+        // All frames get the size of the first one (downscaled)
+        let (w, h) = *frame_size.get_or_insert_with(|| {
+            let w = (width as u32).min(VIDEO_MAX_WIDTH);
+            let h = ((height as u64 * w as u64) / width as u64).max(1) as u32;
+            (w, h)
+        });
+        let rgba = image::DynamicImage::ImageRgb8(img).to_rgba8();
+        frames.push((imageops::resize(&rgba, w, h, FilterType::Triangle), VIDEO_FRAME_INTERVAL));
+    }
+
+    if frames.is_empty() {
+        return Err(Error::Device("No frames captured".to_string()));
+    }
+
+    let path = dir.join("recording.gif");
+    let file = fs::File::create(&path).map_err(|e| Error::io(&path, &e))?;
+    let mut encoder = GifEncoder::new_with_speed(file, 30);
+    encoder.set_repeat(Repeat::Infinite)
+        .map_err(|e| Error::Io { path: path.clone(), message: format!("GIF setup failed: {}", e) })?;
+
+    let count = frames.len();
+    for (frame, duration) in frames {
+        encoder
+            .encode_frame(Frame::from_parts(frame, 0, 0, Delay::from_saturating_duration(duration)))
+            .map_err(|e| Error::Io { path: path.clone(), message: format!("GIF encoding failed: {}", e) })?;
+    }
+
+    Ok((count, duplicates))
+}
+
+/// Longest file name written into an alert folder (e.g. "screenshot_monitor2_10.jpg")
+const LONGEST_FILE_NAME: usize = 32;
+
+/// Length of the "YYYY-MM-DD_HH-MM-SS" part of an alert folder name
+const FOLDER_TIMESTAMP_LEN: usize = 19;
+
+/// Process part of an alert folder name in base_dir (also used to find the folder again): shrinks
+/// in deep base folders so the files stay below MAX_PATH (Explorer and the recorders do not take
+/// longer paths)
+pub fn folder_process_name(base_dir: &Path, process_name: &str) -> String {
+    let used = base_dir.to_string_lossy().chars().count() + FOLDER_TIMESTAMP_LEN + LONGEST_FILE_NAME + 3;
+    let room = crate::long_path::MAX_PATH_CHARS.saturating_sub(used).clamp(8, 30);
+    sanitize_filename(process_name).chars().take(room).collect()
+}
+
+/// Alert folder name "date_time_process"
+fn folder_name(base_dir: &Path, timestamp: &str, process_name: &str) -> String {
+    format!("{}_{}", timestamp, folder_process_name(base_dir, process_name))
+}
+
 /// Sanitizes filename
-fn sanitize_filename(name: &str) -> String {
+pub fn sanitize_filename(name: &str) -> String {
     name.chars()
         .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
         .take(30)
         .collect()
 }
 
-/// Takes a screenshot and saves it as JPEG
-fn capture_and_save(dir: &PathBuf, name: &str) -> Result<(), String> {
-    let (pixels, width, height) = capture_foreground_window()?;
-    save_screenshot(dir, name, &pixels, width, height)
+/// Perceptual hash: brightness gradients of a 17x16 grayscale thumbnail (256 bit)
+fn frame_hash(pixels: &[u8], width: i32, height: i32) -> Option<FrameHash> {
+    let img: ImageBuffer<Rgb<u8>, &[u8]> = ImageBuffer::from_raw(width as u32, height as u32, pixels)?;
+    let gray = imageops::grayscale(&img);
+    let small = imageops::resize(&gray, HASH_SIZE + 1, HASH_SIZE, FilterType::Triangle);
+
+    let mut hash = [0u64; 4];
+    for y in 0..HASH_SIZE {
+        for x in 0..HASH_SIZE {
+            if small.get_pixel(x, y).0[0] > small.get_pixel(x + 1, y).0[0] {
+                let bit = (y * HASH_SIZE + x) as usize;
+                hash[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+    }
+    Some(hash)
+}
+
+/// Downscales RGB pixels to fit into max_width x max_height (box filter, never upscales)
+/// into a reused buffer and returns the new size
+fn downscale_into(pixels: &[u8], width: u32, height: u32, max_width: u32, max_height: u32, out: &mut Vec<u8>) -> (u32, u32) {
+    let scale = (max_width as f32 / width.max(1) as f32).min(max_height as f32 / height.max(1) as f32).min(1.0);
+    let dst_width = ((width as f32 * scale) as u32).max(1);
+    let dst_height = ((height as f32 * scale) as u32).max(1);
+    let (width, height) = (width as usize, height as usize);
+    let (dw, dh) = (dst_width as usize, dst_height as usize);
+
+    out.clear();
+    out.reserve(dw * dh * 3);
+    if pixels.len() < width * height * 3 {
+        out.resize(dw * dh * 3, 0);
+        return (dst_width, dst_height);
+    }
+    for dy in 0..dh {
+        let y0 = dy * height / dh;
+        let y1 = ((dy + 1) * height / dh).max(y0 + 1);
+        for dx in 0..dw {
+            let x0 = dx * width / dw;
+            let x1 = ((dx + 1) * width / dw).max(x0 + 1);
+            let mut sum = [0u32; 3];
+            for y in y0..y1 {
+                let row = &pixels[(y * width + x0) * 3..(y * width + x1) * 3];
+                for pixel in row.chunks_exact(3) {
+                    sum[0] += pixel[0] as u32;
+                    sum[1] += pixel[1] as u32;
+                    sum[2] += pixel[2] as u32;
+                }
+            }
+            let count = ((y1 - y0) * (x1 - x0)) as u32;
+            out.extend(sum.iter().map(|s| (s / count) as u8));
+        }
+    }
+    (dst_width, dst_height)
+}
+
+/// Remembers the last kept frame per stream (window or monitor) of an alert burst
+#[derive(Default)]
+struct FrameDedup {
+    last: HashMap<usize, FrameHash>,
+}
+
+impl FrameDedup {
+    /// Checks if a frame is nearly identical to the last kept frame of the stream
+    fn is_duplicate(&mut self, stream: usize, pixels: &[u8], width: i32, height: i32) -> bool {
+        let Some(hash) = frame_hash(pixels, width, height) else {
+            return false;
+        };
+        if let Some(last) = self.last.get(&stream) {
+            let distance: u32 = last.iter().zip(&hash).map(|(a, b)| (a ^ b).count_ones()).sum();
+            if distance <= DUPLICATE_BITS {
+                return true;
+            }
+        }
+        self.last.insert(stream, hash);
+        false
+    }
 }
 
 /// Saves pixel data as JPEG
-fn save_screenshot(dir: &PathBuf, name: &str, pixels: &[u8], width: i32, height: i32) -> Result<(), String> {
+fn save_screenshot(
+    dir: &Path,
+    name: &str,
+    pixels: &[u8],
+    width: i32,
+    height: i32,
+    caption: Option<&str>,
+) -> Result<(), Error> {
+    // Caption bar is appended below the image (nothing is covered)
+    let mut pixels = pixels.to_vec();
+    let mut height = height;
+    if let Some(text) = caption {
+        match render_caption(text, width) {
+            Ok(bar) => {
+                pixels.extend_from_slice(&bar);
+                height += CAPTION_HEIGHT;
+            }
+            Err(e) => crate::error::report("Caption", e),
+        }
+    }
+
     // Create ImageBuffer (RGB)
+    let path = dir.join(format!("{}.jpg", name));
     let img: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_raw(
         width as u32,
         height as u32,
-        pixels.to_vec(),
-    ).ok_or("Could not create ImageBuffer")?;
+        pixels,
+    ).ok_or_else(|| Error::Io { path: path.clone(), message: "Could not create ImageBuffer".to_string() })?;
 
     // Save as JPEG
-    let path = dir.join(format!("{}.jpg", name));
-    img.save(&path).map_err(|e| format!("JPEG save failed: {}", e))?;
+    img.save(&path).map_err(|e| Error::Io { path: path.clone(), message: format!("JPEG save failed: {}", e) })?;
 
     Ok(())
 }
 
 /// Gets the size of the focused window
-fn get_window_size(hwnd: HWND) -> Result<(i32, i32, i32, i32), String> {
+fn get_window_size(hwnd: HWND) -> Result<(i32, i32, i32, i32), Error> {
     unsafe {
         let mut rect = RECT::default();
         GetWindowRect(hwnd, &mut rect)
-            .map_err(|_| "GetWindowRect failed".to_string())?;
+            .map_err(|_| Error::Gdi("GetWindowRect"))?;
 
         let width = rect.right - rect.left;
         let height = rect.bottom - rect.top;
 
         // Check minimum size
         if width <= 0 || height <= 0 {
-            return Err("Window has invalid size".to_string());
+            return Err(Error::Device("Window has invalid size".to_string()));
         }
 
         Ok((rect.left, rect.top, width, height))
@@ -162,28 +476,32 @@ fn get_window_size(hwnd: HWND) -> Result<(i32, i32, i32, i32), String> {
 }
 
 /// Takes a screenshot of the focused window
+fn capture_foreground_window() -> Result<(Vec<u8>, i32, i32), Error> {
+    // Get focused window
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.0.is_null() {
+        return Err(Error::Device("No focused window".to_string()));
+    }
+    capture_window(hwnd)
+}
+
+/// Takes a screenshot of a window
 /// Uses PrintWindow to capture only the window itself (without overlapping windows)
-fn capture_foreground_window() -> Result<(Vec<u8>, i32, i32), String> {
+pub fn capture_window(hwnd: HWND) -> Result<(Vec<u8>, i32, i32), Error> {
     unsafe {
-        // Get focused window
-        let hwnd = GetForegroundWindow();
-        if hwnd.0.is_null() {
-            return Err("No focused window".to_string());
-        }
-
-        let (_x, _y, width, height) = get_window_size(hwnd)?;
+        let (x, y, width, height) = get_window_size(hwnd)?;
 
         // Get device context of window
         let hdc_window = GetDC(hwnd);
         if hdc_window.is_invalid() {
-            return Err("GetDC failed".to_string());
+            return Err(Error::Gdi("GetDC"));
         }
 
         // Create compatible DC
         let hdc_mem = CreateCompatibleDC(hdc_window);
         if hdc_mem.is_invalid() {
             ReleaseDC(hwnd, hdc_window);
-            return Err("CreateCompatibleDC failed".to_string());
+            return Err(Error::Gdi("CreateCompatibleDC"));
         }
 
         // Create bitmap
@@ -191,7 +509,7 @@ fn capture_foreground_window() -> Result<(Vec<u8>, i32, i32), String> {
         if hbitmap.is_invalid() {
             let _ = DeleteDC(hdc_mem);
             ReleaseDC(hwnd, hdc_window);
-            return Err("CreateCompatibleBitmap failed".to_string());
+            return Err(Error::Gdi("CreateCompatibleBitmap"));
         }
 
         // Select bitmap
@@ -206,56 +524,249 @@ fn capture_foreground_window() -> Result<(Vec<u8>, i32, i32), String> {
             let _ = PrintWindow(hwnd, hdc_mem, PRINT_WINDOW_FLAGS(0));
         }
 
+        draw_cursor(hdc_mem, x, y);
+
         // Extract pixel data
-        let mut bmi = BITMAPINFO {
-            bmiHeader: BITMAPINFOHEADER {
-                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
-                biWidth: width,
-                biHeight: -height, // Negative = Top-Down
-                biPlanes: 1,
-                biBitCount: 24, // RGB
-                biCompression: BI_RGB.0 as u32,
-                ..Default::default()
-            },
-            ..Default::default()
-        };
+        let pixels = read_bitmap_rgb(hdc_mem, hbitmap, width, height);
+
+        // Cleanup
+        SelectObject(hdc_mem, old_bitmap);
+        let _ = DeleteObject(hbitmap);
+        let _ = DeleteDC(hdc_mem);
+        ReleaseDC(hwnd, hdc_window);
+
+        Ok((pixels?, width, height))
+    }
+}
+
+/// Takes a screenshot of the entire desktop (virtual screen = all monitors)
+fn capture_desktop() -> Result<(Vec<u8>, i32, i32), Error> {
+    let (x, y, width, height) = unsafe {
+        (
+            GetSystemMetrics(SM_XVIRTUALSCREEN),
+            GetSystemMetrics(SM_YVIRTUALSCREEN),
+            GetSystemMetrics(SM_CXVIRTUALSCREEN),
+            GetSystemMetrics(SM_CYVIRTUALSCREEN),
+        )
+    };
+    if width <= 0 || height <= 0 {
+        return Err(Error::Device("Desktop has invalid size".to_string()));
+    }
+    capture_screen_rect(x, y, width, height)
+}
+
+/// Collects the rectangle of every monitor (virtual screen coordinates)
+unsafe extern "system" fn collect_monitor(_monitor: HMONITOR, _hdc: HDC, rect: *mut RECT, data: LPARAM) -> BOOL {
+    let monitors = &mut *(data.0 as *mut Vec<RECT>);
+    monitors.push(*rect);
+    BOOL(1)
+}
 
-        let row_size = ((width * 3 + 3) / 4) * 4; // DWORD-aligned
-        let mut pixels: Vec<u8> = vec![0; (row_size * height) as usize];
-
-        let lines = GetDIBits(
-            hdc_mem,
-            hbitmap,
-            0,
-            height as u32,
-            Some(pixels.as_mut_ptr() as *mut _),
-            &mut bmi,
-            DIB_RGB_COLORS,
+/// Takes one screenshot per monitor
+fn capture_monitors() -> Result<Vec<(Vec<u8>, i32, i32)>, Error> {
+    let mut monitors: Vec<RECT> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            None,
+            None,
+            Some(collect_monitor),
+            LPARAM(&mut monitors as *mut Vec<RECT> as isize),
         );
+    }
+
+    monitors
+        .iter()
+        .map(|rect| capture_screen_rect(rect.left, rect.top, rect.right - rect.left, rect.bottom - rect.top))
+        .collect()
+}
+
+/// Copies an area of the screen (virtual screen coordinates)
+fn capture_screen_rect(x: i32, y: i32, width: i32, height: i32) -> Result<(Vec<u8>, i32, i32), Error> {
+    unsafe {
+        let hdc_screen = GetDC(None);
+        if hdc_screen.is_invalid() {
+            return Err(Error::Gdi("GetDC"));
+        }
+
+        let hdc_mem = CreateCompatibleDC(hdc_screen);
+        if hdc_mem.is_invalid() {
+            ReleaseDC(None, hdc_screen);
+            return Err(Error::Gdi("CreateCompatibleDC"));
+        }
+
+        let hbitmap = CreateCompatibleBitmap(hdc_screen, width, height);
+        if hbitmap.is_invalid() {
+            let _ = DeleteDC(hdc_mem);
+            ReleaseDC(None, hdc_screen);
+            return Err(Error::Gdi("CreateCompatibleBitmap"));
+        }
+
+        let old_bitmap = SelectObject(hdc_mem, hbitmap);
+
+        // CAPTUREBLT: include layered windows
+        let blt_result = BitBlt(hdc_mem, 0, 0, width, height, hdc_screen, x, y, SRCCOPY | CAPTUREBLT);
+
+        let pixels = if blt_result.is_ok() {
+            draw_cursor(hdc_mem, x, y);
+            read_bitmap_rgb(hdc_mem, hbitmap, width, height)
+        } else {
+            Err(Error::Gdi("BitBlt"))
+        };
 
         // Cleanup
         SelectObject(hdc_mem, old_bitmap);
         let _ = DeleteObject(hbitmap);
         let _ = DeleteDC(hdc_mem);
-        ReleaseDC(hwnd, hdc_window);
+        ReleaseDC(None, hdc_screen);
+
+        Ok((pixels?, width, height))
+    }
+}
 
-        if lines == 0 {
-            return Err("GetDIBits failed".to_string());
+/// Renders a caption bar (white text on black) as RGB pixels of the given width
+fn render_caption(text: &str, width: i32) -> Result<Vec<u8>, Error> {
+    let text_wide: Vec<u16> = text.encode_utf16().collect();
+    unsafe {
+        let hdc_screen = GetDC(None);
+        if hdc_screen.is_invalid() {
+            return Err(Error::Gdi("GetDC"));
+        }
+        let hdc_mem = CreateCompatibleDC(hdc_screen);
+        let hbitmap = CreateCompatibleBitmap(hdc_screen, width, CAPTION_HEIGHT);
+        ReleaseDC(None, hdc_screen);
+        if hdc_mem.is_invalid() || hbitmap.is_invalid() {
+            let _ = DeleteObject(hbitmap);
+            let _ = DeleteDC(hdc_mem);
+            return Err(Error::Gdi("CreateCompatibleBitmap (caption)"));
         }
 
-        // Convert BGR to RGB and remove padding
-        let mut rgb_pixels: Vec<u8> = Vec::with_capacity((width * height * 3) as usize);
-        for row in 0..height {
-            let row_start = (row * row_size) as usize;
-            for col in 0..width {
-                let pixel_start = row_start + (col * 3) as usize;
-                // BGR -> RGB
-                rgb_pixels.push(pixels[pixel_start + 2]); // R
-                rgb_pixels.push(pixels[pixel_start + 1]); // G
-                rgb_pixels.push(pixels[pixel_start]);     // B
-            }
+        let old_bitmap = SelectObject(hdc_mem, hbitmap);
+        let old_font = SelectObject(hdc_mem, GetStockObject(DEFAULT_GUI_FONT));
+
+        let rect = RECT { left: 0, top: 0, right: width, bottom: CAPTION_HEIGHT };
+        FillRect(hdc_mem, &rect, HBRUSH(GetStockObject(BLACK_BRUSH).0));
+        SetBkMode(hdc_mem, TRANSPARENT);
+        SetTextColor(hdc_mem, COLORREF(0x00FFFFFF));
+        let _ = TextOutW(hdc_mem, 6, 4, &text_wide);
+
+        let pixels = read_bitmap_rgb(hdc_mem, hbitmap, width, CAPTION_HEIGHT);
+
+        SelectObject(hdc_mem, old_font);
+        SelectObject(hdc_mem, old_bitmap);
+        let _ = DeleteObject(hbitmap);
+        let _ = DeleteDC(hdc_mem);
+
+        pixels
+    }
+}
+
+/// Current cursor position (screen coordinates)
+pub fn cursor_position() -> Option<(i32, i32)> {
+    let mut point = POINT::default();
+    unsafe { GetCursorPos(&mut point) }.ok()?;
+    Some((point.x, point.y))
+}
+
+/// Draws the current cursor at its real position onto a capture
+/// (origin = screen position of the captured area's top left corner)
+unsafe fn draw_cursor(hdc: HDC, origin_x: i32, origin_y: i32) {
+    let mut cursor = CURSORINFO {
+        cbSize: std::mem::size_of::<CURSORINFO>() as u32,
+        ..Default::default()
+    };
+    if GetCursorInfo(&mut cursor).is_err() || cursor.flags != CURSOR_SHOWING || cursor.hCursor.is_invalid() {
+        return;
+    }
+
+    // ptScreenPos is the hotspot - the icon is drawn from its top left corner
+    let icon = HICON(cursor.hCursor.0);
+    let mut icon_info = ICONINFO::default();
+    let (hotspot_x, hotspot_y) = if GetIconInfo(icon, &mut icon_info).is_ok() {
+        let _ = DeleteObject(icon_info.hbmMask);
+        if !icon_info.hbmColor.is_invalid() {
+            let _ = DeleteObject(icon_info.hbmColor);
         }
+        (icon_info.xHotspot as i32, icon_info.yHotspot as i32)
+    } else {
+        (0, 0)
+    };
+
+    let _ = DrawIconEx(
+        hdc,
+        cursor.ptScreenPos.x - hotspot_x - origin_x,
+        cursor.ptScreenPos.y - hotspot_y - origin_y,
+        icon,
+        0,
+        0,
+        0,
+        None,
+        DI_NORMAL | DI_DEFAULTSIZE,
+    );
+}
+
+/// Reads the pixels of a bitmap as RGB (top-down, without padding)
+unsafe fn read_bitmap_rgb(hdc: HDC, hbitmap: HBITMAP, width: i32, height: i32) -> Result<Vec<u8>, Error> {
+    let mut bmi = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width,
+            biHeight: -height, // Negative = Top-Down
+            biPlanes: 1,
+            biBitCount: 24, // RGB
+            biCompression: BI_RGB.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
 
-        Ok((rgb_pixels, width, height))
+    let row_size = ((width * 3 + 3) / 4) * 4; // DWORD-aligned
+    let mut pixels: Vec<u8> = vec![0; (row_size * height) as usize];
+
+    let lines = GetDIBits(
+        hdc,
+        hbitmap,
+        0,
+        height as u32,
+        Some(pixels.as_mut_ptr() as *mut _),
+        &mut bmi,
+        DIB_RGB_COLORS,
+    );
+
+    if lines == 0 {
+        return Err(Error::Gdi("GetDIBits"));
+    }
+
+    // Convert BGR to RGB and remove padding
+    let mut rgb_pixels: Vec<u8> = Vec::with_capacity((width * height * 3) as usize);
+    for row in 0..height {
+        let row_start = (row * row_size) as usize;
+        for col in 0..width {
+            let pixel_start = row_start + (col * 3) as usize;
+            // BGR -> RGB
+            rgb_pixels.push(pixels[pixel_start + 2]); // R
+            rgb_pixels.push(pixels[pixel_start + 1]); // G
+            rgb_pixels.push(pixels[pixel_start]);     // B
+        }
+    }
+
+    Ok(rgb_pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folder_names_end_with_the_shared_process_part() {
+        let name = "a_very_long_process_name_from_some_vendor_installer";
+        let shallow = Path::new("C:\\PCWatcher\\logs\\user\\session-1");
+        let deep = PathBuf::from(format!("C:\\{}\\logs\\user\\session-1", "x".repeat(190)));
+
+        assert_eq!(folder_process_name(shallow, name).len(), 30);
+        assert_eq!(folder_process_name(&deep, name).len(), 8);
+        for dir in [shallow, deep.as_path()] {
+            let folder = folder_name(dir, "2026-10-15_08-30-00", name);
+            assert!(folder.ends_with(&format!("_{}", folder_process_name(dir, name))));
+        }
     }
 }
@@ -0,0 +1,148 @@
+//! Pipeline Self-Test
+//!
+//! Monitoring the monitor: periodically a synthetic SELFTEST window event (on the own alert
+//! window) is sent through the event worker and logger. If it does not reach the event log
+//! and the GUI list within the deadline, the pipeline is broken and a Critical alert goes
+//! directly to the sinks (the logger may be the broken part).
+
+use crate::event_hook::EventType;
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use chrono::Local;
+use parking_lot::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+use windows::Win32::Foundation::HWND;
+
+/// Event type of the probe in the log
+pub const EVENT_TYPE: &str = "SELFTEST";
+
+/// First test after start (all workers and the window are up)
+const START_DELAY: Duration = Duration::from_secs(120);
+
+/// Check interval while waiting (exit, deadline)
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Stage the probe has to reach
+#[derive(Debug, Clone, Copy)]
+pub enum Stage {
+    /// Written to the event log
+    Log,
+    /// Added to the GUI list
+    Gui,
+}
+
+/// Probe on its way through the pipeline
+#[derive(Default)]
+struct Pending {
+    logged: bool,
+    shown: bool,
+}
+
+lazy_static::lazy_static! {
+    // Current probe (None = no test running, late probes are ignored)
+    static ref PENDING: Mutex<Option<Pending>> = Mutex::new(None);
+}
+
+/// Marks a stage as reached (called for every entry, only probes count)
+pub fn confirm(entry: &LogEntry, stage: Stage) {
+    if entry.event_type != EVENT_TYPE {
+        return;
+    }
+    if let Some(pending) = PENDING.lock().as_mut() {
+        match stage {
+            Stage::Log => pending.logged = true,
+            Stage::Gui => pending.shown = true,
+        }
+    }
+}
+
+/// Sends one probe and waits for it: None = passed, otherwise what failed
+fn run_once(deadline: Duration) -> Option<String> {
+    let hwnd = crate::alert_window::window_hwnd();
+    if hwnd == 0 {
+        return Some("alert window is not running".to_string());
+    }
+
+    *PENDING.lock() = Some(Pending::default());
+    let started = Instant::now();
+    crate::event_hook::send_window_event(EventType::SelfTest, HWND(hwnd as *mut _));
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let passed = PENDING.lock().as_ref().is_some_and(|p| p.logged && p.shown);
+        if passed {
+            *PENDING.lock() = None;
+            info!("Self-test passed ({} ms)", started.elapsed().as_millis());
+            return None;
+        }
+        if started.elapsed() >= deadline || crate::tray::should_exit() {
+            break;
+        }
+    }
+
+    let pending = PENDING.lock().take().unwrap_or_default();
+    let missing = if pending.logged { "event did not reach the GUI" } else { "event did not reach the event log" };
+    Some(format!("{} within {}s", missing, deadline.as_secs()))
+}
+
+/// Critical alert through all sinks, bypassing the logger
+fn report_failure(reason: &str) {
+    let text = format!("Self-test failed: {}", reason);
+    error!("!!! SELF-TEST FAILED: {} !!!", reason);
+
+    let entry = LogEntry {
+        timestamp: Local::now(),
+        event_type: "SELFTEST_FAILED".to_string(),
+        severity: Severity::Critical,
+        process_name: "pc_watcher".to_string(),
+        window_title: text.clone(),
+        ..Default::default()
+    };
+    if crate::rules::is_enabled(Rule::SelfTest) {
+        crate::alert_window::set_rule_alert(Rule::SelfTest, &text, "", Severity::Critical);
+        crate::sound::play_alert(Rule::SelfTest, Severity::Critical);
+    }
+    crate::mqtt::publish_event(&entry);
+    crate::syslog::send(&entry);
+    crate::discord::send(&entry);
+    crate::push::send(&entry);
+    // Also logged in case the logger still works
+    crate::event_hook::send_log_entry(entry);
+}
+
+/// Starts the periodic self-test
+pub fn start_monitor() {
+    if !crate::config::get().selftest.enabled {
+        return;
+    }
+    thread::spawn(|| {
+        thread::sleep(START_DELAY);
+        let mut failing = false;
+        while !crate::tray::should_exit() {
+            let config = crate::config::get().selftest;
+            if config.enabled {
+                match run_once(Duration::from_secs(config.deadline_seconds.max(1))) {
+                    // One alert per failure streak
+                    Some(reason) if !failing => {
+                        report_failure(&reason);
+                        failing = true;
+                    }
+                    Some(reason) => error!("Self-test still failing: {}", reason),
+                    None if failing => {
+                        info!("Self-test passes again");
+                        failing = false;
+                    }
+                    None => {}
+                }
+            }
+
+            let next = Instant::now() + Duration::from_secs(config.interval_minutes.max(1) * 60);
+            while Instant::now() < next && !crate::tray::should_exit() {
+                thread::sleep(Duration::from_secs(1));
+            }
+        }
+    });
+}
@@ -0,0 +1,195 @@
+//! Central Server
+//!
+//! `pc_watcher server` collects the event streams of several watchers into one database
+//! and serves a combined dashboard. Protocol: length-prefixed JSON frames (u32 big endian
+//! + UTF-8 JSON) over TCP, TLS if a server certificate is configured.
+//!
+//! Client -> server: `hello` {machine, version, token}, `event` {id, event}, `ping`
+//! Server -> client: `welcome` {last_id}, `ack` {id}, `pong`, `error` {message}
+
+use crate::tls::{token_matches, Certificate, TlsStream, Transport};
+use parking_lot::Mutex;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Largest accepted frame (one event with long command line fits easily)
+const MAX_FRAME_BYTES: usize = 1024 * 1024;
+
+/// Largest hello frame (read before the token is checked)
+const MAX_HELLO_BYTES: usize = 4 * 1024;
+
+/// Connections without any frame for this long are closed (clients ping every 30s)
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Writes one frame
+pub fn write_frame(stream: &mut dyn Write, message: &Value) -> io::Result<()> {
+    let data = message.to_string();
+    stream.write_all(&(data.len() as u32).to_be_bytes())?;
+    stream.write_all(data.as_bytes())?;
+    stream.flush()
+}
+
+/// Reads one frame
+pub fn read_frame(stream: &mut dyn Read) -> io::Result<Value> {
+    read_frame_limited(stream, MAX_FRAME_BYTES)
+}
+
+/// Reads one frame of at most max_bytes
+fn read_frame_limited(stream: &mut dyn Read, max_bytes: usize) -> io::Result<Value> {
+    let mut length = [0u8; 4];
+    stream.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length) as usize;
+    if length > max_bytes {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("frame too large: {} bytes", length)));
+    }
+    let mut data = vec![0u8; length];
+    stream.read_exact(&mut data)?;
+    serde_json::from_slice(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Runs the server until the process is ended (blocks)
+pub fn run() -> Result<(), String> {
+    let config = crate::config::get().server;
+
+    // Plaintext / unauthenticated only on request
+    let dashboard_open = config.dashboard_token.is_empty() && crate::api_tokens::list().is_empty();
+    if config.tls_certificate.is_empty() || config.tokens.is_empty() || dashboard_open {
+        if !config.allow_insecure {
+            return Err(
+                "tls_certificate, tokens and dashboard_token (or an API token) are required (or set allow_insecure)"
+                    .to_string(),
+            );
+        }
+        warn!("Running insecure: missing TLS certificate or tokens");
+    }
+
+    let certificate = match config.tls_certificate.as_str() {
+        "" => None,
+        file => Some(Arc::new(Certificate::load(file)?)),
+    };
+    if let Some(certificate) = &certificate {
+        info!("Server certificate SHA-256 (for pinned_sha256): {}", certificate.fingerprint());
+    }
+    let client_pins = Arc::new(config.client_pins.clone());
+
+    let conn = crate::database::open_server().map_err(|e| format!("Server database: {}", e))?;
+    let conn = Arc::new(Mutex::new(conn));
+    info!("Server database: {}", crate::database::get_server_db_path().display());
+
+    let dashboard_conn = conn.clone();
+    let dashboard_address = config.dashboard.clone();
+    let dashboard_certificate = certificate.clone();
+    thread::spawn(move || {
+        if let Err(e) = crate::dashboard::run(&dashboard_address, dashboard_certificate, dashboard_conn) {
+            error!("Dashboard failed: {}", e);
+        }
+    });
+
+    let listener = TcpListener::bind(&config.listen).map_err(|e| format!("Cannot listen on {}: {}", config.listen, e))?;
+    info!("Waiting for watchers on {}", config.listen);
+
+    for tcp in listener.incoming() {
+        let tcp = match tcp {
+            Ok(tcp) => tcp,
+            Err(e) => {
+                warn!("Accept failed: {}", e);
+                continue;
+            }
+        };
+        let conn = conn.clone();
+        let certificate = certificate.clone();
+        let client_pins = client_pins.clone();
+        thread::spawn(move || {
+            let address = tcp.peer_addr().map(|a| a.ip().to_string()).unwrap_or_default();
+            if let Err(e) = handle_client(tcp, &address, certificate.as_deref(), &client_pins, &conn) {
+                warn!("Watcher {} disconnected: {}", address, e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Serves one watcher connection
+fn handle_client(
+    tcp: TcpStream,
+    address: &str,
+    certificate: Option<&Certificate>,
+    client_pins: &[String],
+    conn: &Mutex<Connection>,
+) -> Result<(), String> {
+    tcp.set_read_timeout(Some(CLIENT_TIMEOUT)).map_err(|e| e.to_string())?;
+    let _ = tcp.set_nodelay(true);
+    // Fingerprint of the client certificate (mTLS) for the connect log
+    let (mut stream, client_certificate): (Box<dyn Transport>, Option<String>) = match certificate {
+        Some(certificate) => {
+            let stream = TlsStream::accept(tcp, certificate, client_pins)?;
+            let fingerprint = stream.peer_fingerprint().ok();
+            (Box::new(stream), fingerprint)
+        }
+        None => (Box::new(tcp), None),
+    };
+
+    // Hello first: identifies the machine
+    let hello = read_frame_limited(&mut stream, MAX_HELLO_BYTES).map_err(|e| e.to_string())?;
+    let machine = hello["machine"].as_str().unwrap_or_default().trim().to_string();
+    if hello["type"] != "hello" || machine.is_empty() {
+        let _ = write_frame(&mut stream, &json!({ "type": "error", "message": "hello expected" }));
+        return Err("no hello".to_string());
+    }
+    let version = hello["version"].as_str().unwrap_or_default();
+
+    let tokens = crate::config::get().server.tokens;
+    if !tokens.is_empty() {
+        let token = hello["token"].as_str().unwrap_or_default();
+        if !tokens.get(&machine).is_some_and(|expected| token_matches(expected, token)) {
+            let _ = write_frame(&mut stream, &json!({ "type": "error", "message": "unknown machine or wrong token" }));
+            return Err(format!("{} rejected (unknown machine or wrong token)", machine));
+        }
+    }
+
+    let last_id = {
+        let conn = conn.lock();
+        crate::database::touch_machine(&conn, &machine, address, version).map_err(|e| e.to_string())?;
+        crate::database::last_machine_event(&conn, &machine).map_err(|e| e.to_string())?
+    };
+    write_frame(&mut stream, &json!({ "type": "welcome", "last_id": last_id })).map_err(|e| e.to_string())?;
+    let client_certificate = client_certificate.map(|f| format!(", client certificate {}", f)).unwrap_or_default();
+    info!(
+        "Watcher {} connected from {} (resuming after event {}{})",
+        machine, address, last_id, client_certificate
+    );
+
+    loop {
+        let message = match read_frame(&mut stream) {
+            Ok(message) => message,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                info!("Watcher {} disconnected", machine);
+                return Ok(());
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let reply = match message["type"].as_str().unwrap_or_default() {
+            "event" => {
+                let id = message["id"].as_i64().unwrap_or_default();
+                let entry = crate::logger::LogEntry::from_json(&message["event"]).ok_or("invalid event")?;
+                let conn = conn.lock();
+                crate::database::insert_machine_event(&conn, &machine, id, &entry).map_err(|e| e.to_string())?;
+                json!({ "type": "ack", "id": id })
+            }
+            "ping" => {
+                let conn = conn.lock();
+                crate::database::touch_machine(&conn, &machine, address, version).map_err(|e| e.to_string())?;
+                json!({ "type": "pong" })
+            }
+            other => json!({ "type": "error", "message": format!("unknown message type: {}", other) }),
+        };
+        write_frame(&mut stream, &reply).map_err(|e| e.to_string())?;
+    }
+}
@@ -0,0 +1,219 @@
+//! Service and Driver Monitoring
+//!
+//! Persistent spyware usually installs a service (or a kernel driver). The Service Control
+//! Manager database is polled and compared with the previous snapshot: new services and
+//! drivers, and drivers that were loaded since, are logged and raise a Critical alert.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use chrono::Local;
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use windows::core::{PCWSTR, PWSTR};
+use windows::Win32::Foundation::ERROR_MORE_DATA;
+use windows::Win32::System::Services::{
+    CloseServiceHandle, EnumServicesStatusExW, OpenSCManagerW, OpenServiceW, QueryServiceConfigW,
+    ENUM_SERVICE_STATUS_PROCESSW, QUERY_SERVICE_CONFIGW, SC_ENUM_PROCESS_INFO, SC_HANDLE,
+    SC_MANAGER_ENUMERATE_SERVICE, SERVICE_AUTO_START, SERVICE_DRIVER, SERVICE_QUERY_CONFIG, SERVICE_RUNNING,
+    SERVICE_STATE_ALL, SERVICE_WIN32,
+};
+
+/// Buffer for one enumeration call (u64 for the alignment of the records)
+const ENUM_BUFFER_WORDS: usize = 32 * 1024;
+
+/// One entry of the SCM database
+#[derive(Debug, Clone)]
+struct Service {
+    display_name: String,
+    driver: bool,
+    running: bool,
+}
+
+/// Details read on demand (only for reported services)
+struct ServiceConfig {
+    binary_path: String,
+    auto_start: bool,
+    account: String,
+}
+
+/// String of a PWSTR from the SCM (empty for null)
+unsafe fn read_pwstr(text: PWSTR) -> String {
+    if text.is_null() {
+        String::new()
+    } else {
+        text.to_string().unwrap_or_default()
+    }
+}
+
+/// All services and drivers by name
+unsafe fn snapshot(scm: SC_HANDLE) -> Result<HashMap<String, Service>, String> {
+    let mut services = HashMap::new();
+    let mut buffer = vec![0u64; ENUM_BUFFER_WORDS];
+    let mut resume: u32 = 0;
+    loop {
+        let bytes = std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 8);
+        let mut needed: u32 = 0;
+        let mut returned: u32 = 0;
+        let result = EnumServicesStatusExW(
+            scm,
+            SC_ENUM_PROCESS_INFO,
+            SERVICE_WIN32 | SERVICE_DRIVER,
+            SERVICE_STATE_ALL,
+            Some(bytes),
+            &mut needed,
+            &mut returned,
+            Some(&mut resume),
+            PCWSTR::null(),
+        );
+        let more = match result {
+            Ok(()) => false,
+            Err(e) if e.code() == ERROR_MORE_DATA.to_hresult() => true,
+            Err(e) => return Err(format!("EnumServicesStatusEx: {}", e)),
+        };
+
+        let records = std::slice::from_raw_parts(buffer.as_ptr() as *const ENUM_SERVICE_STATUS_PROCESSW, returned as usize);
+        for record in records {
+            let status = record.ServiceStatusProcess;
+            services.insert(
+                read_pwstr(record.lpServiceName),
+                Service {
+                    display_name: read_pwstr(record.lpDisplayName),
+                    driver: status.dwServiceType.0 & SERVICE_DRIVER.0 != 0,
+                    running: status.dwCurrentState == SERVICE_RUNNING,
+                },
+            );
+        }
+        if !more {
+            return Ok(services);
+        }
+    }
+}
+
+/// Binary path, start type and account of a service
+unsafe fn query_config(scm: SC_HANDLE, name: &str) -> Option<ServiceConfig> {
+    let wide: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+    let service = OpenServiceW(scm, PCWSTR(wide.as_ptr()), SERVICE_QUERY_CONFIG).ok()?;
+
+    let mut needed: u32 = 0;
+    let _ = QueryServiceConfigW(service, None, 0, &mut needed);
+    let mut buffer = vec![0u64; (needed as usize).div_ceil(8).max(1)];
+    let config = buffer.as_mut_ptr() as *mut QUERY_SERVICE_CONFIGW;
+    let result = QueryServiceConfigW(service, Some(config), (buffer.len() * 8) as u32, &mut needed);
+    let _ = CloseServiceHandle(service);
+    result.ok()?;
+
+    let config = &*config;
+    Some(ServiceConfig {
+        binary_path: read_pwstr(config.lpBinaryPathName),
+        auto_start: config.dwStartType == SERVICE_AUTO_START,
+        account: read_pwstr(config.lpServiceStartName),
+    })
+}
+
+/// Checks if a service is expected (ignored list of the config)
+fn is_ignored(name: &str) -> bool {
+    let name = name.to_lowercase();
+    crate::config::get().services.ignored.iter().any(|i| i.to_lowercase() == name)
+}
+
+/// Logs a new service / loaded driver and alerts, unless the rule is disabled
+unsafe fn report(scm: SC_HANDLE, name: &str, service: &Service, event_type: &str) {
+    if is_ignored(name) {
+        return;
+    }
+    let config = query_config(scm, name);
+    let binary_path = config.as_ref().map(|c| c.binary_path.clone()).unwrap_or_default();
+    let kind = if service.driver { "Driver" } else { "Service" };
+    let action = if event_type == "DRIVER_LOADED" { "loaded" } else { "installed" };
+
+    let severity = Severity::Critical;
+    warn!("!!! {} {}: {} ({}) - {} !!!", kind.to_uppercase(), action.to_uppercase(), name, service.display_name, binary_path);
+    if crate::rules::is_enabled(Rule::ServiceInstalled) {
+        crate::alert_window::set_rule_alert(
+            Rule::ServiceInstalled,
+            &format!("{} {}: {}", kind, action, name),
+            &binary_path,
+            severity,
+        );
+        crate::sound::play_alert(Rule::ServiceInstalled, severity);
+        let capture = crate::rules::capture_mode(Rule::ServiceInstalled, severity);
+        crate::screenshot::capture_alert_screenshots(name.to_string(), event_type, capture, severity);
+    }
+
+    let details = match &config {
+        Some(c) => format!(
+            "{} ({}, {}{})",
+            service.display_name,
+            kind,
+            if c.auto_start { "auto start" } else { "manual/boot start" },
+            if c.account.is_empty() { String::new() } else { format!(", runs as {}", c.account) }
+        ),
+        None => format!("{} ({})", service.display_name, kind),
+    };
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: Local::now(),
+        event_type: event_type.to_string(),
+        severity,
+        process_name: name.to_string(),
+        process_path: binary_path.clone(),
+        window_title: details,
+        window_class: kind.to_string(),
+        command_line: Some(binary_path).filter(|p| !p.is_empty()),
+        ..Default::default()
+    });
+}
+
+/// Starts the polling thread (only if enabled in the config)
+pub fn start_monitor() {
+    let settings = crate::config::get().services;
+    if !settings.enabled {
+        return;
+    }
+
+    thread::spawn(move || {
+        let scm = match unsafe { OpenSCManagerW(PCWSTR::null(), PCWSTR::null(), SC_MANAGER_ENUMERATE_SERVICE) } {
+            Ok(scm) => scm,
+            Err(e) => {
+                error!("Service monitor disabled: cannot open the Service Control Manager: {}", e);
+                return;
+            }
+        };
+        // Baseline: what exists at start is not reported
+        let mut known = match unsafe { snapshot(scm) } {
+            Ok(services) => services,
+            Err(e) => {
+                error!("Service monitor disabled: {}", e);
+                return;
+            }
+        };
+        info!("Service monitor started: {} services and drivers, every {}s", known.len(), settings.interval_secs);
+
+        while !crate::tray::should_exit() {
+            thread::sleep(Duration::from_secs(settings.interval_secs.max(1)));
+            let current = match unsafe { snapshot(scm) } {
+                Ok(services) => services,
+                Err(e) => {
+                    error!("Service monitor: {}", e);
+                    continue;
+                }
+            };
+
+            for (name, service) in &current {
+                let event_type = match known.get(name) {
+                    None if service.driver => "DRIVER_INSTALLED",
+                    None => "SERVICE_INSTALLED",
+                    Some(previous) if service.driver && service.running && !previous.running => "DRIVER_LOADED",
+                    Some(_) => continue,
+                };
+                unsafe { report(scm, name, service, event_type) };
+            }
+            known = current;
+        }
+        unsafe {
+            let _ = CloseServiceHandle(scm);
+        }
+    });
+}
@@ -0,0 +1,714 @@
+//! Sigma Rule Import
+//!
+//! Converts Sigma rules of the `process_creation` category into detections of the config
+//! (see detection.rs). Supported: the fields Image, ParentImage, CommandLine and
+//! OriginalFileName (matched against the file name of the image), the modifiers contains,
+//! startswith, endswith, all, windash and re (CommandLine only), and conditions built from selection names,
+//! `1 of`/`all of`, `and`, `or`, `not` and parentheses. Rules using anything else are skipped
+//! with the reason. Only the YAML subset used by Sigma rules is parsed.
+
+use crate::config::{DetectionRule, ProcessMatch};
+use crate::notification::Severity;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Parsed YAML value
+#[derive(Debug, Clone)]
+enum Yaml {
+    Scalar(String),
+    List(Vec<Yaml>),
+    Map(Vec<(String, Yaml)>),
+}
+
+impl Yaml {
+    fn get(&self, key: &str) -> Option<&Yaml> {
+        match self {
+            Yaml::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Yaml::Scalar(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Line of a YAML document: indentation and content without comment
+struct Line {
+    indent: usize,
+    text: String,
+}
+
+/// Removes a `#` comment outside of quotes
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '#') if previous == ' ' => return &line[..i],
+            _ => {}
+        }
+        previous = c;
+    }
+    line
+}
+
+/// Unquotes a scalar ('' and \" escapes)
+fn scalar(text: &str) -> String {
+    let text = text.trim();
+    if text.len() >= 2 && text.starts_with('\'') && text.ends_with('\'') {
+        return text[1..text.len() - 1].replace("''", "'");
+    }
+    if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+        return text[1..text.len() - 1].replace("\\\"", "\"").replace("\\\\", "\\");
+    }
+    text.to_string()
+}
+
+/// Scalar or flow list (`[a, b]`)
+fn inline_value(text: &str) -> Yaml {
+    let text = text.trim();
+    match text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        Some(items) if !items.trim().is_empty() => Yaml::List(items.split(',').map(|i| Yaml::Scalar(scalar(i))).collect()),
+        Some(_) => Yaml::List(Vec::new()),
+        None => Yaml::Scalar(scalar(text)),
+    }
+}
+
+/// Splits `key: value` at the first colon outside of quotes
+fn split_key(text: &str) -> Option<(String, &str)> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, ':') if text[i + 1..].is_empty() || text[i + 1..].starts_with(' ') => {
+                return Some((scalar(&text[..i]), text[i + 1..].trim()));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parses the block starting at `pos` with the given indentation
+fn parse_block(lines: &mut Vec<Line>, pos: &mut usize, indent: usize) -> Yaml {
+    if lines[*pos].text.starts_with('-') {
+        let mut items = Vec::new();
+        while *pos < lines.len() && lines[*pos].indent == indent && lines[*pos].text.starts_with('-') {
+            let rest = lines[*pos].text[1..].trim_start().to_string();
+            if rest.is_empty() {
+                *pos += 1;
+                let child = lines.get(*pos).map(|l| l.indent).filter(|i| *i > indent);
+                items.push(match child {
+                    Some(child) => parse_block(lines, pos, child),
+                    None => Yaml::Scalar(String::new()),
+                });
+            } else if split_key(&rest).is_some() {
+                // "- key: value" starts a map indented like its first key
+                let offset = lines[*pos].text.len() - rest.len();
+                lines[*pos] = Line { indent: indent + offset, text: rest };
+                items.push(parse_block(lines, pos, indent + offset));
+            } else {
+                items.push(inline_value(&rest));
+                *pos += 1;
+            }
+        }
+        return Yaml::List(items);
+    }
+
+    let mut entries = Vec::new();
+    while *pos < lines.len() && lines[*pos].indent == indent && !lines[*pos].text.starts_with('-') {
+        let text = lines[*pos].text.clone();
+        *pos += 1;
+        let Some((key, value)) = split_key(&text) else {
+            continue;
+        };
+        let value = if value.starts_with('|') || value.starts_with('>') {
+            // Block scalar: all deeper lines
+            let mut parts = Vec::new();
+            while *pos < lines.len() && lines[*pos].indent > indent {
+                parts.push(lines[*pos].text.clone());
+                *pos += 1;
+            }
+            Yaml::Scalar(parts.join("\n"))
+        } else if !value.is_empty() {
+            inline_value(value)
+        } else {
+            // Nested block (a list may start at the same indentation as its key)
+            match lines.get(*pos) {
+                Some(next) if next.indent > indent || (next.indent == indent && next.text.starts_with('-')) => {
+                    let child = next.indent;
+                    parse_block(lines, pos, child)
+                }
+                _ => Yaml::Scalar(String::new()),
+            }
+        };
+        entries.push((key, value));
+    }
+    Yaml::Map(entries)
+}
+
+/// Parses the documents of a YAML file (separated by `---`)
+fn parse_documents(content: &str) -> Vec<Yaml> {
+    let mut documents = Vec::new();
+    for document in content.split("\n---") {
+        let mut lines: Vec<Line> = document
+            .lines()
+            .map(|l| strip_comment(l).trim_end())
+            .filter(|l| !l.trim().is_empty() && l.trim() != "---")
+            .map(|l| Line { indent: l.len() - l.trim_start().len(), text: l.trim_start().to_string() })
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+        let mut pos = 0;
+        let indent = lines[0].indent;
+        documents.push(parse_block(&mut lines, &mut pos, indent));
+    }
+    documents
+}
+
+/// Condition as "matches one of `any` and none of `exclude`"
+#[derive(Debug, Clone)]
+struct Condition {
+    any: Vec<ProcessMatch>,
+    exclude: Vec<ProcessMatch>,
+}
+
+/// Combines two OR-lists of a field (an AND of OR-lists only works if one has a single entry)
+fn merge_list(a: &[String], b: &[String], all: &mut Vec<String>) -> Result<Vec<String>, String> {
+    match (a.len(), b.len()) {
+        (0, _) => Ok(b.to_vec()),
+        (_, 0) => Ok(a.to_vec()),
+        (1, _) => {
+            all.push(a[0].clone());
+            Ok(b.to_vec())
+        }
+        (_, 1) => {
+            all.push(b[0].clone());
+            Ok(a.to_vec())
+        }
+        _ => Err("two value lists for the same field".to_string()),
+    }
+}
+
+/// Both conditions must match (only expressible for the command line, see merge_list)
+fn merge(a: &ProcessMatch, b: &ProcessMatch) -> Result<ProcessMatch, String> {
+    let single = |x: &[String], y: &[String]| -> Result<Vec<String>, String> {
+        if !x.is_empty() && !y.is_empty() {
+            return Err("conditions on the same field combined with and".to_string());
+        }
+        Ok(if x.is_empty() { y.to_vec() } else { x.to_vec() })
+    };
+    let mut command_line_all: Vec<String> = a.command_line_all.iter().chain(&b.command_line_all).cloned().collect();
+    let command_line = merge_list(&a.command_line, &b.command_line, &mut command_line_all)?;
+    Ok(ProcessMatch {
+        image: single(&a.image, &b.image)?,
+        parent_image: single(&a.parent_image, &b.parent_image)?,
+        command_line,
+        command_line_all,
+        command_line_regex: single(&a.command_line_regex, &b.command_line_regex)?,
+        ..Default::default()
+    })
+}
+
+/// Values of a field as wildcard patterns
+fn patterns(value: &Yaml, modifiers: &[&str]) -> Result<Vec<String>, String> {
+    let values: Vec<String> = match value {
+        Yaml::Scalar(v) => vec![v.clone()],
+        Yaml::List(items) => items.iter().map(|i| i.as_str().map(str::to_string).ok_or("nested value list")).collect::<Result<_, _>>()?,
+        Yaml::Map(_) => return Err("map as field value".to_string()),
+    };
+    let mut result = Vec::new();
+    for value in values {
+        // windash: "-x" also as "/x"
+        let variants = if modifiers.contains(&"windash") && value.contains('-') {
+            vec![value.clone(), value.replace('-', "/")]
+        } else {
+            vec![value]
+        };
+        for v in variants {
+            result.push(if modifiers.contains(&"contains") {
+                format!("*{}*", v)
+            } else if modifiers.contains(&"startswith") {
+                format!("{}*", v)
+            } else if modifiers.contains(&"endswith") {
+                format!("*{}", v)
+            } else {
+                v
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// One map of a selection: all fields must match
+fn selection_map(entries: &[(String, Yaml)]) -> Result<ProcessMatch, String> {
+    let mut result = ProcessMatch::default();
+    for (key, value) in entries {
+        let mut parts = key.split('|');
+        let field = parts.next().unwrap_or_default();
+        let modifiers: Vec<&str> = parts.collect();
+        if let Some(unknown) = modifiers.iter().find(|m| !["contains", "startswith", "endswith", "all", "windash", "re"].contains(m)) {
+            return Err(format!("modifier {}", unknown));
+        }
+        if modifiers.contains(&"re") {
+            if field != "CommandLine" || modifiers.len() > 1 {
+                return Err(format!("modifier re on {}", key));
+            }
+            let condition = ProcessMatch { command_line_regex: patterns(value, &[])?, ..Default::default() };
+            result = merge(&result, &condition)?;
+            continue;
+        }
+        let values = patterns(value, &modifiers)?;
+        let mut condition = ProcessMatch::default();
+        match field {
+            "Image" => condition.image = values,
+            "ParentImage" => condition.parent_image = values,
+            "OriginalFileName" => condition.image = values.iter().map(|v| format!("*\\{}", v.trim_start_matches('*'))).collect(),
+            "CommandLine" if modifiers.contains(&"all") => condition.command_line_all = values,
+            "CommandLine" => condition.command_line = values,
+            _ => return Err(format!("field {}", field)),
+        }
+        result = merge(&result, &condition)?;
+    }
+    Ok(result)
+}
+
+/// A named selection: a map (AND of fields) or a list of maps (OR)
+fn selection(value: &Yaml) -> Result<Condition, String> {
+    let any = match value {
+        Yaml::Map(entries) => vec![selection_map(entries)?],
+        Yaml::List(items) => items
+            .iter()
+            .map(|item| match item {
+                Yaml::Map(entries) => selection_map(entries),
+                _ => Err("keyword list".to_string()),
+            })
+            .collect::<Result<_, _>>()?,
+        Yaml::Scalar(_) => return Err("keyword selection".to_string()),
+    };
+    Ok(Condition { any, exclude: Vec::new() })
+}
+
+fn and(a: Condition, b: Condition) -> Result<Condition, String> {
+    let mut any = Vec::new();
+    for x in &a.any {
+        for y in &b.any {
+            any.push(merge(x, y)?);
+        }
+    }
+    Ok(Condition { any, exclude: a.exclude.into_iter().chain(b.exclude).collect() })
+}
+
+fn or(a: Condition, b: Condition) -> Result<Condition, String> {
+    if !a.exclude.is_empty() || !b.exclude.is_empty() {
+        return Err("\"or\" with a negated part".to_string());
+    }
+    Ok(Condition { any: a.any.into_iter().chain(b.any).collect(), exclude: Vec::new() })
+}
+
+fn not(a: Condition) -> Result<Condition, String> {
+    if !a.exclude.is_empty() {
+        return Err("double negation".to_string());
+    }
+    Ok(Condition { any: vec![ProcessMatch::default()], exclude: a.any })
+}
+
+/// Recursive descent over the tokens of the condition
+struct ConditionParser<'a> {
+    tokens: Vec<String>,
+    pos: usize,
+    selections: &'a [(String, Yaml)],
+}
+
+impl ConditionParser<'_> {
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn expression(&mut self) -> Result<Condition, String> {
+        let mut result = self.term()?;
+        while self.peek() == Some("or") {
+            self.pos += 1;
+            result = or(result, self.term()?)?;
+        }
+        Ok(result)
+    }
+
+    fn term(&mut self) -> Result<Condition, String> {
+        let mut result = self.factor()?;
+        while self.peek() == Some("and") {
+            self.pos += 1;
+            result = and(result, self.factor()?)?;
+        }
+        Ok(result)
+    }
+
+    fn factor(&mut self) -> Result<Condition, String> {
+        match self.next().as_deref() {
+            Some("not") => not(self.factor()?),
+            Some("(") => {
+                let result = self.expression()?;
+                if self.next().as_deref() != Some(")") {
+                    return Err("unbalanced parentheses".to_string());
+                }
+                Ok(result)
+            }
+            Some(quantifier @ ("1" | "all")) => {
+                if self.next().as_deref() != Some("of") {
+                    return Err("expected \"of\"".to_string());
+                }
+                let pattern = self.next().ok_or("missing selection pattern")?;
+                // "them" = all selections except the helpers starting with "_"
+                let named: Vec<Condition> = self
+                    .selections
+                    .iter()
+                    .filter(|(name, _)| {
+                        if pattern == "them" { !name.starts_with('_') } else { crate::detection::wildcard_match(&pattern, name) }
+                    })
+                    .map(|(_, value)| selection(value))
+                    .collect::<Result<_, _>>()?;
+                let combine = if quantifier == "1" { or } else { and };
+                named
+                    .into_iter()
+                    .try_fold(None, |acc: Option<Condition>, c| match acc {
+                        Some(acc) => combine(acc, c).map(Some),
+                        None => Ok(Some(c)),
+                    })?
+                    .ok_or_else(|| format!("no selection matches {}", pattern))
+            }
+            Some(name) => {
+                let value = self.selections.iter().find(|(n, _)| n == name).map(|(_, v)| v);
+                selection(value.ok_or_else(|| format!("unknown selection {}", name))?)
+            }
+            None => Err("incomplete condition".to_string()),
+        }
+    }
+}
+
+/// Parses a condition string against the named selections
+fn parse_condition(condition: &str, selections: &[(String, Yaml)]) -> Result<Condition, String> {
+    if condition.contains('|') {
+        return Err("aggregation in condition".to_string());
+    }
+    let tokens = condition.replace('(', " ( ").replace(')', " ) ").split_whitespace().map(str::to_string).collect();
+    let mut parser = ConditionParser { tokens, pos: 0, selections };
+    let result = parser.expression()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(format!("unexpected \"{}\" in condition", parser.tokens[parser.pos]));
+    }
+    Ok(result)
+}
+
+/// Converts one Sigma rule
+fn convert(rule: &Yaml) -> Result<DetectionRule, String> {
+    let category = rule.get("logsource").and_then(|l| l.get("category")).and_then(Yaml::as_str);
+    if category != Some("process_creation") {
+        return Err(format!("category {} (only process_creation)", category.unwrap_or("none")));
+    }
+    if let Some(product) = rule.get("logsource").and_then(|l| l.get("product")).and_then(Yaml::as_str) {
+        if product != "windows" {
+            return Err(format!("product {}", product));
+        }
+    }
+    if rule.get("status").and_then(Yaml::as_str) == Some("deprecated") {
+        return Err("deprecated".to_string());
+    }
+
+    let Some(Yaml::Map(detection)) = rule.get("detection") else {
+        return Err("no detection".to_string());
+    };
+    let selections: Vec<(String, Yaml)> = detection.iter().filter(|(k, _)| k != "condition" && k != "timeframe").cloned().collect();
+    let condition = match detection.iter().find(|(k, _)| k == "condition").map(|(_, v)| v) {
+        Some(Yaml::Scalar(condition)) => parse_condition(condition, &selections)?,
+        // Several conditions: each one alone triggers the rule
+        Some(Yaml::List(conditions)) => conditions
+            .iter()
+            .map(|c| parse_condition(c.as_str().unwrap_or_default(), &selections))
+            .try_fold(None, |acc: Option<Condition>, c| match acc {
+                Some(acc) => or(acc, c?).map(Some),
+                None => c.map(Some),
+            })?
+            .ok_or("empty condition list")?,
+        _ => return Err("no condition".to_string()),
+    };
+    // Pure negations would match nearly every process
+    if condition.any.iter().any(ProcessMatch::is_empty) {
+        return Err("condition without positive selection".to_string());
+    }
+
+    let severity = match rule.get("level").and_then(Yaml::as_str) {
+        Some("critical" | "high") => Severity::Critical,
+        _ => Severity::Warning,
+    };
+    let title = rule.get("title").and_then(Yaml::as_str).unwrap_or("Sigma rule").to_string();
+    let id = rule.get("id").and_then(Yaml::as_str).map(str::to_string).unwrap_or_else(|| title.clone());
+    Ok(DetectionRule {
+        title,
+        source: Some(format!("sigma:{}", id)),
+        enabled: true,
+        severity,
+        selections: condition.any,
+        filters: condition.exclude,
+        // Sigma tags like "attack.execution"
+        tags: match rule.get("tags") {
+            Some(Yaml::List(items)) => items.iter().filter_map(Yaml::as_str).map(str::to_string).collect(),
+            _ => Vec::new(),
+        },
+    })
+}
+
+/// Result of an import
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    /// Titles of the imported rules
+    pub imported: Vec<String>,
+    /// File/title and reason of the skipped rules
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Rule files of a path (a file or all .yml/.yaml files below a folder)
+fn rule_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let entries = std::fs::read_dir(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let entry_path = entry.path();
+        if entry_path.is_dir() {
+            files.extend(rule_files(&entry_path)?);
+        } else if entry_path.extension().is_some_and(|e| e.eq_ignore_ascii_case("yml") || e.eq_ignore_ascii_case("yaml")) {
+            files.push(entry_path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Converts the Sigma rules of a file or folder and adds them to the detections of the config
+/// (an imported rule replaces the one with the same Sigma ID)
+pub fn import(path: &Path) -> Result<ImportSummary, String> {
+    let mut summary = ImportSummary::default();
+    let mut rules = Vec::new();
+    for file in rule_files(path)? {
+        let content = std::fs::read_to_string(&file).map_err(|e| format!("Could not read {}: {}", file.display(), e))?;
+        let file_name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        for document in parse_documents(&content) {
+            let name = document.get("title").and_then(Yaml::as_str).map(str::to_string).unwrap_or_else(|| file_name.clone());
+            match convert(&document) {
+                Ok(rule) => {
+                    summary.imported.push(rule.title.clone());
+                    rules.push(rule);
+                }
+                Err(reason) => summary.skipped.push((name, reason)),
+            }
+        }
+    }
+
+    if !rules.is_empty() {
+        crate::config::update(|config| {
+            config.detections.retain(|existing| !rules.iter().any(|r| r.source.is_some() && r.source == existing.source));
+            config.detections.extend(rules);
+        });
+    }
+    info!("Sigma import from {}: {} imported, {} skipped", path.display(), summary.imported.len(), summary.skipped.len());
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert_text(text: &str) -> Result<DetectionRule, String> {
+        convert(&parse_documents(text).remove(0))
+    }
+
+    /// Process creation rule with the given detection block (indented by 8)
+    fn with_detection(detection: &str) -> String {
+        format!("
+        title: Test
+        logsource:
+            category: process_creation
+            product: windows
+        detection:
+{}", detection)
+    }
+
+    #[test]
+    fn yaml_scalars_and_comments() {
+        assert_eq!(strip_comment("key: '#1' # comment"), "key: '#1' ");
+        assert_eq!(strip_comment("key: a#b"), "key: a#b");
+        assert_eq!(scalar("'it''s'"), "it's");
+        assert_eq!(scalar(r#""a \"b\" c:\\d""#), r#"a "b" c:\d"#);
+        assert_eq!(split_key("'a: b': c").map(|(k, v)| (k, v.to_string())), Some(("a: b".to_string(), "c".to_string())));
+        match inline_value("[a, 'b c']") {
+            Yaml::List(items) => {
+                let values: Vec<_> = items.iter().filter_map(Yaml::as_str).collect();
+                assert_eq!(values, ["a", "b c"]);
+            }
+            other => panic!("expected a list, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_nested_maps_lists_and_documents() {
+        let documents = parse_documents("
+        a:
+            b: 1
+            c:
+            - x
+            - y: 2
+              z: 3
+        text: |
+            line 1
+            line 2
+---
+        second: true
+        ");
+        assert_eq!(documents.len(), 2);
+        let first = &documents[0];
+        assert_eq!(first.get("a").and_then(|a| a.get("b")).and_then(Yaml::as_str), Some("1"));
+        let Some(Yaml::List(items)) = first.get("a").and_then(|a| a.get("c")) else {
+            panic!("expected a list");
+        };
+        assert_eq!(items[0].as_str(), Some("x"));
+        assert_eq!(items[1].get("z").and_then(Yaml::as_str), Some("3"));
+        assert_eq!(first.get("text").and_then(Yaml::as_str), Some("line 1\nline 2"));
+        assert_eq!(documents[1].get("second").and_then(Yaml::as_str), Some("true"));
+    }
+
+    #[test]
+    fn converts_selections_filters_and_metadata() {
+        let rule = convert_text(r"
+        title: Office starts PowerShell  # comment
+        id: 0a1b2c
+        logsource:
+            category: process_creation
+            product: windows
+        detection:
+            selection_parent:
+                ParentImage|endswith:
+                    - '\winword.exe'
+                    - '\excel.exe'
+            selection_img:
+                - Image|endswith: '\powershell.exe'
+                - OriginalFileName: 'PowerShell.EXE'
+            filter:
+                CommandLine|contains: 'Get-Help'
+            condition: all of selection_* and not filter
+        level: high
+        tags:
+            - attack.execution
+            - attack.t1059.001
+        ")
+        .unwrap();
+
+        assert_eq!(rule.title, "Office starts PowerShell");
+        assert_eq!(rule.source.as_deref(), Some("sigma:0a1b2c"));
+        assert_eq!(rule.severity, Severity::Critical);
+        assert_eq!(rule.tags, ["attack.execution", "attack.t1059.001"]);
+
+        // (winword or excel) and (powershell image or original name) = one selection per image
+        assert_eq!(rule.selections.len(), 2);
+        assert_eq!(rule.selections[0].parent_image, [r"*\winword.exe", r"*\excel.exe"]);
+        assert_eq!(rule.selections[0].image, [r"*\powershell.exe"]);
+        assert_eq!(rule.selections[1].image, [r"*\PowerShell.EXE"]);
+        assert_eq!(rule.filters.len(), 1);
+        assert_eq!(rule.filters[0].command_line, ["*Get-Help*"]);
+    }
+
+    #[test]
+    fn command_line_modifiers() {
+        let rule = convert_text(&with_detection("
+            selection:
+                CommandLine|windash|contains: '-enc'
+                CommandLine|contains|all:
+                    - 'Net.WebClient'
+                    - 'Download'
+            regex:
+                CommandLine|re: 'iex\\s*\\('
+            condition: selection or regex
+        "))
+        .unwrap();
+        assert_eq!(rule.selections.len(), 2);
+        assert_eq!(rule.selections[0].command_line, ["*-enc*", "*/enc*"]);
+        assert_eq!(rule.selections[0].command_line_all, ["*Net.WebClient*", "*Download*"]);
+        assert_eq!(rule.selections[1].command_line_regex, [r"iex\s*\("]);
+        assert_eq!(rule.severity, Severity::Warning);
+    }
+
+    #[test]
+    fn one_of_them_skips_helper_selections() {
+        let rule = convert_text(&with_detection("
+            selection_a:
+                Image|endswith: '\\a.exe'
+            selection_b:
+                Image|endswith: '\\b.exe'
+            _helper:
+                Image|endswith: '\\c.exe'
+            condition: 1 of them
+        "))
+        .unwrap();
+        let images: Vec<&str> = rule.selections.iter().map(|s| s.image[0].as_str()).collect();
+        assert_eq!(images, [r"*\a.exe", r"*\b.exe"]);
+    }
+
+    #[test]
+    fn unsupported_rules_are_skipped_with_the_reason() {
+        let skipped = |detection: &str| convert_text(&with_detection(detection)).unwrap_err();
+        assert_eq!(skipped("
+            selection:
+                User: admin
+            condition: selection
+        "), "field User");
+        assert_eq!(skipped("
+            selection:
+                Image|base64: x
+            condition: selection
+        "), "modifier base64");
+        assert_eq!(skipped("
+            selection:
+                Image: a
+            condition: not selection
+        "), "condition without positive selection");
+        assert_eq!(skipped("
+            selection:
+                Image: a
+            condition: selection | count() > 5
+        "), "aggregation in condition");
+        assert_eq!(skipped("
+            selection:
+                Image: a
+            condition: (selection
+        "), "unbalanced parentheses");
+        assert_eq!(skipped("
+            selection:
+                Image: a
+            condition: other
+        "), "unknown selection other");
+
+        let network = convert_text("
+        logsource:
+            category: network_connection
+        detection:
+            selection:
+                Image: a
+            condition: selection
+        ");
+        assert_eq!(network.unwrap_err(), "category network_connection (only process_creation)");
+    }
+}
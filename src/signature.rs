@@ -0,0 +1,132 @@
+//! Code Signatures
+//!
+//! Authenticode check of executables and DLLs with WinVerifyTrust: the embedded signature
+//! first, then the system catalogs (most Windows binaries are catalog-signed, without an
+//! embedded signature). Revocation is not checked, so no network access is needed.
+//! Results are cached per path.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fs::File;
+use std::os::windows::io::AsRawHandle;
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::{HANDLE, HWND};
+use windows::Win32::Security::Cryptography::Catalog::{
+    CryptCATAdminAcquireContext2, CryptCATAdminCalcHashFromFileHandle2, CryptCATAdminEnumCatalogFromHash,
+    CryptCATAdminReleaseCatalogContext, CryptCATAdminReleaseContext, CryptCATCatalogInfoFromContext, CATALOG_INFO,
+};
+use windows::Win32::Security::WinTrust::{
+    WinVerifyTrust, WINTRUST_ACTION_GENERIC_VERIFY_V2, WINTRUST_CATALOG_INFO, WINTRUST_DATA, WINTRUST_DATA_0,
+    WINTRUST_DATA_UNION_CHOICE, WINTRUST_FILE_INFO, WTD_CACHE_ONLY_URL_RETRIEVAL, WTD_CHOICE_CATALOG,
+    WTD_CHOICE_FILE, WTD_REVOCATION_CHECK_NONE, WTD_REVOKE_NONE, WTD_STATEACTION_CLOSE, WTD_STATEACTION_VERIFY,
+    WTD_UI_NONE,
+};
+
+/// Cached results (cleared when full)
+const MAX_CACHED: usize = 2048;
+
+lazy_static::lazy_static! {
+    // Signature state per file (key = lowercase path)
+    static ref SIGNED: Mutex<HashMap<String, bool>> = Mutex::new(HashMap::new());
+}
+
+/// Runs WinVerifyTrust for a file or catalog member (true = valid signature)
+unsafe fn verify(choice: WINTRUST_DATA_UNION_CHOICE, info: WINTRUST_DATA_0) -> bool {
+    let mut action = WINTRUST_ACTION_GENERIC_VERIFY_V2;
+    let mut data = WINTRUST_DATA {
+        cbStruct: std::mem::size_of::<WINTRUST_DATA>() as u32,
+        dwUIChoice: WTD_UI_NONE,
+        fdwRevocationChecks: WTD_REVOKE_NONE,
+        dwUnionChoice: choice,
+        Anonymous: info,
+        dwStateAction: WTD_STATEACTION_VERIFY,
+        dwProvFlags: WTD_REVOCATION_CHECK_NONE | WTD_CACHE_ONLY_URL_RETRIEVAL,
+        ..Default::default()
+    };
+    let result = WinVerifyTrust(HWND::default(), &mut action, &mut data as *mut _ as *mut _);
+    // Frees the state of the verification
+    data.dwStateAction = WTD_STATEACTION_CLOSE;
+    let _ = WinVerifyTrust(HWND::default(), &mut action, &mut data as *mut _ as *mut _);
+    result == 0
+}
+
+/// Embedded Authenticode signature
+unsafe fn verify_embedded(path: &[u16]) -> bool {
+    let mut file_info = WINTRUST_FILE_INFO {
+        cbStruct: std::mem::size_of::<WINTRUST_FILE_INFO>() as u32,
+        pcwszFilePath: PCWSTR(path.as_ptr()),
+        ..Default::default()
+    };
+    verify(WTD_CHOICE_FILE, WINTRUST_DATA_0 { pFile: &mut file_info })
+}
+
+/// Signature through a system catalog (SHA-256 catalogs first, then the older SHA-1 ones)
+unsafe fn verify_catalog(path: &str, path_wide: &[u16]) -> bool {
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    let handle = HANDLE(file.as_raw_handle());
+
+    for algorithm in [w!("SHA256"), w!("SHA1")] {
+        let mut admin = 0isize;
+        if CryptCATAdminAcquireContext2(&mut admin, None, algorithm, None, 0).is_err() {
+            continue;
+        }
+        let mut size = 0u32;
+        let _ = CryptCATAdminCalcHashFromFileHandle2(admin, handle, &mut size, None, 0);
+        let mut hash = vec![0u8; size as usize];
+        let mut valid = false;
+        if size > 0 && CryptCATAdminCalcHashFromFileHandle2(admin, handle, &mut size, Some(hash.as_mut_ptr()), 0).is_ok() {
+            let catalog = CryptCATAdminEnumCatalogFromHash(admin, &hash, 0, None);
+            if catalog != 0 {
+                let mut info = CATALOG_INFO { cbStruct: std::mem::size_of::<CATALOG_INFO>() as u32, ..Default::default() };
+                if CryptCATCatalogInfoFromContext(catalog, &mut info, 0).is_ok() {
+                    // Members are tagged with the hash as uppercase hex
+                    let tag: Vec<u16> = hash
+                        .iter()
+                        .map(|b| format!("{:02X}", b))
+                        .collect::<String>()
+                        .encode_utf16()
+                        .chain(std::iter::once(0))
+                        .collect();
+                    let mut catalog_info = WINTRUST_CATALOG_INFO {
+                        cbStruct: std::mem::size_of::<WINTRUST_CATALOG_INFO>() as u32,
+                        pcwszCatalogFilePath: PCWSTR(info.wszCatalogFile.as_ptr()),
+                        pcwszMemberTag: PCWSTR(tag.as_ptr()),
+                        pcwszMemberFilePath: PCWSTR(path_wide.as_ptr()),
+                        hMemberFile: handle,
+                        pbCalculatedFileHash: hash.as_mut_ptr(),
+                        cbCalculatedFileHash: hash.len() as u32,
+                        hCatAdmin: admin,
+                        ..Default::default()
+                    };
+                    valid = verify(WTD_CHOICE_CATALOG, WINTRUST_DATA_0 { pCatalog: &mut catalog_info });
+                }
+                let _ = CryptCATAdminReleaseCatalogContext(admin, catalog, 0);
+            }
+        }
+        let _ = CryptCATAdminReleaseContext(admin, 0);
+        if valid {
+            return true;
+        }
+    }
+    false
+}
+
+/// Checks if a file has a valid signature (embedded or by catalog)
+pub fn is_signed(path: &str) -> bool {
+    let key = path.to_lowercase();
+    if let Some(signed) = SIGNED.lock().get(&key) {
+        return *signed;
+    }
+
+    let path_wide: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+    let signed = unsafe { verify_embedded(&path_wide) || verify_catalog(path, &path_wide) };
+
+    let mut cache = SIGNED.lock();
+    if cache.len() >= MAX_CACHED {
+        cache.clear();
+    }
+    cache.insert(key, signed);
+    signed
+}
@@ -0,0 +1,102 @@
+//! Alert Snooze
+//!
+//! While a full-screen application is active (game, video, PowerPoint slideshow) or
+//! "Do not disturb" is switched on in the tray, visual alerts are queued instead of shown
+//! and the alert window stops forcing itself on top. Logging and evidence capture continue.
+//! Critical alerts are only held back by "Do not disturb". Queued alerts are shown with their
+//! own header once the snooze ends.
+
+use crate::notification::Severity;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use tracing::info;
+use windows::Win32::UI::Shell::{
+    SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_RUNNING_D3D_FULL_SCREEN,
+};
+
+/// How often the end of a snooze is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Alerts held back at most (the oldest are dropped, they stay in the log)
+const MAX_QUEUED: usize = 50;
+
+// Manual "Do not disturb" (tray menu, not saved)
+static DO_NOT_DISTURB: AtomicBool = AtomicBool::new(false);
+
+/// Alert held back during the snooze, shown as it would have been
+#[derive(Debug, Clone)]
+pub struct QueuedAlert {
+    pub header: String,
+    pub color: Option<u32>,
+    pub duration_secs: u64,
+    pub severity: Severity,
+}
+
+lazy_static::lazy_static! {
+    // Alerts held back during the snooze, oldest first
+    static ref QUEUED: Mutex<VecDeque<QueuedAlert>> = Mutex::new(VecDeque::new());
+}
+
+/// Full-screen app or presentation in the foreground (Windows notification state)
+pub fn is_fullscreen_active() -> bool {
+    match unsafe { SHQueryUserNotificationState() } {
+        Ok(state) => state == QUNS_BUSY || state == QUNS_RUNNING_D3D_FULL_SCREEN || state == QUNS_PRESENTATION_MODE,
+        Err(_) => false,
+    }
+}
+
+/// Are visual alerts suppressed right now?
+pub fn is_snoozed() -> bool {
+    DO_NOT_DISTURB.load(Ordering::SeqCst)
+        || (crate::config::get().snooze.on_fullscreen && is_fullscreen_active())
+}
+
+/// Manual "Do not disturb" state
+pub fn is_do_not_disturb() -> bool {
+    DO_NOT_DISTURB.load(Ordering::SeqCst)
+}
+
+/// Switches "Do not disturb" on/off
+pub fn toggle_do_not_disturb() {
+    let enabled = !DO_NOT_DISTURB.fetch_xor(true, Ordering::SeqCst);
+    info!("Do not disturb: {}", if enabled { "on" } else { "off" });
+}
+
+/// Switches "Do not disturb" on or off (remote interfaces)
+pub fn set_do_not_disturb(enabled: bool) {
+    DO_NOT_DISTURB.store(enabled, Ordering::SeqCst);
+    info!("Do not disturb: {}", if enabled { "on" } else { "off" });
+}
+
+/// Holds back an alert until the snooze ends
+pub fn queue_alert(alert: QueuedAlert) {
+    let mut queued = QUEUED.lock();
+    if queued.len() >= MAX_QUEUED {
+        queued.pop_front();
+    }
+    queued.push_back(alert);
+}
+
+/// Shows the queued alerts in their order (the header cycles through them)
+fn release_queued() {
+    let queued = std::mem::take(&mut *QUEUED.lock());
+    info!("Snooze ended - {} alerts were held back", queued.len());
+    for alert in queued {
+        crate::alert_window::show_queued_alert(alert);
+    }
+}
+
+/// Starts the thread that releases queued alerts after the snooze
+pub fn start_monitor() {
+    thread::spawn(|| {
+        while !crate::tray::should_exit() {
+            thread::sleep(CHECK_INTERVAL);
+            if !QUEUED.lock().is_empty() && !is_snoozed() {
+                release_queued();
+            }
+        }
+    });
+}
@@ -0,0 +1,70 @@
+//! Alert Sounds
+//!
+//! Plays a sound for Warning and Critical alerts: a Windows system sound alias
+//! ("SystemExclamation", "SystemHand", ...) or a WAV file. Single rules can override the
+//! sound, "none" keeps an alert silent. Muted from the tray, silent while alerts are snoozed.
+
+use crate::notification::Severity;
+use crate::rules::Rule;
+use std::path::PathBuf;
+use tracing::{info, warn};
+use windows::core::PCWSTR;
+use windows::Win32::Media::Audio::{PlaySoundW, SND_ALIAS, SND_ASYNC, SND_FILENAME, SND_NODEFAULT};
+
+/// Sound for an alert of a rule (rule setting, otherwise the severity default)
+pub fn play_alert(rule: Rule, severity: Severity) {
+    play(&crate::rules::alert_sound(rule, severity));
+}
+
+/// Sound for an alert without rule (e.g. low disk space)
+pub fn play_default(severity: Severity) {
+    play(&crate::config::get().sounds.for_severity(severity));
+}
+
+/// Global mute (tray menu, saved)
+pub fn is_muted() -> bool {
+    crate::config::get().sounds.muted
+}
+
+/// Switches the global mute on/off
+pub fn toggle_mute() {
+    let mut muted = false;
+    crate::config::update(|config| {
+        config.sounds.muted = !config.sounds.muted;
+        muted = config.sounds.muted;
+    });
+    info!("Alert sounds: {}", if muted { "muted" } else { "on" });
+}
+
+/// WAV files: relative paths are relative to the program folder
+fn sound_file(sound: &str) -> PathBuf {
+    let path = PathBuf::from(sound);
+    if path.is_absolute() {
+        return path;
+    }
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(&path)))
+        .unwrap_or(path)
+}
+
+/// Plays a system sound alias or WAV file asynchronously ("none"/empty = silent)
+fn play(sound: &str) {
+    let sound = sound.trim();
+    if sound.is_empty() || sound.eq_ignore_ascii_case("none") || is_muted() || crate::snooze::is_snoozed() {
+        return;
+    }
+
+    let (name, flags) = if sound.to_lowercase().ends_with(".wav") {
+        (sound_file(sound).to_string_lossy().to_string(), SND_FILENAME)
+    } else {
+        (sound.to_string(), SND_ALIAS)
+    };
+
+    // NODEFAULT: a missing file/alias stays silent instead of the default beep
+    let wide: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+    let played = unsafe { PlaySoundW(PCWSTR(wide.as_ptr()), None, flags | SND_ASYNC | SND_NODEFAULT) };
+    if !played.as_bool() {
+        warn!("Could not play alert sound {}", name);
+    }
+}
@@ -0,0 +1,156 @@
+//! Storage Manager
+//!
+//! Keeps the logs/ tree (event logs, screenshots, recordings of all users and sessions) below
+//! the configured size by pruning the oldest data, and warns when free disk space runs low.
+
+use crate::notification::Severity;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use tracing::{error, info, warn};
+use windows::core::PCWSTR;
+use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+/// Check interval
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+const MB: u64 = 1024 * 1024;
+
+/// Free disk space is below the configured threshold
+static DISK_LOW: AtomicBool = AtomicBool::new(false);
+
+/// Status flag: free disk space is low (screenshots/recordings are reduced)
+pub fn is_disk_low() -> bool {
+    DISK_LOW.load(Ordering::SeqCst)
+}
+
+/// Total size of a file or folder (recursive)
+fn size_of(path: &Path) -> u64 {
+    let meta = match fs::symlink_metadata(path) {
+        Ok(m) => m,
+        Err(_) => return 0,
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| size_of(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Free disk space for the drive of a path (bytes available to the user)
+fn free_disk_space(path: &Path) -> Option<u64> {
+    let wide: Vec<u16> = path
+        .to_string_lossy()
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut free: u64 = 0;
+    unsafe {
+        GetDiskFreeSpaceExW(PCWSTR(wide.as_ptr()), Some(&mut free), None, None).ok()?;
+    }
+    Some(free)
+}
+
+/// Entries of a folder (empty if it can't be read)
+fn children(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+        .unwrap_or_default()
+}
+
+/// Prunable entries of the whole logs/ tree: the contents of every session folder of every
+/// user, plus loose files and capture folders of older versions directly in logs/
+fn prunable_entries(root: &Path) -> Vec<PathBuf> {
+    let mut entries = Vec::new();
+    for path in children(root) {
+        let sessions: Vec<PathBuf> = children(&path).into_iter().filter(|p| crate::logger::is_session_dir(p)).collect();
+        if sessions.is_empty() {
+            entries.push(path);
+        } else {
+            sessions.iter().for_each(|session| entries.extend(children(session)));
+        }
+    }
+    entries
+}
+
+/// Deletes the oldest entries until the tree is below max_bytes
+/// Files in use (event logs and app.log of running instances) can't be deleted and are skipped
+fn prune(root: &Path, mut used: u64, max_bytes: u64) -> u64 {
+    let mut entries: Vec<(SystemTime, PathBuf, u64)> = prunable_entries(root)
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+            let size = size_of(&path);
+            (modified, path, size)
+        })
+        .collect();
+
+    // Oldest first, regardless of user and session
+    entries.sort_by_key(|e| e.0);
+
+    let current = crate::logger::get_log_dir();
+    for (_, path, size) in entries {
+        if used <= max_bytes {
+            break;
+        }
+        let result = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        match result {
+            Ok(()) => {
+                used = used.saturating_sub(size);
+                info!("Storage limit: deleted {}", path.display());
+                // Session folders of earlier logons go once they are empty
+                if let Some(session) = path.parent().filter(|p| *p != current && crate::logger::is_session_dir(p)) {
+                    let _ = fs::remove_dir(session);
+                }
+            }
+            Err(_) => continue,
+        }
+    }
+
+    used
+}
+
+/// Checks the size of the logs/ tree and free disk space once
+fn check() {
+    let storage = crate::config::get().storage;
+    let dir = crate::logger::get_logs_root();
+
+    let mut used = size_of(&dir);
+    if storage.max_size_mb > 0 && used > storage.max_size_mb * MB {
+        used = prune(&dir, used, storage.max_size_mb * MB);
+        if used > storage.max_size_mb * MB {
+            error!("Log folder still above limit: {} MB", used / MB);
+        }
+    }
+
+    let low = match free_disk_space(&dir) {
+        Some(free) if storage.min_free_disk_mb > 0 => free < storage.min_free_disk_mb * MB,
+        _ => false,
+    };
+    let was_low = DISK_LOW.swap(low, Ordering::SeqCst);
+
+    if low && !was_low {
+        warn!("Free disk space below {} MB", storage.min_free_disk_mb);
+        crate::alert_window::set_alert("LOW DISK SPACE", &dir.to_string_lossy(), Severity::Warning);
+        crate::sound::play_default(Severity::Warning);
+    } else if !low && was_low {
+        info!("Free disk space OK again");
+    }
+}
+
+/// Starts the storage monitor thread
+pub fn start_monitor() {
+    thread::spawn(|| {
+        while !crate::tray::should_exit() {
+            check();
+            thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
@@ -0,0 +1,196 @@
+//! Syslog Forwarding
+//!
+//! Sends events to a syslog server or SIEM collector as RFC 5424 messages over TLS (RFC 5425,
+//! octet-counted frames, port 6514). The message is the JSON of the event log. Same TLS options
+//! as the other network features (pinning, client certificate); plain TCP only with
+//! allow_insecure.
+
+use crate::config::SyslogConfig;
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::tls::{TlsStream, Transport};
+use chrono::SecondsFormat;
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender};
+use once_cell::sync::OnceCell;
+use std::io::Write;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Pending messages (further events are dropped while full, e.g. server offline)
+const QUEUE_SIZE: usize = 1024;
+
+/// Facility "log audit"
+const FACILITY: u8 = 13;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reconnect backoff
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(5);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(120);
+
+/// How often the worker checks for shutdown
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Queue of the sending thread (created by start())
+static SENDER: OnceCell<Sender<Vec<u8>>> = OnceCell::new();
+
+/// Starts the sending thread if enabled
+pub fn start() {
+    let config = crate::config::get().syslog;
+    if !config.enabled || config.server.is_empty() {
+        return;
+    }
+    if !config.tls && !config.allow_insecure {
+        error!("Syslog disabled: TLS is off (set allow_insecure for test setups)");
+        return;
+    }
+    SENDER.get_or_init(|| {
+        let (sender, receiver) = bounded::<Vec<u8>>(QUEUE_SIZE);
+        thread::spawn(move || worker(receiver));
+        sender
+    });
+}
+
+/// Queues an event for the syslog server (no-op if off or below min_severity)
+pub fn send(entry: &LogEntry) {
+    let Some(sender) = SENDER.get() else {
+        return;
+    };
+    if entry.severity < crate::config::get().syslog.min_severity {
+        return;
+    }
+    let host = hostname::get().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+    if sender.try_send(frame(&message(entry, &host))).is_err() {
+        warn!("Syslog queue full - {} event not sent", entry.event_type);
+    }
+}
+
+/// Header field: printable ASCII without spaces, "-" if empty (RFC 5424 NILVALUE)
+fn header_field(value: &str, max_len: usize) -> String {
+    let field: String = value
+        .chars()
+        .map(|c| if c.is_ascii_graphic() { c } else { '_' })
+        .take(max_len)
+        .collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
+
+/// RFC 5424 message: <PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID - BOM+JSON
+fn message(entry: &LogEntry, host: &str) -> String {
+    let severity = match entry.severity {
+        Severity::Info => 6,
+        Severity::Warning => 4,
+        Severity::Critical => 2,
+    };
+    format!(
+        "<{}>1 {} {} pc_watcher {} {} - \u{feff}{}",
+        FACILITY * 8 + severity,
+        entry.timestamp.to_rfc3339_opts(SecondsFormat::Millis, false),
+        header_field(host, 255),
+        std::process::id(),
+        header_field(&entry.event_type, 32),
+        entry.format_json()
+    )
+}
+
+/// Octet-counting frame (RFC 5425): "<length> <message>"
+fn frame(message: &str) -> Vec<u8> {
+    format!("{} {}", message.len(), message).into_bytes()
+}
+
+/// TCP (+ TLS) connection to the server
+fn connect(config: &SyslogConfig) -> Result<Box<dyn Transport>, String> {
+    let address = config
+        .server
+        .to_socket_addrs()
+        .map_err(|e| format!("{}: {}", config.server, e))?
+        .next()
+        .ok_or_else(|| format!("{}: no address", config.server))?;
+    let tcp = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).map_err(|e| e.to_string())?;
+    let _ = tcp.set_read_timeout(Some(CONNECT_TIMEOUT));
+    let _ = tcp.set_write_timeout(Some(WRITE_TIMEOUT));
+
+    if config.tls {
+        let host = config.server.rsplit_once(':').map(|(host, _)| host).unwrap_or(&config.server);
+        Ok(Box::new(TlsStream::connect(tcp, host, &config.client_certificate, &config.pinned_sha256)?))
+    } else {
+        Ok(Box::new(tcp))
+    }
+}
+
+/// Sending thread: keeps the connection, reconnects with backoff
+fn worker(receiver: Receiver<Vec<u8>>) {
+    info!("Syslog forwarding to {}", crate::config::get().syslog.server);
+    let mut connection: Option<Box<dyn Transport>> = None;
+    let mut pending: Option<Vec<u8>> = None;
+    let mut retry_delay = MIN_RETRY_DELAY;
+
+    while !crate::event_hook::is_shutting_down() {
+        let Some(stream) = connection.as_mut() else {
+            match connect(&crate::config::get().syslog) {
+                Ok(stream) => {
+                    connection = Some(stream);
+                    retry_delay = MIN_RETRY_DELAY;
+                }
+                Err(e) => {
+                    error!("Syslog connection failed: {} (retry in {}s)", e, retry_delay.as_secs());
+                    thread::sleep(retry_delay);
+                    retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+                }
+            }
+            continue;
+        };
+
+        let frame = match pending.take() {
+            Some(frame) => frame,
+            None => match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(frame) => frame,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            },
+        };
+        if let Err(e) = stream.write_all(&frame).and_then(|_| stream.flush()) {
+            warn!("Syslog connection lost: {}", e);
+            connection = None;
+            // Sent again after reconnecting
+            pending = Some(frame);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    #[test]
+    fn formats_rfc5424_messages_in_octet_counted_frames() {
+        let entry = LogEntry {
+            timestamp: Local.with_ymd_and_hms(2026, 10, 15, 8, 30, 0).unwrap(),
+            event_type: "DETECTION".to_string(),
+            severity: Severity::Critical,
+            process_name: "cmd".to_string(),
+            ..Default::default()
+        };
+        let message = message(&entry, "OFFICE PC");
+        let header = format!(
+            "<106>1 {} OFFICE_PC pc_watcher {} DETECTION - \u{feff}{{",
+            entry.timestamp.to_rfc3339_opts(SecondsFormat::Millis, false),
+            std::process::id()
+        );
+        assert!(message.starts_with(&header), "{}", message);
+        assert!(message.contains("\"process_name\":\"cmd\""));
+
+        let frame = String::from_utf8(frame(&message)).unwrap();
+        assert_eq!(frame, format!("{} {}", message.len(), message));
+        assert_eq!(header_field("", 32), "-");
+        assert_eq!(header_field("ABCDEFGH", 4), "ABCD");
+    }
+}
@@ -0,0 +1,77 @@
+//! Event Tags
+//!
+//! Labels like "lolbin", "night-activity" or "usb" attached to events: by the tag rules of the
+//! config (event type, executable, time of day, severity) and by detections (their `tags`).
+//! Tags are stored with the event, shown as colored chips in the log list, can be filtered
+//! (log list, `pc_watcher events --tag`) and are counted in digests.
+
+use crate::config::TagRule;
+use crate::logger::LogEntry;
+use chrono::Timelike;
+
+/// Chip colors for tags without a configured color (COLORREF, 0x00BBGGRR)
+const PALETTE: &[u32] = &[
+    0x00C08040, 0x004080C0, 0x0040A040, 0x008040C0, 0x00A0A040, 0x004040C0, 0x00C04080, 0x00408080,
+];
+
+/// Checks if the hour is within the range (may wrap around midnight)
+fn in_hours(hour: u32, start: u32, end: u32) -> bool {
+    match start.cmp(&end) {
+        std::cmp::Ordering::Less => hour >= start && hour < end,
+        std::cmp::Ordering::Greater => hour >= start || hour < end,
+        std::cmp::Ordering::Equal => false,
+    }
+}
+
+/// Checks if an event fulfills all conditions of a tag rule
+fn matches(rule: &TagRule, entry: &LogEntry) -> bool {
+    let any = |patterns: &[String], text: &str| {
+        patterns.is_empty() || patterns.iter().any(|p| crate::detection::wildcard_match(p, text))
+    };
+    let hours = match (rule.start_hour, rule.end_hour) {
+        (Some(start), Some(end)) => in_hours(entry.timestamp.hour(), start, end),
+        _ => true,
+    };
+    !rule.tag.is_empty()
+        && any(&rule.event_types, &entry.event_type)
+        && any(&rule.image, &entry.process_path)
+        && hours
+        && rule.min_severity.is_none_or(|min| entry.severity >= min)
+}
+
+/// Adds a tag once (tags are compared case-insensitively)
+pub fn add(tags: &mut Vec<String>, tag: &str) {
+    let tag = tag.trim();
+    if !tag.is_empty() && !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+        tags.push(tag.to_string());
+    }
+}
+
+/// Attaches the tags of all matching tag rules (called by the log worker)
+pub fn apply(entry: &mut LogEntry) {
+    for rule in &crate::config::read().tag_rules {
+        if matches(rule, entry) {
+            add(&mut entry.tags, &rule.tag);
+        }
+    }
+}
+
+/// Checks if an event has a tag
+pub fn has_tag(tags: &[String], tag: &str) -> bool {
+    tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+}
+
+/// Chip color of a tag: configured "#RRGGBB" or a palette color derived from the name
+pub fn color(tag: &str) -> u32 {
+    let configured = crate::config::read()
+        .tag_rules
+        .iter()
+        .filter(|rule| rule.tag.eq_ignore_ascii_case(tag))
+        .find_map(|rule| rule.color.clone());
+    if let Some(rgb) = configured.and_then(|c| u32::from_str_radix(c.trim_start_matches('#'), 16).ok()) {
+        // RGB -> COLORREF (BGR)
+        return ((rgb & 0xFF) << 16) | (rgb & 0xFF00) | ((rgb >> 16) & 0xFF);
+    }
+    let hash = tag.to_lowercase().bytes().fold(0u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32));
+    PALETTE[hash as usize % PALETTE.len()]
+}
@@ -0,0 +1,155 @@
+//! Scheduled Task Monitoring
+//!
+//! Every registered task is an XML file under %WINDIR%\System32\Tasks. The folder tree is
+//! watched with ReadDirectoryChangesW; a new task file raises a Warning with the command
+//! line of its actions. PC Watcher's own autostart task (install) is not reported.
+
+use crate::error::Error;
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use crate::rules::Rule;
+use chrono::Local;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+use windows::Win32::Storage::FileSystem::{FILE_ACTION_ADDED, FILE_ACTION_RENAMED_NEW_NAME, FILE_NOTIFY_CHANGE_FILE_NAME};
+
+/// Event type in the log
+const EVENT_TYPE: &str = "TASK_CREATED";
+
+/// Name of the autostart task created by `pc_watcher install`
+const OWN_TASK: &str = "\\PCWatcher";
+
+/// The Task Scheduler writes the XML right after creating the file
+const WRITE_DELAY: Duration = Duration::from_millis(500);
+
+/// Folder of the task definitions
+fn tasks_folder() -> PathBuf {
+    let windir = std::env::var("WINDIR").unwrap_or_else(|_| "C:\\Windows".to_string());
+    PathBuf::from(windir).join("System32").join("Tasks")
+}
+
+/// Text of a task file (UTF-16 with BOM as written by the Task Scheduler, or UTF-8)
+fn read_task_file(path: &Path) -> Option<String> {
+    let bytes = std::fs::read(path).ok()?;
+    if bytes.starts_with(&[0xFF, 0xFE]) {
+        let wide: Vec<u16> = bytes[2..].chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+        Some(String::from_utf16_lossy(&wide))
+    } else {
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// Text between <tag> and </tag> (XML entities decoded)
+fn xml_value(xml: &str, tag: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", tag))?;
+    Some(
+        xml[start..end]
+            .trim()
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&"),
+    )
+}
+
+/// Command lines of all Exec actions of a task
+fn task_commands(xml: &str) -> Vec<String> {
+    xml.split("<Exec>")
+        .skip(1)
+        .filter_map(|action| {
+            let command = xml_value(action, "Command")?;
+            Some(match xml_value(action, "Arguments") {
+                Some(arguments) if !arguments.is_empty() => format!("{} {}", command, arguments),
+                _ => command,
+            })
+        })
+        .collect()
+}
+
+/// Own autostart task (same name and pointing to this executable)
+fn is_own_task(task_name: &str, commands: &[String]) -> bool {
+    let Ok(exe) = std::env::current_exe() else {
+        return false;
+    };
+    let exe = exe.to_string_lossy().to_lowercase();
+    task_name.eq_ignore_ascii_case(OWN_TASK)
+        && !commands.is_empty()
+        && commands.iter().all(|c| c.trim_matches('"').to_lowercase() == exe)
+}
+
+/// Checks if a task is expected (ignored list of the config, substring of the task path)
+fn is_ignored(task_name: &str) -> bool {
+    let name = task_name.to_lowercase();
+    crate::config::get().tasks.ignored.iter().any(|i| name.contains(&i.to_lowercase()))
+}
+
+/// Logs a new task and alerts, unless the rule is disabled
+fn report_task(path: &Path, task_name: &str) {
+    thread::sleep(WRITE_DELAY);
+    let xml = read_task_file(path).unwrap_or_default();
+    let commands = task_commands(&xml);
+    if is_own_task(task_name, &commands) || is_ignored(task_name) {
+        return;
+    }
+
+    let command_line = if commands.is_empty() { "(no program action)".to_string() } else { commands.join(" & ") };
+    let author = xml_value(&xml, "Author").unwrap_or_default();
+
+    let severity = Severity::Warning;
+    warn!("!!! SCHEDULED TASK CREATED: {} - {} !!!", task_name, command_line);
+    if crate::rules::is_enabled(Rule::ScheduledTask) {
+        crate::alert_window::set_rule_alert(
+            Rule::ScheduledTask,
+            &format!("New scheduled task: {}", task_name),
+            &command_line,
+            severity,
+        );
+        crate::sound::play_alert(Rule::ScheduledTask, severity);
+        let capture = crate::rules::capture_mode(Rule::ScheduledTask, severity);
+        crate::screenshot::capture_alert_screenshots(task_name.trim_start_matches('\\').to_string(), EVENT_TYPE, capture, severity);
+    }
+
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: Local::now(),
+        event_type: EVENT_TYPE.to_string(),
+        severity,
+        process_name: task_name.to_string(),
+        process_path: path.to_string_lossy().to_string(),
+        window_title: if author.is_empty() { "Scheduled task".to_string() } else { format!("Scheduled task by {}", author) },
+        command_line: Some(command_line),
+        ..Default::default()
+    });
+}
+
+/// Starts the watcher thread (only if enabled in the config)
+pub fn start_monitor() {
+    if !crate::config::get().tasks.enabled {
+        return;
+    }
+    thread::spawn(|| {
+        let folder = tasks_folder();
+        if let Err(e) = watch_tasks(&folder) {
+            crate::error::report("Scheduled task watcher (run as administrator)", e);
+        }
+    });
+}
+
+/// Watches the task folder tree for new files (blocking)
+fn watch_tasks(folder: &Path) -> Result<(), Error> {
+    info!("Watching scheduled tasks in: {}", folder.display());
+    crate::file_watch::watch_directory(folder, true, FILE_NOTIFY_CHANGE_FILE_NAME, |name, action| {
+        if action != FILE_ACTION_ADDED && action != FILE_ACTION_RENAMED_NEW_NAME {
+            return;
+        }
+        let path = folder.join(&name);
+        // New task folders are not tasks
+        if !path.is_dir() {
+            let task_name = format!("\\{}", name);
+            thread::spawn(move || report_task(&path, &task_name));
+        }
+    })
+}
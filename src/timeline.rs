@@ -0,0 +1,338 @@
+//! Timeline Window
+//!
+//! Tray action "Timeline": one horizontal 24-hour strip per day with colored segments for
+//! active, idle, locked and asleep, derived from the events in the database (window
+//! activity, input telemetry, LockApp focus, SLEEP/RESUME, monitoring gaps). Time without
+//! monitoring stays dark. F5 reloads. The window lives on the tray thread.
+//! Also served as JSON to the web dashboard (web_ui.rs).
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, TimeZone};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicIsize, Ordering};
+use tracing::error;
+use windows::core::w;
+use windows::Win32::Foundation::{COLORREF, HWND, LPARAM, LRESULT, RECT, WPARAM};
+use windows::Win32::Graphics::Gdi::{
+    BeginPaint, CreateSolidBrush, DeleteObject, EndPaint, FillRect, GetStockObject, InvalidateRect, SelectObject,
+    SetBkMode, SetTextColor, TextOutW, DEFAULT_GUI_FONT, HBRUSH, HDC, HGDIOBJ, PAINTSTRUCT, TRANSPARENT,
+};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Input::KeyboardAndMouse::VK_F5;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+/// Days shown (today at the bottom)
+const DAYS: usize = 7;
+const MINUTES_PER_DAY: usize = 24 * 60;
+
+// Layout
+const LABEL_WIDTH: i32 = 80;
+const HOUR_WIDTH: i32 = 30;
+const ROW_HEIGHT: i32 = 22;
+const ROW_GAP: i32 = 8;
+const MARGIN: i32 = 12;
+const HEADER_HEIGHT: i32 = 20;
+const LEGEND_HEIGHT: i32 = 30;
+
+// Colors (0x00BBGGRR)
+const COLOR_BACKGROUND: u32 = 0x00202020;
+const COLOR_TEXT: u32 = 0x00E0E0E0;
+const COLOR_UNKNOWN: u32 = 0x00383838;
+const COLOR_IDLE: u32 = 0x0000A5E6;
+const COLOR_ACTIVE: u32 = 0x0050AF4C;
+const COLOR_LOCKED: u32 = 0x00F48542;
+const COLOR_ASLEEP: u32 = 0x00C2577E;
+
+/// Time between two events above which the watcher cannot have been running unnoticed
+const MAX_QUIET_HOURS: i64 = 12;
+
+/// Window events that show the user was doing something
+const ACTIVITY_EVENTS: [&str; 6] = ["FOCUS", "CREATED", "SHOWN", "MINIMIZED", "RESTORED", "Z-ORDER"];
+
+/// State of one minute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Not monitored (off or watcher not running)
+    Unknown,
+    /// Logged in, no input
+    Idle,
+    Active,
+    Locked,
+    Asleep,
+}
+
+impl State {
+    fn color(self) -> u32 {
+        match self {
+            State::Unknown => COLOR_UNKNOWN,
+            State::Idle => COLOR_IDLE,
+            State::Active => COLOR_ACTIVE,
+            State::Locked => COLOR_LOCKED,
+            State::Asleep => COLOR_ASLEEP,
+        }
+    }
+
+    /// Name in the JSON of the web dashboard
+    fn name(self) -> &'static str {
+        match self {
+            State::Unknown => "unknown",
+            State::Idle => "idle",
+            State::Active => "active",
+            State::Locked => "locked",
+            State::Asleep => "asleep",
+        }
+    }
+}
+
+/// One strip
+struct Day {
+    date: NaiveDate,
+    minutes: Vec<State>,
+}
+
+thread_local! {
+    static DAYS_SHOWN: RefCell<Vec<Day>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Open timeline window (one at a time)
+static TIMELINE_HWND: AtomicIsize = AtomicIsize::new(0);
+
+/// Derives the minute states of the last days from the database
+fn build_days() -> Vec<Day> {
+    let today = Local::now().date_naive();
+    let first = today - ChronoDuration::days(DAYS as i64 - 1);
+    let Some(since) = first.and_hms_opt(0, 0, 0).and_then(|t| Local.from_local_datetime(&t).earliest()) else {
+        return Vec::new();
+    };
+    let now = Local::now();
+
+    let (events, input_minutes) = match crate::database::open() {
+        Ok(conn) => (
+            crate::database::read_timeline_events(&conn, &since).unwrap_or_default(),
+            crate::database::read_input_minutes(&conn, &since).unwrap_or_default(),
+        ),
+        Err(e) => {
+            error!("Timeline: cannot open database: {}", e);
+            (Vec::new(), Vec::new())
+        }
+    };
+
+    let total = DAYS * MINUTES_PER_DAY;
+    let index = |t: &DateTime<Local>| ((*t - since).num_minutes().max(0) as usize).min(total);
+    let mut states = vec![State::Unknown; total];
+
+    // Fill the time between two events with the state after the first one
+    let (mut asleep, mut locked) = (false, false);
+    for (i, (timestamp, event_type, process_name)) in events.iter().enumerate() {
+        match event_type.as_str() {
+            "SLEEP" => asleep = true,
+            "RESUME" => asleep = false,
+            "FOCUS" => locked = process_name.to_lowercase().starts_with("lockapp"),
+            _ => {}
+        }
+        let (next, next_type) = match events.get(i + 1) {
+            Some((t, event_type, _)) => (*t, event_type.as_str()),
+            None => (now, ""),
+        };
+        // A gap entry is written at startup: the time before it was not monitored
+        if next_type == "MONITORING_GAP" || (!asleep && next - *timestamp > ChronoDuration::hours(MAX_QUIET_HOURS)) {
+            continue;
+        }
+        let state = if asleep {
+            State::Asleep
+        } else if locked {
+            State::Locked
+        } else {
+            State::Idle
+        };
+        states[index(timestamp)..index(&next)].fill(state);
+    }
+
+    // Minutes with input or window activity
+    let activity = events
+        .iter()
+        .filter(|(_, event_type, _)| ACTIVITY_EVENTS.contains(&event_type.as_str()))
+        .map(|(t, _, _)| t)
+        .chain(input_minutes.iter());
+    for t in activity {
+        let i = index(t);
+        if i < total && states[i] == State::Idle {
+            states[i] = State::Active;
+        }
+    }
+
+    states
+        .chunks(MINUTES_PER_DAY)
+        .enumerate()
+        .map(|(day, minutes)| Day { date: first + ChronoDuration::days(day as i64), minutes: minutes.to_vec() })
+        .collect()
+}
+
+/// Runs of equal states: (first minute, end minute, state)
+fn runs(minutes: &[State]) -> Vec<(usize, usize, State)> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    while start < minutes.len() {
+        let state = minutes[start];
+        let end = minutes[start..].iter().position(|s| *s != state).map_or(minutes.len(), |n| start + n);
+        runs.push((start, end, state));
+        start = end;
+    }
+    runs
+}
+
+/// Timeline of the last days as JSON (web dashboard): [{date, runs: [{start, end, state}]}]
+pub fn json() -> serde_json::Value {
+    let days: Vec<serde_json::Value> = build_days()
+        .iter()
+        .map(|day| {
+            let runs: Vec<serde_json::Value> = runs(&day.minutes)
+                .into_iter()
+                .map(|(start, end, state)| serde_json::json!({ "start": start, "end": end, "state": state.name() }))
+                .collect();
+            serde_json::json!({ "date": day.date.to_string(), "runs": runs })
+        })
+        .collect();
+    serde_json::Value::Array(days)
+}
+
+unsafe fn draw_text(hdc: HDC, x: i32, y: i32, text: &str) {
+    let wide: Vec<u16> = text.encode_utf16().collect();
+    let _ = TextOutW(hdc, x, y, &wide);
+}
+
+unsafe fn fill(hdc: HDC, rect: &RECT, color: u32) {
+    let brush = CreateSolidBrush(COLORREF(color));
+    let _ = FillRect(hdc, rect, brush);
+    let _ = DeleteObject(HGDIOBJ(brush.0));
+}
+
+/// Draws the hour scale, one strip per day and the legend
+unsafe fn paint(hdc: HDC, client: &RECT) {
+    fill(hdc, client, COLOR_BACKGROUND);
+    let old_font = SelectObject(hdc, GetStockObject(DEFAULT_GUI_FONT));
+    SetBkMode(hdc, TRANSPARENT);
+    let _ = SetTextColor(hdc, COLORREF(COLOR_TEXT));
+
+    let strip_x = MARGIN + LABEL_WIDTH;
+    for hour in (0..=24).step_by(3) {
+        draw_text(hdc, strip_x + hour * HOUR_WIDTH - 4, MARGIN, &hour.to_string());
+    }
+
+    DAYS_SHOWN.with(|days| {
+        for (row, day) in days.borrow().iter().enumerate() {
+            let y = MARGIN + HEADER_HEIGHT + row as i32 * (ROW_HEIGHT + ROW_GAP);
+            let label = format!("{} {:02}.{:02}.", day.date.weekday(), day.date.day(), day.date.month());
+            draw_text(hdc, MARGIN, y + 4, &label);
+
+            // One rectangle per run of equal states
+            for (start, end, state) in runs(&day.minutes) {
+                let rect = RECT {
+                    left: strip_x + start as i32 * HOUR_WIDTH / 60,
+                    top: y,
+                    right: strip_x + end as i32 * HOUR_WIDTH / 60,
+                    bottom: y + ROW_HEIGHT,
+                };
+                fill(hdc, &rect, state.color());
+            }
+        }
+    });
+
+    let legend_y = MARGIN + HEADER_HEIGHT + DAYS as i32 * (ROW_HEIGHT + ROW_GAP) + 4;
+    let items = [
+        (COLOR_ACTIVE, "Active"),
+        (COLOR_IDLE, "Idle"),
+        (COLOR_LOCKED, "Locked"),
+        (COLOR_ASLEEP, "Asleep"),
+        (COLOR_UNKNOWN, "Not monitored"),
+    ];
+    let mut x = strip_x;
+    for (color, label) in items {
+        fill(hdc, &RECT { left: x, top: legend_y + 3, right: x + 10, bottom: legend_y + 13 }, color);
+        draw_text(hdc, x + 14, legend_y, label);
+        x += 14 + label.len() as i32 * 7 + 16;
+    }
+    draw_text(hdc, client.right - MARGIN - 90, legend_y, "F5 = refresh");
+
+    SelectObject(hdc, old_font);
+}
+
+unsafe extern "system" fn timeline_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    match msg {
+        WM_PAINT => {
+            let mut ps = PAINTSTRUCT::default();
+            let hdc = BeginPaint(hwnd, &mut ps);
+            let mut client = RECT::default();
+            let _ = GetClientRect(hwnd, &mut client);
+            paint(hdc, &client);
+            let _ = EndPaint(hwnd, &ps);
+            LRESULT(0)
+        }
+        WM_ERASEBKGND => LRESULT(1),
+        WM_KEYDOWN if wparam.0 == VK_F5.0 as usize => {
+            DAYS_SHOWN.with(|d| *d.borrow_mut() = build_days());
+            let _ = InvalidateRect(hwnd, None, false);
+            LRESULT(0)
+        }
+        WM_DESTROY => {
+            DAYS_SHOWN.with(|d| d.borrow_mut().clear());
+            TIMELINE_HWND.store(0, Ordering::SeqCst);
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+/// Opens the timeline window (or brings the open one to the front)
+pub unsafe fn show_window() {
+    let open = TIMELINE_HWND.load(Ordering::SeqCst);
+    if open != 0 {
+        let _ = SetForegroundWindow(HWND(open as *mut _));
+        return;
+    }
+
+    DAYS_SHOWN.with(|d| *d.borrow_mut() = build_days());
+
+    let instance = GetModuleHandleW(None).unwrap_or_default();
+    let class_name = w!("PCWatcherTimeline");
+    let wc = WNDCLASSW {
+        lpfnWndProc: Some(timeline_proc),
+        hInstance: instance.into(),
+        lpszClassName: class_name,
+        hCursor: LoadCursorW(None, IDC_ARROW).unwrap_or_default(),
+        hbrBackground: HBRUSH::default(),
+        ..Default::default()
+    };
+    RegisterClassW(&wc);
+
+    // Window size for the client area
+    let style = WS_OVERLAPPED | WS_CAPTION | WS_SYSMENU | WS_MINIMIZEBOX;
+    let mut rect = RECT {
+        left: 0,
+        top: 0,
+        right: 2 * MARGIN + LABEL_WIDTH + 24 * HOUR_WIDTH + 10,
+        bottom: 2 * MARGIN + HEADER_HEIGHT + DAYS as i32 * (ROW_HEIGHT + ROW_GAP) + LEGEND_HEIGHT,
+    };
+    let _ = AdjustWindowRectEx(&mut rect, style, false, WINDOW_EX_STYLE(0));
+
+    let hwnd = CreateWindowExW(
+        WINDOW_EX_STYLE(0),
+        class_name,
+        w!("PC Watcher - Timeline"),
+        style | WS_VISIBLE,
+        CW_USEDEFAULT,
+        CW_USEDEFAULT,
+        rect.right - rect.left,
+        rect.bottom - rect.top,
+        None,
+        None,
+        instance,
+        None,
+    );
+    match hwnd {
+        Ok(hwnd) => {
+            TIMELINE_HWND.store(hwnd.0 as isize, Ordering::SeqCst);
+            let _ = SetForegroundWindow(hwnd);
+        }
+        Err(e) => error!("Could not create timeline window: {}", e),
+    }
+}
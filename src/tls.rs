@@ -0,0 +1,427 @@
+//! TLS Streams and Authentication
+//!
+//! TLS over a TcpStream with rustls (TLS 1.2/1.3, ring crypto). Client side for network
+//! integrations that do not speak HTTP (MQTT, central server link, syslog), server side for
+//! the central server and the dashboards. Certificates with their private key are PEM files,
+//! servers without pins are validated against the Windows root store. Blocking, one stream
+//! per connection.
+//!
+//! Shared by all network features: certificate pinning (SHA-256 fingerprints of the peer
+//! certificate), client certificates (mTLS) and the token check.
+
+use once_cell::sync::Lazy;
+use ring::digest::{digest, SHA256};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::{
+    ClientConfig, ClientConnection, Connection, DigitallySignedStruct, DistinguishedName, RootCertStore,
+    ServerConfig, ServerConnection, SignatureScheme,
+};
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+static PROVIDER: Lazy<Arc<CryptoProvider>> = Lazy::new(|| Arc::new(rustls::crypto::ring::default_provider()));
+
+/// Trusted root certificates of Windows (loaded once, for clients without pins)
+static SYSTEM_ROOTS: Lazy<Arc<RootCertStore>> = Lazy::new(|| {
+    let mut roots = RootCertStore::empty();
+    roots.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+    Arc::new(roots)
+});
+
+/// Certificate chain with private key (one PEM file: certificates first, then the key)
+pub struct Certificate {
+    chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+}
+
+impl Certificate {
+    /// Loads a PEM file (relative paths are relative to the EXE folder)
+    pub fn load(file: &str) -> Result<Self, String> {
+        let path = PathBuf::from(file);
+        let path = if path.is_absolute() {
+            path
+        } else {
+            std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(|dir| dir.join(&path)))
+                .unwrap_or(path)
+        };
+        let pem = std::fs::read(&path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        Self::from_pem(&pem).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn from_pem(pem: &[u8]) -> Result<Self, String> {
+        let chain = CertificateDer::pem_slice_iter(pem)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("invalid certificate: {}", e))?;
+        if chain.is_empty() {
+            return Err("no certificate".to_string());
+        }
+        let key = PrivateKeyDer::from_pem_slice(pem).map_err(|e| format!("no private key: {}", e))?;
+        Ok(Certificate { chain, key })
+    }
+
+    /// SHA-256 fingerprint of the certificate (for the pin lists of the other side)
+    pub fn fingerprint(&self) -> String {
+        fingerprint(&self.chain[0])
+    }
+}
+
+/// SHA-256 fingerprint of a certificate (lowercase hex, as used for pinning)
+fn fingerprint(certificate: &CertificateDer) -> String {
+    digest(&SHA256, certificate).as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Pin from the config in the fingerprint format (colons/spaces and case ignored)
+fn normalize_pin(pin: &str) -> String {
+    pin.chars().filter(|c| c.is_ascii_hexdigit()).collect::<String>().to_lowercase()
+}
+
+/// Error unless the certificate is one of the pinned ones
+fn check_pin(certificate: &CertificateDer, pins: &[String], side: &str) -> Result<(), rustls::Error> {
+    let fingerprint = fingerprint(certificate);
+    if pins.iter().any(|pin| normalize_pin(pin) == fingerprint) {
+        Ok(())
+    } else {
+        Err(rustls::Error::General(format!("{} certificate {} is not pinned", side, fingerprint)))
+    }
+}
+
+/// Compares tokens in constant time (no timing hints about the correct prefix)
+pub fn token_matches(expected: &str, token: &str) -> bool {
+    !expected.is_empty()
+        && expected.len() == token.len()
+        && expected.bytes().zip(token.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Accepts pinned server certificates instead of validating them (self-signed certificates work)
+#[derive(Debug)]
+struct PinnedServer {
+    pins: Vec<String>,
+}
+
+impl ServerCertVerifier for PinnedServer {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer,
+        _intermediates: &[CertificateDer],
+        _server_name: &ServerName,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        check_pin(end_entity, &self.pins, "server").map(|_| ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &PROVIDER.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &PROVIDER.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        PROVIDER.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Requires a client certificate with one of the pinned fingerprints (mTLS)
+#[derive(Debug)]
+struct PinnedClients {
+    pins: Vec<String>,
+}
+
+impl ClientCertVerifier for PinnedClients {
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer,
+        _intermediates: &[CertificateDer],
+        _now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        check_pin(end_entity, &self.pins, "client").map(|_| ClientCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &PROVIDER.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &PROVIDER.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        PROVIDER.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Server side settings (also for the async gRPC listener)
+/// client_pins: accepted client certificates (empty = no client certificate required)
+pub fn server_config(certificate: &Certificate, client_pins: &[String]) -> Result<ServerConfig, String> {
+    let builder = ServerConfig::builder_with_provider(PROVIDER.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?;
+    let builder = match client_pins {
+        [] => builder.with_no_client_auth(),
+        pins => builder.with_client_cert_verifier(Arc::new(PinnedClients { pins: pins.to_vec() })),
+    };
+    builder
+        .with_single_cert(certificate.chain.clone(), certificate.key.clone_key())
+        .map_err(|e| format!("server certificate: {}", e))
+}
+
+/// Socket or TLS stream
+pub trait Transport: Read + Write + Send {}
+impl<T: Read + Write + Send> Transport for T {}
+
+/// TLS connection (client or server side)
+pub struct TlsStream {
+    tcp: TcpStream,
+    connection: Connection,
+}
+
+impl TlsStream {
+    /// Client handshake on a connected socket
+    /// client_certificate: PEM file of the own certificate for mTLS (empty = none)
+    /// pins: accepted server fingerprints (empty = host is validated against the system store)
+    pub fn connect(tcp: TcpStream, host: &str, client_certificate: &str, pins: &[String]) -> Result<Self, String> {
+        let certificate = match client_certificate {
+            "" => None,
+            file => Some(Certificate::load(file)?),
+        };
+        Self::client(tcp, host, certificate.as_ref(), pins)
+    }
+
+    fn client(tcp: TcpStream, host: &str, certificate: Option<&Certificate>, pins: &[String]) -> Result<Self, String> {
+        let builder = ClientConfig::builder_with_provider(PROVIDER.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| e.to_string())?;
+        let builder = match pins {
+            [] => builder.with_root_certificates(SYSTEM_ROOTS.clone()),
+            pins => builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(PinnedServer { pins: pins.to_vec() })),
+        };
+        let config = match certificate {
+            Some(certificate) => builder
+                .with_client_auth_cert(certificate.chain.clone(), certificate.key.clone_key())
+                .map_err(|e| format!("client certificate: {}", e))?,
+            None => builder.with_no_client_auth(),
+        };
+        let name = ServerName::try_from(host.to_string()).map_err(|e| format!("{}: {}", host, e))?;
+        let connection = ClientConnection::new(Arc::new(config), name).map_err(|e| e.to_string())?;
+        Self::establish(tcp, connection.into())
+    }
+
+    /// Server handshake on an accepted socket
+    /// client_pins: accepted client certificates (empty = no client certificate required)
+    pub fn accept(tcp: TcpStream, certificate: &Certificate, client_pins: &[String]) -> Result<Self, String> {
+        let config = server_config(certificate, client_pins)?;
+        let connection = ServerConnection::new(Arc::new(config)).map_err(|e| e.to_string())?;
+        Self::establish(tcp, connection.into())
+    }
+
+    /// Runs the handshake, so certificate and pin errors show up on connect
+    fn establish(tcp: TcpStream, connection: Connection) -> Result<Self, String> {
+        let mut stream = TlsStream { tcp, connection };
+        while stream.connection.is_handshaking() {
+            stream
+                .connection
+                .complete_io(&mut stream.tcp)
+                .map_err(|e| format!("TLS handshake failed: {}", e))?;
+        }
+        Ok(stream)
+    }
+
+    /// SHA-256 fingerprint of the certificate of the other side
+    pub fn peer_fingerprint(&self) -> Result<String, String> {
+        self.connection
+            .peer_certificates()
+            .and_then(|chain| chain.first())
+            .map(fingerprint)
+            .ok_or_else(|| "no peer certificate".to_string())
+    }
+
+    /// Sends all pending TLS records
+    fn send_pending(&mut self) -> io::Result<()> {
+        while self.connection.wants_write() {
+            self.connection.write_tls(&mut self.tcp)?;
+        }
+        Ok(())
+    }
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.connection.reader().read(buf) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                result => return result,
+            }
+            // No plaintext buffered: next records from the socket (0 = closed, reported by the reader)
+            self.connection.read_tls(&mut self.tcp)?;
+            if let Err(e) = self.connection.process_new_packets() {
+                // Alert for the peer
+                let _ = self.send_pending();
+                return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+            }
+            self.send_pending()?;
+        }
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.connection.writer().write(buf)?;
+        self.send_pending()?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send_pending()?;
+        self.tcp.flush()
+    }
+}
+
+impl Drop for TlsStream {
+    fn drop(&mut self) {
+        self.connection.send_close_notify();
+        let _ = self.send_pending();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Self-signed certificate for localhost
+    fn certificate() -> Certificate {
+        let generated = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let pem = format!("{}{}", generated.cert.pem(), generated.key_pair.serialize_pem());
+        Certificate::from_pem(pem.as_bytes()).unwrap()
+    }
+
+    /// Serves one connection: reads "ping", answers "pong"
+    fn serve(certificate: Certificate, client_pins: Vec<String>) -> (u16, thread::JoinHandle<Result<String, String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (tcp, _) = listener.accept().map_err(|e| e.to_string())?;
+            let mut stream = TlsStream::accept(tcp, &certificate, &client_pins)?;
+            let mut request = [0u8; 4];
+            stream.read_exact(&mut request).map_err(|e| e.to_string())?;
+            stream.write_all(b"pong").map_err(|e| e.to_string())?;
+            let client = stream.peer_fingerprint().unwrap_or_default();
+            Ok(format!("{} {}", String::from_utf8_lossy(&request), client))
+        });
+        (port, server)
+    }
+
+    fn exchange(stream: &mut TlsStream) -> io::Result<String> {
+        stream.write_all(b"ping")?;
+        let mut answer = [0u8; 4];
+        stream.read_exact(&mut answer)?;
+        Ok(String::from_utf8_lossy(&answer).to_string())
+    }
+
+    #[test]
+    fn loopback_handshake_with_pinned_server() {
+        let server_certificate = certificate();
+        let pin = server_certificate.fingerprint().to_uppercase();
+        let (port, server) = serve(server_certificate, Vec::new());
+
+        let tcp = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let mut stream = TlsStream::client(tcp, "localhost", None, std::slice::from_ref(&pin)).unwrap();
+        assert_eq!(stream.peer_fingerprint().unwrap(), pin.to_lowercase());
+        assert_eq!(exchange(&mut stream).unwrap(), "pong");
+        assert_eq!(server.join().unwrap().unwrap(), "ping ");
+    }
+
+    #[test]
+    fn rejects_unpinned_server() {
+        let (port, server) = serve(certificate(), Vec::new());
+
+        let tcp = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let pin = "00".repeat(32);
+        let error = TlsStream::client(tcp, "localhost", None, &[pin]).err().unwrap();
+        assert!(error.contains("is not pinned"), "{}", error);
+        assert!(server.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn unknown_self_signed_server_fails_system_validation() {
+        let (port, server) = serve(certificate(), Vec::new());
+
+        let tcp = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        assert!(TlsStream::client(tcp, "localhost", None, &[]).is_err());
+        assert!(server.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn mutual_tls_with_pinned_client() {
+        let server_certificate = certificate();
+        let server_pin = server_certificate.fingerprint();
+        let client_certificate = certificate();
+        let client_pin = client_certificate.fingerprint();
+        let (port, server) = serve(server_certificate, vec![client_pin.clone()]);
+
+        let tcp = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let mut stream = TlsStream::client(tcp, "localhost", Some(&client_certificate), &[server_pin]).unwrap();
+        assert_eq!(exchange(&mut stream).unwrap(), "pong");
+        assert_eq!(server.join().unwrap().unwrap(), format!("ping {}", client_pin));
+    }
+
+    #[test]
+    fn rejects_client_without_pinned_certificate() {
+        let server_certificate = certificate();
+        let server_pin = server_certificate.fingerprint();
+        let (port, server) = serve(server_certificate, vec!["00".repeat(32)]);
+
+        // TLS 1.3: the client finishes first and sees the rejection on the first read
+        let tcp = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        if let Ok(mut stream) = TlsStream::client(tcp, "localhost", Some(&certificate()), &[server_pin]) {
+            assert!(exchange(&mut stream).is_err());
+        }
+        let error = server.join().unwrap().err().unwrap();
+        assert!(error.contains("client certificate") && error.contains("is not pinned"), "{}", error);
+    }
+
+    #[test]
+    fn normalizes_pins() {
+        assert_eq!(normalize_pin("AB:cd 0F"), "abcd0f");
+        assert!(token_matches("secret", "secret"));
+        assert!(!token_matches("secret", "secreT"));
+        assert!(!token_matches("", ""));
+    }
+}
@@ -0,0 +1,62 @@
+//! Always-on-Top Detection
+//!
+//! Windows that make themselves TOPMOST stay above everything the user clicks - the basis of
+//! overlay and clickjacking tricks. The event worker checks the WS_EX_TOPMOST style on
+//! Z-order changes and reports the transition to topmost once per window; optionally the
+//! window is demoted to HWND_NOTOPMOST right away.
+
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use windows::Win32::Foundation::HWND;
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetWindowLongW, IsWindow, SetWindowPos, GWL_EXSTYLE, HWND_NOTOPMOST, SWP_ASYNCWINDOWPOS, SWP_NOACTIVATE,
+    SWP_NOMOVE, SWP_NOSIZE, WS_EX_TOPMOST,
+};
+
+/// Event type of the log entry for a demoted window
+pub const DEMOTED_EVENT_TYPE: &str = "TOPMOST_DEMOTED";
+
+/// Shell windows that are topmost by design
+const SHELL_CLASSES: [&str; 4] = ["Shell_TrayWnd", "Shell_SecondaryTrayWnd", "NotifyIconOverflowWindow", "TopLevelWindowForOverflowXamlIsland"];
+
+/// Known windows are pruned (closed windows removed) above this size
+const MAX_KNOWN: usize = 1000;
+
+lazy_static::lazy_static! {
+    // Windows already reported as topmost (reported again after they lost the style)
+    static ref KNOWN_TOPMOST: Mutex<HashSet<isize>> = Mutex::new(HashSet::new());
+}
+
+/// Checks if a window has the TOPMOST style
+pub fn is_topmost(hwnd: HWND) -> bool {
+    unsafe { GetWindowLongW(hwnd, GWL_EXSTYLE) as u32 & WS_EX_TOPMOST.0 != 0 }
+}
+
+/// True when the window became topmost since the last check (once per transition)
+pub fn became_topmost(hwnd: HWND, window_class: &str) -> bool {
+    if SHELL_CLASSES.contains(&window_class) {
+        return false;
+    }
+    let mut known = KNOWN_TOPMOST.lock();
+    if !is_topmost(hwnd) {
+        known.remove(&(hwnd.0 as isize));
+        return false;
+    }
+    if known.len() > MAX_KNOWN {
+        known.retain(|&h| unsafe { IsWindow(HWND(h as *mut _)).as_bool() });
+    }
+    known.insert(hwnd.0 as isize)
+}
+
+/// Takes the TOPMOST style away (asynchronous, a hung window does not block the worker)
+pub fn demote(hwnd: HWND) -> Result<(), String> {
+    unsafe {
+        SetWindowPos(
+            hwnd,
+            HWND_NOTOPMOST,
+            0, 0, 0, 0,
+            SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE | SWP_ASYNCWINDOWPOS,
+        )
+        .map_err(|e| e.to_string())
+    }
+}
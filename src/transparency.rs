@@ -0,0 +1,107 @@
+//! Alert Window Transparency
+//!
+//! Opacity of the layered alert window (tray submenu, saved in the config) and the
+//! click-through mode: with WS_EX_TRANSPARENT every click passes to the window below, so
+//! the alert window can stay over content without intercepting input. Click-through is
+//! toggled with a global hotkey (or the tray) and not saved - a window that cannot be
+//! clicked should not come back like that after a restart.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{info, warn};
+use windows::Win32::Foundation::{COLORREF, HWND};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    RegisterHotKey, HOT_KEY_MODIFIERS, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, MOD_WIN,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetWindowLongW, SetLayeredWindowAttributes, SetWindowLongW, GWL_EXSTYLE, LWA_ALPHA, WS_EX_TRANSPARENT,
+};
+
+/// ID of the click-through hotkey (WM_HOTKEY wparam)
+pub const HOTKEY_CLICK_THROUGH: i32 = 1;
+
+/// Opacity levels offered in the tray menu (percent)
+pub const OPACITY_LEVELS: [u8; 6] = [100, 90, 75, 60, 45, 30];
+
+/// Lowest opacity (a fully transparent window could not be found again)
+const MIN_OPACITY: u8 = 20;
+
+static CLICK_THROUGH: AtomicBool = AtomicBool::new(false);
+
+/// Layered window alpha of an opacity in percent
+fn alpha(percent: u8) -> u8 {
+    (percent.clamp(MIN_OPACITY, 100) as u32 * 255 / 100) as u8
+}
+
+/// Applies the configured opacity to the window (at startup)
+pub fn apply(hwnd: HWND) {
+    let percent = crate::config::get().alert_window.opacity;
+    unsafe {
+        let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha(percent), LWA_ALPHA);
+    }
+}
+
+/// Changes the opacity of the window and saves it
+pub fn set_opacity(hwnd: HWND, percent: u8) {
+    let percent = percent.clamp(MIN_OPACITY, 100);
+    crate::config::update(|config| config.alert_window.opacity = percent);
+    apply(hwnd);
+    info!("Alert window opacity: {}%", percent);
+}
+
+/// Is the window click-through right now?
+pub fn is_click_through() -> bool {
+    CLICK_THROUGH.load(Ordering::SeqCst)
+}
+
+/// Switches click-through on/off (hotkey, tray menu)
+pub fn toggle_click_through(hwnd: HWND) {
+    let enabled = !CLICK_THROUGH.fetch_xor(true, Ordering::SeqCst);
+    unsafe {
+        let style = GetWindowLongW(hwnd, GWL_EXSTYLE);
+        let style = if enabled { style | WS_EX_TRANSPARENT.0 as i32 } else { style & !(WS_EX_TRANSPARENT.0 as i32) };
+        SetWindowLongW(hwnd, GWL_EXSTYLE, style);
+    }
+    info!("Alert window click-through: {}", if enabled { "on" } else { "off" });
+}
+
+/// Parses a hotkey like "Ctrl+Shift+F12" into modifiers and virtual key (letters, digits, F1-F24)
+fn parse_hotkey(text: &str) -> Option<(HOT_KEY_MODIFIERS, u32)> {
+    let mut modifiers = MOD_NOREPEAT;
+    let mut key = None;
+    for part in text.split('+').map(|p| p.trim().to_uppercase()) {
+        match part.as_str() {
+            "CTRL" | "CONTROL" => modifiers |= MOD_CONTROL,
+            "ALT" => modifiers |= MOD_ALT,
+            "SHIFT" => modifiers |= MOD_SHIFT,
+            "WIN" => modifiers |= MOD_WIN,
+            _ if key.is_some() => return None,
+            name => {
+                key = match name.as_bytes() {
+                    [c] if c.is_ascii_alphanumeric() => Some(*c as u32),
+                    [b'F', digits @ ..] => {
+                        let number: u32 = std::str::from_utf8(digits).ok()?.parse().ok()?;
+                        (1..=24).contains(&number).then_some(0x6F + number)
+                    }
+                    _ => return None,
+                };
+            }
+        }
+    }
+    key.map(|key| (modifiers, key))
+}
+
+/// Registers the click-through hotkey for the window (WM_HOTKEY goes to its thread)
+pub fn register_hotkey(hwnd: HWND) {
+    let hotkey = crate::config::get().alert_window.click_through_hotkey;
+    if hotkey.trim().is_empty() {
+        return;
+    }
+    let Some((modifiers, key)) = parse_hotkey(&hotkey) else {
+        warn!("Invalid click-through hotkey \"{}\" (e.g. \"Ctrl+Shift+F12\")", hotkey);
+        return;
+    };
+    match unsafe { RegisterHotKey(hwnd, HOTKEY_CLICK_THROUGH, modifiers, key) } {
+        Ok(()) => info!("Click-through hotkey: {}", hotkey),
+        Err(e) => warn!("Click-through hotkey {} not registered (used by another program?): {}", hotkey, e),
+    }
+}
@@ -1,22 +1,36 @@
 //! System Tray Icon
 //!
-//! Shows a tray icon with context menu to exit the application.
+//! Shows a tray icon with context menu (profiles, do not disturb, docking, opacity, hidden windows, exit).
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
-use tracing::{info, error};
+use tracing::info;
 use windows::core::w;
 use windows::Win32::Foundation::{HWND, LPARAM, WPARAM, LRESULT, POINT};
 use windows::Win32::UI::WindowsAndMessaging::*;
 use windows::Win32::UI::Shell::{
-    Shell_NotifyIconW, NIF_ICON, NIF_MESSAGE, NIF_TIP, NIM_ADD, NIM_DELETE,
+    Shell_NotifyIconW, NIF_ICON, NIF_MESSAGE, NIF_TIP, NIM_ADD, NIM_DELETE, NIM_MODIFY,
     NOTIFYICONDATAW,
 };
 use windows::Win32::UI::WindowsAndMessaging::LoadImageW;
 use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use crate::config::Profile;
+use crate::error::Error;
 
 const WM_TRAYICON: u32 = WM_USER + 1;
 const ID_TRAY_EXIT: u32 = 1001;
+const ID_TRAY_DND: u32 = 1002;
+const ID_TRAY_MUTE_SOUNDS: u32 = 1003;
+const ID_TRAY_REVEAL: u32 = 1004;
+const ID_TRAY_TIMELINE: u32 = 1005;
+const ID_TRAY_INCIDENTS: u32 = 1006;
+const ID_TRAY_DOCK: u32 = 1007;
+const ID_TRAY_CLICK_THROUGH: u32 = 1008;
+const ID_TRAY_DIAGNOSTICS: u32 = 1009;
+// Profile menu entries: ID_TRAY_PROFILE + index in Profile::ALL
+const ID_TRAY_PROFILE: u32 = 1101;
+// Opacity menu entries: ID_TRAY_OPACITY + index in OPACITY_LEVELS
+const ID_TRAY_OPACITY: u32 = 1201;
 
 static TRAY_HWND: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
 static SHOULD_EXIT: AtomicBool = AtomicBool::new(false);
@@ -35,7 +49,7 @@ pub fn request_exit() {
 pub fn start_tray() {
     thread::spawn(|| {
         if let Err(e) = create_tray_window() {
-            error!("Tray window error: {}", e);
+            crate::error::report("Tray window", e);
         }
     });
 }
@@ -51,10 +65,10 @@ pub fn stop_tray() {
 }
 
 /// Creates the invisible window for tray messages
-fn create_tray_window() -> Result<(), String> {
+fn create_tray_window() -> Result<(), Error> {
     unsafe {
         let instance = GetModuleHandleW(None)
-            .map_err(|e| format!("GetModuleHandle: {}", e))?;
+            .map_err(|e| Error::win32("GetModuleHandle", &e))?;
 
         let class_name = w!("PCWatcherTray");
         let wc = WNDCLASSW {
@@ -81,16 +95,19 @@ fn create_tray_window() -> Result<(), String> {
             None,
         );
 
-        let hwnd = match hwnd {
-            Ok(h) => h,
-            Err(e) => return Err(format!("CreateWindowExW: {}", e)),
-        };
+        let hwnd = hwnd.map_err(|e| Error::win32("CreateWindowExW", &e))?;
 
         TRAY_HWND.store(hwnd.0 as usize, Ordering::SeqCst);
 
         // Add tray icon
         add_tray_icon(hwnd)?;
 
+        // Display and lid changes (WM_POWERBROADCAST)
+        crate::power::register(hwnd);
+
+        // Session lock/unlock for the input telemetry (WM_WTSSESSION_CHANGE)
+        crate::input_stats::register(hwnd);
+
         info!("Tray icon created");
 
         // Message Loop
@@ -108,13 +125,13 @@ fn create_tray_window() -> Result<(), String> {
 }
 
 /// Adds the tray icon
-unsafe fn add_tray_icon(hwnd: HWND) -> Result<(), String> {
+unsafe fn add_tray_icon(hwnd: HWND) -> Result<(), Error> {
     let instance = GetModuleHandleW(None).unwrap_or_default();
 
     // Load icon from EXE resources (ID 1 is the main icon)
     let icon = LoadImageW(
         instance,
-        windows::core::PCWSTR(1 as *const u16), // Resource ID 1
+        windows::core::PCWSTR(std::ptr::without_provenance(1)), // Resource ID 1
         IMAGE_ICON,
         32, 32,
         LR_DEFAULTCOLOR,
@@ -142,12 +159,33 @@ unsafe fn add_tray_icon(hwnd: HWND) -> Result<(), String> {
     }
 
     if !Shell_NotifyIconW(NIM_ADD, &nid).as_bool() {
-        return Err("Shell_NotifyIconW ADD failed".to_string());
+        return Err(Error::Win32 { call: "Shell_NotifyIconW", message: "NIM_ADD rejected".to_string() });
     }
 
     Ok(())
 }
 
+/// Changes the tooltip of the tray icon (e.g. count of open alerts)
+pub fn set_tooltip(text: &str) {
+    let hwnd = TRAY_HWND.load(Ordering::SeqCst);
+    if hwnd == 0 {
+        return;
+    }
+    let mut nid = NOTIFYICONDATAW {
+        cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+        hWnd: HWND(hwnd as *mut _),
+        uID: 1,
+        uFlags: NIF_TIP,
+        ..Default::default()
+    };
+    for (i, c) in text.encode_utf16().take(127).enumerate() {
+        nid.szTip[i] = c;
+    }
+    unsafe {
+        let _ = Shell_NotifyIconW(NIM_MODIFY, &nid);
+    }
+}
+
 /// Removes the tray icon
 unsafe fn remove_tray_icon(hwnd: HWND) {
     let nid = NOTIFYICONDATAW {
@@ -163,6 +201,56 @@ unsafe fn remove_tray_icon(hwnd: HWND) {
 unsafe fn show_context_menu(hwnd: HWND) {
     let menu = CreatePopupMenu().unwrap_or_default();
 
+    // Profile submenu (active profile checked)
+    let profile_menu = CreatePopupMenu().unwrap_or_default();
+    let active = crate::config::get().profile;
+    for (i, profile) in Profile::ALL.iter().enumerate() {
+        let mut flags = MF_STRING;
+        if active.as_deref() == Some(profile.name()) {
+            flags |= MF_CHECKED;
+        }
+        let text: Vec<u16> = profile.name().encode_utf16().chain(std::iter::once(0)).collect();
+        let _ = AppendMenuW(
+            profile_menu,
+            flags,
+            (ID_TRAY_PROFILE + i as u32) as usize,
+            windows::core::PCWSTR(text.as_ptr()),
+        );
+    }
+    let _ = AppendMenuW(menu, MF_POPUP, profile_menu.0 as usize, w!("Profile"));
+
+    // Do not disturb (alerts are queued until switched off)
+    let dnd_flags = if crate::snooze::is_do_not_disturb() { MF_STRING | MF_CHECKED } else { MF_STRING };
+    let _ = AppendMenuW(menu, dnd_flags, ID_TRAY_DND as usize, w!("Do not disturb"));
+    let mute_flags = if crate::sound::is_muted() { MF_STRING | MF_CHECKED } else { MF_STRING };
+    let _ = AppendMenuW(menu, mute_flags, ID_TRAY_MUTE_SOUNDS as usize, w!("Mute sounds"));
+    let dock_flags = if crate::config::get().alert_window.dock { MF_STRING | MF_CHECKED } else { MF_STRING };
+    let _ = AppendMenuW(menu, dock_flags, ID_TRAY_DOCK as usize, w!("Dock window at screen edges"));
+
+    // Opacity submenu (current level checked) and click-through
+    let opacity_menu = CreatePopupMenu().unwrap_or_default();
+    let opacity = crate::config::get().alert_window.opacity;
+    for (i, level) in crate::transparency::OPACITY_LEVELS.iter().enumerate() {
+        let flags = if *level == opacity { MF_STRING | MF_CHECKED } else { MF_STRING };
+        let text: Vec<u16> = format!("{}%", level).encode_utf16().chain(std::iter::once(0)).collect();
+        let _ = AppendMenuW(
+            opacity_menu,
+            flags,
+            (ID_TRAY_OPACITY + i as u32) as usize,
+            windows::core::PCWSTR(text.as_ptr()),
+        );
+    }
+    let _ = AppendMenuW(menu, MF_POPUP, opacity_menu.0 as usize, w!("Window opacity"));
+    let click_through_flags =
+        if crate::transparency::is_click_through() { MF_STRING | MF_CHECKED } else { MF_STRING };
+    let _ = AppendMenuW(menu, click_through_flags, ID_TRAY_CLICK_THROUGH as usize, w!("Click-through window"));
+    let _ = AppendMenuW(menu, MF_SEPARATOR, 0, None);
+    let _ = AppendMenuW(menu, MF_STRING, ID_TRAY_REVEAL as usize, w!("Reveal hidden topmost windows"));
+    let _ = AppendMenuW(menu, MF_STRING, ID_TRAY_TIMELINE as usize, w!("Timeline"));
+    let _ = AppendMenuW(menu, MF_STRING, ID_TRAY_INCIDENTS as usize, w!("Incidents"));
+    let _ = AppendMenuW(menu, MF_STRING, ID_TRAY_DIAGNOSTICS as usize, w!("Diagnostics log"));
+    let _ = AppendMenuW(menu, MF_SEPARATOR, 0, None);
+
     let exit_text = w!("Exit");
     let _ = AppendMenuW(menu, MF_STRING, ID_TRAY_EXIT as usize, exit_text);
 
@@ -211,12 +299,49 @@ unsafe extern "system" fn tray_window_proc(
             let cmd = (wparam.0 & 0xFFFF) as u32;
             if cmd == ID_TRAY_EXIT {
                 info!("Exit requested via tray menu");
-                SHOULD_EXIT.store(true, Ordering::SeqCst);
+                request_exit();
                 PostQuitMessage(0);
+            } else if cmd == ID_TRAY_DND {
+                crate::snooze::toggle_do_not_disturb();
+            } else if cmd == ID_TRAY_MUTE_SOUNDS {
+                crate::sound::toggle_mute();
+            } else if cmd == ID_TRAY_DOCK {
+                let window = HWND(crate::alert_window::window_hwnd() as *mut _);
+                crate::dock::set_enabled(window, !crate::config::get().alert_window.dock);
+            } else if cmd == ID_TRAY_CLICK_THROUGH {
+                crate::transparency::toggle_click_through(HWND(crate::alert_window::window_hwnd() as *mut _));
+            } else if let Some(level) = cmd
+                .checked_sub(ID_TRAY_OPACITY)
+                .and_then(|i| crate::transparency::OPACITY_LEVELS.get(i as usize))
+            {
+                crate::transparency::set_opacity(HWND(crate::alert_window::window_hwnd() as *mut _), *level);
+            } else if cmd == ID_TRAY_REVEAL {
+                crate::reveal::show_dialog(hwnd);
+            } else if cmd == ID_TRAY_TIMELINE {
+                crate::timeline::show_window();
+            } else if cmd == ID_TRAY_INCIDENTS {
+                crate::incident_window::show_dialog(hwnd);
+            } else if cmd == ID_TRAY_DIAGNOSTICS {
+                crate::diagnostics_window::show_dialog(hwnd);
+            } else if let Some(profile) = cmd
+                .checked_sub(ID_TRAY_PROFILE)
+                .and_then(|i| Profile::ALL.get(i as usize))
+            {
+                crate::config::apply_profile(*profile);
             }
             LRESULT(0)
         }
 
+        WM_POWERBROADCAST => {
+            crate::power::on_power_broadcast(wparam, lparam);
+            LRESULT(1)
+        }
+
+        WM_WTSSESSION_CHANGE => {
+            crate::input_stats::on_session_change(wparam);
+            LRESULT(0)
+        }
+
         WM_DESTROY => {
             remove_tray_icon(hwnd);
             PostQuitMessage(0);
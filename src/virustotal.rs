@@ -0,0 +1,223 @@
+//! VirusTotal Lookup
+//!
+//! With a user-provided API key, the SHA-256 of alerted executables is looked up against
+//! VirusTotal (only the hash is sent, never the file). Lookups run on one background thread
+//! (public API: 4 requests per minute), verdicts are cached in the event database.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use chrono::{Duration as ChronoDuration, Local};
+use crossbeam_channel::{bounded, Sender};
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use windows::Win32::Security::Cryptography::{
+    BCryptCreateHash, BCryptDestroyHash, BCryptFinishHash, BCryptHashData, BCRYPT_HASH_HANDLE,
+    BCRYPT_SHA256_ALG_HANDLE,
+};
+
+/// Public API quota: 4 lookups per minute
+const REQUEST_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Pending lookups (further alerts are skipped while full)
+const QUEUE_SIZE: usize = 32;
+
+/// Files are hashed in chunks (executables can be large)
+const HASH_CHUNK: usize = 1024 * 1024;
+
+/// Verdict for one executable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Verdict {
+    Pending,
+    /// Detection counts of the last analysis
+    Found { malicious: u32, suspicious: u32, total: u32 },
+    /// Hash not known to VirusTotal
+    Unknown,
+    /// Lookup failed (no network, invalid key, quota, ...)
+    Unavailable(String),
+}
+
+impl Verdict {
+    pub fn describe(&self) -> String {
+        match self {
+            Verdict::Pending => "looking up...".to_string(),
+            Verdict::Found { malicious, suspicious, total } if *suspicious > 0 => {
+                format!("{}/{} detections ({} suspicious)", malicious, total, suspicious)
+            }
+            Verdict::Found { malicious, total, .. } => format!("{}/{} detections", malicious, total),
+            Verdict::Unknown => "not known to VirusTotal".to_string(),
+            Verdict::Unavailable(reason) => format!("not available ({})", reason),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    // Verdict per executable (key = lowercase path)
+    static ref VERDICTS: Mutex<HashMap<String, Verdict>> = Mutex::new(HashMap::new());
+}
+
+// Queue of the lookup thread: (process name, process path)
+static LOOKUP_SENDER: OnceCell<Sender<(String, String)>> = OnceCell::new();
+
+/// Verdict for an executable (None = never looked up)
+pub fn verdict(process_path: &str) -> Option<Verdict> {
+    VERDICTS.lock().get(&process_path.to_lowercase()).cloned()
+}
+
+/// Queues a lookup for the executable of an alert (once per file and run)
+pub fn lookup(process_name: &str, process_path: &str) {
+    let config = crate::config::get().virustotal;
+    if !config.enabled || config.api_key.is_empty() || !Path::new(process_path).is_absolute() {
+        return;
+    }
+
+    {
+        let mut verdicts = VERDICTS.lock();
+        let key = process_path.to_lowercase();
+        if verdicts.contains_key(&key) {
+            return;
+        }
+        verdicts.insert(key, Verdict::Pending);
+    }
+
+    let sender = LOOKUP_SENDER.get_or_init(start_worker);
+    if sender.try_send((process_name.to_string(), process_path.to_string())).is_err() {
+        // Allow a new attempt with the next alert
+        VERDICTS.lock().remove(&process_path.to_lowercase());
+        warn!("VirusTotal queue full - lookup of {} skipped", process_path);
+    }
+}
+
+/// Lookup thread (one request at a time, respecting the quota)
+fn start_worker() -> Sender<(String, String)> {
+    let (sender, receiver) = bounded::<(String, String)>(QUEUE_SIZE);
+    thread::spawn(move || {
+        for (process_name, process_path) in receiver {
+            let (verdict, requested) = match sha256_file(Path::new(&process_path)) {
+                Ok(sha256) => resolve(&sha256),
+                Err(e) => (Verdict::Unavailable(e), false),
+            };
+            VERDICTS.lock().insert(process_path.to_lowercase(), verdict.clone());
+            report(process_name, process_path, &verdict);
+
+            if requested {
+                thread::sleep(REQUEST_INTERVAL);
+            }
+        }
+    });
+    sender
+}
+
+/// Verdict from the cache or VirusTotal (second value: an API request was made)
+fn resolve(sha256: &str) -> (Verdict, bool) {
+    let config = crate::config::get().virustotal;
+    if let Some((cached, checked)) = crate::database::get_file_verdict(sha256) {
+        let fresh = Local::now() - checked < ChronoDuration::days(config.cache_days as i64);
+        if let (true, Ok(verdict)) = (fresh, serde_json::from_str::<Verdict>(&cached)) {
+            return (verdict, false);
+        }
+    }
+
+    let verdict = match query(sha256, &config.api_key) {
+        Ok(verdict) => verdict,
+        Err(e) => {
+            error!("VirusTotal lookup failed: {}", e);
+            Verdict::Unavailable(e)
+        }
+    };
+
+    // Failed lookups are retried with the next run
+    if matches!(verdict, Verdict::Found { .. } | Verdict::Unknown) {
+        if let Ok(json) = serde_json::to_string(&verdict) {
+            crate::database::set_file_verdict(sha256, &json);
+        }
+    }
+    (verdict, true)
+}
+
+/// Logs the verdict and refreshes open details windows
+fn report(process_name: String, process_path: String, verdict: &Verdict) {
+    let severity = if matches!(verdict, Verdict::Found { malicious, .. } if *malicious > 0) {
+        warn!("!!! VirusTotal verdict for {}: {} !!!", process_path, verdict.describe());
+        Severity::Warning
+    } else {
+        info!("VirusTotal verdict for {}: {}", process_path, verdict.describe());
+        Severity::Info
+    };
+
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: Local::now(),
+        event_type: "VIRUSTOTAL".to_string(),
+        severity,
+        process_name,
+        process_path,
+        window_title: format!("VirusTotal: {}", verdict.describe()),
+        ..Default::default()
+    });
+    crate::alert_window::redraw_details_window();
+}
+
+/// SHA-256 of a file as lowercase hex (BCrypt)
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("cannot read file: {}", e))?;
+
+    unsafe {
+        let mut hash = BCRYPT_HASH_HANDLE::default();
+        let status = BCryptCreateHash(BCRYPT_SHA256_ALG_HANDLE, &mut hash, None, None, 0);
+        if status.is_err() {
+            return Err(format!("BCryptCreateHash failed: {:?}", status));
+        }
+
+        let mut buffer = vec![0u8; HASH_CHUNK];
+        let mut digest = [0u8; 32];
+        let result = loop {
+            match file.read(&mut buffer) {
+                Ok(0) => break BCryptFinishHash(hash, &mut digest, 0).ok().map_err(|e| e.to_string()),
+                Ok(n) => {
+                    if let Err(e) = BCryptHashData(hash, &buffer[..n], 0).ok() {
+                        break Err(e.to_string());
+                    }
+                }
+                Err(e) => break Err(format!("cannot read file: {}", e)),
+            }
+        };
+        let _ = BCryptDestroyHash(hash);
+
+        result.map(|_| digest.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+}
+
+/// GET /api/v3/files/<sha256>
+fn query(sha256: &str, api_key: &str) -> Result<Verdict, String> {
+    let response = crate::http::request(
+        "GET",
+        &format!("https://www.virustotal.com/api/v3/files/{}", sha256),
+        &[("x-apikey", api_key), ("Accept", "application/json")],
+        &[],
+    )?;
+
+    match response.status {
+        200 => {
+            let json: serde_json::Value = serde_json::from_slice(&response.body).map_err(|e| format!("invalid response: {}", e))?;
+            let stats = &json["data"]["attributes"]["last_analysis_stats"];
+            let count = |key: &str| stats[key].as_u64().unwrap_or(0) as u32;
+            let total = ["malicious", "suspicious", "undetected", "harmless"].iter().map(|k| count(k)).sum();
+            Ok(Verdict::Found {
+                malicious: count("malicious"),
+                suspicious: count("suspicious"),
+                total,
+            })
+        }
+        404 => Ok(Verdict::Unknown),
+        401 => Err("invalid API key".to_string()),
+        429 => Err("quota exceeded".to_string()),
+        status => Err(format!("HTTP {}", status)),
+    }
+}
@@ -0,0 +1,144 @@
+//! Watched Processes
+//!
+//! Processes pinned by the user (context menu or config).
+//! Tracks live status (running/exited) and focus count today for the watch panel.
+
+use chrono::{Local, NaiveDate};
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::thread;
+use std::time::Duration;
+use tracing::info;
+
+/// Status refresh interval
+const MONITOR_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Live status of a watched process (for the watch panel)
+#[derive(Debug, Clone)]
+pub struct WatchStatus {
+    pub name: String,
+    pub running: bool,
+    pub focus_count_today: u32,
+}
+
+lazy_static::lazy_static! {
+    // Normalized name -> (day, focus count on that day)
+    static ref FOCUS_COUNTS: Mutex<HashMap<String, (NaiveDate, u32)>> = Mutex::new(HashMap::new());
+    // Normalized names of watched processes that are currently running
+    static ref RUNNING: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+/// Normalizes a process name for comparisons (lowercase, without .exe)
+fn normalize(name: &str) -> String {
+    let lower = name.trim().to_lowercase();
+    lower.strip_suffix(".exe").unwrap_or(&lower).to_string()
+}
+
+/// Checks if a process is watched
+pub fn is_watched(process_name: &str) -> bool {
+    let name = normalize(process_name);
+    crate::config::get().watched_processes.iter().any(|w| normalize(w) == name)
+}
+
+/// Adds a process to the watch list
+pub fn watch_process(process_name: &str) {
+    if process_name.is_empty() || is_watched(process_name) {
+        return;
+    }
+    let name = process_name.to_string();
+    crate::config::update(|config| config.watched_processes.push(name));
+    info!("Watching process: {}", process_name);
+    refresh_status();
+}
+
+/// Removes a process from the watch list
+pub fn unwatch_process(process_name: &str) {
+    let name = normalize(process_name);
+    crate::config::update(|config| config.watched_processes.retain(|w| normalize(w) != name));
+    FOCUS_COUNTS.lock().remove(&name);
+    RUNNING.lock().remove(&name);
+    info!("Stopped watching process: {}", process_name);
+    crate::alert_window::refresh_watch_panel();
+}
+
+/// Counts a focus event of a watched process (resets every day)
+pub fn record_focus(process_name: &str) {
+    let today = Local::now().date_naive();
+    {
+        let mut counts = FOCUS_COUNTS.lock();
+        let entry = counts.entry(normalize(process_name)).or_insert((today, 0));
+        if entry.0 != today {
+            *entry = (today, 0);
+        }
+        entry.1 += 1;
+    }
+    crate::alert_window::refresh_watch_panel();
+}
+
+/// Returns the status of all watched processes (in config order)
+pub fn status() -> Vec<WatchStatus> {
+    let today = Local::now().date_naive();
+    let counts = FOCUS_COUNTS.lock();
+    let running = RUNNING.lock();
+
+    crate::config::get()
+        .watched_processes
+        .iter()
+        .map(|name| {
+            let key = normalize(name);
+            let focus_count_today = match counts.get(&key) {
+                Some((day, count)) if *day == today => *count,
+                _ => 0,
+            };
+            WatchStatus {
+                name: name.clone(),
+                running: running.contains(&key),
+                focus_count_today,
+            }
+        })
+        .collect()
+}
+
+/// Updates the running state of all watched processes
+/// Logs start/exit transitions and refreshes the panel on changes
+fn refresh_status() {
+    let watched: Vec<String> = crate::config::get()
+        .watched_processes
+        .iter()
+        .map(|w| normalize(w))
+        .collect();
+
+    let now_running: HashSet<String> = if watched.is_empty() {
+        HashSet::new()
+    } else {
+        let running_names = crate::process_info::get_running_process_names();
+        watched.into_iter().filter(|w| running_names.contains(w)).collect()
+    };
+
+    let changed = {
+        let mut running = RUNNING.lock();
+        for name in now_running.difference(&running) {
+            info!("Watched process running: {}", name);
+        }
+        for name in running.difference(&now_running) {
+            info!("Watched process exited: {}", name);
+        }
+        let changed = *running != now_running;
+        *running = now_running;
+        changed
+    };
+
+    if changed {
+        crate::alert_window::refresh_watch_panel();
+    }
+}
+
+/// Starts the status monitor thread
+pub fn start_monitor() {
+    thread::spawn(|| {
+        while !crate::tray::should_exit() {
+            refresh_status();
+            thread::sleep(MONITOR_INTERVAL);
+        }
+    });
+}
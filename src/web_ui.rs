@@ -0,0 +1,399 @@
+//! Web Dashboard
+//!
+//! Local single-page dashboard of the watcher, as an alternative to the GDI windows: live
+//! events (polled), the activity timeline, the incident list with member events and captures,
+//! and a gallery of the alert screenshots/recordings. Same minimal HTTP as the server
+//! dashboard, HTTPS with a certificate, password via HTTP basic auth (token or API token).
+//! Viewers only read; admins (token or admin API token) can also pause alerts, kill a
+//! process and reload the configuration (POST requests).
+
+use crate::api_tokens::Role;
+use crate::dashboard::{accept, event_query, query_param, respond, url_decode, Request};
+use crate::database::EventQuery;
+use crate::logger::LogEntry;
+use crate::tls::Certificate;
+use chrono::{Local, NaiveDateTime, TimeZone};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use tracing::{error, info, warn};
+
+/// Incidents in the list
+const MAX_INCIDENTS: usize = 100;
+/// Capture folders in the gallery
+const MAX_CAPTURE_FOLDERS: usize = 60;
+
+const HTML: &str = "text/html; charset=utf-8";
+const JSON: &str = "application/json; charset=utf-8";
+
+/// Starts the dashboard server (opt-in, refuses to run without a token)
+pub fn start() {
+    let config = crate::config::get().web_ui;
+    if !config.enabled {
+        return;
+    }
+    if config.token.is_empty() && crate::api_tokens::list().is_empty() {
+        error!("Web dashboard not started: web_ui.token is empty and no API tokens exist");
+        return;
+    }
+    let certificate = match config.tls_certificate.as_str() {
+        "" => None,
+        file => match Certificate::load(file) {
+            Ok(certificate) => Some(Arc::new(certificate)),
+            Err(e) => {
+                error!("Web dashboard not started: {}", e);
+                return;
+            }
+        },
+    };
+
+    thread::spawn(move || {
+        let listener = match std::net::TcpListener::bind(&config.listen) {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Web dashboard cannot listen on {}: {}", config.listen, e);
+                return;
+            }
+        };
+        let scheme = if certificate.is_some() { "https" } else { "http" };
+        info!("Web dashboard on {}://{}/", scheme, config.listen);
+        for tcp in listener.incoming().flatten() {
+            let certificate = certificate.clone();
+            thread::spawn(move || {
+                if let Err(e) = handle_request(tcp, certificate.as_deref()) {
+                    warn!("Web dashboard request failed: {}", e);
+                }
+            });
+        }
+    });
+}
+
+fn handle_request(tcp: std::net::TcpStream, certificate: Option<&Certificate>) -> Result<(), String> {
+    let (mut reader, request) = accept(tcp, certificate)?;
+    // Read per request: a changed token applies without restart
+    let token = crate::config::get().web_ui.token;
+    let Some(role) = crate::api_tokens::authorize(request.password.as_deref(), &token) else {
+        return respond(reader.get_mut(), "401 Unauthorized", HTML, b"Login required", false);
+    };
+
+    let routed = match request.method.as_str() {
+        "POST" if role < Role::Admin => {
+            return respond(reader.get_mut(), "403 Forbidden", JSON, &error_json("Admin role required"), true);
+        }
+        "POST" if !request.is_same_origin() => {
+            warn!("Web dashboard: cross-origin POST {} from {:?} rejected", request.path(), request.origin);
+            return respond(reader.get_mut(), "403 Forbidden", JSON, &error_json("Cross-origin request"), true);
+        }
+        "POST" => action(&request),
+        // Same filters and JSON as the query API of the server dashboard
+        "GET" if request.path() == "/api/events" => match event_query(&request.target) {
+            Ok(query) => events_json(&query).map(|body| Some((JSON, body.into_bytes()))),
+            Err(e) => return respond(reader.get_mut(), "400 Bad Request", JSON, &error_json(&e), true),
+        },
+        _ => route(&request, role),
+    };
+    let (status, content_type, body) = match routed {
+        Ok(Some((content_type, body))) => ("200 OK", content_type, body),
+        Ok(None) => ("404 Not Found", JSON, error_json("Not found")),
+        Err(e) => {
+            warn!("Web dashboard: {} failed: {}", request.path(), e);
+            ("500 Internal Server Error", JSON, error_json(&e))
+        }
+    };
+    respond(reader.get_mut(), status, content_type, &body, true)
+}
+
+fn error_json(message: &str) -> Vec<u8> {
+    serde_json::json!({ "error": message }).to_string().into_bytes()
+}
+
+/// Response of a GET request (None = not found)
+fn route(request: &Request, role: Role) -> Result<Option<(&'static str, Vec<u8>)>, String> {
+    if request.method != "GET" {
+        return Ok(None);
+    }
+    let path = request.path();
+    let json = |value: serde_json::Value| Ok(Some((JSON, value.to_string().into_bytes())));
+    match path {
+        "/" => Ok(Some((HTML, PAGE.as_bytes().to_vec()))),
+        "/api/session" => json(serde_json::json!({
+            "role": role.as_str(),
+            "do_not_disturb": crate::snooze::is_do_not_disturb(),
+        })),
+        "/api/timeline" => json(crate::timeline::json()),
+        "/api/incidents" => json(incidents_json()?),
+        "/api/captures" => json(captures_json()),
+        _ => {
+            if let Some(id) = path.strip_prefix("/api/incidents/").and_then(|id| id.parse().ok()) {
+                return json(incident_json(id)?);
+            }
+            if let Some(rest) = path.strip_prefix("/captures/") {
+                return Ok(capture_file(rest));
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Admin actions (POST): pause alerts, kill a process, reload the configuration
+fn action(request: &Request) -> Result<Option<(&'static str, Vec<u8>)>, String> {
+    let ok = |value: serde_json::Value| Ok(Some((JSON, value.to_string().into_bytes())));
+    match request.path() {
+        "/api/pause" => {
+            let enabled = query_param(&request.target, "enabled").is_none_or(|v| v != "0" && v != "false");
+            crate::snooze::set_do_not_disturb(enabled);
+            ok(serde_json::json!({ "do_not_disturb": enabled }))
+        }
+        "/api/kill" => {
+            let pid: u32 = query_param(&request.target, "pid")
+                .and_then(|v| v.parse().ok())
+                .ok_or("pid missing or invalid")?;
+            crate::alert_window::terminate_process(pid).map_err(|e| format!("PID {}: {}", pid, e))?;
+            info!("Process killed via web dashboard (PID: {})", pid);
+            ok(serde_json::json!({ "killed": pid }))
+        }
+        "/api/reload" => {
+            crate::config::reload()?;
+            ok(serde_json::json!({ "reloaded": true }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Local events of the query, as /api/events of the server dashboard (machine: this computer)
+fn events_json(query: &EventQuery) -> Result<String, String> {
+    let machine = crate::forwarder::machine_name(&crate::config::get().forwarder);
+    if query.machine.as_deref().is_some_and(|m| !m.eq_ignore_ascii_case(&machine)) {
+        return Ok(crate::dashboard::events_json(&[], query.limit));
+    }
+    let conn = crate::database::open().map_err(|e| e.to_string())?;
+    let events = crate::database::query_events(&conn, query).map_err(|e| e.to_string())?;
+    let events: Vec<(i64, String, LogEntry)> =
+        events.into_iter().map(|(id, entry)| (id, machine.clone(), entry)).collect();
+    Ok(crate::dashboard::events_json(&events, query.limit))
+}
+
+fn incidents_json() -> Result<serde_json::Value, String> {
+    let conn = crate::database::open().map_err(|e| e.to_string())?;
+    let incidents = crate::database::read_incidents(&conn, MAX_INCIDENTS).map_err(|e| e.to_string())?;
+    Ok(incidents
+        .iter()
+        .map(|incident| {
+            serde_json::json!({
+                "id": incident.id,
+                "started": incident.started.to_rfc3339(),
+                "last_event": incident.last_event.to_rfc3339(),
+                "severity": incident.severity.as_str(),
+                "process_name": incident.process_name,
+                "title": incident.title,
+                "acknowledged": incident.acknowledged,
+                "events": incident.event_count,
+            })
+        })
+        .collect())
+}
+
+/// Member events and capture files of an incident
+fn incident_json(id: i64) -> Result<serde_json::Value, String> {
+    let conn = crate::database::open().map_err(|e| e.to_string())?;
+    let events = crate::database::read_incident_events(&conn, id).map_err(|e| e.to_string())?;
+    let captures: Vec<serde_json::Value> = crate::alert_export::incident_capture_folders(&events)
+        .iter()
+        .filter_map(|folder| folder_json(folder))
+        .collect();
+    let events: Vec<serde_json::Value> = events.iter().map(LogEntry::format_json).collect();
+    Ok(serde_json::json!({ "id": id, "events": events, "captures": captures }))
+}
+
+/// Capture folder with its files: {folder, time, files: [URL]}
+fn folder_json(folder: &std::path::Path) -> Option<serde_json::Value> {
+    let name = folder.file_name()?.to_string_lossy().to_string();
+    let mut files: Vec<String> = std::fs::read_dir(folder)
+        .ok()?
+        .flatten()
+        .filter(|e| e.path().is_file())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    let time = name
+        .get(..19)
+        .and_then(|t| NaiveDateTime::parse_from_str(t, "%Y-%m-%d_%H-%M-%S").ok())
+        .and_then(|t| Local.from_local_datetime(&t).earliest())
+        .map(|t| t.to_rfc3339());
+    let urls: Vec<String> = files.iter().map(|file| format!("/captures/{}/{}", name, file)).collect();
+    Some(serde_json::json!({ "folder": name, "time": time, "files": urls }))
+}
+
+/// Capture folders of all sessions of this user, newest first
+fn captures_json() -> serde_json::Value {
+    let mut folders: Vec<PathBuf> = crate::logger::user_log_dirs()
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten().map(|e| e.path()))
+        .collect();
+    folders.retain(|p| {
+        p.is_dir()
+            && p.file_name().and_then(|n| n.to_str()).and_then(|n| n.get(..19)).is_some_and(|t| {
+                NaiveDateTime::parse_from_str(t, "%Y-%m-%d_%H-%M-%S").is_ok()
+            })
+    });
+    // By folder name = capture time, across sessions
+    folders.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
+    folders.truncate(MAX_CAPTURE_FOLDERS);
+    serde_json::Value::Array(folders.iter().filter_map(|folder| folder_json(folder)).collect())
+}
+
+/// Name that cannot leave its folder ("..", separators, drive letters)
+fn safe_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', ':'])
+}
+
+/// File of a capture folder: /captures/<folder>/<file>
+fn capture_file(rest: &str) -> Option<(&'static str, Vec<u8>)> {
+    let (folder, file) = rest.split_once('/')?;
+    let (folder, file) = (url_decode(folder), url_decode(file));
+    if !safe_name(&folder) || !safe_name(&file) {
+        return None;
+    }
+    let path = crate::logger::user_log_dirs()
+        .into_iter()
+        .map(|dir| dir.join(&folder).join(&file))
+        .find(|path| path.is_file())?;
+    let content_type = match path.extension()?.to_string_lossy().to_lowercase().as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "png" => "image/png",
+        "txt" => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    };
+    std::fs::read(&path).ok().map(|bytes| (content_type, bytes))
+}
+
+/// The single page: plain HTML/JS, talks to the /api endpoints above
+const PAGE: &str = r#"<!DOCTYPE html>
+<html><head><meta charset="utf-8"><title>PC Watcher</title><style>
+body{font-family:Segoe UI,sans-serif;margin:0;background:#f4f4f4;font-size:13px}
+header{background:#333;color:#fff;padding:8px 16px;display:flex;gap:16px;align-items:center}
+header b{font-size:15px;margin-right:16px}
+header a{color:#ccc;cursor:pointer;text-decoration:none}header a.active{color:#fff;font-weight:bold}
+main{padding:16px}section{display:none}section.active{display:block}
+table{border-collapse:collapse;background:#fff;width:100%}
+th,td{padding:3px 8px;border-bottom:1px solid #ddd;text-align:left;vertical-align:top}
+th{background:#555;color:#fff}.WARNING{background:#fff1dc}.CRITICAL{background:#ffd9d9}
+.tag{background:#4080c0;color:#fff;border-radius:8px;padding:0 6px;margin-left:4px;font-size:11px}
+.day{display:flex;align-items:center;margin:4px 0}.day span{width:90px}
+.strip{position:relative;flex:1;height:20px;background:#383838}.strip div{position:absolute;top:0;bottom:0}
+.active-state{background:#4caf50}.idle{background:#e6a500}.locked{background:#4285f4}.asleep{background:#7e57c2}.unknown{background:#383838}
+.legend span{display:inline-block;width:10px;height:10px;margin:0 4px 0 12px}
+.gallery{display:flex;flex-wrap:wrap;gap:12px}.capture{background:#fff;padding:8px;max-width:420px}
+.capture img{max-width:200px;max-height:150px;margin:2px;cursor:pointer}
+#incident-list tr{cursor:pointer}#incident-list tr:hover{background:#eef}
+</style></head><body>
+<header><b>PC Watcher</b>
+<a data-tab="live" class="active">Live events</a><a data-tab="timeline">Timeline</a>
+<a data-tab="incidents">Incidents</a><a data-tab="captures">Screenshots</a>
+<span id="status" style="margin-left:auto;color:#aaa"></span>
+<span id="admin" style="display:none"><button id="pause"></button> <button id="reload">Reload config</button></span></header>
+<main>
+<section id="live" class="active"><label><input type="checkbox" id="alerts-only"> alerts only</label>
+<table><thead><tr><th>Time</th><th>Severity</th><th>Event</th><th>Process</th><th>Window</th><th>Path</th></tr></thead>
+<tbody id="events"></tbody></table></section>
+<section id="timeline"><div id="days"></div><div class="legend"><span class="active-state"></span>Active
+<span class="idle"></span>Idle<span class="locked"></span>Locked<span class="asleep"></span>Asleep
+<span class="unknown"></span>Not monitored</div></section>
+<section id="incidents"><table><thead><tr><th>#</th><th>Started</th><th>Last event</th><th>Severity</th><th>Process</th>
+<th>Title</th><th>Events</th></tr></thead><tbody id="incident-list"></tbody></table><div id="incident"></div></section>
+<section id="captures"><div class="gallery" id="gallery"></div></section>
+</main>
+<script>
+const MAX_ROWS = 500;
+let lastId = null;
+const esc = t => { const d = document.createElement('div'); d.textContent = t == null ? '' : String(t); return d.innerHTML; };
+const time = t => t ? new Date(t).toLocaleString() : '';
+const get = url => fetch(url, {cache: 'no-store'}).then(r => { if (!r.ok) throw new Error(r.status); return r.json(); });
+
+function eventRow(e) {
+  const tags = (e.tags || []).map(t => `<span class="tag">${esc(t)}</span>`).join('');
+  return `<tr class="${esc(e.severity)}" data-severity="${esc(e.severity)}"><td>${time(e.timestamp)}</td><td>${esc(e.severity)}</td>` +
+    `<td>${esc(e.event_type)}${tags}</td><td>${esc(e.process_name)} (${esc(e.process_id)})</td>` +
+    `<td>${esc(e.window_title)}</td><td>${esc(e.process_path)}</td></tr>`;
+}
+function applyFilter() {
+  const alertsOnly = document.getElementById('alerts-only').checked;
+  for (const row of document.getElementById('events').rows)
+    row.style.display = alertsOnly && row.dataset.severity === 'INFO' ? 'none' : '';
+}
+async function poll() {
+  try {
+    const query = lastId === null ? 'limit=200' : `after=${lastId}&limit=${MAX_ROWS}`;
+    const data = await get('/api/events?' + query);
+    const body = document.getElementById('events');
+    body.insertAdjacentHTML('afterbegin', data.events.map(eventRow).join(''));
+    while (body.rows.length > MAX_ROWS) body.deleteRow(-1);
+    if (data.events.length) lastId = data.events[0].id;
+    else if (lastId === null) lastId = 0;
+    applyFilter();
+    document.getElementById('status').textContent = 'updated ' + new Date().toLocaleTimeString();
+  } catch (e) {
+    document.getElementById('status').textContent = 'connection lost (' + e.message + ')';
+  }
+}
+async function loadTimeline() {
+  const days = await get('/api/timeline');
+  document.getElementById('days').innerHTML = days.map(day =>
+    `<div class="day"><span>${esc(day.date)}</span><div class="strip">` +
+    day.runs.filter(r => r.state !== 'unknown').map(r =>
+      `<div class="${r.state === 'active' ? 'active-state' : esc(r.state)}" style="left:${r.start / 14.4}%;width:${(r.end - r.start) / 14.4}%"></div>`
+    ).join('') + '</div></div>').join('');
+}
+function captureHtml(c) {
+  const files = c.files.map(f => /\.(jpe?g|gif|png)$/i.test(f)
+    ? `<a href="${esc(f)}" target="_blank"><img src="${esc(f)}" loading="lazy"></a>`
+    : `<div><a href="${esc(f)}" target="_blank">${esc(f.split('/').pop())}</a></div>`).join('');
+  return `<div class="capture"><div><b>${esc(c.folder)}</b></div>${files}</div>`;
+}
+async function loadIncidents() {
+  const incidents = await get('/api/incidents');
+  document.getElementById('incident-list').innerHTML = incidents.map(i =>
+    `<tr class="${esc(i.severity)}" data-id="${i.id}"><td>${i.id}${i.acknowledged ? '' : ' *'}</td><td>${time(i.started)}</td>` +
+    `<td>${time(i.last_event)}</td><td>${esc(i.severity)}</td><td>${esc(i.process_name)}</td><td>${esc(i.title)}</td><td>${i.events}</td></tr>`
+  ).join('') || '<tr><td colspan="7">No incidents recorded</td></tr>';
+}
+async function showIncident(id) {
+  const incident = await get(`/api/incidents/${id}`);
+  document.getElementById('incident').innerHTML = `<h3>Incident #${incident.id}</h3>` +
+    `<table><tbody>${incident.events.map(e => eventRow(e)).join('')}</tbody></table>` +
+    `<div class="gallery" style="margin-top:12px">${incident.captures.map(captureHtml).join('')}</div>`;
+}
+async function loadCaptures() {
+  const captures = await get('/api/captures');
+  document.getElementById('gallery').innerHTML = captures.map(captureHtml).join('') || 'No captures in this session';
+}
+const loaders = {timeline: loadTimeline, incidents: loadIncidents, captures: loadCaptures};
+for (const link of document.querySelectorAll('header a')) {
+  link.onclick = () => {
+    for (const other of document.querySelectorAll('header a, section')) other.classList.remove('active');
+    link.classList.add('active');
+    document.getElementById(link.dataset.tab).classList.add('active');
+    (loaders[link.dataset.tab] || (() => {}))();
+  };
+}
+let doNotDisturb = false;
+const post = url => fetch(url, {method: 'POST'}).then(r => r.json().then(d => { if (!r.ok) throw new Error(d.error || r.status); return d; }));
+const showPause = () => document.getElementById('pause').textContent = doNotDisturb ? 'Resume alerts' : 'Pause alerts';
+async function loadSession() {
+  const session = await get('/api/session');
+  doNotDisturb = session.do_not_disturb;
+  showPause();
+  if (session.role === 'admin') document.getElementById('admin').style.display = '';
+}
+document.getElementById('pause').onclick = () => post(`/api/pause?enabled=${doNotDisturb ? 0 : 1}`)
+  .then(d => { doNotDisturb = d.do_not_disturb; showPause(); }).catch(e => alert(e.message));
+document.getElementById('reload').onclick = () => post('/api/reload')
+  .then(() => alert('Configuration reloaded')).catch(e => alert(e.message));
+document.getElementById('alerts-only').onchange = applyFilter;
+document.getElementById('incident-list').onclick = e => { const row = e.target.closest('tr'); if (row && row.dataset.id) showIncident(row.dataset.id); };
+loadSession();
+poll();
+setInterval(poll, 2000);
+</script></body></html>"#;
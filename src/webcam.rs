@@ -0,0 +1,160 @@
+//! Webcam Snapshot
+//!
+//! Optional (opt-in) webcam frame on Critical alerts - "who is physically at my PC?".
+//! Uses Media Foundation: first video capture device, one frame converted to RGB32.
+
+use crate::error::Error;
+use image::{ImageBuffer, Rgb};
+use std::path::Path;
+use tracing::info;
+use windows::Win32::Media::MediaFoundation::{
+    IMFActivate, IMFAttributes, IMFMediaSource, IMFSample, MFCreateAttributes, MFCreateMediaType,
+    MFCreateSourceReaderFromMediaSource, MFEnumDeviceSources, MFShutdown, MFStartup,
+    MFMediaType_Video, MFVideoFormat_RGB32, MFSTARTUP_FULL, MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE,
+    MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE_VIDCAP_GUID, MF_MT_DEFAULT_STRIDE, MF_MT_FRAME_SIZE,
+    MF_MT_MAJOR_TYPE, MF_MT_SUBTYPE, MF_SOURCE_READERF_ENDOFSTREAM, MF_SOURCE_READERF_ERROR,
+    MF_SOURCE_READER_ENABLE_VIDEO_PROCESSING, MF_SOURCE_READER_FIRST_VIDEO_STREAM, MF_VERSION,
+};
+use windows::Win32::System::Com::{CoInitializeEx, CoTaskMemFree, CoUninitialize, COINIT_MULTITHREADED};
+
+/// First frames are often dark (auto exposure) - use a later one
+const WARMUP_FRAMES: usize = 10;
+
+/// Checks if webcam snapshots are enabled (shown in the alert window)
+pub fn is_enabled() -> bool {
+    crate::config::get().capture.webcam_on_critical
+}
+
+/// Takes one webcam frame and saves it as webcam.jpg in the folder
+pub fn capture_snapshot(dir: &Path) -> Result<(), Error> {
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        MFStartup(MF_VERSION, MFSTARTUP_FULL).map_err(|e| Error::win32("MFStartup", &e))?;
+
+        let result = capture_frame();
+
+        let _ = MFShutdown();
+        CoUninitialize();
+
+        let (pixels, width, height) = result?;
+        let img: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_raw(width, height, pixels)
+            .ok_or_else(|| Error::Device("Invalid webcam frame".to_string()))?;
+        let path = dir.join("webcam.jpg");
+        img.save(&path)
+            .map_err(|e| Error::Io { path: path.clone(), message: format!("Could not save webcam frame: {}", e) })?;
+        info!("Webcam snapshot saved: {}", path.display());
+    }
+    Ok(())
+}
+
+/// Opens the first video capture device
+unsafe fn open_first_camera() -> Result<IMFMediaSource, Error> {
+    let mut attributes: Option<IMFAttributes> = None;
+    MFCreateAttributes(&mut attributes, 1).map_err(|e| Error::win32("MFCreateAttributes", &e))?;
+    let attributes = attributes
+        .ok_or_else(|| Error::Win32 { call: "MFCreateAttributes", message: "no attributes".to_string() })?;
+    attributes
+        .SetGUID(&MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE, &MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE_VIDCAP_GUID)
+        .map_err(|e| Error::win32("SetGUID", &e))?;
+
+    let mut devices: *mut Option<IMFActivate> = std::ptr::null_mut();
+    let mut count: u32 = 0;
+    MFEnumDeviceSources(&attributes, &mut devices, &mut count)
+        .map_err(|e| Error::win32("MFEnumDeviceSources", &e))?;
+
+    let list = std::slice::from_raw_parts_mut(devices, count as usize);
+    let source = match list.first().and_then(|d| d.as_ref()) {
+        Some(device) => device.ActivateObject::<IMFMediaSource>().map_err(|e| Error::win32("ActivateObject", &e)),
+        None => Err(Error::Device("No webcam found".to_string())),
+    };
+
+    // Release activation objects and the array
+    for device in list.iter_mut() {
+        *device = None;
+    }
+    CoTaskMemFree(Some(devices as *const _));
+
+    source
+}
+
+/// Reads a frame as RGB (pixels, width, height)
+unsafe fn capture_frame() -> Result<(Vec<u8>, u32, u32), Error> {
+    let source = open_first_camera()?;
+
+    // Let Media Foundation convert any camera format to RGB32
+    let mut reader_attributes: Option<IMFAttributes> = None;
+    MFCreateAttributes(&mut reader_attributes, 1).map_err(|e| Error::win32("MFCreateAttributes", &e))?;
+    let reader_attributes = reader_attributes
+        .ok_or_else(|| Error::Win32 { call: "MFCreateAttributes", message: "no attributes".to_string() })?;
+    let _ = reader_attributes.SetUINT32(&MF_SOURCE_READER_ENABLE_VIDEO_PROCESSING, 1);
+
+    let result = read_frame(&source, &reader_attributes);
+    let _ = source.Shutdown();
+    result
+}
+
+/// Reads frames until warmed up and converts the last one
+unsafe fn read_frame(source: &IMFMediaSource, attributes: &IMFAttributes) -> Result<(Vec<u8>, u32, u32), Error> {
+    let stream = MF_SOURCE_READER_FIRST_VIDEO_STREAM.0 as u32;
+    let reader = MFCreateSourceReaderFromMediaSource(source, attributes)
+        .map_err(|e| Error::win32("MFCreateSourceReaderFromMediaSource", &e))?;
+
+    let media_type = MFCreateMediaType().map_err(|e| Error::win32("MFCreateMediaType", &e))?;
+    media_type.SetGUID(&MF_MT_MAJOR_TYPE, &MFMediaType_Video).map_err(|e| Error::win32("SetGUID", &e))?;
+    media_type.SetGUID(&MF_MT_SUBTYPE, &MFVideoFormat_RGB32).map_err(|e| Error::win32("SetGUID", &e))?;
+    reader
+        .SetCurrentMediaType(stream, None, &media_type)
+        .map_err(|e| Error::Device(format!("Camera does not support RGB32: {}", e)))?;
+
+    let current = reader.GetCurrentMediaType(stream).map_err(|e| Error::win32("GetCurrentMediaType", &e))?;
+    let frame_size = current.GetUINT64(&MF_MT_FRAME_SIZE).map_err(|e| Error::win32("GetUINT64 (frame size)", &e))?;
+    let width = (frame_size >> 32) as u32;
+    let height = (frame_size & 0xFFFF_FFFF) as u32;
+    // Negative stride = bottom-up
+    let stride = current
+        .GetUINT32(&MF_MT_DEFAULT_STRIDE)
+        .map(|s| s as i32)
+        .unwrap_or((width * 4) as i32);
+
+    let mut last_sample: Option<IMFSample> = None;
+    for _ in 0..WARMUP_FRAMES {
+        let mut flags: u32 = 0;
+        let mut sample: Option<IMFSample> = None;
+        reader
+            .ReadSample(stream, 0, None, Some(&mut flags), None, Some(&mut sample))
+            .map_err(|e| Error::win32("ReadSample", &e))?;
+        if flags & (MF_SOURCE_READERF_ERROR.0 | MF_SOURCE_READERF_ENDOFSTREAM.0) as u32 != 0 {
+            break;
+        }
+        if sample.is_some() {
+            last_sample = sample;
+        }
+    }
+    let sample = last_sample.ok_or_else(|| Error::Device("No webcam frame received".to_string()))?;
+
+    let buffer = sample.ConvertToContiguousBuffer().map_err(|e| Error::win32("ConvertToContiguousBuffer", &e))?;
+    let mut data: *mut u8 = std::ptr::null_mut();
+    let mut length: u32 = 0;
+    buffer.Lock(&mut data, None, Some(&mut length)).map_err(|e| Error::win32("Lock", &e))?;
+
+    let row_bytes = stride.unsigned_abs() as usize;
+    let needed = row_bytes * height as usize;
+    let result = if data.is_null() || (length as usize) < needed || row_bytes < width as usize * 4 {
+        Err(Error::Device("Unexpected webcam buffer size".to_string()))
+    } else {
+        let frame = std::slice::from_raw_parts(data, needed);
+        let mut rgb = Vec::with_capacity((width * height * 3) as usize);
+        for row in 0..height as usize {
+            let src_row = if stride < 0 { height as usize - 1 - row } else { row };
+            let start = src_row * row_bytes;
+            for px in frame[start..start + width as usize * 4].chunks_exact(4) {
+                // BGRA -> RGB
+                rgb.extend_from_slice(&[px[2], px[1], px[0]]);
+            }
+        }
+        Ok((rgb, width, height))
+    };
+
+    let _ = buffer.Unlock();
+    result
+}
@@ -0,0 +1,241 @@
+//! WMI Process Information
+//!
+//! Second source for process data when OpenProcess is denied (hardened systems, processes
+//! protected by security software): name, path, command line, parent and creation time come
+//! from `Win32_Process` in root\cimv2. Slower than the direct API, so it is only asked when
+//! that fails (`process_info.wmi_fallback`). Each thread keeps its own connection.
+
+use crate::process_info::{ProcessInfoProvider, Win32Provider};
+use chrono::{FixedOffset, NaiveDateTime, TimeZone};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+use windows::core::{BSTR, PCWSTR, VARIANT};
+use windows::Win32::Foundation::HWND;
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoSetProxyBlanket, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED, EOAC_NONE,
+    RPC_C_AUTHN_LEVEL_CALL, RPC_C_IMP_LEVEL_IMPERSONATE,
+};
+use windows::Win32::System::Wmi::{
+    IWbemClassObject, IWbemLocator, IWbemServices, WbemLocator, WBEM_FLAG_FORWARD_ONLY, WBEM_FLAG_RETURN_IMMEDIATELY,
+    WBEM_INFINITE,
+};
+
+/// RPC authentication service / authorization (rpcdce.h)
+const RPC_C_AUTHN_WINNT: u32 = 10;
+const RPC_C_AUTHZ_NONE: u32 = 0;
+
+/// One lookup answers path, command line and creation time of a process
+const CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Seconds between 1601-01-01 (FILETIME) and 1970-01-01
+const FILETIME_UNIX_OFFSET: i64 = 11_644_473_600;
+
+/// Process as reported by WMI
+#[derive(Debug, Clone, Default)]
+struct WmiProcess {
+    name: String,
+    path: Option<String>,
+    command_line: Option<String>,
+    parent_id: u32,
+    /// FILETIME ticks
+    created: Option<u64>,
+}
+
+thread_local! {
+    // Connection of this thread (None = not connected yet, Some(None) = WMI not available)
+    static SERVICES: RefCell<Option<Option<IWbemServices>>> = const { RefCell::new(None) };
+    // Recent lookups of this thread (None = no such process)
+    static CACHE: RefCell<HashMap<u32, (Option<WmiProcess>, Instant)>> = RefCell::new(HashMap::new());
+}
+
+/// Connection to root\cimv2
+unsafe fn connect() -> Result<IWbemServices, String> {
+    // Fails harmlessly on threads that already use COM (e.g. the GUI thread)
+    let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+    let locator: IWbemLocator =
+        CoCreateInstance(&WbemLocator, None, CLSCTX_INPROC_SERVER).map_err(|e| format!("WbemLocator: {}", e))?;
+    let services = locator
+        .ConnectServer(&BSTR::from(r"ROOT\CIMV2"), &BSTR::new(), &BSTR::new(), &BSTR::new(), 0, &BSTR::new(), None)
+        .map_err(|e| format!("ConnectServer: {}", e))?;
+    CoSetProxyBlanket(
+        &services,
+        RPC_C_AUTHN_WINNT,
+        RPC_C_AUTHZ_NONE,
+        PCWSTR::null(),
+        RPC_C_AUTHN_LEVEL_CALL,
+        RPC_C_IMP_LEVEL_IMPERSONATE,
+        None,
+        EOAC_NONE,
+    )
+    .map_err(|e| format!("CoSetProxyBlanket: {}", e))?;
+    Ok(services)
+}
+
+/// Runs a WQL query and returns all result objects
+fn query(wql: &str) -> Vec<IWbemClassObject> {
+    SERVICES.with(|services| {
+        let mut services = services.borrow_mut();
+        let services = services.get_or_insert_with(|| match unsafe { connect() } {
+            Ok(services) => Some(services),
+            Err(e) => {
+                warn!("WMI process queries not available: {}", e);
+                None
+            }
+        });
+        let Some(services) = services.as_ref() else {
+            return Vec::new();
+        };
+
+        let mut objects = Vec::new();
+        unsafe {
+            let flags = WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_RETURN_IMMEDIATELY;
+            let enumerator = match services.ExecQuery(&BSTR::from("WQL"), &BSTR::from(wql), flags, None) {
+                Ok(enumerator) => enumerator,
+                Err(e) => {
+                    debug!("WMI query \"{}\" failed: {}", wql, e);
+                    return objects;
+                }
+            };
+            loop {
+                let mut next = [None];
+                let mut returned = 0;
+                if enumerator.Next(WBEM_INFINITE, &mut next, &mut returned).is_err() || returned == 0 {
+                    break;
+                }
+                match next[0].take() {
+                    Some(object) => objects.push(object),
+                    None => break,
+                }
+            }
+        }
+        objects
+    })
+}
+
+/// Property of a WMI object (None if missing/null)
+fn property(object: &IWbemClassObject, name: &str) -> Option<VARIANT> {
+    let name: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+    let mut value = VARIANT::default();
+    unsafe { object.Get(PCWSTR(name.as_ptr()), 0, &mut value, None, None) }.ok()?;
+    (!value.is_empty()).then_some(value)
+}
+
+fn string_property(object: &IWbemClassObject, name: &str) -> Option<String> {
+    let value = property(object, name)?;
+    BSTR::try_from(&value).ok().map(|s| s.to_string()).filter(|s| !s.is_empty())
+}
+
+/// CIM datetime ("20240105133012.123456+060", offset in minutes) as FILETIME ticks
+fn filetime_ticks(cim: &str) -> Option<u64> {
+    let (local, offset) = (cim.get(..21)?, cim.get(21..)?);
+    let time = NaiveDateTime::parse_from_str(local, "%Y%m%d%H%M%S%.6f").ok()?;
+    let offset = FixedOffset::east_opt(offset.parse::<i32>().ok()? * 60)?;
+    let time = offset.from_local_datetime(&time).single()?;
+    let seconds = time.timestamp() + FILETIME_UNIX_OFFSET;
+    u64::try_from(seconds).ok().map(|s| s * 10_000_000 + time.timestamp_subsec_micros() as u64 * 10)
+}
+
+/// Looks up one process (cached for a few seconds)
+fn process(process_id: u32) -> Option<WmiProcess> {
+    let cached = CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        cache.retain(|_, (_, queried)| queried.elapsed() < CACHE_TTL);
+        cache.get(&process_id).map(|(process, _)| process.clone())
+    });
+    if let Some(process) = cached {
+        return process;
+    }
+    let process = query_process(process_id);
+    CACHE.with(|cache| cache.borrow_mut().insert(process_id, (process.clone(), Instant::now())));
+    process
+}
+
+fn query_process(process_id: u32) -> Option<WmiProcess> {
+    let wql = format!(
+        "SELECT Name, ExecutablePath, CommandLine, ParentProcessId, CreationDate FROM Win32_Process WHERE ProcessId = {}",
+        process_id
+    );
+    let object = query(&wql).into_iter().next()?;
+    let name = string_property(&object, "Name").unwrap_or_default();
+    Some(WmiProcess {
+        name: name.strip_suffix(".exe").unwrap_or(&name).to_string(),
+        path: string_property(&object, "ExecutablePath").map(|path| crate::long_path::normalize(&path)),
+        command_line: string_property(&object, "CommandLine"),
+        parent_id: property(&object, "ParentProcessId").and_then(|v| u32::try_from(&v).ok()).unwrap_or(0),
+        created: string_property(&object, "CreationDate").and_then(|d| filetime_ticks(&d)),
+    })
+}
+
+/// Process data from WMI (windows are still read directly, WMI does not know them)
+pub struct WmiProvider;
+
+impl ProcessInfoProvider for WmiProvider {
+    fn window_process_id(&self, hwnd: HWND) -> u32 {
+        Win32Provider.window_process_id(hwnd)
+    }
+
+    fn window_title(&self, hwnd: HWND) -> String {
+        Win32Provider.window_title(hwnd)
+    }
+
+    fn window_class(&self, hwnd: HWND) -> String {
+        Win32Provider.window_class(hwnd)
+    }
+
+    fn process_path(&self, process_id: u32) -> Option<String> {
+        process(process_id)?.path
+    }
+
+    fn command_line(&self, process_id: u32) -> Option<String> {
+        process(process_id)?.command_line
+    }
+
+    fn parent_id(&self, process_id: u32) -> Option<u32> {
+        process(process_id).map(|p| p.parent_id)
+    }
+
+    fn snapshot_name(&self, process_id: u32) -> Option<String> {
+        process(process_id).map(|p| p.name).filter(|n| !n.is_empty())
+    }
+
+    fn creation_time(&self, process_id: u32) -> Result<Option<u64>, ()> {
+        process(process_id).map(|p| p.created).ok_or(())
+    }
+
+    fn running_process_names(&self) -> HashSet<String> {
+        query("SELECT Name FROM Win32_Process")
+            .iter()
+            .filter_map(|object| string_property(object, "Name"))
+            .map(|name| {
+                let name = name.to_lowercase();
+                name.strip_suffix(".exe").unwrap_or(&name).to_string()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cim_datetimes_become_filetime_ticks() {
+        assert_eq!(filetime_ticks("16010101000000.000000+000"), Some(0));
+        assert_eq!(filetime_ticks("19700101000000.000000+000"), Some(116_444_736_000_000_000));
+        // Offset in minutes: 13:30 at UTC+1 is 12:30 UTC, microseconds are kept
+        assert_eq!(filetime_ticks("20240105133012.123456+060"), Some(133_489_314_121_234_560));
+        assert_eq!(filetime_ticks("20240105073012.123456-300"), Some(133_489_314_121_234_560));
+    }
+
+    #[test]
+    fn invalid_cim_datetimes_are_rejected() {
+        assert_eq!(filetime_ticks(""), None);
+        assert_eq!(filetime_ticks("20240105133012.123456"), None);
+        assert_eq!(filetime_ticks("20241305133012.123456+000"), None);
+        assert_eq!(filetime_ticks("2024010513301x.123456+000"), None);
+        // Before 1601 there are no FILETIME ticks
+        assert_eq!(filetime_ticks("16001231235959.000000+000"), None);
+    }
+}
@@ -0,0 +1,180 @@
+//! YARA Scanning
+//!
+//! Optionally scans the executable behind a Critical alert with the YARA rules (.yar/.yara) of
+//! a folder. Matches are logged as YARA event and shown in the details window. Needs a build
+//! with the `yara` feature (`cargo build --release --features yara`), which compiles libyara.
+
+use crate::logger::LogEntry;
+use crate::notification::Severity;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::thread;
+use tracing::{info, warn};
+
+/// Verdict for one executable
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(not(feature = "yara"), allow(dead_code))]
+pub enum Verdict {
+    Pending,
+    Clean,
+    /// Matching rules as "file:rule"
+    Matches(Vec<String>),
+    /// Scan not possible (no rules, build without YARA, file not readable, ...)
+    Unavailable(String),
+}
+
+impl Verdict {
+    pub fn describe(&self) -> String {
+        match self {
+            Verdict::Pending => "scanning...".to_string(),
+            Verdict::Clean => "no rule matched".to_string(),
+            Verdict::Matches(rules) => format!("MATCH {}", rules.join(", ")),
+            Verdict::Unavailable(reason) => format!("not available ({})", reason),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    // Verdict per executable (key = lowercase path), every file is scanned once per run
+    static ref VERDICTS: Mutex<HashMap<String, Verdict>> = Mutex::new(HashMap::new());
+}
+
+/// Rule folder (relative paths are relative to the EXE folder)
+fn rules_dir() -> PathBuf {
+    let dir = PathBuf::from(crate::config::get().yara.rules_dir);
+    if dir.is_absolute() {
+        return dir;
+    }
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|p| p.join(&dir)))
+        .unwrap_or(dir)
+}
+
+/// Rule files of the folder (.yar/.yara, not recursive)
+fn rule_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+        .unwrap_or_default();
+    files.retain(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("yar") || e.eq_ignore_ascii_case("yara")));
+    files.sort();
+    files
+}
+
+#[cfg(feature = "yara")]
+mod engine {
+    use super::Verdict;
+    use once_cell::sync::OnceCell;
+    use std::path::{Path, PathBuf};
+    use tracing::{info, warn};
+    use yara::{Compiler, Rules};
+
+    /// Compiled rules per file (compiled on the first scan, a broken file only drops itself)
+    static RULES: OnceCell<Vec<(String, Rules)>> = OnceCell::new();
+
+    fn compile(files: &[PathBuf]) -> Vec<(String, Rules)> {
+        let mut compiled = Vec::new();
+        for file in files {
+            let name = file.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let rules = Compiler::new()
+                .map_err(|e| e.to_string())
+                .and_then(|c| c.add_rules_file(file).map_err(|e| e.to_string()))
+                .and_then(|c| c.compile_rules().map_err(|e| e.to_string()));
+            match rules {
+                Ok(rules) => compiled.push((name, rules)),
+                Err(e) => warn!("YARA rules {} skipped: {}", file.display(), e),
+            }
+        }
+        info!("YARA: {} rule file(s) loaded", compiled.len());
+        compiled
+    }
+
+    pub fn scan(path: &Path, files: &[PathBuf], timeout_secs: u64) -> Verdict {
+        let rules = RULES.get_or_init(|| compile(files));
+        if rules.is_empty() {
+            return Verdict::Unavailable("no valid rules".to_string());
+        }
+        let timeout = timeout_secs.min(i32::MAX as u64) as i32;
+        let mut matches = Vec::new();
+        for (name, rules) in rules {
+            match rules.scan_file(path, timeout) {
+                Ok(found) => matches.extend(found.iter().map(|rule| format!("{}:{}", name, rule.identifier))),
+                Err(e) => return Verdict::Unavailable(e.to_string()),
+            }
+        }
+        if matches.is_empty() {
+            Verdict::Clean
+        } else {
+            Verdict::Matches(matches)
+        }
+    }
+}
+
+#[cfg(not(feature = "yara"))]
+mod engine {
+    use super::Verdict;
+    use std::path::{Path, PathBuf};
+
+    pub fn scan(_path: &Path, _files: &[PathBuf], _timeout_secs: u64) -> Verdict {
+        Verdict::Unavailable("built without the yara feature".to_string())
+    }
+}
+
+/// Cached verdict for an executable (None = never scanned)
+pub fn verdict(process_path: &str) -> Option<Verdict> {
+    VERDICTS.lock().get(&process_path.to_lowercase()).cloned()
+}
+
+/// Starts a background scan of the executable of a Critical alert (once per file)
+pub fn lookup(process_name: &str, process_path: &str) {
+    let config = crate::config::get().yara;
+    if !config.enabled || !Path::new(process_path).is_absolute() {
+        return;
+    }
+
+    {
+        let mut verdicts = VERDICTS.lock();
+        let key = process_path.to_lowercase();
+        if verdicts.contains_key(&key) {
+            return;
+        }
+        verdicts.insert(key, Verdict::Pending);
+    }
+
+    let process_name = process_name.to_string();
+    let process_path = process_path.to_string();
+    thread::spawn(move || {
+        let dir = rules_dir();
+        let files = rule_files(&dir);
+        let verdict = if files.is_empty() {
+            Verdict::Unavailable(format!("no rules in {}", dir.display()))
+        } else {
+            engine::scan(Path::new(&process_path), &files, config.timeout_secs)
+        };
+        VERDICTS.lock().insert(process_path.to_lowercase(), verdict.clone());
+        report(process_name, process_path, &verdict);
+    });
+}
+
+/// Logs the verdict and refreshes open details windows
+fn report(process_name: String, process_path: String, verdict: &Verdict) {
+    let severity = if matches!(verdict, Verdict::Matches(_)) {
+        warn!("!!! YARA verdict for {}: {} !!!", process_path, verdict.describe());
+        Severity::Warning
+    } else {
+        info!("YARA verdict for {}: {}", process_path, verdict.describe());
+        Severity::Info
+    };
+
+    crate::event_hook::send_log_entry(LogEntry {
+        timestamp: chrono::Local::now(),
+        event_type: "YARA".to_string(),
+        severity,
+        process_name,
+        process_path,
+        window_title: format!("YARA: {}", verdict.describe()),
+        ..Default::default()
+    });
+    crate::alert_window::redraw_details_window();
+}